@@ -36,10 +36,12 @@ namespace Devolutions.NowClient
 
         private static async Task<NowClient> ConnectImpl(NowChannelTransport channel)
         {
-            // Support all capabilities by default on client side.
+            // Support all implemented capabilities by default on client side; system messages
+            // introduced in NOW-PROTO 1.7 (info, shutdown abort, power action and process
+            // management) are not implemented yet.
             var clientCapabilities = new NowMsgChannelCapset.Builder()
                 .HeartbeatInterval(TimeSpan.FromSeconds(60))
-                .SystemCapset(NowCapabilitySystem.All)
+                .SystemCapset(NowCapabilitySystem.Shutdown)
                 .SessionCapset(NowCapabilitySession.All)
                 .ExecCapset(NowCapabilityExec.All)
                 .Build();
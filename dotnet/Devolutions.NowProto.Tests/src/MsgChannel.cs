@@ -16,7 +16,7 @@ namespace Devolutions.NowProto.Tests
 
             var encoded = new byte[]
             {
-                0x0E, 0x00, 0x00, 0x00, 0x10, 0x01, 0x01, 0x00, 0x01, 0x00, 0x06, 0x00, 0x01, 0x00, 0x04, 0x00, 0x05, 0x00, 0x2C, 0x01, 0x00, 0x00
+                0x0E, 0x00, 0x00, 0x00, 0x10, 0x01, 0x01, 0x00, 0x01, 0x00, 0x07, 0x00, 0x01, 0x00, 0x04, 0x00, 0x05, 0x00, 0x2C, 0x01, 0x00, 0x00
             };
 
             var decoded = NowTest.MessageRoundtrip(msg, encoded);
@@ -37,7 +37,7 @@ namespace Devolutions.NowProto.Tests
 
             var encoded = new byte[]
             {
-                0x0E, 0x00, 0x00, 0x00, 0x10, 0x01, 0x00, 0x00, 0x01, 0x00, 0x06, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00
+                0x0E, 0x00, 0x00, 0x00, 0x10, 0x01, 0x00, 0x00, 0x01, 0x00, 0x07, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00
             };
 
             var decoded = NowTest.MessageRoundtrip(msg, encoded);
@@ -50,6 +50,42 @@ namespace Devolutions.NowProto.Tests
             Assert.Null(decoded.HeartbeatInterval);
         }
 
+        [Fact]
+        public void CapsetFileCapset()
+        {
+            var msg = new NowMsgChannelCapset.Builder()
+                .SystemCapset(NowCapabilitySystem.Info | NowCapabilitySystem.Process)
+                .FileCapset(NowCapabilityFile.Read | NowCapabilityFile.Browse)
+                .Build();
+
+            var encoded = new byte[]
+            {
+                0x10, 0x00, 0x00, 0x00, 0x10, 0x01, 0x02, 0x00, 0x01, 0x00, 0x07, 0x00, 0x12, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x00
+            };
+
+            var decoded = NowTest.MessageRoundtrip(msg, encoded);
+
+            Assert.Equal(NowCapabilitySystem.Info | NowCapabilitySystem.Process, decoded.SystemCapset);
+            Assert.Equal(NowCapabilityFile.Read | NowCapabilityFile.Browse, decoded.FileCapset);
+            Assert.Null(decoded.HeartbeatInterval);
+        }
+
+        [Fact]
+        public void CapsetWithoutFileCapset()
+        {
+            // Message from a 1.6 peer, which does not send the fileCapset field.
+            var encoded = new byte[]
+            {
+                0x0E, 0x00, 0x00, 0x00, 0x10, 0x01, 0x00, 0x00, 0x01, 0x00, 0x06, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00
+            };
+
+            var decoded = NowMessage.Read(new NowReadCursor(encoded)).Deserialize<NowMsgChannelCapset>();
+
+            Assert.Equal(new NowProtoVersion(1, 6), decoded.Version);
+            Assert.Equal(NowCapabilitySystem.Shutdown, decoded.SystemCapset);
+            Assert.Equal(NowCapabilityFile.None, decoded.FileCapset);
+        }
+
         [Fact]
         public void CapsetTooSmallHeartbeatInterval()
         {
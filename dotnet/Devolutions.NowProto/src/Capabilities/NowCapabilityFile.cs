@@ -0,0 +1,40 @@
+﻿namespace Devolutions.NowProto.Capabilities
+{
+    /// <summary>
+    /// NOW-PROTO: NOW_CHANNEL_CAPSET_MSG fileCapset field.
+    /// </summary>
+    [Flags]
+    public enum NowCapabilityFile : ushort
+    {
+        /// <summary>
+        /// Empty capability set.
+        /// </summary>
+        None = 0x0000,
+        /// <summary>
+        /// File download (read transfer) support.
+        ///
+        /// NOW-PROTO: NOW_CAP_FILE_READ
+        /// </summary>
+        Read = 0x0001,
+        /// <summary>
+        /// File upload (write transfer) support.
+        ///
+        /// NOW-PROTO: NOW_CAP_FILE_WRITE
+        /// </summary>
+        Write = 0x0002,
+        /// <summary>
+        /// Filesystem browsing support (stat and directory listing).
+        ///
+        /// NOW-PROTO: NOW_CAP_FILE_BROWSE
+        /// </summary>
+        Browse = 0x0004,
+        /// <summary>
+        /// Filesystem management support (directory creation, deletion and renaming).
+        ///
+        /// NOW-PROTO: NOW_CAP_FILE_MANAGE
+        /// </summary>
+        Manage = 0x0008,
+
+        All = Read | Write | Browse | Manage,
+    }
+}
\ No newline at end of file
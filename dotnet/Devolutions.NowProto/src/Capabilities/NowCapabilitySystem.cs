@@ -16,7 +16,31 @@
         /// NOW-PROTO: NOW_CAP_SYSTEM_SHUTDOWN
         /// </summary>
         Shutdown = 0x0001,
+        /// <summary>
+        /// System information query support.
+        ///
+        /// NOW-PROTO: NOW_CAP_SYSTEM_INFO
+        /// </summary>
+        Info = 0x0002,
+        /// <summary>
+        /// Pending system shutdown abort support.
+        ///
+        /// NOW-PROTO: NOW_CAP_SYSTEM_SHUTDOWN_ABORT
+        /// </summary>
+        ShutdownAbort = 0x0004,
+        /// <summary>
+        /// System power actions support (shutdown, reboot, sleep, hibernate and session restart).
+        ///
+        /// NOW-PROTO: NOW_CAP_SYSTEM_POWER_ACTION
+        /// </summary>
+        PowerAction = 0x0008,
+        /// <summary>
+        /// Process list and termination support.
+        ///
+        /// NOW-PROTO: NOW_CAP_SYSTEM_PROCESS
+        /// </summary>
+        Process = 0x0010,
 
-        All = Shutdown,
+        All = Shutdown | Info | ShutdownAbort | PowerAction | Process,
     }
 }
\ No newline at end of file
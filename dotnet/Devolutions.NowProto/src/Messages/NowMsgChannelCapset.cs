@@ -42,20 +42,48 @@ namespace Devolutions.NowProto.Messages
             var execСapset = (NowCapabilityExec)cursor.ReadUInt16Le();
             var heartbeatInterval = cursor.ReadUInt32Le();
 
+            // Optional field, only sent by peers supporting file transfers.
+            var fileCapset = NowCapabilityFile.None;
+            if (msgFlags.HasFlag(MsgFlags.FlagChannelSetFileCapset))
+            {
+                cursor.EnsureEnoughBytes(FileCapsetSize);
+                fileCapset = (NowCapabilityFile)cursor.ReadUInt16Le();
+            }
+
             return new NowMsgChannelCapset
             {
                 Version = version,
                 SystemCapset = systemСapset,
                 SessionCapset = sessionСapset,
                 ExecCapset = execСapset,
+                FileCapset = fileCapset,
                 _heartbeatInterval = msgFlags.HasFlag(MsgFlags.FlagChannelSetHeartbeat) ? heartbeatInterval : null,
             };
         }
 
         // -- INowSerialize --
 
-        ushort INowSerialize.Flags => (HeartbeatInterval != null) ? (ushort)MsgFlags.FlagChannelSetHeartbeat : (ushort)0;
-        uint INowSerialize.BodySize => FixedPartSize;
+        ushort INowSerialize.Flags
+        {
+            get
+            {
+                var flags = MsgFlags.None;
+
+                if (HeartbeatInterval != null)
+                {
+                    flags |= MsgFlags.FlagChannelSetHeartbeat;
+                }
+
+                if (FileCapset != NowCapabilityFile.None)
+                {
+                    flags |= MsgFlags.FlagChannelSetFileCapset;
+                }
+
+                return (ushort)flags;
+            }
+        }
+
+        uint INowSerialize.BodySize => FixedPartSize + ((FileCapset != NowCapabilityFile.None) ? FileCapsetSize : 0);
 
         void INowSerialize.SerializeBody(NowWriteCursor cursor)
         {
@@ -66,21 +94,38 @@ namespace Devolutions.NowProto.Messages
             cursor.WriteUint16Le((ushort)SessionCapset);
             cursor.WriteUint16Le((ushort)ExecCapset);
             cursor.WriteUint32Le(_heartbeatInterval ?? (uint)0);
+
+            // fileCapset is omitted to stay compatible with peers which do not support file transfers.
+            if (FileCapset != NowCapabilityFile.None)
+            {
+                cursor.EnsureEnoughBytes(FileCapsetSize);
+                cursor.WriteUint16Le((ushort)FileCapset);
+            }
         }
 
         // -- impl ---
 
         private const uint FixedPartSize = 14;
+        private const uint FileCapsetSize = 2;
 
         [Flags]
         private enum MsgFlags : ushort
         {
+            None = 0x0000,
+
             /// <summary>
             /// Set if heartbeat specify channel heartbeat interval.
             ///
             /// NOW-PROTO: NOW_CHANNEL_SET_HEARTBEAT
             /// </summary>
             FlagChannelSetHeartbeat = 0x0001,
+
+            /// <summary>
+            /// Set if the message contains the fileCapset field.
+            ///
+            /// NOW-PROTO: NOW_CHANNEL_SET_FILE_CAPSET
+            /// </summary>
+            FlagChannelSetFileCapset = 0x0002,
         }
 
         public class Builder
@@ -121,6 +166,12 @@ namespace Devolutions.NowProto.Messages
                 return this;
             }
 
+            public Builder FileCapset(NowCapabilityFile capset)
+            {
+                _fileCapset = capset;
+                return this;
+            }
+
             public NowMsgChannelCapset Build()
             {
                 return new NowMsgChannelCapset
@@ -129,6 +180,7 @@ namespace Devolutions.NowProto.Messages
                     SystemCapset = _systemСapset,
                     SessionCapset = _sessionСapset,
                     ExecCapset = _execСapset,
+                    FileCapset = _fileCapset,
                     _heartbeatInterval = _heartbeatInterval,
                 };
             }
@@ -136,6 +188,7 @@ namespace Devolutions.NowProto.Messages
             private NowCapabilitySystem _systemСapset = NowCapabilitySystem.None;
             private NowCapabilitySession _sessionСapset = NowCapabilitySession.None;
             private NowCapabilityExec _execСapset = NowCapabilityExec.None;
+            private NowCapabilityFile _fileCapset = NowCapabilityFile.None;
             private uint? _heartbeatInterval;
         }
 
@@ -158,6 +211,11 @@ namespace Devolutions.NowProto.Messages
         /// </summary>
         public NowCapabilityExec ExecCapset { get; private init; } = NowCapabilityExec.None;
 
+        /// <summary>
+        /// Available file transfer capabilities.
+        /// </summary>
+        public NowCapabilityFile FileCapset { get; private init; } = NowCapabilityFile.None;
+
         /// <summary>
         /// Expected heartbeat interval or null if not set.
         /// </summary>
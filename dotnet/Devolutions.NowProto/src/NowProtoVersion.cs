@@ -2,7 +2,7 @@
 {
     public readonly record struct NowProtoVersion(ushort Major, ushort Minor) : IComparable<NowProtoVersion>
     {
-        public static NowProtoVersion Current => new(1, 6);
+        public static NowProtoVersion Current => new(1, 7);
 
         // -- IComparable<NowProtoVersion> --
         public int CompareTo(NowProtoVersion other)
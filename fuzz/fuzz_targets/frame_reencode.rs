@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use now_proto_fuzzing::generators::NowFrameInput;
+use now_proto_fuzzing::oracles;
+
+fuzz_target!(|input: NowFrameInput| {
+    oracles::frame_reencode(&input);
+});
@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use now_proto_fuzzing::oracles;
+
+fuzz_target!(|data: &[u8]| {
+    oracles::message_decode(data);
+});
@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use now_proto_fuzzing::generators::NowMessageInput;
+use now_proto_fuzzing::oracles;
+
+fuzz_target!(|input: NowMessageInput| {
+    oracles::message_size(&input);
+});
@@ -0,0 +1,294 @@
+use std::io;
+use std::os::unix::process::ExitStatusExt as _;
+use std::process::{ExitStatus, Stdio};
+
+use now_proto_pdu::{NowExecProcessMsg, NowExecRunMsg, NowExecShellMsg, NowProtoError, NowStatusError};
+use now_proto_server::{
+    now_status_from_io_error, NowExecContext, NowExecSessionEvent, NowHandlerResult, NowServerError,
+};
+use tokio::io::{AsyncRead, AsyncReadExt as _, AsyncWriteExt as _};
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::mpsc;
+
+const DEFAULT_SHELL: &str = "/bin/sh";
+
+const OUTPUT_BUFFER_SIZE: usize = 8 * 1024;
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ExecOptions {
+    pub(crate) io_redirection: bool,
+    pub(crate) detached: bool,
+}
+
+pub(crate) fn run_command(request: &NowExecRunMsg<'_>) -> Command {
+    let mut command = Command::new(DEFAULT_SHELL);
+    command.arg("-c").arg(request.command());
+
+    if let Some(directory) = request.directory() {
+        command.current_dir(directory);
+    }
+
+    command
+}
+
+pub(crate) fn process_command(request: &NowExecProcessMsg<'_>) -> Command {
+    let mut command = Command::new(request.filename());
+
+    if let Some(parameters) = request.parameters() {
+        command.args(split_parameters(parameters));
+    }
+
+    if let Some(directory) = request.directory() {
+        command.current_dir(directory);
+    }
+
+    command
+}
+
+pub(crate) fn shell_command(request: &NowExecShellMsg<'_>) -> Command {
+    let mut command = Command::new(request.shell().unwrap_or(DEFAULT_SHELL));
+    command.arg("-c").arg(request.command());
+
+    if let Some(directory) = request.directory() {
+        command.current_dir(directory);
+    }
+
+    command
+}
+
+/// Starts the process without tracking its execution.
+pub(crate) fn spawn_detached(mut command: Command) -> NowHandlerResult<()> {
+    let child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|error| now_status_from_io_error(&error))?;
+
+    // Exited process is reaped by tokio in the background.
+    tracing::debug!(pid = child.id(), "Detached process started");
+
+    Ok(())
+}
+
+/// Runs the exec session until the process exits and returns its exit code.
+pub(crate) async fn run_session(
+    mut command: Command,
+    options: ExecOptions,
+    mut session: NowExecContext,
+) -> NowHandlerResult<u32> {
+    if options.detached {
+        spawn_detached(command)?;
+        return Ok(0);
+    }
+
+    let stdio = || {
+        if options.io_redirection {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        }
+    };
+
+    // Process is killed if the session task is aborted (e.g. the channel is closed). Session runs
+    // in its own process group, so that cancellation also reaches the processes started by it.
+    let mut child = command
+        .stdin(stdio())
+        .stdout(stdio())
+        .stderr(stdio())
+        .process_group(0)
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|error| now_status_from_io_error(&error))?;
+
+    session.started().await.map_err(channel_error_status)?;
+
+    let mut stdin_writer = child.stdin.take().map(spawn_stdin_writer);
+    let mut stdout = child.stdout.take();
+    let mut stderr = child.stderr.take();
+
+    let mut stdout_buffer = vec![0u8; OUTPUT_BUFFER_SIZE];
+    let mut stderr_buffer = vec![0u8; OUTPUT_BUFFER_SIZE];
+
+    loop {
+        tokio::select! {
+            read = read_output(&mut stdout, &mut stdout_buffer), if stdout.is_some() => {
+                let read = read.unwrap_or_else(|error| {
+                    tracing::debug!(%error, "Failed to read process stdout");
+                    0
+                });
+
+                session
+                    .send_stdout(&stdout_buffer[..read], read == 0)
+                    .await
+                    .map_err(channel_error_status)?;
+
+                if read == 0 {
+                    stdout = None;
+                }
+            }
+            read = read_output(&mut stderr, &mut stderr_buffer), if stderr.is_some() => {
+                let read = read.unwrap_or_else(|error| {
+                    tracing::debug!(%error, "Failed to read process stderr");
+                    0
+                });
+
+                session
+                    .send_stderr(&stderr_buffer[..read], read == 0)
+                    .await
+                    .map_err(channel_error_status)?;
+
+                if read == 0 {
+                    stderr = None;
+                }
+            }
+            event = session.next_event() => match event {
+                Some(NowExecSessionEvent::Stdin { data, last }) => {
+                    if let Some(writer) = &stdin_writer {
+                        // Writer could have stopped if the process has closed its stdin.
+                        let _ = writer.send(data);
+                    }
+
+                    if last {
+                        // Process stdin is closed once the pending data is written.
+                        stdin_writer = None;
+                    }
+                }
+                Some(NowExecSessionEvent::Cancel) => signal_process_group(&child, libc::SIGTERM),
+                Some(NowExecSessionEvent::Abort { exit_code }) => {
+                    signal_process_group(&child, libc::SIGKILL);
+
+                    // Result is not sent to the client for aborted sessions.
+                    return Ok(exit_code);
+                }
+                None => return Err(NowStatusError::new_proto(NowProtoError::Aborted)),
+            },
+            // Output is fully forwarded before the result is reported.
+            status = child.wait(), if stdout.is_none() && stderr.is_none() => {
+                return status.map(exit_code).map_err(|error| now_status_from_io_error(&error));
+            }
+        }
+    }
+}
+
+/// Reads process output. Pending forever if the stream is already closed.
+async fn read_output<R: AsyncRead + Unpin>(stream: &mut Option<R>, buffer: &mut [u8]) -> io::Result<usize> {
+    match stream {
+        Some(stream) => stream.read(buffer).await,
+        None => core::future::pending().await,
+    }
+}
+
+/// Spawns a task writing client data to the process stdin, so that a process which is not reading
+/// its stdin does not block the output forwarding. Stdin is closed when the sender is dropped.
+fn spawn_stdin_writer(mut stdin: ChildStdin) -> mpsc::UnboundedSender<Vec<u8>> {
+    let (sender, mut receiver) = mpsc::unbounded_channel::<Vec<u8>>();
+
+    tokio::spawn(async move {
+        while let Some(data) = receiver.recv().await {
+            if let Err(error) = stdin.write_all(&data).await {
+                tracing::debug!(%error, "Failed to write process stdin");
+                break;
+            }
+        }
+    });
+
+    sender
+}
+
+/// Sends signal to the process group of the session.
+fn signal_process_group(child: &Child, signal: libc::c_int) {
+    // Process has already exited.
+    let Some(pid) = child.id() else {
+        return;
+    };
+
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return;
+    };
+
+    // SAFETY: `kill` has no memory safety preconditions. The child is not reaped yet (`id` returns
+    // `None` after that), therefore its process group ID could not be reused by another process.
+    let result = unsafe { libc::kill(-pid, signal) };
+
+    if result != 0 {
+        tracing::debug!(error = %io::Error::last_os_error(), pid, signal, "Failed to signal process group");
+    }
+}
+
+/// Converts process exit status to the exit code, processes terminated by a signal report
+/// `128 + signal` like shells do.
+fn exit_code(status: ExitStatus) -> u32 {
+    let code = status
+        .code()
+        .or_else(|| status.signal().map(|signal| 128 + signal))
+        .unwrap_or(-1);
+
+    u32::from_ne_bytes(code.to_ne_bytes())
+}
+
+pub(crate) fn channel_error_status(error: NowServerError) -> NowStatusError {
+    tracing::debug!(%error, "Failed to send exec session message");
+
+    NowStatusError::new_proto(NowProtoError::Aborted)
+}
+
+/// Splits process parameters into arguments, following shell quoting rules: whitespace separates
+/// arguments, single quotes preserve the literal value, double quotes and backslash preserve
+/// whitespace.
+fn split_parameters(parameters: &str) -> Vec<String> {
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    enum Quote {
+        None,
+        Single,
+        Double,
+    }
+
+    let mut arguments = Vec::new();
+    let mut current = String::new();
+    let mut in_argument = false;
+    let mut quote = Quote::None;
+    let mut chars = parameters.chars();
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Quote::None, c) if c.is_whitespace() => {
+                if in_argument {
+                    arguments.push(core::mem::take(&mut current));
+                    in_argument = false;
+                }
+            }
+            (Quote::None, '\'') => {
+                quote = Quote::Single;
+                in_argument = true;
+            }
+            (Quote::None, '"') => {
+                quote = Quote::Double;
+                in_argument = true;
+            }
+            (Quote::Single, '\'') | (Quote::Double, '"') => quote = Quote::None,
+            (Quote::None, '\\') => {
+                current.extend(chars.next());
+                in_argument = true;
+            }
+            // Inside double quotes backslash only escapes the special characters.
+            (Quote::Double, '\\') => match chars.clone().next() {
+                Some(next @ ('"' | '\\' | '$' | '`')) => {
+                    current.push(next);
+                    chars.next();
+                }
+                _ => current.push('\\'),
+            },
+            (_, c) => {
+                current.push(c);
+                in_argument = true;
+            }
+        }
+    }
+
+    if in_argument {
+        arguments.push(current);
+    }
+
+    arguments
+}
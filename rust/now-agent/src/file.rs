@@ -0,0 +1,310 @@
+use std::fs::Metadata;
+use std::io::{self, SeekFrom};
+use std::os::unix::fs::PermissionsExt as _;
+use std::path::Path;
+use std::time::SystemTime;
+
+use now_proto_pdu::{
+    NowFileAttributes, NowFileDirEntryMsg, NowFileEntryKind, NowFileStatRspMsg, NowProtoError, NowStatusError,
+    OwnedNowFileDeleteMsg, OwnedNowFileListDirReqMsg, OwnedNowFileMkdirMsg, OwnedNowFileOpenMsg, OwnedNowFileRenameMsg,
+    OwnedNowFileStatReqMsg, OwnedNowFileStatRspMsg,
+};
+use now_proto_server::{now_status_from_io_error, NowFileDirListing, NowFileTransferContext, NowHandlerResult};
+use tokio::fs::{self, File, OpenOptions};
+use tokio::io::{AsyncReadExt as _, AsyncSeekExt as _, AsyncWriteExt as _};
+
+use crate::exec::channel_error_status;
+
+/// Size of `NOW_FILE_CHUNK_MSG` data sent by the agent.
+const FILE_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Sends the file content from the requested offset up to the end of the file.
+pub(crate) async fn file_read(
+    request: &OwnedNowFileOpenMsg,
+    mut transfer: NowFileTransferContext,
+) -> NowHandlerResult<()> {
+    let mut file = File::open(request_path(request.path())?)
+        .await
+        .map_err(fs_error_status)?;
+    let file_size = file
+        .metadata()
+        .await
+        .map_err(|error| now_status_from_io_error(&error))?
+        .len();
+
+    let offset = request.offset();
+    if offset > file_size {
+        return Err(invalid_offset(offset, file_size));
+    }
+
+    file.seek(SeekFrom::Start(offset))
+        .await
+        .map_err(|error| now_status_from_io_error(&error))?;
+
+    transfer.accept(file_size).await.map_err(channel_error_status)?;
+
+    let mut remaining = file_size - offset;
+    let mut buffer = vec![0; FILE_CHUNK_SIZE];
+
+    while !transfer.is_closed() {
+        let read = file
+            .read(&mut buffer)
+            .await
+            .map_err(|error| now_status_from_io_error(&error))?;
+        let read_len = u64::try_from(read).expect("usize fits into u64");
+
+        // The file could be truncated while being read.
+        remaining = remaining.saturating_sub(read_len);
+        let last = read == 0 || remaining == 0;
+
+        transfer
+            .send_chunk(&buffer[..read], last)
+            .await
+            .map_err(channel_error_status)?;
+
+        if last {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes the received file content at the requested offset (or at the end of the file for
+/// resumed transfers). The file is created if it does not exist.
+pub(crate) async fn file_write(
+    request: &OwnedNowFileOpenMsg,
+    mut transfer: NowFileTransferContext,
+) -> NowHandlerResult<()> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(request_path(request.path())?)
+        .await
+        .map_err(fs_error_status)?;
+    let file_size = file
+        .metadata()
+        .await
+        .map_err(|error| now_status_from_io_error(&error))?
+        .len();
+
+    let offset = if request.is_resume() {
+        file_size
+    } else {
+        let offset = request.offset();
+        if offset > file_size {
+            return Err(invalid_offset(offset, file_size));
+        }
+
+        file.set_len(offset)
+            .await
+            .map_err(|error| now_status_from_io_error(&error))?;
+        offset
+    };
+
+    file.seek(SeekFrom::Start(offset))
+        .await
+        .map_err(|error| now_status_from_io_error(&error))?;
+
+    transfer.accept(offset).await.map_err(channel_error_status)?;
+
+    while let Some(data) = transfer.next_chunk().await {
+        file.write_all(&data)
+            .await
+            .map_err(|error| now_status_from_io_error(&error))?;
+    }
+
+    // Transfer is reported as complete only once the data is on disk.
+    file.sync_all().await.map_err(|error| now_status_from_io_error(&error))
+}
+
+/// Describes the filesystem entry; symbolic links are not followed.
+pub(crate) async fn file_stat(request: &OwnedNowFileStatReqMsg) -> NowHandlerResult<OwnedNowFileStatRspMsg> {
+    let path = request_path(request.path())?;
+    let metadata = fs::symlink_metadata(path).await.map_err(fs_error_status)?;
+
+    let name = Path::new(path).file_name().unwrap_or_default().to_string_lossy();
+
+    Ok(
+        NowFileStatRspMsg::new_success(request.request_id(), entry_kind(&metadata))
+            .with_file_size(entry_size(&metadata))
+            .with_mtime(entry_mtime(&metadata))
+            .with_attributes(entry_attributes(&name, &metadata)),
+    )
+}
+
+/// Lists the requested page of directory entries sorted by name. Only entry names are collected
+/// for the whole directory, entries are described for the requested page only. Entries removed
+/// during enumeration are skipped.
+pub(crate) async fn file_list_dir(request: &OwnedNowFileListDirReqMsg) -> NowHandlerResult<NowFileDirListing> {
+    let path = Path::new(request_path(request.path())?);
+    let mut directory = fs::read_dir(path).await.map_err(fs_error_status)?;
+
+    let mut names = Vec::new();
+
+    while let Some(entry) = directory.next_entry().await.map_err(fs_error_status)? {
+        names.push(entry.file_name());
+    }
+
+    // Stable order is required for the pagination.
+    names.sort_unstable();
+
+    let start_index = usize::try_from(request.start_index()).unwrap_or(usize::MAX);
+    let max_entries = request
+        .max_entries()
+        .map_or(usize::MAX, |max| usize::try_from(max).unwrap_or(usize::MAX));
+
+    let mut names = names.into_iter().skip(start_index);
+    let mut entries = Vec::new();
+
+    while entries.len() < max_entries {
+        let Some(name) = names.next() else {
+            break;
+        };
+
+        let Ok(metadata) = fs::symlink_metadata(path.join(&name)).await else {
+            continue;
+        };
+
+        let name = name.to_string_lossy().into_owned();
+        let attributes = entry_attributes(&name, &metadata);
+
+        let entry = NowFileDirEntryMsg::new(request.request_id(), entry_kind(&metadata), name)
+            .map_err(|error| {
+                tracing::debug!(%error, "Failed to encode directory entry");
+                NowStatusError::new_proto(NowProtoError::Internal)
+            })?
+            .with_file_size(entry_size(&metadata))
+            .with_mtime(entry_mtime(&metadata))
+            .with_attributes(attributes);
+
+        entries.push(entry);
+    }
+
+    let listing = NowFileDirListing::new(entries);
+
+    Ok(if names.next().is_some() {
+        listing.with_more()
+    } else {
+        listing
+    })
+}
+
+/// Creates the directory, along with its missing parents for recursive requests.
+pub(crate) async fn file_mkdir(request: &OwnedNowFileMkdirMsg) -> NowHandlerResult<()> {
+    let path = request_path(request.path())?;
+
+    let result = if request.is_recursive() {
+        fs::create_dir_all(path).await
+    } else {
+        fs::create_dir(path).await
+    };
+
+    result.map_err(fs_error_status)
+}
+
+/// Deletes the file, symbolic link or directory. Non-empty directories are only deleted by
+/// recursive requests.
+pub(crate) async fn file_delete(request: &OwnedNowFileDeleteMsg) -> NowHandlerResult<()> {
+    let path = request_path(request.path())?;
+    let metadata = fs::symlink_metadata(path).await.map_err(fs_error_status)?;
+
+    let result = if !metadata.is_dir() {
+        fs::remove_file(path).await
+    } else if request.is_recursive() {
+        fs::remove_dir_all(path).await
+    } else {
+        fs::remove_dir(path).await
+    };
+
+    result.map_err(fs_error_status)
+}
+
+/// Renames the entry. Existing destination is only replaced if requested.
+pub(crate) async fn file_rename(request: &OwnedNowFileRenameMsg) -> NowHandlerResult<()> {
+    let path = request_path(request.path())?;
+    let new_path = request_path(request.new_path())?;
+
+    if !request.is_replace() && fs::symlink_metadata(new_path).await.is_ok() {
+        return Err(now_status_from_io_error(&io::Error::from_raw_os_error(libc::EEXIST)));
+    }
+
+    fs::rename(path, new_path).await.map_err(fs_error_status)
+}
+
+fn entry_kind(metadata: &Metadata) -> NowFileEntryKind {
+    let file_type = metadata.file_type();
+
+    if file_type.is_file() {
+        NowFileEntryKind::FILE
+    } else if file_type.is_dir() {
+        NowFileEntryKind::DIRECTORY
+    } else if file_type.is_symlink() {
+        NowFileEntryKind::SYMLINK
+    } else {
+        NowFileEntryKind::OTHER
+    }
+}
+
+fn entry_size(metadata: &Metadata) -> u64 {
+    if metadata.is_dir() {
+        0
+    } else {
+        metadata.len()
+    }
+}
+
+fn entry_mtime(metadata: &Metadata) -> u64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|mtime| mtime.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map_or(0, |mtime| mtime.as_secs())
+}
+
+/// Derives entry attributes from the permission bits; dot files are reported as hidden.
+fn entry_attributes(name: &str, metadata: &Metadata) -> NowFileAttributes {
+    let mut attributes = NowFileAttributes::empty();
+
+    if name.starts_with('.') {
+        attributes |= NowFileAttributes::HIDDEN;
+    }
+
+    if metadata.permissions().readonly() {
+        attributes |= NowFileAttributes::READONLY;
+    }
+
+    if metadata.is_file() && metadata.permissions().mode() & 0o111 != 0 {
+        attributes |= NowFileAttributes::EXECUTABLE;
+    }
+
+    attributes
+}
+
+/// Maps missing entries and permission errors to the protocol codes, and other errors to the
+/// Unix error codes.
+fn fs_error_status(error: io::Error) -> NowStatusError {
+    match error.raw_os_error() {
+        Some(libc::ENOENT) => NowStatusError::new_proto(NowProtoError::NotFound),
+        Some(libc::EACCES | libc::EPERM) => NowStatusError::new_proto(NowProtoError::AccessDenied),
+        _ => now_status_from_io_error(&error),
+    }
+}
+
+fn request_path(path: &str) -> NowHandlerResult<&str> {
+    if path.is_empty() {
+        return Err(NowStatusError::new_proto(NowProtoError::InvalidRequest));
+    }
+
+    Ok(path)
+}
+
+fn invalid_offset(offset: u64, file_size: u64) -> NowStatusError {
+    let status = NowStatusError::new_proto(NowProtoError::InvalidRequest);
+
+    status
+        .clone()
+        .with_message(format!("offset {offset} is beyond the end of file ({file_size} bytes)"))
+        .unwrap_or(status)
+}
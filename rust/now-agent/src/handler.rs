@@ -0,0 +1,140 @@
+use std::path::PathBuf;
+
+use now_proto_pdu::{
+    NowChannelCapsetMsg, NowExecCapsetFlags, NowFileCapsetFlags, NowProtoError, NowStatusError, NowSystemCapsetFlags,
+    NowSystemInfoReqMsg, NowSystemProcessListReqMsg, NowSystemProcessTerminateMsg, OwnedNowExecBatchMsg,
+    OwnedNowExecProcessMsg, OwnedNowExecRunMsg, OwnedNowExecShellMsg, OwnedNowExecWinPsMsg, OwnedNowFileDeleteMsg,
+    OwnedNowFileListDirReqMsg, OwnedNowFileMkdirMsg, OwnedNowFileOpenMsg, OwnedNowFileRenameMsg,
+    OwnedNowFileStatReqMsg, OwnedNowFileStatRspMsg, OwnedNowSystemInfoRspMsg, OwnedNowSystemProcessInfoMsg,
+};
+use now_proto_server::{NowExecContext, NowFileDirListing, NowFileTransferContext, NowHandlerResult, NowServerHandler};
+
+use crate::exec::{self, ExecOptions};
+use crate::{file, process, system};
+
+/// [`NowServerHandler`] implementation running exec requests on the local host with
+/// `std::process`.
+#[derive(Debug, Clone)]
+pub struct NowAgentHandler {
+    utmp_path: PathBuf,
+}
+
+impl Default for NowAgentHandler {
+    fn default() -> Self {
+        Self {
+            utmp_path: PathBuf::from(system::UTMP_PATH),
+        }
+    }
+}
+
+impl NowAgentHandler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Overrides the `utmp(5)` database used to find the logged on user (`/var/run/utmp` by
+    /// default).
+    #[must_use]
+    pub fn with_utmp_path(mut self, utmp_path: impl Into<PathBuf>) -> Self {
+        self.utmp_path = utmp_path.into();
+        self
+    }
+
+    /// Returns capabilities implemented by the agent, without heartbeat interval.
+    ///
+    /// Windows-only execution styles are advertised as well, so that clients receive a
+    /// `NowProtoError::NotImplemented` session result instead of a closed channel.
+    pub fn capabilities() -> NowChannelCapsetMsg {
+        NowChannelCapsetMsg::default()
+            .with_system_capset(NowSystemCapsetFlags::INFO | NowSystemCapsetFlags::PROCESS)
+            .with_exec_capset(
+                NowExecCapsetFlags::STYLE_RUN
+                    | NowExecCapsetFlags::STYLE_PROCESS
+                    | NowExecCapsetFlags::STYLE_SHELL
+                    | NowExecCapsetFlags::STYLE_BATCH
+                    | NowExecCapsetFlags::STYLE_WINPS
+                    | NowExecCapsetFlags::IO_REDIRECTION,
+            )
+            .with_file_capset(
+                NowFileCapsetFlags::READ
+                    | NowFileCapsetFlags::WRITE
+                    | NowFileCapsetFlags::BROWSE
+                    | NowFileCapsetFlags::MANAGE,
+            )
+    }
+}
+
+impl NowServerHandler for NowAgentHandler {
+    async fn system_info(&self, request: NowSystemInfoReqMsg) -> NowHandlerResult<OwnedNowSystemInfoRspMsg> {
+        system::system_info(&request, &self.utmp_path)
+    }
+
+    async fn system_process_list(
+        &self,
+        request: NowSystemProcessListReqMsg,
+    ) -> NowHandlerResult<Vec<OwnedNowSystemProcessInfoMsg>> {
+        process::process_list(&request)
+    }
+
+    async fn system_process_terminate(&self, request: NowSystemProcessTerminateMsg) -> NowHandlerResult<()> {
+        process::process_terminate(&request)
+    }
+
+    async fn exec_run(&self, request: OwnedNowExecRunMsg) -> NowHandlerResult<()> {
+        exec::spawn_detached(exec::run_command(&request))
+    }
+
+    async fn exec_process(&self, request: OwnedNowExecProcessMsg, session: NowExecContext) -> NowHandlerResult<u32> {
+        let options = ExecOptions {
+            io_redirection: request.is_with_io_redirection(),
+            detached: request.is_detached(),
+        };
+
+        exec::run_session(exec::process_command(&request), options, session).await
+    }
+
+    async fn exec_shell(&self, request: OwnedNowExecShellMsg, session: NowExecContext) -> NowHandlerResult<u32> {
+        let options = ExecOptions {
+            io_redirection: request.is_with_io_redirection(),
+            detached: request.is_detached(),
+        };
+
+        exec::run_session(exec::shell_command(&request), options, session).await
+    }
+
+    async fn exec_batch(&self, _request: OwnedNowExecBatchMsg, _session: NowExecContext) -> NowHandlerResult<u32> {
+        Err(NowStatusError::new_proto(NowProtoError::NotImplemented))
+    }
+
+    async fn exec_winps(&self, _request: OwnedNowExecWinPsMsg, _session: NowExecContext) -> NowHandlerResult<u32> {
+        Err(NowStatusError::new_proto(NowProtoError::NotImplemented))
+    }
+
+    async fn file_read(&self, request: OwnedNowFileOpenMsg, transfer: NowFileTransferContext) -> NowHandlerResult<()> {
+        file::file_read(&request, transfer).await
+    }
+
+    async fn file_write(&self, request: OwnedNowFileOpenMsg, transfer: NowFileTransferContext) -> NowHandlerResult<()> {
+        file::file_write(&request, transfer).await
+    }
+
+    async fn file_stat(&self, request: OwnedNowFileStatReqMsg) -> NowHandlerResult<OwnedNowFileStatRspMsg> {
+        file::file_stat(&request).await
+    }
+
+    async fn file_list_dir(&self, request: OwnedNowFileListDirReqMsg) -> NowHandlerResult<NowFileDirListing> {
+        file::file_list_dir(&request).await
+    }
+
+    async fn file_mkdir(&self, request: OwnedNowFileMkdirMsg) -> NowHandlerResult<()> {
+        file::file_mkdir(&request).await
+    }
+
+    async fn file_delete(&self, request: OwnedNowFileDeleteMsg) -> NowHandlerResult<()> {
+        file::file_delete(&request).await
+    }
+
+    async fn file_rename(&self, request: OwnedNowFileRenameMsg) -> NowHandlerResult<()> {
+        file::file_rename(&request).await
+    }
+}
@@ -0,0 +1,14 @@
+#![doc = include_str!("../README.md")]
+#![doc(
+    html_logo_url = "https://webdevolutions.blob.core.windows.net/images/projects/devolutions/logos/devolutions-icon-shadow.svg"
+)]
+#![cfg(unix)]
+#![allow(unused_crate_dependencies)] // false positives because there is both a library and a binary
+
+mod exec;
+mod file;
+mod handler;
+mod process;
+mod system;
+
+pub use handler::*;
@@ -0,0 +1,139 @@
+#![allow(unused_crate_dependencies)] // false positives because there is both a library and a binary
+#![allow(clippy::print_stdout)]
+
+#[cfg(unix)]
+mod cli {
+    use core::time::Duration;
+    use std::path::PathBuf;
+
+    use anyhow::Context as _;
+
+    const HELP: &str = "\
+now-agent
+
+USAGE:
+  now-agent [OPTIONS] --socket <PATH>
+
+FLAGS:
+  -h, --help                        Prints help information
+
+OPTIONS:
+  -s, --socket <PATH>               Unix domain socket path to listen on
+  --heartbeat-interval <SECONDS>    Server heartbeat interval (heartbeat is disabled by default)
+";
+
+    pub(crate) fn print_help() {
+        println!("{HELP}");
+    }
+
+    pub(crate) struct Args {
+        pub(crate) socket: PathBuf,
+        pub(crate) heartbeat_interval: Option<Duration>,
+    }
+
+    /// Returns `None` if help was requested.
+    pub(crate) fn parse_args() -> anyhow::Result<Option<Args>> {
+        let mut args = pico_args::Arguments::from_env();
+
+        if args.contains(["-h", "--help"]) {
+            return Ok(None);
+        }
+
+        let socket = args.value_from_str(["-s", "--socket"]).context("socket path")?;
+        let heartbeat_interval = args
+            .opt_value_from_str("--heartbeat-interval")
+            .context("heartbeat interval")?
+            .map(Duration::from_secs);
+
+        let remaining = args.finish();
+        if !remaining.is_empty() {
+            anyhow::bail!("unexpected arguments: {remaining:?}");
+        }
+
+        Ok(Some(Args {
+            socket,
+            heartbeat_interval,
+        }))
+    }
+}
+
+#[cfg(unix)]
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    use std::os::unix::fs::FileTypeExt as _;
+    use std::sync::Arc;
+
+    use anyhow::Context as _;
+    use now_agent::NowAgentHandler;
+    use now_proto_server::NowServer;
+    use tokio::net::UnixListener;
+    use tracing_subscriber::EnvFilter;
+
+    let args = match cli::parse_args() {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            cli::print_help();
+            return Ok(());
+        }
+        Err(error) => {
+            cli::print_help();
+            return Err(error);
+        }
+    };
+
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")))
+        .init();
+
+    let mut capabilities = NowAgentHandler::capabilities();
+    if let Some(interval) = args.heartbeat_interval {
+        capabilities = capabilities
+            .with_heartbeat_interval(interval)
+            .context("invalid heartbeat interval")?;
+    }
+
+    // Socket left behind by the previous agent instance.
+    if std::fs::symlink_metadata(&args.socket).is_ok_and(|metadata| metadata.file_type().is_socket()) {
+        std::fs::remove_file(&args.socket).context("failed to remove stale socket")?;
+    }
+
+    let listener =
+        UnixListener::bind(&args.socket).with_context(|| format!("failed to listen on {}", args.socket.display()))?;
+
+    tracing::info!(socket = %args.socket.display(), "NOW agent is listening");
+
+    let server = Arc::new(NowServer::new(capabilities, NowAgentHandler::new()));
+
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let (stream, _) = accepted.context("failed to accept connection")?;
+                let server = Arc::clone(&server);
+
+                tokio::spawn(async move {
+                    tracing::info!("NOW channel connected");
+
+                    match server.serve(stream).await {
+                        Ok(()) => tracing::info!("NOW channel closed"),
+                        Err(error) => tracing::warn!(%error, "NOW channel failed"),
+                    }
+                });
+            }
+            result = tokio::signal::ctrl_c() => {
+                result.context("failed to listen for shutdown signal")?;
+                break;
+            }
+        }
+    }
+
+    tracing::info!("Shutting down");
+
+    std::fs::remove_file(&args.socket).context("failed to remove socket")?;
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn main() -> anyhow::Result<()> {
+    anyhow::bail!("now-agent is only supported on Unix systems")
+}
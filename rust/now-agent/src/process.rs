@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+use std::os::unix::fs::MetadataExt as _;
+use std::{fs, io};
+
+use now_proto_pdu::ironrdp_core::EncodeResult;
+use now_proto_pdu::{
+    NowProtoError, NowStatusError, NowSystemProcessInfoMsg, NowSystemProcessListReqMsg, NowSystemProcessTerminateMsg,
+    OwnedNowSystemProcessInfoMsg,
+};
+use now_proto_server::{now_status_from_io_error, NowHandlerResult};
+
+const PROC_PATH: &str = "/proc";
+const PROC_STAT_PATH: &str = "/proc/stat";
+const PASSWD_PATH: &str = "/etc/passwd";
+
+/// Index of the `starttime` field in `/proc/<pid>/stat`, counting from the `state` field which
+/// follows the process name.
+const STAT_START_TIME_INDEX: usize = 19;
+
+/// Enumerates processes from procfs. Processes which exit during enumeration are skipped.
+pub(crate) fn process_list(
+    request: &NowSystemProcessListReqMsg,
+) -> NowHandlerResult<Vec<OwnedNowSystemProcessInfoMsg>> {
+    let entries = fs::read_dir(PROC_PATH).map_err(|error| now_status_from_io_error(&error))?;
+
+    let users = users();
+    let boot_time = boot_time();
+
+    let mut processes = Vec::new();
+
+    for entry in entries.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|name| name.parse::<u32>().ok()) else {
+            continue;
+        };
+
+        let Some(stat) = ProcessStat::read(pid) else {
+            continue;
+        };
+
+        let process = build_process_info(request.request_id(), pid, stat, &users, boot_time).map_err(|error| {
+            tracing::debug!(%error, pid, "Failed to encode process info");
+            NowStatusError::new_proto(NowProtoError::Internal)
+        })?;
+
+        processes.push(process);
+    }
+
+    Ok(processes)
+}
+
+/// Sends `SIGTERM` to the process, or `SIGKILL` if termination is forced.
+pub(crate) fn process_terminate(request: &NowSystemProcessTerminateMsg) -> NowHandlerResult<()> {
+    // Zero and negative values have special meaning for `kill` (process group or all processes).
+    let pid = libc::pid_t::try_from(request.pid())
+        .ok()
+        .filter(|pid| *pid > 0)
+        .ok_or_else(|| NowStatusError::new_proto(NowProtoError::NotFound))?;
+
+    let signal = if request.is_force() {
+        libc::SIGKILL
+    } else {
+        libc::SIGTERM
+    };
+
+    // SAFETY: `kill` has no memory safety preconditions.
+    let result = unsafe { libc::kill(pid, signal) };
+
+    if result != 0 {
+        let error = io::Error::last_os_error();
+
+        return Err(match error.raw_os_error() {
+            Some(libc::ESRCH) => NowStatusError::new_proto(NowProtoError::NotFound),
+            Some(libc::EPERM) => NowStatusError::new_proto(NowProtoError::AccessDenied),
+            _ => now_status_from_io_error(&error),
+        });
+    }
+
+    Ok(())
+}
+
+fn build_process_info(
+    request_id: u32,
+    pid: u32,
+    stat: ProcessStat,
+    users: &HashMap<u32, String>,
+    boot_time: Option<u64>,
+) -> EncodeResult<OwnedNowSystemProcessInfoMsg> {
+    let start_time = boot_time.and_then(|boot_time| stat.start_time(boot_time));
+
+    let mut process = NowSystemProcessInfoMsg::new(request_id, pid, stat.name)?
+        .with_parent_pid(stat.parent_pid)
+        .with_session_id(stat.session_id);
+
+    if let Some(start_time) = start_time {
+        process = process.with_start_time(start_time);
+    }
+
+    // Executable link is only readable for own processes, unless running as root.
+    if let Ok(exe_path) = fs::read_link(format!("{PROC_PATH}/{pid}/exe")) {
+        process = process.with_exe_path(exe_path.to_string_lossy().into_owned())?;
+    }
+
+    let user = fs::metadata(format!("{PROC_PATH}/{pid}"))
+        .ok()
+        .and_then(|metadata| users.get(&metadata.uid()));
+
+    if let Some(user) = user {
+        process = process.with_user(user.clone())?;
+    }
+
+    Ok(process)
+}
+
+/// Fields of `/proc/<pid>/stat`, see `proc_pid_stat(5)`.
+struct ProcessStat {
+    name: String,
+    parent_pid: u32,
+    session_id: u32,
+    /// Process start time, in clock ticks after system boot.
+    start_ticks: u64,
+}
+
+impl ProcessStat {
+    fn read(pid: u32) -> Option<Self> {
+        let stat = fs::read_to_string(format!("{PROC_PATH}/{pid}/stat")).ok()?;
+
+        // Process name could contain spaces and parentheses, so it is delimited by the first `(`
+        // and the last `)`.
+        let name_start = stat.find('(')?;
+        let name_end = stat.rfind(')')?;
+        let name = stat.get(name_start + 1..name_end)?.to_owned();
+
+        let fields: Vec<&str> = stat.get(name_end + 1..)?.split_whitespace().collect();
+
+        Some(Self {
+            name,
+            parent_pid: fields.get(1)?.parse().ok()?,
+            session_id: fields.get(3)?.parse().ok()?,
+            start_ticks: fields.get(STAT_START_TIME_INDEX)?.parse().ok()?,
+        })
+    }
+
+    /// Process start time, as Unix timestamp in seconds.
+    fn start_time(&self, boot_time: u64) -> Option<u64> {
+        // SAFETY: `sysconf` has no memory safety preconditions.
+        let ticks_per_second = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+        let ticks_per_second = u64::try_from(ticks_per_second).ok().filter(|ticks| *ticks > 0)?;
+
+        boot_time.checked_add(self.start_ticks / ticks_per_second)
+    }
+}
+
+/// System boot time, as Unix timestamp in seconds, from the `btime` field of `/proc/stat`.
+fn boot_time() -> Option<u64> {
+    let stat = fs::read_to_string(PROC_STAT_PATH).ok()?;
+
+    stat.lines()
+        .find_map(|line| line.strip_prefix("btime "))
+        .and_then(|value| value.trim().parse().ok())
+}
+
+/// User names by user ID, from `/etc/passwd`.
+fn users() -> HashMap<u32, String> {
+    let Ok(passwd) = fs::read_to_string(PASSWD_PATH) else {
+        return HashMap::new();
+    };
+
+    passwd
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(':');
+            let name = fields.next()?;
+            let uid = fields.nth(1)?.parse().ok()?;
+            Some((uid, name.to_owned()))
+        })
+        .collect()
+}
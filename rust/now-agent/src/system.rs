@@ -0,0 +1,136 @@
+use core::ops::Range;
+use core::time::Duration;
+use std::fs;
+use std::path::Path;
+
+use now_proto_pdu::ironrdp_core::EncodeResult;
+use now_proto_pdu::{
+    NowProtoError, NowStatusError, NowSystemInfoReqMsg, NowSystemInfoRspMsg, OwnedNowSystemInfoRspMsg,
+};
+use now_proto_server::NowHandlerResult;
+
+const OS_RELEASE_PATH: &str = "/etc/os-release";
+const HOSTNAME_PATH: &str = "/proc/sys/kernel/hostname";
+const OS_VERSION_PATH: &str = "/proc/sys/kernel/osrelease";
+const UPTIME_PATH: &str = "/proc/uptime";
+const ACTIVE_CONSOLE_PATH: &str = "/sys/class/tty/tty0/active";
+pub(crate) const UTMP_PATH: &str = "/var/run/utmp";
+
+// glibc `struct utmp` layout, which is the same on 32-bit and 64-bit targets.
+const UTMP_RECORD_SIZE: usize = 384;
+const UT_TYPE: Range<usize> = 0..2;
+const UT_LINE: Range<usize> = 8..40;
+const UT_USER: Range<usize> = 44..76;
+const UT_TV_SEC: Range<usize> = 340..344;
+/// `ut_type` of the normal user process (login session).
+const USER_PROCESS: i16 = 7;
+
+/// Collects system information from procfs, `/etc/os-release` and `utmp(5)`. Information which is
+/// not available on the host is omitted from the response.
+pub(crate) fn system_info(
+    request: &NowSystemInfoReqMsg,
+    utmp_path: &Path,
+) -> NowHandlerResult<OwnedNowSystemInfoRspMsg> {
+    build_system_info(request.request_id(), utmp_path).map_err(|error| {
+        tracing::debug!(%error, "Failed to encode system info response");
+        NowStatusError::new_proto(NowProtoError::Internal)
+    })
+}
+
+fn build_system_info(request_id: u32, utmp_path: &Path) -> EncodeResult<OwnedNowSystemInfoRspMsg> {
+    let mut response = NowSystemInfoRspMsg::new_success(request_id)
+        .with_os_name(os_name().unwrap_or_else(|| "Linux".to_owned()))?
+        .with_architecture(std::env::consts::ARCH)?
+        .with_agent_version(env!("CARGO_PKG_VERSION"))?;
+
+    if let Some(os_version) = read_trimmed(OS_VERSION_PATH) {
+        response = response.with_os_version(os_version)?;
+    }
+
+    if let Some(hostname) = read_trimmed(HOSTNAME_PATH) {
+        response = response.with_hostname(hostname)?;
+    }
+
+    if let Some(uptime) = uptime() {
+        response = response.with_uptime(uptime);
+    }
+
+    if let Some(user) = logged_on_user(utmp_path) {
+        response = response.with_logged_on_user(user)?;
+    }
+
+    Ok(response)
+}
+
+/// Distribution name from the `PRETTY_NAME` field of `os-release(5)`.
+fn os_name() -> Option<String> {
+    let os_release = fs::read_to_string(OS_RELEASE_PATH).ok()?;
+
+    os_release.lines().find_map(|line| {
+        let value = line.strip_prefix("PRETTY_NAME=")?;
+        let value = value.trim_matches(|c| c == '"' || c == '\'');
+        (!value.is_empty()).then(|| value.to_owned())
+    })
+}
+
+/// First field of `/proc/uptime`, in seconds with fractional part.
+fn uptime() -> Option<Duration> {
+    let uptime = fs::read_to_string(UPTIME_PATH).ok()?;
+    let seconds = uptime.split_whitespace().next()?.parse::<f64>().ok()?;
+
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+/// Login session read from the `USER_PROCESS` entry of `utmp(5)`.
+struct UtmpSession {
+    line: String,
+    user: String,
+    login_time: i32,
+}
+
+/// User of the active session: the session on the active virtual console if any (e.g. `tty2`),
+/// or the most recent login otherwise (e.g. SSH sessions on headless hosts).
+fn logged_on_user(utmp_path: &Path) -> Option<String> {
+    let utmp = fs::read(utmp_path).ok()?;
+
+    let sessions: Vec<UtmpSession> = utmp.chunks_exact(UTMP_RECORD_SIZE).filter_map(user_session).collect();
+
+    let active_console = read_trimmed(ACTIVE_CONSOLE_PATH);
+    let active = sessions
+        .iter()
+        .find(|session| active_console.as_deref() == Some(session.line.as_str()));
+
+    active
+        .or_else(|| sessions.iter().max_by_key(|session| session.login_time))
+        .map(|session| session.user.clone())
+}
+
+fn user_session(record: &[u8]) -> Option<UtmpSession> {
+    let ut_type = i16::from_ne_bytes(record[UT_TYPE].try_into().ok()?);
+    if ut_type != USER_PROCESS {
+        return None;
+    }
+
+    let user = utmp_str(&record[UT_USER])?;
+
+    Some(UtmpSession {
+        line: utmp_str(&record[UT_LINE]).unwrap_or_default(),
+        user,
+        login_time: i32::from_ne_bytes(record[UT_TV_SEC].try_into().ok()?),
+    })
+}
+
+/// Reads NUL-padded `utmp` string field.
+fn utmp_str(field: &[u8]) -> Option<String> {
+    let len = field.iter().position(|&byte| byte == 0).unwrap_or(field.len());
+    let value = core::str::from_utf8(&field[..len]).ok()?;
+
+    (!value.is_empty()).then(|| value.to_owned())
+}
+
+fn read_trimmed(path: &str) -> Option<String> {
+    let value = fs::read_to_string(path).ok()?;
+    let value = value.trim();
+
+    (!value.is_empty()).then(|| value.to_owned())
+}
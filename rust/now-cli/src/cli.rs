@@ -0,0 +1,791 @@
+use core::time::Duration;
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+use anyhow::Context as _;
+use now_proto_pdu::{NowMessageBoxStyle, NowSystemPowerAction};
+
+const HELP: &str = "\
+now-cli
+
+USAGE:
+  now-cli --pipe <PATH> [OPTIONS] <COMMAND>
+
+FLAGS:
+  -h, --help                  Prints help information
+
+OPTIONS:
+  -p, --pipe <PATH>           Unix domain socket or Windows named pipe path
+  --connect-timeout <SECONDS> Connection and negotiation timeout (10 seconds by default)
+
+COMMANDS:
+  exec shell <COMMAND>        Runs the command with the shell
+      [--shell <SHELL>] [--directory <DIR>] [--detached] [--no-stdin]
+  exec process <FILENAME>     Starts the process
+      [--parameters <PARAMS>] [--directory <DIR>] [--detached] [--no-stdin]
+  exec run <COMMAND>          Starts the command without following its execution
+      [--directory <DIR>]
+  exec pwsh <COMMAND>         Runs the PowerShell 7 command
+      [--directory <DIR>] [--detached] [--no-stdin]
+  session lock                Locks the user session
+  session logoff              Logs off the user session
+  session msgbox <MESSAGE>    Shows message box and prints the user response
+      [--title <TITLE>] [--style <STYLE>] [--timeout <SECONDS>] [--no-response]
+  session kbd <LAYOUT>        Sets keyboard layout (`next`, `prev` or layout identifier, e.g. 00000409)
+  system info                 Prints the remote host information
+  system shutdown             Shuts down the remote host
+      [--message <MESSAGE>] [--timeout <SECONDS>] [--force] [--reboot]
+  system shutdown-abort       Aborts pending shutdown or power action
+  system power <ACTION>       Requests power action and waits until it is scheduled
+      [--message <MESSAGE>] [--timeout <SECONDS>] [--force]
+  system ps                   Lists remote processes
+  system kill <PID>           Terminates remote process
+      [--force]
+  file get <REMOTE> <LOCAL>   Downloads remote file
+      [--resume]              Continues at the end of the local file
+  file put <LOCAL> <REMOTE>   Uploads local file
+      [--resume]              Continues at the end of the remote file
+  file stat <REMOTE>          Prints remote file information
+  file ls <REMOTE>            Lists remote directory
+  file mkdir <REMOTE>         Creates remote directory
+      [--parents]             Creates missing parent directories
+  file rm <REMOTE>            Deletes remote file or directory
+      [--recursive]           Deletes non-empty directories
+  file mv <REMOTE> <NEW>      Renames remote file or directory
+      [--replace]             Replaces existing destination
+  rdm start                   Starts RDM application
+      [--jump] [--maximized] [--fullscreen] [--timeout <SECONDS>]
+  rdm session <CONNECTION_ID> <CONNECTION_DATA>
+      [--session-id <UUID>]   Starts RDM session (random session ID by default)
+
+Message box styles: ok, ok-cancel, abort-retry-ignore, yes-no-cancel, yes-no, retry-cancel,
+cancel-try-continue or numeric WinAPI MessageBox style.
+
+Power actions: shutdown, reboot, sleep, hibernate, session-restart or numeric action value.
+";
+
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub(crate) fn print_help() {
+    println!("{HELP}");
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Args {
+    pub(crate) pipe: PathBuf,
+    pub(crate) connect_timeout: Duration,
+    pub(crate) action: Action,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct ExecOptions {
+    pub(crate) directory: Option<String>,
+    pub(crate) detached: bool,
+    pub(crate) forward_stdin: bool,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum KbdLayout {
+    Next,
+    Prev,
+    Specific(String),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Action {
+    ExecShell {
+        command: String,
+        shell: Option<String>,
+        options: ExecOptions,
+    },
+    ExecProcess {
+        filename: String,
+        parameters: Option<String>,
+        options: ExecOptions,
+    },
+    ExecRun {
+        command: String,
+        directory: Option<String>,
+    },
+    ExecPwsh {
+        command: String,
+        options: ExecOptions,
+    },
+    SessionLock,
+    SessionLogoff,
+    SessionMsgBox {
+        message: String,
+        title: Option<String>,
+        style: Option<NowMessageBoxStyle>,
+        timeout: Option<Duration>,
+        response: bool,
+    },
+    SessionKbd {
+        layout: KbdLayout,
+    },
+    SystemInfo,
+    SystemShutdown {
+        message: String,
+        timeout: Duration,
+        force: bool,
+        reboot: bool,
+    },
+    SystemShutdownAbort,
+    SystemProcessList,
+    SystemProcessTerminate {
+        pid: u32,
+        force: bool,
+    },
+    SystemPower {
+        action: NowSystemPowerAction,
+        message: String,
+        timeout: Duration,
+        force: bool,
+    },
+    FileGet {
+        remote: String,
+        local: PathBuf,
+        resume: bool,
+    },
+    FilePut {
+        local: PathBuf,
+        remote: String,
+        resume: bool,
+    },
+    FileStat {
+        remote: String,
+    },
+    FileList {
+        remote: String,
+    },
+    FileMkdir {
+        remote: String,
+        parents: bool,
+    },
+    FileDelete {
+        remote: String,
+        recursive: bool,
+    },
+    FileRename {
+        remote: String,
+        new_remote: String,
+        replace: bool,
+    },
+    RdmStart {
+        jump: bool,
+        maximized: bool,
+        fullscreen: bool,
+        timeout: Option<u32>,
+    },
+    RdmSession {
+        session_id: Option<uuid::Uuid>,
+        connection_id: uuid::Uuid,
+        connection_data: String,
+    },
+}
+
+/// Parses the process command line. Returns `None` if help was requested.
+pub(crate) fn parse_args() -> anyhow::Result<Option<Args>> {
+    parse_arguments(pico_args::Arguments::from_env())
+}
+
+fn parse_arguments(mut args: pico_args::Arguments) -> anyhow::Result<Option<Args>> {
+    if args.contains(["-h", "--help"]) {
+        return Ok(None);
+    }
+
+    let pipe = args.value_from_str(["-p", "--pipe"]).context("--pipe")?;
+    let connect_timeout = args
+        .opt_value_from_str("--connect-timeout")
+        .context("--connect-timeout")?
+        .map_or(DEFAULT_CONNECT_TIMEOUT, Duration::from_secs);
+
+    let action = match args.subcommand()?.as_deref() {
+        Some("exec") => parse_exec(&mut args)?,
+        Some("session") => parse_session(&mut args)?,
+        Some("system") => parse_system(&mut args)?,
+        Some("file") => match args.subcommand()?.as_deref() {
+            Some("get") => Action::FileGet {
+                resume: args.contains("--resume"),
+                remote: args.free_from_str().context("remote path")?,
+                local: args.free_from_str().context("local path")?,
+            },
+            Some("put") => Action::FilePut {
+                resume: args.contains("--resume"),
+                local: args.free_from_str().context("local path")?,
+                remote: args.free_from_str().context("remote path")?,
+            },
+            Some("stat") => Action::FileStat {
+                remote: args.free_from_str().context("remote path")?,
+            },
+            Some("ls") => Action::FileList {
+                remote: args.free_from_str().context("remote path")?,
+            },
+            Some("mkdir") => Action::FileMkdir {
+                parents: args.contains("--parents"),
+                remote: args.free_from_str().context("remote path")?,
+            },
+            Some("rm") => Action::FileDelete {
+                recursive: args.contains("--recursive"),
+                remote: args.free_from_str().context("remote path")?,
+            },
+            Some("mv") => Action::FileRename {
+                replace: args.contains("--replace"),
+                remote: args.free_from_str().context("remote path")?,
+                new_remote: args.free_from_str().context("new remote path")?,
+            },
+            Some(unknown) => anyhow::bail!("unknown file command: {unknown}"),
+            None => anyhow::bail!("missing file command"),
+        },
+        Some("rdm") => match args.subcommand()?.as_deref() {
+            Some("start") => Action::RdmStart {
+                jump: args.contains("--jump"),
+                maximized: args.contains("--maximized"),
+                fullscreen: args.contains("--fullscreen"),
+                timeout: args.opt_value_from_str("--timeout")?,
+            },
+            Some("session") => Action::RdmSession {
+                session_id: args.opt_value_from_str("--session-id")?,
+                connection_id: args.free_from_str().context("connection ID")?,
+                connection_data: args.free_from_str().context("connection data")?,
+            },
+            Some(unknown) => anyhow::bail!("unknown rdm command: {unknown}"),
+            None => anyhow::bail!("missing rdm command"),
+        },
+        Some(unknown) => anyhow::bail!("unknown command: {unknown}"),
+        None => anyhow::bail!("missing command"),
+    };
+
+    finish(args)?;
+
+    Ok(Some(Args {
+        pipe,
+        connect_timeout,
+        action,
+    }))
+}
+
+fn parse_exec(args: &mut pico_args::Arguments) -> anyhow::Result<Action> {
+    let action = match args.subcommand()?.as_deref() {
+        Some("shell") => {
+            let shell = args.opt_value_from_str("--shell")?;
+            let options = parse_exec_options(args)?;
+
+            Action::ExecShell {
+                command: args.free_from_str().context("command")?,
+                shell,
+                options,
+            }
+        }
+        Some("process") => {
+            let parameters = args.opt_value_from_str("--parameters")?;
+            let options = parse_exec_options(args)?;
+
+            Action::ExecProcess {
+                filename: args.free_from_str().context("filename")?,
+                parameters,
+                options,
+            }
+        }
+        Some("run") => {
+            let directory = args.opt_value_from_str("--directory")?;
+
+            Action::ExecRun {
+                command: args.free_from_str().context("command")?,
+                directory,
+            }
+        }
+        Some("pwsh") => {
+            let options = parse_exec_options(args)?;
+
+            Action::ExecPwsh {
+                command: args.free_from_str().context("command")?,
+                options,
+            }
+        }
+        Some(unknown) => anyhow::bail!("unknown exec command: {unknown}"),
+        None => anyhow::bail!("missing exec command"),
+    };
+
+    Ok(action)
+}
+
+fn parse_exec_options(args: &mut pico_args::Arguments) -> anyhow::Result<ExecOptions> {
+    Ok(ExecOptions {
+        directory: args.opt_value_from_str("--directory")?,
+        detached: args.contains("--detached"),
+        forward_stdin: !args.contains("--no-stdin"),
+    })
+}
+
+fn parse_session(args: &mut pico_args::Arguments) -> anyhow::Result<Action> {
+    let action = match args.subcommand()?.as_deref() {
+        Some("lock") => Action::SessionLock,
+        Some("logoff") => Action::SessionLogoff,
+        Some("msgbox") => {
+            let title = args.opt_value_from_str("--title")?;
+            let style = args.opt_value_from_fn("--style", parse_msg_box_style)?;
+            let timeout = args.opt_value_from_str("--timeout")?.map(Duration::from_secs);
+            let response = !args.contains("--no-response");
+
+            Action::SessionMsgBox {
+                message: args.free_from_str().context("message")?,
+                title,
+                style,
+                timeout,
+                response,
+            }
+        }
+        Some("kbd") => {
+            let layout: String = args.free_from_str().context("keyboard layout")?;
+
+            let layout = match layout.as_str() {
+                "next" => KbdLayout::Next,
+                "prev" => KbdLayout::Prev,
+                _ => KbdLayout::Specific(layout),
+            };
+
+            Action::SessionKbd { layout }
+        }
+        Some(unknown) => anyhow::bail!("unknown session command: {unknown}"),
+        None => anyhow::bail!("missing session command"),
+    };
+
+    Ok(action)
+}
+
+fn parse_system(args: &mut pico_args::Arguments) -> anyhow::Result<Action> {
+    let action = match args.subcommand()?.as_deref() {
+        Some("info") => Action::SystemInfo,
+        Some("shutdown") => Action::SystemShutdown {
+            message: args.opt_value_from_str("--message")?.unwrap_or_default(),
+            timeout: parse_timeout(args)?,
+            force: args.contains("--force"),
+            reboot: args.contains("--reboot"),
+        },
+        Some("shutdown-abort") => Action::SystemShutdownAbort,
+        Some("ps") => Action::SystemProcessList,
+        Some("kill") => Action::SystemProcessTerminate {
+            force: args.contains("--force"),
+            pid: args.free_from_str().context("process ID")?,
+        },
+        Some("power") => Action::SystemPower {
+            message: args.opt_value_from_str("--message")?.unwrap_or_default(),
+            timeout: parse_timeout(args)?,
+            force: args.contains("--force"),
+            action: args.free_from_fn(parse_power_action).context("power action")?,
+        },
+        Some(unknown) => anyhow::bail!("unknown system command: {unknown}"),
+        None => anyhow::bail!("missing system command"),
+    };
+
+    Ok(action)
+}
+
+fn parse_timeout(args: &mut pico_args::Arguments) -> anyhow::Result<Duration> {
+    let timeout = args
+        .opt_value_from_str("--timeout")?
+        .map(Duration::from_secs)
+        .unwrap_or_default();
+
+    Ok(timeout)
+}
+
+fn parse_power_action(value: &str) -> anyhow::Result<NowSystemPowerAction> {
+    let action = match value {
+        "shutdown" => NowSystemPowerAction::SHUTDOWN,
+        "reboot" => NowSystemPowerAction::REBOOT,
+        "sleep" => NowSystemPowerAction::SLEEP,
+        "hibernate" => NowSystemPowerAction::HIBERNATE,
+        "session-restart" => NowSystemPowerAction::SESSION_RESTART,
+        value => NowSystemPowerAction::new(
+            value
+                .parse()
+                .with_context(|| format!("invalid power action: {value}"))?,
+        ),
+    };
+
+    Ok(action)
+}
+
+fn parse_msg_box_style(value: &str) -> anyhow::Result<NowMessageBoxStyle> {
+    let style = match value {
+        "ok" => NowMessageBoxStyle::OK,
+        "ok-cancel" => NowMessageBoxStyle::OK_CANCEL,
+        "abort-retry-ignore" => NowMessageBoxStyle::ABORT_RETRY_IGNORE,
+        "yes-no-cancel" => NowMessageBoxStyle::YES_NO_CANCEL,
+        "yes-no" => NowMessageBoxStyle::YES_NO,
+        "retry-cancel" => NowMessageBoxStyle::RETRY_CANCEL,
+        "cancel-try-continue" => NowMessageBoxStyle::CANCEL_TRY_CONTINUE,
+        value => NowMessageBoxStyle::new(
+            value
+                .parse()
+                .with_context(|| format!("invalid message box style: {value}"))?,
+        ),
+    };
+
+    Ok(style)
+}
+
+fn finish(args: pico_args::Arguments) -> anyhow::Result<()> {
+    let remaining: Vec<OsString> = args.finish();
+
+    if !remaining.is_empty() {
+        anyhow::bail!("unexpected arguments: {remaining:?}");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SESSION_ID: &str = "4cb50f86-07a0-4f56-9b42-cd3e5b0cd8b2";
+    const CONNECTION_ID: &str = "7f8d3b33-9bc0-4aa3-8f5d-3e2bf1a4d9c0";
+
+    fn parse(args: &[&str]) -> anyhow::Result<Option<Args>> {
+        parse_arguments(pico_args::Arguments::from_vec(
+            args.iter().map(OsString::from).collect(),
+        ))
+    }
+
+    fn parse_action(args: &[&str]) -> anyhow::Result<Action> {
+        let mut command_line = vec!["--pipe", "/run/now.sock"];
+        command_line.extend_from_slice(args);
+
+        let args = parse(&command_line)?.context("help requested")?;
+        Ok(args.action)
+    }
+
+    fn exec_options() -> ExecOptions {
+        ExecOptions {
+            directory: None,
+            detached: false,
+            forward_stdin: true,
+        }
+    }
+
+    #[test]
+    fn global_options() -> anyhow::Result<()> {
+        assert_eq!(parse(&["--help"])?, None);
+
+        let args = parse(&["-p", "/run/now.sock", "--connect-timeout", "3", "session", "lock"])?;
+        assert_eq!(
+            args,
+            Some(Args {
+                pipe: PathBuf::from("/run/now.sock"),
+                connect_timeout: Duration::from_secs(3),
+                action: Action::SessionLock,
+            })
+        );
+
+        let args = parse(&["--pipe", "/run/now.sock", "session", "lock"])?.context("help requested")?;
+        assert_eq!(args.connect_timeout, DEFAULT_CONNECT_TIMEOUT);
+
+        assert!(parse(&["session", "lock"]).is_err());
+        assert!(parse_action(&[]).is_err());
+        assert!(parse_action(&["unknown"]).is_err());
+        assert!(parse_action(&["session", "lock", "extra"]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn exec_commands() -> anyhow::Result<()> {
+        assert_eq!(
+            parse_action(&["exec", "shell", "--shell", "bash", "--directory", "/tmp", "ls -l"])?,
+            Action::ExecShell {
+                command: "ls -l".to_owned(),
+                shell: Some("bash".to_owned()),
+                options: ExecOptions {
+                    directory: Some("/tmp".to_owned()),
+                    ..exec_options()
+                },
+            }
+        );
+        assert_eq!(
+            parse_action(&["exec", "process", "--parameters", "-a", "--no-stdin", "/bin/uname"])?,
+            Action::ExecProcess {
+                filename: "/bin/uname".to_owned(),
+                parameters: Some("-a".to_owned()),
+                options: ExecOptions {
+                    forward_stdin: false,
+                    ..exec_options()
+                },
+            }
+        );
+        assert_eq!(
+            parse_action(&["exec", "run", "notepad.exe"])?,
+            Action::ExecRun {
+                command: "notepad.exe".to_owned(),
+                directory: None,
+            }
+        );
+        assert_eq!(
+            parse_action(&["exec", "pwsh", "--detached", "Get-Date"])?,
+            Action::ExecPwsh {
+                command: "Get-Date".to_owned(),
+                options: ExecOptions {
+                    detached: true,
+                    ..exec_options()
+                },
+            }
+        );
+
+        assert!(parse_action(&["exec"]).is_err());
+        assert!(parse_action(&["exec", "batch", "dir"]).is_err());
+        assert!(parse_action(&["exec", "shell"]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn session_commands() -> anyhow::Result<()> {
+        assert_eq!(parse_action(&["session", "lock"])?, Action::SessionLock);
+        assert_eq!(parse_action(&["session", "logoff"])?, Action::SessionLogoff);
+        assert_eq!(
+            parse_action(&["session", "msgbox", "Hello"])?,
+            Action::SessionMsgBox {
+                message: "Hello".to_owned(),
+                title: None,
+                style: None,
+                timeout: None,
+                response: true,
+            }
+        );
+        assert_eq!(
+            parse_action(&[
+                "session",
+                "msgbox",
+                "--title",
+                "World",
+                "--style",
+                "yes-no",
+                "--timeout",
+                "30",
+                "--no-response",
+                "Hello",
+            ])?,
+            Action::SessionMsgBox {
+                message: "Hello".to_owned(),
+                title: Some("World".to_owned()),
+                style: Some(NowMessageBoxStyle::YES_NO),
+                timeout: Some(Duration::from_secs(30)),
+                response: false,
+            }
+        );
+        assert_eq!(
+            parse_action(&["session", "kbd", "next"])?,
+            Action::SessionKbd {
+                layout: KbdLayout::Next
+            }
+        );
+        assert_eq!(
+            parse_action(&["session", "kbd", "prev"])?,
+            Action::SessionKbd {
+                layout: KbdLayout::Prev
+            }
+        );
+        assert_eq!(
+            parse_action(&["session", "kbd", "00000409"])?,
+            Action::SessionKbd {
+                layout: KbdLayout::Specific("00000409".to_owned())
+            }
+        );
+
+        assert!(parse_action(&["session"]).is_err());
+        assert!(parse_action(&["session", "msgbox"]).is_err());
+        assert!(parse_action(&["session", "kbd"]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn system_commands() -> anyhow::Result<()> {
+        assert_eq!(parse_action(&["system", "info"])?, Action::SystemInfo);
+        assert_eq!(
+            parse_action(&["system", "shutdown"])?,
+            Action::SystemShutdown {
+                message: String::new(),
+                timeout: Duration::ZERO,
+                force: false,
+                reboot: false,
+            }
+        );
+        assert_eq!(
+            parse_action(&[
+                "system",
+                "shutdown",
+                "--message",
+                "Bye",
+                "--timeout",
+                "60",
+                "--force",
+                "--reboot",
+            ])?,
+            Action::SystemShutdown {
+                message: "Bye".to_owned(),
+                timeout: Duration::from_secs(60),
+                force: true,
+                reboot: true,
+            }
+        );
+        assert_eq!(
+            parse_action(&["system", "shutdown-abort"])?,
+            Action::SystemShutdownAbort
+        );
+        assert_eq!(parse_action(&["system", "ps"])?, Action::SystemProcessList);
+        assert_eq!(
+            parse_action(&["system", "kill", "--force", "1234"])?,
+            Action::SystemProcessTerminate { pid: 1234, force: true }
+        );
+        assert_eq!(
+            parse_action(&["system", "power", "--timeout", "10", "sleep"])?,
+            Action::SystemPower {
+                action: NowSystemPowerAction::SLEEP,
+                message: String::new(),
+                timeout: Duration::from_secs(10),
+                force: false,
+            }
+        );
+
+        assert!(parse_action(&["system"]).is_err());
+        assert!(parse_action(&["system", "kill", "init"]).is_err());
+        assert!(parse_action(&["system", "power"]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn file_commands() -> anyhow::Result<()> {
+        assert_eq!(
+            parse_action(&["file", "get", "--resume", "/remote", "local"])?,
+            Action::FileGet {
+                remote: "/remote".to_owned(),
+                local: PathBuf::from("local"),
+                resume: true,
+            }
+        );
+        assert_eq!(
+            parse_action(&["file", "put", "local", "/remote"])?,
+            Action::FilePut {
+                local: PathBuf::from("local"),
+                remote: "/remote".to_owned(),
+                resume: false,
+            }
+        );
+        assert_eq!(
+            parse_action(&["file", "stat", "/remote"])?,
+            Action::FileStat {
+                remote: "/remote".to_owned()
+            }
+        );
+        assert_eq!(
+            parse_action(&["file", "ls", "/remote"])?,
+            Action::FileList {
+                remote: "/remote".to_owned()
+            }
+        );
+        assert_eq!(
+            parse_action(&["file", "mkdir", "--parents", "/remote/dir"])?,
+            Action::FileMkdir {
+                remote: "/remote/dir".to_owned(),
+                parents: true,
+            }
+        );
+        assert_eq!(
+            parse_action(&["file", "rm", "--recursive", "/remote/dir"])?,
+            Action::FileDelete {
+                remote: "/remote/dir".to_owned(),
+                recursive: true,
+            }
+        );
+        assert_eq!(
+            parse_action(&["file", "mv", "--replace", "/remote/a", "/remote/b"])?,
+            Action::FileRename {
+                remote: "/remote/a".to_owned(),
+                new_remote: "/remote/b".to_owned(),
+                replace: true,
+            }
+        );
+
+        assert!(parse_action(&["file"]).is_err());
+        assert!(parse_action(&["file", "cp", "/remote/a", "/remote/b"]).is_err());
+        assert!(parse_action(&["file", "get", "/remote"]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn rdm_commands() -> anyhow::Result<()> {
+        assert_eq!(
+            parse_action(&["rdm", "start", "--jump", "--fullscreen", "--timeout", "45"])?,
+            Action::RdmStart {
+                jump: true,
+                maximized: false,
+                fullscreen: true,
+                timeout: Some(45),
+            }
+        );
+        assert_eq!(
+            parse_action(&["rdm", "session", "--session-id", SESSION_ID, CONNECTION_ID, "data"])?,
+            Action::RdmSession {
+                session_id: Some(SESSION_ID.parse()?),
+                connection_id: CONNECTION_ID.parse()?,
+                connection_data: "data".to_owned(),
+            }
+        );
+
+        assert!(parse_action(&["rdm"]).is_err());
+        assert!(parse_action(&["rdm", "session", "not-a-uuid", "data"]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn msg_box_style_names() -> anyhow::Result<()> {
+        let styles = [
+            ("ok", NowMessageBoxStyle::OK),
+            ("ok-cancel", NowMessageBoxStyle::OK_CANCEL),
+            ("abort-retry-ignore", NowMessageBoxStyle::ABORT_RETRY_IGNORE),
+            ("yes-no-cancel", NowMessageBoxStyle::YES_NO_CANCEL),
+            ("yes-no", NowMessageBoxStyle::YES_NO),
+            ("retry-cancel", NowMessageBoxStyle::RETRY_CANCEL),
+            ("cancel-try-continue", NowMessageBoxStyle::CANCEL_TRY_CONTINUE),
+            ("4", NowMessageBoxStyle::YES_NO),
+            ("1234", NowMessageBoxStyle::new(1234)),
+        ];
+
+        for (name, style) in styles {
+            assert_eq!(parse_msg_box_style(name)?, style, "{name}");
+        }
+
+        assert!(parse_msg_box_style("yes").is_err());
+        assert!(parse_msg_box_style("-1").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn power_action_names() -> anyhow::Result<()> {
+        let actions = [
+            ("shutdown", NowSystemPowerAction::SHUTDOWN),
+            ("reboot", NowSystemPowerAction::REBOOT),
+            ("sleep", NowSystemPowerAction::SLEEP),
+            ("hibernate", NowSystemPowerAction::HIBERNATE),
+            ("session-restart", NowSystemPowerAction::SESSION_RESTART),
+            ("2", NowSystemPowerAction::REBOOT),
+            ("100", NowSystemPowerAction::new(100)),
+        ];
+
+        for (name, action) in actions {
+            assert_eq!(parse_power_action(name)?, action, "{name}");
+        }
+
+        assert!(parse_power_action("suspend").is_err());
+        assert!(parse_power_action("").is_err());
+
+        Ok(())
+    }
+}
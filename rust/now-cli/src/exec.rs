@@ -0,0 +1,71 @@
+use anyhow::Context as _;
+use now_proto_client::{NowExecEvent, NowExecSession};
+use tokio::io::{AsyncRead, AsyncReadExt as _, AsyncWrite, AsyncWriteExt as _};
+
+const STDIN_BUFFER_SIZE: usize = 8 * 1024;
+
+/// Drives the exec session until it is finished and returns the remote process exit code.
+///
+/// Remote output is written to the local stdout/stderr as soon as it is received, local stdin is
+/// forwarded to the remote process if `forward_stdin` is set, otherwise remote stdin is closed
+/// right away. `Ctrl+C` sends the cancel request to the remote session.
+pub(crate) async fn run_session(mut session: NowExecSession, forward_stdin: bool) -> anyhow::Result<u32> {
+    let mut stdin = if forward_stdin {
+        Some(tokio::io::stdin())
+    } else {
+        session
+            .send_stdin(&[], true)
+            .await
+            .context("failed to close remote stdin")?;
+        None
+    };
+
+    let mut stdout = tokio::io::stdout();
+    let mut stderr = tokio::io::stderr();
+    let mut stdin_buffer = vec![0u8; STDIN_BUFFER_SIZE];
+    let mut cancelled = false;
+
+    loop {
+        tokio::select! {
+            read = read_input(&mut stdin, &mut stdin_buffer), if stdin.is_some() => {
+                let read = read.context("failed to read stdin")?;
+
+                session
+                    .send_stdin(&stdin_buffer[..read], read == 0)
+                    .await
+                    .context("failed to send stdin")?;
+
+                if read == 0 {
+                    stdin = None;
+                }
+            }
+            event = session.next_event() => match event {
+                Some(NowExecEvent::Started) => {}
+                Some(NowExecEvent::Stdout { data, .. }) => write_output(&mut stdout, &data).await?,
+                Some(NowExecEvent::Stderr { data, .. }) => write_output(&mut stderr, &data).await?,
+                Some(NowExecEvent::Finished(result)) => return result.context("remote execution failed"),
+                None => anyhow::bail!("NOW-PROTO channel closed before the session was finished"),
+            },
+            result = tokio::signal::ctrl_c(), if !cancelled => {
+                result.context("failed to listen for Ctrl+C")?;
+                cancelled = true;
+                session.cancel().await.context("failed to cancel remote session")?;
+            }
+        }
+    }
+}
+
+/// Reads local input. Pending forever once the input is closed.
+async fn read_input<R: AsyncRead + Unpin>(input: &mut Option<R>, buffer: &mut [u8]) -> std::io::Result<usize> {
+    match input {
+        Some(input) => input.read(buffer).await,
+        None => core::future::pending().await,
+    }
+}
+
+async fn write_output<W: AsyncWrite + Unpin>(output: &mut W, data: &[u8]) -> anyhow::Result<()> {
+    output.write_all(data).await.context("failed to write output")?;
+    output.flush().await.context("failed to write output")?;
+
+    Ok(())
+}
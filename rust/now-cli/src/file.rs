@@ -0,0 +1,147 @@
+use std::io::SeekFrom;
+use std::path::Path;
+
+use anyhow::Context as _;
+use now_proto_client::NowClient;
+use now_proto_pdu::{NowFileAttributes, NowFileEntryKind, NowFileOpenMode, NowFileOpenMsg};
+use tokio::fs::{File, OpenOptions};
+use tokio::io::{AsyncReadExt as _, AsyncSeekExt as _, AsyncWriteExt as _};
+
+const FILE_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Number of directory entries requested at once.
+const LIST_PAGE_SIZE: u32 = 256;
+
+/// Downloads the remote file. If `resume` is set, the download continues at the end of the
+/// existing local file.
+pub(crate) async fn get(client: &NowClient, remote: String, local: &Path, resume: bool) -> anyhow::Result<()> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(!resume)
+        .open(local)
+        .await
+        .with_context(|| format!("failed to open {}", local.display()))?;
+
+    let offset = if resume {
+        file.seek(SeekFrom::End(0)).await.context("failed to seek local file")?
+    } else {
+        0
+    };
+
+    let mut transfer = client
+        .file_open(|id| Ok(NowFileOpenMsg::new(id, NowFileOpenMode::Read, remote)?.with_offset(offset)))
+        .await
+        .context("failed to open remote file")?;
+
+    while let Some(data) = transfer.read_chunk().await.context("failed to read remote file")? {
+        file.write_all(&data).await.context("failed to write local file")?;
+    }
+
+    file.flush().await.context("failed to write local file")?;
+
+    transfer.close().await.context("failed to complete file transfer")
+}
+
+/// Uploads the local file. If `resume` is set, the upload continues at the end of the existing
+/// remote file.
+pub(crate) async fn put(client: &NowClient, local: &Path, remote: String, resume: bool) -> anyhow::Result<()> {
+    let mut file = File::open(local)
+        .await
+        .with_context(|| format!("failed to open {}", local.display()))?;
+
+    let mut transfer = client
+        .file_open(|id| {
+            let message = NowFileOpenMsg::new(id, NowFileOpenMode::Write, remote)?;
+
+            Ok(if resume { message.with_resume() } else { message })
+        })
+        .await
+        .context("failed to open remote file")?;
+
+    // Resumed uploads continue at the offset chosen by the server.
+    file.seek(SeekFrom::Start(transfer.offset()))
+        .await
+        .context("failed to seek local file")?;
+
+    let mut buffer = vec![0u8; FILE_CHUNK_SIZE];
+
+    loop {
+        let read = file.read(&mut buffer).await.context("failed to read local file")?;
+
+        if read == 0 {
+            break;
+        }
+
+        transfer
+            .write_chunk(&buffer[..read])
+            .await
+            .context("failed to write remote file")?;
+    }
+
+    transfer.close().await.context("failed to complete file transfer")
+}
+
+/// Prints the remote filesystem entry information.
+pub(crate) async fn stat(client: &NowClient, remote: &str) -> anyhow::Result<()> {
+    let info = client.file_stat(remote).await?;
+
+    println!("Kind: {}", entry_kind_name(info.kind()));
+    println!("Size: {}", info.file_size());
+    println!("Modified: {}", info.mtime());
+    println!("Attributes: {}", attributes_names(info.attributes()));
+
+    Ok(())
+}
+
+/// Prints the remote directory entries, requested page by page.
+pub(crate) async fn list(client: &NowClient, remote: &str) -> anyhow::Result<()> {
+    println!("{:<9} {:>12} {:>12} NAME", "KIND", "SIZE", "MODIFIED");
+
+    let mut start_index = 0;
+
+    loop {
+        let page = client.file_list_dir(remote, start_index, LIST_PAGE_SIZE).await?;
+
+        for entry in page.entries() {
+            println!(
+                "{:<9} {:>12} {:>12} {}",
+                entry_kind_name(entry.kind()),
+                entry.file_size(),
+                entry.mtime(),
+                entry.file_name(),
+            );
+        }
+
+        let count = u32::try_from(page.entries().len()).context("too many directory entries")?;
+
+        // Empty page with MORE flag set would never complete.
+        if !page.has_more() || count == 0 {
+            return Ok(());
+        }
+
+        start_index = start_index.saturating_add(count);
+    }
+}
+
+fn entry_kind_name(kind: NowFileEntryKind) -> String {
+    let name = match kind {
+        NowFileEntryKind::FILE => "file",
+        NowFileEntryKind::DIRECTORY => "directory",
+        NowFileEntryKind::SYMLINK => "symlink",
+        NowFileEntryKind::OTHER => "other",
+        other => return other.value().to_string(),
+    };
+
+    name.to_owned()
+}
+
+fn attributes_names(attributes: NowFileAttributes) -> String {
+    let names: Vec<_> = attributes.iter_names().map(|(name, _)| name).collect();
+
+    if names.is_empty() {
+        "-".to_owned()
+    } else {
+        names.join(" | ")
+    }
+}
@@ -0,0 +1,408 @@
+#![allow(clippy::print_stdout)]
+#![allow(clippy::print_stderr)]
+
+mod cli;
+mod exec;
+mod file;
+mod transport;
+
+use core::time::Duration;
+
+use anyhow::Context as _;
+use now_proto_client::{NowClient, NowClientConfig};
+use now_proto_pdu::{
+    NowExecProcessMsg, NowExecPwshMsg, NowExecRunMsg, NowExecShellMsg, NowMsgBoxResponse, NowRdmAppStartMsg,
+    NowRdmSessionStartMsg, NowSessionMsgBoxReqMsg, NowSessionSetKbdLayoutMsg, NowSystemPowerActionMsg,
+    NowSystemShutdownMsg,
+};
+use tracing_subscriber::EnvFilter;
+
+use crate::cli::{Action, ExecOptions, KbdLayout};
+
+/// Maximum time to wait for the graceful NOW-PROTO channel shutdown.
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let args = match cli::parse_args() {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            cli::print_help();
+            return Ok(());
+        }
+        Err(error) => {
+            cli::print_help();
+            return Err(error);
+        }
+    };
+
+    // Stdout is reserved for the command output.
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("warn")))
+        .with_writer(std::io::stderr)
+        .init();
+
+    let transport = tokio::time::timeout(args.connect_timeout, transport::connect(&args.pipe))
+        .await
+        .context("connection timed out")?
+        .with_context(|| format!("failed to connect to {}", args.pipe.display()))?;
+
+    let config = NowClientConfig::default().with_connect_timeout(args.connect_timeout);
+    let client = NowClient::connect_with_config(transport, config)
+        .await
+        .context("NOW-PROTO negotiation failed")?;
+
+    let exit_code = run_action(&client, args.action).await?;
+
+    // Agent releases the channel resources on NOW_CHANNEL_CLOSE instead of reporting the dropped
+    // connection.
+    let close_result = tokio::time::timeout(CLOSE_TIMEOUT, client.close())
+        .await
+        .context("timed out closing NOW-PROTO channel")
+        .and_then(|result| result.context("failed to close NOW-PROTO channel"));
+
+    match exit_code {
+        Some(exit_code) => {
+            // Remote exit code is reported even if the channel could not be closed cleanly.
+            if let Err(error) = close_result {
+                eprintln!("warning: {error:#}");
+            }
+
+            // Local stdin reader is still blocked on the runtime thread pool, therefore the process
+            // is terminated without waiting for the runtime shutdown.
+            std::process::exit(i32::from_ne_bytes(exit_code.to_ne_bytes()));
+        }
+        None => close_result?,
+    }
+
+    Ok(())
+}
+
+/// Performs the requested action. Returns the remote process exit code for exec sessions.
+async fn run_action(client: &NowClient, action: Action) -> anyhow::Result<Option<u32>> {
+    match action {
+        Action::ExecShell {
+            command,
+            shell,
+            options,
+        } => {
+            let session = client
+                .exec_shell(|id| {
+                    let mut message = NowExecShellMsg::new(id, command)?;
+
+                    if let Some(shell) = shell {
+                        message = message.with_shell(shell)?;
+                    }
+
+                    if let Some(directory) = &options.directory {
+                        message = message.with_directory(directory.clone())?;
+                    }
+
+                    Ok(if options.detached {
+                        message.with_detached()
+                    } else {
+                        message.with_io_redirection()
+                    })
+                })
+                .await?;
+
+            run_session(session, &options).await
+        }
+        Action::ExecProcess {
+            filename,
+            parameters,
+            options,
+        } => {
+            let session = client
+                .exec_process(|id| {
+                    let mut message = NowExecProcessMsg::new(id, filename)?;
+
+                    if let Some(parameters) = parameters {
+                        message = message.with_parameters(parameters)?;
+                    }
+
+                    if let Some(directory) = &options.directory {
+                        message = message.with_directory(directory.clone())?;
+                    }
+
+                    Ok(if options.detached {
+                        message.with_detached()
+                    } else {
+                        message.with_io_redirection()
+                    })
+                })
+                .await?;
+
+            run_session(session, &options).await
+        }
+        Action::ExecRun { command, directory } => {
+            client
+                .exec_run(|id| {
+                    let message = NowExecRunMsg::new(id, command)?;
+
+                    match directory {
+                        Some(directory) => message.with_directory(directory),
+                        None => Ok(message),
+                    }
+                })
+                .await?;
+
+            Ok(None)
+        }
+        Action::ExecPwsh { command, options } => {
+            let session = client
+                .exec_pwsh(|id| {
+                    let mut message = NowExecPwshMsg::new(id, command)?;
+
+                    if let Some(directory) = &options.directory {
+                        message = message.with_directory(directory.clone())?;
+                    }
+
+                    Ok(if options.detached {
+                        message.with_detached()
+                    } else {
+                        message.with_io_redirection()
+                    })
+                })
+                .await?;
+
+            run_session(session, &options).await
+        }
+        Action::SessionLock => {
+            client.session_lock().await?;
+            Ok(None)
+        }
+        Action::SessionLogoff => {
+            client.session_logoff().await?;
+            Ok(None)
+        }
+        Action::SessionMsgBox {
+            message,
+            title,
+            style,
+            timeout,
+            response,
+        } => {
+            let build = |id| {
+                let mut request = NowSessionMsgBoxReqMsg::new(id, message)?;
+
+                if let Some(title) = title {
+                    request = request.with_title(title)?;
+                }
+
+                if let Some(style) = style {
+                    request = request.with_style(style);
+                }
+
+                if let Some(timeout) = timeout {
+                    request = request.with_timeout(timeout)?;
+                }
+
+                Ok(request)
+            };
+
+            if response {
+                let response = client.msg_box(build).await?;
+                println!("{}", msg_box_response_name(response));
+            } else {
+                client.msg_box_no_response(build).await?;
+            }
+
+            Ok(None)
+        }
+        Action::SessionKbd { layout } => {
+            let message = match layout {
+                KbdLayout::Next => NowSessionSetKbdLayoutMsg::new_next(),
+                KbdLayout::Prev => NowSessionSetKbdLayoutMsg::new_prev(),
+                KbdLayout::Specific(layout) => NowSessionSetKbdLayoutMsg::new_specific(layout)?,
+            };
+
+            client.session_set_kbd_layout(message).await?;
+            Ok(None)
+        }
+        Action::SystemInfo => {
+            let info = client.system_info().await?;
+
+            let fields = [
+                ("OS name", info.os_name().map(str::to_owned)),
+                ("OS version", info.os_version().map(str::to_owned)),
+                ("Hostname", info.hostname().map(str::to_owned)),
+                ("Architecture", info.architecture().map(str::to_owned)),
+                ("Uptime", info.uptime().map(|uptime| format!("{}s", uptime.as_secs()))),
+                ("Logged on user", info.logged_on_user().map(str::to_owned)),
+                ("Agent version", info.agent_version().map(str::to_owned)),
+            ];
+
+            for (name, value) in fields {
+                if let Some(value) = value {
+                    println!("{name}: {value}");
+                }
+            }
+
+            Ok(None)
+        }
+        Action::SystemShutdown {
+            message,
+            timeout,
+            force,
+            reboot,
+        } => {
+            let mut message = NowSystemShutdownMsg::new(timeout, message)?;
+
+            if force {
+                message = message.with_force_shutdown();
+            }
+
+            if reboot {
+                message = message.with_reboot();
+            }
+
+            client.system_shutdown(message).await?;
+            Ok(None)
+        }
+        Action::SystemProcessList => {
+            let processes = client.system_process_list().await?;
+
+            println!("{:>8} {:>8} {:>8} {:<16} NAME", "PID", "PPID", "SESSION", "USER");
+
+            for process in processes {
+                println!(
+                    "{:>8} {:>8} {:>8} {:<16} {}",
+                    process.pid(),
+                    process.parent_pid(),
+                    process.session_id(),
+                    process.user(),
+                    process.process_name(),
+                );
+            }
+
+            Ok(None)
+        }
+        Action::SystemProcessTerminate { pid, force } => {
+            client.system_process_terminate(pid, force).await?;
+            Ok(None)
+        }
+        Action::SystemShutdownAbort => {
+            client.system_shutdown_abort().await?;
+            Ok(None)
+        }
+        Action::SystemPower {
+            action,
+            message,
+            timeout,
+            force,
+        } => {
+            client
+                .system_power_action(|id| {
+                    let mut request = NowSystemPowerActionMsg::new(id, action)
+                        .with_timeout(timeout)?
+                        .with_message(message)?;
+
+                    if force {
+                        request = request.with_force();
+                    }
+
+                    Ok(request)
+                })
+                .await?;
+
+            Ok(None)
+        }
+        Action::FileGet { remote, local, resume } => {
+            file::get(client, remote, &local, resume).await?;
+            Ok(None)
+        }
+        Action::FilePut { local, remote, resume } => {
+            file::put(client, &local, remote, resume).await?;
+            Ok(None)
+        }
+        Action::FileStat { remote } => {
+            file::stat(client, &remote).await?;
+            Ok(None)
+        }
+        Action::FileList { remote } => {
+            file::list(client, &remote).await?;
+            Ok(None)
+        }
+        Action::FileMkdir { remote, parents } => {
+            client.file_mkdir(&remote, parents).await?;
+            Ok(None)
+        }
+        Action::FileDelete { remote, recursive } => {
+            client.file_delete(&remote, recursive).await?;
+            Ok(None)
+        }
+        Action::FileRename {
+            remote,
+            new_remote,
+            replace,
+        } => {
+            client.file_rename(&remote, &new_remote, replace).await?;
+            Ok(None)
+        }
+        Action::RdmStart {
+            jump,
+            maximized,
+            fullscreen,
+            timeout,
+        } => {
+            let mut message = NowRdmAppStartMsg::default();
+
+            if jump {
+                message = message.with_jump_mode();
+            }
+
+            if maximized {
+                message = message.with_maximized();
+            }
+
+            if fullscreen {
+                message = message.with_fullscreen();
+            }
+
+            if let Some(timeout) = timeout {
+                message = message.with_timeout(timeout);
+            }
+
+            client.rdm_app_start(message).await?;
+            Ok(None)
+        }
+        Action::RdmSession {
+            session_id,
+            connection_id,
+            connection_data,
+        } => {
+            let session_id = session_id.unwrap_or_else(uuid::Uuid::new_v4);
+            let message = NowRdmSessionStartMsg::new(session_id, connection_id, connection_data)?;
+
+            client.rdm_session_start(message).await?;
+            println!("{session_id}");
+            Ok(None)
+        }
+    }
+}
+
+async fn run_session(session: now_proto_client::NowExecSession, options: &ExecOptions) -> anyhow::Result<Option<u32>> {
+    // Detached sessions have no IO redirection.
+    let forward_stdin = options.forward_stdin && !options.detached;
+
+    exec::run_session(session, forward_stdin).await.map(Some)
+}
+
+fn msg_box_response_name(response: NowMsgBoxResponse) -> String {
+    let name = match response {
+        NowMsgBoxResponse::OK => "OK",
+        NowMsgBoxResponse::CANCEL => "CANCEL",
+        NowMsgBoxResponse::ABORT => "ABORT",
+        NowMsgBoxResponse::RETRY => "RETRY",
+        NowMsgBoxResponse::IGNORE => "IGNORE",
+        NowMsgBoxResponse::YES => "YES",
+        NowMsgBoxResponse::NO => "NO",
+        NowMsgBoxResponse::TRY_AGAIN => "TRY_AGAIN",
+        NowMsgBoxResponse::CONTINUE => "CONTINUE",
+        NowMsgBoxResponse::TIMEOUT => "TIMEOUT",
+        other => return other.value().to_string(),
+    };
+
+    name.to_owned()
+}
@@ -0,0 +1,30 @@
+use std::io;
+use std::path::Path;
+
+use now_proto_client::NowTransport;
+
+/// Connects to the NOW-PROTO server listening on the Unix domain socket.
+#[cfg(unix)]
+pub(crate) async fn connect(path: &Path) -> io::Result<impl NowTransport> {
+    tokio::net::UnixStream::connect(path).await
+}
+
+/// Connects to the NOW-PROTO server listening on the named pipe. Waits for the pipe instance to
+/// become available if all instances are busy.
+#[cfg(windows)]
+pub(crate) async fn connect(path: &Path) -> io::Result<impl NowTransport> {
+    use core::time::Duration;
+
+    use tokio::net::windows::named_pipe::ClientOptions;
+
+    const ERROR_PIPE_BUSY: i32 = 231;
+
+    loop {
+        match ClientOptions::new().open(path) {
+            Err(error) if error.raw_os_error() == Some(ERROR_PIPE_BUSY) => {}
+            result => return result,
+        }
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
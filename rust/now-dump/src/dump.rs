@@ -0,0 +1,461 @@
+use core::fmt::{self, Write as _};
+
+use now_proto_pdu::ironrdp_core::{Decode as _, ReadCursor};
+use now_proto_pdu::{NowMessage, NowMessageFramer};
+
+use crate::layout::{self, Field, MessageLayout};
+
+/// NOW_HEADER size.
+const HEADER_SIZE: usize = 8;
+
+/// Number of bytes per line in buffer hex dumps.
+const HEX_LINE_SIZE: usize = 16;
+
+/// Statistics of the decoded capture.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NowDumpSummary {
+    /// Number of complete frames.
+    pub frames: usize,
+    /// Number of complete frames which could not be decoded.
+    pub undecodable: usize,
+    /// Number of bytes at the end of the capture which do not form a complete frame.
+    pub truncated: usize,
+}
+
+impl NowDumpSummary {
+    /// Returns `true` if all frames in the capture are complete and decodable.
+    pub fn is_clean(&self) -> bool {
+        self.undecodable == 0 && self.truncated == 0
+    }
+}
+
+impl fmt::Display for NowDumpSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} frames, {} undecodable, {} truncated bytes",
+            self.frames, self.undecodable, self.truncated
+        )
+    }
+}
+
+/// Splits the raw NOW-PROTO capture into frames and appends their decoded representation to
+/// `out`.
+///
+/// Each message is printed as a tree of fields prefixed with their byte offset in the capture.
+/// Frames which can't be decoded are marked with the `DecodeError` raised by `now-proto-pdu`.
+pub fn dump_capture(data: &[u8], out: &mut String) -> NowDumpSummary {
+    let mut printer = Printer { out };
+    let mut summary = NowDumpSummary::default();
+
+    // The whole capture is already in memory, therefore frame size is not limited.
+    let mut framer = NowMessageFramer::new().with_max_frame_size(usize::MAX);
+    let mut offset = 0;
+
+    if framer.push(data).is_ok() {
+        while let Ok(Some(frame)) = framer.next_frame() {
+            if dump_frame(&mut printer, summary.frames, offset, frame).is_err() {
+                summary.undecodable += 1;
+            }
+
+            summary.frames += 1;
+            offset += frame.len();
+        }
+    }
+
+    let rest = &data[offset..];
+    if !rest.is_empty() {
+        summary.truncated = rest.len();
+        dump_truncated(&mut printer, summary.frames, offset, rest);
+    }
+
+    printer.line(data.len(), 0, format_args!("-- {summary}"));
+
+    summary
+}
+
+struct Printer<'a> {
+    out: &'a mut String,
+}
+
+impl Printer<'_> {
+    fn line(&mut self, offset: usize, depth: usize, text: fmt::Arguments<'_>) {
+        writeln!(self.out, "{offset:08x}  {:indent$}{text}", "", indent = depth * 2)
+            .expect("writing to String never fails");
+    }
+
+    fn error(&mut self, offset: usize, depth: usize, error: impl fmt::Display) {
+        self.line(offset, depth, format_args!("!! {error}"));
+    }
+
+    fn hex(&mut self, offset: usize, depth: usize, data: &[u8]) {
+        for (idx, chunk) in data.chunks(HEX_LINE_SIZE).enumerate() {
+            let mut hex = String::with_capacity(HEX_LINE_SIZE * 3);
+            for byte in chunk {
+                write!(hex, "{byte:02x} ").expect("writing to String never fails");
+            }
+
+            let ascii: String = chunk
+                .iter()
+                .map(|&byte| {
+                    if byte.is_ascii_graphic() || byte == b' ' {
+                        char::from(byte)
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+
+            self.line(
+                offset + idx * HEX_LINE_SIZE,
+                depth,
+                format_args!("{hex:width$} {ascii}", width = HEX_LINE_SIZE * 3),
+            );
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct RawHeader {
+    size: u32,
+    class: u8,
+    kind: u8,
+    flags: u16,
+}
+
+impl RawHeader {
+    fn parse(data: &[u8]) -> Option<Self> {
+        let mut reader = Reader::new(data, 0);
+
+        Some(Self {
+            size: reader.read_u32().ok()?,
+            class: reader.read_u8().ok()?,
+            kind: reader.read_u8().ok()?,
+            flags: reader.read_u16().ok()?,
+        })
+    }
+}
+
+/// Prints the frame. Returns an error if the frame could not be decoded.
+fn dump_frame(printer: &mut Printer<'_>, index: usize, offset: usize, frame: &[u8]) -> Result<(), ()> {
+    let header = RawHeader::parse(frame).expect("framer always yields complete header");
+    let layout = layout::message_layout(header.class, header.kind);
+    let name = layout.map_or("<unknown message>", |layout| layout.name);
+
+    printer.line(offset, 0, format_args!("#{index} {name} ({} bytes)", frame.len()));
+
+    dump_header(printer, offset, header, layout);
+
+    let decode_result = NowMessage::decode(&mut ReadCursor::new(frame)).map(|_| ());
+    if let Err(error) = &decode_result {
+        printer.error(offset, 1, format_args!("undecodable frame: {error}"));
+    }
+
+    let body_offset = offset + HEADER_SIZE;
+    let body = &frame[HEADER_SIZE..];
+
+    if body.is_empty() && layout.is_some_and(|layout| layout.fields.is_empty()) {
+        return decode_result.map_err(|_| ());
+    }
+
+    printer.line(body_offset, 1, format_args!("body"));
+
+    let mut reader = Reader::new(body, body_offset);
+
+    if let Some(layout) = layout {
+        for field in layout.fields {
+            if let Err(error) = dump_field(printer, &mut reader, *field) {
+                printer.error(error.offset, 2, error);
+                break;
+            }
+        }
+    }
+
+    let rest_offset = reader.offset();
+    let rest = reader.read_rest();
+    if !rest.is_empty() {
+        let label = if layout.is_some() { "trailing data" } else { "raw data" };
+        printer.line(rest_offset, 2, format_args!("{label}: {} bytes", rest.len()));
+        printer.hex(rest_offset, 3, rest);
+    }
+
+    decode_result.map_err(|_| ())
+}
+
+fn dump_truncated(printer: &mut Printer<'_>, index: usize, offset: usize, data: &[u8]) {
+    match RawHeader::parse(data) {
+        Some(header) => {
+            let layout = layout::message_layout(header.class, header.kind);
+            let name = layout.map_or("<unknown message>", |layout| layout.name);
+            let expected = u64::from(header.size) + HEADER_SIZE as u64;
+
+            printer.line(offset, 0, format_args!("#{index} {name} (truncated)"));
+            dump_header(printer, offset, header, layout);
+            printer.error(
+                offset,
+                1,
+                format_args!("truncated frame: {} of {expected} bytes", data.len()),
+            );
+        }
+        None => {
+            printer.line(offset, 0, format_args!("#{index} (truncated)"));
+            printer.error(offset, 1, format_args!("incomplete header: {} bytes", data.len()));
+        }
+    }
+
+    printer.hex(offset, 1, data);
+}
+
+fn dump_header(printer: &mut Printer<'_>, offset: usize, header: RawHeader, layout: Option<&MessageLayout>) {
+    printer.line(offset, 1, format_args!("header"));
+    printer.line(offset, 2, format_args!("msgSize: {}", header.size));
+
+    match layout::class_name(header.class) {
+        Some(name) => printer.line(offset + 4, 2, format_args!("msgClass: 0x{:02x} ({name})", header.class)),
+        None => printer.line(offset + 4, 2, format_args!("msgClass: 0x{:02x}", header.class)),
+    }
+
+    match layout {
+        Some(layout) => printer.line(
+            offset + 5,
+            2,
+            format_args!("msgType: 0x{:02x} ({}_ID)", header.kind, layout.name),
+        ),
+        None => printer.line(offset + 5, 2, format_args!("msgType: 0x{:02x}", header.kind)),
+    }
+
+    let flag_names = layout.and_then(|layout| layout.flags).map(|flags| flags(header.flags));
+    printer.line(
+        offset + 6,
+        2,
+        format_args!("msgFlags: {}", FlagsValue(header.flags, flag_names)),
+    );
+}
+
+fn dump_field(printer: &mut Printer<'_>, reader: &mut Reader<'_>, field: Field) -> Result<(), WalkError> {
+    let offset = reader.offset();
+
+    match field {
+        Field::U16(name) => {
+            let value = reader.read_u16()?;
+            printer.line(offset, 2, format_args!("{name}: {value}"));
+        }
+        Field::U32(name) => {
+            let value = reader.read_u32()?;
+            printer.line(offset, 2, format_args!("{name}: {value}"));
+        }
+        Field::U64(name) => {
+            let value = reader.read_u64()?;
+            printer.line(offset, 2, format_args!("{name}: {value}"));
+        }
+        Field::Flags16(name, flags) => {
+            let value = reader.read_u16()?;
+            printer.line(
+                offset,
+                2,
+                format_args!("{name}: {}", FlagsValue(value, Some(flags(value)))),
+            );
+        }
+        Field::OptionalFlags16(name, flags) => {
+            if reader.is_empty() {
+                printer.line(offset, 2, format_args!("{name}: <absent>"));
+            } else {
+                let value = reader.read_u16()?;
+                printer.line(
+                    offset,
+                    2,
+                    format_args!("{name}: {}", FlagsValue(value, Some(flags(value)))),
+                );
+            }
+        }
+        Field::Flags32(name, flags) => {
+            let value = reader.read_u32()?;
+            printer.line(
+                offset,
+                2,
+                format_args!("{name}: {}", FlagsValue(value, Some(flags(value)))),
+            );
+        }
+        Field::VarStr(name) | Field::Guid(name) => dump_var_str(printer, reader, name, 2)?,
+        Field::OptionalVarStr(name) => {
+            if reader.is_empty() {
+                printer.line(offset, 2, format_args!("{name}: <absent>"));
+            } else {
+                dump_var_str(printer, reader, name, 2)?;
+            }
+        }
+        Field::VarBuf(name) => {
+            let len = reader.read_var_u32()?;
+            let data_offset = reader.offset();
+            let data = reader.read_slice(len)?;
+
+            printer.line(offset, 2, format_args!("{name}: {len} bytes"));
+            printer.hex(data_offset, 3, data);
+        }
+        Field::Status(name) => {
+            printer.line(offset, 2, format_args!("{name}"));
+
+            let flags = reader.read_u16()?;
+            let flag_names = layout::status_flags(flags);
+            printer.line(
+                offset,
+                3,
+                format_args!("flags: {}", FlagsValue(flags, Some(flag_names))),
+            );
+
+            let kind = reader.read_u16()?;
+            match layout::status_kind_name(kind) {
+                Some(kind_name) => printer.line(offset + 2, 3, format_args!("kind: {kind} ({kind_name})")),
+                None => printer.line(offset + 2, 3, format_args!("kind: {kind}")),
+            }
+
+            let code = reader.read_u32()?;
+            match layout::proto_error_name(code).filter(|_| kind == 0x0001) {
+                Some(code_name) => printer.line(offset + 4, 3, format_args!("code: {code} ({code_name})")),
+                None => printer.line(offset + 4, 3, format_args!("code: {code}")),
+            }
+
+            dump_var_str(printer, reader, "message", 3)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn dump_var_str(printer: &mut Printer<'_>, reader: &mut Reader<'_>, name: &str, depth: usize) -> Result<(), WalkError> {
+    let offset = reader.offset();
+    let len = reader.read_var_u32()?;
+    let bytes = reader.read_slice(len)?;
+    let null_offset = reader.offset();
+    let null = reader.read_u8()?;
+
+    match core::str::from_utf8(bytes) {
+        Ok(value) => printer.line(offset, depth, format_args!("{name}: {value:?} (len {len})")),
+        Err(error) => {
+            printer.line(offset, depth, format_args!("{name}: <invalid UTF-8> (len {len})"));
+            printer.error(offset, depth + 1, error);
+        }
+    }
+
+    if null != 0 {
+        printer.error(
+            null_offset,
+            depth + 1,
+            format_args!("missing null terminator (0x{null:02x})"),
+        );
+    }
+
+    Ok(())
+}
+
+/// Flags value followed by the names of the set flags.
+struct FlagsValue<T>(T, Option<String>);
+
+impl<T: fmt::LowerHex> fmt::Display for FlagsValue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = size_of::<T>() * 2;
+        write!(f, "0x{:0width$x}", self.0)?;
+
+        match &self.1 {
+            Some(names) if !names.is_empty() => write!(f, " [{names}]"),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Layout walk failure, reported at the offset of the field which could not be read.
+#[derive(Debug)]
+struct WalkError {
+    offset: usize,
+    needed: usize,
+    available: usize,
+}
+
+impl fmt::Display for WalkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "not enough bytes: {} needed, {} available",
+            self.needed, self.available
+        )
+    }
+}
+
+/// Bounds-checked little-endian reader which tracks absolute offsets in the capture.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    base: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8], base: usize) -> Self {
+        Self { data, pos: 0, base }
+    }
+
+    fn offset(&self) -> usize {
+        self.base + self.pos
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos == self.data.len()
+    }
+
+    fn read_slice(&mut self, len: usize) -> Result<&'a [u8], WalkError> {
+        let available = self.data.len() - self.pos;
+
+        if len > available {
+            return Err(WalkError {
+                offset: self.offset(),
+                needed: len,
+                available,
+            });
+        }
+
+        let slice = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+
+        Ok(slice)
+    }
+
+    fn read_rest(&mut self) -> &'a [u8] {
+        let rest = &self.data[self.pos..];
+        self.pos = self.data.len();
+        rest
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], WalkError> {
+        let slice = self.read_slice(N)?;
+        Ok(slice.try_into().expect("slice has exactly N bytes"))
+    }
+
+    fn read_u8(&mut self) -> Result<u8, WalkError> {
+        self.read_array::<1>().map(|[byte]| byte)
+    }
+
+    fn read_u16(&mut self) -> Result<u16, WalkError> {
+        self.read_array().map(u16::from_le_bytes)
+    }
+
+    fn read_u32(&mut self) -> Result<u32, WalkError> {
+        self.read_array().map(u32::from_le_bytes)
+    }
+
+    fn read_u64(&mut self) -> Result<u64, WalkError> {
+        self.read_array().map(u64::from_le_bytes)
+    }
+
+    /// Reads NOW_VARU32: the top 2 bits of the first byte contain the number of extra bytes,
+    /// the value is encoded in big-endian order.
+    fn read_var_u32(&mut self) -> Result<usize, WalkError> {
+        let first = self.read_u8()?;
+        let extra = usize::from(first >> 6);
+
+        let value = self
+            .read_slice(extra)?
+            .iter()
+            .fold(u32::from(first & 0x3F), |value, byte| (value << 8) | u32::from(*byte));
+
+        Ok(usize::try_from(value).expect("usize is at least 32 bits"))
+    }
+}
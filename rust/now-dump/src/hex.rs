@@ -0,0 +1,50 @@
+use anyhow::Context as _;
+
+/// Returns `true` if the capture looks like a textual hex dump rather than raw binary data.
+///
+/// Binary NOW-PROTO captures start with the little-endian `msgSize` field, which contains
+/// non-printable bytes for any realistic message size, therefore they never parse as hex.
+pub fn looks_like_hex(data: &[u8]) -> bool {
+    core::str::from_utf8(data).is_ok_and(|text| parse_hex(text).is_ok_and(|data| !data.is_empty()))
+}
+
+/// Parses textual hex dump.
+///
+/// Bytes may be separated by whitespace or commas and prefixed with `0x`. Line prefixes ending
+/// with a colon (e.g. `00000010:`) are treated as offsets and skipped, and everything after `#`
+/// is treated as a comment.
+pub fn parse_hex(text: &str) -> anyhow::Result<Vec<u8>> {
+    let mut data = Vec::new();
+
+    for (line_idx, line) in text.lines().enumerate() {
+        let line = line.split_once('#').map_or(line, |(line, _comment)| line);
+
+        for token in line.split(|c: char| c.is_ascii_whitespace() || c == ',') {
+            if token.is_empty() || token.ends_with(':') {
+                continue;
+            }
+
+            let digits = token
+                .strip_prefix("0x")
+                .or_else(|| token.strip_prefix("0X"))
+                .unwrap_or(token);
+
+            parse_hex_digits(digits, &mut data)
+                .with_context(|| format!("line {}: invalid hex value `{token}`", line_idx + 1))?;
+        }
+    }
+
+    Ok(data)
+}
+
+fn parse_hex_digits(digits: &str, data: &mut Vec<u8>) -> anyhow::Result<()> {
+    anyhow::ensure!(!digits.is_empty(), "empty value");
+    anyhow::ensure!(digits.len().is_multiple_of(2), "odd number of digits");
+
+    for idx in (0..digits.len()).step_by(2) {
+        let pair = digits.get(idx..idx + 2).context("non-ASCII character")?;
+        data.push(u8::from_str_radix(pair, 16)?);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,632 @@
+//! Wire layouts of NOW-PROTO message bodies, as decoded by `now-proto-pdu`.
+
+use core::fmt;
+
+use bitflags::{Bits as _, Flags};
+use now_proto_pdu::{
+    NowChannelCapsetFlags, NowExecBatchFlags, NowExecCapsetFlags, NowExecDataFlags, NowExecProcessFlags,
+    NowExecRunFlags, NowExecShellFlags, NowExecWinPsFlags, NowFileAttributes, NowFileCapsetFlags, NowFileChunkFlags,
+    NowFileDeleteFlags, NowFileListDirRspFlags, NowFileMkdirFlags, NowFileOpenFlags, NowFileRenameFlags,
+    NowRdmLaunchFlags, NowRdmSyncFlags, NowSessionCapsetFlags, NowSessionMessageBoxFlags, NowSessionSetKbdLayoutFlags,
+    NowStatusFlags, NowSystemCapsetFlags, NowSystemInfoFlags, NowSystemPowerActionFlags,
+    NowSystemProcessTerminateFlags, NowSystemShutdownFlags, WindowRecEventFlags, WindowRecStartFlags,
+};
+
+pub(crate) type Flags16 = fn(u16) -> String;
+pub(crate) type Flags32 = fn(u32) -> String;
+
+/// Message body field.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Field {
+    U16(&'static str),
+    U32(&'static str),
+    U64(&'static str),
+    Flags16(&'static str, Flags16),
+    /// 16-bit flags field which is only present if announced by the message flags.
+    OptionalFlags16(&'static str, Flags16),
+    Flags32(&'static str, Flags32),
+    /// `NOW_VARSTR` structure.
+    VarStr(&'static str),
+    /// `NOW_VARSTR` structure which is absent in messages sent by older protocol versions.
+    OptionalVarStr(&'static str),
+    /// `NOW_VARBUF` structure.
+    VarBuf(&'static str),
+    /// `NOW_GUID` structure (`NOW_VARSTR` containing the hyphenated GUID).
+    Guid(&'static str),
+    /// `NOW_STATUS` structure.
+    Status(&'static str),
+}
+
+#[derive(Debug)]
+pub(crate) struct MessageLayout {
+    pub(crate) class: u8,
+    pub(crate) kind: u8,
+    pub(crate) name: &'static str,
+    /// Names of the `msgFlags` header field bits, `None` if the message defines no flags.
+    pub(crate) flags: Option<Flags16>,
+    pub(crate) fields: &'static [Field],
+}
+
+pub(crate) fn message_layout(class: u8, kind: u8) -> Option<&'static MessageLayout> {
+    LAYOUTS
+        .iter()
+        .find(|layout| layout.class == class && layout.kind == kind)
+}
+
+pub(crate) fn class_name(class: u8) -> Option<&'static str> {
+    let name = match class {
+        0x10 => "NOW_CHANNEL_MSG_CLASS_ID",
+        0x11 => "NOW_SYSTEM_MSG_CLASS_ID",
+        0x12 => "NOW_SESSION_MSG_CLASS_ID",
+        0x13 => "NOW_EXEC_MSG_CLASS_ID",
+        0x14 => "NOW_RDM_MSG_CLASS_ID",
+        0x15 => "NOW_FILE_MSG_CLASS_ID",
+        _ => return None,
+    };
+
+    Some(name)
+}
+
+pub(crate) fn status_kind_name(kind: u16) -> Option<&'static str> {
+    let name = match kind {
+        0x0000 => "NOW_STATUS_ERROR_KIND_GENERIC",
+        0x0001 => "NOW_STATUS_ERROR_KIND_NOW",
+        0x0002 => "NOW_STATUS_ERROR_KIND_WINAPI",
+        0x0003 => "NOW_STATUS_ERROR_KIND_UNIX",
+        _ => return None,
+    };
+
+    Some(name)
+}
+
+/// Returns name of the `NOW_STATUS_ERROR_KIND_NOW` error code.
+pub(crate) fn proto_error_name(code: u32) -> Option<&'static str> {
+    let name = match code {
+        0x0001 => "NOW_CODE_IN_USE",
+        0x0002 => "NOW_CODE_INVALID_REQUEST",
+        0x0003 => "NOW_CODE_ABORTED",
+        0x0004 => "NOW_CODE_NOT_FOUND",
+        0x0005 => "NOW_CODE_ACCESS_DENIED",
+        0x0006 => "NOW_CODE_INTERNAL",
+        0x0007 => "NOW_CODE_NOT_IMPLEMENTED",
+        0x0008 => "NOW_CODE_PROTOCOL_VERSION",
+        _ => return None,
+    };
+
+    Some(name)
+}
+
+pub(crate) fn status_flags(bits: u16) -> String {
+    flags16::<NowStatusFlags>(bits)
+}
+
+fn flags16<F: Flags<Bits = u16>>(bits: u16) -> String {
+    flag_names(F::from_bits_retain(bits))
+}
+
+fn flags32<F: Flags<Bits = u32>>(bits: u32) -> String {
+    flag_names(F::from_bits_retain(bits))
+}
+
+/// Formats set flags as `NAME | NAME | 0xUNKNOWN`.
+fn flag_names<F>(flags: F) -> String
+where
+    F: Flags,
+    F::Bits: fmt::LowerHex,
+{
+    let mut names: Vec<String> = flags.iter_names().map(|(name, _)| name.to_owned()).collect();
+
+    let unknown = flags.bits() & !F::all().bits();
+    if unknown != F::Bits::EMPTY {
+        names.push(format!("0x{unknown:x}"));
+    }
+
+    names.join(" | ")
+}
+
+const EXEC_WINPS_FIELDS: &[Field] = &[
+    Field::U32("sessionId"),
+    Field::VarStr("command"),
+    Field::VarStr("directory"),
+    Field::VarStr("executionPolicy"),
+    Field::VarStr("configurationName"),
+];
+
+static LAYOUTS: &[MessageLayout] = &[
+    // Channel
+    MessageLayout {
+        class: 0x10,
+        kind: 0x01,
+        name: "NOW_CHANNEL_CAPSET_MSG",
+        flags: Some(flags16::<NowChannelCapsetFlags>),
+        fields: &[
+            Field::U16("versionMajor"),
+            Field::U16("versionMinor"),
+            Field::Flags16("systemCapset", flags16::<NowSystemCapsetFlags>),
+            Field::Flags16("sessionCapset", flags16::<NowSessionCapsetFlags>),
+            Field::Flags16("execCapset", flags16::<NowExecCapsetFlags>),
+            Field::U32("heartbeatInterval"),
+            Field::OptionalFlags16("fileCapset", flags16::<NowFileCapsetFlags>),
+        ],
+    },
+    MessageLayout {
+        class: 0x10,
+        kind: 0x02,
+        name: "NOW_CHANNEL_HEARTBEAT_MSG",
+        flags: None,
+        fields: &[],
+    },
+    MessageLayout {
+        class: 0x10,
+        kind: 0x03,
+        name: "NOW_CHANNEL_CLOSE_MSG",
+        flags: None,
+        fields: &[Field::Status("status")],
+    },
+    // System
+    MessageLayout {
+        class: 0x11,
+        kind: 0x01,
+        name: "NOW_SYSTEM_INFO_REQ_MSG",
+        flags: None,
+        fields: &[Field::U32("requestId")],
+    },
+    MessageLayout {
+        class: 0x11,
+        kind: 0x02,
+        name: "NOW_SYSTEM_INFO_RSP_MSG",
+        flags: Some(flags16::<NowSystemInfoFlags>),
+        fields: &[
+            Field::U32("requestId"),
+            Field::U64("uptime"),
+            Field::Status("status"),
+            Field::VarStr("osName"),
+            Field::VarStr("osVersion"),
+            Field::VarStr("hostname"),
+            Field::VarStr("architecture"),
+            Field::VarStr("loggedOnUser"),
+            Field::VarStr("agentVersion"),
+        ],
+    },
+    MessageLayout {
+        class: 0x11,
+        kind: 0x03,
+        name: "NOW_SYSTEM_SHUTDOWN_MSG",
+        flags: Some(flags16::<NowSystemShutdownFlags>),
+        fields: &[Field::U32("timeout"), Field::VarStr("message")],
+    },
+    MessageLayout {
+        class: 0x11,
+        kind: 0x04,
+        name: "NOW_SYSTEM_SHUTDOWN_ABORT_MSG",
+        flags: None,
+        fields: &[Field::U32("requestId")],
+    },
+    MessageLayout {
+        class: 0x11,
+        kind: 0x05,
+        name: "NOW_SYSTEM_POWER_ACTION_MSG",
+        flags: Some(flags16::<NowSystemPowerActionFlags>),
+        fields: &[
+            Field::U32("requestId"),
+            Field::U32("action"),
+            Field::U32("timeout"),
+            Field::VarStr("message"),
+        ],
+    },
+    MessageLayout {
+        class: 0x11,
+        kind: 0x06,
+        name: "NOW_SYSTEM_POWER_RSP_MSG",
+        flags: None,
+        fields: &[Field::U32("requestId"), Field::Status("status")],
+    },
+    MessageLayout {
+        class: 0x11,
+        kind: 0x07,
+        name: "NOW_SYSTEM_PROCESS_LIST_REQ_MSG",
+        flags: None,
+        fields: &[Field::U32("requestId")],
+    },
+    MessageLayout {
+        class: 0x11,
+        kind: 0x08,
+        name: "NOW_SYSTEM_PROCESS_INFO_MSG",
+        flags: None,
+        fields: &[
+            Field::U32("requestId"),
+            Field::U32("pid"),
+            Field::U32("parentPid"),
+            Field::U32("sessionId"),
+            Field::U64("startTime"),
+            Field::VarStr("processName"),
+            Field::VarStr("exePath"),
+            Field::VarStr("user"),
+        ],
+    },
+    MessageLayout {
+        class: 0x11,
+        kind: 0x09,
+        name: "NOW_SYSTEM_PROCESS_LIST_RSP_MSG",
+        flags: None,
+        fields: &[Field::U32("requestId"), Field::Status("status")],
+    },
+    MessageLayout {
+        class: 0x11,
+        kind: 0x0A,
+        name: "NOW_SYSTEM_PROCESS_TERMINATE_MSG",
+        flags: Some(flags16::<NowSystemProcessTerminateFlags>),
+        fields: &[Field::U32("requestId"), Field::U32("pid")],
+    },
+    MessageLayout {
+        class: 0x11,
+        kind: 0x0B,
+        name: "NOW_SYSTEM_PROCESS_TERMINATE_RSP_MSG",
+        flags: None,
+        fields: &[Field::U32("requestId"), Field::Status("status")],
+    },
+    // Session
+    MessageLayout {
+        class: 0x12,
+        kind: 0x01,
+        name: "NOW_SESSION_LOCK_MSG",
+        flags: None,
+        fields: &[],
+    },
+    MessageLayout {
+        class: 0x12,
+        kind: 0x02,
+        name: "NOW_SESSION_LOGOFF_MSG",
+        flags: None,
+        fields: &[],
+    },
+    MessageLayout {
+        class: 0x12,
+        kind: 0x03,
+        name: "NOW_SESSION_MSGBOX_REQ_MSG",
+        flags: Some(flags16::<NowSessionMessageBoxFlags>),
+        fields: &[
+            Field::U32("requestId"),
+            Field::U32("style"),
+            Field::U32("timeout"),
+            Field::VarStr("title"),
+            Field::VarStr("message"),
+        ],
+    },
+    MessageLayout {
+        class: 0x12,
+        kind: 0x04,
+        name: "NOW_SESSION_MSGBOX_RSP_MSG",
+        flags: None,
+        fields: &[Field::U32("requestId"), Field::U32("response"), Field::Status("status")],
+    },
+    MessageLayout {
+        class: 0x12,
+        kind: 0x05,
+        name: "NOW_SESSION_SET_KBD_LAYOUT_MSG",
+        flags: Some(flags16::<NowSessionSetKbdLayoutFlags>),
+        fields: &[Field::VarStr("kbdLayoutId")],
+    },
+    MessageLayout {
+        class: 0x12,
+        kind: 0x06,
+        name: "NOW_SESSION_WINDOW_REC_START_MSG",
+        flags: Some(flags16::<WindowRecStartFlags>),
+        fields: &[Field::U32("pollInterval")],
+    },
+    MessageLayout {
+        class: 0x12,
+        kind: 0x07,
+        name: "NOW_SESSION_WINDOW_REC_STOP_MSG",
+        flags: None,
+        fields: &[],
+    },
+    MessageLayout {
+        class: 0x12,
+        kind: 0x08,
+        name: "NOW_SESSION_WINDOW_REC_EVENT_MSG",
+        flags: Some(flags16::<WindowRecEventFlags>),
+        fields: &[
+            Field::U64("timestamp"),
+            Field::U32("processId"),
+            Field::VarStr("title"),
+            Field::VarStr("executablePath"),
+        ],
+    },
+    // Exec
+    MessageLayout {
+        class: 0x13,
+        kind: 0x01,
+        name: "NOW_EXEC_ABORT_MSG",
+        flags: None,
+        fields: &[Field::U32("sessionId"), Field::U32("exitCode")],
+    },
+    MessageLayout {
+        class: 0x13,
+        kind: 0x02,
+        name: "NOW_EXEC_CANCEL_REQ_MSG",
+        flags: None,
+        fields: &[Field::U32("sessionId")],
+    },
+    MessageLayout {
+        class: 0x13,
+        kind: 0x03,
+        name: "NOW_EXEC_CANCEL_RSP_MSG",
+        flags: None,
+        fields: &[Field::U32("sessionId"), Field::Status("status")],
+    },
+    MessageLayout {
+        class: 0x13,
+        kind: 0x04,
+        name: "NOW_EXEC_RESULT_MSG",
+        flags: None,
+        fields: &[Field::U32("sessionId"), Field::U32("exitCode"), Field::Status("status")],
+    },
+    MessageLayout {
+        class: 0x13,
+        kind: 0x05,
+        name: "NOW_EXEC_DATA_MSG",
+        flags: Some(flags16::<NowExecDataFlags>),
+        fields: &[Field::U32("sessionId"), Field::VarBuf("data")],
+    },
+    MessageLayout {
+        class: 0x13,
+        kind: 0x06,
+        name: "NOW_EXEC_STARTED_MSG",
+        flags: None,
+        fields: &[Field::U32("sessionId")],
+    },
+    MessageLayout {
+        class: 0x13,
+        kind: 0x10,
+        name: "NOW_EXEC_RUN_MSG",
+        flags: Some(flags16::<NowExecRunFlags>),
+        fields: &[
+            Field::U32("sessionId"),
+            Field::VarStr("command"),
+            // Directory field has been added in v1.1.
+            Field::OptionalVarStr("directory"),
+        ],
+    },
+    MessageLayout {
+        class: 0x13,
+        kind: 0x11,
+        name: "NOW_EXEC_PROCESS_MSG",
+        flags: Some(flags16::<NowExecProcessFlags>),
+        fields: &[
+            Field::U32("sessionId"),
+            Field::VarStr("filename"),
+            Field::VarStr("parameters"),
+            Field::VarStr("directory"),
+        ],
+    },
+    MessageLayout {
+        class: 0x13,
+        kind: 0x12,
+        name: "NOW_EXEC_SHELL_MSG",
+        flags: Some(flags16::<NowExecShellFlags>),
+        fields: &[
+            Field::U32("sessionId"),
+            Field::VarStr("command"),
+            Field::VarStr("shell"),
+            Field::VarStr("directory"),
+        ],
+    },
+    MessageLayout {
+        class: 0x13,
+        kind: 0x13,
+        name: "NOW_EXEC_BATCH_MSG",
+        flags: Some(flags16::<NowExecBatchFlags>),
+        fields: &[
+            Field::U32("sessionId"),
+            Field::VarStr("command"),
+            Field::VarStr("directory"),
+        ],
+    },
+    MessageLayout {
+        class: 0x13,
+        kind: 0x14,
+        name: "NOW_EXEC_WINPS_MSG",
+        flags: Some(flags16::<NowExecWinPsFlags>),
+        fields: EXEC_WINPS_FIELDS,
+    },
+    MessageLayout {
+        class: 0x13,
+        kind: 0x15,
+        name: "NOW_EXEC_PWSH_MSG",
+        flags: Some(flags16::<NowExecWinPsFlags>),
+        fields: EXEC_WINPS_FIELDS,
+    },
+    // RDM
+    MessageLayout {
+        class: 0x14,
+        kind: 0x01,
+        name: "NOW_RDM_CAPABILITIES_MSG",
+        flags: None,
+        fields: &[
+            Field::U64("timestamp"),
+            Field::Flags32("syncFlags", flags32::<NowRdmSyncFlags>),
+            Field::VarStr("rdmVersion"),
+            Field::VarStr("versionExtra"),
+        ],
+    },
+    MessageLayout {
+        class: 0x14,
+        kind: 0x02,
+        name: "NOW_RDM_APP_START_MSG",
+        flags: None,
+        fields: &[
+            Field::Flags32("launchFlags", flags32::<NowRdmLaunchFlags>),
+            Field::U32("timeout"),
+        ],
+    },
+    MessageLayout {
+        class: 0x14,
+        kind: 0x03,
+        name: "NOW_RDM_APP_ACTION_MSG",
+        flags: None,
+        fields: &[Field::U32("appAction"), Field::VarStr("actionData")],
+    },
+    MessageLayout {
+        class: 0x14,
+        kind: 0x04,
+        name: "NOW_RDM_APP_NOTIFY_MSG",
+        flags: None,
+        fields: &[
+            Field::U32("appState"),
+            Field::U32("reasonCode"),
+            Field::VarStr("notifyData"),
+        ],
+    },
+    MessageLayout {
+        class: 0x14,
+        kind: 0x05,
+        name: "NOW_RDM_SESSION_START_MSG",
+        flags: None,
+        fields: &[
+            Field::Guid("sessionId"),
+            Field::Guid("connectionId"),
+            Field::VarStr("connectionData"),
+        ],
+    },
+    MessageLayout {
+        class: 0x14,
+        kind: 0x06,
+        name: "NOW_RDM_SESSION_ACTION_MSG",
+        flags: None,
+        fields: &[Field::U32("sessionAction"), Field::Guid("sessionId")],
+    },
+    MessageLayout {
+        class: 0x14,
+        kind: 0x07,
+        name: "NOW_RDM_SESSION_NOTIFY_MSG",
+        flags: None,
+        fields: &[
+            Field::U32("sessionNotify"),
+            Field::Guid("sessionId"),
+            Field::VarStr("logData"),
+        ],
+    },
+    // File
+    MessageLayout {
+        class: 0x15,
+        kind: 0x01,
+        name: "NOW_FILE_OPEN_MSG",
+        flags: Some(flags16::<NowFileOpenFlags>),
+        fields: &[Field::U32("transferId"), Field::U64("offset"), Field::VarStr("path")],
+    },
+    MessageLayout {
+        class: 0x15,
+        kind: 0x02,
+        name: "NOW_FILE_OPEN_RSP_MSG",
+        flags: None,
+        fields: &[
+            Field::U32("transferId"),
+            Field::U64("fileSize"),
+            Field::Status("status"),
+        ],
+    },
+    MessageLayout {
+        class: 0x15,
+        kind: 0x03,
+        name: "NOW_FILE_CHUNK_MSG",
+        flags: Some(flags16::<NowFileChunkFlags>),
+        fields: &[Field::U32("transferId"), Field::U64("offset"), Field::VarBuf("data")],
+    },
+    MessageLayout {
+        class: 0x15,
+        kind: 0x04,
+        name: "NOW_FILE_CLOSE_MSG",
+        flags: None,
+        fields: &[Field::U32("transferId"), Field::VarBuf("sha256")],
+    },
+    MessageLayout {
+        class: 0x15,
+        kind: 0x05,
+        name: "NOW_FILE_CLOSE_RSP_MSG",
+        flags: None,
+        fields: &[Field::U32("transferId"), Field::Status("status")],
+    },
+    MessageLayout {
+        class: 0x15,
+        kind: 0x06,
+        name: "NOW_FILE_STAT_REQ_MSG",
+        flags: None,
+        fields: &[Field::U32("requestId"), Field::VarStr("path")],
+    },
+    MessageLayout {
+        class: 0x15,
+        kind: 0x07,
+        name: "NOW_FILE_STAT_RSP_MSG",
+        flags: None,
+        fields: &[
+            Field::U32("requestId"),
+            Field::U32("kind"),
+            Field::Flags32("attributes", flags32::<NowFileAttributes>),
+            Field::U64("fileSize"),
+            Field::U64("mtime"),
+            Field::Status("status"),
+        ],
+    },
+    MessageLayout {
+        class: 0x15,
+        kind: 0x08,
+        name: "NOW_FILE_LIST_DIR_REQ_MSG",
+        flags: None,
+        fields: &[
+            Field::U32("requestId"),
+            Field::U32("startIndex"),
+            Field::U32("maxEntries"),
+            Field::VarStr("path"),
+        ],
+    },
+    MessageLayout {
+        class: 0x15,
+        kind: 0x09,
+        name: "NOW_FILE_DIR_ENTRY_MSG",
+        flags: None,
+        fields: &[
+            Field::U32("requestId"),
+            Field::U32("kind"),
+            Field::Flags32("attributes", flags32::<NowFileAttributes>),
+            Field::U64("fileSize"),
+            Field::U64("mtime"),
+            Field::VarStr("fileName"),
+        ],
+    },
+    MessageLayout {
+        class: 0x15,
+        kind: 0x0A,
+        name: "NOW_FILE_LIST_DIR_RSP_MSG",
+        flags: Some(flags16::<NowFileListDirRspFlags>),
+        fields: &[Field::U32("requestId"), Field::Status("status")],
+    },
+    MessageLayout {
+        class: 0x15,
+        kind: 0x0B,
+        name: "NOW_FILE_MKDIR_MSG",
+        flags: Some(flags16::<NowFileMkdirFlags>),
+        fields: &[Field::U32("requestId"), Field::VarStr("path")],
+    },
+    MessageLayout {
+        class: 0x15,
+        kind: 0x0C,
+        name: "NOW_FILE_DELETE_MSG",
+        flags: Some(flags16::<NowFileDeleteFlags>),
+        fields: &[Field::U32("requestId"), Field::VarStr("path")],
+    },
+    MessageLayout {
+        class: 0x15,
+        kind: 0x0D,
+        name: "NOW_FILE_RENAME_MSG",
+        flags: Some(flags16::<NowFileRenameFlags>),
+        fields: &[Field::U32("requestId"), Field::VarStr("path"), Field::VarStr("newPath")],
+    },
+    MessageLayout {
+        class: 0x15,
+        kind: 0x0E,
+        name: "NOW_FILE_OP_RSP_MSG",
+        flags: None,
+        fields: &[Field::U32("requestId"), Field::Status("status")],
+    },
+];
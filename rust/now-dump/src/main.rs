@@ -0,0 +1,120 @@
+#![allow(unused_crate_dependencies)] // false positives because there is both a library and a binary
+#![allow(clippy::print_stdout)]
+
+use std::io::{Read as _, Write as _};
+use std::path::PathBuf;
+
+use anyhow::Context as _;
+
+const HELP: &str = "\
+now-dump
+
+USAGE:
+  now-dump [OPTIONS] [INPUT]
+
+ARGS:
+  <INPUT>                           Capture file, stdin is used if omitted or `-`
+
+FLAGS:
+  -h, --help                        Prints help information
+
+OPTIONS:
+  -f, --format <auto|hex|bin>       Capture format (default: auto)
+";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Auto,
+    Hex,
+    Binary,
+}
+
+impl core::str::FromStr for Format {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "hex" => Ok(Self::Hex),
+            "bin" => Ok(Self::Binary),
+            _ => anyhow::bail!("unknown capture format: {s}"),
+        }
+    }
+}
+
+struct Args {
+    input: Option<PathBuf>,
+    format: Format,
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = match parse_args() {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            print_help();
+            return Ok(());
+        }
+        Err(error) => {
+            print_help();
+            return Err(error);
+        }
+    };
+
+    let raw = match &args.input {
+        Some(path) => std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?,
+        None => {
+            let mut raw = Vec::new();
+            std::io::stdin().read_to_end(&mut raw).context("failed to read stdin")?;
+            raw
+        }
+    };
+
+    let is_hex = match args.format {
+        Format::Auto => now_dump::looks_like_hex(&raw),
+        Format::Hex => true,
+        Format::Binary => false,
+    };
+
+    let data = if is_hex {
+        let text = core::str::from_utf8(&raw).context("hex capture is not valid UTF-8")?;
+        now_dump::parse_hex(text).context("failed to parse hex capture")?
+    } else {
+        raw
+    };
+
+    let mut output = String::new();
+    let summary = now_dump::dump_capture(&data, &mut output);
+
+    std::io::stdout()
+        .write_all(output.as_bytes())
+        .context("failed to write output")?;
+
+    if !summary.is_clean() {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+fn print_help() {
+    println!("{HELP}");
+}
+
+fn parse_args() -> anyhow::Result<Option<Args>> {
+    let mut args = pico_args::Arguments::from_env();
+
+    if args.contains(["-h", "--help"]) {
+        return Ok(None);
+    }
+
+    let format = args.opt_value_from_str(["-f", "--format"])?.unwrap_or(Format::Auto);
+
+    let input = args
+        .opt_free_from_os_str(|arg| Ok::<_, core::convert::Infallible>(PathBuf::from(arg)))?
+        .filter(|path| path.as_os_str() != "-");
+
+    let remaining = args.finish();
+    anyhow::ensure!(remaining.is_empty(), "unexpected arguments: {remaining:?}");
+
+    Ok(Some(Args { input, format }))
+}
@@ -0,0 +1,326 @@
+use core::fmt;
+use std::collections::HashMap;
+
+use now_proto_pdu::{NowExecDataStreamKind, NowExecMessage, NowProtoError};
+
+use crate::name::exec_message_name;
+use crate::state::{exec_message_direction, Direction};
+
+/// Lifecycle phase of the exec session tracked by [`NowExecSessionTable`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NowExecSessionPhase {
+    /// Exec request has been sent, `NOW_EXEC_STARTED_MSG` is not received yet.
+    Requested,
+    /// Remote process has been started.
+    Started,
+}
+
+/// Exec session lifecycle violation detected by [`NowExecSessionTable`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NowExecSessionError {
+    /// Exec request uses session ID of another active session.
+    InUse { session_id: u32 },
+    /// Message references a session which is not active (never requested or already terminated).
+    UnknownSession { session_id: u32, message: &'static str },
+    /// Message is not expected in the current session phase (e.g. duplicate started message).
+    OutOfOrder {
+        session_id: u32,
+        message: &'static str,
+        phase: NowExecSessionPhase,
+    },
+    /// Data received for the stream which has already been closed with `NOW_EXEC_FLAG_DATA_LAST`.
+    DataAfterLast {
+        session_id: u32,
+        stream: NowExecDataStreamKind,
+    },
+    /// Data message does not specify a valid stream kind.
+    InvalidStream { session_id: u32 },
+    /// Message could only be sent by the opposite side of the channel (e.g. stdout data sent by
+    /// the client).
+    UnexpectedDirection { session_id: u32, message: &'static str },
+    /// Data stream could only be written by the opposite side of the channel (stdin is written by
+    /// the client, stdout and stderr by the server).
+    UnexpectedStream {
+        session_id: u32,
+        stream: NowExecDataStreamKind,
+    },
+}
+
+impl NowExecSessionError {
+    pub fn session_id(&self) -> u32 {
+        match self {
+            Self::InUse { session_id }
+            | Self::UnknownSession { session_id, .. }
+            | Self::OutOfOrder { session_id, .. }
+            | Self::DataAfterLast { session_id, .. }
+            | Self::InvalidStream { session_id }
+            | Self::UnexpectedDirection { session_id, .. }
+            | Self::UnexpectedStream { session_id, .. } => *session_id,
+        }
+    }
+
+    /// Returns the error which should be reported to the peer (e.g. in `NOW_EXEC_RESULT_MSG`).
+    pub fn proto_error(&self) -> NowProtoError {
+        match self {
+            Self::InUse { .. } => NowProtoError::InUse,
+            Self::UnknownSession { .. } => NowProtoError::NotFound,
+            _ => NowProtoError::InvalidRequest,
+        }
+    }
+}
+
+impl fmt::Display for NowExecSessionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InUse { session_id } => write!(f, "exec session ID {session_id} is already in use"),
+            Self::UnknownSession { session_id, message } => {
+                write!(f, "{message} references unknown exec session {session_id}")
+            }
+            Self::OutOfOrder {
+                session_id,
+                message,
+                phase,
+            } => write!(
+                f,
+                "unexpected {message} for exec session {session_id} in {phase:?} phase"
+            ),
+            Self::DataAfterLast { session_id, stream } => {
+                write!(
+                    f,
+                    "{stream:?} data received after the last chunk for exec session {session_id}"
+                )
+            }
+            Self::InvalidStream { session_id } => {
+                write!(f, "invalid data stream kind for exec session {session_id}")
+            }
+            Self::UnexpectedDirection { session_id, message } => {
+                write!(
+                    f,
+                    "{message} for exec session {session_id} was sent by the wrong side of the channel"
+                )
+            }
+            Self::UnexpectedStream { session_id, stream } => {
+                write!(
+                    f,
+                    "{stream:?} data for exec session {session_id} was sent by the wrong side of the channel"
+                )
+            }
+        }
+    }
+}
+
+impl core::error::Error for NowExecSessionError {}
+
+#[derive(Debug, Clone)]
+struct SessionEntry {
+    phase: NowExecSessionPhase,
+    stdin_closed: bool,
+    stdout_closed: bool,
+    stderr_closed: bool,
+}
+
+/// Sans-IO table of active exec sessions.
+///
+/// All exec messages exchanged over the channel should be passed to [`Self::on_client_message`]
+/// or [`Self::on_server_message`] depending on the side which has sent them. The table tracks
+/// the session lifecycle:
+/// request → `NOW_EXEC_STARTED_MSG` → `NOW_EXEC_DATA_MSG`* → `NOW_EXEC_RESULT_MSG`. Session is
+/// terminated by the result or abort message. The table is only updated when the message is valid.
+///
+/// Note that because of the inherent races between peers (e.g. output sent by the server before
+/// it has received the abort message), [`NowExecSessionError::UnknownSession`] for server messages
+/// does not necessarily mean a misbehaving peer.
+#[derive(Debug, Clone, Default)]
+pub struct NowExecSessionTable {
+    sessions: HashMap<u32, SessionEntry>,
+    next_session_id: u32,
+}
+
+impl NowExecSessionTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a session ID which is not used by any active session.
+    ///
+    /// The session is registered once its exec request is passed to [`Self::on_client_message`].
+    pub fn allocate_id(&mut self) -> u32 {
+        loop {
+            let session_id = self.next_session_id;
+            self.next_session_id = self.next_session_id.wrapping_add(1);
+
+            if !self.sessions.contains_key(&session_id) {
+                return session_id;
+            }
+        }
+    }
+
+    /// Returns the phase of the active session.
+    pub fn phase(&self, session_id: u32) -> Option<NowExecSessionPhase> {
+        self.sessions.get(&session_id).map(|session| session.phase)
+    }
+
+    pub fn contains(&self, session_id: u32) -> bool {
+        self.sessions.contains_key(&session_id)
+    }
+
+    /// Returns number of active sessions.
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    /// Validates exec message sent by the client and updates session state.
+    pub fn on_client_message(&mut self, message: &NowExecMessage<'_>) -> Result<(), NowExecSessionError> {
+        self.on_message(message, Direction::ClientToServer)
+    }
+
+    /// Validates exec message sent by the server and updates session state.
+    pub fn on_server_message(&mut self, message: &NowExecMessage<'_>) -> Result<(), NowExecSessionError> {
+        self.on_message(message, Direction::ServerToClient)
+    }
+
+    fn on_message(&mut self, message: &NowExecMessage<'_>, direction: Direction) -> Result<(), NowExecSessionError> {
+        if exec_message_direction(message).is_some_and(|expected| expected != direction) {
+            return Err(NowExecSessionError::UnexpectedDirection {
+                session_id: exec_session_id(message),
+                message: exec_message_name(message),
+            });
+        }
+
+        match message {
+            // "Run" execution style has no session lifecycle.
+            NowExecMessage::Run(_) => Ok(()),
+            NowExecMessage::Process(msg) => self.on_request(msg.session_id()),
+            NowExecMessage::Shell(msg) => self.on_request(msg.session_id()),
+            NowExecMessage::Batch(msg) => self.on_request(msg.session_id()),
+            NowExecMessage::WinPs(msg) => self.on_request(msg.session_id()),
+            NowExecMessage::Pwsh(msg) => self.on_request(msg.session_id()),
+            NowExecMessage::Started(msg) => {
+                let session = self.get_mut(msg.session_id(), message)?;
+
+                if session.phase != NowExecSessionPhase::Requested {
+                    return Err(NowExecSessionError::OutOfOrder {
+                        session_id: msg.session_id(),
+                        message: exec_message_name(message),
+                        phase: session.phase,
+                    });
+                }
+
+                session.phase = NowExecSessionPhase::Started;
+                Ok(())
+            }
+            NowExecMessage::Data(msg) => {
+                let session_id = msg.session_id();
+                let stream = msg
+                    .stream_kind()
+                    .map_err(|_| NowExecSessionError::InvalidStream { session_id })?;
+
+                let stream_direction = match stream {
+                    NowExecDataStreamKind::Stdin => Direction::ClientToServer,
+                    NowExecDataStreamKind::Stdout | NowExecDataStreamKind::Stderr => Direction::ServerToClient,
+                };
+
+                if stream_direction != direction {
+                    return Err(NowExecSessionError::UnexpectedStream { session_id, stream });
+                }
+
+                let session = self.get_mut(session_id, message)?;
+
+                // Stdin could be sent right after the request, output is only expected after the
+                // process has been started.
+                if stream != NowExecDataStreamKind::Stdin && session.phase != NowExecSessionPhase::Started {
+                    return Err(NowExecSessionError::OutOfOrder {
+                        session_id,
+                        message: exec_message_name(message),
+                        phase: session.phase,
+                    });
+                }
+
+                let closed = match stream {
+                    NowExecDataStreamKind::Stdin => &mut session.stdin_closed,
+                    NowExecDataStreamKind::Stdout => &mut session.stdout_closed,
+                    NowExecDataStreamKind::Stderr => &mut session.stderr_closed,
+                };
+
+                if *closed {
+                    return Err(NowExecSessionError::DataAfterLast { session_id, stream });
+                }
+
+                *closed = msg.is_last();
+                Ok(())
+            }
+            NowExecMessage::CancelReq(msg) => self.get_mut(msg.session_id(), message).map(|_| ()),
+            // Session could have exited before the cancel request was processed, therefore
+            // response for the terminated session is valid.
+            NowExecMessage::CancelRsp(_) => Ok(()),
+            NowExecMessage::Result(msg) => {
+                self.get_mut(msg.session_id(), message)?;
+                self.sessions.remove(&msg.session_id());
+                Ok(())
+            }
+            // Abort could race with the session result, therefore unknown sessions are ignored.
+            NowExecMessage::Abort(msg) => {
+                self.sessions.remove(&msg.session_id());
+                Ok(())
+            }
+        }
+    }
+
+    /// Unregisters the session without any message exchange (e.g. when the exec request could
+    /// not be sent).
+    pub fn remove(&mut self, session_id: u32) -> bool {
+        self.sessions.remove(&session_id).is_some()
+    }
+
+    fn on_request(&mut self, session_id: u32) -> Result<(), NowExecSessionError> {
+        if self.sessions.contains_key(&session_id) {
+            return Err(NowExecSessionError::InUse { session_id });
+        }
+
+        self.sessions.insert(
+            session_id,
+            SessionEntry {
+                phase: NowExecSessionPhase::Requested,
+                stdin_closed: false,
+                stdout_closed: false,
+                stderr_closed: false,
+            },
+        );
+
+        Ok(())
+    }
+
+    fn get_mut(
+        &mut self,
+        session_id: u32,
+        message: &NowExecMessage<'_>,
+    ) -> Result<&mut SessionEntry, NowExecSessionError> {
+        self.sessions
+            .get_mut(&session_id)
+            .ok_or_else(|| NowExecSessionError::UnknownSession {
+                session_id,
+                message: exec_message_name(message),
+            })
+    }
+}
+
+fn exec_session_id(message: &NowExecMessage<'_>) -> u32 {
+    match message {
+        NowExecMessage::Run(msg) => msg.session_id(),
+        NowExecMessage::Process(msg) => msg.session_id(),
+        NowExecMessage::Shell(msg) => msg.session_id(),
+        NowExecMessage::Batch(msg) => msg.session_id(),
+        NowExecMessage::WinPs(msg) => msg.session_id(),
+        NowExecMessage::Pwsh(msg) => msg.session_id(),
+        NowExecMessage::Started(msg) => msg.session_id(),
+        NowExecMessage::Data(msg) => msg.session_id(),
+        NowExecMessage::CancelReq(msg) => msg.session_id(),
+        NowExecMessage::CancelRsp(msg) => msg.session_id(),
+        NowExecMessage::Result(msg) => msg.session_id(),
+        NowExecMessage::Abort(msg) => msg.session_id(),
+    }
+}
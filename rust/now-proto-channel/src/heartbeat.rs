@@ -0,0 +1,154 @@
+use core::time::Duration;
+use std::time::Instant;
+
+use now_proto_pdu::NowChannelCapsetMsg;
+
+use crate::NowChannelRole;
+
+/// Default number of heartbeat intervals without inbound traffic after which the peer is
+/// considered dead.
+const DEFAULT_MAX_MISSED_INTERVALS: u32 = 2;
+
+/// Source of the current time for [`NowHeartbeatSupervisor`].
+pub trait NowClock {
+    fn now(&self) -> Instant;
+}
+
+/// [`NowClock`] implementation backed by the system monotonic clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NowSystemClock;
+
+impl NowClock for NowSystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Action requested by [`NowHeartbeatSupervisor::poll`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NowHeartbeatEvent {
+    /// `NOW_CHANNEL_HEARTBEAT_MSG` should be sent to the client.
+    SendHeartbeat,
+    /// No traffic has been received from the server for the configured number of heartbeat
+    /// intervals; the channel should be considered lost.
+    PeerTimeout { idle: Duration },
+}
+
+/// Sans-IO heartbeat supervisor driven by the negotiated heartbeat interval.
+///
+/// Per NOW-PROTO, heartbeats are sent by the server: on the server side the supervisor schedules
+/// outgoing heartbeats, on the client side it tracks inbound traffic and reports the peer timeout.
+/// The caller should invoke [`Self::poll`] when [`Self::next_deadline`] is reached, and report
+/// received messages with [`Self::on_message_received`].
+#[derive(Debug, Clone)]
+pub struct NowHeartbeatSupervisor<C = NowSystemClock> {
+    clock: C,
+    role: NowChannelRole,
+    interval: Duration,
+    max_missed_intervals: u32,
+    last_sent: Instant,
+    last_received: Instant,
+    timed_out: bool,
+}
+
+impl NowHeartbeatSupervisor {
+    /// Creates supervisor for the negotiated channel. Returns `None` if the periodic heartbeat is
+    /// disabled by the negotiated capabilities.
+    pub fn new(role: NowChannelRole, capabilities: &NowChannelCapsetMsg) -> Option<Self> {
+        Self::with_clock(role, capabilities, NowSystemClock)
+    }
+}
+
+impl<C: NowClock> NowHeartbeatSupervisor<C> {
+    /// Same as [`NowHeartbeatSupervisor::new`], with a custom clock.
+    pub fn with_clock(role: NowChannelRole, capabilities: &NowChannelCapsetMsg, clock: C) -> Option<Self> {
+        let interval = capabilities
+            .heartbeat_interval()
+            .filter(|interval| !interval.is_zero())?;
+
+        let now = clock.now();
+
+        Some(Self {
+            clock,
+            role,
+            interval,
+            max_missed_intervals: DEFAULT_MAX_MISSED_INTERVALS,
+            last_sent: now,
+            last_received: now,
+            timed_out: false,
+        })
+    }
+
+    /// Sets number of heartbeat intervals without inbound traffic after which
+    /// [`NowHeartbeatEvent::PeerTimeout`] is raised (2 by default, values below 1 are clamped).
+    #[must_use]
+    pub fn with_max_missed_intervals(mut self, intervals: u32) -> Self {
+        self.max_missed_intervals = intervals.max(1);
+        self
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Returns the time elapsed since the last inbound message.
+    pub fn idle(&self) -> Duration {
+        self.clock.now().saturating_duration_since(self.last_received)
+    }
+
+    /// Should be called for every message received from the peer; any inbound traffic proves
+    /// that the peer is alive.
+    pub fn on_message_received(&mut self) {
+        self.last_received = self.clock.now();
+    }
+
+    /// Returns the time at which [`Self::poll`] should be called next. Returns `None` once the
+    /// peer timeout has been reported.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        if self.timed_out {
+            return None;
+        }
+
+        let deadline = match self.role {
+            NowChannelRole::Server => self.last_sent + self.interval,
+            NowChannelRole::Client => self.last_received + self.timeout(),
+        };
+
+        Some(deadline)
+    }
+
+    /// Checks timers and returns the action which should be performed by the caller.
+    ///
+    /// [`NowHeartbeatEvent::PeerTimeout`] is reported only once.
+    pub fn poll(&mut self) -> Option<NowHeartbeatEvent> {
+        if self.timed_out {
+            return None;
+        }
+
+        let now = self.clock.now();
+
+        match self.role {
+            NowChannelRole::Server => {
+                if now.saturating_duration_since(self.last_sent) < self.interval {
+                    return None;
+                }
+
+                self.last_sent = now;
+                Some(NowHeartbeatEvent::SendHeartbeat)
+            }
+            NowChannelRole::Client => {
+                let idle = now.saturating_duration_since(self.last_received);
+                if idle < self.timeout() {
+                    return None;
+                }
+
+                self.timed_out = true;
+                Some(NowHeartbeatEvent::PeerTimeout { idle })
+            }
+        }
+    }
+
+    fn timeout(&self) -> Duration {
+        self.interval.saturating_mul(self.max_missed_intervals)
+    }
+}
@@ -0,0 +1,15 @@
+#![doc = include_str!("../README.md")]
+#![doc(
+    html_logo_url = "https://webdevolutions.blob.core.windows.net/images/projects/devolutions/logos/devolutions-icon-shadow.svg"
+)]
+
+mod exec;
+mod heartbeat;
+mod msg_box;
+mod name;
+mod state;
+
+pub use exec::*;
+pub use heartbeat::*;
+pub use msg_box::*;
+pub use state::*;
@@ -0,0 +1,190 @@
+use core::fmt;
+use core::time::Duration;
+use std::collections::HashMap;
+use std::time::Instant;
+
+use now_proto_pdu::{NowMsgBoxResponse, NowProtoError, NowSessionMsgBoxReqMsg, NowSessionMsgBoxRspMsg, NowStatusError};
+
+use crate::{NowClock, NowSystemClock};
+
+/// Default time to wait for the response after the message box timeout has elapsed.
+const DEFAULT_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+/// Error returned by [`NowMsgBoxCorrelator::register`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NowMsgBoxError {
+    /// Request ID is used by another pending message box.
+    InUse { request_id: u32 },
+    /// Request does not have `NOW_MSGBOX_FLAG_RESPONSE` set, the server will never respond.
+    ResponseNotExpected { request_id: u32 },
+}
+
+impl NowMsgBoxError {
+    pub fn request_id(&self) -> u32 {
+        match self {
+            Self::InUse { request_id } | Self::ResponseNotExpected { request_id } => *request_id,
+        }
+    }
+
+    /// Returns the error which should be reported to the caller of the rejected request.
+    pub fn proto_error(&self) -> NowProtoError {
+        match self {
+            Self::InUse { .. } => NowProtoError::InUse,
+            Self::ResponseNotExpected { .. } => NowProtoError::InvalidRequest,
+        }
+    }
+}
+
+impl fmt::Display for NowMsgBoxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InUse { request_id } => write!(f, "message box request ID {request_id} is already in use"),
+            Self::ResponseNotExpected { request_id } => {
+                write!(f, "message box request {request_id} does not expect a response")
+            }
+        }
+    }
+}
+
+impl core::error::Error for NowMsgBoxError {}
+
+#[derive(Debug)]
+struct PendingMsgBox<T> {
+    deadline: Option<Instant>,
+    context: T,
+}
+
+/// Sans-IO message box request/response correlation.
+///
+/// Each pending request carries a caller-defined `context` (e.g. a channel used to notify the
+/// waiting task), which is handed back when the request is resolved by the server response or
+/// expires locally. Requests with a timeout expire once the timeout plus the grace period has
+/// elapsed without a response; requests without a timeout wait until the channel is closed.
+#[derive(Debug)]
+pub struct NowMsgBoxCorrelator<T, C = NowSystemClock> {
+    clock: C,
+    grace_period: Duration,
+    next_request_id: u32,
+    pending: HashMap<u32, PendingMsgBox<T>>,
+}
+
+impl<T> NowMsgBoxCorrelator<T> {
+    pub fn new() -> Self {
+        Self::with_clock(NowSystemClock)
+    }
+}
+
+impl<T> Default for NowMsgBoxCorrelator<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, C: NowClock> NowMsgBoxCorrelator<T, C> {
+    pub fn with_clock(clock: C) -> Self {
+        Self {
+            clock,
+            grace_period: DEFAULT_GRACE_PERIOD,
+            next_request_id: 0,
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Sets additional time to wait for the response after the message box timeout has elapsed
+    /// (5 seconds by default), which covers the channel latency.
+    #[must_use]
+    pub fn with_grace_period(mut self, grace_period: Duration) -> Self {
+        self.grace_period = grace_period;
+        self
+    }
+
+    /// Returns a request ID which is not used by any pending message box.
+    pub fn allocate_id(&mut self) -> u32 {
+        loop {
+            let request_id = self.next_request_id;
+            self.next_request_id = self.next_request_id.wrapping_add(1);
+
+            if !self.pending.contains_key(&request_id) {
+                return request_id;
+            }
+        }
+    }
+
+    /// Registers sent message box request. On error, `context` is handed back to the caller.
+    pub fn register(&mut self, request: &NowSessionMsgBoxReqMsg<'_>, context: T) -> Result<(), (NowMsgBoxError, T)> {
+        let request_id = request.request_id();
+
+        if !request.is_response_expected() {
+            return Err((NowMsgBoxError::ResponseNotExpected { request_id }, context));
+        }
+
+        if self.pending.contains_key(&request_id) {
+            return Err((NowMsgBoxError::InUse { request_id }, context));
+        }
+
+        let deadline = request
+            .timeout()
+            .map(|timeout| self.clock.now() + timeout + self.grace_period);
+
+        self.pending.insert(request_id, PendingMsgBox { deadline, context });
+
+        Ok(())
+    }
+
+    /// Resolves the pending request with the server response. Returns `None` if the response
+    /// does not match any pending request (e.g. it has already expired).
+    pub fn on_response(
+        &mut self,
+        response: &NowSessionMsgBoxRspMsg<'_>,
+    ) -> Option<(T, Result<NowMsgBoxResponse, NowStatusError>)> {
+        let pending = self.pending.remove(&response.request_id())?;
+
+        Some((pending.context, response.to_result()))
+    }
+
+    /// Returns the time at which [`Self::poll_expired`] should be called next.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.pending.values().filter_map(|pending| pending.deadline).min()
+    }
+
+    /// Removes and returns requests which have not received a response in time.
+    pub fn poll_expired(&mut self) -> Vec<(u32, T)> {
+        let now = self.clock.now();
+
+        let expired: Vec<u32> = self
+            .pending
+            .iter()
+            .filter(|(_, pending)| pending.deadline.is_some_and(|deadline| deadline <= now))
+            .map(|(request_id, _)| *request_id)
+            .collect();
+
+        expired
+            .into_iter()
+            .filter_map(|request_id| {
+                self.pending
+                    .remove(&request_id)
+                    .map(|pending| (request_id, pending.context))
+            })
+            .collect()
+    }
+
+    /// Removes all pending requests (e.g. when the channel is closed).
+    pub fn drain(&mut self) -> impl Iterator<Item = (u32, T)> + '_ {
+        self.pending
+            .drain()
+            .map(|(request_id, pending)| (request_id, pending.context))
+    }
+
+    pub fn contains(&self, request_id: u32) -> bool {
+        self.pending.contains_key(&request_id)
+    }
+
+    /// Returns number of pending requests.
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
@@ -0,0 +1,84 @@
+use now_proto_pdu::ironrdp_core::Encode;
+use now_proto_pdu::{
+    NowChannelMessage, NowExecMessage, NowFileMessage, NowMessage, NowRdmMessage, NowSessionMessage, NowSystemMessage,
+};
+
+/// Returns protocol name of the message (e.g. `NOW_SESSION_LOCK_MSG`).
+pub(crate) fn message_name(message: &NowMessage<'_>) -> &'static str {
+    match message {
+        NowMessage::Channel(msg) => match msg {
+            NowChannelMessage::Capset(msg) => msg.name(),
+            NowChannelMessage::Heartbeat(msg) => msg.name(),
+            NowChannelMessage::Close(msg) => msg.name(),
+        },
+        NowMessage::System(msg) => match msg {
+            NowSystemMessage::InfoReq(msg) => msg.name(),
+            NowSystemMessage::InfoRsp(msg) => msg.name(),
+            NowSystemMessage::Shutdown(msg) => msg.name(),
+            NowSystemMessage::ShutdownAbort(msg) => msg.name(),
+            NowSystemMessage::PowerAction(msg) => msg.name(),
+            NowSystemMessage::PowerRsp(msg) => msg.name(),
+            NowSystemMessage::ProcessListReq(msg) => msg.name(),
+            NowSystemMessage::ProcessInfo(msg) => msg.name(),
+            NowSystemMessage::ProcessListRsp(msg) => msg.name(),
+            NowSystemMessage::ProcessTerminate(msg) => msg.name(),
+            NowSystemMessage::ProcessTerminateRsp(msg) => msg.name(),
+        },
+        NowMessage::Session(msg) => match msg {
+            NowSessionMessage::Lock(msg) => msg.name(),
+            NowSessionMessage::Logoff(msg) => msg.name(),
+            NowSessionMessage::MsgBoxReq(msg) => msg.name(),
+            NowSessionMessage::MsgBoxRsp(msg) => msg.name(),
+            NowSessionMessage::SetKbdLayout(msg) => msg.name(),
+            NowSessionMessage::WindowRecStart(msg) => msg.name(),
+            NowSessionMessage::WindowRecStop(msg) => msg.name(),
+            NowSessionMessage::WindowRecEvent(msg) => msg.name(),
+        },
+        NowMessage::Exec(msg) => exec_message_name(msg),
+        NowMessage::Rdm(msg) => match msg {
+            NowRdmMessage::Capabilities(msg) => msg.name(),
+            NowRdmMessage::AppStart(msg) => msg.name(),
+            NowRdmMessage::AppAction(msg) => msg.name(),
+            NowRdmMessage::AppNotify(msg) => msg.name(),
+            NowRdmMessage::SessionStart(msg) => msg.name(),
+            NowRdmMessage::SessionAction(msg) => msg.name(),
+            NowRdmMessage::SessionNotify(msg) => msg.name(),
+        },
+        NowMessage::File(msg) => match msg {
+            NowFileMessage::Open(msg) => msg.name(),
+            NowFileMessage::OpenRsp(msg) => msg.name(),
+            NowFileMessage::Chunk(msg) => msg.name(),
+            NowFileMessage::Close(msg) => msg.name(),
+            NowFileMessage::CloseRsp(msg) => msg.name(),
+            NowFileMessage::StatReq(msg) => msg.name(),
+            NowFileMessage::StatRsp(msg) => msg.name(),
+            NowFileMessage::ListDirReq(msg) => msg.name(),
+            NowFileMessage::DirEntry(msg) => msg.name(),
+            NowFileMessage::ListDirRsp(msg) => msg.name(),
+            NowFileMessage::Mkdir(msg) => msg.name(),
+            NowFileMessage::Delete(msg) => msg.name(),
+            NowFileMessage::Rename(msg) => msg.name(),
+            NowFileMessage::OpRsp(msg) => msg.name(),
+        },
+        NowMessage::Unknown { .. } => message.name(),
+        NowMessage::Extension(msg) => msg.name(),
+    }
+}
+
+/// Returns protocol name of the exec message (e.g. `NOW_EXEC_STARTED_MSG`).
+pub(crate) fn exec_message_name(message: &NowExecMessage<'_>) -> &'static str {
+    match message {
+        NowExecMessage::Abort(msg) => msg.name(),
+        NowExecMessage::CancelReq(msg) => msg.name(),
+        NowExecMessage::CancelRsp(msg) => msg.name(),
+        NowExecMessage::Result(msg) => msg.name(),
+        NowExecMessage::Data(msg) => msg.name(),
+        NowExecMessage::Started(msg) => msg.name(),
+        NowExecMessage::Run(msg) => msg.name(),
+        NowExecMessage::Process(msg) => msg.name(),
+        NowExecMessage::Shell(msg) => msg.name(),
+        NowExecMessage::Batch(msg) => msg.name(),
+        NowExecMessage::WinPs(msg) => msg.name(),
+        NowExecMessage::Pwsh(msg) => msg.name(),
+    }
+}
@@ -0,0 +1,527 @@
+use core::fmt;
+
+use now_proto_pdu::ironrdp_core::EncodeResult;
+use now_proto_pdu::{
+    NowChannelCapsetMsg, NowChannelCloseMsg, NowChannelMessage, NowExecCapsetFlags, NowExecMessage, NowFileCapsetFlags,
+    NowFileMessage, NowFileOpenMode, NowMessage, NowProtoError, NowProtoVersion, NowRdmMessage, NowSessionCapsetFlags,
+    NowSessionMessage, NowSystemCapsetFlags, NowSystemMessage,
+};
+
+use crate::name::message_name;
+
+/// Minimal protocol version supporting exec IO redirection and run message working directory.
+const MIN_IO_REDIRECTION_VERSION: NowProtoVersion = NowProtoVersion { major: 1, minor: 1 };
+
+/// Minimal protocol version supporting PowerShell server mode.
+const MIN_SERVER_MODE_VERSION: NowProtoVersion = NowProtoVersion { major: 1, minor: 2 };
+
+/// Minimal protocol version supporting RDM messages.
+const MIN_RDM_ENABLED_VERSION: NowProtoVersion = NowProtoVersion { major: 1, minor: 3 };
+
+/// Minimal protocol version supporting detached exec sessions.
+const MIN_DETACHED_VERSION: NowProtoVersion = NowProtoVersion { major: 1, minor: 4 };
+
+/// Minimal protocol version supporting window recording messages.
+const MIN_WINDOW_RECORDING_VERSION: NowProtoVersion = NowProtoVersion { major: 1, minor: 5 };
+
+/// Minimal protocol version supporting exec encoding control flags.
+const MIN_EXEC_ENCODING_VERSION: NowProtoVersion = NowProtoVersion { major: 1, minor: 6 };
+
+/// Minimal protocol version supporting system queries, power actions, process management and
+/// file messages.
+const MIN_SYSTEM_AND_FILE_VERSION: NowProtoVersion = NowProtoVersion { major: 1, minor: 7 };
+
+/// Side of the channel tracked by [`NowChannelState`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NowChannelRole {
+    Client,
+    Server,
+}
+
+/// Channel negotiation phase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NowChannelPhase {
+    /// Client capabilities (`NOW_CHANNEL_CAPSET_MSG`) have not been sent yet.
+    AwaitingClientCapset,
+    /// Client capabilities have been sent, downgraded server capabilities are expected.
+    AwaitingServerCapset,
+    /// Channel is negotiated, messages covered by negotiated capabilities could be exchanged.
+    Negotiated,
+    /// `NOW_CHANNEL_CLOSE_MSG` has been exchanged, no more messages are expected.
+    Closed,
+}
+
+/// Capability required to exchange a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NowCapability {
+    System(NowSystemCapsetFlags),
+    Session(NowSessionCapsetFlags),
+    Exec(NowExecCapsetFlags),
+    File(NowFileCapsetFlags),
+}
+
+/// NOW-PROTO channel protocol violation detected by [`NowChannelState`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NowChannelViolation {
+    /// Message was exchanged before channel negotiation was complete.
+    CapsetExpected { message: &'static str },
+    /// `NOW_CHANNEL_CAPSET_MSG` was exchanged out of the negotiation sequence.
+    UnexpectedCapset,
+    /// Major protocol versions of the peers do not match.
+    IncompatibleVersion {
+        client: NowProtoVersion,
+        server: NowProtoVersion,
+    },
+    /// Message requires newer protocol version than the negotiated one.
+    VersionNotSupported {
+        message: &'static str,
+        required: NowProtoVersion,
+        negotiated: NowProtoVersion,
+    },
+    /// Message is not covered by the negotiated capabilities.
+    CapabilityNotNegotiated {
+        message: &'static str,
+        capability: NowCapability,
+    },
+    /// Message could only be sent by the opposite side of the channel.
+    UnexpectedDirection { message: &'static str },
+    /// Message was exchanged after the channel has been closed.
+    ChannelClosed { message: &'static str },
+}
+
+impl NowChannelViolation {
+    /// Returns the error which should be reported to the peer when closing the channel.
+    pub fn proto_error(&self) -> NowProtoError {
+        match self {
+            Self::IncompatibleVersion { .. } | Self::VersionNotSupported { .. } => NowProtoError::ProtocolVersion,
+            _ => NowProtoError::InvalidRequest,
+        }
+    }
+
+    /// Builds `NOW_CHANNEL_CLOSE_MSG` reporting this violation to the peer.
+    pub fn to_close_msg(&self) -> EncodeResult<NowChannelCloseMsg<'static>> {
+        NowChannelCloseMsg::from_error(self.proto_error())
+    }
+}
+
+impl fmt::Display for NowChannelViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CapsetExpected { message } => {
+                write!(f, "{message} was exchanged before channel negotiation")
+            }
+            Self::UnexpectedCapset => write!(f, "unexpected channel capabilities message"),
+            Self::IncompatibleVersion { client, server } => write!(
+                f,
+                "incompatible protocol versions: client {}.{}, server {}.{}",
+                client.major, client.minor, server.major, server.minor
+            ),
+            Self::VersionNotSupported {
+                message,
+                required,
+                negotiated,
+            } => write!(
+                f,
+                "{message} requires protocol version {}.{}, negotiated version is {}.{}",
+                required.major, required.minor, negotiated.major, negotiated.minor
+            ),
+            Self::CapabilityNotNegotiated { message, capability } => {
+                write!(
+                    f,
+                    "{message} requires {capability:?} capability which was not negotiated"
+                )
+            }
+            Self::UnexpectedDirection { message } => {
+                write!(f, "{message} was sent by the wrong side of the channel")
+            }
+            Self::ChannelClosed { message } => write!(f, "{message} was exchanged after channel close"),
+        }
+    }
+}
+
+impl core::error::Error for NowChannelViolation {}
+
+/// Direction of the message on the channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Direction {
+    ClientToServer,
+    ServerToClient,
+}
+
+/// Sans-IO NOW-PROTO channel state machine.
+///
+/// All messages exchanged over the channel should be passed to [`Self::on_incoming`] and
+/// [`Self::on_outgoing`] respectively. Messages are validated against the negotiation status,
+/// negotiated protocol version and capabilities; the state is only updated when the message is
+/// valid. On violation, the channel is expected to be closed with
+/// [`NowChannelViolation::to_close_msg`].
+#[derive(Debug, Clone)]
+pub struct NowChannelState {
+    role: NowChannelRole,
+    version: NowProtoVersion,
+    phase: NowChannelPhase,
+    client_capabilities: Option<NowChannelCapsetMsg>,
+    negotiated: Option<NowChannelCapsetMsg>,
+}
+
+impl NowChannelState {
+    /// Creates state for the channel side implementing the given protocol `version`
+    /// (usually [`NowProtoVersion::CURRENT`]).
+    pub fn new(role: NowChannelRole, version: NowProtoVersion) -> Self {
+        Self {
+            role,
+            version,
+            phase: NowChannelPhase::AwaitingClientCapset,
+            client_capabilities: None,
+            negotiated: None,
+        }
+    }
+
+    pub fn role(&self) -> NowChannelRole {
+        self.role
+    }
+
+    pub fn phase(&self) -> NowChannelPhase {
+        self.phase
+    }
+
+    /// Returns downgraded capabilities once the channel is negotiated.
+    pub fn negotiated_capabilities(&self) -> Option<&NowChannelCapsetMsg> {
+        self.negotiated.as_ref()
+    }
+
+    /// Validates message received from the peer.
+    pub fn on_incoming(&mut self, message: &NowMessage<'_>) -> Result<(), NowChannelViolation> {
+        let direction = match self.role {
+            NowChannelRole::Client => Direction::ServerToClient,
+            NowChannelRole::Server => Direction::ClientToServer,
+        };
+
+        self.on_message(message, direction)
+    }
+
+    /// Validates message before sending it to the peer.
+    pub fn on_outgoing(&mut self, message: &NowMessage<'_>) -> Result<(), NowChannelViolation> {
+        let direction = match self.role {
+            NowChannelRole::Client => Direction::ClientToServer,
+            NowChannelRole::Server => Direction::ServerToClient,
+        };
+
+        self.on_message(message, direction)
+    }
+
+    fn on_message(&mut self, message: &NowMessage<'_>, direction: Direction) -> Result<(), NowChannelViolation> {
+        if self.phase == NowChannelPhase::Closed {
+            return Err(NowChannelViolation::ChannelClosed {
+                message: message_name(message),
+            });
+        }
+
+        match message {
+            // Channel could be closed by either side at any time, including failed negotiation.
+            NowMessage::Channel(NowChannelMessage::Close(_)) => {
+                self.phase = NowChannelPhase::Closed;
+                Ok(())
+            }
+            NowMessage::Channel(NowChannelMessage::Capset(capabilities)) => self.on_capset(capabilities, direction),
+            _ if self.phase != NowChannelPhase::Negotiated => Err(NowChannelViolation::CapsetExpected {
+                message: message_name(message),
+            }),
+            _ => {
+                let negotiated = self.negotiated.as_ref().expect("negotiated capabilities are set");
+                validate_negotiated(negotiated, message, direction)
+            }
+        }
+    }
+
+    fn on_capset(
+        &mut self,
+        capabilities: &NowChannelCapsetMsg,
+        direction: Direction,
+    ) -> Result<(), NowChannelViolation> {
+        match (self.phase, direction) {
+            (NowChannelPhase::AwaitingClientCapset, Direction::ClientToServer) => {
+                if self.role == NowChannelRole::Server {
+                    ensure_compatible(capabilities.version(), self.version)?;
+                }
+
+                self.client_capabilities = Some(capabilities.clone());
+                self.phase = NowChannelPhase::AwaitingServerCapset;
+            }
+            (NowChannelPhase::AwaitingServerCapset, Direction::ServerToClient) => {
+                let client = self.client_capabilities.as_ref().expect("client capabilities are set");
+
+                ensure_compatible(client.version(), capabilities.version())?;
+
+                // Server capabilities are expected to be downgraded already; downgrade them again so
+                // that capabilities never offered by the client are not treated as negotiated.
+                self.negotiated = Some(client.downgrade(capabilities));
+                self.phase = NowChannelPhase::Negotiated;
+            }
+            _ => return Err(NowChannelViolation::UnexpectedCapset),
+        }
+
+        Ok(())
+    }
+}
+
+fn ensure_compatible(client: NowProtoVersion, server: NowProtoVersion) -> Result<(), NowChannelViolation> {
+    if client.major != server.major {
+        return Err(NowChannelViolation::IncompatibleVersion { client, server });
+    }
+
+    Ok(())
+}
+
+fn validate_negotiated(
+    negotiated: &NowChannelCapsetMsg,
+    message: &NowMessage<'_>,
+    direction: Direction,
+) -> Result<(), NowChannelViolation> {
+    let name = message_name(message);
+
+    if let Some(expected) = message_direction(message) {
+        if expected != direction {
+            return Err(NowChannelViolation::UnexpectedDirection { message: name });
+        }
+    }
+
+    if let Some(required) = required_version(message) {
+        if negotiated.version() < required {
+            return Err(NowChannelViolation::VersionNotSupported {
+                message: name,
+                required,
+                negotiated: negotiated.version(),
+            });
+        }
+    }
+
+    if let Some(capability) = required_capability(message) {
+        let supported = match capability {
+            NowCapability::System(flags) => negotiated.system_capset().contains(flags),
+            NowCapability::Session(flags) => negotiated.session_capset().contains(flags),
+            NowCapability::Exec(flags) => negotiated.exec_capset().contains(flags),
+            NowCapability::File(flags) => negotiated.file_capset().contains(flags),
+        };
+
+        if !supported {
+            return Err(NowChannelViolation::CapabilityNotNegotiated {
+                message: name,
+                capability,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the only direction the message could be sent in, or `None` if the message could be
+/// sent by both sides.
+fn message_direction(message: &NowMessage<'_>) -> Option<Direction> {
+    use Direction::{ClientToServer, ServerToClient};
+
+    match message {
+        NowMessage::System(msg) => match msg {
+            NowSystemMessage::InfoRsp(_)
+            | NowSystemMessage::PowerRsp(_)
+            | NowSystemMessage::ProcessInfo(_)
+            | NowSystemMessage::ProcessListRsp(_)
+            | NowSystemMessage::ProcessTerminateRsp(_) => Some(ServerToClient),
+            _ => Some(ClientToServer),
+        },
+        NowMessage::Session(msg) => match msg {
+            NowSessionMessage::MsgBoxRsp(_) | NowSessionMessage::WindowRecEvent(_) => Some(ServerToClient),
+            _ => Some(ClientToServer),
+        },
+        NowMessage::Exec(msg) => exec_message_direction(msg),
+        NowMessage::Rdm(msg) => match msg {
+            // Capabilities are sent as a request and echoed back as a response.
+            NowRdmMessage::Capabilities(_) => None,
+            NowRdmMessage::AppNotify(_) | NowRdmMessage::SessionNotify(_) => Some(ServerToClient),
+            _ => Some(ClientToServer),
+        },
+        NowMessage::File(msg) => match msg {
+            // Chunks are sent by the server for reads, and by the client for writes.
+            NowFileMessage::Chunk(_) => None,
+            NowFileMessage::OpenRsp(_)
+            | NowFileMessage::CloseRsp(_)
+            | NowFileMessage::StatRsp(_)
+            | NowFileMessage::DirEntry(_)
+            | NowFileMessage::ListDirRsp(_)
+            | NowFileMessage::OpRsp(_) => Some(ServerToClient),
+            _ => Some(ClientToServer),
+        },
+        _ => None,
+    }
+}
+
+/// Returns the only direction the exec message could be sent in, or `None` if the message could
+/// be sent by both sides.
+pub(crate) fn exec_message_direction(message: &NowExecMessage<'_>) -> Option<Direction> {
+    match message {
+        // Data is sent in both directions (stdin/stdout/stderr).
+        NowExecMessage::Data(_) => None,
+        NowExecMessage::Started(_) | NowExecMessage::CancelRsp(_) | NowExecMessage::Result(_) => {
+            Some(Direction::ServerToClient)
+        }
+        _ => Some(Direction::ClientToServer),
+    }
+}
+
+/// Returns the capability which should be negotiated to exchange the message.
+fn required_capability(message: &NowMessage<'_>) -> Option<NowCapability> {
+    let capability = match message {
+        NowMessage::System(msg) => NowCapability::System(match msg {
+            NowSystemMessage::InfoReq(_) | NowSystemMessage::InfoRsp(_) => NowSystemCapsetFlags::INFO,
+            NowSystemMessage::Shutdown(_) => NowSystemCapsetFlags::SHUTDOWN,
+            NowSystemMessage::ShutdownAbort(_) => NowSystemCapsetFlags::SHUTDOWN_ABORT,
+            NowSystemMessage::PowerAction(_) => NowSystemCapsetFlags::POWER_ACTION,
+            // Response to either shutdown abort or power action request.
+            NowSystemMessage::PowerRsp(_) => return None,
+            NowSystemMessage::ProcessListReq(_)
+            | NowSystemMessage::ProcessInfo(_)
+            | NowSystemMessage::ProcessListRsp(_)
+            | NowSystemMessage::ProcessTerminate(_)
+            | NowSystemMessage::ProcessTerminateRsp(_) => NowSystemCapsetFlags::PROCESS,
+        }),
+        NowMessage::Session(msg) => NowCapability::Session(match msg {
+            NowSessionMessage::Lock(_) => NowSessionCapsetFlags::LOCK,
+            NowSessionMessage::Logoff(_) => NowSessionCapsetFlags::LOGOFF,
+            NowSessionMessage::MsgBoxReq(_) | NowSessionMessage::MsgBoxRsp(_) => NowSessionCapsetFlags::MSGBOX,
+            NowSessionMessage::SetKbdLayout(_) => NowSessionCapsetFlags::SET_KBD_LAYOUT,
+            NowSessionMessage::WindowRecStart(_)
+            | NowSessionMessage::WindowRecStop(_)
+            | NowSessionMessage::WindowRecEvent(_) => NowSessionCapsetFlags::WINDOW_RECORDING,
+        }),
+        NowMessage::Exec(msg) => {
+            let style = match msg {
+                NowExecMessage::Run(_) => NowExecCapsetFlags::STYLE_RUN,
+                NowExecMessage::Process(_) => NowExecCapsetFlags::STYLE_PROCESS,
+                NowExecMessage::Shell(_) => NowExecCapsetFlags::STYLE_SHELL,
+                NowExecMessage::Batch(_) => NowExecCapsetFlags::STYLE_BATCH,
+                NowExecMessage::WinPs(_) => NowExecCapsetFlags::STYLE_WINPS,
+                NowExecMessage::Pwsh(_) => NowExecCapsetFlags::STYLE_PWSH,
+                _ => return None,
+            };
+
+            let features = ExecFeatures::of(msg);
+            let mut flags = style;
+
+            if features.io_redirection {
+                flags |= NowExecCapsetFlags::IO_REDIRECTION;
+            }
+
+            if features.encoding {
+                flags |= NowExecCapsetFlags::UNICODE_CONSOLE;
+            }
+
+            NowCapability::Exec(flags)
+        }
+        NowMessage::File(NowFileMessage::Open(msg)) => NowCapability::File(match msg.mode() {
+            Ok(NowFileOpenMode::Read) => NowFileCapsetFlags::READ,
+            Ok(NowFileOpenMode::Write) => NowFileCapsetFlags::WRITE,
+            // Invalid open mode is reported by the peer when handling the request.
+            Err(_) => return None,
+        }),
+        NowMessage::File(
+            NowFileMessage::StatReq(_)
+            | NowFileMessage::StatRsp(_)
+            | NowFileMessage::ListDirReq(_)
+            | NowFileMessage::DirEntry(_)
+            | NowFileMessage::ListDirRsp(_),
+        ) => NowCapability::File(NowFileCapsetFlags::BROWSE),
+        NowMessage::File(NowFileMessage::Mkdir(_) | NowFileMessage::Delete(_) | NowFileMessage::Rename(_)) => {
+            NowCapability::File(NowFileCapsetFlags::MANAGE)
+        }
+        _ => return None,
+    };
+
+    Some(capability)
+}
+
+/// Returns the minimal protocol version required to exchange the message.
+fn required_version(message: &NowMessage<'_>) -> Option<NowProtoVersion> {
+    match message {
+        NowMessage::System(NowSystemMessage::Shutdown(_)) => None,
+        // All other system messages were introduced along with the file message class.
+        NowMessage::System(_) | NowMessage::File(_) => Some(MIN_SYSTEM_AND_FILE_VERSION),
+        NowMessage::Session(
+            NowSessionMessage::WindowRecStart(_)
+            | NowSessionMessage::WindowRecStop(_)
+            | NowSessionMessage::WindowRecEvent(_),
+        ) => Some(MIN_WINDOW_RECORDING_VERSION),
+        NowMessage::Rdm(_) => Some(MIN_RDM_ENABLED_VERSION),
+        NowMessage::Exec(msg) => {
+            let features = ExecFeatures::of(msg);
+
+            [
+                (
+                    features.io_redirection || features.run_directory,
+                    MIN_IO_REDIRECTION_VERSION,
+                ),
+                (features.server_mode, MIN_SERVER_MODE_VERSION),
+                (features.detached, MIN_DETACHED_VERSION),
+                (features.encoding, MIN_EXEC_ENCODING_VERSION),
+            ]
+            .into_iter()
+            .filter_map(|(requested, version)| requested.then_some(version))
+            .max()
+        }
+        _ => None,
+    }
+}
+
+/// Optional exec features requested by the exec message, which are gated by the negotiated
+/// protocol version or capabilities.
+#[derive(Debug, Default, Clone, Copy)]
+struct ExecFeatures {
+    io_redirection: bool,
+    /// Any of the encoding control flags (`RAW_ENCODING`, `UNICODE_CONSOLE`, `ENCODING_UTF8`).
+    encoding: bool,
+    detached: bool,
+    server_mode: bool,
+    run_directory: bool,
+}
+
+impl ExecFeatures {
+    fn of(message: &NowExecMessage<'_>) -> Self {
+        match message {
+            NowExecMessage::Run(msg) => Self {
+                run_directory: msg.directory().is_some(),
+                ..Self::default()
+            },
+            NowExecMessage::Process(msg) => Self {
+                io_redirection: msg.is_with_io_redirection(),
+                encoding: msg.is_encoding_utf8(),
+                detached: msg.is_detached(),
+                ..Self::default()
+            },
+            NowExecMessage::Shell(msg) => Self {
+                io_redirection: msg.is_with_io_redirection(),
+                detached: msg.is_detached(),
+                ..Self::default()
+            },
+            NowExecMessage::Batch(msg) => Self {
+                io_redirection: msg.is_with_io_redirection(),
+                encoding: msg.is_raw_encoding() || msg.is_unicode_console(),
+                detached: msg.is_detached(),
+                ..Self::default()
+            },
+            NowExecMessage::WinPs(msg) => Self {
+                io_redirection: msg.is_with_io_redirection(),
+                encoding: msg.is_raw_encoding() || msg.is_unicode_console(),
+                detached: msg.is_detached(),
+                server_mode: msg.is_server_mode(),
+                ..Self::default()
+            },
+            NowExecMessage::Pwsh(msg) => Self {
+                io_redirection: msg.is_with_io_redirection(),
+                encoding: msg.is_raw_encoding() || msg.is_unicode_console(),
+                detached: msg.is_detached(),
+                server_mode: msg.is_server_mode(),
+                ..Self::default()
+            },
+            _ => Self::default(),
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use now_proto_channel::NowChannelViolation;
+use now_proto_pdu::ironrdp_core::{encode_vec, Decode, IntoOwned, ReadCursor};
+use now_proto_pdu::{NowMessage, NowMessageFramer, OwnedNowMessage};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::{NowClientError, NowTransport};
+
+const READ_BUFFER_SIZE: usize = 8 * 1024;
+
+/// Message-level wrapper over the byte-stream transport.
+pub(crate) struct NowChannelTransport<T> {
+    transport: T,
+    framer: NowMessageFramer,
+    read_buffer: Box<[u8]>,
+}
+
+impl<T: NowTransport> NowChannelTransport<T> {
+    pub(crate) fn new(transport: T) -> Self {
+        Self {
+            transport,
+            framer: NowMessageFramer::new(),
+            read_buffer: vec![0u8; READ_BUFFER_SIZE].into_boxed_slice(),
+        }
+    }
+
+    /// Reads the next message from the transport. Returns `None` if the transport is closed.
+    ///
+    /// Messages which could not be decoded are skipped. This method is cancel safe.
+    pub(crate) async fn read_message(&mut self) -> Result<Option<OwnedNowMessage>, NowClientError> {
+        loop {
+            if let Some(frame) = self.framer.next_frame()? {
+                match NowMessage::decode(&mut ReadCursor::new(frame)) {
+                    Ok(message) => return Ok(Some(message.into_owned())),
+                    Err(error) => {
+                        tracing::debug!(%error, "Skipping undecodable NOW-PROTO message");
+                        continue;
+                    }
+                }
+            }
+
+            let read = self.transport.read(&mut self.read_buffer).await?;
+            if read == 0 {
+                return Ok(None);
+            }
+
+            self.framer.push(&self.read_buffer[..read])?;
+        }
+    }
+
+    pub(crate) async fn write_message(&mut self, message: &NowMessage<'_>) -> Result<(), NowClientError> {
+        let encoded = encode_vec(message)?;
+        self.transport.write_all(&encoded).await?;
+        self.transport.flush().await?;
+
+        Ok(())
+    }
+
+    /// Closes the channel reporting the protocol violation to the peer. Write errors are only
+    /// logged, as the violation is more relevant to the caller.
+    pub(crate) async fn close_with_violation(&mut self, violation: &NowChannelViolation) {
+        tracing::warn!(%violation, "Closing NOW-PROTO channel due to protocol violation");
+
+        let message = violation
+            .to_close_msg()
+            .expect("status without message is always encodable");
+
+        if let Err(error) = self.write_message(&message.into()).await {
+            tracing::debug!(%error, "Failed to send channel close message");
+        }
+    }
+}
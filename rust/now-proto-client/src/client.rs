@@ -0,0 +1,755 @@
+use core::time::Duration;
+use std::time::SystemTime;
+
+use now_proto_channel::{NowChannelRole, NowChannelState, NowHeartbeatSupervisor};
+use now_proto_pdu::ironrdp_core::{EncodeResult, IntoOwned};
+use now_proto_pdu::{
+    NowChannelCapsetMsg, NowChannelMessage, NowExecBatchMsg, NowExecCapsetFlags, NowExecProcessMsg, NowExecPwshMsg,
+    NowExecRunMsg, NowExecShellMsg, NowExecWinPsMsg, NowFileCapsetFlags, NowFileDeleteMsg, NowFileListDirReqMsg,
+    NowFileMkdirMsg, NowFileOpenMode, NowFileOpenMsg, NowFileRenameMsg, NowFileStatReqMsg, NowMessage,
+    NowMsgBoxResponse, NowProtoVersion, NowRdmAppActionMsg, NowRdmAppStartMsg, NowRdmCapabilitiesMsg, NowRdmMessage,
+    NowRdmSessionActionMsg, NowRdmSessionStartMsg, NowSessionCapsetFlags, NowSessionLockMsg, NowSessionLogoffMsg,
+    NowSessionMsgBoxReqMsg, NowSessionSetKbdLayoutMsg, NowSessionWindowRecStartMsg, NowSessionWindowRecStopMsg,
+    NowSystemCapsetFlags, NowSystemInfoReqMsg, NowSystemPowerActionMsg, NowSystemProcessListReqMsg,
+    NowSystemProcessTerminateMsg, NowSystemShutdownAbortMsg, NowSystemShutdownMsg, OwnedNowFileStatRspMsg,
+    OwnedNowMessage, OwnedNowRdmAppNotifyMsg, OwnedNowRdmCapabilitiesMsg, OwnedNowRdmSessionNotifyMsg,
+    OwnedNowSessionWindowRecEventMsg, OwnedNowSystemInfoRspMsg, OwnedNowSystemProcessInfoMsg,
+};
+use tokio::sync::{broadcast, mpsc, OnceCell};
+use tokio::task::JoinHandle;
+
+use crate::channel::NowChannelTransport;
+use crate::file::FileTransferEvent;
+use crate::worker::{Command, CommandSender, IdKind, Worker};
+use crate::{NowClientError, NowExecSession, NowFileDirPage, NowFileTransfer, NowTransport};
+
+/// Client -> worker command queue capacity.
+const COMMAND_CHANNEL_CAPACITY: usize = 1024;
+
+/// Number of unsolicited server events buffered for each [`NowClient::subscribe`] receiver.
+const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+/// Minimal protocol version supporting RDM messages.
+const MIN_RDM_ENABLED_VERSION: NowProtoVersion = NowProtoVersion { major: 1, minor: 3 };
+
+/// Unsolicited event sent by the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NowClientEvent {
+    /// Window recording event, see [`NowClient::session_window_rec_start`].
+    WindowRecEvent(OwnedNowSessionWindowRecEventMsg),
+    /// RDM application state notification.
+    RdmAppNotify(OwnedNowRdmAppNotifyMsg),
+    /// RDM session state notification.
+    RdmSessionNotify(OwnedNowRdmSessionNotifyMsg),
+}
+
+/// [`NowClient`] connection settings.
+#[derive(Debug, Clone)]
+pub struct NowClientConfig {
+    capabilities: NowChannelCapsetMsg,
+    connect_timeout: Duration,
+    response_timeout: Duration,
+}
+
+impl Default for NowClientConfig {
+    fn default() -> Self {
+        // Support all capabilities by default on client side.
+        let capabilities = NowChannelCapsetMsg::default()
+            .with_system_capset(NowSystemCapsetFlags::all())
+            .with_session_capset(NowSessionCapsetFlags::all())
+            .with_exec_capset(NowExecCapsetFlags::all())
+            .with_file_capset(NowFileCapsetFlags::all())
+            .with_heartbeat_interval(Duration::from_secs(60))
+            .expect("default heartbeat interval is valid");
+
+        Self {
+            capabilities,
+            connect_timeout: Duration::from_secs(10),
+            response_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl NowClientConfig {
+    /// Sets client capabilities sent to the server during negotiation.
+    #[must_use]
+    pub fn with_capabilities(mut self, capabilities: NowChannelCapsetMsg) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Sets timeout for the server capabilities response.
+    #[must_use]
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    /// Sets timeout for requests which are expected to be answered by the server immediately
+    /// (e.g. RDM capabilities exchange).
+    #[must_use]
+    pub fn with_response_timeout(mut self, timeout: Duration) -> Self {
+        self.response_timeout = timeout;
+        self
+    }
+}
+
+/// NOW-PROTO remote execution channel client.
+///
+/// Rust counterpart of the .NET `NowClient`.
+#[derive(Debug)]
+pub struct NowClient {
+    capabilities: NowChannelCapsetMsg,
+    response_timeout: Duration,
+    commands: CommandSender,
+    events: broadcast::Sender<NowClientEvent>,
+    worker: JoinHandle<Result<(), NowClientError>>,
+    rdm_capabilities: OnceCell<OwnedNowRdmCapabilitiesMsg>,
+}
+
+impl NowClient {
+    /// Performs connection and negotiation sequence with default settings, see
+    /// [`NowClient::connect_with_config`].
+    pub async fn connect(transport: impl NowTransport) -> Result<Self, NowClientError> {
+        Self::connect_with_config(transport, NowClientConfig::default()).await
+    }
+
+    /// Performs connection and negotiation sequence to NOW-PROTO server over the provided
+    /// transport and returns a client ready to accept commands.
+    ///
+    /// Background worker task is spawned on the current tokio runtime.
+    pub async fn connect_with_config(
+        transport: impl NowTransport,
+        config: NowClientConfig,
+    ) -> Result<Self, NowClientError> {
+        let mut channel = NowChannelTransport::new(transport);
+        let mut state = NowChannelState::new(NowChannelRole::Client, config.capabilities.version());
+
+        let client_capabilities = config.capabilities.into();
+        state.on_outgoing(&client_capabilities)?;
+        channel.write_message(&client_capabilities).await?;
+
+        // Server responds with capabilities downgraded to the common subset.
+        let capabilities = tokio::time::timeout(config.connect_timeout, read_capabilities(&mut channel, &mut state))
+            .await
+            .map_err(|_| NowClientError::Timeout)??;
+
+        tracing::debug!(?capabilities, "NOW channel negotiation complete");
+
+        let heartbeat = NowHeartbeatSupervisor::new(NowChannelRole::Client, &capabilities);
+
+        let (commands_tx, commands_rx) = mpsc::channel(COMMAND_CHANNEL_CAPACITY);
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+
+        let worker = Worker::new(channel, state, commands_rx, events.clone(), heartbeat);
+        let worker = tokio::spawn(worker.run());
+
+        Ok(Self {
+            capabilities,
+            response_timeout: config.response_timeout,
+            commands: CommandSender::new(commands_tx),
+            events,
+            worker,
+            rdm_capabilities: OnceCell::new(),
+        })
+    }
+
+    /// Capabilities negotiated with the server.
+    pub fn capabilities(&self) -> &NowChannelCapsetMsg {
+        &self.capabilities
+    }
+
+    /// Subscribes to unsolicited server events (window recording, RDM notifications).
+    ///
+    /// Only events received after this call are delivered to the returned receiver.
+    pub fn subscribe(&self) -> broadcast::Receiver<NowClientEvent> {
+        self.events.subscribe()
+    }
+
+    /// Gracefully closes the NOW-PROTO channel and waits for the background worker to exit.
+    ///
+    /// Returns the reason of the worker termination if it has already exited with an error (e.g.
+    /// transport error or heartbeat timeout).
+    pub async fn close(self) -> Result<(), NowClientError> {
+        // Worker could have already exited, its result is returned below.
+        let _ = self.commands.request(|ack| Command::Close { ack }).await;
+
+        self.worker.await.map_err(|_| NowClientError::ChannelClosed)?
+    }
+
+    // -- System --
+
+    /// Queries the remote system information (OS, hostname, uptime, etc.).
+    ///
+    /// Information fields which are not available on the remote host are not set in the response.
+    pub async fn system_info(&self) -> Result<OwnedNowSystemInfoRspMsg, NowClientError> {
+        self.ensure_system_capability(NowSystemCapsetFlags::INFO, "System info")?;
+
+        let request_id = self.allocate_id(IdKind::Request).await?;
+        let message = NowSystemInfoReqMsg::new(request_id);
+
+        let response = self
+            .commands
+            .request(|response| Command::SystemInfo { message, response });
+
+        let response = tokio::time::timeout(self.response_timeout, response)
+            .await
+            .map_err(|_| NowClientError::Timeout)??;
+
+        response.to_result()?;
+
+        Ok(response)
+    }
+
+    /// Sends system shutdown command.
+    pub async fn system_shutdown(&self, message: NowSystemShutdownMsg<'_>) -> Result<(), NowClientError> {
+        self.ensure_system_capability(NowSystemCapsetFlags::SHUTDOWN, "Shutdown")?;
+
+        self.commands.send_message(message.into()).await
+    }
+
+    /// Aborts pending system shutdown or power action, and waits for the server response.
+    pub async fn system_shutdown_abort(&self) -> Result<(), NowClientError> {
+        self.ensure_system_capability(NowSystemCapsetFlags::SHUTDOWN_ABORT, "Shutdown abort")?;
+
+        let request_id = self.allocate_id(IdKind::Request).await?;
+        let message = NowSystemShutdownAbortMsg::new(request_id).into();
+
+        self.status_request(request_id, message).await
+    }
+
+    /// Requests system power action, and waits for the server to schedule it.
+    ///
+    /// `build` receives the request ID allocated by the client and returns the power action
+    /// request to send, e.g.
+    /// `client.system_power_action(|id| Ok(NowSystemPowerActionMsg::new(id, NowSystemPowerAction::SLEEP)))`.
+    pub async fn system_power_action<'a, F>(&self, build: F) -> Result<(), NowClientError>
+    where
+        F: FnOnce(u32) -> EncodeResult<NowSystemPowerActionMsg<'a>>,
+    {
+        self.ensure_system_capability(NowSystemCapsetFlags::POWER_ACTION, "Power action")?;
+
+        let request_id = self.allocate_id(IdKind::Request).await?;
+        let message = NowMessage::from(build(request_id)?).into_owned();
+
+        self.status_request(request_id, message).await
+    }
+
+    /// Lists processes running on the remote host.
+    pub async fn system_process_list(&self) -> Result<Vec<OwnedNowSystemProcessInfoMsg>, NowClientError> {
+        self.ensure_system_capability(NowSystemCapsetFlags::PROCESS, "Process list")?;
+
+        let request_id = self.allocate_id(IdKind::Request).await?;
+        let message = NowSystemProcessListReqMsg::new(request_id);
+
+        let response = self
+            .commands
+            .request(|response| Command::ProcessList { message, response });
+
+        tokio::time::timeout(self.response_timeout, response)
+            .await
+            .map_err(|_| NowClientError::Timeout)??
+            .map_err(NowClientError::Status)
+    }
+
+    /// Terminates the remote process. If `force` is set, the process is killed immediately
+    /// instead of being requested to exit.
+    pub async fn system_process_terminate(&self, pid: u32, force: bool) -> Result<(), NowClientError> {
+        self.ensure_system_capability(NowSystemCapsetFlags::PROCESS, "Process termination")?;
+
+        let request_id = self.allocate_id(IdKind::Request).await?;
+        let mut message = NowSystemProcessTerminateMsg::new(request_id, pid);
+
+        if force {
+            message = message.with_force();
+        }
+
+        self.status_request(request_id, message.into()).await
+    }
+
+    // -- Session --
+
+    /// Sends session lock command.
+    pub async fn session_lock(&self) -> Result<(), NowClientError> {
+        self.ensure_session_capability(NowSessionCapsetFlags::LOCK, "Session lock")?;
+
+        self.commands.send_message(NowSessionLockMsg::default().into()).await
+    }
+
+    /// Sends session logoff command.
+    pub async fn session_logoff(&self) -> Result<(), NowClientError> {
+        self.ensure_session_capability(NowSessionCapsetFlags::LOGOFF, "Session logoff")?;
+
+        self.commands.send_message(NowSessionLogoffMsg::default().into()).await
+    }
+
+    /// Shows message box in the user session and waits for the user response.
+    ///
+    /// `build` receives the request ID allocated by the client and returns the message box
+    /// request to send, e.g. `client.msg_box(|id| NowSessionMsgBoxReqMsg::new(id, "Hello"))`.
+    /// Response is always requested from the server.
+    pub async fn msg_box<'a, F>(&self, build: F) -> Result<NowMsgBoxResponse, NowClientError>
+    where
+        F: FnOnce(u32) -> EncodeResult<NowSessionMsgBoxReqMsg<'a>>,
+    {
+        self.ensure_session_capability(NowSessionCapsetFlags::MSGBOX, "Message box")?;
+
+        let request_id = self.allocate_id(IdKind::MsgBox).await?;
+        let message = build(request_id)?.with_response().into_owned();
+
+        self.commands
+            .request(|response| Command::MsgBox { message, response })
+            .await?
+    }
+
+    /// Shows message box in the user session without waiting for the user response.
+    ///
+    /// `build` receives the request ID allocated by the client, see [`NowClient::msg_box`].
+    pub async fn msg_box_no_response<'a, F>(&self, build: F) -> Result<(), NowClientError>
+    where
+        F: FnOnce(u32) -> EncodeResult<NowSessionMsgBoxReqMsg<'a>>,
+    {
+        self.ensure_session_capability(NowSessionCapsetFlags::MSGBOX, "Message box")?;
+
+        let request_id = self.allocate_id(IdKind::MsgBox).await?;
+        let message = build(request_id)?;
+
+        self.commands.send_message(message.into()).await
+    }
+
+    /// Sets keyboard layout for the active foreground window.
+    pub async fn session_set_kbd_layout(&self, message: NowSessionSetKbdLayoutMsg<'_>) -> Result<(), NowClientError> {
+        self.ensure_session_capability(NowSessionCapsetFlags::SET_KBD_LAYOUT, "Keyboard layout change")?;
+
+        self.commands.send_message(message.into()).await
+    }
+
+    /// Starts window recording. Window recording events are delivered as
+    /// [`NowClientEvent::WindowRecEvent`], see [`NowClient::subscribe`].
+    pub async fn session_window_rec_start(&self, message: NowSessionWindowRecStartMsg) -> Result<(), NowClientError> {
+        self.ensure_session_capability(NowSessionCapsetFlags::WINDOW_RECORDING, "Window recording")?;
+
+        self.commands.send_message(message.into()).await
+    }
+
+    /// Stops window recording.
+    pub async fn session_window_rec_stop(&self) -> Result<(), NowClientError> {
+        self.ensure_session_capability(NowSessionCapsetFlags::WINDOW_RECORDING, "Window recording")?;
+
+        self.commands
+            .send_message(NowSessionWindowRecStopMsg::default().into())
+            .await
+    }
+
+    // -- Exec --
+
+    /// Starts a new simple remote execution session. "Run" execution style does not report
+    /// execution status or output.
+    ///
+    /// `build` receives the session ID allocated by the client and returns the exec request.
+    pub async fn exec_run<'a, F>(&self, build: F) -> Result<(), NowClientError>
+    where
+        F: FnOnce(u32) -> EncodeResult<NowExecRunMsg<'a>>,
+    {
+        self.ensure_exec_capability(NowExecCapsetFlags::STYLE_RUN, "Run execution style")?;
+
+        let session_id = self.allocate_id(IdKind::ExecSession).await?;
+        let message = build(session_id)?;
+
+        self.commands.send_message(message.into()).await
+    }
+
+    /// Starts a new process remote execution session.
+    ///
+    /// `build` receives the session ID allocated by the client and returns the exec request.
+    pub async fn exec_process<'a, F>(&self, build: F) -> Result<NowExecSession, NowClientError>
+    where
+        F: FnOnce(u32) -> EncodeResult<NowExecProcessMsg<'a>>,
+    {
+        self.ensure_exec_capability(NowExecCapsetFlags::STYLE_PROCESS, "Process execution style")?;
+
+        let session_id = self.allocate_id(IdKind::ExecSession).await?;
+        let message = build(session_id)?;
+
+        self.exec_session(message.session_id(), message.into()).await
+    }
+
+    /// Starts a new shell remote execution session.
+    ///
+    /// `build` receives the session ID allocated by the client and returns the exec request, e.g.
+    /// `client.exec_shell(|id| NowExecShellMsg::new(id, "echo hello"))`.
+    pub async fn exec_shell<'a, F>(&self, build: F) -> Result<NowExecSession, NowClientError>
+    where
+        F: FnOnce(u32) -> EncodeResult<NowExecShellMsg<'a>>,
+    {
+        self.ensure_exec_capability(NowExecCapsetFlags::STYLE_SHELL, "Shell execution style")?;
+
+        let session_id = self.allocate_id(IdKind::ExecSession).await?;
+        let message = build(session_id)?;
+
+        self.exec_session(message.session_id(), message.into()).await
+    }
+
+    /// Starts a new batch remote execution session.
+    ///
+    /// `build` receives the session ID allocated by the client and returns the exec request.
+    pub async fn exec_batch<'a, F>(&self, build: F) -> Result<NowExecSession, NowClientError>
+    where
+        F: FnOnce(u32) -> EncodeResult<NowExecBatchMsg<'a>>,
+    {
+        self.ensure_exec_capability(NowExecCapsetFlags::STYLE_BATCH, "Batch execution style")?;
+
+        let session_id = self.allocate_id(IdKind::ExecSession).await?;
+        let message = build(session_id)?;
+
+        self.exec_session(message.session_id(), message.into()).await
+    }
+
+    /// Starts a new Windows PowerShell remote execution session.
+    ///
+    /// `build` receives the session ID allocated by the client and returns the exec request.
+    pub async fn exec_winps<'a, F>(&self, build: F) -> Result<NowExecSession, NowClientError>
+    where
+        F: FnOnce(u32) -> EncodeResult<NowExecWinPsMsg<'a>>,
+    {
+        self.ensure_exec_capability(NowExecCapsetFlags::STYLE_WINPS, "Windows PowerShell execution style")?;
+
+        let session_id = self.allocate_id(IdKind::ExecSession).await?;
+        let message = build(session_id)?;
+
+        self.exec_session(message.session_id(), message.into()).await
+    }
+
+    /// Starts a new PowerShell 7 remote execution session.
+    ///
+    /// `build` receives the session ID allocated by the client and returns the exec request.
+    pub async fn exec_pwsh<'a, F>(&self, build: F) -> Result<NowExecSession, NowClientError>
+    where
+        F: FnOnce(u32) -> EncodeResult<NowExecPwshMsg<'a>>,
+    {
+        self.ensure_exec_capability(NowExecCapsetFlags::STYLE_PWSH, "Pwsh execution style")?;
+
+        let session_id = self.allocate_id(IdKind::ExecSession).await?;
+        let message = build(session_id)?;
+
+        self.exec_session(message.session_id(), message.into()).await
+    }
+
+    // -- File --
+
+    /// Opens a remote file for reading or writing, and waits for the server to accept the transfer.
+    ///
+    /// `build` receives the transfer ID allocated by the client and returns the open request, e.g.
+    /// `client.file_open(|id| NowFileOpenMsg::new(id, NowFileOpenMode::Read, "C:\\log.txt"))`.
+    /// The transfer should be completed with [`NowFileTransfer::close`].
+    pub async fn file_open<'a, F>(&self, build: F) -> Result<NowFileTransfer, NowClientError>
+    where
+        F: FnOnce(u32) -> EncodeResult<NowFileOpenMsg<'a>>,
+    {
+        let transfer_id = self.allocate_id(IdKind::FileTransfer).await?;
+        let message = build(transfer_id)?;
+
+        let mode = message.mode()?;
+        match mode {
+            NowFileOpenMode::Read => self.ensure_file_capability(NowFileCapsetFlags::READ, "File read")?,
+            NowFileOpenMode::Write => self.ensure_file_capability(NowFileCapsetFlags::WRITE, "File write")?,
+        }
+
+        let offset = message.offset();
+        let message = NowMessage::from(message).into_owned();
+        let (events_tx, mut events_rx) = mpsc::unbounded_channel();
+
+        self.commands
+            .request(|ack| Command::FileOpen {
+                transfer_id,
+                message,
+                events: events_tx,
+                ack,
+            })
+            .await?;
+
+        let response = async {
+            match events_rx.recv().await {
+                Some(FileTransferEvent::Opened(result)) => result.map_err(NowClientError::Status),
+                // Chunks and close response are never sent before the open response.
+                _ => Err(NowClientError::ChannelClosed),
+            }
+        };
+
+        let file_size = tokio::time::timeout(self.response_timeout, response)
+            .await
+            .map_err(|_| NowClientError::Timeout)??;
+
+        // Write transfers start at the offset chosen by the server.
+        let offset = match mode {
+            NowFileOpenMode::Read => offset,
+            NowFileOpenMode::Write => file_size,
+        };
+
+        Ok(NowFileTransfer::new(
+            transfer_id,
+            mode,
+            file_size,
+            offset,
+            self.commands.clone(),
+            events_rx,
+            self.response_timeout,
+        ))
+    }
+
+    /// Queries information about the remote filesystem entry. Symbolic links are not followed.
+    pub async fn file_stat(&self, path: &str) -> Result<OwnedNowFileStatRspMsg, NowClientError> {
+        self.ensure_file_capability(NowFileCapsetFlags::BROWSE, "File stat")?;
+
+        let request_id = self.allocate_id(IdKind::Request).await?;
+        let message = NowFileStatReqMsg::new(request_id, path)?.into_owned();
+
+        let response = self.commands.request(|response| Command::FileStat {
+            request_id,
+            message: message.into(),
+            response,
+        });
+
+        let response = tokio::time::timeout(self.response_timeout, response)
+            .await
+            .map_err(|_| NowClientError::Timeout)??;
+
+        response.to_result()?;
+
+        Ok(response)
+    }
+
+    /// Lists a page of at most `max_entries` remote directory entries (or all entries if
+    /// `max_entries` is `0`) starting at `start_index`. The next page, if any, starts at
+    /// `start_index` plus the number of returned entries.
+    pub async fn file_list_dir(
+        &self,
+        path: &str,
+        start_index: u32,
+        max_entries: u32,
+    ) -> Result<NowFileDirPage, NowClientError> {
+        self.ensure_file_capability(NowFileCapsetFlags::BROWSE, "Directory listing")?;
+
+        let request_id = self.allocate_id(IdKind::Request).await?;
+        let message = NowFileListDirReqMsg::new(request_id, path)?
+            .with_page(start_index, max_entries)
+            .into_owned();
+
+        let response = self.commands.request(|response| Command::ListDir { message, response });
+
+        tokio::time::timeout(self.response_timeout, response)
+            .await
+            .map_err(|_| NowClientError::Timeout)??
+            .map_err(NowClientError::Status)
+    }
+
+    /// Creates the remote directory. If `recursive` is set, missing parent directories are
+    /// created as well, and existing directory is not an error.
+    pub async fn file_mkdir(&self, path: &str, recursive: bool) -> Result<(), NowClientError> {
+        self.ensure_file_capability(NowFileCapsetFlags::MANAGE, "Directory creation")?;
+
+        let request_id = self.allocate_id(IdKind::Request).await?;
+        let mut message = NowFileMkdirMsg::new(request_id, path)?;
+
+        if recursive {
+            message = message.with_recursive();
+        }
+
+        self.status_request(request_id, NowMessage::from(message).into_owned())
+            .await
+    }
+
+    /// Deletes the remote file or directory. Non-empty directories are only deleted if
+    /// `recursive` is set.
+    pub async fn file_delete(&self, path: &str, recursive: bool) -> Result<(), NowClientError> {
+        self.ensure_file_capability(NowFileCapsetFlags::MANAGE, "File deletion")?;
+
+        let request_id = self.allocate_id(IdKind::Request).await?;
+        let mut message = NowFileDeleteMsg::new(request_id, path)?;
+
+        if recursive {
+            message = message.with_recursive();
+        }
+
+        self.status_request(request_id, NowMessage::from(message).into_owned())
+            .await
+    }
+
+    /// Renames or moves the remote file or directory. Existing destination is only replaced if
+    /// `replace` is set.
+    pub async fn file_rename(&self, path: &str, new_path: &str, replace: bool) -> Result<(), NowClientError> {
+        self.ensure_file_capability(NowFileCapsetFlags::MANAGE, "File rename")?;
+
+        let request_id = self.allocate_id(IdKind::Request).await?;
+        let mut message = NowFileRenameMsg::new(request_id, path, new_path)?;
+
+        if replace {
+            message = message.with_replace();
+        }
+
+        self.status_request(request_id, NowMessage::from(message).into_owned())
+            .await
+    }
+
+    // -- RDM --
+
+    /// Performs RDM capabilities exchange with the server and returns server RDM capabilities.
+    pub async fn rdm_sync(&self) -> Result<OwnedNowRdmCapabilitiesMsg, NowClientError> {
+        let negotiated = self.capabilities.version();
+        if negotiated < MIN_RDM_ENABLED_VERSION {
+            return Err(NowClientError::VersionNotSupported {
+                required: MIN_RDM_ENABLED_VERSION,
+                negotiated,
+            });
+        }
+
+        let timestamp = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+
+        let message = NowRdmCapabilitiesMsg::new(timestamp, "")?;
+
+        let response = self
+            .commands
+            .request(|response| Command::RdmCapabilities { message, response });
+
+        tokio::time::timeout(self.response_timeout, response)
+            .await
+            .map_err(|_| NowClientError::Timeout)?
+    }
+
+    /// Returns server RDM capabilities. Capabilities exchange is performed on the first call.
+    pub async fn rdm_capabilities(&self) -> Result<&OwnedNowRdmCapabilitiesMsg, NowClientError> {
+        self.rdm_capabilities.get_or_try_init(|| self.rdm_sync()).await
+    }
+
+    /// Starts RDM application on the server.
+    pub async fn rdm_app_start(&self, message: NowRdmAppStartMsg) -> Result<(), NowClientError> {
+        self.rdm_capabilities().await?;
+
+        self.commands
+            .send_message(NowMessage::Rdm(NowRdmMessage::AppStart(message)))
+            .await
+    }
+
+    /// Sends action command to the RDM application.
+    pub async fn rdm_app_action(&self, message: NowRdmAppActionMsg<'_>) -> Result<(), NowClientError> {
+        self.rdm_capabilities().await?;
+
+        self.commands
+            .send_message(NowMessage::Rdm(NowRdmMessage::AppAction(message)))
+            .await
+    }
+
+    /// Starts a new RDM session.
+    pub async fn rdm_session_start(&self, message: NowRdmSessionStartMsg<'_>) -> Result<(), NowClientError> {
+        self.rdm_capabilities().await?;
+
+        self.commands
+            .send_message(NowMessage::Rdm(NowRdmMessage::SessionStart(message)))
+            .await
+    }
+
+    /// Sends action (focus, close) to the RDM session.
+    pub async fn rdm_session_action(&self, message: NowRdmSessionActionMsg) -> Result<(), NowClientError> {
+        self.rdm_capabilities().await?;
+
+        self.commands
+            .send_message(NowMessage::Rdm(NowRdmMessage::SessionAction(message)))
+            .await
+    }
+
+    /// Allocates ID which is not used by any pending request or active session of its kind.
+    async fn allocate_id(&self, kind: IdKind) -> Result<u32, NowClientError> {
+        self.commands
+            .request(|response| Command::AllocateId { kind, response })
+            .await
+    }
+
+    async fn exec_session(&self, session_id: u32, message: NowMessage<'_>) -> Result<NowExecSession, NowClientError> {
+        let message = message.into_owned();
+        let (events_tx, events_rx) = mpsc::unbounded_channel();
+
+        self.commands
+            .request(|ack| Command::ExecStart {
+                session_id,
+                message,
+                events: events_tx,
+                ack,
+            })
+            .await?;
+
+        Ok(NowExecSession::new(session_id, self.commands.clone(), events_rx))
+    }
+
+    async fn status_request(&self, request_id: u32, message: OwnedNowMessage) -> Result<(), NowClientError> {
+        let response = self.commands.request(|response| Command::StatusRequest {
+            request_id,
+            message,
+            response,
+        });
+
+        tokio::time::timeout(self.response_timeout, response)
+            .await
+            .map_err(|_| NowClientError::Timeout)??
+            .map_err(NowClientError::Status)
+    }
+
+    fn ensure_system_capability(&self, flag: NowSystemCapsetFlags, name: &'static str) -> Result<(), NowClientError> {
+        if !self.capabilities.system_capset().contains(flag) {
+            return Err(NowClientError::Unsupported(name));
+        }
+
+        Ok(())
+    }
+
+    fn ensure_session_capability(&self, flag: NowSessionCapsetFlags, name: &'static str) -> Result<(), NowClientError> {
+        if !self.capabilities.session_capset().contains(flag) {
+            return Err(NowClientError::Unsupported(name));
+        }
+
+        Ok(())
+    }
+
+    fn ensure_file_capability(&self, flag: NowFileCapsetFlags, name: &'static str) -> Result<(), NowClientError> {
+        if !self.capabilities.file_capset().contains(flag) {
+            return Err(NowClientError::Unsupported(name));
+        }
+
+        Ok(())
+    }
+
+    fn ensure_exec_capability(&self, flag: NowExecCapsetFlags, name: &'static str) -> Result<(), NowClientError> {
+        if !self.capabilities.exec_capset().contains(flag) {
+            return Err(NowClientError::Unsupported(name));
+        }
+
+        Ok(())
+    }
+}
+
+async fn read_capabilities<T: NowTransport>(
+    channel: &mut NowChannelTransport<T>,
+    state: &mut NowChannelState,
+) -> Result<NowChannelCapsetMsg, NowClientError> {
+    let message = channel.read_message().await?.ok_or(NowClientError::ChannelClosed)?;
+
+    if let Err(violation) = state.on_incoming(&message) {
+        channel.close_with_violation(&violation).await;
+        return Err(violation.into());
+    }
+
+    match message {
+        NowMessage::Channel(NowChannelMessage::Capset(_)) => Ok(state
+            .negotiated_capabilities()
+            .expect("negotiated capabilities are set")
+            .clone()),
+        NowMessage::Channel(NowChannelMessage::Close(msg)) => {
+            msg.to_result()?;
+            Err(NowClientError::ChannelClosed)
+        }
+        // Any other message is rejected by the channel state.
+        _ => Err(NowClientError::ChannelClosed),
+    }
+}
@@ -0,0 +1,99 @@
+use core::fmt;
+use std::io;
+
+use now_proto_channel::NowChannelViolation;
+use now_proto_pdu::ironrdp_core::{DecodeError, EncodeError};
+use now_proto_pdu::{NowProtoVersion, NowStatusError};
+
+/// Error returned by [`NowClient`](crate::NowClient) operations.
+#[derive(Debug)]
+pub enum NowClientError {
+    /// Underlying transport error.
+    Io(io::Error),
+    /// Received message can't be decoded.
+    Decode(DecodeError),
+    /// Message can't be encoded (e.g. too long string parameter).
+    Encode(EncodeError),
+    /// Remote host returned an error status.
+    Status(NowStatusError),
+    /// Operation is not supported by the server (not present in negotiated capabilities).
+    Unsupported(&'static str),
+    /// Operation requires a newer protocol version than negotiated one.
+    VersionNotSupported {
+        required: NowProtoVersion,
+        negotiated: NowProtoVersion,
+    },
+    /// Server has violated the protocol (e.g. sent a message before negotiation), the channel
+    /// has been closed by the client.
+    Protocol(NowChannelViolation),
+    /// Operation has not completed in time.
+    Timeout,
+    /// Server has not sent heartbeat message in time.
+    HeartbeatTimeout,
+    /// NOW-PROTO channel is closed.
+    ChannelClosed,
+}
+
+impl fmt::Display for NowClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NowClientError::Io(_) => write!(f, "NOW-PROTO transport error"),
+            NowClientError::Decode(_) => write!(f, "failed to decode NOW-PROTO message"),
+            NowClientError::Encode(_) => write!(f, "failed to encode NOW-PROTO message"),
+            NowClientError::Status(_) => write!(f, "remote host returned an error"),
+            NowClientError::Unsupported(what) => write!(f, "{what} is not supported by server"),
+            NowClientError::VersionNotSupported { required, negotiated } => write!(
+                f,
+                "operation requires NOW-PROTO version {}.{} or higher (negotiated {}.{})",
+                required.major, required.minor, negotiated.major, negotiated.minor
+            ),
+            NowClientError::Protocol(_) => write!(f, "NOW-PROTO protocol violation"),
+            NowClientError::Timeout => write!(f, "operation timed out"),
+            NowClientError::HeartbeatTimeout => write!(f, "server heartbeat timeout"),
+            NowClientError::ChannelClosed => write!(f, "NOW-PROTO channel is closed"),
+        }
+    }
+}
+
+impl core::error::Error for NowClientError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            NowClientError::Io(err) => Some(err),
+            NowClientError::Decode(err) => Some(err),
+            NowClientError::Encode(err) => Some(err),
+            NowClientError::Status(err) => Some(err),
+            NowClientError::Protocol(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<NowChannelViolation> for NowClientError {
+    fn from(err: NowChannelViolation) -> Self {
+        NowClientError::Protocol(err)
+    }
+}
+
+impl From<io::Error> for NowClientError {
+    fn from(err: io::Error) -> Self {
+        NowClientError::Io(err)
+    }
+}
+
+impl From<DecodeError> for NowClientError {
+    fn from(err: DecodeError) -> Self {
+        NowClientError::Decode(err)
+    }
+}
+
+impl From<EncodeError> for NowClientError {
+    fn from(err: EncodeError) -> Self {
+        NowClientError::Encode(err)
+    }
+}
+
+impl From<NowStatusError> for NowClientError {
+    fn from(err: NowStatusError) -> Self {
+        NowClientError::Status(err)
+    }
+}
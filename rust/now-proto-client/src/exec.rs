@@ -0,0 +1,93 @@
+use now_proto_pdu::{NowExecAbortMsg, NowExecDataMsg, NowExecDataStreamKind, NowMessage, NowStatusError};
+use tokio::sync::mpsc;
+
+use crate::worker::{Command, CommandSender};
+use crate::NowClientError;
+
+/// Event received from the remote execution session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NowExecEvent {
+    /// Remote process has been started.
+    Started,
+    /// Data received from the remote process stdout (only with IO redirection enabled).
+    Stdout { data: Vec<u8>, last: bool },
+    /// Data received from the remote process stderr (only with IO redirection enabled).
+    Stderr { data: Vec<u8>, last: bool },
+    /// Execution session is finished, contains process exit code or execution error. This is
+    /// always the last event of the session.
+    Finished(Result<u32, NowStatusError>),
+}
+
+/// Active remote execution session.
+///
+/// Returned by [`NowClient`](crate::NowClient) exec methods.
+#[derive(Debug)]
+pub struct NowExecSession {
+    session_id: u32,
+    commands: CommandSender,
+    events: mpsc::UnboundedReceiver<NowExecEvent>,
+}
+
+impl NowExecSession {
+    pub(crate) fn new(session_id: u32, commands: CommandSender, events: mpsc::UnboundedReceiver<NowExecEvent>) -> Self {
+        Self {
+            session_id,
+            commands,
+            events,
+        }
+    }
+
+    pub fn session_id(&self) -> u32 {
+        self.session_id
+    }
+
+    /// Waits for the next session event.
+    ///
+    /// Returns `None` once [`NowExecEvent::Finished`] has been received or if the channel is
+    /// closed.
+    pub async fn next_event(&mut self) -> Option<NowExecEvent> {
+        self.events.recv().await
+    }
+
+    /// Sends stdin data to the remote process. `last` should be set for the last data chunk to
+    /// close the remote stdin.
+    pub async fn send_stdin(&self, data: &[u8], last: bool) -> Result<(), NowClientError> {
+        let message = NowExecDataMsg::new(self.session_id, NowExecDataStreamKind::Stdin, last, data)?;
+
+        self.commands.send_message(NowMessage::from(message)).await
+    }
+
+    /// Sends execution session cancel request and waits for the server response.
+    ///
+    /// Returns success if the session has exited before the server responded to cancel request.
+    pub async fn cancel(&self) -> Result<(), NowClientError> {
+        let session_id = self.session_id;
+
+        self.commands
+            .request(|response| Command::ExecCancel { session_id, response })
+            .await?
+            .map_err(NowClientError::Status)
+    }
+
+    /// Aborts the remote process with the given exit code (if supported by OS). Session is
+    /// considered terminated both on server and client sides after this call.
+    pub async fn abort(self, exit_code: u32) -> Result<(), NowClientError> {
+        let message = NowExecAbortMsg::new(self.session_id, exit_code);
+
+        self.commands.request(|ack| Command::ExecAbort { message, ack }).await
+    }
+
+    /// Waits for the session to finish and returns the process exit code. Output events received
+    /// in the meantime are discarded.
+    ///
+    /// Non-zero exit codes are still considered as a successful execution result.
+    pub async fn wait(mut self) -> Result<u32, NowClientError> {
+        while let Some(event) = self.events.recv().await {
+            if let NowExecEvent::Finished(result) = event {
+                return result.map_err(NowClientError::Status);
+            }
+        }
+
+        Err(NowClientError::ChannelClosed)
+    }
+}
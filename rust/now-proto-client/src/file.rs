@@ -0,0 +1,208 @@
+use core::time::Duration;
+
+use now_proto_pdu::{
+    NowFileChunkMsg, NowFileCloseMsg, NowFileOpenMode, NowMessage, NowStatusError, OwnedNowFileDirEntryMsg,
+};
+use sha2::{Digest as _, Sha256};
+use tokio::sync::mpsc;
+
+use crate::worker::CommandSender;
+use crate::NowClientError;
+
+/// Server message received for the open file transfer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum FileTransferEvent {
+    Opened(Result<u64, NowStatusError>),
+    Chunk { data: Vec<u8>, last: bool },
+    Closed(Result<(), NowStatusError>),
+}
+
+/// Open file transfer.
+///
+/// Returned by [`NowClient::file_open`](crate::NowClient::file_open). SHA-256 digest of the
+/// transferred data is sent to the server on [`NowFileTransfer::close`] and verified by the
+/// server.
+#[derive(Debug)]
+pub struct NowFileTransfer {
+    transfer_id: u32,
+    mode: NowFileOpenMode,
+    file_size: u64,
+    offset: u64,
+    commands: CommandSender,
+    events: mpsc::UnboundedReceiver<FileTransferEvent>,
+    response_timeout: Duration,
+    sha256: Sha256,
+    /// Last chunk of the read transfer has been received.
+    finished: bool,
+    /// Transfer has been completed by the server without waiting for the client to close it.
+    closed: Option<Result<(), NowStatusError>>,
+}
+
+impl NowFileTransfer {
+    pub(crate) fn new(
+        transfer_id: u32,
+        mode: NowFileOpenMode,
+        file_size: u64,
+        offset: u64,
+        commands: CommandSender,
+        events: mpsc::UnboundedReceiver<FileTransferEvent>,
+        response_timeout: Duration,
+    ) -> Self {
+        Self {
+            transfer_id,
+            mode,
+            file_size,
+            offset,
+            commands,
+            events,
+            response_timeout,
+            sha256: Sha256::new(),
+            finished: false,
+            closed: None,
+        }
+    }
+
+    pub fn transfer_id(&self) -> u32 {
+        self.transfer_id
+    }
+
+    pub fn mode(&self) -> NowFileOpenMode {
+        self.mode
+    }
+
+    /// Size of the remote file for read transfers, or the offset of the first written byte
+    /// for write transfers (which is chosen by the server for resumed transfers).
+    pub fn file_size(&self) -> u64 {
+        self.file_size
+    }
+
+    /// Offset of the next transferred chunk.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Waits for the next chunk of the read transfer.
+    ///
+    /// Returns `None` once the last chunk has been received; the transfer should be closed with
+    /// [`NowFileTransfer::close`] afterwards.
+    pub async fn read_chunk(&mut self) -> Result<Option<Vec<u8>>, NowClientError> {
+        if self.finished {
+            return Ok(None);
+        }
+
+        loop {
+            match self.events.recv().await {
+                Some(FileTransferEvent::Chunk { data, last }) => {
+                    self.advance(&data);
+                    self.finished = last;
+
+                    if data.is_empty() && last {
+                        return Ok(None);
+                    }
+
+                    return Ok(Some(data));
+                }
+                Some(FileTransferEvent::Closed(result)) => {
+                    self.closed = Some(result.clone());
+                    result?;
+                    return Err(NowClientError::ChannelClosed);
+                }
+                Some(FileTransferEvent::Opened(_)) => {}
+                None => return Err(NowClientError::ChannelClosed),
+            }
+        }
+    }
+
+    /// Sends the next chunk of the write transfer.
+    pub async fn write_chunk(&mut self, data: &[u8]) -> Result<(), NowClientError> {
+        // Server could have failed the transfer (e.g. disk is full) while the data was sent.
+        if let Ok(FileTransferEvent::Closed(result)) = self.events.try_recv() {
+            self.closed = Some(result.clone());
+            result?;
+        }
+
+        if let Some(result) = &self.closed {
+            result.clone()?;
+            return Err(NowClientError::ChannelClosed);
+        }
+
+        let message = NowFileChunkMsg::new(self.transfer_id, self.offset, false, data)?;
+        self.commands.send_message(NowMessage::from(message)).await?;
+
+        self.advance(data);
+
+        Ok(())
+    }
+
+    /// Closes the transfer and waits for the server to verify the SHA-256 digest of the
+    /// transferred data. Write transfers are complete once this method returns successfully.
+    pub async fn close(self) -> Result<(), NowClientError> {
+        let sha256 = self.sha256.clone().finalize().into();
+        let message = NowFileCloseMsg::new(self.transfer_id).with_sha256(sha256);
+
+        self.close_with(message).await
+    }
+
+    /// Closes the transfer before it is complete; the digest is not verified. Data written
+    /// so far is kept on the server.
+    pub async fn cancel(self) -> Result<(), NowClientError> {
+        let message = NowFileCloseMsg::new(self.transfer_id);
+
+        self.close_with(message).await
+    }
+
+    async fn close_with(mut self, message: NowFileCloseMsg<'_>) -> Result<(), NowClientError> {
+        if let Some(result) = self.closed {
+            return result.map_err(NowClientError::Status);
+        }
+
+        self.commands.send_message(NowMessage::from(message)).await?;
+
+        let response = async {
+            while let Some(event) = self.events.recv().await {
+                if let FileTransferEvent::Closed(result) = event {
+                    return result.map_err(NowClientError::Status);
+                }
+            }
+
+            Err(NowClientError::ChannelClosed)
+        };
+
+        tokio::time::timeout(self.response_timeout, response)
+            .await
+            .map_err(|_| NowClientError::Timeout)?
+    }
+
+    fn advance(&mut self, data: &[u8]) {
+        let len = u64::try_from(data.len()).expect("usize fits into u64");
+        self.offset = self.offset.saturating_add(len);
+        self.sha256.update(data);
+    }
+}
+
+/// Page of remote directory entries, returned by
+/// [`NowClient::file_list_dir`](crate::NowClient::file_list_dir).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NowFileDirPage {
+    entries: Vec<OwnedNowFileDirEntryMsg>,
+    more: bool,
+}
+
+impl NowFileDirPage {
+    pub(crate) fn new(entries: Vec<OwnedNowFileDirEntryMsg>, more: bool) -> Self {
+        Self { entries, more }
+    }
+
+    pub fn entries(&self) -> &[OwnedNowFileDirEntryMsg] {
+        &self.entries
+    }
+
+    pub fn into_entries(self) -> Vec<OwnedNowFileDirEntryMsg> {
+        self.entries
+    }
+
+    /// Returns `true` if more entries are available after this page.
+    pub fn has_more(&self) -> bool {
+        self.more
+    }
+}
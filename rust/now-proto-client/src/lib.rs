@@ -0,0 +1,18 @@
+#![doc = include_str!("../README.md")]
+#![doc(
+    html_logo_url = "https://webdevolutions.blob.core.windows.net/images/projects/devolutions/logos/devolutions-icon-shadow.svg"
+)]
+
+mod channel;
+mod client;
+mod error;
+mod exec;
+mod file;
+mod transport;
+mod worker;
+
+pub use client::*;
+pub use error::*;
+pub use exec::*;
+pub use file::*;
+pub use transport::*;
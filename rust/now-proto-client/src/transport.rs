@@ -0,0 +1,12 @@
+use tokio::io::{AsyncRead, AsyncWrite};
+
+/// Byte-stream transport used to exchange NOW-PROTO messages (e.g. DVC, named pipe or socket).
+///
+/// The transport is not required to preserve message boundaries, messages are framed by
+/// `NOW_HEADER`. Implemented for any `AsyncRead + AsyncWrite` type which could be moved to the
+/// background worker task.
+///
+/// Rust counterpart of the .NET `INowTransport`.
+pub trait NowTransport: AsyncRead + AsyncWrite + Unpin + Send + 'static {}
+
+impl<T> NowTransport for T where T: AsyncRead + AsyncWrite + Unpin + Send + 'static {}
@@ -0,0 +1,651 @@
+use core::future;
+use std::collections::HashMap;
+
+use now_proto_channel::{NowChannelState, NowHeartbeatEvent, NowHeartbeatSupervisor, NowMsgBoxCorrelator};
+use now_proto_pdu::ironrdp_core::IntoOwned;
+use now_proto_pdu::{
+    NowChannelCloseMsg, NowChannelMessage, NowExecAbortMsg, NowExecCancelReqMsg, NowExecDataStreamKind, NowExecMessage,
+    NowFileMessage, NowMessage, NowMsgBoxResponse, NowRdmMessage, NowSessionMessage, NowStatusError,
+    NowSystemInfoReqMsg, NowSystemMessage, NowSystemProcessListReqMsg, OwnedNowFileDirEntryMsg,
+    OwnedNowFileListDirReqMsg, OwnedNowFileStatRspMsg, OwnedNowMessage, OwnedNowRdmCapabilitiesMsg,
+    OwnedNowSessionMsgBoxReqMsg, OwnedNowSystemInfoRspMsg, OwnedNowSystemProcessInfoMsg,
+};
+use tokio::sync::{broadcast, mpsc, oneshot};
+use tokio::time::{self, Instant};
+
+use crate::channel::NowChannelTransport;
+use crate::file::FileTransferEvent;
+use crate::{NowClientError, NowClientEvent, NowExecEvent, NowFileDirPage, NowTransport};
+
+/// Client -> worker commands.
+#[derive(Debug)]
+pub(crate) enum Command {
+    /// Send message which does not expect any response.
+    Send {
+        message: OwnedNowMessage,
+        ack: oneshot::Sender<()>,
+    },
+    MsgBox {
+        message: OwnedNowSessionMsgBoxReqMsg,
+        response: MsgBoxResponder,
+    },
+    ExecStart {
+        session_id: u32,
+        message: OwnedNowMessage,
+        events: mpsc::UnboundedSender<NowExecEvent>,
+        ack: oneshot::Sender<()>,
+    },
+    FileOpen {
+        transfer_id: u32,
+        message: OwnedNowMessage,
+        events: mpsc::UnboundedSender<FileTransferEvent>,
+        ack: oneshot::Sender<()>,
+    },
+    ExecCancel {
+        session_id: u32,
+        response: oneshot::Sender<Result<(), NowStatusError>>,
+    },
+    ExecAbort {
+        message: NowExecAbortMsg,
+        ack: oneshot::Sender<()>,
+    },
+    /// Request answered with the request status only (e.g. `NOW_SYSTEM_POWER_RSP_MSG`).
+    StatusRequest {
+        request_id: u32,
+        message: OwnedNowMessage,
+        response: oneshot::Sender<Result<(), NowStatusError>>,
+    },
+    ProcessList {
+        message: NowSystemProcessListReqMsg,
+        response: ProcessListResponder,
+    },
+    FileStat {
+        request_id: u32,
+        message: OwnedNowMessage,
+        response: oneshot::Sender<OwnedNowFileStatRspMsg>,
+    },
+    ListDir {
+        message: OwnedNowFileListDirReqMsg,
+        response: DirListResponder,
+    },
+    SystemInfo {
+        message: NowSystemInfoReqMsg,
+        response: oneshot::Sender<OwnedNowSystemInfoRspMsg>,
+    },
+    RdmCapabilities {
+        message: OwnedNowRdmCapabilitiesMsg,
+        response: oneshot::Sender<OwnedNowRdmCapabilitiesMsg>,
+    },
+    /// Allocate ID which is not used by any pending request or active session of its kind.
+    AllocateId {
+        kind: IdKind,
+        response: oneshot::Sender<u32>,
+    },
+    Close {
+        ack: oneshot::Sender<()>,
+    },
+}
+
+/// Client-allocated ID spaces.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum IdKind {
+    ExecSession,
+    MsgBox,
+    /// System and file request IDs share the same response correlation tables.
+    Request,
+    FileTransfer,
+}
+
+type MsgBoxResponder = oneshot::Sender<Result<NowMsgBoxResponse, NowClientError>>;
+
+type ProcessListResponder = oneshot::Sender<Result<Vec<OwnedNowSystemProcessInfoMsg>, NowStatusError>>;
+
+type DirListResponder = oneshot::Sender<Result<NowFileDirPage, NowStatusError>>;
+
+/// Cloneable handle used to submit commands to the worker.
+#[derive(Debug, Clone)]
+pub(crate) struct CommandSender(mpsc::Sender<Command>);
+
+impl CommandSender {
+    pub(crate) fn new(sender: mpsc::Sender<Command>) -> Self {
+        Self(sender)
+    }
+
+    /// Submits command to the worker and waits for its completion.
+    ///
+    /// Returns [`NowClientError::ChannelClosed`] if the worker has terminated before completing
+    /// the command.
+    pub(crate) async fn request<R>(
+        &self,
+        command: impl FnOnce(oneshot::Sender<R>) -> Command,
+    ) -> Result<R, NowClientError> {
+        let (tx, rx) = oneshot::channel();
+
+        self.0
+            .send(command(tx))
+            .await
+            .map_err(|_| NowClientError::ChannelClosed)?;
+
+        rx.await.map_err(|_| NowClientError::ChannelClosed)
+    }
+
+    /// Sends message which does not expect any response, resolves once the message is written
+    /// to the transport.
+    pub(crate) async fn send_message(&self, message: NowMessage<'_>) -> Result<(), NowClientError> {
+        let message = message.into_owned();
+
+        self.request(|ack| Command::Send { message, ack }).await
+    }
+}
+
+struct ExecSessionEntry {
+    events: mpsc::UnboundedSender<NowExecEvent>,
+    cancel: Option<oneshot::Sender<Result<(), NowStatusError>>>,
+}
+
+impl ExecSessionEntry {
+    fn notify(&self, event: NowExecEvent) {
+        // Session handle could be already dropped by the user, this is not an error.
+        let _ = self.events.send(event);
+    }
+}
+
+/// Process list being received, completed by `NOW_SYSTEM_PROCESS_LIST_RSP_MSG`.
+struct ProcessListEntry {
+    processes: Vec<OwnedNowSystemProcessInfoMsg>,
+    response: ProcessListResponder,
+}
+
+/// Directory page being received, completed by `NOW_FILE_LIST_DIR_RSP_MSG`.
+struct DirListEntry {
+    entries: Vec<OwnedNowFileDirEntryMsg>,
+    response: DirListResponder,
+}
+
+enum Flow {
+    Continue,
+    Exit,
+}
+
+/// Background worker owning the transport.
+pub(crate) struct Worker<T> {
+    channel: NowChannelTransport<T>,
+    state: NowChannelState,
+    commands: mpsc::Receiver<Command>,
+    events: broadcast::Sender<NowClientEvent>,
+    heartbeat: Option<NowHeartbeatSupervisor>,
+    msg_boxes: NowMsgBoxCorrelator<MsgBoxResponder>,
+    exec_sessions: HashMap<u32, ExecSessionEntry>,
+    file_transfers: HashMap<u32, mpsc::UnboundedSender<FileTransferEvent>>,
+    system_info_requests: HashMap<u32, oneshot::Sender<OwnedNowSystemInfoRspMsg>>,
+    status_requests: HashMap<u32, oneshot::Sender<Result<(), NowStatusError>>>,
+    process_lists: HashMap<u32, ProcessListEntry>,
+    file_stat_requests: HashMap<u32, oneshot::Sender<OwnedNowFileStatRspMsg>>,
+    dir_lists: HashMap<u32, DirListEntry>,
+    rdm_capabilities_request: Option<oneshot::Sender<OwnedNowRdmCapabilitiesMsg>>,
+    next_exec_session_id: u32,
+    next_request_id: u32,
+    next_file_transfer_id: u32,
+}
+
+impl<T: NowTransport> Worker<T> {
+    pub(crate) fn new(
+        channel: NowChannelTransport<T>,
+        state: NowChannelState,
+        commands: mpsc::Receiver<Command>,
+        events: broadcast::Sender<NowClientEvent>,
+        heartbeat: Option<NowHeartbeatSupervisor>,
+    ) -> Self {
+        Self {
+            channel,
+            state,
+            commands,
+            events,
+            heartbeat,
+            msg_boxes: NowMsgBoxCorrelator::new(),
+            exec_sessions: HashMap::new(),
+            file_transfers: HashMap::new(),
+            system_info_requests: HashMap::new(),
+            status_requests: HashMap::new(),
+            process_lists: HashMap::new(),
+            file_stat_requests: HashMap::new(),
+            dir_lists: HashMap::new(),
+            rdm_capabilities_request: None,
+            next_exec_session_id: 0,
+            next_request_id: 0,
+            next_file_transfer_id: 0,
+        }
+    }
+
+    /// Runs the worker until the channel is closed by either side, or all client handles are
+    /// dropped. Pending requests are resolved with [`NowClientError::ChannelClosed`] on exit.
+    pub(crate) async fn run(mut self) -> Result<(), NowClientError> {
+        loop {
+            let heartbeat_deadline = self.heartbeat.as_ref().and_then(NowHeartbeatSupervisor::next_deadline);
+            let heartbeat_timeout = sleep_until(heartbeat_deadline);
+            let msg_box_timeout = sleep_until(self.msg_boxes.next_deadline());
+
+            let flow = tokio::select! {
+                command = self.commands.recv() => match command {
+                    Some(command) => self.handle_command(command).await?,
+                    // All client handles are dropped.
+                    None => Flow::Exit,
+                },
+                message = self.channel.read_message() => match message? {
+                    Some(message) => {
+                        if let Some(heartbeat) = self.heartbeat.as_mut() {
+                            heartbeat.on_message_received();
+                        }
+
+                        if let Err(violation) = self.state.on_incoming(&message) {
+                            self.channel.close_with_violation(&violation).await;
+                            return Err(violation.into());
+                        }
+
+                        self.handle_message(message)?
+                    }
+                    None => return Err(NowClientError::ChannelClosed),
+                },
+                () = heartbeat_timeout => {
+                    let event = self.heartbeat.as_mut().and_then(NowHeartbeatSupervisor::poll);
+
+                    if let Some(NowHeartbeatEvent::PeerTimeout { idle }) = event {
+                        tracing::debug!(?idle, "Server heartbeat timeout");
+                        // Channel is considered dead; no attempt to send any messages should be made.
+                        return Err(NowClientError::HeartbeatTimeout);
+                    }
+
+                    Flow::Continue
+                }
+                () = msg_box_timeout => {
+                    for (request_id, response) in self.msg_boxes.poll_expired() {
+                        tracing::debug!(request_id, "Message box response timeout");
+                        let _ = response.send(Err(NowClientError::Timeout));
+                    }
+
+                    Flow::Continue
+                }
+            };
+
+            if let Flow::Exit = flow {
+                return Ok(());
+            }
+        }
+    }
+
+    async fn handle_command(&mut self, command: Command) -> Result<Flow, NowClientError> {
+        match command {
+            Command::Send { message, ack } => {
+                self.channel.write_message(&message).await?;
+                let _ = ack.send(());
+            }
+            Command::MsgBox { message, response } => match self.msg_boxes.register(&message, response) {
+                Ok(()) => self.channel.write_message(&message.into()).await?,
+                Err((error, response)) => {
+                    tracing::debug!(%error, "Message box request rejected");
+                    let status = NowStatusError::new_proto(error.proto_error());
+                    let _ = response.send(Err(NowClientError::Status(status)));
+                }
+            },
+            Command::ExecStart {
+                session_id,
+                message,
+                events,
+                ack,
+            } => {
+                self.channel.write_message(&message).await?;
+                self.exec_sessions
+                    .insert(session_id, ExecSessionEntry { events, cancel: None });
+                let _ = ack.send(());
+            }
+            Command::ExecCancel { session_id, response } => match self.exec_sessions.get_mut(&session_id) {
+                Some(session) => {
+                    self.channel
+                        .write_message(&NowExecCancelReqMsg::new(session_id).into())
+                        .await?;
+                    session.cancel = Some(response);
+                }
+                // Session has already exited.
+                None => {
+                    let _ = response.send(Ok(()));
+                }
+            },
+            Command::ExecAbort { message, ack } => {
+                let session_id = message.session_id();
+                self.channel.write_message(&message.into()).await?;
+                self.exec_sessions.remove(&session_id);
+                let _ = ack.send(());
+            }
+            Command::FileOpen {
+                transfer_id,
+                message,
+                events,
+                ack,
+            } => {
+                // Drop transfers abandoned by the client.
+                self.file_transfers.retain(|_, events| !events.is_closed());
+
+                self.channel.write_message(&message).await?;
+                self.file_transfers.insert(transfer_id, events);
+                let _ = ack.send(());
+            }
+            Command::StatusRequest {
+                request_id,
+                message,
+                response,
+            } => {
+                // Drop requests abandoned by the client on response timeout.
+                self.status_requests.retain(|_, response| !response.is_closed());
+
+                self.channel.write_message(&message).await?;
+                self.status_requests.insert(request_id, response);
+            }
+            Command::ProcessList { message, response } => {
+                // Drop lists abandoned by the client on response timeout.
+                self.process_lists.retain(|_, entry| !entry.response.is_closed());
+
+                let request_id = message.request_id();
+                self.channel.write_message(&message.into()).await?;
+                self.process_lists.insert(
+                    request_id,
+                    ProcessListEntry {
+                        processes: Vec::new(),
+                        response,
+                    },
+                );
+            }
+            Command::FileStat {
+                request_id,
+                message,
+                response,
+            } => {
+                self.file_stat_requests.retain(|_, response| !response.is_closed());
+
+                self.channel.write_message(&message).await?;
+                self.file_stat_requests.insert(request_id, response);
+            }
+            Command::ListDir { message, response } => {
+                self.dir_lists.retain(|_, entry| !entry.response.is_closed());
+
+                let request_id = message.request_id();
+                self.channel.write_message(&message.into()).await?;
+                self.dir_lists.insert(
+                    request_id,
+                    DirListEntry {
+                        entries: Vec::new(),
+                        response,
+                    },
+                );
+            }
+            Command::SystemInfo { message, response } => {
+                self.system_info_requests.retain(|_, response| !response.is_closed());
+
+                let request_id = message.request_id();
+                self.channel.write_message(&message.into()).await?;
+                self.system_info_requests.insert(request_id, response);
+            }
+            Command::RdmCapabilities { message, response } => {
+                self.channel
+                    .write_message(&NowMessage::Rdm(NowRdmMessage::Capabilities(message)))
+                    .await?;
+                self.rdm_capabilities_request = Some(response);
+            }
+            Command::AllocateId { kind, response } => {
+                let _ = response.send(self.allocate_id(kind));
+            }
+            Command::Close { ack } => {
+                self.channel
+                    .write_message(&NowChannelCloseMsg::default().into())
+                    .await?;
+                let _ = ack.send(());
+                return Ok(Flow::Exit);
+            }
+        }
+
+        Ok(Flow::Continue)
+    }
+
+    fn allocate_id(&mut self, kind: IdKind) -> u32 {
+        match kind {
+            IdKind::ExecSession => next_free_id(&mut self.next_exec_session_id, |session_id| {
+                self.exec_sessions.contains_key(&session_id)
+            }),
+            IdKind::MsgBox => self.msg_boxes.allocate_id(),
+            IdKind::Request => next_free_id(&mut self.next_request_id, |request_id| {
+                self.system_info_requests.contains_key(&request_id)
+                    || self.status_requests.contains_key(&request_id)
+                    || self.process_lists.contains_key(&request_id)
+                    || self.file_stat_requests.contains_key(&request_id)
+                    || self.dir_lists.contains_key(&request_id)
+            }),
+            IdKind::FileTransfer => next_free_id(&mut self.next_file_transfer_id, |transfer_id| {
+                self.file_transfers.contains_key(&transfer_id)
+            }),
+        }
+    }
+
+    fn handle_message(&mut self, message: OwnedNowMessage) -> Result<Flow, NowClientError> {
+        match message {
+            // Inbound traffic is already tracked by the heartbeat supervisor.
+            NowMessage::Channel(NowChannelMessage::Heartbeat(_)) => {}
+            NowMessage::Channel(NowChannelMessage::Close(msg)) => {
+                msg.to_result()?;
+                return Ok(Flow::Exit);
+            }
+            NowMessage::System(NowSystemMessage::InfoRsp(msg)) => {
+                match self.system_info_requests.remove(&msg.request_id()) {
+                    Some(response) => {
+                        let _ = response.send(msg);
+                    }
+                    None => {
+                        tracing::debug!(request_id = msg.request_id(), "Unexpected system info response");
+                    }
+                }
+            }
+            NowMessage::System(NowSystemMessage::PowerRsp(msg)) => {
+                self.on_status_response(msg.request_id(), msg.to_result());
+            }
+            NowMessage::System(NowSystemMessage::ProcessTerminateRsp(msg)) => {
+                self.on_status_response(msg.request_id(), msg.to_result());
+            }
+            NowMessage::System(NowSystemMessage::ProcessInfo(msg)) => {
+                match self.process_lists.get_mut(&msg.request_id()) {
+                    Some(entry) => entry.processes.push(msg),
+                    None => {
+                        tracing::debug!(request_id = msg.request_id(), "Unexpected process info");
+                    }
+                }
+            }
+            NowMessage::System(NowSystemMessage::ProcessListRsp(msg)) => {
+                match self.process_lists.remove(&msg.request_id()) {
+                    Some(entry) => {
+                        let _ = entry.response.send(msg.to_result().map(|()| entry.processes));
+                    }
+                    None => {
+                        tracing::debug!(request_id = msg.request_id(), "Unexpected process list response");
+                    }
+                }
+            }
+            NowMessage::Session(NowSessionMessage::MsgBoxRsp(msg)) => match self.msg_boxes.on_response(&msg) {
+                Some((response, result)) => {
+                    let _ = response.send(result.map_err(NowClientError::Status));
+                }
+                None => {
+                    tracing::debug!(request_id = msg.request_id(), "Unexpected message box response");
+                }
+            },
+            NowMessage::Session(NowSessionMessage::WindowRecEvent(msg)) => {
+                // No subscribers is not an error.
+                let _ = self.events.send(NowClientEvent::WindowRecEvent(msg));
+            }
+            NowMessage::Exec(msg) => self.handle_exec_message(msg),
+            NowMessage::File(NowFileMessage::StatRsp(msg)) => match self.file_stat_requests.remove(&msg.request_id()) {
+                Some(response) => {
+                    let _ = response.send(msg);
+                }
+                None => {
+                    tracing::debug!(request_id = msg.request_id(), "Unexpected file stat response");
+                }
+            },
+            NowMessage::File(NowFileMessage::DirEntry(msg)) => match self.dir_lists.get_mut(&msg.request_id()) {
+                Some(entry) => entry.entries.push(msg),
+                None => {
+                    tracing::debug!(request_id = msg.request_id(), "Unexpected directory entry");
+                }
+            },
+            NowMessage::File(NowFileMessage::ListDirRsp(msg)) => match self.dir_lists.remove(&msg.request_id()) {
+                Some(entry) => {
+                    let page = msg
+                        .to_result()
+                        .map(|()| NowFileDirPage::new(entry.entries, msg.has_more()));
+                    let _ = entry.response.send(page);
+                }
+                None => {
+                    tracing::debug!(request_id = msg.request_id(), "Unexpected directory list response");
+                }
+            },
+            NowMessage::File(NowFileMessage::OpRsp(msg)) => {
+                self.on_status_response(msg.request_id(), msg.to_result());
+            }
+            NowMessage::File(msg) => self.handle_file_message(msg),
+            NowMessage::Rdm(NowRdmMessage::Capabilities(msg)) => match self.rdm_capabilities_request.take() {
+                Some(response) => {
+                    let _ = response.send(msg);
+                }
+                None => {
+                    tracing::debug!("Unexpected RDM capabilities response");
+                }
+            },
+            NowMessage::Rdm(NowRdmMessage::AppNotify(msg)) => {
+                let _ = self.events.send(NowClientEvent::RdmAppNotify(msg));
+            }
+            NowMessage::Rdm(NowRdmMessage::SessionNotify(msg)) => {
+                let _ = self.events.send(NowClientEvent::RdmSessionNotify(msg));
+            }
+            other => {
+                tracing::debug!(message = ?other, "Unhandled NOW-PROTO message");
+            }
+        }
+
+        Ok(Flow::Continue)
+    }
+
+    fn on_status_response(&mut self, request_id: u32, result: Result<(), NowStatusError>) {
+        match self.status_requests.remove(&request_id) {
+            Some(response) => {
+                let _ = response.send(result);
+            }
+            None => {
+                tracing::debug!(request_id, "Unexpected status response");
+            }
+        }
+    }
+
+    fn handle_exec_message(&mut self, message: NowExecMessage<'static>) {
+        let session_id = match &message {
+            NowExecMessage::Started(msg) => msg.session_id(),
+            NowExecMessage::Data(msg) => msg.session_id(),
+            NowExecMessage::CancelRsp(msg) => msg.session_id(),
+            NowExecMessage::Result(msg) => msg.session_id(),
+            other => {
+                tracing::debug!(message = ?other, "Unhandled NOW-PROTO exec message");
+                return;
+            }
+        };
+
+        let Some(session) = self.exec_sessions.get_mut(&session_id) else {
+            tracing::debug!(session_id, "Exec message for unknown session");
+            return;
+        };
+
+        match message {
+            NowExecMessage::Started(_) => session.notify(NowExecEvent::Started),
+            NowExecMessage::Data(msg) => {
+                let data = msg.data().to_vec();
+                let last = msg.is_last();
+
+                match msg.stream_kind() {
+                    Ok(NowExecDataStreamKind::Stdout) => session.notify(NowExecEvent::Stdout { data, last }),
+                    Ok(NowExecDataStreamKind::Stderr) => session.notify(NowExecEvent::Stderr { data, last }),
+                    _ => {
+                        tracing::debug!(session_id, "Received unexpected exec output stream");
+                    }
+                }
+            }
+            NowExecMessage::CancelRsp(msg) => match session.cancel.take() {
+                Some(response) => {
+                    let _ = response.send(msg.to_result());
+                }
+                None => {
+                    tracing::debug!(session_id, "Unexpected exec cancel response");
+                }
+            },
+            NowExecMessage::Result(msg) => {
+                // Unregister session after receiving the result.
+                if let Some(mut session) = self.exec_sessions.remove(&session_id) {
+                    // Graceful exit completes pending cancel request.
+                    if let Some(response) = session.cancel.take() {
+                        let _ = response.send(Ok(()));
+                    }
+                    session.notify(NowExecEvent::Finished(msg.to_result()));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_file_message(&mut self, message: NowFileMessage<'static>) {
+        let (transfer_id, event, done) = match message {
+            NowFileMessage::OpenRsp(msg) => {
+                let result = msg.to_result().map(|()| msg.file_size());
+                let done = result.is_err();
+                (msg.transfer_id(), FileTransferEvent::Opened(result), done)
+            }
+            NowFileMessage::Chunk(msg) => {
+                let event = FileTransferEvent::Chunk {
+                    data: msg.data().to_vec(),
+                    last: msg.is_last(),
+                };
+                (msg.transfer_id(), event, false)
+            }
+            NowFileMessage::CloseRsp(msg) => (msg.transfer_id(), FileTransferEvent::Closed(msg.to_result()), true),
+            other => {
+                tracing::debug!(message = ?other, "Unhandled NOW-PROTO file message");
+                return;
+            }
+        };
+
+        let Some(events) = self.file_transfers.get(&transfer_id) else {
+            tracing::debug!(transfer_id, "File message for unknown transfer");
+            return;
+        };
+
+        // Transfer handle could be already dropped by the user, this is not an error.
+        let _ = events.send(event);
+
+        // Unregister transfer after failed open or close response.
+        if done {
+            self.file_transfers.remove(&transfer_id);
+        }
+    }
+}
+
+/// Returns the next ID which is not in use, wrapping around on overflow.
+fn next_free_id(next_id: &mut u32, in_use: impl Fn(u32) -> bool) -> u32 {
+    loop {
+        let id = *next_id;
+        *next_id = next_id.wrapping_add(1);
+
+        if !in_use(id) {
+            return id;
+        }
+    }
+}
+
+/// Sleeps until the deadline, or forever if no deadline is set.
+async fn sleep_until(deadline: Option<std::time::Instant>) {
+    match deadline {
+        Some(deadline) => time::sleep_until(Instant::from_std(deadline)).await,
+        None => future::pending().await,
+    }
+}
@@ -0,0 +1,305 @@
+//! `#[now(...)]` attribute parsing.
+
+use proc_macro2::Span;
+use syn::spanned::Spanned as _;
+use syn::{Attribute, Data, DeriveInput, Fields, Ident, LitStr, Type};
+
+/// Message class with the matching `now-proto-pdu` type names.
+pub(crate) struct MessageClass {
+    /// `NowMessageClass` constant.
+    pub(crate) constant: Ident,
+    /// `NowMessage` variant.
+    pub(crate) message_variant: &'static str,
+    /// Class message enum (e.g. `NowExecMessage`).
+    pub(crate) message_enum: &'static str,
+    /// Class message kind type (e.g. `NowExecMsgKind`).
+    pub(crate) kind_type: &'static str,
+}
+
+impl MessageClass {
+    fn parse(constant: Ident) -> syn::Result<Self> {
+        let (message_variant, message_enum, kind_type) = match constant.to_string().as_str() {
+            "CHANNEL" => ("Channel", "NowChannelMessage", "NowChannelMsgKind"),
+            "SYSTEM" => ("System", "NowSystemMessage", "NowSystemMessageKind"),
+            "SESSION" => ("Session", "NowSessionMessage", "NowSessionMessageKind"),
+            "EXEC" => ("Exec", "NowExecMessage", "NowExecMsgKind"),
+            "RDM" => ("Rdm", "NowRdmMessage", "NowRdmMsgKind"),
+            "FILE" => ("File", "NowFileMessage", "NowFileMsgKind"),
+            _ => return Err(syn::Error::new(constant.span(), "unknown message class")),
+        };
+
+        Ok(Self {
+            constant,
+            message_variant,
+            message_enum,
+            kind_type,
+        })
+    }
+}
+
+/// Struct-level `#[now(class = ..., kind = ..., variant = ..., name = "...", validate)]` attribute.
+pub(crate) struct MessageAttrs {
+    pub(crate) class: MessageClass,
+    pub(crate) kind: Ident,
+    pub(crate) variant: Ident,
+    pub(crate) name: String,
+    /// Deserialized messages are checked with the hand-written `validate` method.
+    pub(crate) validate: bool,
+}
+
+impl MessageAttrs {
+    fn parse(input: &DeriveInput) -> syn::Result<Self> {
+        let mut class = None;
+        let mut kind = None;
+        let mut variant = None;
+        let mut name = None;
+        let mut validate = false;
+
+        for attr in now_attrs(&input.attrs) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("class") {
+                    class = Some(MessageClass::parse(meta.value()?.parse()?)?);
+                } else if meta.path.is_ident("kind") {
+                    kind = Some(meta.value()?.parse::<Ident>()?);
+                } else if meta.path.is_ident("variant") {
+                    variant = Some(meta.value()?.parse::<Ident>()?);
+                } else if meta.path.is_ident("name") {
+                    name = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("validate") {
+                    validate = true;
+                } else {
+                    return Err(meta.error("unsupported message attribute"));
+                }
+
+                Ok(())
+            })?;
+        }
+
+        let missing = |attr: &str| syn::Error::new(input.ident.span(), format!("missing `#[now({attr} = ...)]`"));
+
+        let class = class.ok_or_else(|| missing("class"))?;
+        let kind = kind.ok_or_else(|| missing("kind"))?;
+        let variant = variant.ok_or_else(|| missing("variant"))?;
+        let name = name.unwrap_or_else(|| format!("NOW_{}_{}_MSG", class.constant, kind));
+
+        Ok(Self {
+            class,
+            kind,
+            variant,
+            name,
+            validate,
+        })
+    }
+}
+
+/// Wire representation of the message field.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FieldKind {
+    /// `NOW_HEADER` msgFlags.
+    Flags {
+        inferred: bool,
+    },
+    /// Little-endian integer of the given size.
+    Integer(usize),
+    VarStr,
+    VarBuf,
+    Guid,
+    Status,
+}
+
+impl FieldKind {
+    pub(crate) fn is_variable(self) -> bool {
+        matches!(self, Self::VarStr | Self::VarBuf | Self::Guid | Self::Status)
+    }
+}
+
+pub(crate) struct MessageField {
+    pub(crate) ident: Ident,
+    pub(crate) ty: Type,
+    pub(crate) kind: FieldKind,
+    /// Field type has a lifetime parameter, and should be converted with `IntoOwned`.
+    pub(crate) is_borrowing: bool,
+    pub(crate) optional: Option<Ident>,
+    pub(crate) is_trailing: bool,
+    /// `#[serde(...)]` attributes, forwarded to the deserialization representation.
+    pub(crate) serde_attrs: Vec<Attribute>,
+}
+
+impl MessageField {
+    fn parse(field: &syn::Field) -> syn::Result<Self> {
+        let ident = field
+            .ident
+            .clone()
+            .ok_or_else(|| syn::Error::new(field.span(), "tuple structs are not supported"))?;
+
+        let mut is_flags = false;
+        let mut inferred = false;
+        let mut optional = None;
+        let mut is_trailing = false;
+
+        for attr in now_attrs(&field.attrs) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("flags") {
+                    is_flags = true;
+                } else if meta.path.is_ident("inferred") {
+                    inferred = true;
+                } else if meta.path.is_ident("optional") {
+                    optional = Some(meta.value()?.parse::<Ident>()?);
+                } else if meta.path.is_ident("trailing") {
+                    is_trailing = true;
+                } else {
+                    return Err(meta.error("unsupported field attribute"));
+                }
+
+                Ok(())
+            })?;
+        }
+
+        if inferred && !is_flags {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`inferred` is only allowed on `flags` field",
+            ));
+        }
+
+        let (type_name, is_borrowing) = type_name(&field.ty)?;
+
+        let kind = if is_flags {
+            FieldKind::Flags { inferred }
+        } else {
+            match type_name.to_string().as_str() {
+                "u8" => FieldKind::Integer(1),
+                "u16" => FieldKind::Integer(2),
+                "u32" => FieldKind::Integer(4),
+                "u64" => FieldKind::Integer(8),
+                "NowVarStr" => FieldKind::VarStr,
+                "NowVarBuf" => FieldKind::VarBuf,
+                "NowGuid" => FieldKind::Guid,
+                "NowStatus" => FieldKind::Status,
+                _ => return Err(syn::Error::new(field.ty.span(), "unsupported message field type")),
+            }
+        };
+
+        if (optional.is_some() || is_trailing) && !matches!(kind, FieldKind::VarStr | FieldKind::VarBuf) {
+            return Err(syn::Error::new(
+                ident.span(),
+                "only `NowVarStr` and `NowVarBuf` fields could be optional",
+            ));
+        }
+
+        Ok(Self {
+            ident,
+            ty: field.ty.clone(),
+            kind,
+            is_borrowing,
+            optional,
+            is_trailing,
+            serde_attrs: field
+                .attrs
+                .iter()
+                .filter(|attr| attr.path().is_ident("serde"))
+                .cloned()
+                .collect(),
+        })
+    }
+}
+
+pub(crate) struct Message {
+    pub(crate) attrs: MessageAttrs,
+    pub(crate) flags: Option<MessageField>,
+    /// Message body fields, in encoding order.
+    pub(crate) fields: Vec<MessageField>,
+}
+
+impl Message {
+    pub(crate) fn parse(input: &DeriveInput) -> syn::Result<Self> {
+        let Data::Struct(data) = &input.data else {
+            return Err(syn::Error::new(input.ident.span(), "only structs are supported"));
+        };
+
+        let attrs = MessageAttrs::parse(input)?;
+
+        let mut flags = None;
+        let mut fields = Vec::new();
+
+        let declared_fields = match &data.fields {
+            Fields::Named(named) => named.named.iter().collect(),
+            Fields::Unit => Vec::new(),
+            Fields::Unnamed(_) => return Err(syn::Error::new(input.ident.span(), "tuple structs are not supported")),
+        };
+
+        for field in declared_fields {
+            let field = MessageField::parse(field)?;
+
+            if matches!(field.kind, FieldKind::Flags { .. }) {
+                if flags.is_some() {
+                    return Err(syn::Error::new(field.ident.span(), "duplicate `flags` field"));
+                }
+
+                flags = Some(field);
+                continue;
+            }
+
+            if let Some(previous) = fields.last() {
+                ensure_field_order(previous, &field)?;
+            }
+
+            fields.push(field);
+        }
+
+        Ok(Self { attrs, flags, fields })
+    }
+
+    pub(crate) fn fixed_part_size(&self) -> usize {
+        self.fields
+            .iter()
+            .map(|field| match field.kind {
+                FieldKind::Integer(size) => size,
+                _ => 0,
+            })
+            .sum()
+    }
+
+    pub(crate) fn variable_fields(&self) -> impl Iterator<Item = &MessageField> {
+        self.fields.iter().filter(|field| field.kind.is_variable())
+    }
+}
+
+/// Fixed-size fields are expected to be at the start of the message body, and fields added in
+/// newer protocol versions at the end.
+fn ensure_field_order(previous: &MessageField, field: &MessageField) -> syn::Result<()> {
+    if previous.kind.is_variable() && !field.kind.is_variable() {
+        return Err(syn::Error::new(
+            field.ident.span(),
+            "fixed-size fields should precede variable-size fields",
+        ));
+    }
+
+    if previous.is_trailing && !field.is_trailing {
+        return Err(syn::Error::new(
+            field.ident.span(),
+            "fields without `trailing` should precede `trailing` fields",
+        ));
+    }
+
+    Ok(())
+}
+
+fn now_attrs(attrs: &[Attribute]) -> impl Iterator<Item = &Attribute> {
+    attrs.iter().filter(|attr| attr.path().is_ident("now"))
+}
+
+/// Returns last path segment of the field type, and whether it has generic arguments.
+fn type_name(ty: &Type) -> syn::Result<(Ident, bool)> {
+    let Type::Path(path) = ty else {
+        return Err(syn::Error::new(ty.span(), "unsupported message field type"));
+    };
+
+    let segment = path
+        .path
+        .segments
+        .last()
+        .ok_or_else(|| syn::Error::new(Span::call_site(), "empty field type path"))?;
+
+    Ok((segment.ident.clone(), !segment.arguments.is_empty()))
+}
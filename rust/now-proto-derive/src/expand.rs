@@ -0,0 +1,458 @@
+//! `NowPdu` derive code generation.
+
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{DeriveInput, GenericParam, Lifetime};
+
+use crate::attrs::{FieldKind, Message, MessageField};
+
+pub(crate) fn now_pdu(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let message = Message::parse(input)?;
+    let lifetime = message_lifetime(input)?;
+
+    let inherent = expand_inherent(input, &message, lifetime.as_ref());
+    let encode = expand_encode(input, &message);
+    let decode = expand_decode(input, &message, lifetime.as_ref());
+    let into_owned = lifetime.is_some().then(|| expand_into_owned(input, &message));
+    let check_decode_limits = expand_check_decode_limits(input, &message);
+    let from = expand_from(input, &message, lifetime.as_ref());
+    let deserialize = expand_deserialize(input, &message, lifetime.as_ref());
+
+    Ok(quote! {
+        #inherent
+        #encode
+        #decode
+        #into_owned
+        #check_decode_limits
+        #from
+        #deserialize
+    })
+}
+
+/// Messages could only be generic over a single lifetime of the borrowed message data.
+fn message_lifetime(input: &DeriveInput) -> syn::Result<Option<Lifetime>> {
+    let mut params = input.generics.params.iter();
+
+    let lifetime = match params.next() {
+        None => None,
+        Some(GenericParam::Lifetime(param)) => Some(param.lifetime.clone()),
+        Some(param) => return Err(syn::Error::new_spanned(param, "only a lifetime parameter is supported")),
+    };
+
+    if let Some(param) = params.next() {
+        return Err(syn::Error::new_spanned(
+            param,
+            "only a single lifetime parameter is supported",
+        ));
+    }
+
+    Ok(lifetime)
+}
+
+/// Message type with the lifetime elided (e.g. `NowExecRunMsg<'_>`), for implementations which
+/// do not depend on the borrowed data lifetime.
+fn elided_type(input: &DeriveInput) -> TokenStream {
+    let ident = &input.ident;
+
+    if input.generics.params.is_empty() {
+        quote!(#ident)
+    } else {
+        quote!(#ident<'_>)
+    }
+}
+
+fn expand_inherent(input: &DeriveInput, message: &Message, lifetime: Option<&Lifetime>) -> TokenStream {
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+
+    let name = &message.attrs.name;
+    let fixed_part_size = message.fixed_part_size();
+
+    let variable_fields: Vec<_> = message.variable_fields().map(|field| &field.ident).collect();
+
+    let ensure_message_size = (!variable_fields.is_empty()).then(|| {
+        quote! {
+            fn ensure_message_size(&self) -> ::ironrdp_core::EncodeResult<()> {
+                crate::ensure_now_message_size!(
+                    Self::FIXED_PART_SIZE,
+                    #(::ironrdp_core::Encode::size(&self.#variable_fields)),*
+                );
+
+                Ok(())
+            }
+        }
+    });
+
+    let cursor_lifetime = match lifetime {
+        Some(lifetime) => quote!(#lifetime),
+        None => quote!('_),
+    };
+
+    let decode_from_body = expand_decode_from_body(message);
+
+    quote! {
+        impl #impl_generics #ident #ty_generics {
+            const NAME: &'static str = #name;
+            const FIXED_PART_SIZE: usize = #fixed_part_size;
+
+            // LINTS: Overall message size is validated in the constructor/decode method
+            #[allow(clippy::arithmetic_side_effects)]
+            fn body_size(&self) -> usize {
+                Self::FIXED_PART_SIZE #(+ ::ironrdp_core::Encode::size(&self.#variable_fields))*
+            }
+
+            #ensure_message_size
+
+            pub(super) fn decode_from_body(
+                header: crate::NowHeader,
+                src: &mut ::ironrdp_core::ReadCursor<#cursor_lifetime>,
+            ) -> ::ironrdp_core::DecodeResult<Self> {
+                #decode_from_body
+            }
+        }
+    }
+}
+
+fn expand_decode_from_body(message: &Message) -> TokenStream {
+    let ensure_fixed_part_size =
+        (message.fixed_part_size() != 0).then(|| quote!(::ironrdp_core::ensure_fixed_part_size!(in: src);));
+
+    let optional_fields: Vec<_> = message
+        .fields
+        .iter()
+        .filter_map(|field| field.optional.as_ref().map(|flag| (&field.ident, flag)))
+        .collect();
+
+    let (flags, infer_flags) = match &message.flags {
+        Some(MessageField {
+            ty,
+            kind: FieldKind::Flags { inferred: true },
+            ..
+        }) if !optional_fields.is_empty() => {
+            let (idents, flags): (Vec<_>, Vec<_>) = optional_fields.into_iter().unzip();
+
+            (
+                quote!(let mut flags = <#ty>::empty();),
+                quote! {
+                    #(
+                        if !#idents.is_empty() {
+                            flags |= <#ty>::#flags;
+                        }
+                    )*
+                },
+            )
+        }
+        Some(MessageField {
+            ty,
+            kind: FieldKind::Flags { inferred: true },
+            ..
+        }) => (quote!(let flags = <#ty>::empty();), TokenStream::new()),
+        Some(MessageField { ty, .. }) => (
+            quote!(let flags = <#ty>::from_bits_retain(header.flags);),
+            TokenStream::new(),
+        ),
+        None => (quote!(let _ = header;), TokenStream::new()),
+    };
+
+    let fields = message.fields.iter().map(|field| {
+        let ident = &field.ident;
+        let ty = &field.ty;
+
+        let value = match field.kind {
+            FieldKind::Integer(size) => {
+                let read = format_ident!("read_u{}", size * 8);
+                quote!(src.#read())
+            }
+            _ => quote!(<#ty as ::ironrdp_core::Decode<'_>>::decode(src)?),
+        };
+
+        if field.is_trailing {
+            quote! {
+                let #ident = if !src.is_empty() {
+                    #value
+                } else {
+                    <#ty>::default()
+                };
+            }
+        } else {
+            quote!(let #ident = #value;)
+        }
+    });
+
+    let field_idents = message.flags.iter().chain(&message.fields).map(|field| &field.ident);
+
+    quote! {
+        #ensure_fixed_part_size
+
+        #flags
+        #(#fields)*
+        #infer_flags
+
+        Ok(Self {
+            #(#field_idents),*
+        })
+    }
+}
+
+fn expand_encode(input: &DeriveInput, message: &Message) -> TokenStream {
+    let ty = elided_type(input);
+
+    let class = &message.attrs.class.constant;
+    let kind_type = Ident::new(message.attrs.class.kind_type, Span::call_site());
+    let kind = &message.attrs.kind;
+
+    let flags = match &message.flags {
+        Some(field) => {
+            let flags = &field.ident;
+            quote!(self.#flags.bits())
+        }
+        None => quote!(0),
+    };
+
+    let ensure_fixed_part_size =
+        (message.fixed_part_size() != 0).then(|| quote!(::ironrdp_core::ensure_fixed_part_size!(in: dst);));
+
+    let fields = message.fields.iter().map(|field| {
+        let ident = &field.ident;
+
+        match field.kind {
+            FieldKind::Integer(size) => {
+                let write = format_ident!("write_u{}", size * 8);
+                quote!(dst.#write(self.#ident);)
+            }
+            _ => quote!(::ironrdp_core::Encode::encode(&self.#ident, dst)?;),
+        }
+    });
+
+    quote! {
+        impl ::ironrdp_core::Encode for #ty {
+            fn encode(&self, dst: &mut ::ironrdp_core::WriteCursor<'_>) -> ::ironrdp_core::EncodeResult<()> {
+                let header = crate::NowHeader {
+                    size: ::ironrdp_core::cast_length!("size", self.body_size())?,
+                    class: crate::NowMessageClass::#class,
+                    kind: crate::#kind_type::#kind.0,
+                    flags: #flags,
+                };
+
+                ::ironrdp_core::Encode::encode(&header, dst)?;
+
+                #ensure_fixed_part_size
+                #(#fields)*
+
+                Ok(())
+            }
+
+            fn name(&self) -> &'static str {
+                Self::NAME
+            }
+
+            // LINTS: See body_size()
+            #[allow(clippy::arithmetic_side_effects)]
+            fn size(&self) -> usize {
+                crate::NowHeader::FIXED_PART_SIZE + self.body_size()
+            }
+        }
+    }
+}
+
+fn expand_decode(input: &DeriveInput, message: &Message, lifetime: Option<&Lifetime>) -> TokenStream {
+    let ident = &input.ident;
+
+    let class = &message.attrs.class.constant;
+    let kind_type = Ident::new(message.attrs.class.kind_type, Span::call_site());
+    let kind = &message.attrs.kind;
+
+    let (impl_generics, decode_lifetime, ty) = match lifetime {
+        Some(lifetime) => (quote!(<#lifetime>), quote!(#lifetime), quote!(#ident<#lifetime>)),
+        None => (TokenStream::new(), quote!('_), quote!(#ident)),
+    };
+
+    quote! {
+        impl #impl_generics ::ironrdp_core::Decode<#decode_lifetime> for #ty {
+            fn decode(src: &mut ::ironrdp_core::ReadCursor<#decode_lifetime>) -> ::ironrdp_core::DecodeResult<Self> {
+                let header = <crate::NowHeader as ::ironrdp_core::Decode<'_>>::decode(src)?;
+
+                match (header.class, crate::#kind_type(header.kind)) {
+                    (crate::NowMessageClass::#class, crate::#kind_type::#kind) => Self::decode_from_body(header, src),
+                    _ => Err(::ironrdp_core::invalid_field_err!("type", "invalid message type")),
+                }
+            }
+        }
+    }
+}
+
+fn expand_into_owned(input: &DeriveInput, message: &Message) -> TokenStream {
+    let ident = &input.ident;
+    let owned = format_ident!("Owned{}", ident);
+
+    let fields = message.flags.iter().chain(&message.fields).map(|field| {
+        let ident = &field.ident;
+
+        if field.is_borrowing {
+            quote!(#ident: ::ironrdp_core::IntoOwned::into_owned(self.#ident))
+        } else {
+            quote!(#ident: self.#ident)
+        }
+    });
+
+    quote! {
+        pub type #owned = #ident<'static>;
+
+        impl ::ironrdp_core::DecodeOwned for #owned {
+            fn decode_owned(src: &mut ::ironrdp_core::ReadCursor<'_>) -> ::ironrdp_core::DecodeResult<Self> {
+                let pdu = <#ident<'_> as ::ironrdp_core::Decode<'_>>::decode(src)?;
+                Ok(::ironrdp_core::IntoOwned::into_owned(pdu))
+            }
+        }
+
+        impl ::ironrdp_core::IntoOwned for #ident<'_> {
+            type Owned = #owned;
+
+            fn into_owned(self) -> Self::Owned {
+                #owned {
+                    #(#fields),*
+                }
+            }
+        }
+    }
+}
+
+fn expand_check_decode_limits(input: &DeriveInput, message: &Message) -> Option<TokenStream> {
+    let checks: Vec<_> = message
+        .fields
+        .iter()
+        .filter_map(|field| {
+            let ident = &field.ident;
+
+            match field.kind {
+                FieldKind::VarStr => Some(quote!(checker.string(&self.#ident)?;)),
+                FieldKind::VarBuf => Some(quote!(checker.buffer(&self.#ident)?;)),
+                FieldKind::Status => {
+                    Some(quote!(crate::CheckDecodeLimits::check_decode_limits(&self.#ident, checker)?;))
+                }
+                // GUIDs are never copied on conversion to the owned representation.
+                _ => None,
+            }
+        })
+        .collect();
+
+    if checks.is_empty() {
+        return None;
+    }
+
+    let ty = elided_type(input);
+
+    Some(quote! {
+        impl crate::CheckDecodeLimits for #ty {
+            fn check_decode_limits(&self, checker: &mut crate::DecodeLimitsChecker) -> ::ironrdp_core::DecodeResult<()> {
+                #(#checks)*
+
+                Ok(())
+            }
+        }
+    })
+}
+
+fn expand_from(input: &DeriveInput, message: &Message, lifetime: Option<&Lifetime>) -> TokenStream {
+    let ident = &input.ident;
+
+    let (impl_generics, message_lifetime, ty) = match lifetime {
+        Some(lifetime) => (quote!(<#lifetime>), quote!(#lifetime), quote!(#ident<#lifetime>)),
+        None => (TokenStream::new(), quote!('_), quote!(#ident)),
+    };
+
+    let message_variant = Ident::new(message.attrs.class.message_variant, Span::call_site());
+    let message_enum = Ident::new(message.attrs.class.message_enum, Span::call_site());
+    let variant = &message.attrs.variant;
+
+    quote! {
+        impl #impl_generics From<#ty> for crate::NowMessage<#message_lifetime> {
+            fn from(msg: #ty) -> Self {
+                crate::NowMessage::#message_variant(crate::#message_enum::#variant(msg))
+            }
+        }
+    }
+}
+
+/// Messages are deserialized via the field-wise representation, and then checked the same way
+/// as in the constructors: overall message size and (with `#[now(validate)]`) field invariants.
+fn expand_deserialize(input: &DeriveInput, message: &Message, lifetime: Option<&Lifetime>) -> TokenStream {
+    let ident = &input.ident;
+    let repr_name = ident.to_string();
+
+    let (impl_generics, ty, repr_ty) = match lifetime {
+        Some(lifetime) => (
+            quote!(<'de, #lifetime>),
+            quote!(#ident<#lifetime>),
+            quote!(Repr<#lifetime>),
+        ),
+        None => (quote!(<'de>), quote!(#ident), quote!(Repr)),
+    };
+
+    let fields: Vec<_> = message.flags.iter().chain(&message.fields).collect();
+    let field_idents: Vec<_> = fields.iter().map(|field| &field.ident).collect();
+
+    let repr_fields = fields.iter().map(|field| {
+        let ident = &field.ident;
+        let ty = &field.ty;
+        let serde_attrs = &field.serde_attrs;
+
+        quote! {
+            #(#serde_attrs)*
+            #ident: #ty
+        }
+    });
+
+    let (repr, destructure, construct) = if fields.is_empty() {
+        (
+            quote!(
+                struct Repr;
+            ),
+            quote!(Repr),
+            quote!(Self),
+        )
+    } else {
+        (
+            quote!(struct #repr_ty { #(#repr_fields),* }),
+            quote!(Repr { #(#field_idents),* }),
+            quote!(Self { #(#field_idents),* }),
+        )
+    };
+
+    let ensure_message_size = (message.variable_fields().next().is_some())
+        .then(|| quote!(msg.ensure_message_size().map_err(::serde::de::Error::custom)?;));
+
+    let validate = message
+        .attrs
+        .validate
+        .then(|| quote!(msg.validate().map_err(::serde::de::Error::custom)?;));
+
+    let construct = if ensure_message_size.is_some() || validate.is_some() {
+        quote! {
+            let msg = #construct;
+
+            #ensure_message_size
+            #validate
+
+            Ok(msg)
+        }
+    } else {
+        quote!(Ok(#construct))
+    };
+
+    quote! {
+        #[cfg(feature = "serde")]
+        const _: () = {
+            #[derive(::serde::Deserialize)]
+            #[serde(rename = #repr_name)]
+            #repr
+
+            impl #impl_generics ::serde::Deserialize<'de> for #ty {
+                fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let #destructure = <#repr_ty as ::serde::Deserialize<'de>>::deserialize(deserializer)?;
+                    #construct
+                }
+            }
+        };
+    }
+}
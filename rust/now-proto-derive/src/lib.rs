@@ -0,0 +1,72 @@
+//! Derive macros generating NOW-PROTO message boilerplate for the `now-proto-pdu` crate.
+
+mod attrs;
+mod expand;
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+/// Generates encoding/decoding boilerplate for a NOW-PROTO message struct.
+///
+/// The struct is annotated with its message class, kind and enum variant; fields are encoded in
+/// declaration order, fixed-size fields first:
+///
+/// ```ignore
+/// #[derive(Debug, Clone, PartialEq, Eq, NowPdu)]
+/// #[now(class = EXEC, kind = RUN, variant = Run)]
+/// pub struct NowExecRunMsg<'a> {
+///     #[now(flags, inferred)]
+///     flags: NowExecRunFlags,
+///     session_id: u32,
+///     command: NowVarStr<'a>,
+///     /// Added in NOW-PROTO 1.1.
+///     #[now(optional = DIRECTORY_SET, trailing)]
+///     directory: NowVarStr<'a>,
+/// }
+/// ```
+///
+/// Struct attributes:
+///
+/// - `class`: `NowMessageClass` constant (`CHANNEL`, `SYSTEM`, `SESSION`, `EXEC`, `RDM` or `FILE`);
+/// - `kind`: message kind constant of the class (e.g. `RUN` for `NowExecMsgKind::RUN`);
+/// - `variant`: class message enum variant (e.g. `Run` for `NowExecMessage::Run`);
+/// - `name`: message name, `NOW_<CLASS>_<KIND>_MSG` by default;
+/// - `validate`: deserialized messages are additionally checked with the hand-written
+///   `fn validate(&self) -> Result<(), E>` method (`E: Display`), for constructor invariants which
+///   are not enforced by the field types (e.g. conflicting flags).
+///
+/// Field attributes:
+///
+/// - `flags`: bitflags field encoded as the `NOW_HEADER` msgFlags, not part of the message body;
+/// - `inferred` (with `flags`): received msgFlags are ignored, and flags of `optional` fields are
+///   set on decoding if the field is not empty;
+/// - `optional = FLAG`: flag signaling that the field contains a non-default value;
+/// - `trailing`: field has been added in a newer protocol version, and is decoded only if the
+///   message body has not ended yet (older peers do not send it). Such fields should be the last
+///   ones, and the protocol version is documented on the field itself.
+///
+/// Supported field types are `u8`, `u16`, `u32`, `u64`, `NowVarStr`, `NowVarBuf`, `NowGuid` and
+/// `NowStatus`.
+///
+/// Generated items:
+///
+/// - `NAME` and `FIXED_PART_SIZE` constants, `body_size`, `decode_from_body` and (if the message
+///   has variable-size fields) `ensure_message_size` methods;
+/// - `Encode` and `Decode` implementations, with the message class/kind check on decoding;
+/// - `IntoOwned` implementation and the `Owned*` type alias for messages with a lifetime;
+/// - `CheckDecodeLimits` implementation for messages with variable-size fields;
+/// - `From<Msg> for NowMessage` conversion;
+/// - with the `serde` feature, `Deserialize` implementation checking the message size (and
+///   calling `validate`) after deserializing the fields. `#[serde(...)]` field attributes are
+///   forwarded to it, and the struct itself should only derive `Serialize`.
+///
+/// Generated code refers to `now-proto-pdu` items via `crate::` paths, and is only intended to
+/// be used inside of the `now-proto-pdu` crate.
+#[proc_macro_derive(NowPdu, attributes(now))]
+pub fn derive_now_pdu(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand::now_pdu(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
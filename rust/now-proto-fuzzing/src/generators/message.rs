@@ -0,0 +1,461 @@
+use arbitrary::{Arbitrary, Result, Unstructured};
+use now_proto_pdu::ironrdp_core::{decode, IntoOwned as _};
+use now_proto_pdu::*;
+
+use super::wire::{arbitrary_flags, var_buf, var_str, FrameWriter};
+
+const CLASS_CHANNEL: u8 = 0x10;
+const CLASS_SYSTEM: u8 = 0x11;
+const CLASS_SESSION: u8 = 0x12;
+const CLASS_EXEC: u8 = 0x13;
+const CLASS_RDM: u8 = 0x14;
+const CLASS_FILE: u8 = 0x15;
+
+/// Every message class/kind pair known to NOW-PROTO.
+#[derive(Arbitrary, Debug, Clone, Copy, PartialEq, Eq)]
+enum MessageKind {
+    ChannelCapset,
+    ChannelHeartbeat,
+    ChannelClose,
+    SystemInfoReq,
+    SystemInfoRsp,
+    SystemShutdown,
+    SystemShutdownAbort,
+    SystemPowerAction,
+    SystemPowerRsp,
+    SystemProcessListReq,
+    SystemProcessInfo,
+    SystemProcessListRsp,
+    SystemProcessTerminate,
+    SystemProcessTerminateRsp,
+    SessionLock,
+    SessionLogoff,
+    SessionMsgBoxReq,
+    SessionMsgBoxRsp,
+    SessionSetKbdLayout,
+    SessionWindowRecStart,
+    SessionWindowRecStop,
+    SessionWindowRecEvent,
+    ExecAbort,
+    ExecCancelReq,
+    ExecCancelRsp,
+    ExecResult,
+    ExecData,
+    ExecStarted,
+    ExecRun,
+    ExecProcess,
+    ExecShell,
+    ExecBatch,
+    ExecWinPs,
+    ExecPwsh,
+    RdmCapabilities,
+    RdmAppStart,
+    RdmAppAction,
+    RdmAppNotify,
+    RdmSessionStart,
+    RdmSessionAction,
+    RdmSessionNotify,
+    FileOpen,
+    FileOpenRsp,
+    FileChunk,
+    FileClose,
+    FileCloseRsp,
+    FileStatReq,
+    FileStatRsp,
+    FileListDirReq,
+    FileDirEntry,
+    FileListDirRsp,
+    FileMkdir,
+    FileDelete,
+    FileRename,
+    FileOpRsp,
+}
+
+/// Encoded NOW-PROTO message frame (header + body) of any known class and kind.
+///
+/// The frame is guaranteed to decode successfully, but it is not necessarily canonical: decoding
+/// is lenient, and frames with unknown flag bits, invalid stream kind flags, ignored fields or
+/// flags set on messages without flags are generated on purpose.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NowFrameInput {
+    pub frame: Vec<u8>,
+}
+
+impl<'a> Arbitrary<'a> for NowFrameInput {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let kind = MessageKind::arbitrary(u)?;
+        let frame = arbitrary_frame(u, kind)?;
+
+        Ok(Self { frame })
+    }
+}
+
+/// Decoded NOW-PROTO message of any known class and kind.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NowMessageInput {
+    pub message: OwnedNowMessage,
+}
+
+impl<'a> Arbitrary<'a> for NowMessageInput {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let NowFrameInput { frame } = NowFrameInput::arbitrary(u)?;
+
+        let message = decode::<NowMessage<'_>>(&frame)
+            .expect("BUG: generated frame is not decodable")
+            .into_owned();
+
+        Ok(Self { message })
+    }
+}
+
+fn arbitrary_frame(u: &mut Unstructured<'_>, kind: MessageKind) -> Result<Vec<u8>> {
+    let frame = match kind {
+        MessageKind::ChannelCapset => {
+            let flags = arbitrary_flags(u, NowChannelCapsetFlags::all().bits())?;
+            // Heartbeat interval is always present on the wire, even if SET_HEARTBEAT is not set.
+            let mut writer = FrameWriter::new(CLASS_CHANNEL, 0x01, flags);
+            writer
+                .u16(u16::arbitrary(u)?)
+                .u16(u16::arbitrary(u)?)
+                .u16(arbitrary_flags(u, NowSystemCapsetFlags::all().bits())?)
+                .u16(arbitrary_flags(u, NowSessionCapsetFlags::all().bits())?)
+                .u16(arbitrary_flags(u, NowExecCapsetFlags::all().bits())?)
+                .u32(u32::arbitrary(u)?);
+            // File capset is only present on the wire if SET_FILE_CAPSET is set.
+            if NowChannelCapsetFlags::from_bits_retain(flags).contains(NowChannelCapsetFlags::SET_FILE_CAPSET) {
+                writer.u16(arbitrary_flags(u, NowFileCapsetFlags::all().bits())?);
+            }
+            writer.finish()
+        }
+        MessageKind::ChannelHeartbeat => unit_frame(u, CLASS_CHANNEL, 0x02)?,
+        MessageKind::ChannelClose => FrameWriter::new(CLASS_CHANNEL, 0x03, u16::arbitrary(u)?)
+            .status(u)?
+            .finish(),
+        MessageKind::SystemInfoReq => FrameWriter::new(CLASS_SYSTEM, 0x01, u16::arbitrary(u)?)
+            .u32(u32::arbitrary(u)?)
+            .finish(),
+        MessageKind::SystemInfoRsp => {
+            let flags = arbitrary_flags(u, NowSystemInfoFlags::all().bits())?;
+            FrameWriter::new(CLASS_SYSTEM, 0x02, flags)
+                .u32(u32::arbitrary(u)?)
+                .u64(u64::arbitrary(u)?)
+                .status(u)?
+                .var_str(&var_str(u)?)
+                .var_str(&var_str(u)?)
+                .var_str(&var_str(u)?)
+                .var_str(&var_str(u)?)
+                .var_str(&var_str(u)?)
+                .var_str(&var_str(u)?)
+                .finish()
+        }
+        MessageKind::SystemShutdown => {
+            let flags = arbitrary_flags(u, NowSystemShutdownFlags::all().bits())?;
+            FrameWriter::new(CLASS_SYSTEM, 0x03, flags)
+                .u32(u32::arbitrary(u)?)
+                .var_str(&var_str(u)?)
+                .finish()
+        }
+        MessageKind::SystemShutdownAbort => FrameWriter::new(CLASS_SYSTEM, 0x04, u16::arbitrary(u)?)
+            .u32(u32::arbitrary(u)?)
+            .finish(),
+        MessageKind::SystemPowerAction => {
+            let flags = arbitrary_flags(u, NowSystemPowerActionFlags::all().bits())?;
+            FrameWriter::new(CLASS_SYSTEM, 0x05, flags)
+                .u32(u32::arbitrary(u)?)
+                .u32(u32::arbitrary(u)?)
+                .u32(u32::arbitrary(u)?)
+                .var_str(&var_str(u)?)
+                .finish()
+        }
+        MessageKind::SystemPowerRsp => FrameWriter::new(CLASS_SYSTEM, 0x06, u16::arbitrary(u)?)
+            .u32(u32::arbitrary(u)?)
+            .status(u)?
+            .finish(),
+        MessageKind::SystemProcessListReq => FrameWriter::new(CLASS_SYSTEM, 0x07, u16::arbitrary(u)?)
+            .u32(u32::arbitrary(u)?)
+            .finish(),
+        MessageKind::SystemProcessInfo => FrameWriter::new(CLASS_SYSTEM, 0x08, u16::arbitrary(u)?)
+            .u32(u32::arbitrary(u)?)
+            .u32(u32::arbitrary(u)?)
+            .u32(u32::arbitrary(u)?)
+            .u32(u32::arbitrary(u)?)
+            .u64(u64::arbitrary(u)?)
+            .var_str(&var_str(u)?)
+            .var_str(&var_str(u)?)
+            .var_str(&var_str(u)?)
+            .finish(),
+        MessageKind::SystemProcessListRsp => FrameWriter::new(CLASS_SYSTEM, 0x09, u16::arbitrary(u)?)
+            .u32(u32::arbitrary(u)?)
+            .status(u)?
+            .finish(),
+        MessageKind::SystemProcessTerminate => {
+            let flags = arbitrary_flags(u, NowSystemProcessTerminateFlags::all().bits())?;
+            FrameWriter::new(CLASS_SYSTEM, 0x0A, flags)
+                .u32(u32::arbitrary(u)?)
+                .u32(u32::arbitrary(u)?)
+                .finish()
+        }
+        MessageKind::SystemProcessTerminateRsp => FrameWriter::new(CLASS_SYSTEM, 0x0B, u16::arbitrary(u)?)
+            .u32(u32::arbitrary(u)?)
+            .status(u)?
+            .finish(),
+        MessageKind::SessionLock => unit_frame(u, CLASS_SESSION, 0x01)?,
+        MessageKind::SessionLogoff => unit_frame(u, CLASS_SESSION, 0x02)?,
+        MessageKind::SessionMsgBoxReq => {
+            let flags = arbitrary_flags(u, NowSessionMessageBoxFlags::all().bits())?;
+            FrameWriter::new(CLASS_SESSION, 0x03, flags)
+                .u32(u32::arbitrary(u)?)
+                .u32(u32::arbitrary(u)?)
+                .u32(u32::arbitrary(u)?)
+                .var_str(&var_str(u)?)
+                .var_str(&var_str(u)?)
+                .finish()
+        }
+        MessageKind::SessionMsgBoxRsp => FrameWriter::new(CLASS_SESSION, 0x04, u16::arbitrary(u)?)
+            .u32(u32::arbitrary(u)?)
+            .u32(u32::arbitrary(u)?)
+            .status(u)?
+            .finish(),
+        MessageKind::SessionSetKbdLayout => {
+            let mut flags = arbitrary_flags(u, NowSessionSetKbdLayoutFlags::all().bits())?;
+            // NEXT and PREV are mutually exclusive and rejected by the decoder.
+            let next_prev =
+                (NowSessionSetKbdLayoutFlags::NEXT_LAYOUT | NowSessionSetKbdLayoutFlags::PREV_LAYOUT).bits();
+            if flags & next_prev == next_prev {
+                flags &= !NowSessionSetKbdLayoutFlags::PREV_LAYOUT.bits();
+            }
+            FrameWriter::new(CLASS_SESSION, 0x05, flags)
+                .var_str(&var_str(u)?)
+                .finish()
+        }
+        MessageKind::SessionWindowRecStart => {
+            let flags = arbitrary_flags(u, WindowRecStartFlags::all().bits())?;
+            FrameWriter::new(CLASS_SESSION, 0x06, flags)
+                .u32(u32::arbitrary(u)?)
+                .finish()
+        }
+        MessageKind::SessionWindowRecStop => unit_frame(u, CLASS_SESSION, 0x07)?,
+        MessageKind::SessionWindowRecEvent => {
+            let mut flags = arbitrary_flags(u, WindowRecEventFlags::all().bits())?;
+            // At least one event kind flag is required by the decoder.
+            let kinds = [
+                WindowRecEventFlags::ACTIVE_WINDOW,
+                WindowRecEventFlags::TITLE_CHANGED,
+                WindowRecEventFlags::NO_ACTIVE_WINDOW,
+            ];
+            if !kinds.iter().any(|kind| flags & kind.bits() != 0) {
+                flags |= u.choose(&kinds)?.bits();
+            }
+            FrameWriter::new(CLASS_SESSION, 0x08, flags)
+                .u64(u64::arbitrary(u)?)
+                .u32(u32::arbitrary(u)?)
+                .var_str(&var_str(u)?)
+                .var_str(&var_str(u)?)
+                .finish()
+        }
+        MessageKind::ExecAbort => FrameWriter::new(CLASS_EXEC, 0x01, u16::arbitrary(u)?)
+            .u32(u32::arbitrary(u)?)
+            .u32(u32::arbitrary(u)?)
+            .finish(),
+        MessageKind::ExecCancelReq => FrameWriter::new(CLASS_EXEC, 0x02, u16::arbitrary(u)?)
+            .u32(u32::arbitrary(u)?)
+            .finish(),
+        MessageKind::ExecCancelRsp => FrameWriter::new(CLASS_EXEC, 0x03, u16::arbitrary(u)?)
+            .u32(u32::arbitrary(u)?)
+            .status(u)?
+            .finish(),
+        MessageKind::ExecResult => FrameWriter::new(CLASS_EXEC, 0x04, u16::arbitrary(u)?)
+            .u32(u32::arbitrary(u)?)
+            .u32(u32::arbitrary(u)?)
+            .status(u)?
+            .finish(),
+        MessageKind::ExecData => {
+            // Any combination of stream kind flags (including none or several of them, which
+            // are invalid) is accepted by the decoder and only rejected by `stream_kind()`.
+            let flags = arbitrary_flags(u, NowExecDataFlags::all().bits())?;
+            FrameWriter::new(CLASS_EXEC, 0x05, flags)
+                .u32(u32::arbitrary(u)?)
+                .var_buf(&var_buf(u)?)
+                .finish()
+        }
+        MessageKind::ExecStarted => FrameWriter::new(CLASS_EXEC, 0x06, u16::arbitrary(u)?)
+            .u32(u32::arbitrary(u)?)
+            .finish(),
+        MessageKind::ExecRun => {
+            let flags = arbitrary_flags(u, NowExecRunFlags::all().bits())?;
+            let mut frame = FrameWriter::new(CLASS_EXEC, 0x10, flags);
+            frame.u32(u32::arbitrary(u)?).var_str(&var_str(u)?);
+            // Directory field has been added in v1.1.
+            if bool::arbitrary(u)? {
+                frame.var_str(&var_str(u)?);
+            }
+            frame.finish()
+        }
+        MessageKind::ExecProcess => {
+            let flags = arbitrary_flags(u, NowExecProcessFlags::all().bits())?;
+            FrameWriter::new(CLASS_EXEC, 0x11, flags)
+                .u32(u32::arbitrary(u)?)
+                .var_str(&var_str(u)?)
+                .var_str(&var_str(u)?)
+                .var_str(&var_str(u)?)
+                .finish()
+        }
+        MessageKind::ExecShell => {
+            let flags = arbitrary_flags(u, NowExecShellFlags::all().bits())?;
+            FrameWriter::new(CLASS_EXEC, 0x12, flags)
+                .u32(u32::arbitrary(u)?)
+                .var_str(&var_str(u)?)
+                .var_str(&var_str(u)?)
+                .var_str(&var_str(u)?)
+                .finish()
+        }
+        MessageKind::ExecBatch => {
+            let flags = arbitrary_flags(u, NowExecBatchFlags::all().bits())?;
+            FrameWriter::new(CLASS_EXEC, 0x13, flags)
+                .u32(u32::arbitrary(u)?)
+                .var_str(&var_str(u)?)
+                .var_str(&var_str(u)?)
+                .finish()
+        }
+        MessageKind::ExecWinPs | MessageKind::ExecPwsh => {
+            let kind = if kind == MessageKind::ExecWinPs { 0x14 } else { 0x15 };
+            // STA and MTA are mutually exclusive, but only rejected by `apartment_state()`.
+            let flags = arbitrary_flags(u, NowExecWinPsFlags::all().bits())?;
+            FrameWriter::new(CLASS_EXEC, kind, flags)
+                .u32(u32::arbitrary(u)?)
+                .var_str(&var_str(u)?)
+                .var_str(&var_str(u)?)
+                .var_str(&var_str(u)?)
+                .var_str(&var_str(u)?)
+                .finish()
+        }
+        MessageKind::RdmCapabilities => FrameWriter::new(CLASS_RDM, 0x01, u16::arbitrary(u)?)
+            .u64(u64::arbitrary(u)?)
+            .u32(arbitrary_flags(u, NowRdmSyncFlags::all().bits())?)
+            .var_str(&var_str(u)?)
+            .var_str(&var_str(u)?)
+            .finish(),
+        MessageKind::RdmAppStart => FrameWriter::new(CLASS_RDM, 0x02, u16::arbitrary(u)?)
+            .u32(arbitrary_flags(u, NowRdmLaunchFlags::all().bits())?)
+            .u32(u32::arbitrary(u)?)
+            .finish(),
+        MessageKind::RdmAppAction => FrameWriter::new(CLASS_RDM, 0x03, u16::arbitrary(u)?)
+            .u32(u32::arbitrary(u)?)
+            .var_str(&var_str(u)?)
+            .finish(),
+        MessageKind::RdmAppNotify => FrameWriter::new(CLASS_RDM, 0x04, u16::arbitrary(u)?)
+            .u32(u32::arbitrary(u)?)
+            .u32(u32::arbitrary(u)?)
+            .var_str(&var_str(u)?)
+            .finish(),
+        MessageKind::RdmSessionStart => FrameWriter::new(CLASS_RDM, 0x05, u16::arbitrary(u)?)
+            .guid(u)?
+            .guid(u)?
+            .var_str(&var_str(u)?)
+            .finish(),
+        MessageKind::RdmSessionAction => FrameWriter::new(CLASS_RDM, 0x06, u16::arbitrary(u)?)
+            .u32(u32::arbitrary(u)?)
+            .guid(u)?
+            .finish(),
+        MessageKind::RdmSessionNotify => FrameWriter::new(CLASS_RDM, 0x07, u16::arbitrary(u)?)
+            .u32(u32::arbitrary(u)?)
+            .guid(u)?
+            .var_str(&var_str(u)?)
+            .finish(),
+        MessageKind::FileOpen => {
+            let flags = arbitrary_flags(u, NowFileOpenFlags::all().bits())?;
+            FrameWriter::new(CLASS_FILE, 0x01, flags)
+                .u32(u32::arbitrary(u)?)
+                .u64(u64::arbitrary(u)?)
+                .var_str(&var_str(u)?)
+                .finish()
+        }
+        MessageKind::FileOpenRsp => FrameWriter::new(CLASS_FILE, 0x02, u16::arbitrary(u)?)
+            .u32(u32::arbitrary(u)?)
+            .u64(u64::arbitrary(u)?)
+            .status(u)?
+            .finish(),
+        MessageKind::FileChunk => {
+            let flags = arbitrary_flags(u, NowFileChunkFlags::all().bits())?;
+            FrameWriter::new(CLASS_FILE, 0x03, flags)
+                .u32(u32::arbitrary(u)?)
+                .u64(u64::arbitrary(u)?)
+                .var_buf(&var_buf(u)?)
+                .finish()
+        }
+        MessageKind::FileClose => FrameWriter::new(CLASS_FILE, 0x04, u16::arbitrary(u)?)
+            .u32(u32::arbitrary(u)?)
+            .var_buf(&var_buf(u)?)
+            .finish(),
+        MessageKind::FileCloseRsp => FrameWriter::new(CLASS_FILE, 0x05, u16::arbitrary(u)?)
+            .u32(u32::arbitrary(u)?)
+            .status(u)?
+            .finish(),
+        MessageKind::FileStatReq => FrameWriter::new(CLASS_FILE, 0x06, u16::arbitrary(u)?)
+            .u32(u32::arbitrary(u)?)
+            .var_str(&var_str(u)?)
+            .finish(),
+        MessageKind::FileStatRsp => FrameWriter::new(CLASS_FILE, 0x07, u16::arbitrary(u)?)
+            .u32(u32::arbitrary(u)?)
+            .u32(u32::arbitrary(u)?)
+            .u32(u32::arbitrary(u)?)
+            .u64(u64::arbitrary(u)?)
+            .u64(u64::arbitrary(u)?)
+            .status(u)?
+            .finish(),
+        MessageKind::FileListDirReq => FrameWriter::new(CLASS_FILE, 0x08, u16::arbitrary(u)?)
+            .u32(u32::arbitrary(u)?)
+            .u32(u32::arbitrary(u)?)
+            .u32(u32::arbitrary(u)?)
+            .var_str(&var_str(u)?)
+            .finish(),
+        MessageKind::FileDirEntry => FrameWriter::new(CLASS_FILE, 0x09, u16::arbitrary(u)?)
+            .u32(u32::arbitrary(u)?)
+            .u32(u32::arbitrary(u)?)
+            .u32(u32::arbitrary(u)?)
+            .u64(u64::arbitrary(u)?)
+            .u64(u64::arbitrary(u)?)
+            .var_str(&var_str(u)?)
+            .finish(),
+        MessageKind::FileListDirRsp => {
+            let flags = arbitrary_flags(u, NowFileListDirRspFlags::all().bits())?;
+            FrameWriter::new(CLASS_FILE, 0x0A, flags)
+                .u32(u32::arbitrary(u)?)
+                .status(u)?
+                .finish()
+        }
+        MessageKind::FileMkdir => {
+            let flags = arbitrary_flags(u, NowFileMkdirFlags::all().bits())?;
+            FrameWriter::new(CLASS_FILE, 0x0B, flags)
+                .u32(u32::arbitrary(u)?)
+                .var_str(&var_str(u)?)
+                .finish()
+        }
+        MessageKind::FileDelete => {
+            let flags = arbitrary_flags(u, NowFileDeleteFlags::all().bits())?;
+            FrameWriter::new(CLASS_FILE, 0x0C, flags)
+                .u32(u32::arbitrary(u)?)
+                .var_str(&var_str(u)?)
+                .finish()
+        }
+        MessageKind::FileRename => {
+            let flags = arbitrary_flags(u, NowFileRenameFlags::all().bits())?;
+            FrameWriter::new(CLASS_FILE, 0x0D, flags)
+                .u32(u32::arbitrary(u)?)
+                .var_str(&var_str(u)?)
+                .var_str(&var_str(u)?)
+                .finish()
+        }
+        MessageKind::FileOpRsp => FrameWriter::new(CLASS_FILE, 0x0E, u16::arbitrary(u)?)
+            .u32(u32::arbitrary(u)?)
+            .status(u)?
+            .finish(),
+    };
+
+    Ok(frame)
+}
+
+/// Message without body; header flags are not used by such messages and may be arbitrary.
+fn unit_frame(u: &mut Unstructured<'_>, class: u8, kind: u8) -> Result<Vec<u8>> {
+    Ok(FrameWriter::new(class, kind, u16::arbitrary(u)?).finish())
+}
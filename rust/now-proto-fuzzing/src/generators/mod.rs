@@ -7,3 +7,12 @@
 //! These are generally implementations of the `Arbitrary` trait, or some
 //! wrapper over an external tool, such that the wrapper implements the
 //! `Arbitrary` trait for the wrapped external tool.
+//!
+//! Message generators work at the wire level: they produce frames that always
+//! pass header checks and decode successfully, so that fuzzing spends its time
+//! in the message-specific code paths rather than being rejected early.
+
+mod message;
+mod wire;
+
+pub use message::*;
@@ -0,0 +1,163 @@
+//! Wire-level building blocks for NOW-PROTO message generators.
+
+use core::ops::BitAnd;
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+use now_proto_pdu::Uuid;
+
+/// Lengths which sit on both sides of each NOW_VARU32 encoding size boundary.
+///
+/// The last two values require the maximal (4-byte) NOW_VARU32 length encoding; bigger lengths
+/// (up to 1GiB) are valid as well, but would make fuzzing impractically slow.
+const VAR_U32_BOUNDARY_LENGTHS: &[usize] = &[0x3F, 0x40, 0x3FFF, 0x4000, 0x3F_FFFF, 0x40_0000];
+
+/// NOW_STATUS kinds defined by the protocol (GENERIC, NOW, WINAPI, UNIX).
+const STATUS_KINDS: &[u16] = &[0x0000, 0x0001, 0x0002, 0x0003];
+
+/// Encoded NOW_MSG frame builder.
+pub(super) struct FrameWriter {
+    class: u8,
+    kind: u8,
+    flags: u16,
+    body: Vec<u8>,
+}
+
+impl FrameWriter {
+    pub(super) fn new(class: u8, kind: u8, flags: u16) -> Self {
+        Self {
+            class,
+            kind,
+            flags,
+            body: Vec::new(),
+        }
+    }
+
+    pub(super) fn u16(&mut self, value: u16) -> &mut Self {
+        self.body.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    pub(super) fn u32(&mut self, value: u32) -> &mut Self {
+        self.body.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    pub(super) fn u64(&mut self, value: u64) -> &mut Self {
+        self.body.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    /// NOW_GUID (NOW_VARSTR) in any of the textual representations accepted by the decoder.
+    pub(super) fn guid(&mut self, u: &mut Unstructured<'_>) -> Result<&mut Self> {
+        let uuid = Uuid::from_u128(u128::arbitrary(u)?);
+
+        let value = match u.int_in_range(0..=4)? {
+            0 => uuid.hyphenated().to_string(),
+            1 => uuid.hyphenated().to_string().to_uppercase(),
+            2 => uuid.braced().to_string(),
+            3 => uuid.simple().to_string(),
+            _ => uuid.urn().to_string(),
+        };
+
+        Ok(self.var_str(&value))
+    }
+
+    /// NOW_VARU32, always using the shortest possible encoding.
+    pub(super) fn var_u32(&mut self, value: u32) -> &mut Self {
+        assert!(value <= 0x3FFF_FFFF, "NOW_VARU32 value is out of range");
+
+        let size = match value {
+            0x00..=0x3F => 1,
+            0x40..=0x3FFF => 2,
+            0x4000..=0x3F_FFFF => 3,
+            _ => 4,
+        };
+
+        let bytes = value.to_be_bytes();
+        let mut encoded = [0u8; 4];
+        encoded[..size].copy_from_slice(&bytes[4 - size..]);
+        encoded[0] |= ((size - 1) as u8) << 6;
+
+        self.body.extend_from_slice(&encoded[..size]);
+        self
+    }
+
+    /// NOW_VARSTR: length, UTF-8 data and null terminator.
+    pub(super) fn var_str(&mut self, value: &str) -> &mut Self {
+        self.var_u32(value.len() as u32);
+        self.body.extend_from_slice(value.as_bytes());
+        self.body.push(0);
+        self
+    }
+
+    /// NOW_VARBUF: length and raw data.
+    pub(super) fn var_buf(&mut self, value: &[u8]) -> &mut Self {
+        self.var_u32(value.len() as u32);
+        self.body.extend_from_slice(value);
+        self
+    }
+
+    /// NOW_STATUS with arbitrary flags, kind, code and message.
+    pub(super) fn status(&mut self, u: &mut Unstructured<'_>) -> Result<&mut Self> {
+        let flags = u16::arbitrary(u)?;
+        let kind = if bool::arbitrary(u)? {
+            *u.choose(STATUS_KINDS)?
+        } else {
+            u16::arbitrary(u)?
+        };
+        let code = u32::arbitrary(u)?;
+        let message = var_str(u)?;
+
+        Ok(self.u16(flags).u16(kind).u32(code).var_str(&message))
+    }
+
+    pub(super) fn finish(&self) -> Vec<u8> {
+        let mut frame = Vec::with_capacity(8 + self.body.len());
+        frame.extend_from_slice(&(self.body.len() as u32).to_le_bytes());
+        frame.push(self.class);
+        frame.push(self.kind);
+        frame.extend_from_slice(&self.flags.to_le_bytes());
+        frame.extend_from_slice(&self.body);
+        frame
+    }
+}
+
+/// Flags value built from the `known` flag bits mask.
+///
+/// Yields any combination of the known flags, all of them at once or completely arbitrary bits
+/// (including the ones not defined by the protocol).
+pub(super) fn arbitrary_flags<'a, T>(u: &mut Unstructured<'a>, known: T) -> Result<T>
+where
+    T: Arbitrary<'a> + BitAnd<Output = T> + Copy,
+{
+    Ok(match u.int_in_range(0..=2)? {
+        0 => T::arbitrary(u)? & known,
+        1 => known,
+        _ => T::arbitrary(u)?,
+    })
+}
+
+/// String value for NOW_VARSTR fields, biased towards empty and boundary-length strings.
+pub(super) fn var_str(u: &mut Unstructured<'_>) -> Result<String> {
+    Ok(match u.int_in_range(0..=3)? {
+        0 => String::new(),
+        1 => {
+            let len = *u.choose(VAR_U32_BOUNDARY_LENGTHS)?;
+            let fill = u.int_in_range(0x20..=0x7E)?;
+            String::from_utf8(vec![fill; len]).expect("printable ASCII is valid UTF-8")
+        }
+        _ => String::arbitrary(u)?,
+    })
+}
+
+/// Buffer value for NOW_VARBUF fields, biased towards empty and boundary-length buffers.
+pub(super) fn var_buf(u: &mut Unstructured<'_>) -> Result<Vec<u8>> {
+    Ok(match u.int_in_range(0..=3)? {
+        0 => Vec::new(),
+        1 => {
+            let len = *u.choose(VAR_U32_BOUNDARY_LENGTHS)?;
+            vec![u8::arbitrary(u)?; len]
+        }
+        _ => Vec::arbitrary(u)?,
+    })
+}
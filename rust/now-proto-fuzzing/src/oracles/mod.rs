@@ -9,3 +9,100 @@
 //!
 //! When an oracle finds a bug, it should report it to the fuzzing engine by
 //! panicking.
+
+use now_proto_pdu::ironrdp_core::{decode, encode, Encode, ReadCursor};
+use now_proto_pdu::{NowDecodeOptions, NowMessage, NowMessageFramer};
+
+use crate::generators::{NowFrameInput, NowMessageInput};
+
+/// Encoding a message and decoding it back yields the same message.
+pub fn message_roundtrip(input: &NowMessageInput) {
+    let encoded = encode_exact(&input.message);
+
+    let decoded = decode::<NowMessage<'_>>(&encoded).expect("encoded message should be decodable");
+
+    assert_eq!(decoded, input.message, "decoded message differs from the original one");
+}
+
+/// `Encode::size()` is exactly the number of bytes written by `Encode::encode()`.
+pub fn message_size(input: &NowMessageInput) {
+    encode_exact(&input.message);
+}
+
+/// Decoding arbitrary input never panics, either directly or through the message framer.
+///
+/// Messages which do decode are also checked for re-encoding canonicality, and unknown or strictly
+/// decoded messages for byte-for-byte re-encoding.
+pub fn message_decode(data: &[u8]) {
+    if decode::<NowMessage<'_>>(data).is_ok() {
+        message_reencode(data);
+    }
+
+    let options = NowDecodeOptions::new().with_unknown_messages();
+    let mut cursor = ReadCursor::new(data);
+    if let Ok(message @ NowMessage::Unknown { .. }) = NowMessage::decode_with_options(&mut cursor, options) {
+        let frame = &data[..cursor.pos()];
+        assert_eq!(
+            encode_exact(&message),
+            frame,
+            "unknown message is not re-encoded byte-for-byte"
+        );
+    }
+
+    let options = NowDecodeOptions::new().with_strict();
+    let mut cursor = ReadCursor::new(data);
+    if let Ok(message) = NowMessage::decode_with_options(&mut cursor, options) {
+        let frame = &data[..cursor.pos()];
+        assert_eq!(
+            encode_exact(&message),
+            frame,
+            "strictly decoded message is not canonical"
+        );
+    }
+
+    let mut framer = NowMessageFramer::new();
+
+    for chunk in data.chunks(7) {
+        if framer.push(chunk).is_err() {
+            return;
+        }
+
+        let _ = framer.bytes_needed();
+
+        while let Ok(Some(message)) = framer.next_message() {
+            encode_exact(&message);
+        }
+    }
+}
+
+/// Re-encoding a decoded frame produces its canonical form: decoding the canonical bytes yields the
+/// same message, and encoding it again reproduces the same bytes.
+pub fn frame_reencode(input: &NowFrameInput) {
+    message_reencode(&input.frame);
+}
+
+fn message_reencode(frame: &[u8]) {
+    let message = decode::<NowMessage<'_>>(frame).expect("frame should be decodable");
+    let canonical = encode_exact(&message);
+
+    let redecoded = decode::<NowMessage<'_>>(&canonical).expect("canonical encoding should be decodable");
+    assert_eq!(redecoded, message, "canonical encoding decodes to a different message");
+
+    let reencoded = encode_exact(&redecoded);
+    assert_eq!(reencoded, canonical, "canonical encoding is not stable");
+}
+
+/// Encodes the message into a buffer of exactly `Encode::size()` bytes and checks that all of
+/// them were written.
+fn encode_exact(message: &NowMessage<'_>) -> Vec<u8> {
+    let size = message.size();
+    let mut buf = vec![0u8; size];
+
+    let written = encode(message, &mut buf).expect("message with valid size should be encodable");
+    assert_eq!(
+        written, size,
+        "Encode::size() does not match the number of bytes written"
+    );
+
+    buf
+}
@@ -2,7 +2,8 @@ use core::time;
 
 use bitflags::bitflags;
 use ironrdp_core::{
-    ensure_fixed_part_size, invalid_field_err, Decode, DecodeResult, Encode, EncodeResult, ReadCursor, WriteCursor,
+    ensure_fixed_part_size, ensure_size, invalid_field_err, Decode, DecodeResult, Encode, EncodeResult, ReadCursor,
+    WriteCursor,
 };
 
 use crate::{NowChannelMessage, NowChannelMsgKind, NowHeader, NowMessage, NowMessageClass};
@@ -10,28 +11,51 @@ use crate::{NowChannelMessage, NowChannelMsgKind, NowHeader, NowMessage, NowMess
 bitflags! {
     /// NOW-PROTO: NOW_CHANNEL_CAPSET_MSG flags field.
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
     pub struct NowChannelCapsetFlags: u16 {
         /// Set if heartbeat specify channel heartbeat interval.
         ///
         /// NOW-PROTO: NOW_CHANNEL_SET_HEARTBEAT
         const SET_HEARTBEAT = 0x0001;
+        /// Set if the message contains the `fileCapset` field.
+        ///
+        /// NOW-PROTO: NOW_CHANNEL_SET_FILE_CAPSET
+        const SET_FILE_CAPSET = 0x0002;
     }
 }
 
 bitflags! {
     /// NOW-PROTO: NOW_CHANNEL_CAPSET_MSG systemCapset field.
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
     pub struct NowSystemCapsetFlags: u16 {
         /// System shutdown command support.
         ///
         /// NOW-PROTO: NOW_CAP_SYSTEM_SHUTDOWN
         const SHUTDOWN = 0x0001;
+        /// System information query support.
+        ///
+        /// NOW-PROTO: NOW_CAP_SYSTEM_INFO
+        const INFO = 0x0002;
+        /// Pending system shutdown abort support.
+        ///
+        /// NOW-PROTO: NOW_CAP_SYSTEM_SHUTDOWN_ABORT
+        const SHUTDOWN_ABORT = 0x0004;
+        /// System power actions support (shutdown, reboot, sleep, hibernate and session restart).
+        ///
+        /// NOW-PROTO: NOW_CAP_SYSTEM_POWER_ACTION
+        const POWER_ACTION = 0x0008;
+        /// Process list and termination support.
+        ///
+        /// NOW-PROTO: NOW_CAP_SYSTEM_PROCESS
+        const PROCESS = 0x0010;
     }
 }
 
 bitflags! {
     /// NOW-PROTO: NOW_CHANNEL_CAPSET_MSG sessionCapset field.
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
     pub struct NowSessionCapsetFlags: u16 {
         /// Session lock command support.
         ///
@@ -59,6 +83,7 @@ bitflags! {
 bitflags! {
     /// NOW-PROTO: NOW_CHANNEL_CAPSET_MSG execCapset field.
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
     pub struct NowExecCapsetFlags: u16 {
         /// Generic "Run" execution style.
         ///
@@ -96,8 +121,33 @@ bitflags! {
     }
 }
 
+bitflags! {
+    /// NOW-PROTO: NOW_CHANNEL_CAPSET_MSG fileCapset field.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+    pub struct NowFileCapsetFlags: u16 {
+        /// File download (read transfer) support.
+        ///
+        /// NOW-PROTO: NOW_CAP_FILE_READ
+        const READ = 0x0001;
+        /// File upload (write transfer) support.
+        ///
+        /// NOW-PROTO: NOW_CAP_FILE_WRITE
+        const WRITE = 0x0002;
+        /// Filesystem browsing support (stat and directory listing).
+        ///
+        /// NOW-PROTO: NOW_CAP_FILE_BROWSE
+        const BROWSE = 0x0004;
+        /// Filesystem management support (directory creation, deletion and renaming).
+        ///
+        /// NOW-PROTO: NOW_CAP_FILE_MANAGE
+        const MANAGE = 0x0008;
+    }
+}
+
 /// NOW-PROTO version representation.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NowProtoVersion {
     // IMPORTANT: Field ordering is important for `PartialOrd` and `Ord` derived implementations.
     pub major: u16,
@@ -106,7 +156,7 @@ pub struct NowProtoVersion {
 
 impl NowProtoVersion {
     /// Represents the current version of the NOW protocol implemented by the library.
-    pub const CURRENT: Self = Self { major: 1, minor: 6 };
+    pub const CURRENT: Self = Self { major: 1, minor: 7 };
 
     /// Returns `true` if this version supports the encoding control exec flags
     /// (`NOW_EXEC_FLAG_*_RAW_ENCODING`, `NOW_EXEC_FLAG_*_UNICODE_CONSOLE`, and `NOW_EXEC_FLAG_PROCESS_ENCODING_UTF8`).
@@ -115,6 +165,20 @@ impl NowProtoVersion {
     }
 }
 
+// Sanity check: Limit min heartbeat interval to 5 seconds.
+const MIN_HEARTBEAT_INTERVAL: time::Duration = time::Duration::from_secs(5);
+
+// Sanity check: Limit max heartbeat interval to 24 hours.
+const MAX_HEARTBEAT_INTERVAL: time::Duration = time::Duration::from_secs(60 * 60 * 24);
+
+fn ensure_heartbeat_interval(interval: time::Duration) -> EncodeResult<()> {
+    if interval < MIN_HEARTBEAT_INTERVAL || interval > MAX_HEARTBEAT_INTERVAL {
+        return Err(invalid_field_err!("heartbeat_timeout", "too big heartbeat interval"));
+    }
+
+    Ok(())
+}
+
 /// This message is first set by the client side, to advertise capabilities.
 /// Received client message should be downgraded by the server (remove non-intersecting
 /// capabilities) and sent back to the client at the start of DVC channel communications.
@@ -125,12 +189,59 @@ impl NowProtoVersion {
 ///
 /// NOW-PROTO: NOW_CHANNEL_CAPSET_MSG
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "NowChannelCapsetMsgRepr")
+)]
 pub struct NowChannelCapsetMsg {
     version: NowProtoVersion,
     system_capset: NowSystemCapsetFlags,
     session_capset: NowSessionCapsetFlags,
     exec_capset: NowExecCapsetFlags,
     heartbeat_interval: Option<u32>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            default = "NowFileCapsetFlags::empty",
+            skip_serializing_if = "NowFileCapsetFlags::is_empty"
+        )
+    )]
+    file_capset: NowFileCapsetFlags,
+}
+
+/// Deserialized fields, validated the same way as in the builder methods.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(rename = "NowChannelCapsetMsg")]
+struct NowChannelCapsetMsgRepr {
+    version: NowProtoVersion,
+    system_capset: NowSystemCapsetFlags,
+    session_capset: NowSessionCapsetFlags,
+    exec_capset: NowExecCapsetFlags,
+    heartbeat_interval: Option<u32>,
+    #[serde(default = "NowFileCapsetFlags::empty")]
+    file_capset: NowFileCapsetFlags,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<NowChannelCapsetMsgRepr> for NowChannelCapsetMsg {
+    type Error = ironrdp_core::EncodeError;
+
+    fn try_from(repr: NowChannelCapsetMsgRepr) -> Result<Self, Self::Error> {
+        if let Some(interval) = repr.heartbeat_interval {
+            ensure_heartbeat_interval(time::Duration::from_secs(interval.into()))?;
+        }
+
+        Ok(Self {
+            version: repr.version,
+            system_capset: repr.system_capset,
+            session_capset: repr.session_capset,
+            exec_capset: repr.exec_capset,
+            heartbeat_interval: repr.heartbeat_interval,
+            file_capset: repr.file_capset,
+        })
+    }
 }
 
 impl Default for NowChannelCapsetMsg {
@@ -141,6 +252,7 @@ impl Default for NowChannelCapsetMsg {
             session_capset: NowSessionCapsetFlags::empty(),
             exec_capset: NowExecCapsetFlags::empty(),
             heartbeat_interval: None,
+            file_capset: NowFileCapsetFlags::empty(),
         }
     }
 }
@@ -148,6 +260,7 @@ impl Default for NowChannelCapsetMsg {
 impl NowChannelCapsetMsg {
     const NAME: &'static str = "NOW_CHANNEL_CAPSET_MSG";
     const FIXED_PART_SIZE: usize = 14; // NowProtoVersion(4) + u16(2) + u16(2) + u16(2) + u32(4)
+    const FILE_CAPSET_SIZE: usize = 2;
 
     #[must_use]
     pub fn with_system_capset(mut self, system_capset: NowSystemCapsetFlags) -> Self {
@@ -167,16 +280,16 @@ impl NowChannelCapsetMsg {
         self
     }
 
-    pub fn with_heartbeat_interval(mut self, interval: time::Duration) -> EncodeResult<Self> {
-        // Sanity check: Limit min heartbeat interval to 5 seconds.
-        const MIN_HEARTBEAT_INTERVAL: time::Duration = time::Duration::from_secs(5);
-
-        // Sanity check: Limit max heartbeat interval to 24 hours.
-        const MAX_HEARTBEAT_INTERVAL: time::Duration = time::Duration::from_secs(60 * 60 * 24);
+    /// File transfer capabilities; the `fileCapset` field is only sent if any capability is
+    /// set, to stay compatible with peers which do not support file transfers.
+    #[must_use]
+    pub fn with_file_capset(mut self, file_capset: NowFileCapsetFlags) -> Self {
+        self.file_capset = file_capset;
+        self
+    }
 
-        if interval < MIN_HEARTBEAT_INTERVAL || interval > MAX_HEARTBEAT_INTERVAL {
-            return Err(invalid_field_err!("heartbeat_timeout", "too big heartbeat interval"));
-        }
+    pub fn with_heartbeat_interval(mut self, interval: time::Duration) -> EncodeResult<Self> {
+        ensure_heartbeat_interval(interval)?;
 
         let interval = u32::try_from(interval.as_secs()).expect("heartbeat interval fits into u32");
 
@@ -196,6 +309,10 @@ impl NowChannelCapsetMsg {
         self.exec_capset
     }
 
+    pub fn file_capset(&self) -> NowFileCapsetFlags {
+        self.file_capset
+    }
+
     pub fn heartbeat_interval(&self) -> Option<time::Duration> {
         self.heartbeat_interval
             .map(|interval| time::Duration::from_secs(u64::from(interval)))
@@ -218,6 +335,7 @@ impl NowChannelCapsetMsg {
         let system_capset = self.system_capset & other.system_capset;
         let session_capset = self.session_capset & other.session_capset;
         let exec_capset = self.exec_capset & other.exec_capset;
+        let file_capset = self.file_capset & other.file_capset;
 
         // Choose minimum specified heartbeat interval between two peers.
         let heartbeat_interval = match (self.heartbeat_interval, other.heartbeat_interval) {
@@ -233,6 +351,15 @@ impl NowChannelCapsetMsg {
             session_capset,
             exec_capset,
             heartbeat_interval,
+            file_capset,
+        }
+    }
+
+    fn body_size(&self) -> usize {
+        if self.file_capset.is_empty() {
+            Self::FIXED_PART_SIZE
+        } else {
+            Self::FIXED_PART_SIZE + Self::FILE_CAPSET_SIZE
         }
     }
 
@@ -259,26 +386,36 @@ impl NowChannelCapsetMsg {
             .contains(NowChannelCapsetFlags::SET_HEARTBEAT)
             .then_some(heartbeat_interval_value);
 
+        let file_capset = if flags.contains(NowChannelCapsetFlags::SET_FILE_CAPSET) {
+            ensure_size!(in: src, size: Self::FILE_CAPSET_SIZE);
+            NowFileCapsetFlags::from_bits_retain(src.read_u16())
+        } else {
+            NowFileCapsetFlags::empty()
+        };
+
         Ok(Self {
             version,
             system_capset,
             session_capset,
             exec_capset,
             heartbeat_interval,
+            file_capset,
         })
     }
 }
 
 impl Encode for NowChannelCapsetMsg {
     fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
-        let flags = if self.heartbeat_interval.is_some() {
-            NowChannelCapsetFlags::SET_HEARTBEAT
-        } else {
-            NowChannelCapsetFlags::empty()
-        };
+        let mut flags = NowChannelCapsetFlags::empty();
+        if self.heartbeat_interval.is_some() {
+            flags |= NowChannelCapsetFlags::SET_HEARTBEAT;
+        }
+        if !self.file_capset.is_empty() {
+            flags |= NowChannelCapsetFlags::SET_FILE_CAPSET;
+        }
 
         let header = NowHeader {
-            size: u32::try_from(Self::FIXED_PART_SIZE).expect("Capabilities have fixed size which fits into u32"),
+            size: u32::try_from(self.body_size()).expect("Capabilities have fixed size which fits into u32"),
             class: NowMessageClass::CHANNEL,
             kind: NowChannelMsgKind::CAPSET.0,
             flags: flags.bits(),
@@ -295,6 +432,11 @@ impl Encode for NowChannelCapsetMsg {
         dst.write_u16(self.exec_capset.bits());
         dst.write_u32(self.heartbeat_interval.unwrap_or_default());
 
+        if !self.file_capset.is_empty() {
+            ensure_size!(in: dst, size: Self::FILE_CAPSET_SIZE);
+            dst.write_u16(self.file_capset.bits());
+        }
+
         Ok(())
     }
 
@@ -303,7 +445,7 @@ impl Encode for NowChannelCapsetMsg {
     }
 
     fn size(&self) -> usize {
-        NowHeader::FIXED_PART_SIZE + Self::FIXED_PART_SIZE
+        NowHeader::FIXED_PART_SIZE + self.body_size()
     }
 }
 
@@ -1,12 +1,16 @@
 use ironrdp_core::{invalid_field_err, Decode, DecodeResult, Encode, EncodeResult, IntoOwned, ReadCursor, WriteCursor};
 
-use crate::{NowChannelMessage, NowChannelMsgKind, NowHeader, NowMessage, NowMessageClass, NowStatus, NowStatusError};
+use crate::{
+    CheckDecodeLimits, DecodeLimitsChecker, NowChannelMessage, NowChannelMsgKind, NowHeader, NowMessage,
+    NowMessageClass, NowStatus, NowStatusError,
+};
 
 /// Channel close notice, could be sent by either parties at any moment of communication to
 /// gracefully close DVC channel.
 ///
 /// NOW-PROTO: NOW_CHANNEL_CLOSE_MSG
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NowChannelCloseMsg<'a> {
     status: NowStatus<'a>,
 }
@@ -23,6 +27,12 @@ impl IntoOwned for NowChannelCloseMsg<'_> {
     }
 }
 
+impl CheckDecodeLimits for NowChannelCloseMsg<'_> {
+    fn check_decode_limits(&self, checker: &mut DecodeLimitsChecker) -> DecodeResult<()> {
+        self.status.check_decode_limits(checker)
+    }
+}
+
 impl Default for NowChannelCloseMsg<'_> {
     fn default() -> Self {
         let status = NowStatus::new_success();
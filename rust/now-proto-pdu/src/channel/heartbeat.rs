@@ -7,6 +7,7 @@ use crate::{NowChannelMessage, NowChannelMsgKind, NowHeader, NowMessage, NowMess
 ///
 /// NOW-PROTO: NOW_CHANNEL_HEARTBEAT_MSG
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NowChannelHeartbeatMsg {}
 
 impl NowChannelHeartbeatMsg {
@@ -3,17 +3,22 @@ mod close;
 mod heartbeat;
 
 pub use capset::{
-    NowChannelCapsetFlags, NowChannelCapsetMsg, NowExecCapsetFlags, NowProtoVersion, NowSessionCapsetFlags,
-    NowSystemCapsetFlags,
+    NowChannelCapsetFlags, NowChannelCapsetMsg, NowExecCapsetFlags, NowFileCapsetFlags, NowProtoVersion,
+    NowSessionCapsetFlags, NowSystemCapsetFlags,
 };
 pub use close::{NowChannelCloseMsg, OwnedNowChannelCloseMsg};
 pub use heartbeat::NowChannelHeartbeatMsg;
 use ironrdp_core::{DecodeResult, Encode, EncodeResult, IntoOwned, ReadCursor, WriteCursor};
 
-use crate::NowHeader;
+use crate::{CheckDecodeLimits, DecodeLimitsChecker, NowHeader};
 
 // Wrapper for the `NOW_CHANNEL_MSG_CLASS_ID` message class.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum NowChannelMessage<'a> {
     Capset(NowChannelCapsetMsg),
     Heartbeat(NowChannelHeartbeatMsg),
@@ -34,6 +39,16 @@ impl IntoOwned for NowChannelMessage<'_> {
     }
 }
 
+impl CheckDecodeLimits for NowChannelMessage<'_> {
+    fn check_decode_limits(&self, checker: &mut DecodeLimitsChecker) -> DecodeResult<()> {
+        match self {
+            Self::Close(msg) => msg.check_decode_limits(checker),
+            // Messages without variable-length fields.
+            Self::Capset(_) | Self::Heartbeat(_) => Ok(()),
+        }
+    }
+}
+
 impl<'a> NowChannelMessage<'a> {
     const NAME: &'static str = "NOW_CHANNEL_MSG";
 
@@ -45,6 +60,14 @@ impl<'a> NowChannelMessage<'a> {
             _ => Err(unsupported_message_err!(class: header.class.0, kind: header.kind)),
         }
     }
+
+    /// Returns `true` if the message kind is known for this message class.
+    pub(crate) fn is_known_kind(kind: u8) -> bool {
+        matches!(
+            NowChannelMsgKind(kind),
+            NowChannelMsgKind::CAPSET | NowChannelMsgKind::HEARTBEAT | NowChannelMsgKind::CLOSE
+        )
+    }
 }
 
 impl Encode for NowChannelMessage<'_> {
@@ -0,0 +1,150 @@
+//! `tokio-util` codec for NOW-PROTO messages.
+
+use core::fmt;
+use std::io;
+
+use bytes::BytesMut;
+use ironrdp_core::{DecodeError, Encode, EncodeError, IntoOwned, ReadCursor, WriteCursor};
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::framer::peek_frame_size;
+use crate::{NowDecodeOptions, NowMessage, NowMessageFramer, OwnedNowMessage};
+
+/// Error returned by [`NowMessageCodec`].
+#[derive(Debug)]
+pub enum NowCodecError {
+    /// Underlying transport error.
+    Io(io::Error),
+    /// Received message can't be decoded.
+    Decode(DecodeError),
+    /// Sent message can't be encoded.
+    Encode(EncodeError),
+}
+
+impl fmt::Display for NowCodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NowCodecError::Io(_) => write!(f, "NOW-PROTO transport error"),
+            NowCodecError::Decode(_) => write!(f, "failed to decode NOW-PROTO message"),
+            NowCodecError::Encode(_) => write!(f, "failed to encode NOW-PROTO message"),
+        }
+    }
+}
+
+impl core::error::Error for NowCodecError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            NowCodecError::Io(err) => Some(err),
+            NowCodecError::Decode(err) => Some(err),
+            NowCodecError::Encode(err) => Some(err),
+        }
+    }
+}
+
+impl From<io::Error> for NowCodecError {
+    fn from(err: io::Error) -> Self {
+        NowCodecError::Io(err)
+    }
+}
+
+impl From<DecodeError> for NowCodecError {
+    fn from(err: DecodeError) -> Self {
+        NowCodecError::Decode(err)
+    }
+}
+
+impl From<EncodeError> for NowCodecError {
+    fn from(err: EncodeError) -> Self {
+        NowCodecError::Encode(err)
+    }
+}
+
+/// [`Encoder`]/[`Decoder`] pair framing NOW-PROTO messages by `NOW_HEADER`, allowing to wrap any
+/// `AsyncRead + AsyncWrite` transport into `tokio_util::codec::Framed`.
+///
+/// Frames larger than the configured maximum frame size are rejected as soon as their header is
+/// received.
+#[derive(Debug, Clone)]
+pub struct NowMessageCodec {
+    max_frame_size: usize,
+    decode_options: NowDecodeOptions,
+}
+
+impl Default for NowMessageCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NowMessageCodec {
+    pub fn new() -> Self {
+        Self {
+            max_frame_size: NowMessageFramer::DEFAULT_MAX_FRAME_SIZE,
+            decode_options: NowDecodeOptions::new(),
+        }
+    }
+
+    /// Sets maximum accepted frame size (header included).
+    #[must_use]
+    pub fn with_max_frame_size(mut self, max_frame_size: usize) -> Self {
+        self.max_frame_size = max_frame_size;
+        self
+    }
+
+    pub fn max_frame_size(&self) -> usize {
+        self.max_frame_size
+    }
+
+    /// Sets options used to decode received messages.
+    #[must_use]
+    pub fn with_decode_options(mut self, decode_options: NowDecodeOptions) -> Self {
+        self.decode_options = decode_options;
+        self
+    }
+
+    pub fn decode_options(&self) -> NowDecodeOptions {
+        self.decode_options
+    }
+}
+
+impl Decoder for NowMessageCodec {
+    type Item = OwnedNowMessage;
+    type Error = NowCodecError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        let frame_size = match peek_frame_size(src, self.max_frame_size, &self.decode_options.limits())? {
+            Some(frame_size) => frame_size,
+            None => return Ok(None),
+        };
+
+        if src.len() < frame_size {
+            src.reserve(frame_size - src.len());
+            return Ok(None);
+        }
+
+        let frame = src.split_to(frame_size);
+        let msg = NowMessage::decode_with_options(&mut ReadCursor::new(&frame), self.decode_options)?;
+
+        Ok(Some(msg.into_owned()))
+    }
+}
+
+impl Encoder<NowMessage<'_>> for NowMessageCodec {
+    type Error = NowCodecError;
+
+    fn encode(&mut self, item: NowMessage<'_>, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let start = dst.len();
+        let size = item.size();
+
+        dst.resize(start.saturating_add(size), 0);
+
+        let mut cursor = WriteCursor::new(&mut dst[start..]);
+
+        if let Err(err) = item.encode(&mut cursor) {
+            dst.truncate(start);
+            return Err(err.into());
+        }
+
+        Ok(())
+    }
+}
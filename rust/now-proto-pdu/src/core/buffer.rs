@@ -1,20 +1,23 @@
 //! Buffer types for NOW protocol.
 
 use alloc::borrow::Cow;
+use alloc::vec::Vec;
 use core::ops::Deref;
 
 use ironrdp_core::{
-    cast_length, ensure_size, invalid_field_err, Decode, DecodeResult, Encode, EncodeResult, IntoOwned, ReadCursor,
-    WriteCursor,
+    cast_length, ensure_size, invalid_field_err, Decode, DecodeResult, Encode, EncodeError, EncodeResult, IntoOwned,
+    ReadCursor, WriteCursor,
 };
 
 use crate::VarU32;
 
 /// Buffer up to 2^30 bytes long (Length has compact variable length encoding).
 ///
+/// Decoded buffers borrow their data from the source buffer.
+///
 /// NOW-PROTO: NOW_VARBUF
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
-pub(crate) struct NowVarBuf<'a>(Cow<'a, [u8]>);
+pub struct NowVarBuf<'a>(Cow<'a, [u8]>);
 
 impl IntoOwned for NowVarBuf<'_> {
     type Owned = NowVarBuf<'static>;
@@ -27,8 +30,11 @@ impl IntoOwned for NowVarBuf<'_> {
 impl<'a> NowVarBuf<'a> {
     const NAME: &'static str = "NOW_VARBUF";
 
+    /// Maximum buffer length in bytes.
+    pub const MAX_LEN: usize = VarU32::MAX as usize;
+
     /// Create a new `NowVarBuf` instance. Returns an error if the provided value is too large.
-    pub(crate) fn new(value: impl Into<Cow<'a, [u8]>>) -> EncodeResult<Self> {
+    pub fn new(value: impl Into<Cow<'a, [u8]>>) -> EncodeResult<Self> {
         let value = value.into();
 
         let _: u32 = value
@@ -40,6 +46,14 @@ impl<'a> NowVarBuf<'a> {
 
         Ok(NowVarBuf(value))
     }
+
+    pub fn as_slice(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> Cow<'a, [u8]> {
+        self.0
+    }
 }
 
 impl Encode for NowVarBuf<'_> {
@@ -88,3 +102,116 @@ impl Deref for NowVarBuf<'_> {
         &self.0
     }
 }
+
+impl AsRef<[u8]> for NowVarBuf<'_> {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for NowVarBuf<'a> {
+    type Error = EncodeError;
+
+    fn try_from(value: &'a [u8]) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl TryFrom<Vec<u8>> for NowVarBuf<'_> {
+    type Error = EncodeError;
+
+    fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl<'a> From<NowVarBuf<'a>> for Cow<'a, [u8]> {
+    fn from(value: NowVarBuf<'a>) -> Self {
+        value.0
+    }
+}
+
+/// Buffers are represented as lowercase hex strings in human-readable formats (e.g. JSON), and as
+/// raw bytes otherwise.
+#[cfg(feature = "serde")]
+impl serde::Serialize for NowVarBuf<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if !serializer.is_human_readable() {
+            return serializer.serialize_bytes(&self.0);
+        }
+
+        let mut hex = alloc::string::String::with_capacity(self.0.len() * 2);
+        for byte in self.0.iter() {
+            hex.push(char::from_digit(u32::from(byte >> 4), 16).expect("nibble is a valid hex digit"));
+            hex.push(char::from_digit(u32::from(byte & 0x0F), 16).expect("nibble is a valid hex digit"));
+        }
+
+        serializer.serialize_str(&hex)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for NowVarBuf<'_> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use core::fmt;
+
+        use serde::de::{Error, SeqAccess, Visitor};
+
+        struct BufVisitor;
+
+        impl<'de> Visitor<'de> for BufVisitor {
+            type Value = Vec<u8>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("hex string or byte array")
+            }
+
+            fn visit_str<E: Error>(self, value: &str) -> Result<Self::Value, E> {
+                if !value.len().is_multiple_of(2) {
+                    return Err(E::custom("odd number of hex digits"));
+                }
+
+                value
+                    .as_bytes()
+                    .chunks(2)
+                    .map(|pair| {
+                        let high = char::from(pair[0]).to_digit(16);
+                        let low = char::from(pair[1]).to_digit(16);
+
+                        match (high, low) {
+                            // LINTS: Both digits are below 16, therefore the value fits into u8.
+                            #[allow(clippy::cast_possible_truncation)]
+                            (Some(high), Some(low)) => Ok(((high << 4) | low) as u8),
+                            _ => Err(E::custom("invalid hex digit")),
+                        }
+                    })
+                    .collect()
+            }
+
+            fn visit_bytes<E: Error>(self, value: &[u8]) -> Result<Self::Value, E> {
+                Ok(value.to_vec())
+            }
+
+            fn visit_byte_buf<E: Error>(self, value: Vec<u8>) -> Result<Self::Value, E> {
+                Ok(value)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut data = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+                while let Some(byte) = seq.next_element()? {
+                    data.push(byte);
+                }
+
+                Ok(data)
+            }
+        }
+
+        let data = if deserializer.is_human_readable() {
+            deserializer.deserialize_str(BufVisitor)?
+        } else {
+            deserializer.deserialize_bytes(BufVisitor)?
+        };
+
+        NowVarBuf::new(data).map_err(D::Error::custom)
+    }
+}
@@ -7,12 +7,15 @@ use uuid::Uuid;
 
 use crate::NowVarStr;
 
-/// A GUID (Globally Unique Identifier) represented as a lowercase string
-/// in the format "00112233-4455-6677-8899-aabbccddeeff"
+/// A GUID (Globally Unique Identifier) encoded as a lowercase string in the format
+/// "00112233-4455-6677-8899-aabbccddeeff".
+///
+/// Decoding accepts any textual UUID representation supported by [`Uuid::try_parse`].
 ///
 /// NOW-PROTO: NOW_GUID (encoded as NOW_VARSTR)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub(crate) struct NowGuid {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+pub struct NowGuid {
     inner: Uuid,
 }
 
@@ -20,19 +23,18 @@ impl NowGuid {
     const NAME: &'static str = "NOW_GUID";
 
     /// Create a new GUID from a UUID
-    pub(crate) fn new(uuid: Uuid) -> Self {
+    pub fn new(uuid: Uuid) -> Self {
         Self { inner: uuid }
     }
 
     /// Create a new GUID from a string representation
-    /// String must be in the format "00112233-4455-6677-8899-aabbccddeeff" (lowercase)
     fn from_str_with_validation(s: &str) -> Result<Self, uuid::Error> {
         let uuid = Uuid::try_parse(s)?;
         Ok(Self::new(uuid))
     }
 
     /// Get the UUID representation
-    pub(crate) fn as_uuid(&self) -> Uuid {
+    pub fn as_uuid(&self) -> Uuid {
         self.inner
     }
 }
@@ -50,6 +52,18 @@ impl fmt::Display for NowGuid {
     }
 }
 
+impl From<Uuid> for NowGuid {
+    fn from(uuid: Uuid) -> Self {
+        Self::new(uuid)
+    }
+}
+
+impl From<NowGuid> for Uuid {
+    fn from(guid: NowGuid) -> Self {
+        guid.as_uuid()
+    }
+}
+
 impl IntoOwned for NowGuid {
     type Owned = NowGuid;
 
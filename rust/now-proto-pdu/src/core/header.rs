@@ -1,6 +1,11 @@
-use ironrdp_core::{ensure_fixed_part_size, Decode, DecodeResult, Encode, EncodeResult, ReadCursor, WriteCursor};
+use ironrdp_core::{
+    cast_length, ensure_fixed_part_size, ensure_size, invalid_field_err, Decode, DecodeResult, Encode, EncodeResult,
+    ReadCursor, WriteCursor,
+};
 
+/// NOW-PROTO message class identifier.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
 pub struct NowMessageClass(pub u8);
 
 impl NowMessageClass {
@@ -18,12 +23,36 @@ impl NowMessageClass {
 
     /// NOW-PROTO: NOW_RDM_MSG_CLASS_ID
     pub const RDM: Self = Self(0x14);
+
+    /// NOW-PROTO: NOW_FILE_MSG_CLASS_ID
+    pub const FILE: Self = Self(0x15);
+
+    /// First message class of the range reserved for vendor extensions (`0x40..=0xFF`), which is
+    /// never assigned to NOW-PROTO message classes.
+    ///
+    /// NOW-PROTO: NOW_VENDOR_MSG_CLASS_ID_MIN
+    pub const VENDOR_MIN: Self = Self(0x40);
+
+    /// Returns `true` if the message class is defined by NOW-PROTO (as opposed to vendor
+    /// extension classes).
+    pub fn is_standard(&self) -> bool {
+        matches!(
+            *self,
+            Self::CHANNEL | Self::SYSTEM | Self::SESSION | Self::EXEC | Self::RDM | Self::FILE
+        )
+    }
+
+    /// Returns `true` if the message class belongs to the range reserved for vendor extensions.
+    pub fn is_vendor(&self) -> bool {
+        self.0 >= Self::VENDOR_MIN.0
+    }
 }
 
 /// The NOW_HEADER structure is the header common to all NOW protocol messages.
 ///
 /// NOW-PROTO: NOW_HEADER
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NowHeader {
     pub size: u32,
     pub class: NowMessageClass,
@@ -34,6 +63,50 @@ pub struct NowHeader {
 impl NowHeader {
     const NAME: &'static str = "NOW_HEADER";
     pub const FIXED_PART_SIZE: usize = 8;
+
+    /// Creates header for the message with empty body and no flags set.
+    pub const fn new(class: NowMessageClass, kind: u8) -> Self {
+        Self {
+            size: 0,
+            class,
+            kind,
+            flags: 0,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_flags(mut self, flags: u16) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Sets message body size (header excluded). Returns error if the size does not fit into
+    /// `msgSize` field.
+    pub fn with_body_size(mut self, body_size: usize) -> EncodeResult<Self> {
+        self.size = cast_length!(Self::NAME, "size", body_size)?;
+        Ok(self)
+    }
+
+    /// Ensures that the header belongs to the message with the given class and kind.
+    pub fn ensure_message_type(&self, class: NowMessageClass, kind: u8) -> DecodeResult<()> {
+        if self.class != class || self.kind != kind {
+            return Err(invalid_field_err!("type", "invalid message type"));
+        }
+
+        Ok(())
+    }
+
+    /// Reads the whole message body announced by the header.
+    ///
+    /// Body could contain more data than expected by the message decoder (e.g. fields added in
+    /// newer protocol versions), therefore message bodies should be decoded from the returned
+    /// slice instead of the source cursor.
+    pub fn read_body<'a>(&self, src: &mut ReadCursor<'a>) -> DecodeResult<&'a [u8]> {
+        let size: usize = cast_length!(Self::NAME, "size", self.size)?;
+
+        ensure_size!(ctx: Self::NAME, in: src, size: size);
+        Ok(src.read_slice(size))
+    }
 }
 
 impl Encode for NowHeader {
@@ -1,7 +1,8 @@
 //! This module contains `NOW-PROTO` core types definitions.
 //!
-//! Note that these types are not intended to be used directly by the user, and not exported in the
-//! public API.
+//! Primitive types (`NOW_VARU32`, `NOW_VARSTR`, `NOW_VARBUF`, `NOW_GUID` and `NOW_HEADER`) are
+//! exported to allow defining spec-conformant vendor/extension messages outside of this crate.
+//! Messages defined by this crate do not expose them in their own API.
 
 mod buffer;
 mod guid;
@@ -10,12 +11,12 @@ mod number;
 mod status;
 mod string;
 
-pub(crate) use buffer::NowVarBuf;
-pub(crate) use guid::NowGuid;
-pub(crate) use header::{NowHeader, NowMessageClass};
-pub(crate) use number::VarU32;
+pub use buffer::NowVarBuf;
+pub use guid::NowGuid;
+pub use header::{NowHeader, NowMessageClass};
+pub use number::VarU32;
 pub(crate) use status::NowStatus;
-// Only public-exported type is the status error, which should be available to the user for error
-// handling.
-pub use status::{NowProtoError, NowStatusError, NowStatusErrorKind};
-pub(crate) use string::NowVarStr;
+// Other public-exported types are the status error, which should be available to the user for error
+// handling, and the status flags, which are needed to interpret raw message dumps.
+pub use status::{NowProtoError, NowStatusError, NowStatusErrorKind, NowStatusFlags};
+pub use string::NowVarStr;
@@ -3,19 +3,30 @@ use ironrdp_core::{
     ensure_size, invalid_field_err, Decode, DecodeResult, Encode, EncodeError, EncodeResult, ReadCursor, WriteCursor,
 };
 
-/// Variable-length encoded u32.
+/// Variable-length encoded u32, used for `NOW_VARSTR` and `NOW_VARBUF` lengths.
 /// Value range: `[0..0x3FFFFFFF]`
 ///
+/// Values are encoded big-endian in 1 to 4 bytes; the two most significant bits of the first byte
+/// hold the number of the following bytes, which leaves 30 bits for the value itself:
+///
+/// - `0x00..=0x3F`: 1 byte;
+/// - `0x40..=0x3FFF`: 2 bytes;
+/// - `0x4000..=0x3FFFFF`: 3 bytes;
+/// - `0x400000..=0x3FFFFFFF`: 4 bytes.
+///
 /// NOW-PROTO: NOW_VARU32
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct VarU32(u32);
 
 impl VarU32 {
+    /// Minimum encodable value.
     pub const MIN: u32 = 0x00000000;
+    /// Maximum encodable value (2^30 - 1).
     pub const MAX: u32 = 0x3FFFFFFF;
 
     const NAME: &'static str = "NOW_VARU32";
 
+    /// Creates `VarU32`. Returns error if the value is greater than [`Self::MAX`].
     pub fn new(value: u32) -> EncodeResult<Self> {
         if value > Self::MAX {
             return Err(invalid_field_err!("value", "too large number"));
@@ -7,12 +7,13 @@ use ironrdp_core::{
     ensure_fixed_part_size, Decode, DecodeResult, Encode, EncodeResult, IntoOwned, ReadCursor, WriteCursor,
 };
 
-use crate::NowVarStr;
+use crate::{CheckDecodeLimits, DecodeLimitsChecker, NowVarStr};
 
 bitflags! {
     /// NOW-PROTO: NOW_STATUS flags field.
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-    struct NowStatusFlags: u16 {
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+    pub struct NowStatusFlags: u16 {
         /// This flag set for all error statuses. If flag is not set, operation was successful.
         ///
         /// NOW-PROTO: NOW_STATUS_ERROR
@@ -50,6 +51,11 @@ impl RawNowProtoError {
 
 /// `code` field value of `NOW_STATUS` message if `kind` is `NOW_STATUS_ERROR_KIND_NOW`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum NowProtoError {
     /// Resource (e.g. exec session id is already in use).
     ///
@@ -140,6 +146,11 @@ impl fmt::Display for NowProtoError {
 ///
 /// Converted internally by the library to/from `kind` and `code` fields of `NOW_STATUS` message.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum NowStatusErrorKind {
     /// `code` value is undefined and could be ignored.
     ///
@@ -218,6 +229,7 @@ impl NowStatusErrorKind {
 
 /// Wrapper type around NOW_STATUS errors. Provides rust-friendly interface for error handling.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NowStatusError {
     kind: NowStatusErrorKind,
     message: NowVarStr<'static>,
@@ -301,8 +313,16 @@ impl core::error::Error for NowStatusError {}
 
 /// Channel or session operation status.
 ///
+/// Serialized as `"success"`, or as `{"error": ...}` with the named error kind and message
+/// (see [`NowStatusError`]) instead of raw `NOW_STATUS` fields.
+///
 /// NOW-PROTO: NOW_STATUS
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "NowStatusRepr", from = "NowStatusRepr")
+)]
 pub(crate) struct NowStatus<'a> {
     flags: NowStatusFlags,
     kind: RawNowStatusKind,
@@ -310,6 +330,34 @@ pub(crate) struct NowStatus<'a> {
     message: NowVarStr<'a>,
 }
 
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename = "NowStatus", rename_all = "snake_case")]
+enum NowStatusRepr {
+    Success,
+    Error(NowStatusError),
+}
+
+#[cfg(feature = "serde")]
+impl From<NowStatus<'_>> for NowStatusRepr {
+    fn from(status: NowStatus<'_>) -> Self {
+        match status.to_result() {
+            Ok(()) => Self::Success,
+            Err(error) => Self::Error(error),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<NowStatusRepr> for NowStatus<'_> {
+    fn from(repr: NowStatusRepr) -> Self {
+        match repr {
+            NowStatusRepr::Success => Self::new_success(),
+            NowStatusRepr::Error(error) => Self::new_error(error),
+        }
+    }
+}
+
 impl IntoOwned for NowStatus<'_> {
     type Owned = NowStatus<'static>;
 
@@ -323,6 +371,12 @@ impl IntoOwned for NowStatus<'_> {
     }
 }
 
+impl CheckDecodeLimits for NowStatus<'_> {
+    fn check_decode_limits(&self, checker: &mut DecodeLimitsChecker) -> DecodeResult<()> {
+        checker.string(&self.message)
+    }
+}
+
 impl NowStatus<'_> {
     const NAME: &'static str = "NOW_STATUS";
     const FIXED_PART_SIZE: usize = 8;
@@ -1,21 +1,25 @@
 //! String types
 
 use alloc::borrow::Cow;
+use alloc::string::String;
 use core::ops::Deref;
-use core::str;
+use core::{fmt, str};
 
 use ironrdp_core::{
-    cast_length, ensure_size, invalid_field_err, Decode, DecodeResult, Encode, EncodeResult, IntoOwned, ReadCursor,
-    WriteCursor,
+    cast_length, ensure_size, invalid_field_err, Decode, DecodeResult, Encode, EncodeError, EncodeResult, IntoOwned,
+    ReadCursor, WriteCursor,
 };
 
 use crate::VarU32;
 
 /// String value up to 2^30 bytes long (Length has compact variable length encoding).
 ///
+/// Strings are encoded as UTF-8 bytes followed by the null terminator, which is not included in
+/// the length. Decoded strings borrow their data from the source buffer.
+///
 /// NOW-PROTO: NOW_VARSTR
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
-pub(crate) struct NowVarStr<'a>(Cow<'a, str>);
+pub struct NowVarStr<'a>(Cow<'a, str>);
 
 impl IntoOwned for NowVarStr<'_> {
     type Owned = NowVarStr<'static>;
@@ -28,8 +32,11 @@ impl IntoOwned for NowVarStr<'_> {
 impl<'a> NowVarStr<'a> {
     const NAME: &'static str = "NOW_VARSTR";
 
+    /// Maximum string length in bytes (UTF-8 encoded, null terminator excluded).
+    pub const MAX_LEN: usize = VarU32::MAX as usize;
+
     /// Creates `NowVarStr` from std string. Returns error if string is too big for the protocol.
-    pub(crate) fn new(value: impl Into<Cow<'a, str>>) -> EncodeResult<Self> {
+    pub fn new(value: impl Into<Cow<'a, str>>) -> EncodeResult<Self> {
         let value = value.into();
         // IMPORTANT: we need to check for encoded UTF-8 size, not the string length.
 
@@ -42,6 +49,14 @@ impl<'a> NowVarStr<'a> {
 
         Ok(NowVarStr(value))
     }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> Cow<'a, str> {
+        self.0
+    }
 }
 
 impl Encode for NowVarStr<'_> {
@@ -101,3 +116,52 @@ impl Deref for NowVarStr<'_> {
         &self.0
     }
 }
+
+impl AsRef<str> for NowVarStr<'_> {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for NowVarStr<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl<'a> TryFrom<&'a str> for NowVarStr<'a> {
+    type Error = EncodeError;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl TryFrom<String> for NowVarStr<'_> {
+    type Error = EncodeError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl<'a> From<NowVarStr<'a>> for Cow<'a, str> {
+    fn from(value: NowVarStr<'a>) -> Self {
+        value.0
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for NowVarStr<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for NowVarStr<'_> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        NowVarStr::new(value).map_err(serde::de::Error::custom)
+    }
+}
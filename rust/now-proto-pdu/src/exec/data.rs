@@ -1,17 +1,16 @@
 use alloc::borrow::Cow;
 
 use bitflags::bitflags;
-use ironrdp_core::{
-    cast_length, ensure_fixed_part_size, invalid_field_err, Decode, DecodeResult, Encode, EncodeResult, IntoOwned,
-    ReadCursor, WriteCursor,
-};
+use ironrdp_core::{invalid_field_err, DecodeResult, EncodeResult};
+use now_proto_derive::NowPdu;
 
-use crate::{NowExecMessage, NowExecMsgKind, NowHeader, NowMessage, NowMessageClass, NowVarBuf};
+use crate::NowVarBuf;
 
 bitflags! {
     /// NOW-PROTO: NOW_EXEC_DATA_MSG flags field.
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-    struct NowExecDataFlags: u16 {
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+    pub struct NowExecDataFlags: u16 {
         /// This is the last data message, the command completed execution.
         ///
         /// NOW-PROTO: NOW_EXEC_FLAG_DATA_LAST
@@ -33,6 +32,11 @@ bitflags! {
 
 /// Redirected std stream kind for the NOW_EXEC_DATA_MSG message.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum NowExecDataStreamKind {
     Stdin,
     Stdout,
@@ -68,31 +72,17 @@ impl NowExecDataStreamKind {
 /// The NOW_EXEC_DATA_MSG message is used to send input/output data as part of a remote execution.
 ///
 /// NOW-PROTO: NOW_EXEC_DATA_MSG
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, NowPdu)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[now(class = EXEC, kind = DATA, variant = Data, validate)]
 pub struct NowExecDataMsg<'a> {
+    #[now(flags)]
     flags: NowExecDataFlags,
     session_id: u32,
     data: NowVarBuf<'a>,
 }
 
-impl_pdu_borrowing!(NowExecDataMsg<'_>, OwnedNowExecDataMsg);
-
-impl IntoOwned for NowExecDataMsg<'_> {
-    type Owned = OwnedNowExecDataMsg;
-
-    fn into_owned(self) -> Self::Owned {
-        OwnedNowExecDataMsg {
-            flags: self.flags,
-            session_id: self.session_id,
-            data: self.data.into_owned(),
-        }
-    }
-}
-
 impl<'a> NowExecDataMsg<'a> {
-    const NAME: &'static str = "NOW_EXEC_DATA_MSG";
-    const FIXED_PART_SIZE: usize = 4;
-
     pub fn new(
         session_id: u32,
         stream: NowExecDataStreamKind,
@@ -110,7 +100,7 @@ impl<'a> NowExecDataMsg<'a> {
             data: NowVarBuf::new(data)?,
         };
 
-        ensure_now_message_size!(Self::FIXED_PART_SIZE, msg.data.size());
+        msg.ensure_message_size()?;
 
         Ok(msg)
     }
@@ -131,70 +121,8 @@ impl<'a> NowExecDataMsg<'a> {
         &self.data
     }
 
-    // LINTS: Overall message size always fits into usize; VarBuf size always a few powers of 2 less
-    // than u32::MAX, therefore it fits into usize
-    #[allow(clippy::arithmetic_side_effects)]
-    fn body_size(&self) -> usize {
-        Self::FIXED_PART_SIZE + self.data.size()
-    }
-
-    pub(super) fn decode_from_body(header: NowHeader, src: &mut ReadCursor<'a>) -> DecodeResult<Self> {
-        ensure_fixed_part_size!(in: src);
-
-        let flags = NowExecDataFlags::from_bits_retain(header.flags);
-        let session_id = src.read_u32();
-        let data = NowVarBuf::decode(src)?;
-
-        Ok(Self {
-            flags,
-            session_id,
-            data,
-        })
-    }
-}
-
-impl Encode for NowExecDataMsg<'_> {
-    fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
-        let header = NowHeader {
-            size: cast_length!("size", self.body_size())?,
-            class: NowMessageClass::EXEC,
-            kind: NowExecMsgKind::DATA.0,
-            flags: self.flags.bits(),
-        };
-
-        header.encode(dst)?;
-
-        ensure_fixed_part_size!(in: dst);
-        dst.write_u32(self.session_id);
-        self.data.encode(dst)?;
-
-        Ok(())
-    }
-
-    fn name(&self) -> &'static str {
-        Self::NAME
-    }
-
-    // LINTS: See body_size()
-    #[allow(clippy::arithmetic_side_effects)]
-    fn size(&self) -> usize {
-        NowHeader::FIXED_PART_SIZE + self.body_size()
-    }
-}
-
-impl<'de> Decode<'de> for NowExecDataMsg<'de> {
-    fn decode(src: &mut ReadCursor<'de>) -> DecodeResult<Self> {
-        let header = NowHeader::decode(src)?;
-
-        match (header.class, NowExecMsgKind(header.kind)) {
-            (NowMessageClass::EXEC, NowExecMsgKind::DATA) => Self::decode_from_body(header, src),
-            _ => Err(invalid_field_err!("type", "invalid message type")),
-        }
-    }
-}
-
-impl<'de> From<NowExecDataMsg<'de>> for NowMessage<'de> {
-    fn from(msg: NowExecDataMsg<'de>) -> Self {
-        NowMessage::Exec(NowExecMessage::Data(msg))
+    #[cfg(feature = "serde")]
+    fn validate(&self) -> DecodeResult<()> {
+        self.stream_kind().map(|_| ())
     }
 }
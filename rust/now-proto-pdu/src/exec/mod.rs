@@ -12,23 +12,27 @@ mod started;
 mod win_ps;
 
 pub use abort::NowExecAbortMsg;
-pub use batch::{NowExecBatchMsg, OwnedNowExecBatchMsg};
+pub use batch::{NowExecBatchFlags, NowExecBatchMsg, OwnedNowExecBatchMsg};
 pub use cancel_req::NowExecCancelReqMsg;
 pub use cancel_rsp::{NowExecCancelRspMsg, OwnedNowExecCancelRspMsg};
-pub use data::{NowExecDataMsg, NowExecDataStreamKind, OwnedNowExecDataMsg};
+pub use data::{NowExecDataFlags, NowExecDataMsg, NowExecDataStreamKind, OwnedNowExecDataMsg};
 use ironrdp_core::{invalid_field_err, DecodeResult, Encode, EncodeResult, IntoOwned, ReadCursor, WriteCursor};
-pub use process::{NowExecProcessMsg, OwnedNowExecProcessMsg};
+pub use process::{NowExecProcessFlags, NowExecProcessMsg, OwnedNowExecProcessMsg};
 pub use pwsh::{NowExecPwshMsg, OwnedNowExecPwshMsg};
 pub use result::{NowExecResultMsg, OwnedNowExecResultMsg};
-pub use run::{NowExecRunMsg, OwnedNowExecRunMsg};
-pub use shell::{NowExecShellMsg, OwnedNowExecShellMsg};
+pub use run::{NowExecRunFlags, NowExecRunMsg, OwnedNowExecRunMsg};
+pub use shell::{NowExecShellFlags, NowExecShellMsg, OwnedNowExecShellMsg};
 pub use started::NowExecStartedMsg;
-pub(crate) use win_ps::NowExecWinPsFlags;
-pub use win_ps::{ComApartmentStateKind, NowExecWinPsMsg, OwnedNowExecWinPsMsg};
+pub use win_ps::{ComApartmentStateKind, NowExecWinPsFlags, NowExecWinPsMsg, OwnedNowExecWinPsMsg};
 
-use crate::NowHeader;
+use crate::{CheckDecodeLimits, DecodeLimitsChecker, NowHeader};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum NowExecMessage<'a> {
     Abort(NowExecAbortMsg),
     CancelReq(NowExecCancelReqMsg),
@@ -67,6 +71,24 @@ impl IntoOwned for NowExecMessage<'_> {
     }
 }
 
+impl CheckDecodeLimits for NowExecMessage<'_> {
+    fn check_decode_limits(&self, checker: &mut DecodeLimitsChecker) -> DecodeResult<()> {
+        match self {
+            Self::CancelRsp(msg) => msg.check_decode_limits(checker),
+            Self::Result(msg) => msg.check_decode_limits(checker),
+            Self::Data(msg) => msg.check_decode_limits(checker),
+            Self::Run(msg) => msg.check_decode_limits(checker),
+            Self::Process(msg) => msg.check_decode_limits(checker),
+            Self::Shell(msg) => msg.check_decode_limits(checker),
+            Self::Batch(msg) => msg.check_decode_limits(checker),
+            Self::WinPs(msg) => msg.check_decode_limits(checker),
+            Self::Pwsh(msg) => msg.check_decode_limits(checker),
+            // Messages without variable-length fields.
+            Self::Abort(_) | Self::CancelReq(_) | Self::Started(_) => Ok(()),
+        }
+    }
+}
+
 impl<'a> NowExecMessage<'a> {
     const NAME: &'static str = "NOW_EXEC_MSG";
 
@@ -87,6 +109,25 @@ impl<'a> NowExecMessage<'a> {
             _ => Err(invalid_field_err!("type", "invalid message type")),
         }
     }
+
+    /// Returns `true` if the message kind is known for this message class.
+    pub(crate) fn is_known_kind(kind: u8) -> bool {
+        matches!(
+            NowExecMsgKind(kind),
+            NowExecMsgKind::ABORT
+                | NowExecMsgKind::CANCEL_REQ
+                | NowExecMsgKind::CANCEL_RSP
+                | NowExecMsgKind::RESULT
+                | NowExecMsgKind::DATA
+                | NowExecMsgKind::STARTED
+                | NowExecMsgKind::RUN
+                | NowExecMsgKind::PROCESS
+                | NowExecMsgKind::SHELL
+                | NowExecMsgKind::BATCH
+                | NowExecMsgKind::WINPS
+                | NowExecMsgKind::PWSH
+        )
+    }
 }
 
 impl Encode for NowExecMessage<'_> {
@@ -1,17 +1,16 @@
 use alloc::borrow::Cow;
 
 use bitflags::bitflags;
-use ironrdp_core::{
-    cast_length, ensure_fixed_part_size, invalid_field_err, Decode, DecodeResult, Encode, EncodeResult, IntoOwned,
-    ReadCursor, WriteCursor,
-};
+use ironrdp_core::EncodeResult;
+use now_proto_derive::NowPdu;
 
-use crate::{NowExecMessage, NowExecMsgKind, NowHeader, NowMessage, NowMessageClass, NowVarStr};
+use crate::NowVarStr;
 
 bitflags! {
     /// NOW-PROTO: NOW_EXEC_PROCESS_MSG msgFlags field.
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-    struct NowExecProcessFlags: u16 {
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+    pub struct NowExecProcessFlags: u16 {
         /// Set if parameters field contains non-default value.
         ///
         /// NOW-PROTO: NOW_EXEC_FLAG_PROCESS_PARAMETERS_SET
@@ -45,8 +44,11 @@ bitflags! {
 /// The NOW_EXEC_PROCESS_MSG message is used to send a Windows CreateProcess() request.
 ///
 /// NOW-PROTO: NOW_EXEC_PROCESS_MSG
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, NowPdu)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[now(class = EXEC, kind = PROCESS, variant = Process)]
 pub struct NowExecProcessMsg<'a> {
+    #[now(flags)]
     flags: NowExecProcessFlags,
     session_id: u32,
     filename: NowVarStr<'a>,
@@ -54,26 +56,7 @@ pub struct NowExecProcessMsg<'a> {
     directory: NowVarStr<'a>,
 }
 
-impl_pdu_borrowing!(NowExecProcessMsg<'_>, OwnedNowExecProcessMsg);
-
-impl IntoOwned for NowExecProcessMsg<'_> {
-    type Owned = OwnedNowExecProcessMsg;
-
-    fn into_owned(self) -> Self::Owned {
-        OwnedNowExecProcessMsg {
-            flags: self.flags,
-            session_id: self.session_id,
-            filename: self.filename.into_owned(),
-            parameters: self.parameters.into_owned(),
-            directory: self.directory.into_owned(),
-        }
-    }
-}
-
 impl<'a> NowExecProcessMsg<'a> {
-    const NAME: &'static str = "NOW_EXEC_PROCESS_MSG";
-    const FIXED_PART_SIZE: usize = 4;
-
     pub fn new(session_id: u32, filename: impl Into<Cow<'a, str>>) -> EncodeResult<Self> {
         let msg = Self {
             flags: NowExecProcessFlags::empty(),
@@ -136,17 +119,6 @@ impl<'a> NowExecProcessMsg<'a> {
         self.flags.contains(NowExecProcessFlags::DETACHED)
     }
 
-    fn ensure_message_size(&self) -> EncodeResult<()> {
-        ensure_now_message_size!(
-            Self::FIXED_PART_SIZE,
-            self.filename.size(),
-            self.parameters.size(),
-            self.directory.size()
-        );
-
-        Ok(())
-    }
-
     pub fn session_id(&self) -> u32 {
         self.session_id
     }
@@ -170,78 +142,4 @@ impl<'a> NowExecProcessMsg<'a> {
             None
         }
     }
-
-    // LINTS: Overall message size is validated in the constructor/decode method
-    #[allow(clippy::arithmetic_side_effects)]
-    fn body_size(&self) -> usize {
-        Self::FIXED_PART_SIZE + self.filename.size() + self.parameters.size() + self.directory.size()
-    }
-
-    pub(super) fn decode_from_body(header: NowHeader, src: &mut ReadCursor<'a>) -> DecodeResult<Self> {
-        ensure_fixed_part_size!(in: src);
-
-        let flags = NowExecProcessFlags::from_bits_retain(header.flags);
-        let session_id = src.read_u32();
-        let filename = NowVarStr::decode(src)?;
-        let parameters = NowVarStr::decode(src)?;
-        let directory = NowVarStr::decode(src)?;
-
-        let msg = Self {
-            flags,
-            session_id,
-            filename,
-            parameters,
-            directory,
-        };
-
-        Ok(msg)
-    }
-}
-
-impl Encode for NowExecProcessMsg<'_> {
-    fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
-        let header = NowHeader {
-            size: cast_length!("size", self.body_size())?,
-            class: NowMessageClass::EXEC,
-            kind: NowExecMsgKind::PROCESS.0,
-            flags: self.flags.bits(),
-        };
-
-        header.encode(dst)?;
-
-        ensure_fixed_part_size!(in: dst);
-        dst.write_u32(self.session_id);
-        self.filename.encode(dst)?;
-        self.parameters.encode(dst)?;
-        self.directory.encode(dst)?;
-
-        Ok(())
-    }
-
-    fn name(&self) -> &'static str {
-        Self::NAME
-    }
-
-    // LINTS: See body_size()
-    #[allow(clippy::arithmetic_side_effects)]
-    fn size(&self) -> usize {
-        NowHeader::FIXED_PART_SIZE + self.body_size()
-    }
-}
-
-impl<'de> Decode<'de> for NowExecProcessMsg<'de> {
-    fn decode(src: &mut ReadCursor<'de>) -> DecodeResult<Self> {
-        let header = NowHeader::decode(src)?;
-
-        match (header.class, NowExecMsgKind(header.kind)) {
-            (NowMessageClass::EXEC, NowExecMsgKind::PROCESS) => Self::decode_from_body(header, src),
-            _ => Err(invalid_field_err!("type", "invalid message type")),
-        }
-    }
-}
-
-impl<'a> From<NowExecProcessMsg<'a>> for NowMessage<'a> {
-    fn from(msg: NowExecProcessMsg<'a>) -> Self {
-        NowMessage::Exec(NowExecMessage::Process(msg))
-    }
 }
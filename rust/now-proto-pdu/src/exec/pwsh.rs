@@ -1,20 +1,18 @@
 use alloc::borrow::Cow;
 
-use ironrdp_core::{
-    cast_length, ensure_fixed_part_size, invalid_field_err, Decode, DecodeResult, Encode, EncodeResult, IntoOwned,
-    ReadCursor, WriteCursor,
-};
+use ironrdp_core::{DecodeResult, EncodeResult};
+use now_proto_derive::NowPdu;
 
-use crate::{
-    ComApartmentStateKind, NowExecMessage, NowExecMsgKind, NowExecWinPsFlags, NowHeader, NowMessage, NowMessageClass,
-    NowVarStr,
-};
+use crate::{ComApartmentStateKind, NowExecWinPsFlags, NowVarStr};
 
 /// The NOW_EXEC_PWSH_MSG message is used to execute a remote Windows PowerShell (powershell.exe) command.
 ///
 /// NOW-PROTO: NOW_EXEC_PWSH_MSG
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, NowPdu)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[now(class = EXEC, kind = PWSH, variant = Pwsh, validate)]
 pub struct NowExecPwshMsg<'a> {
+    #[now(flags)]
     flags: NowExecWinPsFlags,
     session_id: u32,
     command: NowVarStr<'a>,
@@ -23,27 +21,7 @@ pub struct NowExecPwshMsg<'a> {
     configuration_name: NowVarStr<'a>,
 }
 
-impl_pdu_borrowing!(NowExecPwshMsg<'_>, OwnedNowExecPwshMsg);
-
-impl IntoOwned for NowExecPwshMsg<'_> {
-    type Owned = OwnedNowExecPwshMsg;
-
-    fn into_owned(self) -> Self::Owned {
-        OwnedNowExecPwshMsg {
-            flags: self.flags,
-            session_id: self.session_id,
-            command: self.command.into_owned(),
-            directory: self.directory.into_owned(),
-            execution_policy: self.execution_policy.into_owned(),
-            configuration_name: self.configuration_name.into_owned(),
-        }
-    }
-}
-
 impl<'a> NowExecPwshMsg<'a> {
-    const NAME: &'static str = "NOW_EXEC_PWSH_MSG";
-    const FIXED_PART_SIZE: usize = 4;
-
     pub fn new(session_id: u32, command: impl Into<Cow<'a, str>>) -> EncodeResult<Self> {
         let msg = Self {
             flags: NowExecWinPsFlags::empty(),
@@ -116,17 +94,6 @@ impl<'a> NowExecPwshMsg<'a> {
         self
     }
 
-    fn ensure_message_size(&self) -> EncodeResult<()> {
-        let _message_size = Self::FIXED_PART_SIZE
-            .checked_add(self.command.size())
-            .and_then(|size| size.checked_add(self.directory.size()))
-            .and_then(|size| size.checked_add(self.execution_policy.size()))
-            .and_then(|size| size.checked_add(self.configuration_name.size()))
-            .ok_or_else(|| invalid_field_err!("size", "message size overflow"))?;
-
-        Ok(())
-    }
-
     pub fn session_id(&self) -> u32 {
         self.session_id
     }
@@ -223,84 +190,8 @@ impl<'a> NowExecPwshMsg<'a> {
         ComApartmentStateKind::from_flags(self.flags)
     }
 
-    // LINTS: Overall message size is validated in the constructor/decode method
-    #[allow(clippy::arithmetic_side_effects)]
-    fn body_size(&self) -> usize {
-        Self::FIXED_PART_SIZE
-            + self.command.size()
-            + self.directory.size()
-            + self.execution_policy.size()
-            + self.configuration_name.size()
-    }
-
-    pub(super) fn decode_from_body(header: NowHeader, src: &mut ReadCursor<'a>) -> DecodeResult<Self> {
-        ensure_fixed_part_size!(in: src);
-
-        let flags = NowExecWinPsFlags::from_bits_retain(header.flags);
-        let session_id = src.read_u32();
-        let command = NowVarStr::decode(src)?;
-        let directory = NowVarStr::decode(src)?;
-        let execution_policy = NowVarStr::decode(src)?;
-        let configuration_name = NowVarStr::decode(src)?;
-
-        let msg = Self {
-            flags,
-            session_id,
-            command,
-            directory,
-            execution_policy,
-            configuration_name,
-        };
-
-        Ok(msg)
-    }
-}
-
-impl Encode for NowExecPwshMsg<'_> {
-    fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
-        let header = NowHeader {
-            size: cast_length!("size", self.body_size())?,
-            class: NowMessageClass::EXEC,
-            kind: NowExecMsgKind::PWSH.0,
-            flags: self.flags.bits(),
-        };
-
-        header.encode(dst)?;
-
-        ensure_fixed_part_size!(in: dst);
-        dst.write_u32(self.session_id);
-        self.command.encode(dst)?;
-        self.directory.encode(dst)?;
-        self.execution_policy.encode(dst)?;
-        self.configuration_name.encode(dst)?;
-
-        Ok(())
-    }
-
-    fn name(&self) -> &'static str {
-        Self::NAME
-    }
-
-    // LINTS: See body_size()
-    #[allow(clippy::arithmetic_side_effects)]
-    fn size(&self) -> usize {
-        NowHeader::FIXED_PART_SIZE + self.body_size()
-    }
-}
-
-impl<'de> Decode<'de> for NowExecPwshMsg<'de> {
-    fn decode(src: &mut ReadCursor<'de>) -> DecodeResult<Self> {
-        let header = NowHeader::decode(src)?;
-
-        match (header.class, NowExecMsgKind(header.kind)) {
-            (NowMessageClass::EXEC, NowExecMsgKind::PWSH) => Self::decode_from_body(header, src),
-            _ => Err(invalid_field_err!("type", "invalid message type")),
-        }
-    }
-}
-
-impl<'a> From<NowExecPwshMsg<'a>> for NowMessage<'a> {
-    fn from(msg: NowExecPwshMsg<'a>) -> Self {
-        NowMessage::Exec(NowExecMessage::Pwsh(msg))
+    #[cfg(feature = "serde")]
+    fn validate(&self) -> DecodeResult<()> {
+        self.apartment_state().map(|_| ())
     }
 }
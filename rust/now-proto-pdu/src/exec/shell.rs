@@ -1,17 +1,16 @@
 use alloc::borrow::Cow;
 
 use bitflags::bitflags;
-use ironrdp_core::{
-    cast_length, ensure_fixed_part_size, invalid_field_err, Decode, DecodeResult, Encode, EncodeResult, IntoOwned,
-    ReadCursor, WriteCursor,
-};
+use ironrdp_core::EncodeResult;
+use now_proto_derive::NowPdu;
 
-use crate::{NowExecMessage, NowExecMsgKind, NowHeader, NowMessage, NowMessageClass, NowVarStr};
+use crate::NowVarStr;
 
 bitflags! {
     /// NOW-PROTO: NOW_EXEC_SHELL_MSG msgFlags field.
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-    struct NowExecShellFlags: u16 {
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+    pub struct NowExecShellFlags: u16 {
         /// Set if parameters shell contains non-default value.
         ///
         /// NOW-PROTO: NOW_EXEC_FLAG_SHELL_SHELL_SET
@@ -37,8 +36,11 @@ bitflags! {
 /// The NOW_EXEC_SHELL_MSG message is used to execute a remote shell command.
 ///
 /// NOW-PROTO: NOW_EXEC_SHELL_MSG
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, NowPdu)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[now(class = EXEC, kind = SHELL, variant = Shell)]
 pub struct NowExecShellMsg<'a> {
+    #[now(flags)]
     flags: NowExecShellFlags,
     session_id: u32,
     command: NowVarStr<'a>,
@@ -46,26 +48,7 @@ pub struct NowExecShellMsg<'a> {
     directory: NowVarStr<'a>,
 }
 
-impl_pdu_borrowing!(NowExecShellMsg<'_>, OwnedNowExecShellMsg);
-
-impl IntoOwned for NowExecShellMsg<'_> {
-    type Owned = OwnedNowExecShellMsg;
-
-    fn into_owned(self) -> Self::Owned {
-        OwnedNowExecShellMsg {
-            flags: self.flags,
-            session_id: self.session_id,
-            command: self.command.into_owned(),
-            shell: self.shell.into_owned(),
-            directory: self.directory.into_owned(),
-        }
-    }
-}
-
 impl<'a> NowExecShellMsg<'a> {
-    const NAME: &'static str = "NOW_EXEC_SHELL_MSG";
-    const FIXED_PART_SIZE: usize = 4;
-
     pub fn new(session_id: u32, command: impl Into<Cow<'a, str>>) -> EncodeResult<Self> {
         let msg = Self {
             flags: NowExecShellFlags::empty(),
@@ -98,17 +81,6 @@ impl<'a> NowExecShellMsg<'a> {
         Ok(self)
     }
 
-    fn ensure_message_size(&self) -> EncodeResult<()> {
-        ensure_now_message_size!(
-            Self::FIXED_PART_SIZE,
-            self.command.size(),
-            self.shell.size(),
-            self.directory.size()
-        );
-
-        Ok(())
-    }
-
     pub fn session_id(&self) -> u32 {
         self.session_id
     }
@@ -152,78 +124,4 @@ impl<'a> NowExecShellMsg<'a> {
     pub fn is_detached(&self) -> bool {
         self.flags.contains(NowExecShellFlags::DETACHED)
     }
-
-    // LINTS: Overall message size is validated in the constructor/decode method
-    #[allow(clippy::arithmetic_side_effects)]
-    fn body_size(&self) -> usize {
-        Self::FIXED_PART_SIZE + self.command.size() + self.shell.size() + self.directory.size()
-    }
-
-    pub(super) fn decode_from_body(header: NowHeader, src: &mut ReadCursor<'a>) -> DecodeResult<Self> {
-        ensure_fixed_part_size!(in: src);
-
-        let flags = NowExecShellFlags::from_bits_retain(header.flags);
-        let session_id = src.read_u32();
-        let command = NowVarStr::decode(src)?;
-        let shell = NowVarStr::decode(src)?;
-        let directory = NowVarStr::decode(src)?;
-
-        let msg = Self {
-            flags,
-            session_id,
-            command,
-            shell,
-            directory,
-        };
-
-        Ok(msg)
-    }
-}
-
-impl Encode for NowExecShellMsg<'_> {
-    fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
-        let header = NowHeader {
-            size: cast_length!("size", self.body_size())?,
-            class: NowMessageClass::EXEC,
-            kind: NowExecMsgKind::SHELL.0,
-            flags: self.flags.bits(),
-        };
-
-        header.encode(dst)?;
-
-        ensure_fixed_part_size!(in: dst);
-        dst.write_u32(self.session_id);
-        self.command.encode(dst)?;
-        self.shell.encode(dst)?;
-        self.directory.encode(dst)?;
-
-        Ok(())
-    }
-
-    fn name(&self) -> &'static str {
-        Self::NAME
-    }
-
-    // LINTS: See body_size()
-    #[allow(clippy::arithmetic_side_effects)]
-    fn size(&self) -> usize {
-        NowHeader::FIXED_PART_SIZE + self.body_size()
-    }
-}
-
-impl<'de> Decode<'de> for NowExecShellMsg<'de> {
-    fn decode(src: &mut ReadCursor<'de>) -> DecodeResult<Self> {
-        let header = NowHeader::decode(src)?;
-
-        match (header.class, NowExecMsgKind(header.kind)) {
-            (NowMessageClass::EXEC, NowExecMsgKind::SHELL) => Self::decode_from_body(header, src),
-            _ => Err(invalid_field_err!("type", "invalid message type")),
-        }
-    }
-}
-
-impl<'a> From<NowExecShellMsg<'a>> for NowMessage<'a> {
-    fn from(msg: NowExecShellMsg<'a>) -> Self {
-        NowMessage::Exec(NowExecMessage::Shell(msg))
-    }
 }
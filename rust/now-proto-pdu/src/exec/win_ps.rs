@@ -1,17 +1,16 @@
 use alloc::borrow::Cow;
 
 use bitflags::bitflags;
-use ironrdp_core::{
-    cast_length, ensure_fixed_part_size, invalid_field_err, Decode, DecodeResult, Encode, EncodeResult, IntoOwned,
-    ReadCursor, WriteCursor,
-};
+use ironrdp_core::{invalid_field_err, DecodeResult, EncodeResult};
+use now_proto_derive::NowPdu;
 
-use crate::{NowExecMessage, NowExecMsgKind, NowHeader, NowMessage, NowMessageClass, NowVarStr};
+use crate::NowVarStr;
 
 bitflags! {
     /// NOW-PROTO: NOW_EXEC_WINPS_MSG msgFlags field.
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-    pub(crate) struct NowExecWinPsFlags: u16 {
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+    pub struct NowExecWinPsFlags: u16 {
         /// PowerShell -NoLogo option.
         ///
         /// NOW-PROTO: NOW_EXEC_FLAG_PS_NO_LOGO
@@ -84,6 +83,11 @@ bitflags! {
 
 /// COM apartment state ([MSDN](https://learn.microsoft.com/en-us/dotnet/api/system.threading.apartmentstate)).
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum ComApartmentStateKind {
     Sta,
     Mta,
@@ -119,8 +123,11 @@ impl ComApartmentStateKind {
 /// The NOW_EXEC_WINPS_MSG message is used to execute a remote Windows PowerShell (powershell.exe) command.
 ///
 /// NOW-PROTO: NOW_EXEC_WINPS_MSG
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, NowPdu)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[now(class = EXEC, kind = WINPS, variant = WinPs, validate)]
 pub struct NowExecWinPsMsg<'a> {
+    #[now(flags)]
     flags: NowExecWinPsFlags,
     session_id: u32,
     command: NowVarStr<'a>,
@@ -129,27 +136,7 @@ pub struct NowExecWinPsMsg<'a> {
     configuration_name: NowVarStr<'a>,
 }
 
-impl_pdu_borrowing!(NowExecWinPsMsg<'_>, OwnedNowExecWinPsMsg);
-
-impl IntoOwned for NowExecWinPsMsg<'_> {
-    type Owned = OwnedNowExecWinPsMsg;
-
-    fn into_owned(self) -> Self::Owned {
-        OwnedNowExecWinPsMsg {
-            flags: self.flags,
-            session_id: self.session_id,
-            command: self.command.into_owned(),
-            directory: self.directory.into_owned(),
-            execution_policy: self.execution_policy.into_owned(),
-            configuration_name: self.configuration_name.into_owned(),
-        }
-    }
-}
-
 impl<'a> NowExecWinPsMsg<'a> {
-    const NAME: &'static str = "NOW_EXEC_WINPS_MSG";
-    const FIXED_PART_SIZE: usize = 4;
-
     pub fn new(session_id: u32, command: impl Into<Cow<'a, str>>) -> EncodeResult<Self> {
         let msg = Self {
             flags: NowExecWinPsFlags::empty(),
@@ -222,18 +209,6 @@ impl<'a> NowExecWinPsMsg<'a> {
         self
     }
 
-    fn ensure_message_size(&self) -> EncodeResult<()> {
-        ensure_now_message_size!(
-            Self::FIXED_PART_SIZE,
-            self.command.size(),
-            self.directory.size(),
-            self.execution_policy.size(),
-            self.configuration_name.size()
-        );
-
-        Ok(())
-    }
-
     pub fn session_id(&self) -> u32 {
         self.session_id
     }
@@ -330,84 +305,8 @@ impl<'a> NowExecWinPsMsg<'a> {
         ComApartmentStateKind::from_flags(self.flags)
     }
 
-    // LINTS: Overall message size is validated in the constructor/decode method
-    #[allow(clippy::arithmetic_side_effects)]
-    fn body_size(&self) -> usize {
-        Self::FIXED_PART_SIZE
-            + self.command.size()
-            + self.directory.size()
-            + self.execution_policy.size()
-            + self.configuration_name.size()
-    }
-
-    pub(super) fn decode_from_body(header: NowHeader, src: &mut ReadCursor<'a>) -> DecodeResult<Self> {
-        ensure_fixed_part_size!(in: src);
-
-        let flags = NowExecWinPsFlags::from_bits_retain(header.flags);
-        let session_id = src.read_u32();
-        let command = NowVarStr::decode(src)?;
-        let directory = NowVarStr::decode(src)?;
-        let execution_policy = NowVarStr::decode(src)?;
-        let configuration_name = NowVarStr::decode(src)?;
-
-        let msg = Self {
-            flags,
-            session_id,
-            command,
-            directory,
-            execution_policy,
-            configuration_name,
-        };
-
-        Ok(msg)
-    }
-}
-
-impl Encode for NowExecWinPsMsg<'_> {
-    fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
-        let header = NowHeader {
-            size: cast_length!("size", self.body_size())?,
-            class: NowMessageClass::EXEC,
-            kind: NowExecMsgKind::WINPS.0,
-            flags: self.flags.bits(),
-        };
-
-        header.encode(dst)?;
-
-        ensure_fixed_part_size!(in: dst);
-        dst.write_u32(self.session_id);
-        self.command.encode(dst)?;
-        self.directory.encode(dst)?;
-        self.execution_policy.encode(dst)?;
-        self.configuration_name.encode(dst)?;
-
-        Ok(())
-    }
-
-    fn name(&self) -> &'static str {
-        Self::NAME
-    }
-
-    // LINTS: See body_size()
-    #[allow(clippy::arithmetic_side_effects)]
-    fn size(&self) -> usize {
-        NowHeader::FIXED_PART_SIZE + self.body_size()
-    }
-}
-
-impl<'de> Decode<'de> for NowExecWinPsMsg<'de> {
-    fn decode(src: &mut ReadCursor<'de>) -> DecodeResult<Self> {
-        let header = NowHeader::decode(src)?;
-
-        match (header.class, NowExecMsgKind(header.kind)) {
-            (NowMessageClass::EXEC, NowExecMsgKind::WINPS) => Self::decode_from_body(header, src),
-            _ => Err(invalid_field_err!("type", "invalid message type")),
-        }
-    }
-}
-
-impl<'a> From<NowExecWinPsMsg<'a>> for NowMessage<'a> {
-    fn from(msg: NowExecWinPsMsg<'a>) -> Self {
-        NowMessage::Exec(NowExecMessage::WinPs(msg))
+    #[cfg(feature = "serde")]
+    fn validate(&self) -> DecodeResult<()> {
+        self.apartment_state().map(|_| ())
     }
 }
@@ -0,0 +1,178 @@
+//! Vendor extension message classes.
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::any::Any;
+use core::fmt;
+
+use ironrdp_core::{DecodeResult, Encode, EncodeResult, ReadCursor, WriteCursor};
+
+use crate::{NowHeader, NowMessage, NowMessageClass};
+
+/// Message of the vendor extension class, see [`NowExtensionRegistry`].
+///
+/// Implemented for all owned message types which could be encoded and compared. Extension
+/// messages are encoded with their own [`Encode`] implementation, header included.
+pub trait NowExtensionMessage: Encode + fmt::Debug + Send + Sync + 'static {
+    fn as_any(&self) -> &dyn Any;
+
+    fn eq_dyn(&self, other: &dyn NowExtensionMessage) -> bool;
+}
+
+impl<T> NowExtensionMessage for T
+where
+    T: Encode + fmt::Debug + Eq + Send + Sync + 'static,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn eq_dyn(&self, other: &dyn NowExtensionMessage) -> bool {
+        other.as_any().downcast_ref::<T>().is_some_and(|other| self == other)
+    }
+}
+
+/// Decoded message of the vendor extension class.
+///
+/// Wraps the concrete message type registered in [`NowExtensionRegistry`], which could be
+/// retrieved with [`Self::downcast_ref`].
+#[derive(Clone)]
+pub struct NowExtensionMsg(Arc<dyn NowExtensionMessage>);
+
+impl NowExtensionMsg {
+    pub fn new(message: impl NowExtensionMessage) -> Self {
+        Self(Arc::new(message))
+    }
+
+    pub fn downcast_ref<T: NowExtensionMessage>(&self) -> Option<&T> {
+        self.0.as_any().downcast_ref()
+    }
+
+    pub fn as_message(&self) -> &dyn NowExtensionMessage {
+        &*self.0
+    }
+}
+
+impl fmt::Debug for NowExtensionMsg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+
+impl PartialEq for NowExtensionMsg {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.eq_dyn(&*other.0)
+    }
+}
+
+impl Eq for NowExtensionMsg {}
+
+impl Encode for NowExtensionMsg {
+    fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
+        self.0.encode(dst)
+    }
+
+    fn name(&self) -> &'static str {
+        self.0.name()
+    }
+
+    fn size(&self) -> usize {
+        self.0.size()
+    }
+}
+
+impl From<NowExtensionMsg> for NowMessage<'_> {
+    fn from(msg: NowExtensionMsg) -> Self {
+        NowMessage::Extension(msg)
+    }
+}
+
+/// Decodes the extension message body; the header has already been read from the source.
+///
+/// The decoder receives the whole message body, and is responsible for the message kind dispatch.
+pub type NowExtensionDecodeFn = fn(header: NowHeader, src: &mut ReadCursor<'_>) -> DecodeResult<NowExtensionMsg>;
+
+/// Error returned when the extension message class could not be registered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NowExtensionRegistryError {
+    /// Message class is outside of the vendor extension range (see [`NowMessageClass::VENDOR_MIN`]).
+    NotVendorClass(NowMessageClass),
+    /// Decoder for the message class has already been registered.
+    AlreadyRegistered(NowMessageClass),
+}
+
+impl fmt::Display for NowExtensionRegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotVendorClass(class) => {
+                write!(
+                    f,
+                    "message class {:#04X} is outside of the vendor extension range",
+                    class.0
+                )
+            }
+            Self::AlreadyRegistered(class) => write!(f, "message class {:#04X} is already registered", class.0),
+        }
+    }
+}
+
+impl core::error::Error for NowExtensionRegistryError {}
+
+/// Set of vendor extension message classes which should be decoded as
+/// [`NowMessage::Extension`] (see [`NowDecodeOptions::with_extensions`]).
+///
+/// Registries are usually defined once per application (e.g. in a `static` with lazy
+/// initialization), as decoding options only keep a `'static` reference to the registry.
+///
+/// [`NowDecodeOptions::with_extensions`]: crate::NowDecodeOptions::with_extensions
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NowExtensionRegistry {
+    classes: Vec<(NowMessageClass, NowExtensionDecodeFn)>,
+}
+
+impl NowExtensionRegistry {
+    pub const fn new() -> Self {
+        Self { classes: Vec::new() }
+    }
+
+    /// Registers decoder for the extension message class. Returns error if the class is outside
+    /// of the vendor extension range or has already been registered.
+    pub fn with_class(
+        mut self,
+        class: NowMessageClass,
+        decoder: NowExtensionDecodeFn,
+    ) -> Result<Self, NowExtensionRegistryError> {
+        self.register(class, decoder)?;
+        Ok(self)
+    }
+
+    /// Registers decoder for the extension message class. Returns error if the class is outside
+    /// of the vendor extension range (`0x40..=0xFF`) or has already been registered.
+    pub fn register(
+        &mut self,
+        class: NowMessageClass,
+        decoder: NowExtensionDecodeFn,
+    ) -> Result<(), NowExtensionRegistryError> {
+        if !class.is_vendor() {
+            return Err(NowExtensionRegistryError::NotVendorClass(class));
+        }
+
+        if self.is_registered(class) {
+            return Err(NowExtensionRegistryError::AlreadyRegistered(class));
+        }
+
+        self.classes.push((class, decoder));
+
+        Ok(())
+    }
+
+    pub fn is_registered(&self, class: NowMessageClass) -> bool {
+        self.decoder(class).is_some()
+    }
+
+    pub(crate) fn decoder(&self, class: NowMessageClass) -> Option<NowExtensionDecodeFn> {
+        self.classes
+            .iter()
+            .find_map(|(registered, decoder)| (*registered == class).then_some(*decoder))
+    }
+}
@@ -0,0 +1,72 @@
+use alloc::borrow::Cow;
+
+use bitflags::bitflags;
+use ironrdp_core::EncodeResult;
+use now_proto_derive::NowPdu;
+
+use crate::NowVarBuf;
+
+bitflags! {
+    /// NOW_PROTO: NOW_FILE_CHUNK_MSG flags field.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+    pub struct NowFileChunkFlags: u16 {
+        /// This is the last chunk of the file.
+        ///
+        /// NOW_PROTO: NOW_FILE_CHUNK_FLAG_LAST
+        const LAST = 0x0001;
+    }
+}
+
+/// The NOW_FILE_CHUNK_MSG message carries a range of file data. Chunks are sent by the server
+/// for read transfers, and by the client for write transfers.
+///
+/// NOW_PROTO: NOW_FILE_CHUNK_MSG
+#[derive(Debug, Clone, PartialEq, Eq, NowPdu)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[now(class = FILE, kind = CHUNK, variant = Chunk)]
+pub struct NowFileChunkMsg<'a> {
+    #[now(flags)]
+    flags: NowFileChunkFlags,
+    transfer_id: u32,
+    /// File offset of the first byte of `data`.
+    offset: u64,
+    data: NowVarBuf<'a>,
+}
+
+impl<'a> NowFileChunkMsg<'a> {
+    pub fn new(transfer_id: u32, offset: u64, last: bool, data: impl Into<Cow<'a, [u8]>>) -> EncodeResult<Self> {
+        let flags = if last {
+            NowFileChunkFlags::LAST
+        } else {
+            NowFileChunkFlags::empty()
+        };
+
+        let msg = Self {
+            flags,
+            transfer_id,
+            offset,
+            data: NowVarBuf::new(data)?,
+        };
+
+        msg.ensure_message_size()?;
+
+        Ok(msg)
+    }
+
+    pub fn transfer_id(&self) -> u32 {
+        self.transfer_id
+    }
+
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    pub fn is_last(&self) -> bool {
+        self.flags.contains(NowFileChunkFlags::LAST)
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
@@ -0,0 +1,57 @@
+use ironrdp_core::{invalid_field_err, DecodeResult};
+use now_proto_derive::NowPdu;
+
+use crate::NowVarBuf;
+
+/// Size of the SHA-256 digest sent in NOW_FILE_CLOSE_MSG.
+pub const NOW_FILE_SHA256_SIZE: usize = 32;
+
+/// The NOW_FILE_CLOSE_MSG message completes the file transfer. It is sent by the client after
+/// the last chunk has been sent or received, or earlier to cancel the transfer. The server
+/// responds with NOW_FILE_CLOSE_RSP_MSG.
+///
+/// NOW_PROTO: NOW_FILE_CLOSE_MSG
+#[derive(Debug, Clone, PartialEq, Eq, NowPdu)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[now(class = FILE, kind = CLOSE, variant = Close, validate)]
+pub struct NowFileCloseMsg<'a> {
+    transfer_id: u32,
+    /// SHA-256 digest of the data transferred from the initial offset, or empty if the digest
+    /// should not be verified.
+    sha256: NowVarBuf<'a>,
+}
+
+impl NowFileCloseMsg<'_> {
+    pub fn new(transfer_id: u32) -> Self {
+        Self {
+            transfer_id,
+            sha256: NowVarBuf::default(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_sha256(mut self, sha256: [u8; NOW_FILE_SHA256_SIZE]) -> Self {
+        self.sha256 = NowVarBuf::new(sha256.to_vec()).expect("SHA-256 digest fits into NOW_VARBUF");
+        self
+    }
+
+    pub fn transfer_id(&self) -> u32 {
+        self.transfer_id
+    }
+
+    /// Returns the digest to verify, or `None` if the verification was not requested.
+    pub fn sha256(&self) -> DecodeResult<Option<[u8; NOW_FILE_SHA256_SIZE]>> {
+        if self.sha256.is_empty() {
+            return Ok(None);
+        }
+
+        <[u8; NOW_FILE_SHA256_SIZE]>::try_from(self.sha256.as_slice())
+            .map(Some)
+            .map_err(|_| invalid_field_err!("sha256", "invalid SHA-256 digest size"))
+    }
+
+    #[cfg(feature = "serde")]
+    fn validate(&self) -> DecodeResult<()> {
+        self.sha256().map(|_| ())
+    }
+}
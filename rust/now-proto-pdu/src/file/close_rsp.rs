@@ -0,0 +1,44 @@
+use ironrdp_core::EncodeResult;
+use now_proto_derive::NowPdu;
+
+use crate::{NowStatus, NowStatusError};
+
+/// The NOW_FILE_CLOSE_RSP_MSG message is sent in response to NOW_FILE_CLOSE_MSG, once the file
+/// has been closed and the digest, if any, has been verified.
+///
+/// NOW_PROTO: NOW_FILE_CLOSE_RSP_MSG
+#[derive(Debug, Clone, PartialEq, Eq, NowPdu)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[now(class = FILE, kind = CLOSE_RSP, variant = CloseRsp)]
+pub struct NowFileCloseRspMsg<'a> {
+    transfer_id: u32,
+    status: NowStatus<'a>,
+}
+
+impl NowFileCloseRspMsg<'_> {
+    pub fn new_success(transfer_id: u32) -> Self {
+        Self {
+            transfer_id,
+            status: NowStatus::new_success(),
+        }
+    }
+
+    pub fn new_error(transfer_id: u32, error: impl Into<NowStatusError>) -> EncodeResult<Self> {
+        let msg = Self {
+            transfer_id,
+            status: NowStatus::new_error(error),
+        };
+
+        msg.ensure_message_size()?;
+
+        Ok(msg)
+    }
+
+    pub fn transfer_id(&self) -> u32 {
+        self.transfer_id
+    }
+
+    pub fn to_result(&self) -> Result<(), NowStatusError> {
+        self.status.to_result()
+    }
+}
@@ -0,0 +1,145 @@
+use alloc::borrow::Cow;
+
+use bitflags::bitflags;
+use ironrdp_core::EncodeResult;
+use now_proto_derive::NowPdu;
+
+use crate::NowVarStr;
+
+/// Filesystem entry kind; unknown values are preserved as is.
+///
+/// NOW_PROTO: `kind` field from NOW_FILE_STAT_RSP_MSG and NOW_FILE_DIR_ENTRY_MSG
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+pub struct NowFileEntryKind(u32);
+
+impl NowFileEntryKind {
+    /// Regular file.
+    ///
+    /// NOW_PROTO: NOW_FILE_KIND_FILE
+    pub const FILE: Self = Self(0x0001);
+    /// Directory.
+    ///
+    /// NOW_PROTO: NOW_FILE_KIND_DIRECTORY
+    pub const DIRECTORY: Self = Self(0x0002);
+    /// Symbolic link; the link itself is described, not its target.
+    ///
+    /// NOW_PROTO: NOW_FILE_KIND_SYMLINK
+    pub const SYMLINK: Self = Self(0x0003);
+    /// Any other entry kind (device, socket, pipe, etc.).
+    ///
+    /// NOW_PROTO: NOW_FILE_KIND_OTHER
+    pub const OTHER: Self = Self(0x0004);
+
+    pub fn new(kind: u32) -> Self {
+        Self(kind)
+    }
+
+    pub fn value(&self) -> u32 {
+        self.0
+    }
+}
+
+bitflags! {
+    /// NOW_PROTO: NOW_FILE_ATTRIBUTE_* constants.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+    pub struct NowFileAttributes: u32 {
+        /// Entry can not be modified by the agent user.
+        ///
+        /// NOW_PROTO: NOW_FILE_ATTRIBUTE_READONLY
+        const READONLY = 0x0000_0001;
+        /// Entry is hidden (hidden attribute on Windows, or name starting with a dot).
+        ///
+        /// NOW_PROTO: NOW_FILE_ATTRIBUTE_HIDDEN
+        const HIDDEN = 0x0000_0002;
+        /// NOW_PROTO: NOW_FILE_ATTRIBUTE_SYSTEM
+        const SYSTEM = 0x0000_0004;
+        /// NOW_PROTO: NOW_FILE_ATTRIBUTE_ARCHIVE
+        const ARCHIVE = 0x0000_0008;
+        /// Entry can be executed by the agent user.
+        ///
+        /// NOW_PROTO: NOW_FILE_ATTRIBUTE_EXECUTABLE
+        const EXECUTABLE = 0x0000_0010;
+    }
+}
+
+/// The NOW_FILE_DIR_ENTRY_MSG message describes a single directory entry, and is sent in
+/// response to NOW_FILE_LIST_DIR_REQ_MSG.
+///
+/// NOW_PROTO: NOW_FILE_DIR_ENTRY_MSG
+#[derive(Debug, Clone, PartialEq, Eq, NowPdu)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[now(class = FILE, kind = DIR_ENTRY, variant = DirEntry)]
+pub struct NowFileDirEntryMsg<'a> {
+    request_id: u32,
+    kind: u32,
+    attributes: u32,
+    file_size: u64,
+    /// Last modification time, as Unix timestamp in seconds.
+    mtime: u64,
+    file_name: NowVarStr<'a>,
+}
+
+impl<'a> NowFileDirEntryMsg<'a> {
+    pub fn new(request_id: u32, kind: NowFileEntryKind, file_name: impl Into<Cow<'a, str>>) -> EncodeResult<Self> {
+        let msg = Self {
+            request_id,
+            kind: kind.value(),
+            attributes: 0,
+            file_size: 0,
+            mtime: 0,
+            file_name: NowVarStr::new(file_name)?,
+        };
+
+        msg.ensure_message_size()?;
+
+        Ok(msg)
+    }
+
+    #[must_use]
+    pub fn with_file_size(mut self, file_size: u64) -> Self {
+        self.file_size = file_size;
+        self
+    }
+
+    /// Sets last modification time, as Unix timestamp in seconds.
+    #[must_use]
+    pub fn with_mtime(mut self, mtime: u64) -> Self {
+        self.mtime = mtime;
+        self
+    }
+
+    #[must_use]
+    pub fn with_attributes(mut self, attributes: NowFileAttributes) -> Self {
+        self.attributes = attributes.bits();
+        self
+    }
+
+    pub fn request_id(&self) -> u32 {
+        self.request_id
+    }
+
+    pub fn kind(&self) -> NowFileEntryKind {
+        NowFileEntryKind::new(self.kind)
+    }
+
+    pub fn attributes(&self) -> NowFileAttributes {
+        NowFileAttributes::from_bits_retain(self.attributes)
+    }
+
+    /// Entry size in bytes; `0` for directories.
+    pub fn file_size(&self) -> u64 {
+        self.file_size
+    }
+
+    /// Last modification time, as Unix timestamp in seconds; `0` if unknown.
+    pub fn mtime(&self) -> u64 {
+        self.mtime
+    }
+
+    /// Entry name, without the directory path.
+    pub fn file_name(&self) -> &str {
+        &self.file_name
+    }
+}
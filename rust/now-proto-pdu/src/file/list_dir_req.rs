@@ -0,0 +1,64 @@
+use alloc::borrow::Cow;
+
+use ironrdp_core::EncodeResult;
+use now_proto_derive::NowPdu;
+
+use crate::NowVarStr;
+
+/// The NOW_FILE_LIST_DIR_REQ_MSG message is used to list a page of directory entries. The server
+/// sends a NOW_FILE_DIR_ENTRY_MSG message for each entry of the page, followed by
+/// NOW_FILE_LIST_DIR_RSP_MSG.
+///
+/// NOW_PROTO: NOW_FILE_LIST_DIR_REQ_MSG
+#[derive(Debug, Clone, PartialEq, Eq, NowPdu)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[now(class = FILE, kind = LIST_DIR_REQ, variant = ListDirReq)]
+pub struct NowFileListDirReqMsg<'a> {
+    request_id: u32,
+    /// Index of the first listed entry.
+    start_index: u32,
+    /// Maximum number of entries in the page, or `0` for no limit.
+    max_entries: u32,
+    path: NowVarStr<'a>,
+}
+
+impl<'a> NowFileListDirReqMsg<'a> {
+    /// Creates a request for all directory entries.
+    pub fn new(request_id: u32, path: impl Into<Cow<'a, str>>) -> EncodeResult<Self> {
+        let msg = Self {
+            request_id,
+            start_index: 0,
+            max_entries: 0,
+            path: NowVarStr::new(path)?,
+        };
+
+        msg.ensure_message_size()?;
+
+        Ok(msg)
+    }
+
+    /// Limits the listing to at most `max_entries` entries starting at `start_index`.
+    #[must_use]
+    pub fn with_page(mut self, start_index: u32, max_entries: u32) -> Self {
+        self.start_index = start_index;
+        self.max_entries = max_entries;
+        self
+    }
+
+    pub fn request_id(&self) -> u32 {
+        self.request_id
+    }
+
+    pub fn start_index(&self) -> u32 {
+        self.start_index
+    }
+
+    /// Maximum number of entries in the page; `None` if not limited.
+    pub fn max_entries(&self) -> Option<u32> {
+        (self.max_entries != 0).then_some(self.max_entries)
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
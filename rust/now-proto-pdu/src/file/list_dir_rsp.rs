@@ -0,0 +1,73 @@
+use bitflags::bitflags;
+use ironrdp_core::EncodeResult;
+use now_proto_derive::NowPdu;
+
+use crate::{NowStatus, NowStatusError};
+
+bitflags! {
+    /// NOW_PROTO: NOW_FILE_LIST_DIR_FLAG_* constants.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+    pub struct NowFileListDirRspFlags: u16 {
+        /// More entries are available after this page.
+        ///
+        /// NOW_PROTO: NOW_FILE_LIST_DIR_FLAG_MORE
+        const MORE = 0x0001;
+    }
+}
+
+/// The NOW_FILE_LIST_DIR_RSP_MSG message completes the page of directory entries requested with
+/// NOW_FILE_LIST_DIR_REQ_MSG, and is sent after all NOW_FILE_DIR_ENTRY_MSG messages.
+///
+/// NOW_PROTO: NOW_FILE_LIST_DIR_RSP_MSG
+#[derive(Debug, Clone, PartialEq, Eq, NowPdu)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[now(class = FILE, kind = LIST_DIR_RSP, variant = ListDirRsp)]
+pub struct NowFileListDirRspMsg<'a> {
+    #[now(flags)]
+    flags: NowFileListDirRspFlags,
+    request_id: u32,
+    status: NowStatus<'a>,
+}
+
+impl NowFileListDirRspMsg<'_> {
+    pub fn new_success(request_id: u32) -> Self {
+        Self {
+            flags: NowFileListDirRspFlags::empty(),
+            request_id,
+            status: NowStatus::new_success(),
+        }
+    }
+
+    pub fn new_error(request_id: u32, error: impl Into<NowStatusError>) -> EncodeResult<Self> {
+        let msg = Self {
+            flags: NowFileListDirRspFlags::empty(),
+            request_id,
+            status: NowStatus::new_error(error),
+        };
+
+        msg.ensure_message_size()?;
+
+        Ok(msg)
+    }
+
+    /// Indicates that more entries are available; the next page starts at the index following
+    /// the last sent entry.
+    #[must_use]
+    pub fn with_more(mut self) -> Self {
+        self.flags |= NowFileListDirRspFlags::MORE;
+        self
+    }
+
+    pub fn request_id(&self) -> u32 {
+        self.request_id
+    }
+
+    pub fn has_more(&self) -> bool {
+        self.flags.contains(NowFileListDirRspFlags::MORE)
+    }
+
+    pub fn to_result(&self) -> Result<(), NowStatusError> {
+        self.status.to_result()
+    }
+}
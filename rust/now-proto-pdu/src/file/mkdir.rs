@@ -0,0 +1,65 @@
+use alloc::borrow::Cow;
+
+use bitflags::bitflags;
+use ironrdp_core::EncodeResult;
+use now_proto_derive::NowPdu;
+
+use crate::NowVarStr;
+
+bitflags! {
+    /// NOW_PROTO: NOW_FILE_MKDIR_FLAG_* constants.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+    pub struct NowFileMkdirFlags: u16 {
+        /// Create missing parent directories, and succeed if the directory already exists.
+        ///
+        /// NOW_PROTO: NOW_FILE_MKDIR_FLAG_RECURSIVE
+        const RECURSIVE = 0x0001;
+    }
+}
+
+/// The NOW_FILE_MKDIR_MSG message is used to create a directory. The server responds with
+/// NOW_FILE_OP_RSP_MSG.
+///
+/// NOW_PROTO: NOW_FILE_MKDIR_MSG
+#[derive(Debug, Clone, PartialEq, Eq, NowPdu)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[now(class = FILE, kind = MKDIR, variant = Mkdir)]
+pub struct NowFileMkdirMsg<'a> {
+    #[now(flags)]
+    flags: NowFileMkdirFlags,
+    request_id: u32,
+    path: NowVarStr<'a>,
+}
+
+impl<'a> NowFileMkdirMsg<'a> {
+    pub fn new(request_id: u32, path: impl Into<Cow<'a, str>>) -> EncodeResult<Self> {
+        let msg = Self {
+            flags: NowFileMkdirFlags::empty(),
+            request_id,
+            path: NowVarStr::new(path)?,
+        };
+
+        msg.ensure_message_size()?;
+
+        Ok(msg)
+    }
+
+    #[must_use]
+    pub fn with_recursive(mut self) -> Self {
+        self.flags |= NowFileMkdirFlags::RECURSIVE;
+        self
+    }
+
+    pub fn request_id(&self) -> u32 {
+        self.request_id
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn is_recursive(&self) -> bool {
+        self.flags.contains(NowFileMkdirFlags::RECURSIVE)
+    }
+}
@@ -0,0 +1,226 @@
+mod chunk;
+mod close;
+mod close_rsp;
+mod delete;
+mod dir_entry;
+mod list_dir_req;
+mod list_dir_rsp;
+mod mkdir;
+mod op_rsp;
+mod open;
+mod open_rsp;
+mod rename;
+mod stat_req;
+mod stat_rsp;
+
+pub use chunk::{NowFileChunkFlags, NowFileChunkMsg, OwnedNowFileChunkMsg};
+pub use close::{NowFileCloseMsg, OwnedNowFileCloseMsg, NOW_FILE_SHA256_SIZE};
+pub use close_rsp::{NowFileCloseRspMsg, OwnedNowFileCloseRspMsg};
+pub use delete::{NowFileDeleteFlags, NowFileDeleteMsg, OwnedNowFileDeleteMsg};
+pub use dir_entry::{NowFileAttributes, NowFileDirEntryMsg, NowFileEntryKind, OwnedNowFileDirEntryMsg};
+use ironrdp_core::{DecodeResult, Encode, EncodeResult, IntoOwned, ReadCursor, WriteCursor};
+pub use list_dir_req::{NowFileListDirReqMsg, OwnedNowFileListDirReqMsg};
+pub use list_dir_rsp::{NowFileListDirRspFlags, NowFileListDirRspMsg, OwnedNowFileListDirRspMsg};
+pub use mkdir::{NowFileMkdirFlags, NowFileMkdirMsg, OwnedNowFileMkdirMsg};
+pub use op_rsp::{NowFileOpRspMsg, OwnedNowFileOpRspMsg};
+pub use open::{NowFileOpenFlags, NowFileOpenMode, NowFileOpenMsg, OwnedNowFileOpenMsg};
+pub use open_rsp::{NowFileOpenRspMsg, OwnedNowFileOpenRspMsg};
+pub use rename::{NowFileRenameFlags, NowFileRenameMsg, OwnedNowFileRenameMsg};
+pub use stat_req::{NowFileStatReqMsg, OwnedNowFileStatReqMsg};
+pub use stat_rsp::{NowFileStatRspMsg, OwnedNowFileStatRspMsg};
+
+use crate::{CheckDecodeLimits, DecodeLimitsChecker, NowHeader};
+
+// Wrapper for the `NOW_FILE_MSG_CLASS_ID` message class.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum NowFileMessage<'a> {
+    Open(NowFileOpenMsg<'a>),
+    OpenRsp(NowFileOpenRspMsg<'a>),
+    Chunk(NowFileChunkMsg<'a>),
+    Close(NowFileCloseMsg<'a>),
+    CloseRsp(NowFileCloseRspMsg<'a>),
+    StatReq(NowFileStatReqMsg<'a>),
+    StatRsp(NowFileStatRspMsg<'a>),
+    ListDirReq(NowFileListDirReqMsg<'a>),
+    DirEntry(NowFileDirEntryMsg<'a>),
+    ListDirRsp(NowFileListDirRspMsg<'a>),
+    Mkdir(NowFileMkdirMsg<'a>),
+    Delete(NowFileDeleteMsg<'a>),
+    Rename(NowFileRenameMsg<'a>),
+    OpRsp(NowFileOpRspMsg<'a>),
+}
+
+pub type OwnedNowFileMessage = NowFileMessage<'static>;
+
+impl IntoOwned for NowFileMessage<'_> {
+    type Owned = OwnedNowFileMessage;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            Self::Open(msg) => OwnedNowFileMessage::Open(msg.into_owned()),
+            Self::OpenRsp(msg) => OwnedNowFileMessage::OpenRsp(msg.into_owned()),
+            Self::Chunk(msg) => OwnedNowFileMessage::Chunk(msg.into_owned()),
+            Self::Close(msg) => OwnedNowFileMessage::Close(msg.into_owned()),
+            Self::CloseRsp(msg) => OwnedNowFileMessage::CloseRsp(msg.into_owned()),
+            Self::StatReq(msg) => OwnedNowFileMessage::StatReq(msg.into_owned()),
+            Self::StatRsp(msg) => OwnedNowFileMessage::StatRsp(msg.into_owned()),
+            Self::ListDirReq(msg) => OwnedNowFileMessage::ListDirReq(msg.into_owned()),
+            Self::DirEntry(msg) => OwnedNowFileMessage::DirEntry(msg.into_owned()),
+            Self::ListDirRsp(msg) => OwnedNowFileMessage::ListDirRsp(msg.into_owned()),
+            Self::Mkdir(msg) => OwnedNowFileMessage::Mkdir(msg.into_owned()),
+            Self::Delete(msg) => OwnedNowFileMessage::Delete(msg.into_owned()),
+            Self::Rename(msg) => OwnedNowFileMessage::Rename(msg.into_owned()),
+            Self::OpRsp(msg) => OwnedNowFileMessage::OpRsp(msg.into_owned()),
+        }
+    }
+}
+
+impl CheckDecodeLimits for NowFileMessage<'_> {
+    fn check_decode_limits(&self, checker: &mut DecodeLimitsChecker) -> DecodeResult<()> {
+        match self {
+            Self::Open(msg) => msg.check_decode_limits(checker),
+            Self::OpenRsp(msg) => msg.check_decode_limits(checker),
+            Self::Chunk(msg) => msg.check_decode_limits(checker),
+            Self::Close(msg) => msg.check_decode_limits(checker),
+            Self::CloseRsp(msg) => msg.check_decode_limits(checker),
+            Self::StatReq(msg) => msg.check_decode_limits(checker),
+            Self::StatRsp(msg) => msg.check_decode_limits(checker),
+            Self::ListDirReq(msg) => msg.check_decode_limits(checker),
+            Self::DirEntry(msg) => msg.check_decode_limits(checker),
+            Self::ListDirRsp(msg) => msg.check_decode_limits(checker),
+            Self::Mkdir(msg) => msg.check_decode_limits(checker),
+            Self::Delete(msg) => msg.check_decode_limits(checker),
+            Self::Rename(msg) => msg.check_decode_limits(checker),
+            Self::OpRsp(msg) => msg.check_decode_limits(checker),
+        }
+    }
+}
+
+impl<'a> NowFileMessage<'a> {
+    const NAME: &'static str = "NOW_FILE_MSG";
+
+    pub fn decode_from_body(header: NowHeader, src: &mut ReadCursor<'a>) -> DecodeResult<Self> {
+        match NowFileMsgKind(header.kind) {
+            NowFileMsgKind::OPEN => Ok(Self::Open(NowFileOpenMsg::decode_from_body(header, src)?)),
+            NowFileMsgKind::OPEN_RSP => Ok(Self::OpenRsp(NowFileOpenRspMsg::decode_from_body(header, src)?)),
+            NowFileMsgKind::CHUNK => Ok(Self::Chunk(NowFileChunkMsg::decode_from_body(header, src)?)),
+            NowFileMsgKind::CLOSE => Ok(Self::Close(NowFileCloseMsg::decode_from_body(header, src)?)),
+            NowFileMsgKind::CLOSE_RSP => Ok(Self::CloseRsp(NowFileCloseRspMsg::decode_from_body(header, src)?)),
+            NowFileMsgKind::STAT_REQ => Ok(Self::StatReq(NowFileStatReqMsg::decode_from_body(header, src)?)),
+            NowFileMsgKind::STAT_RSP => Ok(Self::StatRsp(NowFileStatRspMsg::decode_from_body(header, src)?)),
+            NowFileMsgKind::LIST_DIR_REQ => Ok(Self::ListDirReq(NowFileListDirReqMsg::decode_from_body(header, src)?)),
+            NowFileMsgKind::DIR_ENTRY => Ok(Self::DirEntry(NowFileDirEntryMsg::decode_from_body(header, src)?)),
+            NowFileMsgKind::LIST_DIR_RSP => Ok(Self::ListDirRsp(NowFileListDirRspMsg::decode_from_body(header, src)?)),
+            NowFileMsgKind::MKDIR => Ok(Self::Mkdir(NowFileMkdirMsg::decode_from_body(header, src)?)),
+            NowFileMsgKind::DELETE => Ok(Self::Delete(NowFileDeleteMsg::decode_from_body(header, src)?)),
+            NowFileMsgKind::RENAME => Ok(Self::Rename(NowFileRenameMsg::decode_from_body(header, src)?)),
+            NowFileMsgKind::OP_RSP => Ok(Self::OpRsp(NowFileOpRspMsg::decode_from_body(header, src)?)),
+            _ => Err(unsupported_message_err!(class: header.class.0, kind: header.kind)),
+        }
+    }
+
+    /// Returns `true` if the message kind is known for this message class.
+    pub(crate) fn is_known_kind(kind: u8) -> bool {
+        matches!(
+            NowFileMsgKind(kind),
+            NowFileMsgKind::OPEN
+                | NowFileMsgKind::OPEN_RSP
+                | NowFileMsgKind::CHUNK
+                | NowFileMsgKind::CLOSE
+                | NowFileMsgKind::CLOSE_RSP
+                | NowFileMsgKind::STAT_REQ
+                | NowFileMsgKind::STAT_RSP
+                | NowFileMsgKind::LIST_DIR_REQ
+                | NowFileMsgKind::DIR_ENTRY
+                | NowFileMsgKind::LIST_DIR_RSP
+                | NowFileMsgKind::MKDIR
+                | NowFileMsgKind::DELETE
+                | NowFileMsgKind::RENAME
+                | NowFileMsgKind::OP_RSP
+        )
+    }
+}
+
+impl Encode for NowFileMessage<'_> {
+    fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
+        match self {
+            Self::Open(msg) => msg.encode(dst),
+            Self::OpenRsp(msg) => msg.encode(dst),
+            Self::Chunk(msg) => msg.encode(dst),
+            Self::Close(msg) => msg.encode(dst),
+            Self::CloseRsp(msg) => msg.encode(dst),
+            Self::StatReq(msg) => msg.encode(dst),
+            Self::StatRsp(msg) => msg.encode(dst),
+            Self::ListDirReq(msg) => msg.encode(dst),
+            Self::DirEntry(msg) => msg.encode(dst),
+            Self::ListDirRsp(msg) => msg.encode(dst),
+            Self::Mkdir(msg) => msg.encode(dst),
+            Self::Delete(msg) => msg.encode(dst),
+            Self::Rename(msg) => msg.encode(dst),
+            Self::OpRsp(msg) => msg.encode(dst),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn size(&self) -> usize {
+        match self {
+            Self::Open(msg) => msg.size(),
+            Self::OpenRsp(msg) => msg.size(),
+            Self::Chunk(msg) => msg.size(),
+            Self::Close(msg) => msg.size(),
+            Self::CloseRsp(msg) => msg.size(),
+            Self::StatReq(msg) => msg.size(),
+            Self::StatRsp(msg) => msg.size(),
+            Self::ListDirReq(msg) => msg.size(),
+            Self::DirEntry(msg) => msg.size(),
+            Self::ListDirRsp(msg) => msg.size(),
+            Self::Mkdir(msg) => msg.size(),
+            Self::Delete(msg) => msg.size(),
+            Self::Rename(msg) => msg.size(),
+            Self::OpRsp(msg) => msg.size(),
+        }
+    }
+}
+
+/// NOW-PROTO: NOW_FILE_*_ID
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NowFileMsgKind(pub u8);
+
+impl NowFileMsgKind {
+    /// NOW-PROTO: NOW_FILE_OPEN_ID
+    pub const OPEN: Self = Self(0x01);
+    /// NOW-PROTO: NOW_FILE_OPEN_RSP_ID
+    pub const OPEN_RSP: Self = Self(0x02);
+    /// NOW-PROTO: NOW_FILE_CHUNK_ID
+    pub const CHUNK: Self = Self(0x03);
+    /// NOW-PROTO: NOW_FILE_CLOSE_ID
+    pub const CLOSE: Self = Self(0x04);
+    /// NOW-PROTO: NOW_FILE_CLOSE_RSP_ID
+    pub const CLOSE_RSP: Self = Self(0x05);
+    /// NOW-PROTO: NOW_FILE_STAT_REQ_ID
+    pub const STAT_REQ: Self = Self(0x06);
+    /// NOW-PROTO: NOW_FILE_STAT_RSP_ID
+    pub const STAT_RSP: Self = Self(0x07);
+    /// NOW-PROTO: NOW_FILE_LIST_DIR_REQ_ID
+    pub const LIST_DIR_REQ: Self = Self(0x08);
+    /// NOW-PROTO: NOW_FILE_DIR_ENTRY_ID
+    pub const DIR_ENTRY: Self = Self(0x09);
+    /// NOW-PROTO: NOW_FILE_LIST_DIR_RSP_ID
+    pub const LIST_DIR_RSP: Self = Self(0x0A);
+    /// NOW-PROTO: NOW_FILE_MKDIR_ID
+    pub const MKDIR: Self = Self(0x0B);
+    /// NOW-PROTO: NOW_FILE_DELETE_ID
+    pub const DELETE: Self = Self(0x0C);
+    /// NOW-PROTO: NOW_FILE_RENAME_ID
+    pub const RENAME: Self = Self(0x0D);
+    /// NOW-PROTO: NOW_FILE_OP_RSP_ID
+    pub const OP_RSP: Self = Self(0x0E);
+}
@@ -0,0 +1,44 @@
+use ironrdp_core::EncodeResult;
+use now_proto_derive::NowPdu;
+
+use crate::{NowStatus, NowStatusError};
+
+/// The NOW_FILE_OP_RSP_MSG message is sent in response to NOW_FILE_MKDIR_MSG,
+/// NOW_FILE_DELETE_MSG and NOW_FILE_RENAME_MSG.
+///
+/// NOW_PROTO: NOW_FILE_OP_RSP_MSG
+#[derive(Debug, Clone, PartialEq, Eq, NowPdu)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[now(class = FILE, kind = OP_RSP, variant = OpRsp)]
+pub struct NowFileOpRspMsg<'a> {
+    request_id: u32,
+    status: NowStatus<'a>,
+}
+
+impl NowFileOpRspMsg<'_> {
+    pub fn new_success(request_id: u32) -> Self {
+        Self {
+            request_id,
+            status: NowStatus::new_success(),
+        }
+    }
+
+    pub fn new_error(request_id: u32, error: impl Into<NowStatusError>) -> EncodeResult<Self> {
+        let msg = Self {
+            request_id,
+            status: NowStatus::new_error(error),
+        };
+
+        msg.ensure_message_size()?;
+
+        Ok(msg)
+    }
+
+    pub fn request_id(&self) -> u32 {
+        self.request_id
+    }
+
+    pub fn to_result(&self) -> Result<(), NowStatusError> {
+        self.status.to_result()
+    }
+}
@@ -0,0 +1,127 @@
+use alloc::borrow::Cow;
+
+use bitflags::bitflags;
+use ironrdp_core::{invalid_field_err, DecodeResult, EncodeResult};
+use now_proto_derive::NowPdu;
+
+use crate::NowVarStr;
+
+bitflags! {
+    /// NOW_PROTO: NOW_FILE_OPEN_FLAG_* constants.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+    pub struct NowFileOpenFlags: u16 {
+        /// Open the file for reading; file data is sent by the server.
+        ///
+        /// NOW_PROTO: NOW_FILE_OPEN_FLAG_READ
+        const READ = 0x0001;
+        /// Open the file for writing; file data is sent by the client.
+        ///
+        /// NOW_PROTO: NOW_FILE_OPEN_FLAG_WRITE
+        const WRITE = 0x0002;
+        /// Resume writing at the current end of the file instead of `offset`.
+        ///
+        /// NOW_PROTO: NOW_FILE_OPEN_FLAG_RESUME
+        const RESUME = 0x0004;
+    }
+}
+
+/// File transfer direction for the NOW_FILE_OPEN_MSG message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum NowFileOpenMode {
+    Read,
+    Write,
+}
+
+/// The NOW_FILE_OPEN_MSG message is used to start a file transfer. The server responds with
+/// NOW_FILE_OPEN_RSP_MSG, after which file data is exchanged with NOW_FILE_CHUNK_MSG messages.
+///
+/// NOW_PROTO: NOW_FILE_OPEN_MSG
+#[derive(Debug, Clone, PartialEq, Eq, NowPdu)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[now(class = FILE, kind = OPEN, variant = Open, validate)]
+pub struct NowFileOpenMsg<'a> {
+    #[now(flags)]
+    flags: NowFileOpenFlags,
+    transfer_id: u32,
+    /// Offset of the first transferred byte.
+    offset: u64,
+    path: NowVarStr<'a>,
+}
+
+impl<'a> NowFileOpenMsg<'a> {
+    /// Creates a transfer starting at the beginning of the file. Files opened for writing are
+    /// created or truncated.
+    pub fn new(transfer_id: u32, mode: NowFileOpenMode, path: impl Into<Cow<'a, str>>) -> EncodeResult<Self> {
+        let flags = match mode {
+            NowFileOpenMode::Read => NowFileOpenFlags::READ,
+            NowFileOpenMode::Write => NowFileOpenFlags::WRITE,
+        };
+
+        let msg = Self {
+            flags,
+            transfer_id,
+            offset: 0,
+            path: NowVarStr::new(path)?,
+        };
+
+        msg.ensure_message_size()?;
+
+        Ok(msg)
+    }
+
+    /// Starts the transfer at the given offset. Files opened for writing are truncated to
+    /// `offset`, which should not exceed the current file size.
+    #[must_use]
+    pub fn with_offset(mut self, offset: u64) -> Self {
+        self.flags.remove(NowFileOpenFlags::RESUME);
+        self.offset = offset;
+        self
+    }
+
+    /// Resumes writing at the current end of the file; the resulting offset is sent back in
+    /// the `fileSize` field of NOW_FILE_OPEN_RSP_MSG. Only valid for files opened for writing.
+    #[must_use]
+    pub fn with_resume(mut self) -> Self {
+        self.flags |= NowFileOpenFlags::RESUME;
+        self.offset = 0;
+        self
+    }
+
+    pub fn transfer_id(&self) -> u32 {
+        self.transfer_id
+    }
+
+    pub fn mode(&self) -> DecodeResult<NowFileOpenMode> {
+        match (
+            self.flags.contains(NowFileOpenFlags::READ),
+            self.flags.contains(NowFileOpenFlags::WRITE),
+        ) {
+            (true, false) if !self.is_resume() => Ok(NowFileOpenMode::Read),
+            (false, true) => Ok(NowFileOpenMode::Write),
+            _ => Err(invalid_field_err!("flags", "invalid file open mode")),
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    fn validate(&self) -> DecodeResult<()> {
+        self.mode().map(|_| ())
+    }
+
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    pub fn is_resume(&self) -> bool {
+        self.flags.contains(NowFileOpenFlags::RESUME)
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
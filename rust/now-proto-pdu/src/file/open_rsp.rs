@@ -0,0 +1,51 @@
+use ironrdp_core::EncodeResult;
+use now_proto_derive::NowPdu;
+
+use crate::{NowStatus, NowStatusError};
+
+/// The NOW_FILE_OPEN_RSP_MSG message is sent in response to NOW_FILE_OPEN_MSG.
+///
+/// NOW_PROTO: NOW_FILE_OPEN_RSP_MSG
+#[derive(Debug, Clone, PartialEq, Eq, NowPdu)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[now(class = FILE, kind = OPEN_RSP, variant = OpenRsp)]
+pub struct NowFileOpenRspMsg<'a> {
+    transfer_id: u32,
+    /// File size for read transfers, or the resulting write offset for write transfers.
+    file_size: u64,
+    status: NowStatus<'a>,
+}
+
+impl NowFileOpenRspMsg<'_> {
+    pub fn new_success(transfer_id: u32, file_size: u64) -> Self {
+        Self {
+            transfer_id,
+            file_size,
+            status: NowStatus::new_success(),
+        }
+    }
+
+    pub fn new_error(transfer_id: u32, error: impl Into<NowStatusError>) -> EncodeResult<Self> {
+        let msg = Self {
+            transfer_id,
+            file_size: 0,
+            status: NowStatus::new_error(error),
+        };
+
+        msg.ensure_message_size()?;
+
+        Ok(msg)
+    }
+
+    pub fn transfer_id(&self) -> u32 {
+        self.transfer_id
+    }
+
+    pub fn file_size(&self) -> u64 {
+        self.file_size
+    }
+
+    pub fn to_result(&self) -> Result<(), NowStatusError> {
+        self.status.to_result()
+    }
+}
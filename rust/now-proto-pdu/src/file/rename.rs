@@ -0,0 +1,75 @@
+use alloc::borrow::Cow;
+
+use bitflags::bitflags;
+use ironrdp_core::EncodeResult;
+use now_proto_derive::NowPdu;
+
+use crate::NowVarStr;
+
+bitflags! {
+    /// NOW_PROTO: NOW_FILE_RENAME_FLAG_* constants.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+    pub struct NowFileRenameFlags: u16 {
+        /// Replace the existing destination entry.
+        ///
+        /// NOW_PROTO: NOW_FILE_RENAME_FLAG_REPLACE
+        const REPLACE = 0x0001;
+    }
+}
+
+/// The NOW_FILE_RENAME_MSG message is used to rename or move a file or a directory. The server
+/// responds with NOW_FILE_OP_RSP_MSG.
+///
+/// NOW_PROTO: NOW_FILE_RENAME_MSG
+#[derive(Debug, Clone, PartialEq, Eq, NowPdu)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[now(class = FILE, kind = RENAME, variant = Rename)]
+pub struct NowFileRenameMsg<'a> {
+    #[now(flags)]
+    flags: NowFileRenameFlags,
+    request_id: u32,
+    path: NowVarStr<'a>,
+    new_path: NowVarStr<'a>,
+}
+
+impl<'a> NowFileRenameMsg<'a> {
+    pub fn new(
+        request_id: u32,
+        path: impl Into<Cow<'a, str>>,
+        new_path: impl Into<Cow<'a, str>>,
+    ) -> EncodeResult<Self> {
+        let msg = Self {
+            flags: NowFileRenameFlags::empty(),
+            request_id,
+            path: NowVarStr::new(path)?,
+            new_path: NowVarStr::new(new_path)?,
+        };
+
+        msg.ensure_message_size()?;
+
+        Ok(msg)
+    }
+
+    #[must_use]
+    pub fn with_replace(mut self) -> Self {
+        self.flags |= NowFileRenameFlags::REPLACE;
+        self
+    }
+
+    pub fn request_id(&self) -> u32 {
+        self.request_id
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn new_path(&self) -> &str {
+        &self.new_path
+    }
+
+    pub fn is_replace(&self) -> bool {
+        self.flags.contains(NowFileRenameFlags::REPLACE)
+    }
+}
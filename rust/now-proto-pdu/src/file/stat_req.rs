@@ -0,0 +1,39 @@
+use alloc::borrow::Cow;
+
+use ironrdp_core::EncodeResult;
+use now_proto_derive::NowPdu;
+
+use crate::NowVarStr;
+
+/// The NOW_FILE_STAT_REQ_MSG message is used to query information about a filesystem entry. The
+/// server responds with NOW_FILE_STAT_RSP_MSG.
+///
+/// NOW_PROTO: NOW_FILE_STAT_REQ_MSG
+#[derive(Debug, Clone, PartialEq, Eq, NowPdu)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[now(class = FILE, kind = STAT_REQ, variant = StatReq)]
+pub struct NowFileStatReqMsg<'a> {
+    request_id: u32,
+    path: NowVarStr<'a>,
+}
+
+impl<'a> NowFileStatReqMsg<'a> {
+    pub fn new(request_id: u32, path: impl Into<Cow<'a, str>>) -> EncodeResult<Self> {
+        let msg = Self {
+            request_id,
+            path: NowVarStr::new(path)?,
+        };
+
+        msg.ensure_message_size()?;
+
+        Ok(msg)
+    }
+
+    pub fn request_id(&self) -> u32 {
+        self.request_id
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
@@ -0,0 +1,94 @@
+use ironrdp_core::EncodeResult;
+use now_proto_derive::NowPdu;
+
+use crate::{NowFileAttributes, NowFileEntryKind, NowStatus, NowStatusError};
+
+/// The NOW_FILE_STAT_RSP_MSG message is sent in response to NOW_FILE_STAT_REQ_MSG. Entry fields
+/// are only meaningful when the status is successful.
+///
+/// NOW_PROTO: NOW_FILE_STAT_RSP_MSG
+#[derive(Debug, Clone, PartialEq, Eq, NowPdu)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[now(class = FILE, kind = STAT_RSP, variant = StatRsp)]
+pub struct NowFileStatRspMsg<'a> {
+    request_id: u32,
+    kind: u32,
+    attributes: u32,
+    file_size: u64,
+    /// Last modification time, as Unix timestamp in seconds.
+    mtime: u64,
+    status: NowStatus<'a>,
+}
+
+impl NowFileStatRspMsg<'_> {
+    pub fn new_success(request_id: u32, kind: NowFileEntryKind) -> Self {
+        Self {
+            request_id,
+            kind: kind.value(),
+            attributes: 0,
+            file_size: 0,
+            mtime: 0,
+            status: NowStatus::new_success(),
+        }
+    }
+
+    pub fn new_error(request_id: u32, error: impl Into<NowStatusError>) -> EncodeResult<Self> {
+        let msg = Self {
+            request_id,
+            kind: 0,
+            attributes: 0,
+            file_size: 0,
+            mtime: 0,
+            status: NowStatus::new_error(error),
+        };
+
+        msg.ensure_message_size()?;
+
+        Ok(msg)
+    }
+
+    #[must_use]
+    pub fn with_file_size(mut self, file_size: u64) -> Self {
+        self.file_size = file_size;
+        self
+    }
+
+    /// Sets last modification time, as Unix timestamp in seconds.
+    #[must_use]
+    pub fn with_mtime(mut self, mtime: u64) -> Self {
+        self.mtime = mtime;
+        self
+    }
+
+    #[must_use]
+    pub fn with_attributes(mut self, attributes: NowFileAttributes) -> Self {
+        self.attributes = attributes.bits();
+        self
+    }
+
+    pub fn request_id(&self) -> u32 {
+        self.request_id
+    }
+
+    pub fn kind(&self) -> NowFileEntryKind {
+        NowFileEntryKind::new(self.kind)
+    }
+
+    pub fn attributes(&self) -> NowFileAttributes {
+        NowFileAttributes::from_bits_retain(self.attributes)
+    }
+
+    /// Entry size in bytes; `0` for directories.
+    pub fn file_size(&self) -> u64 {
+        self.file_size
+    }
+
+    /// Last modification time, as Unix timestamp in seconds; `0` if unknown.
+    pub fn mtime(&self) -> u64 {
+        self.mtime
+    }
+
+    pub fn to_result(&self) -> Result<(), NowStatusError> {
+        self.status.to_result()
+    }
+}
@@ -0,0 +1,223 @@
+//! Incremental message framing for byte-stream transports.
+
+use alloc::vec::Vec;
+
+use ironrdp_core::{invalid_field_err, Decode, DecodeResult, IntoOwned, ReadCursor};
+
+use crate::{NowDecodeLimits, NowDecodeOptions, NowHeader, NowMessage, OwnedNowMessage};
+
+/// Accumulates arbitrary chunks of bytes received from a byte-stream transport (e.g. DVC, pipe or
+/// socket) and splits them into complete NOW-PROTO messages.
+///
+/// Messages are framed by `NOW_HEADER`: each frame is `NowHeader::FIXED_PART_SIZE + msgSize`
+/// bytes long. Frames larger than the configured maximum frame size (or the maximum message size of
+/// the [`NowDecodeLimits`] set via decode options) are rejected as soon as their header is
+/// received, before any memory is reserved for the message body.
+///
+/// Rust counterpart of the .NET `NowMessageBuffer`.
+#[derive(Debug, Clone)]
+pub struct NowMessageFramer {
+    buffer: Vec<u8>,
+    /// Number of bytes at the start of `buffer` which belong to the last yielded frame.
+    consumed: usize,
+    max_frame_size: usize,
+    decode_options: NowDecodeOptions,
+}
+
+impl Default for NowMessageFramer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NowMessageFramer {
+    const NAME: &'static str = "NOW_MESSAGE_FRAMER";
+
+    /// Default maximum frame size (header included), 1 MiB.
+    pub const DEFAULT_MAX_FRAME_SIZE: usize = 1024 * 1024;
+
+    pub fn new() -> Self {
+        Self {
+            buffer: Vec::new(),
+            consumed: 0,
+            max_frame_size: Self::DEFAULT_MAX_FRAME_SIZE,
+            decode_options: NowDecodeOptions::new(),
+        }
+    }
+
+    /// Sets maximum accepted frame size (header included).
+    #[must_use]
+    pub fn with_max_frame_size(mut self, max_frame_size: usize) -> Self {
+        self.max_frame_size = max_frame_size;
+        self
+    }
+
+    pub fn max_frame_size(&self) -> usize {
+        self.max_frame_size
+    }
+
+    /// Sets options used to decode messages returned by [`Self::next_message`].
+    #[must_use]
+    pub fn with_decode_options(mut self, decode_options: NowDecodeOptions) -> Self {
+        self.decode_options = decode_options;
+        self
+    }
+
+    pub fn decode_options(&self) -> NowDecodeOptions {
+        self.decode_options
+    }
+
+    /// Number of buffered bytes which have not been yielded as a frame yet.
+    pub fn buffered_len(&self) -> usize {
+        self.pending().len()
+    }
+
+    /// Appends received bytes to the framer.
+    ///
+    /// Returns an error if any frame header in the received bytes (or the pending frame header
+    /// completed by them) announces a frame larger than the maximum frame size or the maximum
+    /// message size limit. In this case the data is not buffered, and the stream should be
+    /// considered corrupted.
+    pub fn push(&mut self, data: &[u8]) -> DecodeResult<()> {
+        self.compact();
+
+        if data.is_empty() {
+            return Ok(());
+        }
+
+        // Validate all frame headers before buffering (and allocating) anything.
+        let total_len = self.buffer.len().saturating_add(data.len());
+        let mut frame_start = 0;
+
+        while frame_start < total_len {
+            let mut header = [0u8; NowHeader::FIXED_PART_SIZE];
+            let header_len = copy_header(&self.buffer, data, frame_start, &mut header);
+
+            let Some(frame_size) = self.frame_size(&header[..header_len])? else {
+                break;
+            };
+
+            let frame_end = frame_start.saturating_add(frame_size);
+            if frame_end > total_len {
+                // Reserve the whole frame at once to avoid reallocations for partially received
+                // frames. This is safe as the frame size is bounded by `max_frame_size`.
+                self.buffer.reserve(frame_end.saturating_sub(self.buffer.len()));
+                break;
+            }
+
+            frame_start = frame_end;
+        }
+
+        self.buffer.extend_from_slice(data);
+
+        Ok(())
+    }
+
+    /// Returns the number of bytes which should be received before the next frame is complete.
+    ///
+    /// Returns `0` if a complete frame is already buffered. Note that if the frame header is not
+    /// received yet, only the number of missing header bytes is reported.
+    pub fn bytes_needed(&self) -> DecodeResult<usize> {
+        let pending = self.pending();
+
+        match self.frame_size(pending)? {
+            Some(frame_size) => Ok(frame_size.saturating_sub(pending.len())),
+            None => Ok(NowHeader::FIXED_PART_SIZE - pending.len()),
+        }
+    }
+
+    /// Returns the next complete frame (header included), if any.
+    ///
+    /// Returned slice stays valid until the next mutable call on the framer.
+    pub fn next_frame(&mut self) -> DecodeResult<Option<&[u8]>> {
+        self.compact();
+
+        let frame_size = match self.frame_size(&self.buffer)? {
+            Some(frame_size) if frame_size <= self.buffer.len() => frame_size,
+            _ => return Ok(None),
+        };
+
+        self.consumed = frame_size;
+
+        Ok(Some(&self.buffer[..frame_size]))
+    }
+
+    /// Decodes the next complete message, borrowing its data from the framer buffer.
+    ///
+    /// The frame is consumed even if message decoding fails, therefore the caller can skip
+    /// undecodable messages (e.g. unsupported messages sent by a newer peer).
+    pub fn next_message(&mut self) -> DecodeResult<Option<NowMessage<'_>>> {
+        let decode_options = self.decode_options;
+
+        let frame = match self.next_frame()? {
+            Some(frame) => frame,
+            None => return Ok(None),
+        };
+
+        NowMessage::decode_with_options(&mut ReadCursor::new(frame), decode_options).map(Some)
+    }
+
+    /// Decodes the next complete message into its owned representation.
+    pub fn next_owned_message(&mut self) -> DecodeResult<Option<OwnedNowMessage>> {
+        self.next_message().map(|msg| msg.map(IntoOwned::into_owned))
+    }
+
+    fn pending(&self) -> &[u8] {
+        &self.buffer[self.consumed..]
+    }
+
+    /// Drops the last yielded frame from the buffer.
+    fn compact(&mut self) {
+        if self.consumed != 0 {
+            self.buffer.drain(..self.consumed);
+            self.consumed = 0;
+        }
+    }
+
+    fn frame_size(&self, data: &[u8]) -> DecodeResult<Option<usize>> {
+        peek_frame_size(data, self.max_frame_size, &self.decode_options.limits())
+    }
+}
+
+/// Copies header bytes starting at `offset` of `buffered` followed by `data`. Returns the number of
+/// copied bytes, which is less than the header size if the header is not complete yet.
+fn copy_header(buffered: &[u8], data: &[u8], offset: usize, header: &mut [u8; NowHeader::FIXED_PART_SIZE]) -> usize {
+    header
+        .iter_mut()
+        .zip(buffered.iter().chain(data).skip(offset))
+        .map(|(dst, src)| *dst = *src)
+        .count()
+}
+
+/// Returns the total frame size announced by the header at the start of `data`, or `None` if the
+/// header is not complete yet.
+pub(crate) fn peek_frame_size(
+    data: &[u8],
+    max_frame_size: usize,
+    limits: &NowDecodeLimits,
+) -> DecodeResult<Option<usize>> {
+    const NAME: &str = NowMessageFramer::NAME;
+
+    if data.len() < NowHeader::FIXED_PART_SIZE {
+        return Ok(None);
+    }
+
+    let header = NowHeader::decode(&mut ReadCursor::new(data))?;
+
+    limits.check_message_size(header.size)?;
+
+    let frame_size = usize::try_from(header.size)
+        .ok()
+        .and_then(|size| size.checked_add(NowHeader::FIXED_PART_SIZE))
+        .ok_or_else(|| invalid_field_err!(NAME, "size", "message size overflow"))?;
+
+    if frame_size > max_frame_size {
+        return Err(invalid_field_err!(
+            NAME,
+            "size",
+            "message size exceeds maximum frame size"
+        ));
+    }
+
+    Ok(Some(frame_size))
+}
@@ -5,6 +5,8 @@
 #![no_std]
 
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
 // Re-export ironrdp crates to allow users to use it without additional imports.
 pub extern crate ironrdp_core;
@@ -22,18 +24,33 @@ mod macros;
 const_assert!(size_of::<usize>() >= 4);
 
 mod channel;
+#[cfg(feature = "tokio-codec")]
+mod codec;
 mod core;
 mod exec;
+mod extension;
+mod file;
+mod framer;
+mod limits;
 mod message;
+mod options;
 mod rdm;
 mod session;
+mod strict;
 mod system;
 
 pub use core::*;
 
 pub use channel::*;
+#[cfg(feature = "tokio-codec")]
+pub use codec::*;
 pub use exec::*;
+pub use extension::*;
+pub use file::*;
+pub use framer::*;
+pub use limits::*;
 pub use message::*;
+pub use options::*;
 pub use rdm::*;
 pub use session::*;
 pub use system::*;
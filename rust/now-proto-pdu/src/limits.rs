@@ -0,0 +1,253 @@
+//! Decoding limits for messages received from less-trusted peers.
+
+use core::fmt;
+
+use ironrdp_core::{DecodeError, DecodeErrorKind, DecodeResult};
+
+use crate::{NowMessage, VarU32};
+
+/// Upper bounds on the memory which could be claimed by a single decoded message.
+///
+/// A `NOW_HEADER` could announce a message body of up to 4GiB, and a single `NOW_VARSTR` or
+/// `NOW_VARBUF` field could be up to 2^30 bytes long. Limits allow rejecting such messages before
+/// they are buffered or copied (e.g. by [`IntoOwned`](ironrdp_core::IntoOwned)):
+///
+/// - maximum message size is checked as soon as the message header is received, both by
+///   [`NowMessage::decode_with_options`] and by the message framer/codec;
+/// - string, buffer and total owned allocation limits are checked after the message is decoded,
+///   before any of its data is copied out of the received frame;
+/// - vendor extension messages are decoded directly into their owned representation, therefore
+///   their body size is checked against the total allocation limit before the extension decoder
+///   is called. String and buffer limits don't apply to extension message fields.
+///
+/// Default limits are the protocol maximums, i.e. nothing is limited. Errors caused by exceeded
+/// limits could be distinguished from other decoding errors with [`Self::is_limit_exceeded`] (e.g.
+/// to close the channel with `NowProtoError::InvalidRequest`), and the exceeded limit retrieved
+/// with [`Self::exceeded_limit`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NowDecodeLimits {
+    max_message_size: u32,
+    max_string_len: u32,
+    max_buffer_len: u32,
+    max_total_alloc: usize,
+}
+
+impl Default for NowDecodeLimits {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NowDecodeLimits {
+    const NAME: &'static str = "NOW_DECODE_LIMITS";
+    const REASON: &'static str = "decode limit exceeded";
+
+    /// Creates limits allowing everything permitted by the protocol.
+    pub const fn new() -> Self {
+        Self {
+            max_message_size: u32::MAX,
+            max_string_len: VarU32::MAX,
+            max_buffer_len: VarU32::MAX,
+            max_total_alloc: usize::MAX,
+        }
+    }
+
+    /// Sets maximum message body size (`NOW_HEADER` excluded).
+    #[must_use]
+    pub const fn with_max_message_size(mut self, max_message_size: u32) -> Self {
+        self.max_message_size = max_message_size;
+        self
+    }
+
+    /// Sets maximum length of a single `NOW_VARSTR` field in bytes (null terminator excluded).
+    #[must_use]
+    pub const fn with_max_string_len(mut self, max_string_len: u32) -> Self {
+        self.max_string_len = max_string_len;
+        self
+    }
+
+    /// Sets maximum length of a single `NOW_VARBUF` field in bytes.
+    #[must_use]
+    pub const fn with_max_buffer_len(mut self, max_buffer_len: u32) -> Self {
+        self.max_buffer_len = max_buffer_len;
+        self
+    }
+
+    /// Sets maximum number of bytes which would be allocated to convert the decoded message into
+    /// its owned representation (sum of all string and buffer field lengths).
+    #[must_use]
+    pub const fn with_max_total_alloc(mut self, max_total_alloc: usize) -> Self {
+        self.max_total_alloc = max_total_alloc;
+        self
+    }
+
+    pub const fn max_message_size(&self) -> u32 {
+        self.max_message_size
+    }
+
+    pub const fn max_string_len(&self) -> u32 {
+        self.max_string_len
+    }
+
+    pub const fn max_buffer_len(&self) -> u32 {
+        self.max_buffer_len
+    }
+
+    pub const fn max_total_alloc(&self) -> usize {
+        self.max_total_alloc
+    }
+
+    /// Returns `true` if the decoding error has been caused by an exceeded limit.
+    #[cfg(feature = "std")]
+    pub fn is_limit_exceeded(error: &DecodeError) -> bool {
+        Self::exceeded_limit(error).is_some()
+    }
+
+    /// Returns `true` if the decoding error has been caused by an exceeded limit.
+    ///
+    /// Error source is not accessible without `std`, so the error kind is checked instead.
+    #[cfg(not(feature = "std"))]
+    pub fn is_limit_exceeded(error: &DecodeError) -> bool {
+        error.context == Self::NAME
+            && matches!(error.kind(), DecodeErrorKind::Other { description } if *description == Self::REASON)
+    }
+
+    /// Returns the limit exceeded by the received message, if the decoding error has been caused
+    /// by one.
+    #[cfg(feature = "std")]
+    pub fn exceeded_limit(error: &DecodeError) -> Option<&NowDecodeLimitError> {
+        core::error::Error::source(error)?.downcast_ref()
+    }
+
+    pub(crate) fn check_message_size(&self, size: u32) -> DecodeResult<()> {
+        if size > self.max_message_size {
+            return Err(NowDecodeLimitError::MessageSize {
+                size,
+                limit: self.max_message_size,
+            }
+            .into_decode_error());
+        }
+
+        Ok(())
+    }
+
+    /// Checks the total allocation limit for the message body which is about to be decoded
+    /// directly into the owned representation.
+    pub(crate) fn check_owned_body(&self, len: usize) -> DecodeResult<()> {
+        DecodeLimitsChecker {
+            limits: *self,
+            total_alloc: 0,
+        }
+        .allocation(len)
+    }
+
+    /// Checks string, buffer and total allocation limits for the decoded message.
+    pub(crate) fn check_message(&self, message: &NowMessage<'_>) -> DecodeResult<()> {
+        // Avoid walking the message when nothing is limited (default decoding).
+        if self.max_string_len >= VarU32::MAX
+            && self.max_buffer_len >= VarU32::MAX
+            && self.max_total_alloc == usize::MAX
+        {
+            return Ok(());
+        }
+
+        message.check_decode_limits(&mut DecodeLimitsChecker {
+            limits: *self,
+            total_alloc: 0,
+        })
+    }
+}
+
+/// Limit exceeded by the received message.
+///
+/// Reported as the source of [`DecodeErrorKind::Other`] decoding error, see
+/// [`NowDecodeLimits::exceeded_limit`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum NowDecodeLimitError {
+    /// Message body size (`NOW_HEADER` excluded).
+    MessageSize { size: u32, limit: u32 },
+    /// Length of a single `NOW_VARSTR` field.
+    StringLength { len: usize, limit: u32 },
+    /// Length of a single `NOW_VARBUF` field.
+    BufferLength { len: usize, limit: u32 },
+    /// Number of bytes allocated for the owned message representation.
+    TotalAlloc { size: usize, limit: usize },
+}
+
+impl fmt::Display for NowDecodeLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MessageSize { size, limit } => write!(f, "message size {size} exceeds the limit of {limit}"),
+            Self::StringLength { len, limit } => write!(f, "string length {len} exceeds the limit of {limit}"),
+            Self::BufferLength { len, limit } => write!(f, "buffer length {len} exceeds the limit of {limit}"),
+            Self::TotalAlloc { size, limit } => write!(f, "allocation size {size} exceeds the limit of {limit}"),
+        }
+    }
+}
+
+impl core::error::Error for NowDecodeLimitError {}
+
+impl NowDecodeLimitError {
+    fn into_decode_error(self) -> DecodeError {
+        DecodeError::new(
+            NowDecodeLimits::NAME,
+            DecodeErrorKind::Other {
+                description: NowDecodeLimits::REASON,
+            },
+        )
+        .with_source(self)
+    }
+}
+
+/// Accumulates sizes of the decoded message fields, failing as soon as any limit is exceeded.
+pub(crate) struct DecodeLimitsChecker {
+    limits: NowDecodeLimits,
+    total_alloc: usize,
+}
+
+impl DecodeLimitsChecker {
+    pub(crate) fn string(&mut self, value: &str) -> DecodeResult<()> {
+        if value.len() > self.limits.max_string_len as usize {
+            return Err(NowDecodeLimitError::StringLength {
+                len: value.len(),
+                limit: self.limits.max_string_len,
+            }
+            .into_decode_error());
+        }
+
+        self.allocation(value.len())
+    }
+
+    pub(crate) fn buffer(&mut self, value: &[u8]) -> DecodeResult<()> {
+        if value.len() > self.limits.max_buffer_len as usize {
+            return Err(NowDecodeLimitError::BufferLength {
+                len: value.len(),
+                limit: self.limits.max_buffer_len,
+            }
+            .into_decode_error());
+        }
+
+        self.allocation(value.len())
+    }
+
+    /// Accounts raw data which is copied on conversion to the owned representation.
+    pub(crate) fn allocation(&mut self, len: usize) -> DecodeResult<()> {
+        self.total_alloc = self.total_alloc.saturating_add(len);
+
+        if self.total_alloc > self.limits.max_total_alloc {
+            return Err(NowDecodeLimitError::TotalAlloc {
+                size: self.total_alloc,
+                limit: self.limits.max_total_alloc,
+            }
+            .into_decode_error());
+        }
+
+        Ok(())
+    }
+}
+
+/// Message types which own (after [`IntoOwned`](ironrdp_core::IntoOwned)) variable-length data.
+pub(crate) trait CheckDecodeLimits {
+    fn check_decode_limits(&self, checker: &mut DecodeLimitsChecker) -> DecodeResult<()>;
+}
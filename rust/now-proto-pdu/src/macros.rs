@@ -13,19 +13,42 @@ macro_rules! unsupported_message_err {
 }
 
 /// Ensures that accumulated message size does not overflow u32 message size field.
+///
+/// Accepts either a single size or multiple size components (e.g. message fixed part size and
+/// sizes of variable-length fields), and returns `EncodeError` from the enclosing function on
+/// overflow:
+///
+/// ```ignore
+/// use now_proto_pdu::ironrdp_core::{Encode as _, EncodeResult};
+/// use now_proto_pdu::{ensure_now_message_size, NowVarStr};
+///
+/// fn vendor_message_body(name: &str) -> EncodeResult<NowVarStr<'_>> {
+///     const FIXED_PART_SIZE: usize = 4;
+///
+///     let name = NowVarStr::new(name)?;
+///     ensure_now_message_size!(FIXED_PART_SIZE, name.size());
+///
+///     Ok(name)
+/// }
+///
+/// assert!(vendor_message_body("diagnostics").is_ok());
+/// ```
+#[macro_export]
 macro_rules! ensure_now_message_size {
     ($e:expr) => {
-        u32::try_from($e).map_err(|_| ironrdp_core::invalid_field_err!("size", "message size overflow"))?;
+        u32::try_from($e).map_err(|_| $crate::ironrdp_core::invalid_field_err!("size", "message size overflow"))?;
     };
     ($e1:expr, $e2:expr) => {
         $e1.checked_add($e2)
-            .ok_or_else(|| ironrdp_core::invalid_field_err!("size", "message size overflow"))?;
+            .and_then(|size: usize| u32::try_from(size).ok())
+            .ok_or_else(|| $crate::ironrdp_core::invalid_field_err!("size", "message size overflow"))?;
     };
 
     ($e1:expr, $e2:expr, $($er:expr),+) => {
         $e1.checked_add($e2)
             $(.and_then(|size| size.checked_add($er)))*
-            .ok_or_else(|| ironrdp_core::invalid_field_err!("size", "message size overflow"))?;
+            .and_then(|size: usize| u32::try_from(size).ok())
+            .ok_or_else(|| $crate::ironrdp_core::invalid_field_err!("size", "message size overflow"))?;
     };
 }
 
@@ -1,19 +1,44 @@
-use ironrdp_core::{ensure_size, Decode, DecodeResult, Encode, EncodeResult, IntoOwned, ReadCursor, WriteCursor};
+use alloc::borrow::Cow;
+
+use ironrdp_core::{
+    cast_length, ensure_size, Decode, DecodeResult, Encode, EncodeResult, IntoOwned, ReadCursor, WriteCursor,
+};
 
 use crate::{
-    NowChannelMessage, NowExecMessage, NowHeader, NowMessageClass, NowRdmMessage, NowSessionMessage, NowSystemMessage,
+    CheckDecodeLimits, DecodeLimitsChecker, NowChannelMessage, NowDecodeOptions, NowExecMessage, NowExtensionMsg,
+    NowFileMessage, NowHeader, NowMessageClass, NowRdmMessage, NowSessionMessage, NowSystemMessage,
 };
 
 /// Wrapper type for messages transferred over the NOW-PROTO communication channel.
 ///
 /// NOW-PROTO: NOW_*_MSG messages
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum NowMessage<'a> {
     Channel(NowChannelMessage<'a>),
     System(NowSystemMessage<'a>),
     Session(NowSessionMessage<'a>),
     Exec(NowExecMessage<'a>),
     Rdm(NowRdmMessage<'a>),
+    File(NowFileMessage<'a>),
+    /// Message with unrecognized class/kind pair (e.g. introduced by a newer protocol version).
+    ///
+    /// Only produced when decoding with [`NowDecodeOptions::with_unknown_messages`]; re-encodes
+    /// to the exact same bytes.
+    Unknown {
+        header: NowHeader,
+        body: Cow<'a, [u8]>,
+    },
+    /// Message of the vendor extension class.
+    ///
+    /// Only produced when decoding with [`NowDecodeOptions::with_extensions`]. Extension messages
+    /// are not serializable with `serde`.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Extension(NowExtensionMsg),
 }
 
 impl_pdu_borrowing!(NowMessage<'_>, OwnedNowMessage);
@@ -28,6 +53,12 @@ impl IntoOwned for NowMessage<'_> {
             Self::Session(msg) => OwnedNowMessage::Session(msg.into_owned()),
             Self::Exec(msg) => OwnedNowMessage::Exec(msg.into_owned()),
             Self::Rdm(msg) => OwnedNowMessage::Rdm(msg.into_owned()),
+            Self::File(msg) => OwnedNowMessage::File(msg.into_owned()),
+            Self::Unknown { header, body } => OwnedNowMessage::Unknown {
+                header,
+                body: Cow::Owned(body.into_owned()),
+            },
+            Self::Extension(msg) => OwnedNowMessage::Extension(msg),
         }
     }
 }
@@ -36,6 +67,23 @@ impl NowMessage<'_> {
     const NAME: &'static str = "NOW_MSG";
 }
 
+impl CheckDecodeLimits for NowMessage<'_> {
+    fn check_decode_limits(&self, checker: &mut DecodeLimitsChecker) -> DecodeResult<()> {
+        match self {
+            Self::Channel(msg) => msg.check_decode_limits(checker),
+            Self::System(msg) => msg.check_decode_limits(checker),
+            Self::Session(msg) => msg.check_decode_limits(checker),
+            Self::Exec(msg) => msg.check_decode_limits(checker),
+            Self::Rdm(msg) => msg.check_decode_limits(checker),
+            Self::File(msg) => msg.check_decode_limits(checker),
+            Self::Unknown { body, .. } => checker.allocation(body.len()),
+            // Extension messages are decoded as owned, body size is the upper bound of the copied
+            // data.
+            Self::Extension(msg) => checker.allocation(msg.size().saturating_sub(NowHeader::FIXED_PART_SIZE)),
+        }
+    }
+}
+
 impl Encode for NowMessage<'_> {
     fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
         match self {
@@ -44,6 +92,20 @@ impl Encode for NowMessage<'_> {
             Self::Session(msg) => msg.encode(dst),
             Self::Exec(msg) => msg.encode(dst),
             Self::Rdm(msg) => msg.encode(dst),
+            Self::File(msg) => msg.encode(dst),
+            Self::Unknown { header, body } => {
+                let header = NowHeader {
+                    size: cast_length!("size", body.len())?,
+                    ..header.clone()
+                };
+
+                header.encode(dst)?;
+                ensure_size!(in: dst, size: body.len());
+                dst.write_slice(body);
+
+                Ok(())
+            }
+            Self::Extension(msg) => msg.encode(dst),
         }
     }
 
@@ -58,25 +120,78 @@ impl Encode for NowMessage<'_> {
             Self::Session(msg) => msg.size(),
             Self::Exec(msg) => msg.size(),
             Self::Rdm(msg) => msg.size(),
+            Self::File(msg) => msg.size(),
+            // LINTS: body size is bounded by u32 header field, therefore it can't overflow usize.
+            #[allow(clippy::arithmetic_side_effects)]
+            Self::Unknown { body, .. } => NowHeader::FIXED_PART_SIZE + body.len(),
+            Self::Extension(msg) => msg.size(),
         }
     }
 }
 
 impl<'de> Decode<'de> for NowMessage<'de> {
     fn decode(src: &mut ReadCursor<'de>) -> DecodeResult<Self> {
+        Self::decode_with_options(src, NowDecodeOptions::default())
+    }
+}
+
+impl<'a> NowMessage<'a> {
+    /// Decodes a message (header included) using the provided decoding options.
+    pub fn decode_with_options(src: &mut ReadCursor<'a>, options: NowDecodeOptions) -> DecodeResult<Self> {
         let header = NowHeader::decode(src)?;
 
+        options.limits().check_message_size(header.size)?;
+
         // Read all message body regardless of the remaining lefover message data.
         // This is required to allow forward compatibility with future now-proto versions,
         // which may add new message fields which are encoded unconditionally.
-        ensure_size!(in: src, size: header.size as usize);
-        let mut body = ReadCursor::new(src.read_slice(header.size as usize));
+        let body = header.read_body(src)?;
+
+        let extension_decoder = options
+            .extensions()
+            .filter(|_| header.class.is_vendor())
+            .and_then(|extensions| extensions.decoder(header.class));
+
+        let message = if let Some(decoder) = extension_decoder {
+            // Extension decoders allocate while decoding, so the limit can't be checked afterwards.
+            options.limits().check_owned_body(body.len())?;
+
+            let message = Self::Extension(decoder(header.clone(), &mut ReadCursor::new(body))?);
+            if options.strict() {
+                crate::strict::validate(&header, body, &message)?;
+            }
+            message
+        } else if options.unknown_messages() && !Self::is_known(&header) {
+            Self::Unknown {
+                header,
+                body: Cow::Borrowed(body),
+            }
+        } else if options.strict() {
+            let message = Self::decode_from_body(header.clone(), &mut ReadCursor::new(body))?;
+            crate::strict::validate(&header, body, &message)?;
+            message
+        } else {
+            Self::decode_from_body(header, &mut ReadCursor::new(body))?
+        };
+
+        options.limits().check_message(&message)?;
 
-        Self::decode_from_body(header, &mut body)
+        Ok(message)
+    }
+
+    /// Returns `true` if the message class/kind pair is known to this implementation.
+    fn is_known(header: &NowHeader) -> bool {
+        match header.class {
+            NowMessageClass::CHANNEL => NowChannelMessage::is_known_kind(header.kind),
+            NowMessageClass::SYSTEM => NowSystemMessage::is_known_kind(header.kind),
+            NowMessageClass::SESSION => NowSessionMessage::is_known_kind(header.kind),
+            NowMessageClass::EXEC => NowExecMessage::is_known_kind(header.kind),
+            NowMessageClass::RDM => NowRdmMessage::is_known_kind(header.kind),
+            NowMessageClass::FILE => NowFileMessage::is_known_kind(header.kind),
+            _ => false,
+        }
     }
-}
 
-impl<'a> NowMessage<'a> {
     pub fn decode_from_body(header: NowHeader, src: &mut ReadCursor<'a>) -> DecodeResult<Self> {
         match NowMessageClass(header.class.0) {
             NowMessageClass::CHANNEL => Ok(Self::Channel(NowChannelMessage::decode_from_body(header, src)?)),
@@ -84,6 +199,7 @@ impl<'a> NowMessage<'a> {
             NowMessageClass::SESSION => Ok(Self::Session(NowSessionMessage::decode_from_body(header, src)?)),
             NowMessageClass::EXEC => Ok(Self::Exec(NowExecMessage::decode_from_body(header, src)?)),
             NowMessageClass::RDM => Ok(Self::Rdm(NowRdmMessage::decode_from_body(header, src)?)),
+            NowMessageClass::FILE => Ok(Self::File(NowFileMessage::decode_from_body(header, src)?)),
             // Handle unknown class; Unknown kind is handled by underlying message type.
             _ => Err(unsupported_message_err!(class: header.class.0, kind: header.kind)),
         }
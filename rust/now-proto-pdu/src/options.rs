@@ -0,0 +1,89 @@
+//! Message decoding options.
+
+use crate::{NowDecodeLimits, NowExtensionRegistry};
+
+/// Options controlling how NOW-PROTO messages are decoded.
+///
+/// Default options match plain [`Decode`](ironrdp_core::Decode) implementations.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NowDecodeOptions {
+    unknown_messages: bool,
+    strict: bool,
+    limits: NowDecodeLimits,
+    extensions: Option<&'static NowExtensionRegistry>,
+}
+
+impl NowDecodeOptions {
+    pub const fn new() -> Self {
+        Self {
+            unknown_messages: false,
+            strict: false,
+            limits: NowDecodeLimits::new(),
+            extensions: None,
+        }
+    }
+
+    /// Decode messages with unrecognized class/kind pairs as [`NowMessage::Unknown`] instead of
+    /// failing, preserving their raw header and body.
+    ///
+    /// This is useful for proxies and recorders which should pass messages introduced by newer
+    /// protocol versions through untouched.
+    ///
+    /// [`NowMessage::Unknown`]: crate::NowMessage::Unknown
+    #[must_use]
+    pub const fn with_unknown_messages(mut self) -> Self {
+        self.unknown_messages = true;
+        self
+    }
+
+    pub const fn unknown_messages(&self) -> bool {
+        self.unknown_messages
+    }
+
+    /// Reject messages which are not encoded canonically, or have inconsistent flags.
+    ///
+    /// By default decoding is lenient to be compatible with other implementations and protocol
+    /// versions. In strict mode, a message is only accepted if it re-encodes to the exact same
+    /// bytes, which rules out e.g. over-long `NOW_VARU32` values, non-null string terminators,
+    /// trailing body data or ignored fields with non-default values (such as fields added in newer
+    /// protocol versions). Unknown flag bits and mutually exclusive flags (e.g. STA+MTA apartment
+    /// state or multiple exec data stream kinds) are rejected as well.
+    ///
+    /// Bodies of [`NowMessage::Unknown`] messages are not validated.
+    ///
+    /// [`NowMessage::Unknown`]: crate::NowMessage::Unknown
+    #[must_use]
+    pub const fn with_strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    pub const fn strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Sets limits applied to decoded messages, see [`NowDecodeLimits`].
+    #[must_use]
+    pub const fn with_limits(mut self, limits: NowDecodeLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub const fn limits(&self) -> NowDecodeLimits {
+        self.limits
+    }
+
+    /// Decode messages of the vendor extension classes registered in `extensions` as
+    /// [`NowMessage::Extension`].
+    ///
+    /// [`NowMessage::Extension`]: crate::NowMessage::Extension
+    #[must_use]
+    pub const fn with_extensions(mut self, extensions: &'static NowExtensionRegistry) -> Self {
+        self.extensions = Some(extensions);
+        self
+    }
+
+    pub const fn extensions(&self) -> Option<&'static NowExtensionRegistry> {
+        self.extensions
+    }
+}
@@ -3,12 +3,13 @@ use ironrdp_core::{
     cast_length, ensure_fixed_part_size, Decode, DecodeResult, Encode, EncodeResult, IntoOwned, ReadCursor, WriteCursor,
 };
 
-use crate::{NowHeader, NowMessageClass, NowRdmMsgKind, NowVarStr};
+use crate::{CheckDecodeLimits, DecodeLimitsChecker, NowHeader, NowMessageClass, NowRdmMsgKind, NowVarStr};
 
 /// The NOW_RDM_APP_ACTION_MSG is sent by the client to trigger an application state change.
 ///
 /// NOW-PROTO: NOW_RDM_APP_ACTION_MSG
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NowRdmAppActionMsg<'a> {
     app_action: NowRdmAppAction,
     action_data: NowVarStr<'a>,
@@ -27,8 +28,15 @@ impl IntoOwned for NowRdmAppActionMsg<'_> {
     }
 }
 
+impl CheckDecodeLimits for NowRdmAppActionMsg<'_> {
+    fn check_decode_limits(&self, checker: &mut DecodeLimitsChecker) -> DecodeResult<()> {
+        checker.string(&self.action_data)
+    }
+}
+
 /// Application action types for RDM
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
 pub struct NowRdmAppAction(u32);
 
 impl NowRdmAppAction {
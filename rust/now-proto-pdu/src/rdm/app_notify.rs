@@ -4,7 +4,7 @@ use ironrdp_core::{
     cast_length, ensure_fixed_part_size, Decode, DecodeResult, Encode, EncodeResult, IntoOwned, ReadCursor, WriteCursor,
 };
 
-use crate::{NowHeader, NowMessageClass, NowRdmMsgKind, NowVarStr};
+use crate::{CheckDecodeLimits, DecodeLimitsChecker, NowHeader, NowMessageClass, NowRdmMsgKind, NowVarStr};
 
 bitflags! {
     /// NOW-PROTO: NOW_RDM_APP_NOTIFY_MSG msgFlags field.
@@ -17,6 +17,7 @@ bitflags! {
 
 /// NOW-PROTO: Application state values for NOW_RDM_APP_NOTIFY_MSG
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
 pub struct NowRdmAppState(u32);
 
 impl NowRdmAppState {
@@ -46,6 +47,7 @@ impl NowRdmAppState {
 
 /// NOW-PROTO: Reason code values for NOW_RDM_APP_NOTIFY_MSG
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
 pub struct NowRdmReason(u32);
 
 impl NowRdmReason {
@@ -73,6 +75,7 @@ impl NowRdmReason {
 ///
 /// NOW-PROTO: NOW_RDM_APP_NOTIFY_MSG
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NowRdmAppNotifyMsg<'a> {
     app_state: NowRdmAppState,
     reason_code: NowRdmReason,
@@ -93,6 +96,12 @@ impl IntoOwned for NowRdmAppNotifyMsg<'_> {
     }
 }
 
+impl CheckDecodeLimits for NowRdmAppNotifyMsg<'_> {
+    fn check_decode_limits(&self, checker: &mut DecodeLimitsChecker) -> DecodeResult<()> {
+        checker.string(&self.notify_data)
+    }
+}
+
 impl<'a> NowRdmAppNotifyMsg<'a> {
     const NAME: &'static str = "NOW_RDM_APP_NOTIFY_MSG";
     const FIXED_PART_SIZE: usize = 8; // 4 + 4 bytes
@@ -6,7 +6,8 @@ use crate::{NowHeader, NowMessageClass, NowRdmMsgKind};
 bitflags! {
     /// NOW-PROTO: NOW_RDM_APP_START_MSG launch_flags field.
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-    struct NowRdmLaunchFlags: u32 {
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+    pub struct NowRdmLaunchFlags: u32 {
         /// Launch RDM in Jump mode.
         ///
         /// NOW-PROTO: NOW_RDM_LAUNCH_FLAG_JUMP_MODE
@@ -26,6 +27,7 @@ bitflags! {
 ///
 /// NOW-PROTO: NOW_RDM_APP_START_MSG
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NowRdmAppStartMsg {
     launch_flags: NowRdmLaunchFlags,
     timeout: u32,
@@ -4,12 +4,13 @@ use ironrdp_core::{
     cast_length, ensure_fixed_part_size, Decode, DecodeResult, Encode, EncodeResult, IntoOwned, ReadCursor, WriteCursor,
 };
 
-use crate::{NowHeader, NowMessageClass, NowRdmMsgKind, NowVarStr};
+use crate::{CheckDecodeLimits, DecodeLimitsChecker, NowHeader, NowMessageClass, NowRdmMsgKind, NowVarStr};
 
 bitflags! {
     /// NOW-PROTO: NOW_RDM_CAPABILITIES_MSG sync_flags field.
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-    struct NowRdmSyncFlags: u32 {
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+    pub struct NowRdmSyncFlags: u32 {
         /// RDM application is available. Only sent by the server to the client.
         ///
         /// NOW-PROTO: NOW_RDM_SYNC_FLAG_APP_AVAILABLE
@@ -22,6 +23,7 @@ bitflags! {
 ///
 /// NOW-PROTO: NOW_RDM_CAPABILITIES_MSG
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NowRdmCapabilitiesMsg<'a> {
     timestamp: u64,
     sync_flags: NowRdmSyncFlags,
@@ -44,6 +46,13 @@ impl IntoOwned for NowRdmCapabilitiesMsg<'_> {
     }
 }
 
+impl CheckDecodeLimits for NowRdmCapabilitiesMsg<'_> {
+    fn check_decode_limits(&self, checker: &mut DecodeLimitsChecker) -> DecodeResult<()> {
+        checker.string(&self.rdm_version)?;
+        checker.string(&self.version_extra)
+    }
+}
+
 impl<'a> NowRdmCapabilitiesMsg<'a> {
     const NAME: &'static str = "NOW_RDM_CAPABILITIES_MSG";
     const FIXED_PART_SIZE: usize = 12; // 8 + 4 bytes
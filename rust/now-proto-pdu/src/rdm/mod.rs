@@ -8,18 +8,23 @@ mod session_start;
 
 pub use app_action::{NowRdmAppAction, NowRdmAppActionMsg, OwnedNowRdmAppActionMsg};
 pub use app_notify::{NowRdmAppNotifyMsg, NowRdmAppState, NowRdmReason, OwnedNowRdmAppNotifyMsg};
-pub use app_start::NowRdmAppStartMsg;
-pub use capabilities::{NowRdmCapabilitiesMsg, OwnedNowRdmCapabilitiesMsg};
+pub use app_start::{NowRdmAppStartMsg, NowRdmLaunchFlags};
+pub use capabilities::{NowRdmCapabilitiesMsg, NowRdmSyncFlags, OwnedNowRdmCapabilitiesMsg};
 pub use session_action::{NowRdmSessionAction, NowRdmSessionActionMsg, OwnedNowRdmSessionActionMsg};
 pub use session_notify::{NowRdmSessionNotifyKind, NowRdmSessionNotifyMsg, OwnedNowRdmSessionNotifyMsg};
 pub use session_start::{NowRdmSessionStartMsg, OwnedNowRdmSessionStartMsg};
 
 use ironrdp_core::{DecodeResult, Encode, EncodeResult, IntoOwned, ReadCursor, WriteCursor};
 
-use crate::NowHeader;
+use crate::{CheckDecodeLimits, DecodeLimitsChecker, NowHeader};
 
 // Wrapper for the `NOW_RDM_MSG_CLASS_ID` message class.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum NowRdmMessage<'a> {
     Capabilities(NowRdmCapabilitiesMsg<'a>),
     AppStart(NowRdmAppStartMsg),
@@ -48,6 +53,20 @@ impl IntoOwned for NowRdmMessage<'_> {
     }
 }
 
+impl CheckDecodeLimits for NowRdmMessage<'_> {
+    fn check_decode_limits(&self, checker: &mut DecodeLimitsChecker) -> DecodeResult<()> {
+        match self {
+            Self::Capabilities(msg) => msg.check_decode_limits(checker),
+            Self::AppAction(msg) => msg.check_decode_limits(checker),
+            Self::AppNotify(msg) => msg.check_decode_limits(checker),
+            Self::SessionStart(msg) => msg.check_decode_limits(checker),
+            Self::SessionNotify(msg) => msg.check_decode_limits(checker),
+            // Messages without variable-length fields.
+            Self::AppStart(_) | Self::SessionAction(_) => Ok(()),
+        }
+    }
+}
+
 impl<'a> NowRdmMessage<'a> {
     const NAME: &'static str = "NOW_RDM_MSG";
 
@@ -71,6 +90,20 @@ impl<'a> NowRdmMessage<'a> {
             _ => Err(unsupported_message_err!(class: header.class.0, kind: header.kind)),
         }
     }
+
+    /// Returns `true` if the message kind is known for this message class.
+    pub(crate) fn is_known_kind(kind: u8) -> bool {
+        matches!(
+            NowRdmMsgKind(kind),
+            NowRdmMsgKind::CAPABILITIES
+                | NowRdmMsgKind::APP_START
+                | NowRdmMsgKind::APP_ACTION
+                | NowRdmMsgKind::APP_NOTIFY
+                | NowRdmMsgKind::SESSION_START
+                | NowRdmMsgKind::SESSION_ACTION
+                | NowRdmMsgKind::SESSION_NOTIFY
+        )
+    }
 }
 
 impl Encode for NowRdmMessage<'_> {
@@ -10,6 +10,7 @@ use crate::{NowHeader, NowMessageClass, NowRdmMsgKind};
 ///
 /// NOW-PROTO: NOW_RDM_SESSION_ACTION_MSG
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NowRdmSessionActionMsg {
     session_action: NowRdmSessionAction,
     session_id: NowGuid,
@@ -27,6 +28,7 @@ impl IntoOwned for NowRdmSessionActionMsg {
 
 /// Session action types for RDM
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
 pub struct NowRdmSessionAction(u32);
 
 impl NowRdmSessionAction {
@@ -4,10 +4,11 @@ use ironrdp_core::{
 };
 
 use crate::core::NowGuid;
-use crate::{NowHeader, NowMessageClass, NowRdmMsgKind, NowVarStr};
+use crate::{CheckDecodeLimits, DecodeLimitsChecker, NowHeader, NowMessageClass, NowRdmMsgKind, NowVarStr};
 
 /// NOW-PROTO: Session notify values for NOW_RDM_SESSION_NOTIFY_MSG
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
 pub struct NowRdmSessionNotifyKind(u32);
 
 impl NowRdmSessionNotifyKind {
@@ -29,6 +30,7 @@ impl NowRdmSessionNotifyKind {
 ///
 /// NOW-PROTO: NOW_RDM_SESSION_NOTIFY_MSG
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NowRdmSessionNotifyMsg<'a> {
     kind: NowRdmSessionNotifyKind,
     session_id: NowGuid,
@@ -49,6 +51,12 @@ impl IntoOwned for NowRdmSessionNotifyMsg<'_> {
     }
 }
 
+impl CheckDecodeLimits for NowRdmSessionNotifyMsg<'_> {
+    fn check_decode_limits(&self, checker: &mut DecodeLimitsChecker) -> DecodeResult<()> {
+        checker.string(&self.log_data)
+    }
+}
+
 impl<'a> NowRdmSessionNotifyMsg<'a> {
     const NAME: &'static str = "NOW_RDM_SESSION_NOTIFY_MSG";
     const FIXED_PART_SIZE: usize = 4; // 4 bytes session_notify + variable GUID + variable log_data
@@ -4,12 +4,13 @@ use ironrdp_core::{
 };
 
 use crate::core::NowGuid;
-use crate::{NowHeader, NowMessageClass, NowRdmMsgKind, NowVarStr};
+use crate::{CheckDecodeLimits, DecodeLimitsChecker, NowHeader, NowMessageClass, NowRdmMsgKind, NowVarStr};
 
 /// The NOW_RDM_SESSION_START_MSG message is used to start a new RDM session.
 ///
 /// NOW-PROTO: NOW_RDM_SESSION_START_MSG
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NowRdmSessionStartMsg<'a> {
     session_id: NowGuid,
     connection_id: NowGuid,
@@ -30,6 +31,12 @@ impl IntoOwned for NowRdmSessionStartMsg<'_> {
     }
 }
 
+impl CheckDecodeLimits for NowRdmSessionStartMsg<'_> {
+    fn check_decode_limits(&self, checker: &mut DecodeLimitsChecker) -> DecodeResult<()> {
+        checker.string(&self.connection_data)
+    }
+}
+
 impl<'a> NowRdmSessionStartMsg<'a> {
     const NAME: &'static str = "NOW_RDM_SESSION_START_MSG";
     const FIXED_PART_SIZE: usize = 0; // All fields are variable size (2 GUIDs + 1 string)
@@ -10,17 +10,22 @@ mod window_rec_stop;
 use ironrdp_core::{DecodeResult, Encode, EncodeResult, IntoOwned, ReadCursor, WriteCursor};
 pub use lock::NowSessionLockMsg;
 pub use logoff::NowSessionLogoffMsg;
-pub use msg_box_req::{NowMessageBoxStyle, NowSessionMsgBoxReqMsg, OwnedNowSessionMsgBoxReqMsg};
+pub use msg_box_req::{
+    NowMessageBoxStyle, NowSessionMessageBoxFlags, NowSessionMsgBoxReqMsg, OwnedNowSessionMsgBoxReqMsg,
+};
 pub use msg_box_rsp::{NowMsgBoxResponse, NowSessionMsgBoxRspMsg, OwnedNowSessionMsgBoxRspMsg};
-pub use set_kbd_layout::{NowSessionSetKbdLayoutMsg, OwnedNowSessionSetKbdLayoutMsg, SetKbdLayoutOption};
+pub use set_kbd_layout::{
+    NowSessionSetKbdLayoutFlags, NowSessionSetKbdLayoutMsg, OwnedNowSessionSetKbdLayoutMsg, SetKbdLayoutOption,
+};
 pub use window_rec_event::{
     ActiveWindowEventData, NowSessionWindowRecEventMsg, OwnedActiveWindowEventData, OwnedNowSessionWindowRecEventMsg,
-    OwnedTitleChangedEventData, OwnedWindowRecEventKind, TitleChangedEventData, WindowRecEventKind,
+    OwnedTitleChangedEventData, OwnedWindowRecEventKind, TitleChangedEventData, WindowRecEventFlags,
+    WindowRecEventKind,
 };
 pub use window_rec_start::{NowSessionWindowRecStartMsg, WindowRecStartFlags};
 pub use window_rec_stop::NowSessionWindowRecStopMsg;
 
-use crate::NowHeader;
+use crate::{CheckDecodeLimits, DecodeLimitsChecker, NowHeader};
 
 /// Wrapper for the `NOW_SESSION_MSG_CLASS_ID` message class.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -47,6 +52,11 @@ impl NowSessionMessageKind {
 
 // Wrapper for the `NOW_SESSION_MSG_CLASS_ID` message class.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum NowSessionMessage<'a> {
     Lock(NowSessionLockMsg),
     Logoff(NowSessionLogoffMsg),
@@ -77,6 +87,19 @@ impl IntoOwned for NowSessionMessage<'_> {
     }
 }
 
+impl CheckDecodeLimits for NowSessionMessage<'_> {
+    fn check_decode_limits(&self, checker: &mut DecodeLimitsChecker) -> DecodeResult<()> {
+        match self {
+            Self::MsgBoxReq(msg) => msg.check_decode_limits(checker),
+            Self::MsgBoxRsp(msg) => msg.check_decode_limits(checker),
+            Self::SetKbdLayout(msg) => msg.check_decode_limits(checker),
+            Self::WindowRecEvent(msg) => msg.check_decode_limits(checker),
+            // Messages without variable-length fields.
+            Self::Lock(_) | Self::Logoff(_) | Self::WindowRecStart(_) | Self::WindowRecStop(_) => Ok(()),
+        }
+    }
+}
+
 impl<'a> NowSessionMessage<'a> {
     const NAME: &'static str = "NOW_SESSION_MSG";
 
@@ -103,6 +126,21 @@ impl<'a> NowSessionMessage<'a> {
             _ => Err(unsupported_message_err!(class: header.class.0, kind: header.kind)),
         }
     }
+
+    /// Returns `true` if the message kind is known for this message class.
+    pub(crate) fn is_known_kind(kind: u8) -> bool {
+        matches!(
+            NowSessionMessageKind(kind),
+            NowSessionMessageKind::LOCK
+                | NowSessionMessageKind::LOGOFF
+                | NowSessionMessageKind::MSGBOX_REQ
+                | NowSessionMessageKind::MSGBOX_RSP
+                | NowSessionMessageKind::SET_KBD_LAYOUT
+                | NowSessionMessageKind::WINDOW_REC_START
+                | NowSessionMessageKind::WINDOW_REC_STOP
+                | NowSessionMessageKind::WINDOW_REC_EVENT
+        )
+    }
 }
 
 impl Encode for NowSessionMessage<'_> {
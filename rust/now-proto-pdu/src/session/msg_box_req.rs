@@ -7,12 +7,16 @@ use ironrdp_core::{
     ReadCursor, WriteCursor,
 };
 
-use crate::{NowHeader, NowMessage, NowMessageClass, NowSessionMessage, NowSessionMessageKind, NowVarStr};
+use crate::{
+    CheckDecodeLimits, DecodeLimitsChecker, NowHeader, NowMessage, NowMessageClass, NowSessionMessage,
+    NowSessionMessageKind, NowVarStr,
+};
 
 /// Message box style; Directly maps to the WinAPI MessageBox function message box style field.
 ///
 /// NOW_PROTO: `style` field from NOW_SESSION_MESSAGE_BOX_REQ_MSG
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
 pub struct NowMessageBoxStyle(u32);
 
 impl NowMessageBoxStyle {
@@ -36,6 +40,7 @@ impl NowMessageBoxStyle {
 
 bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
     pub struct NowSessionMessageBoxFlags: u16 {
         /// The title field contains non-default value.
         ///
@@ -59,12 +64,28 @@ bitflags! {
     }
 }
 
+// Sanity check: Limit message box timeout to ~1 week.
+const MAX_MSGBOX_TINEOUT: time::Duration = time::Duration::from_secs(60 * 60 * 24 * 7);
+
+fn ensure_msgbox_timeout(timeout: time::Duration) -> EncodeResult<()> {
+    if timeout > MAX_MSGBOX_TINEOUT {
+        return Err(invalid_field_err!("timeout", "too big message box timeout"));
+    }
+
+    Ok(())
+}
+
 /// The NOW_SESSION_MSGBOX_REQ_MSG is used to show a message box in the user session, similar to
 /// what the [WTSSendMessage function](https://learn.microsoft.com/en-us/windows/win32/api/wtsapi32/nf-wtsapi32-wtssendmessagew)
 /// does.
 ///
 /// NOW_PROTO: NOW_SESSION_MSGBOX_REQ_MSG
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "NowSessionMsgBoxReqMsgRepr<'a>")
+)]
 pub struct NowSessionMsgBoxReqMsg<'a> {
     flags: NowSessionMessageBoxFlags,
     request_id: u32,
@@ -74,6 +95,43 @@ pub struct NowSessionMsgBoxReqMsg<'a> {
     message: NowVarStr<'a>,
 }
 
+/// Deserialized fields, validated the same way as in the builder methods.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(rename = "NowSessionMsgBoxReqMsg")]
+struct NowSessionMsgBoxReqMsgRepr<'a> {
+    flags: NowSessionMessageBoxFlags,
+    request_id: u32,
+    style: NowMessageBoxStyle,
+    timeout: u32,
+    title: NowVarStr<'a>,
+    message: NowVarStr<'a>,
+}
+
+#[cfg(feature = "serde")]
+impl<'a> TryFrom<NowSessionMsgBoxReqMsgRepr<'a>> for NowSessionMsgBoxReqMsg<'a> {
+    type Error = ironrdp_core::EncodeError;
+
+    fn try_from(repr: NowSessionMsgBoxReqMsgRepr<'a>) -> Result<Self, Self::Error> {
+        let msg = Self {
+            flags: repr.flags,
+            request_id: repr.request_id,
+            style: repr.style,
+            timeout: repr.timeout,
+            title: repr.title,
+            message: repr.message,
+        };
+
+        if msg.flags.contains(NowSessionMessageBoxFlags::TIMEOUT) {
+            ensure_msgbox_timeout(time::Duration::from_secs(msg.timeout.into()))?;
+        }
+
+        msg.ensure_message_size()?;
+
+        Ok(msg)
+    }
+}
+
 impl_pdu_borrowing!(NowSessionMsgBoxReqMsg<'_>, OwnedNowSessionMsgBoxReqMsg);
 
 impl IntoOwned for NowSessionMsgBoxReqMsg<'_> {
@@ -91,6 +149,13 @@ impl IntoOwned for NowSessionMsgBoxReqMsg<'_> {
     }
 }
 
+impl CheckDecodeLimits for NowSessionMsgBoxReqMsg<'_> {
+    fn check_decode_limits(&self, checker: &mut DecodeLimitsChecker) -> DecodeResult<()> {
+        checker.string(&self.title)?;
+        checker.string(&self.message)
+    }
+}
+
 impl<'a> NowSessionMsgBoxReqMsg<'a> {
     const NAME: &'static str = "NOW_SESSION_MSGBOX_REQ_MSG";
     const FIXED_PART_SIZE: usize = 12;
@@ -130,12 +195,7 @@ impl<'a> NowSessionMsgBoxReqMsg<'a> {
     }
 
     pub fn with_timeout(mut self, timeout: time::Duration) -> EncodeResult<Self> {
-        // Sanity check: Limit message box timeout to ~1 week.
-        const MAX_MSGBOX_TINEOUT: time::Duration = time::Duration::from_secs(60 * 60 * 24 * 7);
-
-        if timeout > MAX_MSGBOX_TINEOUT {
-            return Err(invalid_field_err!("timeout", "too big message box timeout"));
-        }
+        ensure_msgbox_timeout(timeout)?;
 
         let timeout = u32::try_from(timeout.as_secs()).expect("timeout is within u32 range");
 
@@ -3,13 +3,15 @@ use ironrdp_core::{
 };
 
 use crate::{
-    NowHeader, NowMessage, NowMessageClass, NowSessionMessage, NowSessionMessageKind, NowStatus, NowStatusError,
+    CheckDecodeLimits, DecodeLimitsChecker, NowHeader, NowMessage, NowMessageClass, NowSessionMessage,
+    NowSessionMessageKind, NowStatus, NowStatusError,
 };
 
 /// Message box response; Directly maps to the WinAPI MessageBox function response.
 ///
 /// NOW_PROTO: `response` field from NOW_SESSION_MESSAGE_BOX_RSP_MSG
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
 pub struct NowMsgBoxResponse(u32);
 
 impl NowMsgBoxResponse {
@@ -68,6 +70,7 @@ impl NowMsgBoxResponse {
 ///
 /// NOW_PROTO: NOW_SESSION_MSGBOX_RSP_MSG
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NowSessionMsgBoxRspMsg<'a> {
     request_id: u32,
     response: NowMsgBoxResponse,
@@ -88,6 +91,12 @@ impl IntoOwned for NowSessionMsgBoxRspMsg<'_> {
     }
 }
 
+impl CheckDecodeLimits for NowSessionMsgBoxRspMsg<'_> {
+    fn check_decode_limits(&self, checker: &mut DecodeLimitsChecker) -> DecodeResult<()> {
+        self.status.check_decode_limits(checker)
+    }
+}
+
 impl<'a> NowSessionMsgBoxRspMsg<'a> {
     const NAME: &'static str = "NOW_SESSION_MSGBOX_RSP_MSG";
     const FIXED_PART_SIZE: usize = 8;
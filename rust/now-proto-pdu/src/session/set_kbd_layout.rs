@@ -5,10 +5,14 @@ use ironrdp_core::{
     cast_length, invalid_field_err, Decode, DecodeResult, Encode, EncodeResult, IntoOwned, ReadCursor, WriteCursor,
 };
 
-use crate::{NowHeader, NowMessage, NowMessageClass, NowSessionMessage, NowSessionMessageKind, NowVarStr};
+use crate::{
+    CheckDecodeLimits, DecodeLimitsChecker, NowHeader, NowMessage, NowMessageClass, NowSessionMessage,
+    NowSessionMessageKind, NowVarStr,
+};
 
 bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
     pub struct NowSessionSetKbdLayoutFlags: u16 {
         /// Switches to next keyboard layout. kbdLayoutId field should contain empty string.
         /// Conflicts with NOW_SET_KBD_LAYOUT_FLAG_PREV.
@@ -24,7 +28,22 @@ bitflags! {
     }
 }
 
+fn ensure_layout_flags(flags: NowSessionSetKbdLayoutFlags) -> DecodeResult<()> {
+    if flags.contains(NowSessionSetKbdLayoutFlags::NEXT_LAYOUT)
+        && flags.contains(NowSessionSetKbdLayoutFlags::PREV_LAYOUT)
+    {
+        return Err(invalid_field_err!("flags", "both NEXT and PREV flags are set"));
+    }
+
+    Ok(())
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum SetKbdLayoutOption<'a> {
     Next,
     Prev,
@@ -36,12 +55,40 @@ pub enum SetKbdLayoutOption<'a> {
 ///
 /// NOW_PROTO: NOW_SESSION_SET_KBD_LAYOUT_MSG
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "NowSessionSetKbdLayoutMsgRepr<'a>")
+)]
 #[non_exhaustive]
 pub struct NowSessionSetKbdLayoutMsg<'a> {
     flags: NowSessionSetKbdLayoutFlags,
     layout: NowVarStr<'a>,
 }
 
+/// Deserialized fields, validated the same way as on decoding.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(rename = "NowSessionSetKbdLayoutMsg")]
+struct NowSessionSetKbdLayoutMsgRepr<'a> {
+    flags: NowSessionSetKbdLayoutFlags,
+    layout: NowVarStr<'a>,
+}
+
+#[cfg(feature = "serde")]
+impl<'a> TryFrom<NowSessionSetKbdLayoutMsgRepr<'a>> for NowSessionSetKbdLayoutMsg<'a> {
+    type Error = ironrdp_core::DecodeError;
+
+    fn try_from(repr: NowSessionSetKbdLayoutMsgRepr<'a>) -> Result<Self, Self::Error> {
+        ensure_layout_flags(repr.flags)?;
+
+        Ok(Self {
+            flags: repr.flags,
+            layout: repr.layout,
+        })
+    }
+}
+
 impl_pdu_borrowing!(NowSessionSetKbdLayoutMsg<'_>, OwnedNowSessionSetKbdLayoutMsg);
 
 impl IntoOwned for NowSessionSetKbdLayoutMsg<'_> {
@@ -55,6 +102,12 @@ impl IntoOwned for NowSessionSetKbdLayoutMsg<'_> {
     }
 }
 
+impl CheckDecodeLimits for NowSessionSetKbdLayoutMsg<'_> {
+    fn check_decode_limits(&self, checker: &mut DecodeLimitsChecker) -> DecodeResult<()> {
+        checker.string(&self.layout)
+    }
+}
+
 impl<'a> NowSessionSetKbdLayoutMsg<'a> {
     const NAME: &'static str = "NOW_SESSION_SET_KBD_LAYOUT_MSG";
 
@@ -103,11 +156,7 @@ impl<'a> NowSessionSetKbdLayoutMsg<'a> {
         let flags = NowSessionSetKbdLayoutFlags::from_bits_retain(header.flags);
         let layout = NowVarStr::decode(src)?;
 
-        if flags.contains(NowSessionSetKbdLayoutFlags::NEXT_LAYOUT)
-            && flags.contains(NowSessionSetKbdLayoutFlags::PREV_LAYOUT)
-        {
-            return Err(invalid_field_err!("flags", "both NEXT and PREV flags are set"));
-        }
+        ensure_layout_flags(flags)?;
 
         Ok(Self { flags, layout })
     }
@@ -5,12 +5,16 @@ use ironrdp_core::{
     cast_length, other_err, Decode, DecodeResult, Encode, EncodeResult, IntoOwned, ReadCursor, WriteCursor,
 };
 
-use crate::{NowHeader, NowMessage, NowMessageClass, NowSessionMessage, NowSessionMessageKind, NowVarStr};
+use crate::{
+    CheckDecodeLimits, DecodeLimitsChecker, NowHeader, NowMessage, NowMessageClass, NowSessionMessage,
+    NowSessionMessageKind, NowVarStr,
+};
 
 bitflags! {
     /// Event kind flags for window recording events (internal).
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-    struct WindowRecEventFlags: u16 {
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+    pub struct WindowRecEventFlags: u16 {
         /// NOW-PROTO: NOW_WINDOW_REC_EVENT_ACTIVE_WINDOW
         const ACTIVE_WINDOW = 0x0001;
         /// NOW-PROTO: NOW_WINDOW_REC_EVENT_TITLE_CHANGED
@@ -24,6 +28,7 @@ bitflags! {
 ///
 /// NOW-PROTO: NOW_WINDOW_REC_EVENT_ACTIVE_WINDOW
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ActiveWindowEventData<'a> {
     process_id: u32,
     title: NowVarStr<'a>,
@@ -51,6 +56,7 @@ impl ActiveWindowEventData<'_> {
 ///
 /// NOW-PROTO: NOW_WINDOW_REC_EVENT_TITLE_CHANGED
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TitleChangedEventData<'a> {
     title: NowVarStr<'a>,
 }
@@ -66,6 +72,11 @@ impl TitleChangedEventData<'_> {
 ///
 /// NOW_PROTO: NOW_SESSION_WINDOW_REC_EVENT_MSG msgFlags
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum WindowRecEventKind<'a> {
     /// Active window changed.
     ///
@@ -126,6 +137,7 @@ impl IntoOwned for WindowRecEventKind<'_> {
 ///
 /// NOW_PROTO: NOW_SESSION_WINDOW_REC_EVENT_MSG
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NowSessionWindowRecEventMsg<'a> {
     timestamp: u64,
     kind: WindowRecEventKind<'a>,
@@ -144,6 +156,19 @@ impl IntoOwned for NowSessionWindowRecEventMsg<'_> {
     }
 }
 
+impl CheckDecodeLimits for NowSessionWindowRecEventMsg<'_> {
+    fn check_decode_limits(&self, checker: &mut DecodeLimitsChecker) -> DecodeResult<()> {
+        match &self.kind {
+            WindowRecEventKind::ActiveWindow(data) => {
+                checker.string(&data.title)?;
+                checker.string(&data.executable_path)
+            }
+            WindowRecEventKind::TitleChanged(data) => checker.string(&data.title),
+            WindowRecEventKind::NoActiveWindow => Ok(()),
+        }
+    }
+}
+
 impl<'a> NowSessionWindowRecEventMsg<'a> {
     const NAME: &'static str = "NOW_SESSION_WINDOW_REC_EVENT_MSG";
     const FIXED_PART_SIZE: usize = 8 + 4; // timestamp (8) + process_id (4)
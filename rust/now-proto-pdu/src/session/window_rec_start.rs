@@ -8,6 +8,7 @@ bitflags! {
     ///
     /// NOW_PROTO: NOW_SESSION_WINDOW_REC_START_MSG msgFlags
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
     pub struct WindowRecStartFlags: u16 {
         /// Enable window title change tracking.
         ///
@@ -21,6 +22,7 @@ bitflags! {
 ///
 /// NOW_PROTO: NOW_SESSION_WINDOW_REC_START_MSG
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NowSessionWindowRecStartMsg {
     /// Flags for window recording options
     pub flags: WindowRecStartFlags,
@@ -6,6 +6,7 @@ use crate::{NowHeader, NowMessage, NowMessageClass, NowSessionMessage, NowSessio
 ///
 /// NOW_PROTO: NOW_SESSION_WINDOW_REC_STOP_MSG
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[non_exhaustive]
 pub struct NowSessionWindowRecStopMsg;
 
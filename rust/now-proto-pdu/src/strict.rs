@@ -0,0 +1,181 @@
+//! Strict decoding checks, see [`NowDecodeOptions::with_strict`].
+//!
+//! [`NowDecodeOptions::with_strict`]: crate::NowDecodeOptions::with_strict
+
+use bitflags::Flags;
+use ironrdp_core::{encode_vec, invalid_field_err, DecodeResult, ReadCursor};
+
+use crate::{
+    NowChannelCapsetFlags, NowChannelMessage, NowExecBatchFlags, NowExecCapsetFlags, NowExecDataFlags, NowExecMessage,
+    NowExecProcessFlags, NowExecRunFlags, NowExecShellFlags, NowExecWinPsFlags, NowFileAttributes, NowFileCapsetFlags,
+    NowFileChunkFlags, NowFileDeleteFlags, NowFileListDirRspFlags, NowFileMessage, NowFileMkdirFlags, NowFileOpenFlags,
+    NowFileRenameFlags, NowHeader, NowMessage, NowRdmLaunchFlags, NowRdmMessage, NowRdmSyncFlags,
+    NowSessionCapsetFlags, NowSessionMessage, NowSessionMessageBoxFlags, NowSessionSetKbdLayoutFlags, NowStatusFlags,
+    NowSystemCapsetFlags, NowSystemInfoFlags, NowSystemMessage, NowSystemPowerActionFlags,
+    NowSystemProcessTerminateFlags, NowSystemShutdownFlags, WindowRecEventFlags, WindowRecStartFlags,
+};
+
+const NAME: &str = "NOW_MSG";
+
+/// Validates that the decoded message has been received in its canonical form, and that all of
+/// its flags are known and consistent.
+pub(crate) fn validate(header: &NowHeader, body: &[u8], message: &NowMessage<'_>) -> DecodeResult<()> {
+    ensure_canonical(header, body, message)?;
+    validate_flags(header.flags, body, message)
+}
+
+/// Encoded form of a canonical message is byte-for-byte identical to the received one. This
+/// rejects over-long `NOW_VARU32` values, non-null string terminators, trailing body data, unused
+/// fields with non-default values, flags which are not consistent with the message fields, etc.
+fn ensure_canonical(header: &NowHeader, body: &[u8], message: &NowMessage<'_>) -> DecodeResult<()> {
+    let encoded = encode_vec(message).map_err(|_| invalid_field_err!(NAME, "message", "failed to re-encode"))?;
+    let (encoded_header, encoded_body) = encoded.split_at(NowHeader::FIXED_PART_SIZE);
+
+    let mut expected_header = ReadCursor::new(encoded_header);
+    let is_canonical_header = expected_header.read_u32() == header.size
+        && expected_header.read_u8() == header.class.0
+        && expected_header.read_u8() == header.kind
+        && expected_header.read_u16() == header.flags;
+
+    if !is_canonical_header {
+        return Err(invalid_field_err!(NAME, "header", "non-canonical message header"));
+    }
+
+    if encoded_body != body {
+        return Err(invalid_field_err!(NAME, "body", "non-canonical message body"));
+    }
+
+    Ok(())
+}
+
+fn validate_flags(header_flags: u16, body: &[u8], message: &NowMessage<'_>) -> DecodeResult<()> {
+    // Body offsets below are fixed-size fields, which are guaranteed to be present as the body has
+    // been checked to be canonical.
+    match message {
+        NowMessage::Channel(NowChannelMessage::Capset(_)) => {
+            ensure_known::<NowChannelCapsetFlags>("flags", header_flags)?;
+            ensure_known::<NowSystemCapsetFlags>("systemCapset", body_u16(body, 4))?;
+            ensure_known::<NowSessionCapsetFlags>("sessionCapset", body_u16(body, 6))?;
+            ensure_known::<NowExecCapsetFlags>("execCapset", body_u16(body, 8))?;
+            if NowChannelCapsetFlags::from_bits_retain(header_flags).contains(NowChannelCapsetFlags::SET_FILE_CAPSET) {
+                ensure_known::<NowFileCapsetFlags>("fileCapset", body_u16(body, 14))?;
+            }
+        }
+        NowMessage::Channel(NowChannelMessage::Close(_)) => ensure_status(body, 0)?,
+        NowMessage::System(NowSystemMessage::InfoRsp(_)) => {
+            ensure_known::<NowSystemInfoFlags>("flags", header_flags)?;
+            ensure_status(body, 12)?;
+        }
+        NowMessage::System(NowSystemMessage::Shutdown(_)) => {
+            ensure_known::<NowSystemShutdownFlags>("flags", header_flags)?;
+        }
+        NowMessage::System(NowSystemMessage::PowerAction(_)) => {
+            ensure_known::<NowSystemPowerActionFlags>("flags", header_flags)?;
+        }
+        NowMessage::System(
+            NowSystemMessage::PowerRsp(_)
+            | NowSystemMessage::ProcessListRsp(_)
+            | NowSystemMessage::ProcessTerminateRsp(_),
+        ) => ensure_status(body, 4)?,
+        NowMessage::System(NowSystemMessage::ProcessTerminate(_)) => {
+            ensure_known::<NowSystemProcessTerminateFlags>("flags", header_flags)?;
+        }
+        NowMessage::Session(NowSessionMessage::MsgBoxReq(_)) => {
+            ensure_known::<NowSessionMessageBoxFlags>("flags", header_flags)?;
+        }
+        NowMessage::Session(NowSessionMessage::MsgBoxRsp(_)) => ensure_status(body, 8)?,
+        NowMessage::Session(NowSessionMessage::SetKbdLayout(_)) => {
+            // NEXT+PREV combination is rejected by the lenient decoding as well.
+            ensure_known::<NowSessionSetKbdLayoutFlags>("flags", header_flags)?;
+        }
+        NowMessage::Session(NowSessionMessage::WindowRecStart(_)) => {
+            ensure_known::<WindowRecStartFlags>("flags", header_flags)?;
+        }
+        NowMessage::Session(NowSessionMessage::WindowRecEvent(_)) => {
+            // Exactly one event kind flag is guaranteed by the canonical encoding check.
+            ensure_known::<WindowRecEventFlags>("flags", header_flags)?;
+        }
+        NowMessage::Exec(NowExecMessage::CancelRsp(_)) => ensure_status(body, 4)?,
+        NowMessage::Exec(NowExecMessage::Result(_)) => ensure_status(body, 8)?,
+        NowMessage::Exec(NowExecMessage::Data(msg)) => {
+            ensure_known::<NowExecDataFlags>("flags", header_flags)?;
+            // Exactly one stream kind flag should be set.
+            msg.stream_kind()?;
+        }
+        NowMessage::Exec(NowExecMessage::Run(_)) => ensure_known::<NowExecRunFlags>("flags", header_flags)?,
+        NowMessage::Exec(NowExecMessage::Process(_)) => ensure_known::<NowExecProcessFlags>("flags", header_flags)?,
+        NowMessage::Exec(NowExecMessage::Shell(_)) => ensure_known::<NowExecShellFlags>("flags", header_flags)?,
+        NowMessage::Exec(NowExecMessage::Batch(_)) => ensure_known::<NowExecBatchFlags>("flags", header_flags)?,
+        NowMessage::Exec(NowExecMessage::WinPs(msg)) => {
+            ensure_known::<NowExecWinPsFlags>("flags", header_flags)?;
+            // STA and MTA are mutually exclusive.
+            msg.apartment_state()?;
+        }
+        NowMessage::Exec(NowExecMessage::Pwsh(msg)) => {
+            ensure_known::<NowExecWinPsFlags>("flags", header_flags)?;
+            // STA and MTA are mutually exclusive.
+            msg.apartment_state()?;
+        }
+        NowMessage::Rdm(NowRdmMessage::Capabilities(_)) => {
+            ensure_known::<NowRdmSyncFlags>("syncFlags", body_u32(body, 8))?;
+        }
+        NowMessage::Rdm(NowRdmMessage::AppStart(_)) => {
+            ensure_known::<NowRdmLaunchFlags>("launchFlags", body_u32(body, 0))?;
+        }
+        NowMessage::File(NowFileMessage::Open(msg)) => {
+            ensure_known::<NowFileOpenFlags>("flags", header_flags)?;
+            // Exactly one of READ and WRITE should be set, RESUME is only valid for writes.
+            msg.mode()?;
+        }
+        NowMessage::File(NowFileMessage::OpenRsp(_)) => ensure_status(body, 12)?,
+        NowMessage::File(NowFileMessage::Chunk(_)) => ensure_known::<NowFileChunkFlags>("flags", header_flags)?,
+        NowMessage::File(NowFileMessage::Close(msg)) => {
+            msg.sha256()?;
+        }
+        NowMessage::File(NowFileMessage::StatRsp(_)) => {
+            ensure_known::<NowFileAttributes>("attributes", body_u32(body, 8))?;
+            ensure_status(body, 28)?;
+        }
+        NowMessage::File(NowFileMessage::DirEntry(_)) => {
+            ensure_known::<NowFileAttributes>("attributes", body_u32(body, 8))?;
+        }
+        NowMessage::File(NowFileMessage::ListDirRsp(_)) => {
+            ensure_known::<NowFileListDirRspFlags>("flags", header_flags)?;
+            ensure_status(body, 4)?;
+        }
+        NowMessage::File(NowFileMessage::Mkdir(_)) => ensure_known::<NowFileMkdirFlags>("flags", header_flags)?,
+        NowMessage::File(NowFileMessage::Delete(_)) => ensure_known::<NowFileDeleteFlags>("flags", header_flags)?,
+        NowMessage::File(NowFileMessage::Rename(_)) => ensure_known::<NowFileRenameFlags>("flags", header_flags)?,
+        NowMessage::File(NowFileMessage::CloseRsp(_) | NowFileMessage::OpRsp(_)) => ensure_status(body, 4)?,
+        // Remaining messages have no flags, and their header flags are always zero in the
+        // canonical encoding.
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn ensure_known<F: Flags>(field: &'static str, bits: F::Bits) -> DecodeResult<()> {
+    if F::from_bits(bits).is_none() {
+        return Err(invalid_field_err!(NAME, field, "unknown flags set"));
+    }
+
+    Ok(())
+}
+
+/// Checks `NOW_STATUS` flags located at the given body offset.
+fn ensure_status(body: &[u8], offset: usize) -> DecodeResult<()> {
+    ensure_known::<NowStatusFlags>("status flags", body_u16(body, offset))
+}
+
+fn body_u16(body: &[u8], offset: usize) -> u16 {
+    let mut cursor = ReadCursor::new(body);
+    cursor.read_slice(offset);
+    cursor.read_u16()
+}
+
+fn body_u32(body: &[u8], offset: usize) -> u32 {
+    let mut cursor = ReadCursor::new(body);
+    cursor.read_slice(offset);
+    cursor.read_u32()
+}
@@ -0,0 +1,22 @@
+use now_proto_derive::NowPdu;
+
+/// The NOW_SYSTEM_INFO_REQ_MSG message is used to query the remote system information, which is
+/// sent back with NOW_SYSTEM_INFO_RSP_MSG.
+///
+/// NOW_PROTO: NOW_SYSTEM_INFO_REQ_MSG
+#[derive(Debug, Clone, PartialEq, Eq, NowPdu)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[now(class = SYSTEM, kind = INFO_REQ, variant = InfoReq)]
+pub struct NowSystemInfoReqMsg {
+    request_id: u32,
+}
+
+impl NowSystemInfoReqMsg {
+    pub fn new(request_id: u32) -> Self {
+        Self { request_id }
+    }
+
+    pub fn request_id(&self) -> u32 {
+        self.request_id
+    }
+}
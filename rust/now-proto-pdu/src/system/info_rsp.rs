@@ -0,0 +1,205 @@
+use alloc::borrow::Cow;
+use core::time;
+
+use bitflags::bitflags;
+use ironrdp_core::EncodeResult;
+use now_proto_derive::NowPdu;
+
+use crate::{NowStatus, NowStatusError, NowVarStr};
+
+bitflags! {
+    /// NOW_PROTO: NOW_SYSTEM_INFO_FLAG_* constants.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+    pub struct NowSystemInfoFlags: u16 {
+        /// `osName` field contains a valid value.
+        ///
+        /// NOW_PROTO: NOW_SYSTEM_INFO_FLAG_OS_NAME
+        const OS_NAME = 0x0001;
+        /// `osVersion` field contains a valid value.
+        ///
+        /// NOW_PROTO: NOW_SYSTEM_INFO_FLAG_OS_VERSION
+        const OS_VERSION = 0x0002;
+        /// `hostname` field contains a valid value.
+        ///
+        /// NOW_PROTO: NOW_SYSTEM_INFO_FLAG_HOSTNAME
+        const HOSTNAME = 0x0004;
+        /// `architecture` field contains a valid value.
+        ///
+        /// NOW_PROTO: NOW_SYSTEM_INFO_FLAG_ARCHITECTURE
+        const ARCHITECTURE = 0x0008;
+        /// `uptime` field contains a valid value.
+        ///
+        /// NOW_PROTO: NOW_SYSTEM_INFO_FLAG_UPTIME
+        const UPTIME = 0x0010;
+        /// `loggedOnUser` field contains a valid value.
+        ///
+        /// NOW_PROTO: NOW_SYSTEM_INFO_FLAG_LOGGED_ON_USER
+        const LOGGED_ON_USER = 0x0020;
+        /// `agentVersion` field contains a valid value.
+        ///
+        /// NOW_PROTO: NOW_SYSTEM_INFO_FLAG_AGENT_VERSION
+        const AGENT_VERSION = 0x0040;
+    }
+}
+
+/// The NOW_SYSTEM_INFO_RSP_MSG message is sent in response to NOW_SYSTEM_INFO_REQ_MSG, and
+/// contains the remote system information. Each information field is optional, and only valid
+/// if the corresponding flag is set.
+///
+/// NOW_PROTO: NOW_SYSTEM_INFO_RSP_MSG
+#[derive(Debug, Clone, PartialEq, Eq, NowPdu)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[now(class = SYSTEM, kind = INFO_RSP, variant = InfoRsp)]
+pub struct NowSystemInfoRspMsg<'a> {
+    #[now(flags)]
+    flags: NowSystemInfoFlags,
+    request_id: u32,
+    /// System uptime, in seconds.
+    uptime: u64,
+    status: NowStatus<'a>,
+    os_name: NowVarStr<'a>,
+    os_version: NowVarStr<'a>,
+    hostname: NowVarStr<'a>,
+    architecture: NowVarStr<'a>,
+    logged_on_user: NowVarStr<'a>,
+    agent_version: NowVarStr<'a>,
+}
+
+impl<'a> NowSystemInfoRspMsg<'a> {
+    /// Creates successful response without any information fields set.
+    pub fn new_success(request_id: u32) -> Self {
+        Self {
+            flags: NowSystemInfoFlags::empty(),
+            request_id,
+            uptime: 0,
+            status: NowStatus::new_success(),
+            os_name: NowVarStr::default(),
+            os_version: NowVarStr::default(),
+            hostname: NowVarStr::default(),
+            architecture: NowVarStr::default(),
+            logged_on_user: NowVarStr::default(),
+            agent_version: NowVarStr::default(),
+        }
+    }
+
+    pub fn new_error(request_id: u32, error: impl Into<NowStatusError>) -> EncodeResult<Self> {
+        let msg = Self {
+            status: NowStatus::new_error(error),
+            ..Self::new_success(request_id)
+        };
+
+        msg.ensure_message_size()?;
+
+        Ok(msg)
+    }
+
+    /// Operating system name (e.g. `Windows 11 Pro` or `Ubuntu 24.04 LTS`).
+    pub fn with_os_name(mut self, os_name: impl Into<Cow<'a, str>>) -> EncodeResult<Self> {
+        self.flags |= NowSystemInfoFlags::OS_NAME;
+        self.os_name = NowVarStr::new(os_name)?;
+
+        self.ensure_message_size()?;
+
+        Ok(self)
+    }
+
+    /// Operating system version (e.g. `10.0.26100` or the kernel release).
+    pub fn with_os_version(mut self, os_version: impl Into<Cow<'a, str>>) -> EncodeResult<Self> {
+        self.flags |= NowSystemInfoFlags::OS_VERSION;
+        self.os_version = NowVarStr::new(os_version)?;
+
+        self.ensure_message_size()?;
+
+        Ok(self)
+    }
+
+    pub fn with_hostname(mut self, hostname: impl Into<Cow<'a, str>>) -> EncodeResult<Self> {
+        self.flags |= NowSystemInfoFlags::HOSTNAME;
+        self.hostname = NowVarStr::new(hostname)?;
+
+        self.ensure_message_size()?;
+
+        Ok(self)
+    }
+
+    /// Processor architecture (e.g. `x86_64` or `aarch64`).
+    pub fn with_architecture(mut self, architecture: impl Into<Cow<'a, str>>) -> EncodeResult<Self> {
+        self.flags |= NowSystemInfoFlags::ARCHITECTURE;
+        self.architecture = NowVarStr::new(architecture)?;
+
+        self.ensure_message_size()?;
+
+        Ok(self)
+    }
+
+    /// System uptime; sent with the precision of seconds.
+    #[must_use]
+    pub fn with_uptime(mut self, uptime: time::Duration) -> Self {
+        self.flags |= NowSystemInfoFlags::UPTIME;
+        self.uptime = uptime.as_secs();
+        self
+    }
+
+    /// Name of the user logged on the interactive session.
+    pub fn with_logged_on_user(mut self, logged_on_user: impl Into<Cow<'a, str>>) -> EncodeResult<Self> {
+        self.flags |= NowSystemInfoFlags::LOGGED_ON_USER;
+        self.logged_on_user = NowVarStr::new(logged_on_user)?;
+
+        self.ensure_message_size()?;
+
+        Ok(self)
+    }
+
+    /// Version of the NOW-PROTO host implementation.
+    pub fn with_agent_version(mut self, agent_version: impl Into<Cow<'a, str>>) -> EncodeResult<Self> {
+        self.flags |= NowSystemInfoFlags::AGENT_VERSION;
+        self.agent_version = NowVarStr::new(agent_version)?;
+
+        self.ensure_message_size()?;
+
+        Ok(self)
+    }
+
+    pub fn request_id(&self) -> u32 {
+        self.request_id
+    }
+
+    pub fn to_result(&self) -> Result<(), NowStatusError> {
+        self.status.to_result()
+    }
+
+    pub fn os_name(&self) -> Option<&str> {
+        self.optional_str(NowSystemInfoFlags::OS_NAME, &self.os_name)
+    }
+
+    pub fn os_version(&self) -> Option<&str> {
+        self.optional_str(NowSystemInfoFlags::OS_VERSION, &self.os_version)
+    }
+
+    pub fn hostname(&self) -> Option<&str> {
+        self.optional_str(NowSystemInfoFlags::HOSTNAME, &self.hostname)
+    }
+
+    pub fn architecture(&self) -> Option<&str> {
+        self.optional_str(NowSystemInfoFlags::ARCHITECTURE, &self.architecture)
+    }
+
+    pub fn uptime(&self) -> Option<time::Duration> {
+        self.flags
+            .contains(NowSystemInfoFlags::UPTIME)
+            .then(|| time::Duration::from_secs(self.uptime))
+    }
+
+    pub fn logged_on_user(&self) -> Option<&str> {
+        self.optional_str(NowSystemInfoFlags::LOGGED_ON_USER, &self.logged_on_user)
+    }
+
+    pub fn agent_version(&self) -> Option<&str> {
+        self.optional_str(NowSystemInfoFlags::AGENT_VERSION, &self.agent_version)
+    }
+
+    fn optional_str<'s>(&'s self, flag: NowSystemInfoFlags, value: &'s NowVarStr<'_>) -> Option<&'s str> {
+        self.flags.contains(flag).then(|| value.as_str())
+    }
+}
@@ -1,14 +1,51 @@
+mod info_req;
+mod info_rsp;
+mod power_action;
+mod power_rsp;
+mod process_info;
+mod process_list_req;
+mod process_list_rsp;
+mod process_terminate;
+mod process_terminate_rsp;
 mod shutdown;
+mod shutdown_abort;
 
+pub use info_req::NowSystemInfoReqMsg;
+pub use info_rsp::{NowSystemInfoFlags, NowSystemInfoRspMsg, OwnedNowSystemInfoRspMsg};
 use ironrdp_core::{DecodeResult, Encode, EncodeResult, IntoOwned, ReadCursor, WriteCursor};
-pub use shutdown::{NowSystemShutdownMsg, OwnedNowSystemShutdownMsg};
+pub use power_action::{
+    NowSystemPowerAction, NowSystemPowerActionFlags, NowSystemPowerActionMsg, OwnedNowSystemPowerActionMsg,
+};
+pub use power_rsp::{NowSystemPowerRspMsg, OwnedNowSystemPowerRspMsg};
+pub use process_info::{NowSystemProcessInfoMsg, OwnedNowSystemProcessInfoMsg};
+pub use process_list_req::NowSystemProcessListReqMsg;
+pub use process_list_rsp::{NowSystemProcessListRspMsg, OwnedNowSystemProcessListRspMsg};
+pub use process_terminate::{NowSystemProcessTerminateFlags, NowSystemProcessTerminateMsg};
+pub use process_terminate_rsp::{NowSystemProcessTerminateRspMsg, OwnedNowSystemProcessTerminateRspMsg};
+pub use shutdown::{NowSystemShutdownFlags, NowSystemShutdownMsg, OwnedNowSystemShutdownMsg};
+pub use shutdown_abort::NowSystemShutdownAbortMsg;
 
-use crate::NowHeader;
+use crate::{CheckDecodeLimits, DecodeLimitsChecker, NowHeader};
 
 // Wrapper for the `NOW_SYSTEM_MSG_CLASS_ID` message class.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum NowSystemMessage<'a> {
+    InfoReq(NowSystemInfoReqMsg),
+    InfoRsp(NowSystemInfoRspMsg<'a>),
     Shutdown(NowSystemShutdownMsg<'a>),
+    ShutdownAbort(NowSystemShutdownAbortMsg),
+    PowerAction(NowSystemPowerActionMsg<'a>),
+    PowerRsp(NowSystemPowerRspMsg<'a>),
+    ProcessListReq(NowSystemProcessListReqMsg),
+    ProcessInfo(NowSystemProcessInfoMsg<'a>),
+    ProcessListRsp(NowSystemProcessListRspMsg<'a>),
+    ProcessTerminate(NowSystemProcessTerminateMsg),
+    ProcessTerminateRsp(NowSystemProcessTerminateRspMsg<'a>),
 }
 
 pub type OwnedNowSystemMessage = NowSystemMessage<'static>;
@@ -18,7 +55,32 @@ impl IntoOwned for NowSystemMessage<'_> {
 
     fn into_owned(self) -> Self::Owned {
         match self {
+            Self::InfoReq(msg) => OwnedNowSystemMessage::InfoReq(msg),
+            Self::InfoRsp(msg) => OwnedNowSystemMessage::InfoRsp(msg.into_owned()),
             Self::Shutdown(msg) => OwnedNowSystemMessage::Shutdown(msg.into_owned()),
+            Self::ShutdownAbort(msg) => OwnedNowSystemMessage::ShutdownAbort(msg),
+            Self::PowerAction(msg) => OwnedNowSystemMessage::PowerAction(msg.into_owned()),
+            Self::PowerRsp(msg) => OwnedNowSystemMessage::PowerRsp(msg.into_owned()),
+            Self::ProcessListReq(msg) => OwnedNowSystemMessage::ProcessListReq(msg),
+            Self::ProcessInfo(msg) => OwnedNowSystemMessage::ProcessInfo(msg.into_owned()),
+            Self::ProcessListRsp(msg) => OwnedNowSystemMessage::ProcessListRsp(msg.into_owned()),
+            Self::ProcessTerminate(msg) => OwnedNowSystemMessage::ProcessTerminate(msg),
+            Self::ProcessTerminateRsp(msg) => OwnedNowSystemMessage::ProcessTerminateRsp(msg.into_owned()),
+        }
+    }
+}
+
+impl CheckDecodeLimits for NowSystemMessage<'_> {
+    fn check_decode_limits(&self, checker: &mut DecodeLimitsChecker) -> DecodeResult<()> {
+        match self {
+            Self::InfoReq(_) | Self::ShutdownAbort(_) | Self::ProcessListReq(_) | Self::ProcessTerminate(_) => Ok(()),
+            Self::InfoRsp(msg) => msg.check_decode_limits(checker),
+            Self::Shutdown(msg) => msg.check_decode_limits(checker),
+            Self::PowerAction(msg) => msg.check_decode_limits(checker),
+            Self::PowerRsp(msg) => msg.check_decode_limits(checker),
+            Self::ProcessInfo(msg) => msg.check_decode_limits(checker),
+            Self::ProcessListRsp(msg) => msg.check_decode_limits(checker),
+            Self::ProcessTerminateRsp(msg) => msg.check_decode_limits(checker),
         }
     }
 }
@@ -28,16 +90,68 @@ impl<'a> NowSystemMessage<'a> {
 
     pub fn decode_from_body(header: NowHeader, src: &mut ReadCursor<'a>) -> DecodeResult<Self> {
         match NowSystemMessageKind(header.kind) {
+            NowSystemMessageKind::INFO_REQ => Ok(Self::InfoReq(NowSystemInfoReqMsg::decode_from_body(header, src)?)),
+            NowSystemMessageKind::INFO_RSP => Ok(Self::InfoRsp(NowSystemInfoRspMsg::decode_from_body(header, src)?)),
             NowSystemMessageKind::SHUTDOWN => Ok(Self::Shutdown(NowSystemShutdownMsg::decode_from_body(header, src)?)),
+            NowSystemMessageKind::SHUTDOWN_ABORT => Ok(Self::ShutdownAbort(
+                NowSystemShutdownAbortMsg::decode_from_body(header, src)?,
+            )),
+            NowSystemMessageKind::POWER_ACTION => Ok(Self::PowerAction(NowSystemPowerActionMsg::decode_from_body(
+                header, src,
+            )?)),
+            NowSystemMessageKind::POWER_RSP => Ok(Self::PowerRsp(NowSystemPowerRspMsg::decode_from_body(header, src)?)),
+            NowSystemMessageKind::PROCESS_LIST_REQ => Ok(Self::ProcessListReq(
+                NowSystemProcessListReqMsg::decode_from_body(header, src)?,
+            )),
+            NowSystemMessageKind::PROCESS_INFO => Ok(Self::ProcessInfo(NowSystemProcessInfoMsg::decode_from_body(
+                header, src,
+            )?)),
+            NowSystemMessageKind::PROCESS_LIST_RSP => Ok(Self::ProcessListRsp(
+                NowSystemProcessListRspMsg::decode_from_body(header, src)?,
+            )),
+            NowSystemMessageKind::PROCESS_TERMINATE => Ok(Self::ProcessTerminate(
+                NowSystemProcessTerminateMsg::decode_from_body(header, src)?,
+            )),
+            NowSystemMessageKind::PROCESS_TERMINATE_RSP => Ok(Self::ProcessTerminateRsp(
+                NowSystemProcessTerminateRspMsg::decode_from_body(header, src)?,
+            )),
             _ => Err(unsupported_message_err!(class: header.class.0, kind: header.kind)),
         }
     }
+
+    /// Returns `true` if the message kind is known for this message class.
+    pub(crate) fn is_known_kind(kind: u8) -> bool {
+        matches!(
+            NowSystemMessageKind(kind),
+            NowSystemMessageKind::INFO_REQ
+                | NowSystemMessageKind::INFO_RSP
+                | NowSystemMessageKind::SHUTDOWN
+                | NowSystemMessageKind::SHUTDOWN_ABORT
+                | NowSystemMessageKind::POWER_ACTION
+                | NowSystemMessageKind::POWER_RSP
+                | NowSystemMessageKind::PROCESS_LIST_REQ
+                | NowSystemMessageKind::PROCESS_INFO
+                | NowSystemMessageKind::PROCESS_LIST_RSP
+                | NowSystemMessageKind::PROCESS_TERMINATE
+                | NowSystemMessageKind::PROCESS_TERMINATE_RSP
+        )
+    }
 }
 
 impl Encode for NowSystemMessage<'_> {
     fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
         match self {
+            Self::InfoReq(msg) => msg.encode(dst),
+            Self::InfoRsp(msg) => msg.encode(dst),
             Self::Shutdown(msg) => msg.encode(dst),
+            Self::ShutdownAbort(msg) => msg.encode(dst),
+            Self::PowerAction(msg) => msg.encode(dst),
+            Self::PowerRsp(msg) => msg.encode(dst),
+            Self::ProcessListReq(msg) => msg.encode(dst),
+            Self::ProcessInfo(msg) => msg.encode(dst),
+            Self::ProcessListRsp(msg) => msg.encode(dst),
+            Self::ProcessTerminate(msg) => msg.encode(dst),
+            Self::ProcessTerminateRsp(msg) => msg.encode(dst),
         }
     }
 
@@ -47,7 +161,17 @@ impl Encode for NowSystemMessage<'_> {
 
     fn size(&self) -> usize {
         match self {
+            Self::InfoReq(msg) => msg.size(),
+            Self::InfoRsp(msg) => msg.size(),
             Self::Shutdown(msg) => msg.size(),
+            Self::ShutdownAbort(msg) => msg.size(),
+            Self::PowerAction(msg) => msg.size(),
+            Self::PowerRsp(msg) => msg.size(),
+            Self::ProcessListReq(msg) => msg.size(),
+            Self::ProcessInfo(msg) => msg.size(),
+            Self::ProcessListRsp(msg) => msg.size(),
+            Self::ProcessTerminate(msg) => msg.size(),
+            Self::ProcessTerminateRsp(msg) => msg.size(),
         }
     }
 }
@@ -57,13 +181,26 @@ impl Encode for NowSystemMessage<'_> {
 pub struct NowSystemMessageKind(pub u8);
 
 impl NowSystemMessageKind {
-    // TODO: NOW_SYSTEM_INFO_REQ_ID/NOW_SYSTEM_INFO_RSP_ID when will be added to the protocol
-    // specification.
-
-    // /// NOW-PROTO: NOW_SYSTEM_INFO_REQ_ID
-    // pub const INFO_REQ: Self = Self(0x01);
-    // /// NOW-PROTO: NOW_SYSTEM_INFO_RSP_ID
-    // pub const INFO_RSP: Self = Self(0x02);
+    /// NOW-PROTO: NOW_SYSTEM_INFO_REQ_ID
+    pub const INFO_REQ: Self = Self(0x01);
+    /// NOW-PROTO: NOW_SYSTEM_INFO_RSP_ID
+    pub const INFO_RSP: Self = Self(0x02);
     /// NOW-PROTO: NOW_SYSTEM_SHUTDOWN_ID
     pub const SHUTDOWN: Self = Self(0x03);
+    /// NOW-PROTO: NOW_SYSTEM_SHUTDOWN_ABORT_ID
+    pub const SHUTDOWN_ABORT: Self = Self(0x04);
+    /// NOW-PROTO: NOW_SYSTEM_POWER_ACTION_ID
+    pub const POWER_ACTION: Self = Self(0x05);
+    /// NOW-PROTO: NOW_SYSTEM_POWER_RSP_ID
+    pub const POWER_RSP: Self = Self(0x06);
+    /// NOW-PROTO: NOW_SYSTEM_PROCESS_LIST_REQ_ID
+    pub const PROCESS_LIST_REQ: Self = Self(0x07);
+    /// NOW-PROTO: NOW_SYSTEM_PROCESS_INFO_ID
+    pub const PROCESS_INFO: Self = Self(0x08);
+    /// NOW-PROTO: NOW_SYSTEM_PROCESS_LIST_RSP_ID
+    pub const PROCESS_LIST_RSP: Self = Self(0x09);
+    /// NOW-PROTO: NOW_SYSTEM_PROCESS_TERMINATE_ID
+    pub const PROCESS_TERMINATE: Self = Self(0x0A);
+    /// NOW-PROTO: NOW_SYSTEM_PROCESS_TERMINATE_RSP_ID
+    pub const PROCESS_TERMINATE_RSP: Self = Self(0x0B);
 }
@@ -0,0 +1,146 @@
+use alloc::borrow::Cow;
+use core::time;
+
+use bitflags::bitflags;
+use ironrdp_core::{invalid_field_err, EncodeResult};
+use now_proto_derive::NowPdu;
+
+use crate::NowVarStr;
+
+/// System power action; unknown values are preserved as is.
+///
+/// NOW_PROTO: `action` field from NOW_SYSTEM_POWER_ACTION_MSG
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+pub struct NowSystemPowerAction(u32);
+
+impl NowSystemPowerAction {
+    /// Shut down the system.
+    ///
+    /// NOW_PROTO: NOW_POWER_ACTION_SHUTDOWN
+    pub const SHUTDOWN: Self = Self(0x0001);
+    /// Reboot the system.
+    ///
+    /// NOW_PROTO: NOW_POWER_ACTION_REBOOT
+    pub const REBOOT: Self = Self(0x0002);
+    /// Suspend the system to RAM.
+    ///
+    /// NOW_PROTO: NOW_POWER_ACTION_SLEEP
+    pub const SLEEP: Self = Self(0x0003);
+    /// Suspend the system to disk.
+    ///
+    /// NOW_PROTO: NOW_POWER_ACTION_HIBERNATE
+    pub const HIBERNATE: Self = Self(0x0004);
+    /// Restart user sessions only, without rebooting the operating system.
+    ///
+    /// NOW_PROTO: NOW_POWER_ACTION_SESSION_RESTART
+    pub const SESSION_RESTART: Self = Self(0x0005);
+
+    pub fn new(action: u32) -> Self {
+        Self(action)
+    }
+
+    pub fn value(&self) -> u32 {
+        self.0
+    }
+}
+
+bitflags! {
+    /// NOW_PROTO: NOW_POWER_ACTION_FLAG_* constants.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+    pub struct NowSystemPowerActionFlags: u16 {
+        /// Close applications without waiting for them to exit gracefully.
+        ///
+        /// NOW_PROTO: NOW_POWER_ACTION_FLAG_FORCE
+        const FORCE = 0x0001;
+    }
+}
+
+// Sanity check: Limit power action timeout to ~1 year.
+const MAX_POWER_ACTION_TIMEOUT: time::Duration = time::Duration::from_secs(60 * 60 * 24 * 365);
+
+fn ensure_power_action_timeout(timeout: time::Duration) -> EncodeResult<()> {
+    if timeout > MAX_POWER_ACTION_TIMEOUT {
+        return Err(invalid_field_err!("timeout", "too big power action timeout"));
+    }
+
+    Ok(())
+}
+
+/// The NOW_SYSTEM_POWER_ACTION_MSG message is used to request a system power state change. The
+/// server responds with NOW_SYSTEM_POWER_RSP_MSG once the action has been scheduled.
+///
+/// NOW_PROTO: NOW_SYSTEM_POWER_ACTION_MSG
+#[derive(Debug, Clone, PartialEq, Eq, NowPdu)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[now(class = SYSTEM, kind = POWER_ACTION, variant = PowerAction, validate)]
+pub struct NowSystemPowerActionMsg<'a> {
+    #[now(flags)]
+    flags: NowSystemPowerActionFlags,
+    request_id: u32,
+    action: u32,
+    /// Delay before the action is performed, in seconds.
+    timeout: u32,
+    /// Optional message displayed to the logged on users.
+    message: NowVarStr<'a>,
+}
+
+impl<'a> NowSystemPowerActionMsg<'a> {
+    pub fn new(request_id: u32, action: NowSystemPowerAction) -> Self {
+        Self {
+            flags: NowSystemPowerActionFlags::empty(),
+            request_id,
+            action: action.value(),
+            timeout: 0,
+            message: NowVarStr::default(),
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: time::Duration) -> EncodeResult<Self> {
+        ensure_power_action_timeout(timeout)?;
+
+        self.timeout = u32::try_from(timeout.as_secs()).expect("timeout is within u32 range");
+
+        Ok(self)
+    }
+
+    pub fn with_message(mut self, message: impl Into<Cow<'a, str>>) -> EncodeResult<Self> {
+        self.message = NowVarStr::new(message)?;
+
+        self.ensure_message_size()?;
+
+        Ok(self)
+    }
+
+    #[must_use]
+    pub fn with_force(mut self) -> Self {
+        self.flags |= NowSystemPowerActionFlags::FORCE;
+        self
+    }
+
+    pub fn request_id(&self) -> u32 {
+        self.request_id
+    }
+
+    pub fn action(&self) -> NowSystemPowerAction {
+        NowSystemPowerAction(self.action)
+    }
+
+    pub fn is_force(&self) -> bool {
+        self.flags.contains(NowSystemPowerActionFlags::FORCE)
+    }
+
+    pub fn timeout(&self) -> time::Duration {
+        time::Duration::from_secs(self.timeout.into())
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    #[cfg(feature = "serde")]
+    fn validate(&self) -> EncodeResult<()> {
+        ensure_power_action_timeout(self.timeout())
+    }
+}
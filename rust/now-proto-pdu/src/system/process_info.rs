@@ -0,0 +1,116 @@
+use alloc::borrow::Cow;
+
+use ironrdp_core::EncodeResult;
+use now_proto_derive::NowPdu;
+
+use crate::NowVarStr;
+
+/// The NOW_SYSTEM_PROCESS_INFO_MSG message describes a single process running on the remote host,
+/// and is sent in response to NOW_SYSTEM_PROCESS_LIST_REQ_MSG.
+///
+/// NOW_PROTO: NOW_SYSTEM_PROCESS_INFO_MSG
+#[derive(Debug, Clone, PartialEq, Eq, NowPdu)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[now(class = SYSTEM, kind = PROCESS_INFO, variant = ProcessInfo)]
+pub struct NowSystemProcessInfoMsg<'a> {
+    request_id: u32,
+    pid: u32,
+    parent_pid: u32,
+    session_id: u32,
+    /// Process start time, as Unix timestamp in seconds.
+    start_time: u64,
+    process_name: NowVarStr<'a>,
+    exe_path: NowVarStr<'a>,
+    user: NowVarStr<'a>,
+}
+
+impl<'a> NowSystemProcessInfoMsg<'a> {
+    pub fn new(request_id: u32, pid: u32, process_name: impl Into<Cow<'a, str>>) -> EncodeResult<Self> {
+        let msg = Self {
+            request_id,
+            pid,
+            parent_pid: 0,
+            session_id: 0,
+            start_time: 0,
+            process_name: NowVarStr::new(process_name)?,
+            exe_path: NowVarStr::default(),
+            user: NowVarStr::default(),
+        };
+
+        msg.ensure_message_size()?;
+
+        Ok(msg)
+    }
+
+    #[must_use]
+    pub fn with_parent_pid(mut self, parent_pid: u32) -> Self {
+        self.parent_pid = parent_pid;
+        self
+    }
+
+    #[must_use]
+    pub fn with_session_id(mut self, session_id: u32) -> Self {
+        self.session_id = session_id;
+        self
+    }
+
+    /// Sets process start time, as Unix timestamp in seconds.
+    #[must_use]
+    pub fn with_start_time(mut self, start_time: u64) -> Self {
+        self.start_time = start_time;
+        self
+    }
+
+    pub fn with_exe_path(mut self, exe_path: impl Into<Cow<'a, str>>) -> EncodeResult<Self> {
+        self.exe_path = NowVarStr::new(exe_path)?;
+
+        self.ensure_message_size()?;
+
+        Ok(self)
+    }
+
+    /// Name of the user owning the process.
+    pub fn with_user(mut self, user: impl Into<Cow<'a, str>>) -> EncodeResult<Self> {
+        self.user = NowVarStr::new(user)?;
+
+        self.ensure_message_size()?;
+
+        Ok(self)
+    }
+
+    pub fn request_id(&self) -> u32 {
+        self.request_id
+    }
+
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    /// Parent process ID; `0` if the process has no parent or it is unknown.
+    pub fn parent_pid(&self) -> u32 {
+        self.parent_pid
+    }
+
+    pub fn session_id(&self) -> u32 {
+        self.session_id
+    }
+
+    /// Process start time, as Unix timestamp in seconds; `0` if unknown.
+    pub fn start_time(&self) -> u64 {
+        self.start_time
+    }
+
+    pub fn process_name(&self) -> &str {
+        &self.process_name
+    }
+
+    /// Full path of the process executable; empty if unknown or inaccessible.
+    pub fn exe_path(&self) -> &str {
+        &self.exe_path
+    }
+
+    /// Name of the user owning the process; empty if unknown.
+    pub fn user(&self) -> &str {
+        &self.user
+    }
+}
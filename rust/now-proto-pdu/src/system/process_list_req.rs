@@ -0,0 +1,23 @@
+use now_proto_derive::NowPdu;
+
+/// The NOW_SYSTEM_PROCESS_LIST_REQ_MSG message is used to enumerate processes running on the
+/// remote host. The server sends NOW_SYSTEM_PROCESS_INFO_MSG for each process, followed by
+/// NOW_SYSTEM_PROCESS_LIST_RSP_MSG.
+///
+/// NOW_PROTO: NOW_SYSTEM_PROCESS_LIST_REQ_MSG
+#[derive(Debug, Clone, PartialEq, Eq, NowPdu)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[now(class = SYSTEM, kind = PROCESS_LIST_REQ, variant = ProcessListReq)]
+pub struct NowSystemProcessListReqMsg {
+    request_id: u32,
+}
+
+impl NowSystemProcessListReqMsg {
+    pub fn new(request_id: u32) -> Self {
+        Self { request_id }
+    }
+
+    pub fn request_id(&self) -> u32 {
+        self.request_id
+    }
+}
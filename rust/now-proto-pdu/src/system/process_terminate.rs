@@ -0,0 +1,56 @@
+use bitflags::bitflags;
+use now_proto_derive::NowPdu;
+
+bitflags! {
+    /// NOW_PROTO: NOW_PROCESS_TERMINATE_FLAG_* constants.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+    pub struct NowSystemProcessTerminateFlags: u16 {
+        /// Kill the process immediately, without requesting it to exit gracefully.
+        ///
+        /// NOW_PROTO: NOW_PROCESS_TERMINATE_FLAG_FORCE
+        const FORCE = 0x0001;
+    }
+}
+
+/// The NOW_SYSTEM_PROCESS_TERMINATE_MSG message is used to terminate a process running on the
+/// remote host. The server responds with NOW_SYSTEM_PROCESS_TERMINATE_RSP_MSG.
+///
+/// NOW_PROTO: NOW_SYSTEM_PROCESS_TERMINATE_MSG
+#[derive(Debug, Clone, PartialEq, Eq, NowPdu)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[now(class = SYSTEM, kind = PROCESS_TERMINATE, variant = ProcessTerminate)]
+pub struct NowSystemProcessTerminateMsg {
+    #[now(flags)]
+    flags: NowSystemProcessTerminateFlags,
+    request_id: u32,
+    pid: u32,
+}
+
+impl NowSystemProcessTerminateMsg {
+    pub fn new(request_id: u32, pid: u32) -> Self {
+        Self {
+            flags: NowSystemProcessTerminateFlags::empty(),
+            request_id,
+            pid,
+        }
+    }
+
+    #[must_use]
+    pub fn with_force(mut self) -> Self {
+        self.flags |= NowSystemProcessTerminateFlags::FORCE;
+        self
+    }
+
+    pub fn request_id(&self) -> u32 {
+        self.request_id
+    }
+
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    pub fn is_force(&self) -> bool {
+        self.flags.contains(NowSystemProcessTerminateFlags::FORCE)
+    }
+}
@@ -0,0 +1,44 @@
+use ironrdp_core::EncodeResult;
+use now_proto_derive::NowPdu;
+
+use crate::{NowStatus, NowStatusError};
+
+/// The NOW_SYSTEM_PROCESS_TERMINATE_RSP_MSG message is sent in response to
+/// NOW_SYSTEM_PROCESS_TERMINATE_MSG.
+///
+/// NOW_PROTO: NOW_SYSTEM_PROCESS_TERMINATE_RSP_MSG
+#[derive(Debug, Clone, PartialEq, Eq, NowPdu)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[now(class = SYSTEM, kind = PROCESS_TERMINATE_RSP, variant = ProcessTerminateRsp)]
+pub struct NowSystemProcessTerminateRspMsg<'a> {
+    request_id: u32,
+    status: NowStatus<'a>,
+}
+
+impl NowSystemProcessTerminateRspMsg<'_> {
+    pub fn new_success(request_id: u32) -> Self {
+        Self {
+            request_id,
+            status: NowStatus::new_success(),
+        }
+    }
+
+    pub fn new_error(request_id: u32, error: impl Into<NowStatusError>) -> EncodeResult<Self> {
+        let msg = Self {
+            request_id,
+            status: NowStatus::new_error(error),
+        };
+
+        msg.ensure_message_size()?;
+
+        Ok(msg)
+    }
+
+    pub fn request_id(&self) -> u32 {
+        self.request_id
+    }
+
+    pub fn to_result(&self) -> Result<(), NowStatusError> {
+        self.status.to_result()
+    }
+}
@@ -8,12 +8,15 @@ use ironrdp_core::{
 };
 
 use crate::system::NowSystemMessageKind;
-use crate::{NowHeader, NowMessage, NowMessageClass, NowSystemMessage, NowVarStr};
+use crate::{
+    CheckDecodeLimits, DecodeLimitsChecker, NowHeader, NowMessage, NowMessageClass, NowSystemMessage, NowVarStr,
+};
 
 bitflags! {
     /// NOW_PROTO: NOW_SYSTEM_SHUTDOWN_FLAG_* constants.
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-    struct NowSystemShutdownFlags: u16 {
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+    pub struct NowSystemShutdownFlags: u16 {
         /// Force shutdown
         ///
         /// NOW-PROTO: NOW_SHUTDOWN_FLAG_FORCE
@@ -25,10 +28,26 @@ bitflags! {
     }
 }
 
+// Sanity check: Limit shutdown timeout to ~1 year.
+const MAX_SHUTDOWN_TINEOUT: time::Duration = time::Duration::from_secs(60 * 60 * 24 * 365);
+
+fn ensure_shutdown_timeout(timeout: time::Duration) -> EncodeResult<()> {
+    if timeout > MAX_SHUTDOWN_TINEOUT {
+        return Err(invalid_field_err!("timeout", "too big shutdown timeout"));
+    }
+
+    Ok(())
+}
+
 /// The NOW_SYSTEM_SHUTDOWN_MSG structure is used to request a system shutdown.
 ///
 /// NOW_PROTO: NOW_SYSTEM_SHUTDOWN_MSG
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "NowSystemShutdownMsgRepr<'a>")
+)]
 pub struct NowSystemShutdownMsg<'a> {
     flags: NowSystemShutdownFlags,
     /// This system shutdown timeout, in seconds.
@@ -37,6 +56,32 @@ pub struct NowSystemShutdownMsg<'a> {
     message: NowVarStr<'a>,
 }
 
+/// Deserialized fields, validated the same way as in the constructor.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(rename = "NowSystemShutdownMsg")]
+struct NowSystemShutdownMsgRepr<'a> {
+    flags: NowSystemShutdownFlags,
+    timeout: u32,
+    message: NowVarStr<'a>,
+}
+
+#[cfg(feature = "serde")]
+impl<'a> TryFrom<NowSystemShutdownMsgRepr<'a>> for NowSystemShutdownMsg<'a> {
+    type Error = ironrdp_core::EncodeError;
+
+    fn try_from(repr: NowSystemShutdownMsgRepr<'a>) -> Result<Self, Self::Error> {
+        ensure_shutdown_timeout(time::Duration::from_secs(repr.timeout.into()))?;
+        ensure_now_message_size!(Self::FIXED_PART_SIZE, repr.message.size());
+
+        Ok(Self {
+            flags: repr.flags,
+            timeout: repr.timeout,
+            message: repr.message,
+        })
+    }
+}
+
 pub type OwnedNowSystemShutdownMsg = NowSystemShutdownMsg<'static>;
 
 impl IntoOwned for NowSystemShutdownMsg<'_> {
@@ -51,17 +96,18 @@ impl IntoOwned for NowSystemShutdownMsg<'_> {
     }
 }
 
+impl CheckDecodeLimits for NowSystemShutdownMsg<'_> {
+    fn check_decode_limits(&self, checker: &mut DecodeLimitsChecker) -> DecodeResult<()> {
+        checker.string(&self.message)
+    }
+}
+
 impl<'a> NowSystemShutdownMsg<'a> {
     const NAME: &'static str = "NOW_SYSTEM_SHUTDOWN_MSG";
     const FIXED_PART_SIZE: usize = 4 /* u32 timeout */;
 
     pub fn new(timeout: time::Duration, message: impl Into<Cow<'a, str>>) -> EncodeResult<Self> {
-        // Sanity check: Limit shutdown timeout to ~1 year.
-        const MAX_SHUTDOWN_TINEOUT: time::Duration = time::Duration::from_secs(60 * 60 * 24 * 365);
-
-        if timeout > MAX_SHUTDOWN_TINEOUT {
-            return Err(invalid_field_err!("timeout", "too big shutdown timeout"));
-        }
+        ensure_shutdown_timeout(timeout)?;
 
         let timeout = u32::try_from(timeout.as_secs()).expect("timeout is within u32 range");
 
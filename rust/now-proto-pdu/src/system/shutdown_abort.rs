@@ -0,0 +1,22 @@
+use now_proto_derive::NowPdu;
+
+/// The NOW_SYSTEM_SHUTDOWN_ABORT_MSG message is used to abort a pending system shutdown or power
+/// action scheduled with a timeout. The server responds with NOW_SYSTEM_POWER_RSP_MSG.
+///
+/// NOW_PROTO: NOW_SYSTEM_SHUTDOWN_ABORT_MSG
+#[derive(Debug, Clone, PartialEq, Eq, NowPdu)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[now(class = SYSTEM, kind = SHUTDOWN_ABORT, variant = ShutdownAbort)]
+pub struct NowSystemShutdownAbortMsg {
+    request_id: u32,
+}
+
+impl NowSystemShutdownAbortMsg {
+    pub fn new(request_id: u32) -> Self {
+        Self { request_id }
+    }
+
+    pub fn request_id(&self) -> u32 {
+        self.request_id
+    }
+}
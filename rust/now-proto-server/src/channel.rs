@@ -0,0 +1,105 @@
+use now_proto_channel::NowChannelViolation;
+use now_proto_pdu::ironrdp_core::{encode_vec, DecodeError, IntoOwned};
+use now_proto_pdu::{
+    NowChannelCloseMsg, NowDecodeLimits, NowDecodeOptions, NowMessage, NowMessageFramer, NowProtoError, NowStatusError,
+    OwnedNowMessage,
+};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::NowServerError;
+
+const READ_BUFFER_SIZE: usize = 8 * 1024;
+
+/// Message-level wrapper over the byte-stream transport.
+pub(crate) struct NowChannelTransport<T> {
+    transport: T,
+    framer: NowMessageFramer,
+    read_buffer: Box<[u8]>,
+}
+
+impl<T> NowChannelTransport<T>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    pub(crate) fn new(transport: T, decode_options: NowDecodeOptions) -> Self {
+        Self {
+            transport,
+            framer: NowMessageFramer::new().with_decode_options(decode_options),
+            read_buffer: vec![0u8; READ_BUFFER_SIZE].into_boxed_slice(),
+        }
+    }
+
+    /// Reads the next message from the transport. Returns `None` if the transport is closed.
+    ///
+    /// Messages which could not be decoded are skipped, unless decode limits are exceeded: in this
+    /// case the channel is closed with `NowProtoError::InvalidRequest`. This method is cancel safe.
+    pub(crate) async fn read_message(&mut self) -> Result<Option<OwnedNowMessage>, NowServerError> {
+        loop {
+            let error = match self.framer.next_message() {
+                Ok(Some(message)) => return Ok(Some(message.into_owned())),
+                Ok(None) => None,
+                Err(error) => Some(error),
+            };
+
+            if let Some(error) = error {
+                if NowDecodeLimits::is_limit_exceeded(&error) {
+                    return Err(self.close_with_exceeded_limit(error).await);
+                }
+
+                tracing::debug!(%error, "Skipping undecodable NOW-PROTO message");
+                continue;
+            }
+
+            let read = self.transport.read(&mut self.read_buffer).await?;
+            if read == 0 {
+                return Ok(None);
+            }
+
+            if let Err(error) = self.framer.push(&self.read_buffer[..read]) {
+                if NowDecodeLimits::is_limit_exceeded(&error) {
+                    return Err(self.close_with_exceeded_limit(error).await);
+                }
+
+                return Err(error.into());
+            }
+        }
+    }
+
+    pub(crate) async fn write_message(&mut self, message: &NowMessage<'_>) -> Result<(), NowServerError> {
+        let encoded = encode_vec(message)?;
+        self.transport.write_all(&encoded).await?;
+        self.transport.flush().await?;
+
+        Ok(())
+    }
+
+    /// Closes the channel reporting the protocol violation to the peer. Write errors are only
+    /// logged, as the violation is more relevant to the caller.
+    pub(crate) async fn close_with_violation(&mut self, violation: &NowChannelViolation) {
+        tracing::warn!(%violation, "Closing NOW-PROTO channel due to protocol violation");
+
+        let message = violation
+            .to_close_msg()
+            .expect("status without message is always encodable");
+
+        if let Err(error) = self.write_message(&message.into()).await {
+            tracing::debug!(%error, "Failed to send channel close message");
+        }
+    }
+
+    /// Closes the channel with `NowProtoError::InvalidRequest` after the client has sent a message
+    /// exceeding decode limits. Write errors are only logged, as the decoding error is more relevant
+    /// to the caller.
+    async fn close_with_exceeded_limit(&mut self, error: DecodeError) -> NowServerError {
+        tracing::warn!(%error, "Closing NOW-PROTO channel due to exceeded decode limits");
+
+        let message = NowChannelCloseMsg::from_error(NowStatusError::new_proto(NowProtoError::InvalidRequest))
+            .expect("status without message is always encodable");
+
+        if let Err(error) = self.write_message(&message.into()).await {
+            tracing::debug!(%error, "Failed to send channel close message");
+        }
+
+        error.into()
+    }
+}
@@ -0,0 +1,117 @@
+use core::fmt;
+use std::io;
+
+use now_proto_channel::NowChannelViolation;
+use now_proto_pdu::ironrdp_core::{DecodeError, EncodeError};
+use now_proto_pdu::{NowProtoError, NowStatusError};
+
+/// Error returned by [`NowServer`](crate::NowServer) operations.
+#[derive(Debug)]
+pub enum NowServerError {
+    /// Underlying transport error.
+    Io(io::Error),
+    /// Received message can't be decoded.
+    Decode(DecodeError),
+    /// Message can't be encoded.
+    Encode(EncodeError),
+    /// Client has closed the channel with an error status.
+    Status(NowStatusError),
+    /// Client has violated the protocol (e.g. sent a request before negotiation or not covered by
+    /// negotiated capabilities), the channel has been closed by the server.
+    Protocol(NowChannelViolation),
+    /// Client has not sent capabilities in time.
+    Timeout,
+    /// NOW-PROTO channel is closed.
+    ChannelClosed,
+}
+
+impl fmt::Display for NowServerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NowServerError::Io(_) => write!(f, "NOW-PROTO transport error"),
+            NowServerError::Decode(_) => write!(f, "failed to decode NOW-PROTO message"),
+            NowServerError::Encode(_) => write!(f, "failed to encode NOW-PROTO message"),
+            NowServerError::Status(_) => write!(f, "client closed the channel with an error"),
+            NowServerError::Protocol(_) => write!(f, "NOW-PROTO protocol violation"),
+            NowServerError::Timeout => write!(f, "operation timed out"),
+            NowServerError::ChannelClosed => write!(f, "NOW-PROTO channel is closed"),
+        }
+    }
+}
+
+impl core::error::Error for NowServerError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            NowServerError::Io(err) => Some(err),
+            NowServerError::Decode(err) => Some(err),
+            NowServerError::Encode(err) => Some(err),
+            NowServerError::Status(err) => Some(err),
+            NowServerError::Protocol(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for NowServerError {
+    fn from(err: io::Error) -> Self {
+        NowServerError::Io(err)
+    }
+}
+
+impl From<DecodeError> for NowServerError {
+    fn from(err: DecodeError) -> Self {
+        NowServerError::Decode(err)
+    }
+}
+
+impl From<EncodeError> for NowServerError {
+    fn from(err: EncodeError) -> Self {
+        NowServerError::Encode(err)
+    }
+}
+
+impl From<NowChannelViolation> for NowServerError {
+    fn from(err: NowChannelViolation) -> Self {
+        NowServerError::Protocol(err)
+    }
+}
+
+impl From<NowStatusError> for NowServerError {
+    fn from(err: NowStatusError) -> Self {
+        NowServerError::Status(err)
+    }
+}
+
+/// Maps I/O error returned by the OS to the `NOW_STATUS` error sent to the client.
+///
+/// OS error codes are reported as `NOW_STATUS_ERROR_KIND_WINAPI` on Windows and
+/// `NOW_STATUS_ERROR_KIND_UNIX` on other platforms. Errors without an OS error code are mapped to
+/// the closest `NowProtoError`. The error description is sent as the status message, unless it is
+/// too long to be encoded.
+pub fn now_status_from_io_error(error: &io::Error) -> NowStatusError {
+    let status = status_from_io_error_kind(error);
+
+    status.clone().with_message(error.to_string()).unwrap_or(status)
+}
+
+fn status_from_io_error_kind(error: &io::Error) -> NowStatusError {
+    if let Some(code) = error.raw_os_error().and_then(|code| u32::try_from(code).ok()) {
+        return if cfg!(windows) {
+            NowStatusError::new_winapi(code)
+        } else {
+            NowStatusError::new_unix(code)
+        };
+    }
+
+    let error = match error.kind() {
+        io::ErrorKind::NotFound => NowProtoError::NotFound,
+        io::ErrorKind::PermissionDenied => NowProtoError::AccessDenied,
+        io::ErrorKind::AlreadyExists | io::ErrorKind::AddrInUse => NowProtoError::InUse,
+        io::ErrorKind::InvalidInput | io::ErrorKind::InvalidData => NowProtoError::InvalidRequest,
+        io::ErrorKind::Unsupported => NowProtoError::NotImplemented,
+        io::ErrorKind::Interrupted => NowProtoError::Aborted,
+        _ => NowProtoError::Internal,
+    };
+
+    NowStatusError::new_proto(error)
+}
@@ -0,0 +1,92 @@
+use core::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use now_proto_pdu::{NowExecDataMsg, NowExecDataStreamKind, NowExecStartedMsg, NowMessage};
+use tokio::sync::mpsc;
+
+use crate::{NowMessageSender, NowServerError};
+
+/// Client request received for the running execution session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NowExecSessionEvent {
+    /// Data for the process stdin. `last` is set when the client has closed the stdin.
+    Stdin { data: Vec<u8>, last: bool },
+    /// Client requested graceful session cancellation. Cancellation request is acknowledged by
+    /// the server automatically, the handler is expected to terminate the process and return.
+    Cancel,
+    /// Client aborted the session, the handler is expected to kill the process immediately.
+    /// Result is not sent to the client for aborted sessions.
+    Abort { exit_code: u32 },
+}
+
+/// Execution session context passed to [`NowServerHandler`](crate::NowServerHandler) exec
+/// callbacks.
+///
+/// `NOW_EXEC_STARTED_MSG` is sent automatically before the first output data if the handler has
+/// not called [`NowExecContext::started`] explicitly. `NOW_EXEC_RESULT_MSG` is sent by the server
+/// when the exec callback returns.
+#[derive(Debug)]
+pub struct NowExecContext {
+    session_id: u32,
+    sender: NowMessageSender,
+    events: mpsc::UnboundedReceiver<NowExecSessionEvent>,
+    started: Arc<AtomicBool>,
+}
+
+impl NowExecContext {
+    pub(crate) fn new(
+        session_id: u32,
+        sender: NowMessageSender,
+        events: mpsc::UnboundedReceiver<NowExecSessionEvent>,
+    ) -> Self {
+        Self {
+            session_id,
+            sender,
+            events,
+            started: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn session_id(&self) -> u32 {
+        self.session_id
+    }
+
+    /// Notifies the client that the process has been started. Subsequent calls are no-op.
+    pub async fn started(&self) -> Result<(), NowServerError> {
+        if self.started.swap(true, Ordering::AcqRel) {
+            return Ok(());
+        }
+
+        self.sender.send(NowExecStartedMsg::new(self.session_id).into()).await
+    }
+
+    /// Shared flag set once `NOW_EXEC_STARTED_MSG` has been sent.
+    pub(crate) fn started_flag(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.started)
+    }
+
+    /// Sends process stdout data. `last` should be set when stdout is closed.
+    pub async fn send_stdout(&self, data: &[u8], last: bool) -> Result<(), NowServerError> {
+        self.send_output(NowExecDataStreamKind::Stdout, data, last).await
+    }
+
+    /// Sends process stderr data. `last` should be set when stderr is closed.
+    pub async fn send_stderr(&self, data: &[u8], last: bool) -> Result<(), NowServerError> {
+        self.send_output(NowExecDataStreamKind::Stderr, data, last).await
+    }
+
+    /// Waits for the next client request for this session.
+    ///
+    /// Returns `None` if the channel has been closed.
+    pub async fn next_event(&mut self) -> Option<NowExecSessionEvent> {
+        self.events.recv().await
+    }
+
+    async fn send_output(&self, stream: NowExecDataStreamKind, data: &[u8], last: bool) -> Result<(), NowServerError> {
+        self.started().await?;
+
+        let message = NowExecDataMsg::new(self.session_id, stream, last, data)?;
+
+        self.sender.send(NowMessage::from(message)).await
+    }
+}
@@ -0,0 +1,168 @@
+use core::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use now_proto_pdu::{NowFileChunkMsg, NowFileOpenMode, NowFileOpenRspMsg, NowMessage, OwnedNowFileDirEntryMsg};
+use tokio::sync::mpsc;
+
+use crate::{NowMessageSender, NowServerError};
+
+/// Page of directory entries returned by
+/// [`NowServerHandler::file_list_dir`](crate::NowServerHandler::file_list_dir).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NowFileDirListing {
+    entries: Vec<OwnedNowFileDirEntryMsg>,
+    more: bool,
+}
+
+impl NowFileDirListing {
+    pub fn new(entries: Vec<OwnedNowFileDirEntryMsg>) -> Self {
+        Self { entries, more: false }
+    }
+
+    /// Reports that more entries are available after this page.
+    #[must_use]
+    pub fn with_more(mut self) -> Self {
+        self.more = true;
+        self
+    }
+
+    pub fn entries(&self) -> &[OwnedNowFileDirEntryMsg] {
+        &self.entries
+    }
+
+    /// Returns `true` if more entries are available after this page.
+    pub fn has_more(&self) -> bool {
+        self.more
+    }
+
+    pub(crate) fn into_parts(self) -> (Vec<OwnedNowFileDirEntryMsg>, bool) {
+        (self.entries, self.more)
+    }
+}
+
+/// Client request received for the open file transfer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum FileTransferEvent {
+    /// File data sent by the client for write transfers.
+    Chunk(Vec<u8>),
+    /// Client has closed the transfer.
+    Close,
+}
+
+/// File transfer context passed to [`NowServerHandler`](crate::NowServerHandler) file callbacks.
+///
+/// The handler opens the file and calls [`NowFileTransferContext::accept`] to send
+/// `NOW_FILE_OPEN_RSP_MSG`; if the callback fails before that, the error is sent as the open
+/// response status instead. SHA-256 digest of the transferred data is computed and verified by
+/// the server, and `NOW_FILE_CLOSE_RSP_MSG` is sent once the callback has returned and the client
+/// has closed the transfer.
+#[derive(Debug)]
+pub struct NowFileTransferContext {
+    transfer_id: u32,
+    mode: NowFileOpenMode,
+    offset: u64,
+    sender: NowMessageSender,
+    events: mpsc::UnboundedReceiver<FileTransferEvent>,
+    accepted: Arc<AtomicBool>,
+    closed: bool,
+}
+
+impl NowFileTransferContext {
+    pub(crate) fn new(
+        transfer_id: u32,
+        mode: NowFileOpenMode,
+        offset: u64,
+        sender: NowMessageSender,
+        events: mpsc::UnboundedReceiver<FileTransferEvent>,
+    ) -> Self {
+        Self {
+            transfer_id,
+            mode,
+            offset,
+            sender,
+            events,
+            accepted: Arc::new(AtomicBool::new(false)),
+            closed: false,
+        }
+    }
+
+    pub fn transfer_id(&self) -> u32 {
+        self.transfer_id
+    }
+
+    /// Offset of the next transferred chunk.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Notifies the client that the file has been opened. `file_size` is the size of the file
+    /// for read transfers, and the offset at which received data is written for write transfers.
+    pub async fn accept(&mut self, file_size: u64) -> Result<(), NowServerError> {
+        if self.accepted.swap(true, Ordering::AcqRel) {
+            return Ok(());
+        }
+
+        // Resumed write transfers start at the offset chosen by the handler.
+        if self.mode == NowFileOpenMode::Write {
+            self.offset = file_size;
+        }
+
+        self.sender
+            .send(NowFileOpenRspMsg::new_success(self.transfer_id, file_size).into())
+            .await
+    }
+
+    /// Shared flag set once `NOW_FILE_OPEN_RSP_MSG` has been sent.
+    pub(crate) fn accepted_flag(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.accepted)
+    }
+
+    /// Sends the next chunk of a read transfer. `last` should be set for the final chunk (which
+    /// could be empty). Chunks sent after the client has closed the transfer are dropped.
+    pub async fn send_chunk(&mut self, data: &[u8], last: bool) -> Result<(), NowServerError> {
+        let message = NowFileChunkMsg::new(self.transfer_id, self.offset, last, data)?;
+
+        self.advance(data.len());
+
+        self.sender.send(NowMessage::from(message)).await
+    }
+
+    /// Waits for the next chunk of a write transfer.
+    ///
+    /// Returns `None` once the client has closed the transfer, or if the channel has been closed.
+    pub async fn next_chunk(&mut self) -> Option<Vec<u8>> {
+        if self.closed {
+            return None;
+        }
+
+        match self.events.recv().await {
+            Some(FileTransferEvent::Chunk(data)) => {
+                self.advance(data.len());
+                Some(data)
+            }
+            Some(FileTransferEvent::Close) | None => {
+                self.closed = true;
+                None
+            }
+        }
+    }
+
+    /// Returns `true` if the client has closed (or cancelled) the transfer. Read transfer
+    /// handlers should stop sending chunks once the transfer is closed.
+    pub fn is_closed(&mut self) -> bool {
+        while !self.closed {
+            match self.events.try_recv() {
+                Ok(FileTransferEvent::Chunk(_)) => {}
+                Ok(FileTransferEvent::Close) | Err(mpsc::error::TryRecvError::Disconnected) => self.closed = true,
+                Err(mpsc::error::TryRecvError::Empty) => break,
+            }
+        }
+
+        self.closed
+    }
+
+    fn advance(&mut self, len: usize) {
+        let len = u64::try_from(len).expect("usize fits into u64");
+        self.offset = self.offset.saturating_add(len);
+    }
+}
@@ -0,0 +1,282 @@
+use core::future::Future;
+
+use now_proto_pdu::{
+    NowExtensionMsg, NowMsgBoxResponse, NowProtoError, NowRdmAppStartMsg, NowRdmSessionActionMsg,
+    NowSessionWindowRecStartMsg, NowStatusError, NowSystemInfoReqMsg, NowSystemProcessListReqMsg,
+    NowSystemProcessTerminateMsg, OwnedNowExecBatchMsg, OwnedNowExecProcessMsg, OwnedNowExecPwshMsg,
+    OwnedNowExecRunMsg, OwnedNowExecShellMsg, OwnedNowExecWinPsMsg, OwnedNowFileDeleteMsg, OwnedNowFileListDirReqMsg,
+    OwnedNowFileMkdirMsg, OwnedNowFileOpenMsg, OwnedNowFileRenameMsg, OwnedNowFileStatReqMsg, OwnedNowFileStatRspMsg,
+    OwnedNowRdmAppActionMsg, OwnedNowRdmCapabilitiesMsg, OwnedNowRdmSessionStartMsg, OwnedNowSessionMsgBoxReqMsg,
+    OwnedNowSessionSetKbdLayoutMsg, OwnedNowSystemInfoRspMsg, OwnedNowSystemPowerActionMsg,
+    OwnedNowSystemProcessInfoMsg, OwnedNowSystemShutdownMsg,
+};
+
+use crate::{NowExecContext, NowFileDirListing, NowFileTransferContext, NowMessageSender};
+
+/// Result of the [`NowServerHandler`] callback. Error is sent back to the client as `NOW_STATUS`
+/// when the request has a response message, and logged otherwise.
+pub type NowHandlerResult<T> = Result<T, NowStatusError>;
+
+fn not_implemented<T>() -> NowHandlerResult<T> {
+    Err(NowStatusError::new_proto(NowProtoError::NotImplemented))
+}
+
+/// Host-side implementation of NOW-PROTO requests, driven by [`NowServer`](crate::NowServer).
+///
+/// Each callback is invoked in its own task, concurrently with other requests. Callbacks which are
+/// not overridden fail with `NowProtoError::NotImplemented`. Callbacks are only invoked for
+/// requests covered by negotiated capabilities.
+pub trait NowServerHandler: Send + Sync + 'static {
+    // -- System --
+
+    /// Collects the system information. Returned response should be created with the request ID;
+    /// errors are sent back to the client as the response status.
+    fn system_info(
+        &self,
+        _request: NowSystemInfoReqMsg,
+    ) -> impl Future<Output = NowHandlerResult<OwnedNowSystemInfoRspMsg>> + Send {
+        async { not_implemented() }
+    }
+
+    fn system_shutdown(
+        &self,
+        _request: OwnedNowSystemShutdownMsg,
+    ) -> impl Future<Output = NowHandlerResult<()>> + Send {
+        async { not_implemented() }
+    }
+
+    /// Aborts pending shutdown or power action. Result is sent back to the client.
+    fn system_shutdown_abort(&self) -> impl Future<Output = NowHandlerResult<()>> + Send {
+        async { not_implemented() }
+    }
+
+    /// Schedules the power action. Result is sent back to the client as soon as the callback
+    /// returns, so the callback should not wait for the action to be performed.
+    fn system_power_action(
+        &self,
+        _request: OwnedNowSystemPowerActionMsg,
+    ) -> impl Future<Output = NowHandlerResult<()>> + Send {
+        async { not_implemented() }
+    }
+
+    /// Enumerates running processes. Returned entries should be created with the request ID, and
+    /// are sent to the client before the final NOW_SYSTEM_PROCESS_LIST_RSP_MSG.
+    fn system_process_list(
+        &self,
+        _request: NowSystemProcessListReqMsg,
+    ) -> impl Future<Output = NowHandlerResult<Vec<OwnedNowSystemProcessInfoMsg>>> + Send {
+        async { not_implemented() }
+    }
+
+    /// Terminates the process. Expected to fail with `NowProtoError::NotFound` if the process
+    /// does not exist, and with `NowProtoError::AccessDenied` if it could not be terminated.
+    fn system_process_terminate(
+        &self,
+        _request: NowSystemProcessTerminateMsg,
+    ) -> impl Future<Output = NowHandlerResult<()>> + Send {
+        async { not_implemented() }
+    }
+
+    // -- Session --
+
+    fn session_lock(&self) -> impl Future<Output = NowHandlerResult<()>> + Send {
+        async { not_implemented() }
+    }
+
+    fn session_logoff(&self) -> impl Future<Output = NowHandlerResult<()>> + Send {
+        async { not_implemented() }
+    }
+
+    /// Shows message box. Returned response is only sent to the client if the request has
+    /// `NOW_MSGBOX_FLAG_RESPONSE` set.
+    fn session_msg_box(
+        &self,
+        _request: OwnedNowSessionMsgBoxReqMsg,
+    ) -> impl Future<Output = NowHandlerResult<NowMsgBoxResponse>> + Send {
+        async { not_implemented() }
+    }
+
+    fn session_set_kbd_layout(
+        &self,
+        _request: OwnedNowSessionSetKbdLayoutMsg,
+    ) -> impl Future<Output = NowHandlerResult<()>> + Send {
+        async { not_implemented() }
+    }
+
+    /// Starts window recording. Window recording events should be sent with `events`.
+    fn session_window_rec_start(
+        &self,
+        _request: NowSessionWindowRecStartMsg,
+        _events: NowMessageSender,
+    ) -> impl Future<Output = NowHandlerResult<()>> + Send {
+        async { not_implemented() }
+    }
+
+    fn session_window_rec_stop(&self) -> impl Future<Output = NowHandlerResult<()>> + Send {
+        async { not_implemented() }
+    }
+
+    // -- Exec --
+
+    /// Starts the program with "Run" execution style. Output and result are not reported to the
+    /// client for this execution style.
+    fn exec_run(&self, _request: OwnedNowExecRunMsg) -> impl Future<Output = NowHandlerResult<()>> + Send {
+        async { not_implemented() }
+    }
+
+    /// Runs the process session until completion and returns the process exit code.
+    fn exec_process(
+        &self,
+        _request: OwnedNowExecProcessMsg,
+        _session: NowExecContext,
+    ) -> impl Future<Output = NowHandlerResult<u32>> + Send {
+        async { not_implemented() }
+    }
+
+    /// Runs the shell session until completion and returns the process exit code.
+    fn exec_shell(
+        &self,
+        _request: OwnedNowExecShellMsg,
+        _session: NowExecContext,
+    ) -> impl Future<Output = NowHandlerResult<u32>> + Send {
+        async { not_implemented() }
+    }
+
+    /// Runs the batch session until completion and returns the process exit code.
+    fn exec_batch(
+        &self,
+        _request: OwnedNowExecBatchMsg,
+        _session: NowExecContext,
+    ) -> impl Future<Output = NowHandlerResult<u32>> + Send {
+        async { not_implemented() }
+    }
+
+    /// Runs the Windows PowerShell session until completion and returns the process exit code.
+    fn exec_winps(
+        &self,
+        _request: OwnedNowExecWinPsMsg,
+        _session: NowExecContext,
+    ) -> impl Future<Output = NowHandlerResult<u32>> + Send {
+        async { not_implemented() }
+    }
+
+    /// Runs the PowerShell 7 session until completion and returns the process exit code.
+    fn exec_pwsh(
+        &self,
+        _request: OwnedNowExecPwshMsg,
+        _session: NowExecContext,
+    ) -> impl Future<Output = NowHandlerResult<u32>> + Send {
+        async { not_implemented() }
+    }
+
+    // -- RDM --
+
+    /// Returns host RDM capabilities. On error, capabilities with RDM application marked as not
+    /// available are sent to the client.
+    fn rdm_capabilities(
+        &self,
+        _request: OwnedNowRdmCapabilitiesMsg,
+    ) -> impl Future<Output = NowHandlerResult<OwnedNowRdmCapabilitiesMsg>> + Send {
+        async { not_implemented() }
+    }
+
+    /// Starts RDM application. Application state notifications should be sent with `notifier`.
+    fn rdm_app_start(
+        &self,
+        _request: NowRdmAppStartMsg,
+        _notifier: NowMessageSender,
+    ) -> impl Future<Output = NowHandlerResult<()>> + Send {
+        async { not_implemented() }
+    }
+
+    fn rdm_app_action(&self, _request: OwnedNowRdmAppActionMsg) -> impl Future<Output = NowHandlerResult<()>> + Send {
+        async { not_implemented() }
+    }
+
+    /// Starts RDM session. Session notifications should be sent with `notifier`.
+    fn rdm_session_start(
+        &self,
+        _request: OwnedNowRdmSessionStartMsg,
+        _notifier: NowMessageSender,
+    ) -> impl Future<Output = NowHandlerResult<()>> + Send {
+        async { not_implemented() }
+    }
+
+    fn rdm_session_action(
+        &self,
+        _request: NowRdmSessionActionMsg,
+    ) -> impl Future<Output = NowHandlerResult<()>> + Send {
+        async { not_implemented() }
+    }
+
+    // -- File --
+
+    /// Sends the file content starting at the requested offset. The file size should be reported
+    /// with [`NowFileTransferContext::accept`] once the file is opened, followed by chunks up to
+    /// the end of the file, or until the client closes the transfer.
+    fn file_read(
+        &self,
+        _request: OwnedNowFileOpenMsg,
+        _transfer: NowFileTransferContext,
+    ) -> impl Future<Output = NowHandlerResult<()>> + Send {
+        async { not_implemented() }
+    }
+
+    /// Writes the file content received from the client until the client closes the transfer.
+    /// The file should be truncated to the requested offset (or kept as is for resumed
+    /// transfers), and the resulting offset reported with [`NowFileTransferContext::accept`].
+    fn file_write(
+        &self,
+        _request: OwnedNowFileOpenMsg,
+        _transfer: NowFileTransferContext,
+    ) -> impl Future<Output = NowHandlerResult<()>> + Send {
+        async { not_implemented() }
+    }
+
+    /// Queries information about the filesystem entry; symbolic links should not be followed.
+    /// Returned response should be created with the request ID.
+    fn file_stat(
+        &self,
+        _request: OwnedNowFileStatReqMsg,
+    ) -> impl Future<Output = NowHandlerResult<OwnedNowFileStatRspMsg>> + Send {
+        async { not_implemented() }
+    }
+
+    /// Lists the page of directory entries requested with `start_index` and `max_entries`, in a
+    /// stable order (e.g. sorted by name) so that pages do not overlap. Entries should be created
+    /// with the request ID, and are sent before the final NOW_FILE_LIST_DIR_RSP_MSG; the listing
+    /// reports whether more entries are available after the page.
+    fn file_list_dir(
+        &self,
+        _request: OwnedNowFileListDirReqMsg,
+    ) -> impl Future<Output = NowHandlerResult<NowFileDirListing>> + Send {
+        async { not_implemented() }
+    }
+
+    fn file_mkdir(&self, _request: OwnedNowFileMkdirMsg) -> impl Future<Output = NowHandlerResult<()>> + Send {
+        async { not_implemented() }
+    }
+
+    /// Deletes the file or the directory. Expected to fail with `NowProtoError::NotFound` if the
+    /// entry does not exist.
+    fn file_delete(&self, _request: OwnedNowFileDeleteMsg) -> impl Future<Output = NowHandlerResult<()>> + Send {
+        async { not_implemented() }
+    }
+
+    fn file_rename(&self, _request: OwnedNowFileRenameMsg) -> impl Future<Output = NowHandlerResult<()>> + Send {
+        async { not_implemented() }
+    }
+
+    // -- Extensions --
+
+    /// Handles message of the vendor extension class registered with
+    /// [`NowServer::with_extensions`](crate::NowServer::with_extensions). Responses (if any)
+    /// should be sent with `sender`.
+    fn extension_message(
+        &self,
+        _message: NowExtensionMsg,
+        _sender: NowMessageSender,
+    ) -> impl Future<Output = NowHandlerResult<()>> + Send {
+        async { not_implemented() }
+    }
+}
@@ -0,0 +1,19 @@
+#![doc = include_str!("../README.md")]
+#![doc(
+    html_logo_url = "https://webdevolutions.blob.core.windows.net/images/projects/devolutions/logos/devolutions-icon-shadow.svg"
+)]
+
+mod channel;
+mod error;
+mod exec;
+mod file;
+mod handler;
+mod sender;
+mod server;
+
+pub use error::*;
+pub use exec::*;
+pub use file::*;
+pub use handler::*;
+pub use sender::*;
+pub use server::*;
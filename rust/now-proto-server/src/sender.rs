@@ -0,0 +1,31 @@
+use now_proto_pdu::ironrdp_core::IntoOwned;
+use now_proto_pdu::{NowMessage, OwnedNowMessage};
+use tokio::sync::mpsc;
+
+use crate::NowServerError;
+
+/// Cloneable handle used by handlers to send unsolicited messages to the client (e.g. window
+/// recording events or RDM notifications).
+#[derive(Debug, Clone)]
+pub struct NowMessageSender(mpsc::Sender<OwnedNowMessage>);
+
+impl NowMessageSender {
+    pub(crate) fn new(sender: mpsc::Sender<OwnedNowMessage>) -> Self {
+        Self(sender)
+    }
+
+    /// Queues message to be sent to the client.
+    ///
+    /// Returns [`NowServerError::ChannelClosed`] if the channel has been closed.
+    pub async fn send(&self, message: NowMessage<'_>) -> Result<(), NowServerError> {
+        self.0
+            .send(message.into_owned())
+            .await
+            .map_err(|_| NowServerError::ChannelClosed)
+    }
+
+    /// Returns `true` if the channel has been closed.
+    pub fn is_closed(&self) -> bool {
+        self.0.is_closed()
+    }
+}
@@ -0,0 +1,969 @@
+use core::future::{self, Future};
+use core::sync::atomic::Ordering;
+use core::time::Duration;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use now_proto_channel::{
+    NowChannelRole, NowChannelState, NowExecSessionError, NowExecSessionTable, NowHeartbeatEvent,
+    NowHeartbeatSupervisor,
+};
+use now_proto_pdu::ironrdp_core::EncodeResult;
+use now_proto_pdu::{
+    NowChannelCapsetMsg, NowChannelHeartbeatMsg, NowChannelMessage, NowDecodeLimits, NowDecodeOptions,
+    NowExecCancelRspMsg, NowExecMessage, NowExecResultMsg, NowExecStartedMsg, NowExtensionRegistry, NowFileCloseMsg,
+    NowFileCloseRspMsg, NowFileListDirRspMsg, NowFileMessage, NowFileOpRspMsg, NowFileOpenMode, NowFileOpenRspMsg,
+    NowFileStatRspMsg, NowMessage, NowProtoError, NowRdmCapabilitiesMsg, NowRdmMessage, NowSessionMessage,
+    NowSessionMsgBoxRspMsg, NowStatusError, NowSystemInfoRspMsg, NowSystemMessage, NowSystemPowerRspMsg,
+    NowSystemProcessListRspMsg, NowSystemProcessTerminateRspMsg, OwnedNowFileOpenMsg, OwnedNowMessage,
+    NOW_FILE_SHA256_SIZE,
+};
+use sha2::{Digest as _, Sha256};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinSet;
+use tokio::time;
+
+use crate::channel::NowChannelTransport;
+use crate::file::FileTransferEvent;
+use crate::{
+    NowExecContext, NowExecSessionEvent, NowFileTransferContext, NowHandlerResult, NowMessageSender, NowServerError,
+    NowServerHandler,
+};
+
+/// Handler -> server outgoing message queue capacity.
+const OUTGOING_CHANNEL_CAPACITY: usize = 1024;
+
+/// NOW-PROTO channel server, dispatching client requests to the [`NowServerHandler`].
+///
+/// A single server instance could serve multiple channels, the handler is shared between them.
+#[derive(Debug)]
+pub struct NowServer<H> {
+    capabilities: NowChannelCapsetMsg,
+    negotiation_timeout: Duration,
+    decode_options: NowDecodeOptions,
+    handler: Arc<H>,
+}
+
+impl<H: NowServerHandler> NowServer<H> {
+    /// Creates server with the given host capabilities. Capabilities are downgraded to the
+    /// common subset with client capabilities during negotiation. Heartbeat interval specified
+    /// in `capabilities` is used as a maximum interval between server heartbeats.
+    pub fn new(capabilities: NowChannelCapsetMsg, handler: H) -> Self {
+        Self {
+            capabilities,
+            negotiation_timeout: Duration::from_secs(10),
+            decode_options: NowDecodeOptions::new(),
+            handler: Arc::new(handler),
+        }
+    }
+
+    /// Sets timeout for the client capabilities message.
+    #[must_use]
+    pub fn with_negotiation_timeout(mut self, timeout: Duration) -> Self {
+        self.negotiation_timeout = timeout;
+        self
+    }
+
+    /// Sets limits applied to messages received from the client. Messages exceeding the limits
+    /// close the channel with `NowProtoError::InvalidRequest`.
+    #[must_use]
+    pub fn with_decode_limits(mut self, decode_limits: NowDecodeLimits) -> Self {
+        self.decode_options = self.decode_options.with_limits(decode_limits);
+        self
+    }
+
+    /// Sets vendor extension message classes accepted from the client. Extension messages are
+    /// dispatched to [`NowServerHandler::extension_message`].
+    #[must_use]
+    pub fn with_extensions(mut self, extensions: &'static NowExtensionRegistry) -> Self {
+        self.decode_options = self.decode_options.with_extensions(extensions);
+        self
+    }
+
+    pub fn handler(&self) -> &H {
+        &self.handler
+    }
+
+    /// Serves a single NOW-PROTO channel over the provided transport until it is closed by the
+    /// client.
+    ///
+    /// Handler callbacks are spawned on the current tokio runtime, in-flight callbacks are aborted
+    /// when this method returns.
+    pub async fn serve<T>(&self, transport: T) -> Result<(), NowServerError>
+    where
+        T: AsyncRead + AsyncWrite + Unpin,
+    {
+        let mut channel = NowChannelTransport::new(transport, self.decode_options);
+
+        let state = time::timeout(self.negotiation_timeout, self.negotiate(&mut channel))
+            .await
+            .map_err(|_| NowServerError::Timeout)??;
+
+        tracing::debug!(capabilities = ?state.negotiated_capabilities(), "NOW channel negotiation complete");
+
+        let (outgoing_tx, outgoing_rx) = mpsc::channel(OUTGOING_CHANNEL_CAPACITY);
+
+        let connection = Connection {
+            channel,
+            state,
+            handler: Arc::clone(&self.handler),
+            sender: NowMessageSender::new(outgoing_tx),
+            outgoing: outgoing_rx,
+            exec_table: NowExecSessionTable::new(),
+            exec_sessions: HashMap::new(),
+            file_transfers: HashMap::new(),
+            tasks: JoinSet::new(),
+        };
+
+        connection.run().await
+    }
+
+    async fn negotiate<T>(&self, channel: &mut NowChannelTransport<T>) -> Result<NowChannelState, NowServerError>
+    where
+        T: AsyncRead + AsyncWrite + Unpin,
+    {
+        let mut state = NowChannelState::new(NowChannelRole::Server, self.capabilities.version());
+
+        let message = channel.read_message().await?.ok_or(NowServerError::ChannelClosed)?;
+
+        if let Err(violation) = state.on_incoming(&message) {
+            channel.close_with_violation(&violation).await;
+            return Err(violation.into());
+        }
+
+        let client_capabilities = match message {
+            NowMessage::Channel(NowChannelMessage::Capset(capabilities)) => capabilities,
+            // Only channel close message could be received before client capabilities.
+            _ => return Err(NowServerError::ChannelClosed),
+        };
+
+        let capabilities: NowMessage<'_> = self.capabilities.downgrade(&client_capabilities).into();
+
+        state.on_outgoing(&capabilities)?;
+        channel.write_message(&capabilities).await?;
+
+        Ok(state)
+    }
+}
+
+enum Flow {
+    Continue,
+    Exit,
+}
+
+/// State of the single negotiated channel.
+struct Connection<H, T> {
+    channel: NowChannelTransport<T>,
+    state: NowChannelState,
+    handler: Arc<H>,
+    sender: NowMessageSender,
+    outgoing: mpsc::Receiver<OwnedNowMessage>,
+    exec_table: NowExecSessionTable,
+    exec_sessions: HashMap<u32, mpsc::UnboundedSender<NowExecSessionEvent>>,
+    file_transfers: HashMap<u32, FileTransfer>,
+    tasks: JoinSet<()>,
+}
+
+/// Server-side state of the open file transfer.
+struct FileTransfer {
+    mode: NowFileOpenMode,
+    events: mpsc::UnboundedSender<FileTransferEvent>,
+    /// Digest verification result, passed to the handler task once the client closes the
+    /// transfer.
+    close: oneshot::Sender<NowHandlerResult<()>>,
+    /// Digest of the data transferred so far, in either direction.
+    sha256: Sha256,
+    /// Expected offset of the next chunk received from the client.
+    next_offset: u64,
+    /// Error detected while receiving chunks, reported when the transfer is closed.
+    error: Option<NowStatusError>,
+}
+
+impl<H, T> Connection<H, T>
+where
+    H: NowServerHandler,
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    async fn run(mut self) -> Result<(), NowServerError> {
+        let mut heartbeat = self
+            .state
+            .negotiated_capabilities()
+            .and_then(|capabilities| NowHeartbeatSupervisor::new(NowChannelRole::Server, capabilities));
+
+        loop {
+            let heartbeat_deadline = heartbeat.as_ref().and_then(NowHeartbeatSupervisor::next_deadline);
+
+            let heartbeat_tick = async {
+                match heartbeat_deadline {
+                    Some(deadline) => time::sleep_until(time::Instant::from_std(deadline)).await,
+                    None => future::pending().await,
+                }
+            };
+
+            let flow = tokio::select! {
+                message = self.channel.read_message() => match message? {
+                    Some(message) => self.handle_incoming(message).await?,
+                    // Client has disconnected without closing the channel.
+                    None => Flow::Exit,
+                },
+                Some(message) = self.outgoing.recv() => {
+                    self.handle_outgoing(message).await?;
+                    Flow::Continue
+                }
+                () = heartbeat_tick => {
+                    if let Some(NowHeartbeatEvent::SendHeartbeat) = heartbeat.as_mut().and_then(NowHeartbeatSupervisor::poll) {
+                        self.channel.write_message(&NowChannelHeartbeatMsg::default().into()).await?;
+                    }
+                    Flow::Continue
+                }
+                Some(result) = self.tasks.join_next(), if !self.tasks.is_empty() => {
+                    if let Err(error) = result {
+                        tracing::error!(%error, "NOW-PROTO request handler task failed");
+                    }
+                    Flow::Continue
+                }
+            };
+
+            if let Flow::Exit = flow {
+                return Ok(());
+            }
+        }
+    }
+
+    async fn handle_outgoing(&mut self, message: OwnedNowMessage) -> Result<(), NowServerError> {
+        if let Err(violation) = self.state.on_outgoing(&message) {
+            tracing::warn!(%violation, "Dropping invalid outgoing NOW-PROTO message");
+            return Ok(());
+        }
+
+        if let NowMessage::Exec(exec) = &message {
+            // Session could have been aborted by the client, no more messages should be sent.
+            if let Err(error) = self.exec_table.on_server_message(exec) {
+                tracing::debug!(%error, "Dropping outgoing exec message");
+                return Ok(());
+            }
+
+            // Session is terminated as soon as the result is sent.
+            if let NowExecMessage::Result(msg) = exec {
+                self.exec_sessions.remove(&msg.session_id());
+            }
+        }
+
+        if let NowMessage::File(file) = &message {
+            if !self.on_outgoing_file_message(file) {
+                return Ok(());
+            }
+        }
+
+        self.channel.write_message(&message).await
+    }
+
+    async fn handle_incoming(&mut self, message: OwnedNowMessage) -> Result<Flow, NowServerError> {
+        if let Err(violation) = self.state.on_incoming(&message) {
+            self.channel.close_with_violation(&violation).await;
+            return Err(violation.into());
+        }
+
+        match message {
+            NowMessage::Channel(NowChannelMessage::Close(msg)) => {
+                msg.to_result()?;
+                return Ok(Flow::Exit);
+            }
+            NowMessage::Channel(NowChannelMessage::Heartbeat(_)) => {}
+            NowMessage::System(msg) => self.handle_system_message(msg),
+            NowMessage::Session(msg) => self.handle_session_message(msg),
+            NowMessage::Exec(msg) => self.handle_exec_message(msg).await?,
+            NowMessage::Rdm(msg) => self.handle_rdm_message(msg),
+            NowMessage::File(msg) => self.handle_file_message(msg).await?,
+            NowMessage::Extension(msg) => {
+                let sender = self.sender.clone();
+                self.spawn_request("extension message", move |handler| async move {
+                    handler.extension_message(msg, sender).await
+                });
+            }
+            other => {
+                tracing::debug!(message = ?other, "Unexpected NOW-PROTO message");
+            }
+        }
+
+        Ok(Flow::Continue)
+    }
+
+    fn handle_system_message(&mut self, message: NowSystemMessage<'static>) {
+        match message {
+            NowSystemMessage::InfoReq(msg) => {
+                let handler = Arc::clone(&self.handler);
+                let sender = self.sender.clone();
+
+                self.tasks.spawn(async move {
+                    let request_id = msg.request_id();
+
+                    let response = match handler.system_info(msg).await {
+                        Ok(response) => response,
+                        Err(error) => match NowSystemInfoRspMsg::new_error(request_id, error) {
+                            Ok(response) => response,
+                            Err(error) => {
+                                tracing::error!(%error, "Failed to encode system info response");
+                                return;
+                            }
+                        },
+                    };
+
+                    send_or_log(&sender, response.into()).await;
+                });
+            }
+            NowSystemMessage::Shutdown(msg) => {
+                self.spawn_request("system shutdown", move |handler| async move {
+                    handler.system_shutdown(msg).await
+                });
+            }
+            NowSystemMessage::ShutdownAbort(msg) => {
+                self.spawn_status_request(
+                    |handler| async move { handler.system_shutdown_abort().await },
+                    power_response(msg.request_id()),
+                );
+            }
+            NowSystemMessage::PowerAction(msg) => {
+                let request_id = msg.request_id();
+                self.spawn_status_request(
+                    move |handler| async move { handler.system_power_action(msg).await },
+                    power_response(request_id),
+                );
+            }
+            NowSystemMessage::ProcessListReq(msg) => {
+                let handler = Arc::clone(&self.handler);
+                let sender = self.sender.clone();
+
+                self.tasks.spawn(async move {
+                    let request_id = msg.request_id();
+
+                    let response = match handler.system_process_list(msg).await {
+                        Ok(processes) => {
+                            for process in processes {
+                                send_or_log(&sender, process.into()).await;
+                            }
+
+                            NowSystemProcessListRspMsg::new_success(request_id)
+                        }
+                        Err(error) => match NowSystemProcessListRspMsg::new_error(request_id, error) {
+                            Ok(response) => response,
+                            Err(error) => {
+                                tracing::error!(%error, "Failed to encode process list response");
+                                return;
+                            }
+                        },
+                    };
+
+                    send_or_log(&sender, response.into()).await;
+                });
+            }
+            NowSystemMessage::ProcessTerminate(msg) => {
+                let request_id = msg.request_id();
+                self.spawn_status_request(
+                    move |handler| async move { handler.system_process_terminate(msg).await },
+                    move |result| match result {
+                        Ok(()) => Ok(NowSystemProcessTerminateRspMsg::new_success(request_id).into()),
+                        Err(error) => NowSystemProcessTerminateRspMsg::new_error(request_id, error).map(Into::into),
+                    },
+                );
+            }
+            other => {
+                tracing::debug!(message = ?other, "Unexpected NOW-PROTO system message");
+            }
+        }
+    }
+
+    fn handle_session_message(&mut self, message: NowSessionMessage<'static>) {
+        match message {
+            NowSessionMessage::Lock(_) => {
+                self.spawn_request("session lock", |handler| async move { handler.session_lock().await });
+            }
+            NowSessionMessage::Logoff(_) => {
+                self.spawn_request(
+                    "session logoff",
+                    |handler| async move { handler.session_logoff().await },
+                );
+            }
+            NowSessionMessage::MsgBoxReq(msg) => {
+                let request_id = msg.request_id();
+                let response_expected = msg.is_response_expected();
+
+                let handler = Arc::clone(&self.handler);
+                let sender = self.sender.clone();
+
+                self.tasks.spawn(async move {
+                    let result = handler.session_msg_box(msg).await;
+
+                    if !response_expected {
+                        log_request_result("message box", result);
+                        return;
+                    }
+
+                    let response = match result {
+                        Ok(response) => NowSessionMsgBoxRspMsg::new_success(request_id, response),
+                        Err(error) => match NowSessionMsgBoxRspMsg::new_error(request_id, error) {
+                            Ok(response) => response,
+                            Err(error) => {
+                                tracing::error!(%error, "Failed to encode message box response");
+                                return;
+                            }
+                        },
+                    };
+
+                    send_or_log(&sender, response.into()).await;
+                });
+            }
+            NowSessionMessage::SetKbdLayout(msg) => {
+                self.spawn_request("set keyboard layout", move |handler| async move {
+                    handler.session_set_kbd_layout(msg).await
+                });
+            }
+            NowSessionMessage::WindowRecStart(msg) => {
+                let events = self.sender.clone();
+                self.spawn_request("window recording start", move |handler| async move {
+                    handler.session_window_rec_start(msg, events).await
+                });
+            }
+            NowSessionMessage::WindowRecStop(_) => {
+                self.spawn_request("window recording stop", |handler| async move {
+                    handler.session_window_rec_stop().await
+                });
+            }
+            other => {
+                tracing::debug!(message = ?other, "Unexpected NOW-PROTO session message");
+            }
+        }
+    }
+
+    async fn handle_exec_message(&mut self, message: NowExecMessage<'static>) -> Result<(), NowServerError> {
+        if let Err(error) = self.exec_table.on_client_message(&message) {
+            tracing::debug!(%error, "Exec message rejected");
+            return self.reject_exec_message(&message, &error).await;
+        }
+
+        match message {
+            NowExecMessage::Run(msg) => {
+                self.spawn_request("exec run", move |handler| async move { handler.exec_run(msg).await });
+            }
+            NowExecMessage::Process(msg) => {
+                let session_id = msg.session_id();
+                self.spawn_exec(session_id, move |handler, session| async move {
+                    handler.exec_process(msg, session).await
+                })
+                .await?;
+            }
+            NowExecMessage::Shell(msg) => {
+                let session_id = msg.session_id();
+                self.spawn_exec(session_id, move |handler, session| async move {
+                    handler.exec_shell(msg, session).await
+                })
+                .await?;
+            }
+            NowExecMessage::Batch(msg) => {
+                let session_id = msg.session_id();
+                self.spawn_exec(session_id, move |handler, session| async move {
+                    handler.exec_batch(msg, session).await
+                })
+                .await?;
+            }
+            NowExecMessage::WinPs(msg) => {
+                let session_id = msg.session_id();
+                self.spawn_exec(session_id, move |handler, session| async move {
+                    handler.exec_winps(msg, session).await
+                })
+                .await?;
+            }
+            NowExecMessage::Pwsh(msg) => {
+                let session_id = msg.session_id();
+                self.spawn_exec(session_id, move |handler, session| async move {
+                    handler.exec_pwsh(msg, session).await
+                })
+                .await?;
+            }
+            NowExecMessage::Data(msg) => {
+                let event = NowExecSessionEvent::Stdin {
+                    data: msg.data().to_vec(),
+                    last: msg.is_last(),
+                };
+                self.notify_exec_session(msg.session_id(), event);
+            }
+            NowExecMessage::CancelReq(msg) => {
+                let session_id = msg.session_id();
+                self.notify_exec_session(session_id, NowExecSessionEvent::Cancel);
+
+                let response = NowExecCancelRspMsg::new_success(session_id);
+                self.channel.write_message(&response.into()).await?;
+            }
+            NowExecMessage::Abort(msg) => {
+                let session_id = msg.session_id();
+                let exit_code = msg.exit_code();
+
+                // Session is considered aborted as soon as the abort message is sent by the client.
+                if let Some(events) = self.exec_sessions.remove(&session_id) {
+                    let _ = events.send(NowExecSessionEvent::Abort { exit_code });
+                }
+            }
+            other => {
+                tracing::debug!(message = ?other, "Unexpected NOW-PROTO exec message");
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_rdm_message(&mut self, message: NowRdmMessage<'static>) {
+        match message {
+            NowRdmMessage::Capabilities(msg) => {
+                let handler = Arc::clone(&self.handler);
+                let sender = self.sender.clone();
+
+                self.tasks.spawn(async move {
+                    let response = match handler.rdm_capabilities(msg).await {
+                        Ok(response) => response,
+                        Err(error) => {
+                            tracing::debug!(%error, "RDM capabilities are not available");
+
+                            let timestamp = SystemTime::now()
+                                .duration_since(SystemTime::UNIX_EPOCH)
+                                .map(|duration| duration.as_secs())
+                                .unwrap_or_default();
+
+                            NowRdmCapabilitiesMsg::new(timestamp, "").expect("empty version is always encodable")
+                        }
+                    };
+
+                    send_or_log(&sender, NowMessage::Rdm(NowRdmMessage::Capabilities(response))).await;
+                });
+            }
+            NowRdmMessage::AppStart(msg) => {
+                let notifier = self.sender.clone();
+                self.spawn_request("RDM app start", move |handler| async move {
+                    handler.rdm_app_start(msg, notifier).await
+                });
+            }
+            NowRdmMessage::AppAction(msg) => {
+                self.spawn_request("RDM app action", move |handler| async move {
+                    handler.rdm_app_action(msg).await
+                });
+            }
+            NowRdmMessage::SessionStart(msg) => {
+                let notifier = self.sender.clone();
+                self.spawn_request("RDM session start", move |handler| async move {
+                    handler.rdm_session_start(msg, notifier).await
+                });
+            }
+            NowRdmMessage::SessionAction(msg) => {
+                self.spawn_request("RDM session action", move |handler| async move {
+                    handler.rdm_session_action(msg).await
+                });
+            }
+            other => {
+                tracing::debug!(message = ?other, "Unexpected NOW-PROTO RDM message");
+            }
+        }
+    }
+
+    async fn handle_file_message(&mut self, message: NowFileMessage<'static>) -> Result<(), NowServerError> {
+        match message {
+            NowFileMessage::Open(msg) => {
+                let transfer_id = msg.transfer_id();
+
+                let error = match msg.mode() {
+                    _ if self.file_transfers.contains_key(&transfer_id) => Some(NowProtoError::InUse),
+                    Ok(NowFileOpenMode::Read) if msg.is_resume() => Some(NowProtoError::InvalidRequest),
+                    Ok(_) => None,
+                    Err(_) => Some(NowProtoError::InvalidRequest),
+                };
+
+                if let Some(error) = error {
+                    tracing::debug!(transfer_id, ?error, "File open request rejected");
+
+                    let response = NowFileOpenRspMsg::new_error(transfer_id, error)
+                        .expect("status without message is always encodable");
+                    return self.channel.write_message(&response.into()).await;
+                }
+
+                self.spawn_file_transfer(msg);
+            }
+            NowFileMessage::Chunk(msg) => {
+                let Some(transfer) = self.file_transfers.get_mut(&msg.transfer_id()) else {
+                    tracing::debug!(
+                        transfer_id = msg.transfer_id(),
+                        "Dropping chunk of unknown file transfer"
+                    );
+                    return Ok(());
+                };
+
+                if transfer.error.is_some() {
+                    return Ok(());
+                }
+
+                if transfer.mode != NowFileOpenMode::Write || msg.offset() != transfer.next_offset {
+                    transfer.error = Some(NowStatusError::new_proto(NowProtoError::InvalidRequest));
+                    return Ok(());
+                }
+
+                let len = u64::try_from(msg.data().len()).expect("usize fits into u64");
+                transfer.next_offset = transfer.next_offset.saturating_add(len);
+                transfer.sha256.update(msg.data());
+
+                let _ = transfer.events.send(FileTransferEvent::Chunk(msg.data().to_vec()));
+            }
+            NowFileMessage::Close(msg) => {
+                let Some(transfer) = self.file_transfers.remove(&msg.transfer_id()) else {
+                    // Transfer could have been already completed with an error by the server.
+                    tracing::debug!(transfer_id = msg.transfer_id(), "Close of unknown file transfer");
+                    return Ok(());
+                };
+
+                let _ = transfer.events.send(FileTransferEvent::Close);
+                let _ = transfer
+                    .close
+                    .send(verify_file_transfer(transfer.sha256, transfer.error, &msg));
+            }
+            NowFileMessage::StatReq(msg) => {
+                let handler = Arc::clone(&self.handler);
+                let sender = self.sender.clone();
+
+                self.tasks.spawn(async move {
+                    let request_id = msg.request_id();
+
+                    let response = match handler.file_stat(msg).await {
+                        Ok(response) => response,
+                        Err(error) => match NowFileStatRspMsg::new_error(request_id, error) {
+                            Ok(response) => response,
+                            Err(error) => {
+                                tracing::error!(%error, "Failed to encode file stat response");
+                                return;
+                            }
+                        },
+                    };
+
+                    send_or_log(&sender, response.into()).await;
+                });
+            }
+            NowFileMessage::ListDirReq(msg) => {
+                let handler = Arc::clone(&self.handler);
+                let sender = self.sender.clone();
+
+                self.tasks.spawn(async move {
+                    let request_id = msg.request_id();
+                    let max_entries = msg
+                        .max_entries()
+                        .map_or(usize::MAX, |max| usize::try_from(max).unwrap_or(usize::MAX));
+
+                    let response = match handler.file_list_dir(msg).await {
+                        Ok(listing) => {
+                            let (mut entries, mut more) = listing.into_parts();
+
+                            // Pages larger than requested are truncated instead of being sent.
+                            if entries.len() > max_entries {
+                                entries.truncate(max_entries);
+                                more = true;
+                            }
+
+                            for entry in entries {
+                                send_or_log(&sender, entry.into()).await;
+                            }
+
+                            let response = NowFileListDirRspMsg::new_success(request_id);
+
+                            if more {
+                                response.with_more()
+                            } else {
+                                response
+                            }
+                        }
+                        Err(error) => match NowFileListDirRspMsg::new_error(request_id, error) {
+                            Ok(response) => response,
+                            Err(error) => {
+                                tracing::error!(%error, "Failed to encode directory list response");
+                                return;
+                            }
+                        },
+                    };
+
+                    send_or_log(&sender, response.into()).await;
+                });
+            }
+            NowFileMessage::Mkdir(msg) => {
+                let request_id = msg.request_id();
+                self.spawn_status_request(
+                    move |handler| async move { handler.file_mkdir(msg).await },
+                    file_op_response(request_id),
+                );
+            }
+            NowFileMessage::Delete(msg) => {
+                let request_id = msg.request_id();
+                self.spawn_status_request(
+                    move |handler| async move { handler.file_delete(msg).await },
+                    file_op_response(request_id),
+                );
+            }
+            NowFileMessage::Rename(msg) => {
+                let request_id = msg.request_id();
+                self.spawn_status_request(
+                    move |handler| async move { handler.file_rename(msg).await },
+                    file_op_response(request_id),
+                );
+            }
+            other => {
+                tracing::debug!(message = ?other, "Unexpected NOW-PROTO file message");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Tracks file transfer state for the message sent by the handler. Returns `false` if the
+    /// message should be dropped.
+    fn on_outgoing_file_message(&mut self, message: &NowFileMessage<'_>) -> bool {
+        match message {
+            NowFileMessage::OpenRsp(msg) => match msg.to_result() {
+                Ok(()) => {
+                    if let Some(transfer) = self.file_transfers.get_mut(&msg.transfer_id()) {
+                        transfer.next_offset = msg.file_size();
+                    }
+                }
+                Err(_) => {
+                    self.file_transfers.remove(&msg.transfer_id());
+                }
+            },
+            NowFileMessage::Chunk(msg) => {
+                // Transfer could have been closed by the client, no more chunks should be sent.
+                let Some(transfer) = self.file_transfers.get_mut(&msg.transfer_id()) else {
+                    return false;
+                };
+
+                transfer.sha256.update(msg.data());
+            }
+            NowFileMessage::CloseRsp(msg) => {
+                self.file_transfers.remove(&msg.transfer_id());
+            }
+            _ => {}
+        }
+
+        true
+    }
+
+    /// Registers file transfer and spawns handler callback for it. Open response is sent on
+    /// behalf of the handler if it fails before accepting the transfer, and close response is
+    /// sent when the callback returns and the transfer is closed by the client.
+    fn spawn_file_transfer(&mut self, request: OwnedNowFileOpenMsg) {
+        let transfer_id = request.transfer_id();
+        let mode = request.mode().expect("validated by the caller");
+
+        let (events_tx, events_rx) = mpsc::unbounded_channel();
+        let (close_tx, close_rx) = oneshot::channel();
+
+        self.file_transfers.insert(
+            transfer_id,
+            FileTransfer {
+                mode,
+                events: events_tx,
+                close: close_tx,
+                sha256: Sha256::new(),
+                next_offset: request.offset(),
+                error: None,
+            },
+        );
+
+        let sender = self.sender.clone();
+        let transfer = NowFileTransferContext::new(transfer_id, mode, request.offset(), sender.clone(), events_rx);
+        let accepted = transfer.accepted_flag();
+
+        let handler = Arc::clone(&self.handler);
+
+        self.tasks.spawn(async move {
+            let result = match mode {
+                NowFileOpenMode::Read => handler.file_read(request, transfer).await,
+                NowFileOpenMode::Write => handler.file_write(request, transfer).await,
+            };
+
+            let response: EncodeResult<OwnedNowMessage> = match (accepted.load(Ordering::Acquire), result) {
+                (false, result) => {
+                    let error = result
+                        .err()
+                        .unwrap_or_else(|| NowStatusError::new_proto(NowProtoError::Internal));
+                    NowFileOpenRspMsg::new_error(transfer_id, error).map(Into::into)
+                }
+                // Transfer is failed without waiting for the client to close it.
+                (true, Err(error)) => NowFileCloseRspMsg::new_error(transfer_id, error).map(Into::into),
+                (true, Ok(())) => match close_rx.await {
+                    Ok(Ok(())) => Ok(NowFileCloseRspMsg::new_success(transfer_id).into()),
+                    Ok(Err(error)) => NowFileCloseRspMsg::new_error(transfer_id, error).map(Into::into),
+                    // Channel has been closed.
+                    Err(_) => return,
+                },
+            };
+
+            match response {
+                Ok(response) => send_or_log(&sender, response).await,
+                Err(error) => tracing::error!(%error, "Failed to encode file transfer response"),
+            }
+        });
+    }
+
+    /// Spawns handler callback for the request without response message.
+    fn spawn_request<F, Fut>(&mut self, name: &'static str, request: F)
+    where
+        F: FnOnce(Arc<H>) -> Fut,
+        Fut: Future<Output = NowHandlerResult<()>> + Send + 'static,
+    {
+        let future = request(Arc::clone(&self.handler));
+
+        self.tasks.spawn(async move {
+            log_request_result(name, future.await);
+        });
+    }
+
+    /// Spawns handler callback for the request answered with its status only, and sends the
+    /// result back to the client with the message built by `response`.
+    fn spawn_status_request<F, Fut, R>(&mut self, request: F, response: R)
+    where
+        F: FnOnce(Arc<H>) -> Fut,
+        Fut: Future<Output = NowHandlerResult<()>> + Send + 'static,
+        R: FnOnce(NowHandlerResult<()>) -> EncodeResult<OwnedNowMessage> + Send + 'static,
+    {
+        let future = request(Arc::clone(&self.handler));
+        let sender = self.sender.clone();
+
+        self.tasks.spawn(async move {
+            match response(future.await) {
+                Ok(response) => send_or_log(&sender, response).await,
+                Err(error) => tracing::error!(%error, "Failed to encode status response"),
+            }
+        });
+    }
+
+    /// Registers exec session and spawns handler callback for it. Session result is sent to the
+    /// client when the callback returns.
+    async fn spawn_exec<F, Fut>(&mut self, session_id: u32, request: F) -> Result<(), NowServerError>
+    where
+        F: FnOnce(Arc<H>, NowExecContext) -> Fut,
+        Fut: Future<Output = NowHandlerResult<u32>> + Send + 'static,
+    {
+        let (events_tx, events_rx) = mpsc::unbounded_channel();
+        self.exec_sessions.insert(session_id, events_tx);
+
+        let sender = self.sender.clone();
+        let session = NowExecContext::new(session_id, sender.clone(), events_rx);
+        let started = session.started_flag();
+        let future = request(Arc::clone(&self.handler), session);
+
+        self.tasks.spawn(async move {
+            let result = future.await;
+
+            let response = match result {
+                Ok(exit_code) => {
+                    // Ensure the client receives the started notification for successful sessions.
+                    if !started.load(Ordering::Acquire) {
+                        send_or_log(&sender, NowExecStartedMsg::new(session_id).into()).await;
+                    }
+
+                    NowExecResultMsg::new_success(session_id, exit_code)
+                }
+                Err(error) => match NowExecResultMsg::new_error(session_id, error) {
+                    Ok(response) => response,
+                    Err(error) => {
+                        tracing::error!(%error, "Failed to encode exec result");
+                        NowExecResultMsg::new_error(session_id, NowProtoError::Internal)
+                            .expect("status without message is always encodable")
+                    }
+                },
+            };
+
+            send_or_log(&sender, response.into()).await;
+        });
+
+        Ok(())
+    }
+
+    /// Reports rejected exec request or cancel request back to the client. Responses are written
+    /// directly, bypassing the exec session table, as the rejected session is not registered.
+    async fn reject_exec_message(
+        &mut self,
+        message: &NowExecMessage<'_>,
+        error: &NowExecSessionError,
+    ) -> Result<(), NowServerError> {
+        let session_id = error.session_id();
+
+        let response = match message {
+            NowExecMessage::Process(_)
+            | NowExecMessage::Shell(_)
+            | NowExecMessage::Batch(_)
+            | NowExecMessage::WinPs(_)
+            | NowExecMessage::Pwsh(_) => NowExecResultMsg::new_error(session_id, error.proto_error())
+                .expect("status without message is always encodable")
+                .into(),
+            NowExecMessage::CancelReq(_) => NowExecCancelRspMsg::new_error(session_id, error.proto_error())
+                .expect("status without message is always encodable")
+                .into(),
+            // Other messages have no response, rejected stdin data is just dropped.
+            _ => return Ok(()),
+        };
+
+        self.channel.write_message(&response).await
+    }
+
+    /// Forwards client event to the exec session handler.
+    fn notify_exec_session(&self, session_id: u32, event: NowExecSessionEvent) {
+        if let Some(events) = self.exec_sessions.get(&session_id) {
+            // Handler could have stopped listening for events, this is not an error.
+            let _ = events.send(event);
+        }
+    }
+}
+
+/// Builds `NOW_SYSTEM_POWER_RSP_MSG` for the shutdown abort or power action request.
+fn power_response(request_id: u32) -> impl FnOnce(NowHandlerResult<()>) -> EncodeResult<OwnedNowMessage> {
+    move |result| match result {
+        Ok(()) => Ok(NowSystemPowerRspMsg::new_success(request_id).into()),
+        Err(error) => NowSystemPowerRspMsg::new_error(request_id, error).map(Into::into),
+    }
+}
+
+fn file_op_response(request_id: u32) -> impl FnOnce(NowHandlerResult<()>) -> EncodeResult<OwnedNowMessage> {
+    move |result| match result {
+        Ok(()) => Ok(NowFileOpRspMsg::new_success(request_id).into()),
+        Err(error) => NowFileOpRspMsg::new_error(request_id, error).map(Into::into),
+    }
+}
+
+/// Checks the digest sent by the client against the digest of the transferred data.
+fn verify_file_transfer(
+    sha256: Sha256,
+    error: Option<NowStatusError>,
+    request: &NowFileCloseMsg<'_>,
+) -> NowHandlerResult<()> {
+    if let Some(error) = error {
+        return Err(error);
+    }
+
+    let expected = request
+        .sha256()
+        .map_err(|_| NowStatusError::new_proto(NowProtoError::InvalidRequest))?;
+
+    match expected {
+        Some(expected) if expected != <[u8; NOW_FILE_SHA256_SIZE]>::from(sha256.finalize()) => {
+            Err(NowStatusError::new_proto(NowProtoError::InvalidRequest)
+                .with_message("SHA-256 digest mismatch")
+                .expect("short message is always encodable"))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn log_request_result<T>(name: &'static str, result: NowHandlerResult<T>) {
+    if let Err(error) = result {
+        tracing::warn!(%error, "NOW-PROTO {name} request failed");
+    }
+}
+
+async fn send_or_log(sender: &NowMessageSender, message: NowMessage<'_>) {
+    if let Err(error) = sender.send(message).await {
+        tracing::debug!(%error, "Failed to send NOW-PROTO message");
+    }
+}
@@ -0,0 +1,81 @@
+//! Vendor extension message class used to test extension registry.
+
+use std::sync::LazyLock;
+
+use now_proto_pdu::ironrdp_core::{
+    ensure_fixed_part_size, invalid_field_err, Decode, DecodeResult, Encode, EncodeResult, IntoOwned, ReadCursor,
+    WriteCursor,
+};
+use now_proto_pdu::{NowExtensionMsg, NowExtensionRegistry, NowHeader, NowMessageClass, NowVarStr};
+
+/// Vendor diagnostics message class.
+pub const DIAGNOSTICS_CLASS: NowMessageClass = NowMessageClass(0x40);
+
+/// Diagnostics class messages registry.
+pub static DIAGNOSTICS_REGISTRY: LazyLock<NowExtensionRegistry> = LazyLock::new(|| {
+    NowExtensionRegistry::new()
+        .with_class(DIAGNOSTICS_CLASS, decode_diagnostics)
+        .expect("diagnostics class is in the vendor range")
+});
+
+/// Diagnostics request sent by the client, echoed back by the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiagnosticsPingMsg {
+    pub sequence: u32,
+    pub note: NowVarStr<'static>,
+}
+
+impl DiagnosticsPingMsg {
+    pub const KIND: u8 = 0x01;
+    const FIXED_PART_SIZE: usize = 4;
+
+    pub fn new(sequence: u32, note: &str) -> EncodeResult<Self> {
+        Ok(Self {
+            sequence,
+            note: NowVarStr::new(note.to_owned())?,
+        })
+    }
+
+    fn body_size(&self) -> usize {
+        Self::FIXED_PART_SIZE + self.note.size()
+    }
+
+    fn decode_from_body(src: &mut ReadCursor<'_>) -> DecodeResult<Self> {
+        ensure_fixed_part_size!(in: src);
+
+        let sequence = src.read_u32();
+        let note = NowVarStr::decode(src)?;
+
+        Ok(Self {
+            sequence,
+            note: note.into_owned(),
+        })
+    }
+}
+
+impl Encode for DiagnosticsPingMsg {
+    fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
+        NowHeader::new(DIAGNOSTICS_CLASS, Self::KIND)
+            .with_body_size(self.body_size())?
+            .encode(dst)?;
+
+        ensure_fixed_part_size!(in: dst);
+        dst.write_u32(self.sequence);
+        self.note.encode(dst)
+    }
+
+    fn name(&self) -> &'static str {
+        "DIAGNOSTICS_PING_MSG"
+    }
+
+    fn size(&self) -> usize {
+        NowHeader::FIXED_PART_SIZE + self.body_size()
+    }
+}
+
+fn decode_diagnostics(header: NowHeader, src: &mut ReadCursor<'_>) -> DecodeResult<NowExtensionMsg> {
+    match header.kind {
+        DiagnosticsPingMsg::KIND => Ok(NowExtensionMsg::new(DiagnosticsPingMsg::decode_from_body(src)?)),
+        _ => Err(invalid_field_err!("kind", "unknown diagnostics message kind")),
+    }
+}
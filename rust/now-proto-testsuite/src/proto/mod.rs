@@ -1,3 +1,5 @@
+pub mod extension;
+
 use expect_test::Expect;
 use now_proto_pdu::ironrdp_core::{Decode, IntoOwned, ReadCursor};
 use now_proto_pdu::NowMessage;
@@ -9,6 +11,8 @@ pub fn now_msg_roundtrip(msg: impl Into<NowMessage<'static>>, expected_bytes: Ex
 
     expected_bytes.assert_eq(&format!("{:02X?}", buf));
 
+    assert_dump_matches(&buf);
+
     let mut cursor = ReadCursor::new(&buf);
     let decoded = NowMessage::decode(&mut cursor).expect("failed to decode message");
 
@@ -27,3 +31,17 @@ pub fn now_msg_decodes_into(msg: impl Into<NowMessage<'static>>, expected_bytes:
 
     decoded.into_owned()
 }
+
+/// Checks that the `now-dump` layout of the message matches the `now-proto-pdu` encoding.
+fn assert_dump_matches(buf: &[u8]) {
+    let mut output = String::new();
+    let summary = now_dump::dump_capture(buf, &mut output);
+
+    assert!(summary.is_clean() && summary.frames == 1, "{output}");
+    assert!(!output.contains("!!"), "now-dump layout error:\n{output}");
+    assert!(!output.contains("trailing data"), "now-dump layout mismatch:\n{output}");
+    assert!(
+        !output.contains("<unknown message>"),
+        "now-dump layout missing:\n{output}"
+    );
+}
@@ -0,0 +1,428 @@
+use std::os::unix::process::ExitStatusExt as _;
+
+use now_agent::NowAgentHandler;
+use now_proto_client::{NowClient, NowClientError, NowExecEvent, NowExecSession};
+use now_proto_pdu::*;
+use now_proto_server::NowServer;
+
+async fn connect() -> NowClient {
+    connect_with_handler(NowAgentHandler::new()).await
+}
+
+async fn connect_with_handler(handler: NowAgentHandler) -> NowClient {
+    let (client_io, server_io) = tokio::io::duplex(1024);
+
+    let server = NowServer::new(NowAgentHandler::capabilities(), handler);
+    tokio::spawn(async move { server.serve(server_io).await });
+
+    NowClient::connect(client_io).await.unwrap()
+}
+
+fn status_error(error: NowClientError) -> NowStatusError {
+    match error {
+        NowClientError::Status(status) => status,
+        error => panic!("unexpected error: {error}"),
+    }
+}
+
+/// Collects session output until the session is finished.
+async fn collect_output(mut session: NowExecSession) -> (Vec<u8>, Vec<u8>, Result<u32, NowStatusError>) {
+    let mut stdout = Vec::new();
+    let mut stderr = Vec::new();
+
+    while let Some(event) = session.next_event().await {
+        match event {
+            NowExecEvent::Started => {}
+            NowExecEvent::Stdout { data, .. } => stdout.extend(data),
+            NowExecEvent::Stderr { data, .. } => stderr.extend(data),
+            NowExecEvent::Finished(result) => return (stdout, stderr, result),
+        }
+    }
+
+    panic!("channel closed before the session was finished");
+}
+
+#[tokio::test]
+async fn agent_exec_shell_io_redirection() {
+    let client = connect().await;
+
+    let session = client
+        .exec_shell(|id| Ok(NowExecShellMsg::new(id, "echo hello; echo oops >&2; exit 3")?.with_io_redirection()))
+        .await
+        .unwrap();
+
+    let (stdout, stderr, result) = collect_output(session).await;
+    assert_eq!(stdout, b"hello\n");
+    assert_eq!(stderr, b"oops\n");
+    assert_eq!(result.unwrap(), 3);
+}
+
+#[tokio::test]
+async fn agent_exec_shell_stdin() {
+    let client = connect().await;
+
+    let session = client
+        .exec_shell(|id| Ok(NowExecShellMsg::new(id, "cat")?.with_io_redirection()))
+        .await
+        .unwrap();
+
+    session.send_stdin(b"hello ", false).await.unwrap();
+    session.send_stdin(b"world", true).await.unwrap();
+
+    let (stdout, _, result) = collect_output(session).await;
+    assert_eq!(stdout, b"hello world");
+    assert_eq!(result.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn agent_exec_process_parameters() {
+    let client = connect().await;
+
+    let session = client
+        .exec_process(|id| {
+            Ok(NowExecProcessMsg::new(id, "printf")?
+                .with_parameters(r#"'%s|' plain "double quoted" 'single quoted' escaped\ space"#)?
+                .with_io_redirection())
+        })
+        .await
+        .unwrap();
+
+    let (stdout, _, result) = collect_output(session).await;
+    assert_eq!(stdout, b"plain|double quoted|single quoted|escaped space|");
+    assert_eq!(result.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn agent_exec_process_not_found() {
+    let client = connect().await;
+
+    let session = client
+        .exec_process(|id| NowExecProcessMsg::new(id, "/nonexistent/now-agent-test"))
+        .await
+        .unwrap();
+
+    let status = status_error(session.wait().await.unwrap_err());
+    assert_eq!(status.kind(), NowStatusErrorKind::Unix(2)); // ENOENT
+}
+
+#[tokio::test]
+async fn agent_exec_detached() {
+    let client = connect().await;
+
+    let session = client
+        .exec_shell(|id| Ok(NowExecShellMsg::new(id, "sleep 30")?.with_detached()))
+        .await
+        .unwrap();
+
+    // Session is finished as soon as the process is started.
+    assert_eq!(session.wait().await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn agent_exec_cancel() {
+    let client = connect().await;
+
+    let mut session = client
+        .exec_shell(|id| Ok(NowExecShellMsg::new(id, "sleep 30")?.with_io_redirection()))
+        .await
+        .unwrap();
+
+    assert_eq!(session.next_event().await, Some(NowExecEvent::Started));
+
+    session.cancel().await.unwrap();
+
+    // 128 + SIGTERM
+    let (_, _, result) = collect_output(session).await;
+    assert_eq!(result.unwrap(), 143);
+}
+
+#[tokio::test]
+async fn agent_exec_windows_styles_not_implemented() {
+    let client = connect().await;
+
+    let session = client
+        .exec_batch(|id| NowExecBatchMsg::new(id, "echo hello"))
+        .await
+        .unwrap();
+
+    let status = status_error(session.wait().await.unwrap_err());
+    assert_eq!(status.kind(), NowStatusErrorKind::Now(NowProtoError::NotImplemented));
+}
+
+/// Builds glibc `struct utmp` record (384 bytes).
+fn utmp_record(ut_type: i16, line: &str, user: &str, login_time: i32) -> Vec<u8> {
+    let mut record = vec![0u8; 384];
+    record[0..2].copy_from_slice(&ut_type.to_ne_bytes());
+    record[8..8 + line.len()].copy_from_slice(line.as_bytes());
+    record[44..44 + user.len()].copy_from_slice(user.as_bytes());
+    record[340..344].copy_from_slice(&login_time.to_ne_bytes());
+    record
+}
+
+#[tokio::test]
+async fn agent_system_info() {
+    const USER_PROCESS: i16 = 7;
+    const DEAD_PROCESS: i16 = 8;
+
+    let dir = tempfile::tempdir().unwrap();
+    let utmp_path = dir.path().join("utmp");
+
+    let utmp = [
+        utmp_record(USER_PROCESS, "pts/0", "alice", 100),
+        utmp_record(USER_PROCESS, "pts/1", "bob", 200),
+        // Terminated session is ignored even if it is the most recent one.
+        utmp_record(DEAD_PROCESS, "pts/2", "carol", 300),
+    ]
+    .concat();
+    std::fs::write(&utmp_path, utmp).unwrap();
+
+    let client = connect_with_handler(NowAgentHandler::new().with_utmp_path(&utmp_path)).await;
+
+    let info = client.system_info().await.unwrap();
+    assert!(info.os_name().is_some());
+    assert_eq!(info.architecture(), Some(std::env::consts::ARCH));
+    assert!(info.agent_version().is_some());
+    assert!(info.uptime().is_some());
+    assert_eq!(info.logged_on_user(), Some("bob"));
+}
+
+#[tokio::test]
+async fn agent_system_process_list() {
+    let client = connect().await;
+
+    let processes = client.system_process_list().await.unwrap();
+
+    let current = processes
+        .iter()
+        .find(|process| process.pid() == std::process::id())
+        .expect("current process is listed");
+    assert_ne!(current.parent_pid(), 0);
+    assert_ne!(current.start_time(), 0);
+    assert!(!current.process_name().is_empty());
+    assert_eq!(current.exe_path(), std::env::current_exe().unwrap().to_string_lossy());
+}
+
+#[tokio::test]
+async fn agent_system_process_terminate() {
+    let client = connect().await;
+
+    let mut child = std::process::Command::new("sleep").arg("60").spawn().unwrap();
+
+    client.system_process_terminate(child.id(), false).await.unwrap();
+
+    // Terminated by SIGTERM.
+    let status = child.wait().unwrap();
+    assert_eq!(status.signal(), Some(15));
+
+    // Larger than the maximum PID on Linux.
+    let error = client.system_process_terminate(0x7FFF_FFFF, true).await.unwrap_err();
+    assert_eq!(
+        status_error(error).kind(),
+        NowStatusErrorKind::Now(NowProtoError::NotFound)
+    );
+}
+
+async fn read_remote_file(client: &NowClient, path: &str, offset: u64) -> Result<Vec<u8>, NowClientError> {
+    let mut transfer = client
+        .file_open(|id| Ok(NowFileOpenMsg::new(id, NowFileOpenMode::Read, path)?.with_offset(offset)))
+        .await?;
+
+    let mut data = Vec::new();
+    while let Some(chunk) = transfer.read_chunk().await? {
+        data.extend(chunk);
+    }
+
+    transfer.close().await?;
+
+    Ok(data)
+}
+
+#[tokio::test]
+async fn agent_file_write_resume_and_read() {
+    let client = connect().await;
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("file.txt");
+    let path = path.to_str().unwrap();
+
+    let mut transfer = client
+        .file_open(|id| NowFileOpenMsg::new(id, NowFileOpenMode::Write, path))
+        .await
+        .unwrap();
+    transfer.write_chunk(b"hello ").await.unwrap();
+    transfer.close().await.unwrap();
+
+    let mut transfer = client
+        .file_open(|id| Ok(NowFileOpenMsg::new(id, NowFileOpenMode::Write, path)?.with_resume()))
+        .await
+        .unwrap();
+    assert_eq!(transfer.offset(), 6);
+    transfer.write_chunk(b"world").await.unwrap();
+    transfer.close().await.unwrap();
+
+    assert_eq!(std::fs::read(path).unwrap(), b"hello world");
+    assert_eq!(read_remote_file(&client, path, 0).await.unwrap(), b"hello world");
+    assert_eq!(read_remote_file(&client, path, 6).await.unwrap(), b"world");
+    assert_eq!(read_remote_file(&client, path, 11).await.unwrap(), b"");
+}
+
+#[tokio::test]
+async fn agent_file_write_truncates_at_offset() {
+    let client = connect().await;
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("file.txt");
+    std::fs::write(&path, b"hello world").unwrap();
+    let path = path.to_str().unwrap();
+
+    let mut transfer = client
+        .file_open(|id| Ok(NowFileOpenMsg::new(id, NowFileOpenMode::Write, path)?.with_offset(5)))
+        .await
+        .unwrap();
+    transfer.write_chunk(b"!").await.unwrap();
+    transfer.close().await.unwrap();
+
+    assert_eq!(std::fs::read(path).unwrap(), b"hello!");
+
+    // Writing past the end of file would leave a hole.
+    let error = client
+        .file_open(|id| Ok(NowFileOpenMsg::new(id, NowFileOpenMode::Write, path)?.with_offset(7)))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        status_error(error).kind(),
+        NowStatusErrorKind::Now(NowProtoError::InvalidRequest)
+    );
+}
+
+#[tokio::test]
+async fn agent_file_read_large() {
+    let client = connect().await;
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("large.bin");
+    let content: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+    std::fs::write(&path, &content).unwrap();
+
+    let data = read_remote_file(&client, path.to_str().unwrap(), 0).await.unwrap();
+    assert_eq!(data, content);
+}
+
+#[tokio::test]
+async fn agent_file_read_not_found() {
+    let client = connect().await;
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("missing.txt");
+
+    let error = read_remote_file(&client, path.to_str().unwrap(), 0).await.unwrap_err();
+    assert_eq!(
+        status_error(error).kind(),
+        NowStatusErrorKind::Now(NowProtoError::NotFound)
+    );
+
+    // Write transfers create missing files, but not missing parent directories.
+    let path = dir.path().join("missing").join("file.txt");
+    let error = client
+        .file_open(|id| NowFileOpenMsg::new(id, NowFileOpenMode::Write, path.to_str().unwrap()))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        status_error(error).kind(),
+        NowStatusErrorKind::Now(NowProtoError::NotFound)
+    );
+}
+
+#[tokio::test]
+async fn agent_file_stat_and_list_dir() {
+    let client = connect().await;
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("b.txt"), b"hello").unwrap();
+    std::fs::write(dir.path().join(".hidden"), b"").unwrap();
+    std::fs::create_dir(dir.path().join("a")).unwrap();
+    let path = dir.path().to_str().unwrap();
+
+    let stat = client.file_stat(path).await.unwrap();
+    assert_eq!(stat.kind(), NowFileEntryKind::DIRECTORY);
+
+    let stat = client
+        .file_stat(dir.path().join("b.txt").to_str().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(stat.kind(), NowFileEntryKind::FILE);
+    assert_eq!(stat.file_size(), 5);
+    assert_ne!(stat.mtime(), 0);
+
+    let page = client.file_list_dir(path, 0, 0).await.unwrap();
+    assert!(!page.has_more());
+
+    let entries: Vec<_> = page
+        .entries()
+        .iter()
+        .map(|entry| (entry.file_name().to_owned(), entry.kind()))
+        .collect();
+    assert_eq!(
+        entries,
+        [
+            (".hidden".to_owned(), NowFileEntryKind::FILE),
+            ("a".to_owned(), NowFileEntryKind::DIRECTORY),
+            ("b.txt".to_owned(), NowFileEntryKind::FILE),
+        ]
+    );
+    assert!(page.entries()[0].attributes().contains(NowFileAttributes::HIDDEN));
+    assert!(!page.entries()[2].attributes().contains(NowFileAttributes::HIDDEN));
+
+    let page = client.file_list_dir(path, 1, 1).await.unwrap();
+    assert_eq!(page.entries().len(), 1);
+    assert_eq!(page.entries()[0].file_name(), "a");
+    assert!(page.has_more());
+
+    let page = client.file_list_dir(path, 2, 1).await.unwrap();
+    assert_eq!(page.entries().len(), 1);
+    assert_eq!(page.entries()[0].file_name(), "b.txt");
+    assert!(!page.has_more());
+
+    let error = client
+        .file_stat(dir.path().join("missing").to_str().unwrap())
+        .await
+        .unwrap_err();
+    assert_eq!(
+        status_error(error).kind(),
+        NowStatusErrorKind::Now(NowProtoError::NotFound)
+    );
+}
+
+#[tokio::test]
+async fn agent_file_mkdir_delete_rename() {
+    let client = connect().await;
+    let dir = tempfile::tempdir().unwrap();
+    let nested = dir.path().join("a/b");
+    let nested = nested.to_str().unwrap();
+    let parent = dir.path().join("a");
+    let parent = parent.to_str().unwrap();
+
+    let error = client.file_mkdir(nested, false).await.unwrap_err();
+    assert_eq!(
+        status_error(error).kind(),
+        NowStatusErrorKind::Now(NowProtoError::NotFound)
+    );
+    client.file_mkdir(nested, true).await.unwrap();
+    assert!(std::fs::metadata(nested).unwrap().is_dir());
+
+    let source = dir.path().join("source.txt");
+    let target = dir.path().join("target.txt");
+    std::fs::write(&source, b"source").unwrap();
+    std::fs::write(&target, b"target").unwrap();
+    let source = source.to_str().unwrap();
+    let target = target.to_str().unwrap();
+
+    let error = client.file_rename(source, target, false).await.unwrap_err();
+    assert_eq!(status_error(error).kind(), NowStatusErrorKind::Unix(17)); // EEXIST
+    client.file_rename(source, target, true).await.unwrap();
+    assert_eq!(std::fs::read(target).unwrap(), b"source");
+    assert!(!std::path::Path::new(source).exists());
+
+    // Directory is not empty.
+    assert!(client.file_delete(parent, false).await.is_err());
+    client.file_delete(parent, true).await.unwrap();
+    assert!(!std::path::Path::new(parent).exists());
+
+    client.file_delete(target, false).await.unwrap();
+    assert!(!std::path::Path::new(target).exists());
+}
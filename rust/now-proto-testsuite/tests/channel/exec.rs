@@ -0,0 +1,236 @@
+use now_proto_channel::*;
+use now_proto_pdu::*;
+
+fn shell(session_id: u32) -> NowExecMessage<'static> {
+    NowExecMessage::Shell(NowExecShellMsg::new(session_id, "ls").unwrap())
+}
+
+fn data(session_id: u32, stream: NowExecDataStreamKind, last: bool) -> NowExecMessage<'static> {
+    NowExecMessage::Data(NowExecDataMsg::new(session_id, stream, last, b"data").unwrap())
+}
+
+fn started(session_id: u32) -> NowExecMessage<'static> {
+    NowExecMessage::Started(NowExecStartedMsg::new(session_id))
+}
+
+fn result(session_id: u32) -> NowExecMessage<'static> {
+    NowExecMessage::Result(NowExecResultMsg::new_success(session_id, 0))
+}
+
+#[test]
+fn exec_table_lifecycle() {
+    let mut table = NowExecSessionTable::new();
+
+    let session_id = table.allocate_id();
+    table.on_client_message(&shell(session_id)).unwrap();
+    assert_eq!(table.phase(session_id), Some(NowExecSessionPhase::Requested));
+
+    // Stdin could be sent before the process is started.
+    table
+        .on_client_message(&data(session_id, NowExecDataStreamKind::Stdin, true))
+        .unwrap();
+
+    table.on_server_message(&started(session_id)).unwrap();
+    assert_eq!(table.phase(session_id), Some(NowExecSessionPhase::Started));
+
+    table
+        .on_server_message(&data(session_id, NowExecDataStreamKind::Stdout, false))
+        .unwrap();
+    table
+        .on_server_message(&data(session_id, NowExecDataStreamKind::Stdout, true))
+        .unwrap();
+    table
+        .on_server_message(&data(session_id, NowExecDataStreamKind::Stderr, true))
+        .unwrap();
+
+    table.on_server_message(&result(session_id)).unwrap();
+    assert!(!table.contains(session_id));
+    assert!(table.is_empty());
+}
+
+#[test]
+fn exec_table_allocate_unique_ids() {
+    let mut table = NowExecSessionTable::new();
+
+    let first = table.allocate_id();
+    table.on_client_message(&shell(first)).unwrap();
+
+    let second = table.allocate_id();
+    assert_ne!(first, second);
+    table.on_client_message(&shell(second)).unwrap();
+
+    assert_eq!(table.len(), 2);
+}
+
+#[test]
+fn exec_table_id_in_use() {
+    let mut table = NowExecSessionTable::new();
+    table.on_client_message(&shell(1)).unwrap();
+
+    let error = table.on_client_message(&shell(1)).unwrap_err();
+    assert_eq!(error, NowExecSessionError::InUse { session_id: 1 });
+    assert_eq!(error.proto_error(), NowProtoError::InUse);
+
+    // ID could be reused once the session is terminated.
+    table.on_server_message(&result(1)).unwrap();
+    table.on_client_message(&shell(1)).unwrap();
+}
+
+#[test]
+fn exec_table_data_after_last() {
+    let mut table = NowExecSessionTable::new();
+    table.on_client_message(&shell(1)).unwrap();
+    table.on_server_message(&started(1)).unwrap();
+    table
+        .on_server_message(&data(1, NowExecDataStreamKind::Stdout, true))
+        .unwrap();
+
+    let error = table
+        .on_server_message(&data(1, NowExecDataStreamKind::Stdout, false))
+        .unwrap_err();
+    assert_eq!(
+        error,
+        NowExecSessionError::DataAfterLast {
+            session_id: 1,
+            stream: NowExecDataStreamKind::Stdout
+        }
+    );
+    assert_eq!(error.proto_error(), NowProtoError::InvalidRequest);
+}
+
+#[test]
+fn exec_table_out_of_order() {
+    let mut table = NowExecSessionTable::new();
+    table.on_client_message(&shell(1)).unwrap();
+
+    let error = table
+        .on_server_message(&data(1, NowExecDataStreamKind::Stdout, false))
+        .unwrap_err();
+    assert_eq!(
+        error,
+        NowExecSessionError::OutOfOrder {
+            session_id: 1,
+            message: "NOW_EXEC_DATA_MSG",
+            phase: NowExecSessionPhase::Requested,
+        }
+    );
+
+    table.on_server_message(&started(1)).unwrap();
+    let error = table.on_server_message(&started(1)).unwrap_err();
+    assert!(matches!(error, NowExecSessionError::OutOfOrder { .. }));
+}
+
+#[test]
+fn exec_table_unknown_session() {
+    let mut table = NowExecSessionTable::new();
+
+    let error = table.on_server_message(&result(7)).unwrap_err();
+    assert_eq!(
+        error,
+        NowExecSessionError::UnknownSession {
+            session_id: 7,
+            message: "NOW_EXEC_RESULT_MSG"
+        }
+    );
+    assert_eq!(error.proto_error(), NowProtoError::NotFound);
+
+    let cancel = NowExecMessage::CancelReq(NowExecCancelReqMsg::new(7));
+    assert!(matches!(
+        table.on_client_message(&cancel).unwrap_err(),
+        NowExecSessionError::UnknownSession { .. }
+    ));
+}
+
+#[test]
+fn exec_table_abort() {
+    let mut table = NowExecSessionTable::new();
+    table.on_client_message(&shell(1)).unwrap();
+
+    table
+        .on_client_message(&NowExecMessage::Abort(NowExecAbortMsg::new(1, 1)))
+        .unwrap();
+    assert!(table.is_empty());
+
+    // Result sent by the peer before receiving abort is reported as unknown session.
+    assert!(matches!(
+        table.on_server_message(&result(1)).unwrap_err(),
+        NowExecSessionError::UnknownSession { .. }
+    ));
+
+    // Late abort for already terminated session is not an error.
+    table
+        .on_client_message(&NowExecMessage::Abort(NowExecAbortMsg::new(1, 1)))
+        .unwrap();
+}
+
+#[test]
+fn exec_table_rejects_stream_from_wrong_side() {
+    let mut table = NowExecSessionTable::new();
+    table.on_client_message(&shell(1)).unwrap();
+    table.on_server_message(&started(1)).unwrap();
+
+    // Output written by the client must not close the server streams.
+    let error = table
+        .on_client_message(&data(1, NowExecDataStreamKind::Stdout, true))
+        .unwrap_err();
+    assert_eq!(
+        error,
+        NowExecSessionError::UnexpectedStream {
+            session_id: 1,
+            stream: NowExecDataStreamKind::Stdout
+        }
+    );
+    assert_eq!(error.proto_error(), NowProtoError::InvalidRequest);
+    assert!(matches!(
+        table.on_client_message(&data(1, NowExecDataStreamKind::Stderr, true)),
+        Err(NowExecSessionError::UnexpectedStream { .. })
+    ));
+
+    table
+        .on_server_message(&data(1, NowExecDataStreamKind::Stdout, false))
+        .unwrap();
+    table
+        .on_server_message(&data(1, NowExecDataStreamKind::Stderr, false))
+        .unwrap();
+
+    // Stdin written by the server must not close the client stream.
+    let error = table
+        .on_server_message(&data(1, NowExecDataStreamKind::Stdin, true))
+        .unwrap_err();
+    assert_eq!(
+        error,
+        NowExecSessionError::UnexpectedStream {
+            session_id: 1,
+            stream: NowExecDataStreamKind::Stdin
+        }
+    );
+
+    table
+        .on_client_message(&data(1, NowExecDataStreamKind::Stdin, true))
+        .unwrap();
+}
+
+#[test]
+fn exec_table_rejects_message_from_wrong_side() {
+    let mut table = NowExecSessionTable::new();
+
+    assert_eq!(
+        table.on_server_message(&shell(1)).unwrap_err(),
+        NowExecSessionError::UnexpectedDirection {
+            session_id: 1,
+            message: "NOW_EXEC_SHELL_MSG"
+        }
+    );
+    assert!(table.is_empty());
+
+    table.on_client_message(&shell(1)).unwrap();
+    assert!(matches!(
+        table.on_client_message(&started(1)),
+        Err(NowExecSessionError::UnexpectedDirection { .. })
+    ));
+    assert!(matches!(
+        table.on_client_message(&result(1)),
+        Err(NowExecSessionError::UnexpectedDirection { .. })
+    ));
+    assert_eq!(table.phase(1), Some(NowExecSessionPhase::Requested));
+}
@@ -0,0 +1,105 @@
+use core::cell::Cell;
+use core::time::Duration;
+use std::rc::Rc;
+use std::time::Instant;
+
+use now_proto_channel::*;
+use now_proto_pdu::*;
+
+const INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Clone)]
+struct ManualClock(Rc<Cell<Instant>>);
+
+impl ManualClock {
+    fn new() -> Self {
+        Self(Rc::new(Cell::new(Instant::now())))
+    }
+
+    fn advance(&self, duration: Duration) {
+        self.0.set(self.0.get() + duration);
+    }
+}
+
+impl NowClock for ManualClock {
+    fn now(&self) -> Instant {
+        self.0.get()
+    }
+}
+
+fn supervisor(role: NowChannelRole, clock: &ManualClock) -> NowHeartbeatSupervisor<ManualClock> {
+    let capabilities = NowChannelCapsetMsg::default()
+        .with_heartbeat_interval(INTERVAL)
+        .unwrap();
+
+    NowHeartbeatSupervisor::with_clock(role, &capabilities, clock.clone()).unwrap()
+}
+
+#[test]
+fn heartbeat_disabled() {
+    assert!(NowHeartbeatSupervisor::new(NowChannelRole::Client, &NowChannelCapsetMsg::default()).is_none());
+}
+
+#[test]
+fn heartbeat_server_schedules_heartbeats() {
+    let clock = ManualClock::new();
+    let mut heartbeat = supervisor(NowChannelRole::Server, &clock);
+
+    assert_eq!(heartbeat.next_deadline(), Some(clock.now() + INTERVAL));
+    assert_eq!(heartbeat.poll(), None);
+
+    clock.advance(INTERVAL);
+    assert_eq!(heartbeat.poll(), Some(NowHeartbeatEvent::SendHeartbeat));
+    assert_eq!(heartbeat.poll(), None);
+    assert_eq!(heartbeat.next_deadline(), Some(clock.now() + INTERVAL));
+
+    // Server never reports peer timeout, clients are not required to send heartbeats.
+    clock.advance(INTERVAL * 10);
+    assert_eq!(heartbeat.poll(), Some(NowHeartbeatEvent::SendHeartbeat));
+}
+
+#[test]
+fn heartbeat_client_peer_timeout() {
+    let clock = ManualClock::new();
+    let mut heartbeat = supervisor(NowChannelRole::Client, &clock);
+
+    clock.advance(INTERVAL);
+    assert_eq!(heartbeat.poll(), None);
+
+    clock.advance(INTERVAL);
+    assert_eq!(
+        heartbeat.poll(),
+        Some(NowHeartbeatEvent::PeerTimeout { idle: INTERVAL * 2 })
+    );
+
+    // Timeout is reported only once.
+    assert_eq!(heartbeat.poll(), None);
+    assert_eq!(heartbeat.next_deadline(), None);
+}
+
+#[test]
+fn heartbeat_client_inbound_traffic() {
+    let clock = ManualClock::new();
+    let mut heartbeat = supervisor(NowChannelRole::Client, &clock);
+
+    for _ in 0..5 {
+        clock.advance(INTERVAL + INTERVAL / 2);
+        heartbeat.on_message_received();
+        assert_eq!(heartbeat.poll(), None);
+    }
+
+    assert_eq!(heartbeat.idle(), Duration::ZERO);
+    assert_eq!(heartbeat.next_deadline(), Some(clock.now() + INTERVAL * 2));
+}
+
+#[test]
+fn heartbeat_client_max_missed_intervals() {
+    let clock = ManualClock::new();
+    let mut heartbeat = supervisor(NowChannelRole::Client, &clock).with_max_missed_intervals(5);
+
+    clock.advance(INTERVAL * 4);
+    assert_eq!(heartbeat.poll(), None);
+
+    clock.advance(INTERVAL);
+    assert!(matches!(heartbeat.poll(), Some(NowHeartbeatEvent::PeerTimeout { .. })));
+}
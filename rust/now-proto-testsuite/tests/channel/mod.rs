@@ -0,0 +1,4 @@
+mod exec;
+mod heartbeat;
+mod msg_box;
+mod state;
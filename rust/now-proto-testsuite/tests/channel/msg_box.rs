@@ -0,0 +1,132 @@
+use core::cell::Cell;
+use core::time::Duration;
+use std::rc::Rc;
+use std::time::Instant;
+
+use now_proto_channel::*;
+use now_proto_pdu::*;
+
+const GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+#[derive(Clone)]
+struct ManualClock(Rc<Cell<Instant>>);
+
+impl ManualClock {
+    fn new() -> Self {
+        Self(Rc::new(Cell::new(Instant::now())))
+    }
+
+    fn advance(&self, duration: Duration) {
+        self.0.set(self.0.get() + duration);
+    }
+}
+
+impl NowClock for ManualClock {
+    fn now(&self) -> Instant {
+        self.0.get()
+    }
+}
+
+fn request(request_id: u32) -> NowSessionMsgBoxReqMsg<'static> {
+    NowSessionMsgBoxReqMsg::new(request_id, "Hello")
+        .unwrap()
+        .with_response()
+}
+
+#[test]
+fn msg_box_response_correlation() {
+    let mut correlator = NowMsgBoxCorrelator::new();
+
+    let first = correlator.allocate_id();
+    let second = correlator.allocate_id();
+    assert_ne!(first, second);
+
+    correlator.register(&request(first), "first").unwrap();
+    correlator.register(&request(second), "second").unwrap();
+    assert_eq!(correlator.len(), 2);
+
+    let response = NowSessionMsgBoxRspMsg::new_success(second, NowMsgBoxResponse::YES);
+    let (context, result) = correlator.on_response(&response).unwrap();
+    assert_eq!(context, "second");
+    assert_eq!(result.unwrap(), NowMsgBoxResponse::YES);
+
+    let response = NowSessionMsgBoxRspMsg::new_error(first, NowProtoError::Aborted).unwrap();
+    let (context, result) = correlator.on_response(&response).unwrap();
+    assert_eq!(context, "first");
+    assert!(result.is_err());
+
+    assert!(correlator.is_empty());
+}
+
+#[test]
+fn msg_box_unexpected_response() {
+    let mut correlator = NowMsgBoxCorrelator::<()>::new();
+
+    let response = NowSessionMsgBoxRspMsg::new_success(42, NowMsgBoxResponse::OK);
+    assert!(correlator.on_response(&response).is_none());
+}
+
+#[test]
+fn msg_box_rejects_request_id_in_use() {
+    let mut correlator = NowMsgBoxCorrelator::new();
+
+    correlator.register(&request(1), 1).unwrap();
+
+    let (error, context) = correlator.register(&request(1), 2).unwrap_err();
+    assert_eq!(error, NowMsgBoxError::InUse { request_id: 1 });
+    assert_eq!(error.proto_error(), NowProtoError::InUse);
+    assert_eq!(context, 2);
+
+    // Allocated IDs skip pending requests.
+    assert_eq!(correlator.allocate_id(), 0);
+    assert_eq!(correlator.allocate_id(), 2);
+}
+
+#[test]
+fn msg_box_rejects_request_without_response() {
+    let mut correlator = NowMsgBoxCorrelator::new();
+
+    let request = NowSessionMsgBoxReqMsg::new(1, "Hello").unwrap();
+    let (error, ()) = correlator.register(&request, ()).unwrap_err();
+    assert_eq!(error, NowMsgBoxError::ResponseNotExpected { request_id: 1 });
+    assert!(!correlator.contains(1));
+}
+
+#[test]
+fn msg_box_expires_after_timeout_and_grace_period() {
+    let clock = ManualClock::new();
+    let mut correlator = NowMsgBoxCorrelator::with_clock(clock.clone()).with_grace_period(GRACE_PERIOD);
+
+    let timeout = Duration::from_secs(30);
+    let request = request(1).with_timeout(timeout).unwrap();
+    correlator.register(&request, "context").unwrap();
+
+    assert_eq!(correlator.next_deadline(), Some(clock.now() + timeout + GRACE_PERIOD));
+
+    clock.advance(timeout);
+    assert!(correlator.poll_expired().is_empty());
+
+    clock.advance(GRACE_PERIOD);
+    assert_eq!(correlator.poll_expired(), vec![(1, "context")]);
+    assert!(correlator.is_empty());
+    assert_eq!(correlator.next_deadline(), None);
+
+    // Late response is ignored.
+    let response = NowSessionMsgBoxRspMsg::new_success(1, NowMsgBoxResponse::OK);
+    assert!(correlator.on_response(&response).is_none());
+}
+
+#[test]
+fn msg_box_without_timeout_never_expires() {
+    let clock = ManualClock::new();
+    let mut correlator = NowMsgBoxCorrelator::with_clock(clock.clone());
+
+    correlator.register(&request(1), ()).unwrap();
+    assert_eq!(correlator.next_deadline(), None);
+
+    clock.advance(Duration::from_secs(24 * 60 * 60));
+    assert!(correlator.poll_expired().is_empty());
+
+    assert_eq!(correlator.drain().collect::<Vec<_>>(), vec![(1, ())]);
+    assert!(correlator.is_empty());
+}
@@ -0,0 +1,506 @@
+use now_proto_channel::*;
+use now_proto_pdu::*;
+
+fn capabilities() -> NowChannelCapsetMsg {
+    NowChannelCapsetMsg::default()
+        .with_system_capset(NowSystemCapsetFlags::SHUTDOWN)
+        .with_session_capset(NowSessionCapsetFlags::LOCK | NowSessionCapsetFlags::MSGBOX)
+        .with_exec_capset(NowExecCapsetFlags::STYLE_RUN)
+}
+
+/// Builds capabilities with the custom protocol version, which can't be set via the public API.
+fn capabilities_with_version(major: u8, minor: u8) -> NowChannelCapsetMsg {
+    let encoded = [
+        0x0E, 0x00, 0x00, 0x00, 0x10, 0x01, 0x00, 0x00, major, 0x00, minor, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+    ];
+
+    ironrdp_core::decode(&encoded).unwrap()
+}
+
+fn negotiated(role: NowChannelRole, capabilities: NowChannelCapsetMsg) -> NowChannelState {
+    let mut state = NowChannelState::new(role, NowProtoVersion::CURRENT);
+    let client = NowMessage::from(capabilities.clone());
+    let server = NowMessage::from(capabilities);
+
+    match role {
+        NowChannelRole::Client => {
+            state.on_outgoing(&client).unwrap();
+            state.on_incoming(&server).unwrap();
+        }
+        NowChannelRole::Server => {
+            state.on_incoming(&client).unwrap();
+            state.on_outgoing(&server).unwrap();
+        }
+    }
+
+    assert_eq!(state.phase(), NowChannelPhase::Negotiated);
+    state
+}
+
+#[test]
+fn channel_state_negotiation() {
+    let mut state = NowChannelState::new(NowChannelRole::Client, NowProtoVersion::CURRENT);
+    assert_eq!(state.phase(), NowChannelPhase::AwaitingClientCapset);
+    assert!(state.negotiated_capabilities().is_none());
+
+    state.on_outgoing(&capabilities().into()).unwrap();
+    assert_eq!(state.phase(), NowChannelPhase::AwaitingServerCapset);
+
+    let downgraded = capabilities().downgrade(&NowChannelCapsetMsg::default());
+    state.on_incoming(&downgraded.clone().into()).unwrap();
+    assert_eq!(state.phase(), NowChannelPhase::Negotiated);
+    assert_eq!(state.negotiated_capabilities(), Some(&downgraded));
+}
+
+#[test]
+fn channel_state_server_capset_downgraded() {
+    let mut state = NowChannelState::new(NowChannelRole::Client, NowProtoVersion::CURRENT);
+    state.on_outgoing(&capabilities().into()).unwrap();
+
+    // Server advertises capabilities which were never offered by the client.
+    let server = capabilities()
+        .with_system_capset(NowSystemCapsetFlags::SHUTDOWN | NowSystemCapsetFlags::INFO)
+        .with_exec_capset(NowExecCapsetFlags::STYLE_RUN | NowExecCapsetFlags::STYLE_SHELL);
+    state.on_incoming(&server.clone().into()).unwrap();
+
+    assert_eq!(
+        state.negotiated_capabilities(),
+        Some(&capabilities().downgrade(&server))
+    );
+    assert_eq!(
+        state.negotiated_capabilities().unwrap().exec_capset(),
+        NowExecCapsetFlags::STYLE_RUN
+    );
+
+    let violation = state
+        .on_outgoing(&NowExecShellMsg::new(1, "ls").unwrap().into())
+        .unwrap_err();
+    assert_eq!(
+        violation,
+        NowChannelViolation::CapabilityNotNegotiated {
+            message: "NOW_EXEC_SHELL_MSG",
+            capability: NowCapability::Exec(NowExecCapsetFlags::STYLE_SHELL),
+        }
+    );
+}
+
+#[test]
+fn channel_state_capset_expected() {
+    let mut state = NowChannelState::new(NowChannelRole::Server, NowProtoVersion::CURRENT);
+
+    let violation = state.on_incoming(&NowSessionLockMsg::default().into()).unwrap_err();
+    assert_eq!(
+        violation,
+        NowChannelViolation::CapsetExpected {
+            message: "NOW_SESSION_LOCK_MSG"
+        }
+    );
+    assert_eq!(violation.proto_error(), NowProtoError::InvalidRequest);
+
+    // State is not changed on violation.
+    assert_eq!(state.phase(), NowChannelPhase::AwaitingClientCapset);
+
+    // Server capabilities must not be sent before client capabilities.
+    let violation = state.on_outgoing(&capabilities().into()).unwrap_err();
+    assert_eq!(violation, NowChannelViolation::UnexpectedCapset);
+}
+
+#[test]
+fn channel_state_incompatible_version() {
+    let mut state = NowChannelState::new(NowChannelRole::Server, NowProtoVersion { major: 1, minor: 6 });
+
+    let client = capabilities_with_version(2, 0);
+
+    let violation = state.on_incoming(&client.into()).unwrap_err();
+    assert!(matches!(violation, NowChannelViolation::IncompatibleVersion { .. }));
+    assert_eq!(violation.proto_error(), NowProtoError::ProtocolVersion);
+}
+
+#[test]
+fn channel_state_capability_not_negotiated() {
+    let mut state = negotiated(NowChannelRole::Client, capabilities());
+
+    state.on_outgoing(&NowSessionLockMsg::default().into()).unwrap();
+
+    let violation = state.on_outgoing(&NowSessionLogoffMsg::default().into()).unwrap_err();
+    assert_eq!(
+        violation,
+        NowChannelViolation::CapabilityNotNegotiated {
+            message: "NOW_SESSION_LOGOFF_MSG",
+            capability: NowCapability::Session(NowSessionCapsetFlags::LOGOFF),
+        }
+    );
+
+    let shell = NowExecShellMsg::new(1, "ls").unwrap();
+    let violation = state.on_outgoing(&shell.into()).unwrap_err();
+    assert!(matches!(
+        violation,
+        NowChannelViolation::CapabilityNotNegotiated {
+            capability: NowCapability::Exec(NowExecCapsetFlags::STYLE_SHELL),
+            ..
+        }
+    ));
+}
+
+#[test]
+fn channel_state_unexpected_direction() {
+    let mut state = negotiated(NowChannelRole::Server, capabilities());
+
+    let violation = state.on_incoming(&NowExecStartedMsg::new(1).into()).unwrap_err();
+    assert_eq!(
+        violation,
+        NowChannelViolation::UnexpectedDirection {
+            message: "NOW_EXEC_STARTED_MSG"
+        }
+    );
+
+    // Heartbeats are sent by both sides.
+    state.on_incoming(&NowChannelHeartbeatMsg::default().into()).unwrap();
+    state.on_outgoing(&NowChannelHeartbeatMsg::default().into()).unwrap();
+}
+
+#[test]
+fn channel_state_system_info() {
+    let mut state = negotiated(NowChannelRole::Server, capabilities());
+
+    let violation = state.on_incoming(&NowSystemInfoReqMsg::new(1).into()).unwrap_err();
+    assert_eq!(
+        violation,
+        NowChannelViolation::CapabilityNotNegotiated {
+            message: "NOW_SYSTEM_INFO_REQ_MSG",
+            capability: NowCapability::System(NowSystemCapsetFlags::INFO),
+        }
+    );
+
+    let capabilities = capabilities().with_system_capset(NowSystemCapsetFlags::INFO);
+    let mut state = negotiated(NowChannelRole::Server, capabilities);
+
+    state.on_incoming(&NowSystemInfoReqMsg::new(1).into()).unwrap();
+    state.on_outgoing(&NowSystemInfoRspMsg::new_success(1).into()).unwrap();
+
+    let violation = state
+        .on_incoming(&NowSystemInfoRspMsg::new_success(1).into())
+        .unwrap_err();
+    assert_eq!(
+        violation,
+        NowChannelViolation::UnexpectedDirection {
+            message: "NOW_SYSTEM_INFO_RSP_MSG"
+        }
+    );
+}
+
+#[test]
+fn channel_state_system_power_action() {
+    let mut state = negotiated(NowChannelRole::Server, capabilities());
+
+    let request = NowSystemPowerActionMsg::new(1, NowSystemPowerAction::SLEEP);
+    let violation = state.on_incoming(&request.clone().into()).unwrap_err();
+    assert_eq!(
+        violation,
+        NowChannelViolation::CapabilityNotNegotiated {
+            message: "NOW_SYSTEM_POWER_ACTION_MSG",
+            capability: NowCapability::System(NowSystemCapsetFlags::POWER_ACTION),
+        }
+    );
+
+    let violation = state
+        .on_incoming(&NowSystemShutdownAbortMsg::new(2).into())
+        .unwrap_err();
+    assert_eq!(
+        violation,
+        NowChannelViolation::CapabilityNotNegotiated {
+            message: "NOW_SYSTEM_SHUTDOWN_ABORT_MSG",
+            capability: NowCapability::System(NowSystemCapsetFlags::SHUTDOWN_ABORT),
+        }
+    );
+
+    let capabilities =
+        capabilities().with_system_capset(NowSystemCapsetFlags::SHUTDOWN_ABORT | NowSystemCapsetFlags::POWER_ACTION);
+    let mut state = negotiated(NowChannelRole::Server, capabilities);
+
+    state.on_incoming(&request.into()).unwrap();
+    state.on_outgoing(&NowSystemPowerRspMsg::new_success(1).into()).unwrap();
+    state.on_incoming(&NowSystemShutdownAbortMsg::new(2).into()).unwrap();
+
+    let violation = state
+        .on_incoming(&NowSystemPowerRspMsg::new_success(2).into())
+        .unwrap_err();
+    assert_eq!(
+        violation,
+        NowChannelViolation::UnexpectedDirection {
+            message: "NOW_SYSTEM_POWER_RSP_MSG"
+        }
+    );
+}
+
+#[test]
+fn channel_state_system_process() {
+    let mut state = negotiated(NowChannelRole::Client, capabilities());
+
+    let violation = state
+        .on_outgoing(&NowSystemProcessListReqMsg::new(1).into())
+        .unwrap_err();
+    assert_eq!(
+        violation,
+        NowChannelViolation::CapabilityNotNegotiated {
+            message: "NOW_SYSTEM_PROCESS_LIST_REQ_MSG",
+            capability: NowCapability::System(NowSystemCapsetFlags::PROCESS),
+        }
+    );
+
+    let capabilities = capabilities().with_system_capset(NowSystemCapsetFlags::PROCESS);
+    let mut state = negotiated(NowChannelRole::Client, capabilities);
+
+    state.on_outgoing(&NowSystemProcessListReqMsg::new(1).into()).unwrap();
+    state
+        .on_incoming(&NowSystemProcessInfoMsg::new(1, 42, "init").unwrap().into())
+        .unwrap();
+    state
+        .on_incoming(&NowSystemProcessListRspMsg::new_success(1).into())
+        .unwrap();
+    state
+        .on_outgoing(&NowSystemProcessTerminateMsg::new(2, 42).into())
+        .unwrap();
+    state
+        .on_incoming(&NowSystemProcessTerminateRspMsg::new_success(2).into())
+        .unwrap();
+
+    let violation = state
+        .on_outgoing(&NowSystemProcessInfoMsg::new(1, 42, "init").unwrap().into())
+        .unwrap_err();
+    assert_eq!(
+        violation,
+        NowChannelViolation::UnexpectedDirection {
+            message: "NOW_SYSTEM_PROCESS_INFO_MSG"
+        }
+    );
+}
+
+#[test]
+fn channel_state_file() {
+    let mut state = negotiated(
+        NowChannelRole::Server,
+        capabilities().with_file_capset(NowFileCapsetFlags::READ),
+    );
+
+    let write = NowFileOpenMsg::new(1, NowFileOpenMode::Write, "a").unwrap();
+    let violation = state.on_incoming(&write.into()).unwrap_err();
+    assert_eq!(
+        violation,
+        NowChannelViolation::CapabilityNotNegotiated {
+            message: "NOW_FILE_OPEN_MSG",
+            capability: NowCapability::File(NowFileCapsetFlags::WRITE),
+        }
+    );
+
+    let read = NowFileOpenMsg::new(1, NowFileOpenMode::Read, "a").unwrap();
+    state.on_incoming(&read.into()).unwrap();
+    state.on_outgoing(&NowFileOpenRspMsg::new_success(1, 4).into()).unwrap();
+    // Chunks are sent by the server for read transfers, and by the client for write transfers.
+    let chunk = NowFileChunkMsg::new(1, 0, true, b"data".as_slice()).unwrap();
+    state.on_outgoing(&chunk.clone().into()).unwrap();
+    state.on_incoming(&chunk.into()).unwrap();
+    state.on_incoming(&NowFileCloseMsg::new(1).into()).unwrap();
+    state.on_outgoing(&NowFileCloseRspMsg::new_success(1).into()).unwrap();
+
+    let violation = state
+        .on_incoming(&NowFileOpenRspMsg::new_success(1, 4).into())
+        .unwrap_err();
+    assert_eq!(
+        violation,
+        NowChannelViolation::UnexpectedDirection {
+            message: "NOW_FILE_OPEN_RSP_MSG"
+        }
+    );
+
+    // Without file capset, file transfers are not negotiated at all.
+    let mut state = negotiated(NowChannelRole::Server, capabilities());
+    let read = NowFileOpenMsg::new(1, NowFileOpenMode::Read, "a").unwrap();
+    assert!(matches!(
+        state.on_incoming(&read.into()).unwrap_err(),
+        NowChannelViolation::CapabilityNotNegotiated {
+            capability: NowCapability::File(NowFileCapsetFlags::READ),
+            ..
+        }
+    ));
+}
+
+#[test]
+fn channel_state_file_browse() {
+    let mut state = negotiated(
+        NowChannelRole::Server,
+        capabilities().with_file_capset(NowFileCapsetFlags::BROWSE),
+    );
+
+    state
+        .on_incoming(&NowFileListDirReqMsg::new(1, "/").unwrap().into())
+        .unwrap();
+    state
+        .on_outgoing(
+            &NowFileDirEntryMsg::new(1, NowFileEntryKind::DIRECTORY, "tmp")
+                .unwrap()
+                .into(),
+        )
+        .unwrap();
+    state.on_outgoing(&NowFileListDirRspMsg::new_success(1).into()).unwrap();
+
+    let violation = state.on_incoming(&NowFileOpRspMsg::new_success(2).into()).unwrap_err();
+    assert_eq!(
+        violation,
+        NowChannelViolation::UnexpectedDirection {
+            message: "NOW_FILE_OP_RSP_MSG"
+        }
+    );
+
+    let violation = state
+        .on_incoming(&NowFileDeleteMsg::new(2, "/tmp").unwrap().into())
+        .unwrap_err();
+    assert_eq!(
+        violation,
+        NowChannelViolation::CapabilityNotNegotiated {
+            message: "NOW_FILE_DELETE_MSG",
+            capability: NowCapability::File(NowFileCapsetFlags::MANAGE),
+        }
+    );
+}
+
+#[test]
+fn channel_state_rdm_version() {
+    let mut state = NowChannelState::new(NowChannelRole::Client, NowProtoVersion::CURRENT);
+    state.on_outgoing(&capabilities().into()).unwrap();
+
+    let server = capabilities_with_version(1, 2);
+    state.on_incoming(&server.into()).unwrap();
+
+    let request = NowRdmCapabilitiesMsg::new(0, "").unwrap();
+    let violation = state
+        .on_outgoing(&NowMessage::Rdm(NowRdmMessage::Capabilities(request)))
+        .unwrap_err();
+    assert_eq!(
+        violation,
+        NowChannelViolation::VersionNotSupported {
+            message: "NOW_RDM_CAPABILITIES_MSG",
+            required: NowProtoVersion { major: 1, minor: 3 },
+            negotiated: NowProtoVersion { major: 1, minor: 2 },
+        }
+    );
+    assert_eq!(violation.proto_error(), NowProtoError::ProtocolVersion);
+}
+
+#[test]
+fn channel_state_exec_feature_version() {
+    let mut state = NowChannelState::new(NowChannelRole::Server, NowProtoVersion::CURRENT);
+    let client = capabilities_with_version(1, 5).with_exec_capset(
+        NowExecCapsetFlags::STYLE_BATCH | NowExecCapsetFlags::IO_REDIRECTION | NowExecCapsetFlags::UNICODE_CONSOLE,
+    );
+    state.on_incoming(&client.clone().into()).unwrap();
+    state.on_outgoing(&client.into()).unwrap();
+
+    let batch = NowExecBatchMsg::new(1, "dir")
+        .unwrap()
+        .with_io_redirection()
+        .with_detached();
+    state.on_incoming(&batch.into()).unwrap();
+
+    let batch = NowExecBatchMsg::new(2, "dir").unwrap().with_unicode_console();
+    let violation = state.on_incoming(&batch.into()).unwrap_err();
+    assert_eq!(
+        violation,
+        NowChannelViolation::VersionNotSupported {
+            message: "NOW_EXEC_BATCH_MSG",
+            required: NowProtoVersion { major: 1, minor: 6 },
+            negotiated: NowProtoVersion { major: 1, minor: 5 },
+        }
+    );
+    assert_eq!(violation.proto_error(), NowProtoError::ProtocolVersion);
+}
+
+#[test]
+fn channel_state_exec_feature_capability() {
+    let mut state = negotiated(
+        NowChannelRole::Server,
+        NowChannelCapsetMsg::default().with_exec_capset(NowExecCapsetFlags::STYLE_BATCH),
+    );
+
+    state
+        .on_incoming(&NowExecBatchMsg::new(1, "dir").unwrap().into())
+        .unwrap();
+
+    let batch = NowExecBatchMsg::new(2, "dir").unwrap().with_raw_encoding();
+    assert_eq!(
+        state.on_incoming(&batch.into()).unwrap_err(),
+        NowChannelViolation::CapabilityNotNegotiated {
+            message: "NOW_EXEC_BATCH_MSG",
+            capability: NowCapability::Exec(NowExecCapsetFlags::STYLE_BATCH | NowExecCapsetFlags::UNICODE_CONSOLE),
+        }
+    );
+
+    let batch = NowExecBatchMsg::new(3, "dir").unwrap().with_io_redirection();
+    assert_eq!(
+        state.on_incoming(&batch.into()).unwrap_err(),
+        NowChannelViolation::CapabilityNotNegotiated {
+            message: "NOW_EXEC_BATCH_MSG",
+            capability: NowCapability::Exec(NowExecCapsetFlags::STYLE_BATCH | NowExecCapsetFlags::IO_REDIRECTION),
+        }
+    );
+}
+
+#[test]
+fn channel_state_system_and_file_version() {
+    let mut state = NowChannelState::new(NowChannelRole::Server, NowProtoVersion::CURRENT);
+    let client = capabilities_with_version(1, 6)
+        .with_system_capset(NowSystemCapsetFlags::SHUTDOWN | NowSystemCapsetFlags::INFO)
+        .with_file_capset(NowFileCapsetFlags::BROWSE);
+    state.on_incoming(&client.clone().into()).unwrap();
+    state.on_outgoing(&client.into()).unwrap();
+
+    let shutdown = NowSystemShutdownMsg::new(core::time::Duration::from_secs(0), "").unwrap();
+    state.on_incoming(&shutdown.into()).unwrap();
+
+    let violation = state.on_incoming(&NowSystemInfoReqMsg::new(1).into()).unwrap_err();
+    assert_eq!(
+        violation,
+        NowChannelViolation::VersionNotSupported {
+            message: "NOW_SYSTEM_INFO_REQ_MSG",
+            required: NowProtoVersion { major: 1, minor: 7 },
+            negotiated: NowProtoVersion { major: 1, minor: 6 },
+        }
+    );
+
+    let stat = NowFileStatReqMsg::new(1, "/").unwrap();
+    assert!(matches!(
+        state.on_incoming(&stat.into()),
+        Err(NowChannelViolation::VersionNotSupported { .. })
+    ));
+}
+
+#[test]
+fn channel_state_closed() {
+    let mut state = negotiated(NowChannelRole::Server, capabilities());
+
+    state.on_incoming(&NowChannelCloseMsg::default().into()).unwrap();
+    assert_eq!(state.phase(), NowChannelPhase::Closed);
+
+    let violation = state.on_incoming(&NowSessionLockMsg::default().into()).unwrap_err();
+    assert_eq!(
+        violation,
+        NowChannelViolation::ChannelClosed {
+            message: "NOW_SESSION_LOCK_MSG"
+        }
+    );
+}
+
+#[test]
+fn channel_state_unexpected_capset_after_negotiation() {
+    let mut state = negotiated(NowChannelRole::Server, capabilities());
+
+    let violation = state.on_incoming(&capabilities().into()).unwrap_err();
+    assert_eq!(violation, NowChannelViolation::UnexpectedCapset);
+
+    let close = violation.to_close_msg().unwrap();
+    assert_eq!(
+        close.to_result().unwrap_err().kind(),
+        NowStatusErrorKind::Now(NowProtoError::InvalidRequest)
+    );
+}
@@ -0,0 +1,267 @@
+use futures_util::{SinkExt as _, StreamExt as _};
+use now_proto_client::*;
+use now_proto_pdu::*;
+use tokio::io::DuplexStream;
+use tokio_util::codec::Framed;
+
+type ServerChannel = Framed<DuplexStream, NowMessageCodec>;
+
+fn server_capabilities() -> NowChannelCapsetMsg {
+    NowChannelCapsetMsg::default()
+        .with_session_capset(NowSessionCapsetFlags::LOCK | NowSessionCapsetFlags::MSGBOX)
+        .with_exec_capset(NowExecCapsetFlags::STYLE_SHELL | NowExecCapsetFlags::IO_REDIRECTION)
+}
+
+async fn connect() -> (NowClient, ServerChannel) {
+    connect_with_capabilities(server_capabilities()).await
+}
+
+async fn connect_with_capabilities(server_capabilities: NowChannelCapsetMsg) -> (NowClient, ServerChannel) {
+    let (client, server) = tokio::io::duplex(1024);
+    let mut server = Framed::new(server, NowMessageCodec::new());
+
+    let server_task = tokio::spawn(async move {
+        let client_capabilities = match server.next().await.unwrap().unwrap() {
+            NowMessage::Channel(NowChannelMessage::Capset(capabilities)) => capabilities,
+            other => panic!("unexpected message: {other:?}"),
+        };
+
+        let capabilities = server_capabilities.downgrade(&client_capabilities);
+        server.send(capabilities.into()).await.unwrap();
+
+        server
+    });
+
+    let client = NowClient::connect(client).await.unwrap();
+    let server = server_task.await.unwrap();
+
+    (client, server)
+}
+
+async fn next_message(server: &mut ServerChannel) -> OwnedNowMessage {
+    server.next().await.unwrap().unwrap()
+}
+
+#[tokio::test]
+async fn client_negotiates_capabilities() {
+    let (client, _server) = connect().await;
+
+    let capabilities = client.capabilities();
+    assert_eq!(capabilities.version(), NowProtoVersion::CURRENT);
+    assert_eq!(capabilities.system_capset(), NowSystemCapsetFlags::empty());
+    assert_eq!(
+        capabilities.session_capset(),
+        NowSessionCapsetFlags::LOCK | NowSessionCapsetFlags::MSGBOX
+    );
+    assert_eq!(
+        capabilities.heartbeat_interval(),
+        Some(core::time::Duration::from_secs(60))
+    );
+}
+
+#[tokio::test]
+async fn client_rejects_unsupported_command() {
+    let (client, _server) = connect().await;
+
+    assert!(matches!(
+        client.system_info().await,
+        Err(NowClientError::Unsupported(_))
+    ));
+    assert!(matches!(
+        client.system_shutdown_abort().await,
+        Err(NowClientError::Unsupported(_))
+    ));
+    assert!(matches!(
+        client
+            .system_power_action(|id| Ok(NowSystemPowerActionMsg::new(id, NowSystemPowerAction::SLEEP)))
+            .await,
+        Err(NowClientError::Unsupported(_))
+    ));
+    assert!(matches!(
+        client.system_process_list().await,
+        Err(NowClientError::Unsupported(_))
+    ));
+    assert!(matches!(
+        client.system_process_terminate(1, false).await,
+        Err(NowClientError::Unsupported(_))
+    ));
+    assert!(matches!(
+        client.session_logoff().await,
+        Err(NowClientError::Unsupported(_))
+    ));
+    assert!(matches!(
+        client.exec_pwsh(|id| NowExecPwshMsg::new(id, "Get-Date")).await,
+        Err(NowClientError::Unsupported(_))
+    ));
+    assert!(matches!(
+        client
+            .file_open(|id| NowFileOpenMsg::new(id, NowFileOpenMode::Read, "a"))
+            .await,
+        Err(NowClientError::Unsupported(_))
+    ));
+    assert!(matches!(
+        client.file_stat("a").await,
+        Err(NowClientError::Unsupported(_))
+    ));
+    assert!(matches!(
+        client.file_mkdir("a", false).await,
+        Err(NowClientError::Unsupported(_))
+    ));
+}
+
+#[tokio::test]
+async fn client_system_info_matches_response_by_request_id() {
+    let capabilities = server_capabilities().with_system_capset(NowSystemCapsetFlags::INFO);
+    let (client, mut server) = connect_with_capabilities(capabilities).await;
+
+    let server_task = tokio::spawn(async move {
+        let mut request_ids = Vec::new();
+        for _ in 0..2 {
+            match next_message(&mut server).await {
+                NowMessage::System(NowSystemMessage::InfoReq(request)) => request_ids.push(request.request_id()),
+                other => panic!("unexpected message: {other:?}"),
+            }
+        }
+
+        assert_ne!(request_ids[0], request_ids[1]);
+
+        // Responses are sent in the reverse order of the requests.
+        for request_id in request_ids.into_iter().rev() {
+            let response = NowSystemInfoRspMsg::new_success(request_id)
+                .with_hostname(format!("host-{request_id}"))
+                .unwrap();
+            server.send(response.into()).await.unwrap();
+        }
+    });
+
+    let (first, second) = tokio::join!(client.system_info(), client.system_info());
+    let (first, second) = (first.unwrap(), second.unwrap());
+
+    assert_ne!(first.request_id(), second.request_id());
+    for response in [first, second] {
+        let expected = format!("host-{}", response.request_id());
+        assert_eq!(response.hostname(), Some(expected.as_str()));
+    }
+
+    server_task.await.unwrap();
+}
+
+#[tokio::test]
+async fn client_session_lock() {
+    let (client, mut server) = connect().await;
+
+    client.session_lock().await.unwrap();
+
+    assert_eq!(next_message(&mut server).await, NowSessionLockMsg::default().into());
+}
+
+#[tokio::test]
+async fn client_msg_box() {
+    let (client, mut server) = connect().await;
+
+    let server_task = tokio::spawn(async move {
+        let request = match next_message(&mut server).await {
+            NowMessage::Session(NowSessionMessage::MsgBoxReq(request)) => request,
+            other => panic!("unexpected message: {other:?}"),
+        };
+
+        assert_eq!(request.message(), "Hello");
+        assert_eq!(request.title(), Some("World"));
+        assert!(request.is_response_expected());
+
+        let response = NowSessionMsgBoxRspMsg::new_success(request.request_id(), NowMsgBoxResponse::YES);
+        server.send(response.into()).await.unwrap();
+    });
+
+    let response = client
+        .msg_box(|id| NowSessionMsgBoxReqMsg::new(id, "Hello")?.with_title("World"))
+        .await
+        .unwrap();
+
+    assert_eq!(response, NowMsgBoxResponse::YES);
+    server_task.await.unwrap();
+}
+
+#[tokio::test]
+async fn client_msg_box_no_response() {
+    let (client, mut server) = connect().await;
+
+    client
+        .msg_box_no_response(|id| NowSessionMsgBoxReqMsg::new(id, "Hello"))
+        .await
+        .unwrap();
+
+    let request = match next_message(&mut server).await {
+        NowMessage::Session(NowSessionMessage::MsgBoxReq(request)) => request,
+        other => panic!("unexpected message: {other:?}"),
+    };
+
+    assert_eq!(request.message(), "Hello");
+    assert!(!request.is_response_expected());
+}
+
+#[tokio::test]
+async fn client_exec_shell() {
+    let (client, mut server) = connect().await;
+
+    let server_task = tokio::spawn(async move {
+        let request = match next_message(&mut server).await {
+            NowMessage::Exec(NowExecMessage::Shell(request)) => request,
+            other => panic!("unexpected message: {other:?}"),
+        };
+
+        assert_eq!(request.command(), "echo hello");
+
+        let session_id = request.session_id();
+        let stdout = NowExecDataMsg::new(session_id, NowExecDataStreamKind::Stdout, true, b"hello\n".as_slice());
+
+        server.send(NowExecStartedMsg::new(session_id).into()).await.unwrap();
+        server.send(stdout.unwrap().into()).await.unwrap();
+        server
+            .send(NowExecResultMsg::new_success(session_id, 0).into())
+            .await
+            .unwrap();
+    });
+
+    let mut session = client
+        .exec_shell(|id| Ok(NowExecShellMsg::new(id, "echo hello")?.with_io_redirection()))
+        .await
+        .unwrap();
+
+    assert_eq!(session.next_event().await, Some(NowExecEvent::Started));
+    assert_eq!(
+        session.next_event().await,
+        Some(NowExecEvent::Stdout {
+            data: b"hello\n".to_vec(),
+            last: true
+        })
+    );
+    assert_eq!(session.wait().await.unwrap(), 0);
+
+    server_task.await.unwrap();
+}
+
+#[tokio::test]
+async fn client_close() {
+    let (client, mut server) = connect().await;
+
+    client.close().await.unwrap();
+
+    assert_eq!(next_message(&mut server).await, NowChannelCloseMsg::default().into());
+}
+
+#[tokio::test]
+async fn client_remote_close_with_error() {
+    let (client, mut server) = connect().await;
+
+    let error = NowStatusError::from(NowProtoError::NotImplemented);
+    server
+        .send(NowChannelCloseMsg::from_error(error).unwrap().into())
+        .await
+        .unwrap();
+
+    // Transport is closed once the client worker has processed the close message.
+    assert!(server.next().await.is_none());
+
+    assert!(matches!(client.close().await, Err(NowClientError::Status(_))));
+}
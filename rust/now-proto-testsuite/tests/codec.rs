@@ -0,0 +1,76 @@
+use bytes::BytesMut;
+use futures_util::{SinkExt as _, StreamExt as _};
+use now_proto_pdu::ironrdp_core::encode_vec;
+use now_proto_pdu::*;
+use tokio_util::codec::{Decoder as _, Encoder as _, Framed};
+
+#[test]
+fn codec_decode_partial_frame() {
+    let msg = NowExecDataMsg::new(0x42, NowExecDataStreamKind::Stdout, true, b"output".as_slice()).unwrap();
+    let bytes = encode_vec(&msg).unwrap();
+
+    let mut codec = NowMessageCodec::new();
+    let mut buf = BytesMut::new();
+
+    buf.extend_from_slice(&bytes[..5]);
+    assert!(codec.decode(&mut buf).unwrap().is_none());
+
+    buf.extend_from_slice(&bytes[5..bytes.len() - 1]);
+    assert!(codec.decode(&mut buf).unwrap().is_none());
+
+    buf.extend_from_slice(&bytes[bytes.len() - 1..]);
+    assert_eq!(codec.decode(&mut buf).unwrap().unwrap(), NowMessage::from(msg));
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn codec_encode() {
+    let msg = NowExecStartedMsg::new(0x1234);
+
+    let mut codec = NowMessageCodec::new();
+    let mut buf = BytesMut::from(&b"prefix"[..]);
+
+    codec.encode(NowMessage::from(msg.clone()), &mut buf).unwrap();
+
+    assert_eq!(&buf[..6], b"prefix");
+    assert_eq!(&buf[6..], encode_vec(&msg).unwrap().as_slice());
+}
+
+#[test]
+fn codec_rejects_oversized_frame() {
+    let msg = NowExecDataMsg::new(1, NowExecDataStreamKind::Stdout, false, vec![0u8; 64]).unwrap();
+    let bytes = encode_vec(&msg).unwrap();
+
+    let mut codec = NowMessageCodec::new().with_max_frame_size(32);
+    let mut buf = BytesMut::from(&bytes[..8]);
+
+    assert!(matches!(codec.decode(&mut buf), Err(NowCodecError::Decode(_))));
+}
+
+#[tokio::test]
+async fn codec_framed_duplex() {
+    let (client, server) = tokio::io::duplex(16);
+
+    let mut client = Framed::new(client, NowMessageCodec::new());
+    let mut server = Framed::new(server, NowMessageCodec::new());
+
+    let capset = NowChannelCapsetMsg::default().with_exec_capset(NowExecCapsetFlags::STYLE_SHELL);
+    let shell = NowExecShellMsg::new(1, "echo hello").unwrap().with_io_redirection();
+
+    let writer = tokio::spawn(async move {
+        client.send(capset.into()).await.unwrap();
+        client.send(shell.into()).await.unwrap();
+        client
+    });
+
+    let received = server.next().await.unwrap().unwrap();
+    assert!(matches!(received, NowMessage::Channel(NowChannelMessage::Capset(_))));
+
+    let received = server.next().await.unwrap().unwrap();
+    match received {
+        NowMessage::Exec(NowExecMessage::Shell(msg)) => assert_eq!(msg.command(), "echo hello"),
+        _ => panic!("Expected NowExecShellMsg"),
+    }
+
+    writer.await.unwrap();
+}
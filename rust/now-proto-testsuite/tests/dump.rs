@@ -0,0 +1,123 @@
+use now_proto_pdu::ironrdp_core::encode_vec;
+use now_proto_pdu::*;
+
+fn dump(data: &[u8]) -> (String, now_dump::NowDumpSummary) {
+    let mut output = String::new();
+    let summary = now_dump::dump_capture(data, &mut output);
+    (output, summary)
+}
+
+#[test]
+fn dump_fields_with_offsets_and_flag_names() {
+    let mut capture = encode_vec(&NowMessage::from(NowChannelHeartbeatMsg::default())).unwrap();
+    capture.extend_from_slice(
+        &encode_vec(&NowMessage::from(
+            NowExecDataMsg::new(7, NowExecDataStreamKind::Stderr, true, b"oops".as_slice()).unwrap(),
+        ))
+        .unwrap(),
+    );
+
+    let (output, summary) = dump(&capture);
+
+    assert!(summary.is_clean());
+    assert_eq!(summary.frames, 2);
+    assert!(output.contains("00000000  #0 NOW_CHANNEL_HEARTBEAT_MSG (8 bytes)"));
+    assert!(output.contains("00000008  #1 NOW_EXEC_DATA_MSG (17 bytes)"));
+    assert!(output.contains("0000000e      msgFlags: 0x0009 [LAST | STDERR]"));
+    assert!(output.contains("00000010      sessionId: 7"));
+    assert!(output.contains("00000014      data: 4 bytes"));
+}
+
+#[test]
+fn dump_status_fields() {
+    let msg = NowChannelCloseMsg::from_error(NowProtoError::NotImplemented).unwrap();
+    let capture = encode_vec(&NowMessage::from(msg)).unwrap();
+
+    let (output, summary) = dump(&capture);
+
+    assert!(summary.is_clean());
+    assert!(output.contains("flags: 0x0001 [ERROR]"));
+    assert!(output.contains("kind: 1 (NOW_STATUS_ERROR_KIND_NOW)"));
+    assert!(output.contains("code: 7 (NOW_CODE_NOT_IMPLEMENTED)"));
+}
+
+#[test]
+fn dump_optional_capset_field() {
+    let mut capture = encode_vec(&NowMessage::from(NowChannelCapsetMsg::default())).unwrap();
+    capture.extend_from_slice(
+        &encode_vec(&NowMessage::from(
+            NowChannelCapsetMsg::default().with_file_capset(NowFileCapsetFlags::READ),
+        ))
+        .unwrap(),
+    );
+
+    let (output, summary) = dump(&capture);
+
+    assert!(summary.is_clean());
+    assert!(output.contains("00000016      fileCapset: <absent>"));
+    assert!(output.contains("0000001c      msgFlags: 0x0002 [SET_FILE_CAPSET]"));
+    assert!(output.contains("0000002c      fileCapset: 0x0001 [READ]"));
+}
+
+#[test]
+fn dump_file_dir_entry() {
+    let msg = NowFileDirEntryMsg::new(3, NowFileEntryKind::FILE, ".profile")
+        .unwrap()
+        .with_file_size(42)
+        .with_attributes(NowFileAttributes::HIDDEN | NowFileAttributes::READONLY);
+    let capture = encode_vec(&NowMessage::from(msg)).unwrap();
+
+    let (output, summary) = dump(&capture);
+
+    assert!(summary.is_clean());
+    assert!(output.contains("NOW_FILE_DIR_ENTRY_MSG"));
+    assert!(output.contains("00000010      attributes: 0x00000003 [READONLY | HIDDEN]"));
+    assert!(output.contains("00000014      fileSize: 42"));
+    assert!(output.contains("fileName: \".profile\""));
+}
+
+#[test]
+fn dump_undecodable_frame() {
+    // NOW_EXEC_RUN_MSG with invalid UTF-8 command, followed by unknown exec message.
+    let capture = now_dump::parse_hex(
+        "07000000 1310 0000 01000000 01ff00\n\
+         01000000 1399 0000 05",
+    )
+    .unwrap();
+
+    let (output, summary) = dump(&capture);
+
+    assert_eq!(summary.frames, 2);
+    assert_eq!(summary.undecodable, 2);
+    assert!(output.contains("!! undecodable frame:"));
+    assert!(output.contains("invalid utf-8"));
+    assert!(output.contains("00000008      sessionId: 1"));
+    assert!(output.contains("0000000f    !! undecodable frame:"));
+    assert!(output.contains("invalid message type"));
+}
+
+#[test]
+fn dump_truncated_frame() {
+    let capture = encode_vec(&NowMessage::from(NowExecStartedMsg::new(1))).unwrap();
+
+    let (output, summary) = dump(&capture[..capture.len() - 1]);
+
+    assert_eq!(summary.frames, 0);
+    assert_eq!(summary.truncated, capture.len() - 1);
+    assert!(output.contains("!! truncated frame: 11 of 12 bytes"));
+}
+
+#[test]
+fn dump_parse_hex() {
+    let text = "# heartbeat\n00000000: 00 00 00 00\n00000004: 0x10, 0x02, 0000\n";
+
+    assert!(now_dump::looks_like_hex(text.as_bytes()));
+    assert!(!now_dump::looks_like_hex(&[
+        0x00, 0x00, 0x00, 0x00, 0x10, 0x02, 0x00, 0x00
+    ]));
+    assert_eq!(
+        now_dump::parse_hex(text).unwrap(),
+        [0x00, 0x00, 0x00, 0x00, 0x10, 0x02, 0x00, 0x00]
+    );
+    assert!(now_dump::parse_hex("123").is_err());
+}
@@ -0,0 +1,199 @@
+use now_proto_pdu::ironrdp_core::{encode_vec, DecodeErrorKind};
+use now_proto_pdu::*;
+
+fn encoded_messages() -> (Vec<NowMessage<'static>>, Vec<u8>) {
+    let messages: Vec<NowMessage<'static>> = vec![
+        NowChannelHeartbeatMsg::default().into(),
+        NowExecDataMsg::new(0x1234, NowExecDataStreamKind::Stdout, false, b"hello".as_slice())
+            .unwrap()
+            .into(),
+        NowSessionLockMsg::default().into(),
+    ];
+
+    let mut bytes = Vec::new();
+    for message in &messages {
+        bytes.extend_from_slice(&encode_vec(message).unwrap());
+    }
+
+    (messages, bytes)
+}
+
+#[test]
+fn framer_multiple_messages_in_single_chunk() {
+    let (messages, bytes) = encoded_messages();
+
+    let mut framer = NowMessageFramer::new();
+    framer.push(&bytes).unwrap();
+
+    for expected in messages {
+        assert_eq!(framer.bytes_needed().unwrap(), 0);
+        assert_eq!(framer.next_owned_message().unwrap().unwrap(), expected);
+    }
+
+    assert!(framer.next_message().unwrap().is_none());
+    assert_eq!(framer.buffered_len(), 0);
+}
+
+#[test]
+fn framer_byte_by_byte() {
+    let (messages, bytes) = encoded_messages();
+
+    let mut framer = NowMessageFramer::new();
+    let mut decoded = Vec::new();
+
+    for byte in bytes {
+        assert_ne!(framer.bytes_needed().unwrap(), 0);
+        framer.push(&[byte]).unwrap();
+
+        while let Some(message) = framer.next_owned_message().unwrap() {
+            decoded.push(message);
+        }
+    }
+
+    assert_eq!(decoded, messages);
+}
+
+#[test]
+fn framer_bytes_needed() {
+    let msg = NowExecDataMsg::new(1, NowExecDataStreamKind::Stdin, true, b"abc".as_slice()).unwrap();
+    let bytes = encode_vec(&msg).unwrap();
+
+    let mut framer = NowMessageFramer::new();
+    assert_eq!(framer.bytes_needed().unwrap(), 8);
+
+    framer.push(&bytes[..3]).unwrap();
+    assert_eq!(framer.bytes_needed().unwrap(), 5);
+
+    framer.push(&bytes[3..10]).unwrap();
+    assert_eq!(framer.bytes_needed().unwrap(), bytes.len() - 10);
+    assert!(framer.next_frame().unwrap().is_none());
+
+    framer.push(&bytes[10..]).unwrap();
+    assert_eq!(framer.bytes_needed().unwrap(), 0);
+    assert_eq!(framer.next_frame().unwrap().unwrap(), bytes.as_slice());
+}
+
+#[test]
+fn framer_borrowed_message() {
+    let msg = NowExecDataMsg::new(7, NowExecDataStreamKind::Stderr, false, b"data".as_slice()).unwrap();
+    let bytes = encode_vec(&msg).unwrap();
+
+    let mut framer = NowMessageFramer::new();
+    framer.push(&bytes).unwrap();
+
+    match framer.next_message().unwrap().unwrap() {
+        NowMessage::Exec(NowExecMessage::Data(data)) => assert_eq!(data.data(), b"data"),
+        _ => panic!("Expected NowExecDataMsg"),
+    }
+}
+
+#[test]
+fn framer_rejects_oversized_frame() {
+    let msg = NowExecDataMsg::new(1, NowExecDataStreamKind::Stdout, false, vec![0u8; 64]).unwrap();
+    let bytes = encode_vec(&msg).unwrap();
+
+    let mut framer = NowMessageFramer::new().with_max_frame_size(32);
+
+    // Header is incomplete, size can't be validated yet.
+    framer.push(&bytes[..4]).unwrap();
+
+    let err = framer.push(&bytes[4..]).unwrap_err();
+    assert!(matches!(
+        err.kind(),
+        DecodeErrorKind::InvalidField { field: "size", .. }
+    ));
+
+    // Data is not buffered on error.
+    assert_eq!(framer.buffered_len(), 4);
+}
+
+#[test]
+fn framer_rejects_oversized_frame_after_complete_frame() {
+    let mut bytes = encode_vec(&NowChannelHeartbeatMsg::default()).unwrap();
+    let heartbeat_len = bytes.len();
+
+    let msg = NowExecDataMsg::new(1, NowExecDataStreamKind::Stdout, false, vec![0u8; 64]).unwrap();
+    bytes.extend_from_slice(&encode_vec(&msg).unwrap()[..40]);
+
+    let mut framer = NowMessageFramer::new().with_max_frame_size(32);
+
+    // Every header in the chunk is validated, not only the first one.
+    let err = framer.push(&bytes).unwrap_err();
+    assert!(matches!(
+        err.kind(),
+        DecodeErrorKind::InvalidField { field: "size", .. }
+    ));
+    assert_eq!(framer.buffered_len(), 0);
+
+    // Header split between the buffered data and the next chunk is validated as well.
+    framer.push(&bytes[..heartbeat_len + 4]).unwrap();
+    framer.push(&bytes[heartbeat_len + 4..]).unwrap_err();
+    assert_eq!(framer.buffered_len(), heartbeat_len + 4);
+}
+
+#[test]
+fn framer_applies_decode_limits() {
+    let msg = NowExecDataMsg::new(1, NowExecDataStreamKind::Stdout, false, vec![0u8; 64]).unwrap();
+    let bytes = encode_vec(&msg).unwrap();
+
+    let limits = NowDecodeLimits::new().with_max_message_size(32);
+    let mut framer = NowMessageFramer::new().with_decode_options(NowDecodeOptions::new().with_limits(limits));
+
+    // Message size is validated as soon as the header is received.
+    let err = framer.push(&bytes).unwrap_err();
+    assert!(NowDecodeLimits::is_limit_exceeded(&err));
+    assert_eq!(
+        NowDecodeLimits::exceeded_limit(&err),
+        Some(&NowDecodeLimitError::MessageSize { size: 70, limit: 32 })
+    );
+    assert_eq!(framer.buffered_len(), 0);
+
+    let limits = NowDecodeLimits::new().with_max_buffer_len(32);
+    let mut framer = NowMessageFramer::new().with_decode_options(NowDecodeOptions::new().with_limits(limits));
+    framer.push(&bytes).unwrap();
+
+    let err = framer.next_message().unwrap_err();
+    assert!(NowDecodeLimits::is_limit_exceeded(&err));
+    assert_eq!(
+        NowDecodeLimits::exceeded_limit(&err),
+        Some(&NowDecodeLimitError::BufferLength { len: 64, limit: 32 })
+    );
+
+    // Frame is consumed, the next one could be received.
+    assert!(framer.next_message().unwrap().is_none());
+}
+
+#[test]
+fn framer_skips_undecodable_frame() {
+    // Unknown message class followed by a valid message.
+    let mut bytes = vec![0x02, 0x00, 0x00, 0x00, 0x7F, 0x01, 0x00, 0x00, 0xAA, 0xBB];
+    bytes.extend_from_slice(&encode_vec(&NowChannelHeartbeatMsg::default()).unwrap());
+
+    let mut framer = NowMessageFramer::new();
+    framer.push(&bytes).unwrap();
+
+    assert!(framer.next_message().is_err());
+    assert_eq!(
+        framer.next_owned_message().unwrap().unwrap(),
+        NowMessage::from(NowChannelHeartbeatMsg::default())
+    );
+}
+
+#[test]
+fn framer_passes_unknown_messages_through() {
+    let unknown = [0x02, 0x00, 0x00, 0x00, 0x7F, 0x01, 0x00, 0x00, 0xAA, 0xBB];
+    let mut bytes = unknown.to_vec();
+    bytes.extend_from_slice(&encode_vec(&NowChannelHeartbeatMsg::default()).unwrap());
+
+    let mut framer = NowMessageFramer::new().with_decode_options(NowDecodeOptions::new().with_unknown_messages());
+    framer.push(&bytes).unwrap();
+
+    let msg = framer.next_message().unwrap().unwrap();
+    assert!(matches!(msg, NowMessage::Unknown { .. }));
+    assert_eq!(encode_vec(&msg).unwrap(), unknown);
+
+    assert_eq!(
+        framer.next_owned_message().unwrap().unwrap(),
+        NowMessage::from(NowChannelHeartbeatMsg::default())
+    );
+}
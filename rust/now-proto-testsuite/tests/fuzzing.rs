@@ -0,0 +1,125 @@
+use arbitrary::{Arbitrary as _, Unstructured};
+use now_proto_fuzzing::generators::{NowFrameInput, NowMessageInput};
+use now_proto_fuzzing::oracles;
+use now_proto_pdu::*;
+
+/// Deterministic pseudo-random fuzzer input (xorshift64).
+fn fuzzer_input(seed: u64, len: usize) -> Vec<u8> {
+    let mut state = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state.to_le_bytes()[0]
+        })
+        .collect()
+}
+
+fn generate<T: for<'a> arbitrary::Arbitrary<'a>>(seed: u64) -> T {
+    let input = fuzzer_input(seed, 512);
+    T::arbitrary(&mut Unstructured::new(&input)).expect("generator never rejects input")
+}
+
+#[test]
+fn generated_frames_have_consistent_header() {
+    for seed in 0..500 {
+        let NowFrameInput { frame } = generate(seed);
+
+        let body_size = u32::from_le_bytes(frame[..4].try_into().unwrap());
+        assert_eq!(body_size as usize + 8, frame.len(), "seed {seed}");
+    }
+}
+
+#[test]
+fn generated_messages_cover_all_classes() {
+    let mut seen = [false; 6];
+    let mut invalid_stream_kind = false;
+
+    for seed in 0..500 {
+        let NowMessageInput { message } = generate(seed);
+
+        let class = match &message {
+            NowMessage::Channel(_) => 0,
+            NowMessage::System(_) => 1,
+            NowMessage::Session(_) => 2,
+            NowMessage::Exec(msg) => {
+                if let NowExecMessage::Data(msg) = msg {
+                    invalid_stream_kind |= msg.stream_kind().is_err();
+                }
+                3
+            }
+            NowMessage::Rdm(_) => 4,
+            NowMessage::File(_) => 5,
+            NowMessage::Unknown { .. } | NowMessage::Extension(_) => {
+                panic!("unknown and extension messages are never generated")
+            }
+        };
+
+        seen[class] = true;
+    }
+
+    assert_eq!(seen, [true; 6]);
+    assert!(invalid_stream_kind);
+}
+
+#[test]
+fn generated_empty_input() {
+    // Fuzzers often start from an empty corpus; the generator should still produce a message.
+    let NowMessageInput { message } = NowMessageInput::arbitrary(&mut Unstructured::new(&[])).unwrap();
+
+    assert!(matches!(message, NowMessage::Channel(NowChannelMessage::Capset(_))));
+}
+
+#[test]
+fn oracles_on_generated_messages() {
+    for seed in 0..500 {
+        let input: NowMessageInput = generate(seed);
+
+        oracles::message_roundtrip(&input);
+        oracles::message_size(&input);
+    }
+}
+
+#[test]
+fn oracles_on_generated_frames() {
+    for seed in 0..500 {
+        let input: NowFrameInput = generate(seed);
+
+        oracles::frame_reencode(&input);
+        oracles::message_decode(&input.frame);
+    }
+}
+
+#[test]
+fn oracles_on_mutated_frames() {
+    for seed in 0..500 {
+        let NowFrameInput { mut frame } = generate(seed);
+
+        // Truncated frame
+        oracles::message_decode(&frame[..frame.len() / 2]);
+
+        // Corrupted body byte
+        let mutation = fuzzer_input(seed, 2);
+        if frame.len() > 8 {
+            let index = 8 + usize::from(mutation[0]) % (frame.len() - 8);
+            frame[index] ^= mutation[1] | 1;
+        }
+        oracles::message_decode(&frame);
+    }
+}
+
+#[test]
+fn oracles_on_random_input() {
+    for seed in 0..500 {
+        let mut input = fuzzer_input(seed, 64);
+        // Make the header consistent and point it at one of the known message classes, so that
+        // random data actually reaches message body decoding.
+        input[..4].copy_from_slice(&56u32.to_le_bytes());
+        input[4] = 0x10 + input[4] % 5;
+        input[5] %= 0x16;
+
+        oracles::message_decode(&input);
+    }
+}
@@ -12,4 +12,14 @@
 //! Cargo will run all tests from a single binary in parallel, but
 //! binaries themselves are run sequentally.
 
+#[cfg(unix)]
+mod agent;
+mod channel;
+mod client;
+mod codec;
+mod dump;
+mod framer;
+mod fuzzing;
 mod proto;
+mod serde;
+mod server;
@@ -15,7 +15,7 @@ fn roundtrip_channel_capset() {
 
     let decoded = now_msg_roundtrip(
         msg,
-        expect!["[0E, 00, 00, 00, 10, 01, 01, 00, 01, 00, 06, 00, 01, 00, 04, 00, 05, 00, 2C, 01, 00, 00]"],
+        expect!["[0E, 00, 00, 00, 10, 01, 01, 00, 01, 00, 07, 00, 01, 00, 04, 00, 05, 00, 2C, 01, 00, 00]"],
     );
 
     let actual = match decoded {
@@ -40,7 +40,7 @@ fn roundtrip_channel_capset_simple() {
 
     let decoded = now_msg_roundtrip(
         msg,
-        expect!["[0E, 00, 00, 00, 10, 01, 00, 00, 01, 00, 06, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00]"],
+        expect!["[0E, 00, 00, 00, 10, 01, 00, 00, 01, 00, 07, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00]"],
     );
 
     let actual = match decoded {
@@ -54,6 +54,40 @@ fn roundtrip_channel_capset_simple() {
     assert!(actual.heartbeat_interval().is_none());
 }
 
+#[test]
+fn roundtrip_channel_capset_file_capset() {
+    let msg = NowChannelCapsetMsg::default().with_file_capset(NowFileCapsetFlags::READ | NowFileCapsetFlags::WRITE);
+
+    let decoded = now_msg_roundtrip(
+        msg,
+        expect!["[10, 00, 00, 00, 10, 01, 02, 00, 01, 00, 07, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 03, 00]"],
+    );
+
+    let actual = match decoded {
+        NowMessage::Channel(NowChannelMessage::Capset(msg)) => msg,
+        _ => panic!("Expected NowChannelCapsetMsg"),
+    };
+
+    assert_eq!(
+        actual.file_capset(),
+        NowFileCapsetFlags::READ | NowFileCapsetFlags::WRITE
+    );
+}
+
+#[test]
+fn channel_capset_file_capset_downgrade() {
+    let client = NowChannelCapsetMsg::default().with_file_capset(NowFileCapsetFlags::all());
+    let server = NowChannelCapsetMsg::default().with_file_capset(NowFileCapsetFlags::READ);
+
+    assert_eq!(client.downgrade(&server).file_capset(), NowFileCapsetFlags::READ);
+
+    // Peers without file transfer support do not send the file capset at all.
+    assert!(client
+        .downgrade(&NowChannelCapsetMsg::default())
+        .file_capset()
+        .is_empty());
+}
+
 #[test]
 fn roundtrip_channel_capset_too_small_heartbeat_interval() {
     // Sanity check should fail
@@ -1,4 +1,5 @@
 use expect_test::expect;
+use now_proto_pdu::ironrdp_core::{decode, encode_vec, DecodeOwned as _, ReadCursor};
 use now_proto_pdu::*;
 use now_proto_testsuite::proto::{now_msg_decodes_into, now_msg_roundtrip};
 
@@ -148,7 +149,7 @@ fn roundtrip_exec_data_empty() {
     assert_eq!(actual.session_id(), 0x12345678);
     assert_eq!(actual.stream_kind().unwrap(), NowExecDataStreamKind::Stdin);
     assert!(!actual.is_last());
-    assert_eq!(actual.data(), &[]);
+    assert_eq!(actual.data(), b"");
 }
 
 #[test]
@@ -444,3 +445,25 @@ fn roundtrip_exec_pwsh_simple() {
     assert!(actual.execution_policy().is_none());
     assert!(actual.configuration_name().is_none());
 }
+
+#[test]
+fn exec_message_decode_checks_message_type() {
+    let shell = encode_vec(&NowExecShellMsg::new(0x12345678, "a").unwrap()).unwrap();
+
+    assert!(decode::<NowExecShellMsg<'_>>(&shell).is_ok());
+    assert!(decode::<NowExecBatchMsg<'_>>(&shell).is_err());
+    assert!(decode::<NowExecStartedMsg>(&shell).is_err());
+}
+
+#[test]
+fn exec_message_decode_owned() {
+    let msg = NowExecRunMsg::new(0x12345678, "a")
+        .unwrap()
+        .with_directory("d")
+        .unwrap();
+    let encoded = encode_vec(&msg).unwrap();
+
+    let owned = OwnedNowExecRunMsg::decode_owned(&mut ReadCursor::new(&encoded)).unwrap();
+
+    assert_eq!(owned, msg);
+}
@@ -0,0 +1,158 @@
+use now_proto_pdu::ironrdp_core::{decode, encode_vec, DecodeResult, ReadCursor};
+use now_proto_pdu::*;
+use now_proto_testsuite::proto::extension::{DiagnosticsPingMsg, DIAGNOSTICS_CLASS, DIAGNOSTICS_REGISTRY};
+use rstest::rstest;
+
+fn decode_with(frame: &[u8], options: NowDecodeOptions) -> DecodeResult<NowMessage<'_>> {
+    NowMessage::decode_with_options(&mut ReadCursor::new(frame), options)
+}
+
+fn ping_frame() -> Vec<u8> {
+    encode_vec(&DiagnosticsPingMsg::new(7, "hello").unwrap()).unwrap()
+}
+
+#[test]
+fn extension_message_roundtrip() {
+    let frame = ping_frame();
+    assert_eq!(
+        frame,
+        [
+            0x0B, 0x00, 0x00, 0x00, 0x40, 0x01, 0x00, 0x00, 0x07, 0x00, 0x00, 0x00, 0x05, b'h', b'e', b'l', b'l', b'o',
+            0x00
+        ]
+    );
+
+    let msg = decode_with(&frame, NowDecodeOptions::new().with_extensions(&DIAGNOSTICS_REGISTRY)).unwrap();
+
+    let NowMessage::Extension(extension) = &msg else {
+        panic!("unexpected message: {msg:?}");
+    };
+
+    let ping = extension.downcast_ref::<DiagnosticsPingMsg>().unwrap();
+    assert_eq!(ping.sequence, 7);
+    assert_eq!(ping.note.as_str(), "hello");
+
+    assert_eq!(msg, NowMessage::from(NowExtensionMsg::new(ping.clone())));
+    assert_eq!(encode_vec(&msg).unwrap(), frame);
+}
+
+#[test]
+fn extension_message_equality() {
+    let ping = NowExtensionMsg::new(DiagnosticsPingMsg::new(1, "a").unwrap());
+
+    assert_eq!(ping, NowExtensionMsg::new(DiagnosticsPingMsg::new(1, "a").unwrap()));
+    assert_ne!(ping, NowExtensionMsg::new(DiagnosticsPingMsg::new(2, "a").unwrap()));
+    assert_ne!(ping, NowExtensionMsg::new(NowChannelHeartbeatMsg::default()));
+}
+
+#[test]
+fn extension_class_not_registered() {
+    let frame = ping_frame();
+
+    assert!(decode::<NowMessage<'_>>(&frame).is_err());
+    assert!(matches!(
+        decode_with(&frame, NowDecodeOptions::new().with_unknown_messages()).unwrap(),
+        NowMessage::Unknown { .. }
+    ));
+}
+
+#[test]
+fn extension_decoder_errors_are_reported() {
+    let mut frame = ping_frame();
+    frame[5] = 0x02;
+
+    let options = NowDecodeOptions::new()
+        .with_extensions(&DIAGNOSTICS_REGISTRY)
+        .with_unknown_messages();
+    assert!(decode_with(&frame, options).is_err());
+}
+
+#[test]
+fn extension_limits_checked_before_decoding() {
+    // Unknown diagnostics message kind, rejected by the extension decoder.
+    let mut frame = ping_frame();
+    frame[5] = 0x02;
+
+    let options = NowDecodeOptions::new().with_extensions(&DIAGNOSTICS_REGISTRY);
+
+    let error = decode_with(&frame, options).unwrap_err();
+    assert!(!NowDecodeLimits::is_limit_exceeded(&error));
+
+    let limits = NowDecodeLimits::new().with_max_total_alloc(10);
+    let error = decode_with(&frame, options.with_limits(limits)).unwrap_err();
+    assert_eq!(
+        NowDecodeLimits::exceeded_limit(&error),
+        Some(&NowDecodeLimitError::TotalAlloc { size: 11, limit: 10 })
+    );
+
+    let limits = NowDecodeLimits::new().with_max_total_alloc(11);
+    assert!(decode_with(&ping_frame(), options.with_limits(limits)).is_ok());
+}
+
+#[test]
+fn extension_strict_decoding() {
+    let options = NowDecodeOptions::new()
+        .with_extensions(&DIAGNOSTICS_REGISTRY)
+        .with_strict();
+
+    let mut frame = ping_frame();
+    assert!(decode_with(&frame, options).is_ok());
+
+    // Trailing body data is not canonical.
+    frame.push(0xFF);
+    frame[0] += 1;
+    assert!(decode_with(&frame, options).is_err());
+}
+
+#[test]
+fn extension_framer() {
+    let mut bytes = ping_frame();
+    bytes.extend_from_slice(&encode_vec(&NowChannelHeartbeatMsg::default()).unwrap());
+
+    let mut framer =
+        NowMessageFramer::new().with_decode_options(NowDecodeOptions::new().with_extensions(&DIAGNOSTICS_REGISTRY));
+    framer.push(&bytes).unwrap();
+
+    assert!(matches!(
+        framer.next_owned_message().unwrap().unwrap(),
+        NowMessage::Extension(_)
+    ));
+    assert_eq!(
+        framer.next_owned_message().unwrap().unwrap(),
+        NowMessage::from(NowChannelHeartbeatMsg::default())
+    );
+}
+
+#[rstest]
+#[case::standard(NowMessageClass::EXEC)]
+#[case::standard_file(NowMessageClass::FILE)]
+#[case::reserved(NowMessageClass(0x16))]
+#[case::below_vendor_range(NowMessageClass(0x3F))]
+fn extension_registry_rejects_non_vendor_class(#[case] class: NowMessageClass) {
+    assert_eq!(
+        NowExtensionRegistry::new().with_class(class, |_, _| unreachable!()),
+        Err(NowExtensionRegistryError::NotVendorClass(class))
+    );
+}
+
+#[test]
+fn extension_registry_rejects_duplicate_class() {
+    let mut registry = NowExtensionRegistry::new();
+    registry
+        .register(NowMessageClass::VENDOR_MIN, |_, _| unreachable!())
+        .unwrap();
+
+    assert_eq!(
+        registry.register(NowMessageClass::VENDOR_MIN, |_, _| unreachable!()),
+        Err(NowExtensionRegistryError::AlreadyRegistered(
+            NowMessageClass::VENDOR_MIN
+        ))
+    );
+    assert!(registry.register(NowMessageClass(0xFF), |_, _| unreachable!()).is_ok());
+}
+
+#[test]
+fn extension_registry_classes() {
+    assert!(DIAGNOSTICS_REGISTRY.is_registered(DIAGNOSTICS_CLASS));
+    assert!(!DIAGNOSTICS_REGISTRY.is_registered(NowMessageClass(0x41)));
+}
@@ -0,0 +1,383 @@
+use expect_test::expect;
+use now_proto_pdu::ironrdp_core::{decode, encode_vec};
+use now_proto_pdu::*;
+use now_proto_testsuite::proto::now_msg_roundtrip;
+
+#[test]
+fn roundtrip_file_open_read() {
+    let msg = NowFileOpenMsg::new(1, NowFileOpenMode::Read, "/tmp/a")
+        .unwrap()
+        .with_offset(0x10);
+
+    let decoded = now_msg_roundtrip(msg, expect!["[14, 00, 00, 00, 15, 01, 01, 00, 01, 00, 00, 00, 10, 00, 00, 00, 00, 00, 00, 00, 06, 2F, 74, 6D, 70, 2F, 61, 00]"]);
+
+    let actual = match decoded {
+        NowMessage::File(NowFileMessage::Open(msg)) => msg,
+        _ => panic!("Expected NowFileOpenMsg"),
+    };
+
+    assert_eq!(actual.transfer_id(), 1);
+    assert_eq!(actual.mode().unwrap(), NowFileOpenMode::Read);
+    assert_eq!(actual.offset(), 0x10);
+    assert!(!actual.is_resume());
+    assert_eq!(actual.path(), "/tmp/a");
+}
+
+#[test]
+fn roundtrip_file_open_write_resume() {
+    let msg = NowFileOpenMsg::new(2, NowFileOpenMode::Write, "/tmp/a")
+        .unwrap()
+        .with_offset(0x10)
+        .with_resume();
+
+    let decoded = now_msg_roundtrip(msg, expect!["[14, 00, 00, 00, 15, 01, 06, 00, 02, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 06, 2F, 74, 6D, 70, 2F, 61, 00]"]);
+
+    let actual = match decoded {
+        NowMessage::File(NowFileMessage::Open(msg)) => msg,
+        _ => panic!("Expected NowFileOpenMsg"),
+    };
+
+    assert_eq!(actual.mode().unwrap(), NowFileOpenMode::Write);
+    assert_eq!(actual.offset(), 0);
+    assert!(actual.is_resume());
+}
+
+#[test]
+fn file_open_invalid_mode() {
+    let read = encode_vec(&NowMessage::from(
+        NowFileOpenMsg::new(1, NowFileOpenMode::Read, "a").unwrap(),
+    ))
+    .unwrap();
+
+    let mut read_write = read.clone();
+    read_write[6] |= 0x02;
+
+    let mut no_mode = read.clone();
+    no_mode[6] = 0x00;
+
+    let mut read_resume = read;
+    read_resume[6] |= 0x04;
+
+    for frame in [read_write, no_mode, read_resume] {
+        let msg = match decode::<NowMessage<'_>>(&frame).unwrap() {
+            NowMessage::File(NowFileMessage::Open(msg)) => msg,
+            _ => panic!("Expected NowFileOpenMsg"),
+        };
+
+        msg.mode().unwrap_err();
+    }
+}
+
+#[test]
+fn roundtrip_file_open_rsp() {
+    let decoded = now_msg_roundtrip(NowFileOpenRspMsg::new_success(1, 0x1234), expect!["[16, 00, 00, 00, 15, 02, 00, 00, 01, 00, 00, 00, 34, 12, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00]"]);
+
+    let actual = match decoded {
+        NowMessage::File(NowFileMessage::OpenRsp(msg)) => msg,
+        _ => panic!("Expected NowFileOpenRspMsg"),
+    };
+
+    actual.to_result().unwrap();
+    assert_eq!(actual.transfer_id(), 1);
+    assert_eq!(actual.file_size(), 0x1234);
+}
+
+#[test]
+fn roundtrip_file_open_rsp_error() {
+    let msg = NowFileOpenRspMsg::new_error(1, NowProtoError::NotFound).unwrap();
+
+    let decoded = now_msg_roundtrip(msg, expect!["[16, 00, 00, 00, 15, 02, 00, 00, 01, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 01, 00, 01, 00, 04, 00, 00, 00, 00, 00]"]);
+
+    let actual = match decoded {
+        NowMessage::File(NowFileMessage::OpenRsp(msg)) => msg,
+        _ => panic!("Expected NowFileOpenRspMsg"),
+    };
+
+    assert_eq!(
+        actual.to_result().unwrap_err().kind(),
+        NowStatusErrorKind::Now(NowProtoError::NotFound)
+    );
+    assert_eq!(actual.file_size(), 0);
+}
+
+#[test]
+fn roundtrip_file_chunk() {
+    let msg = NowFileChunkMsg::new(1, 0x100, true, b"data".as_slice()).unwrap();
+
+    let decoded = now_msg_roundtrip(
+        msg,
+        expect!["[11, 00, 00, 00, 15, 03, 01, 00, 01, 00, 00, 00, 00, 01, 00, 00, 00, 00, 00, 00, 04, 64, 61, 74, 61]"],
+    );
+
+    let actual = match decoded {
+        NowMessage::File(NowFileMessage::Chunk(msg)) => msg,
+        _ => panic!("Expected NowFileChunkMsg"),
+    };
+
+    assert_eq!(actual.transfer_id(), 1);
+    assert_eq!(actual.offset(), 0x100);
+    assert!(actual.is_last());
+    assert_eq!(actual.data(), b"data");
+}
+
+#[test]
+fn roundtrip_file_close() {
+    let decoded = now_msg_roundtrip(NowFileCloseMsg::new(1).with_sha256([0xAB; 32]), expect!["[25, 00, 00, 00, 15, 04, 00, 00, 01, 00, 00, 00, 20, AB, AB, AB, AB, AB, AB, AB, AB, AB, AB, AB, AB, AB, AB, AB, AB, AB, AB, AB, AB, AB, AB, AB, AB, AB, AB, AB, AB, AB, AB, AB, AB]"]);
+
+    let actual = match decoded {
+        NowMessage::File(NowFileMessage::Close(msg)) => msg,
+        _ => panic!("Expected NowFileCloseMsg"),
+    };
+
+    assert_eq!(actual.transfer_id(), 1);
+    assert_eq!(actual.sha256().unwrap(), Some([0xAB; 32]));
+}
+
+#[test]
+fn roundtrip_file_close_cancel() {
+    let decoded = now_msg_roundtrip(
+        NowFileCloseMsg::new(1),
+        expect!["[05, 00, 00, 00, 15, 04, 00, 00, 01, 00, 00, 00, 00]"],
+    );
+
+    let actual = match decoded {
+        NowMessage::File(NowFileMessage::Close(msg)) => msg,
+        _ => panic!("Expected NowFileCloseMsg"),
+    };
+
+    assert_eq!(actual.sha256().unwrap(), None);
+}
+
+#[test]
+fn file_close_invalid_digest_size() {
+    const ENCODED: &[u8] = &[
+        0x07, 0x00, 0x00, 0x00, 0x15, 0x04, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x02, 0xAB, 0xCD,
+    ];
+
+    let msg = match decode::<NowMessage<'_>>(ENCODED).unwrap() {
+        NowMessage::File(NowFileMessage::Close(msg)) => msg,
+        _ => panic!("Expected NowFileCloseMsg"),
+    };
+
+    msg.sha256().unwrap_err();
+}
+
+#[test]
+fn roundtrip_file_close_rsp() {
+    let msg = NowFileCloseRspMsg::new_error(1, NowProtoError::InvalidRequest).unwrap();
+
+    let decoded = now_msg_roundtrip(
+        msg,
+        expect!["[0E, 00, 00, 00, 15, 05, 00, 00, 01, 00, 00, 00, 01, 00, 01, 00, 02, 00, 00, 00, 00, 00]"],
+    );
+
+    let actual = match decoded {
+        NowMessage::File(NowFileMessage::CloseRsp(msg)) => msg,
+        _ => panic!("Expected NowFileCloseRspMsg"),
+    };
+
+    assert_eq!(actual.transfer_id(), 1);
+    assert!(actual.to_result().is_err());
+}
+
+#[test]
+fn roundtrip_file_stat_req() {
+    let msg = NowFileStatReqMsg::new(7, "/tmp").unwrap();
+
+    let decoded = now_msg_roundtrip(
+        msg,
+        expect!["[0A, 00, 00, 00, 15, 06, 00, 00, 07, 00, 00, 00, 04, 2F, 74, 6D, 70, 00]"],
+    );
+
+    let actual = match decoded {
+        NowMessage::File(NowFileMessage::StatReq(msg)) => msg,
+        _ => panic!("Expected NowFileStatReqMsg"),
+    };
+
+    assert_eq!(actual.request_id(), 7);
+    assert_eq!(actual.path(), "/tmp");
+}
+
+#[test]
+fn roundtrip_file_stat_rsp() {
+    let msg = NowFileStatRspMsg::new_success(7, NowFileEntryKind::FILE)
+        .with_file_size(0x1234)
+        .with_mtime(0x6000_0000)
+        .with_attributes(NowFileAttributes::READONLY | NowFileAttributes::EXECUTABLE);
+
+    let decoded = now_msg_roundtrip(msg, expect!["[26, 00, 00, 00, 15, 07, 00, 00, 07, 00, 00, 00, 01, 00, 00, 00, 11, 00, 00, 00, 34, 12, 00, 00, 00, 00, 00, 00, 00, 00, 00, 60, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00]"]);
+
+    let actual = match decoded {
+        NowMessage::File(NowFileMessage::StatRsp(msg)) => msg,
+        _ => panic!("Expected NowFileStatRspMsg"),
+    };
+
+    assert_eq!(actual.request_id(), 7);
+    assert_eq!(actual.kind(), NowFileEntryKind::FILE);
+    assert_eq!(actual.file_size(), 0x1234);
+    assert_eq!(actual.mtime(), 0x6000_0000);
+    assert_eq!(
+        actual.attributes(),
+        NowFileAttributes::READONLY | NowFileAttributes::EXECUTABLE
+    );
+    assert!(actual.to_result().is_ok());
+}
+
+#[test]
+fn roundtrip_file_stat_rsp_error() {
+    let msg = NowFileStatRspMsg::new_error(7, NowProtoError::NotFound).unwrap();
+
+    let decoded = now_msg_roundtrip(msg, expect!["[26, 00, 00, 00, 15, 07, 00, 00, 07, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 01, 00, 01, 00, 04, 00, 00, 00, 00, 00]"]);
+
+    let actual = match decoded {
+        NowMessage::File(NowFileMessage::StatRsp(msg)) => msg,
+        _ => panic!("Expected NowFileStatRspMsg"),
+    };
+
+    assert_eq!(
+        actual.to_result().unwrap_err().kind(),
+        NowStatusErrorKind::Now(NowProtoError::NotFound)
+    );
+}
+
+#[test]
+fn roundtrip_file_list_dir_req() {
+    let msg = NowFileListDirReqMsg::new(8, "/tmp").unwrap().with_page(100, 50);
+
+    let decoded = now_msg_roundtrip(
+        msg,
+        expect![
+            "[12, 00, 00, 00, 15, 08, 00, 00, 08, 00, 00, 00, 64, 00, 00, 00, 32, 00, 00, 00, 04, 2F, 74, 6D, 70, 00]"
+        ],
+    );
+
+    let actual = match decoded {
+        NowMessage::File(NowFileMessage::ListDirReq(msg)) => msg,
+        _ => panic!("Expected NowFileListDirReqMsg"),
+    };
+
+    assert_eq!(actual.request_id(), 8);
+    assert_eq!(actual.start_index(), 100);
+    assert_eq!(actual.max_entries(), Some(50));
+    assert_eq!(actual.path(), "/tmp");
+
+    let unlimited = NowFileListDirReqMsg::new(8, "/tmp").unwrap();
+    assert_eq!(unlimited.max_entries(), None);
+}
+
+#[test]
+fn roundtrip_file_dir_entry() {
+    let msg = NowFileDirEntryMsg::new(8, NowFileEntryKind::DIRECTORY, ".cache")
+        .unwrap()
+        .with_mtime(0x6000_0000)
+        .with_attributes(NowFileAttributes::HIDDEN);
+
+    let decoded = now_msg_roundtrip(msg, expect!["[24, 00, 00, 00, 15, 09, 00, 00, 08, 00, 00, 00, 02, 00, 00, 00, 02, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 60, 00, 00, 00, 00, 06, 2E, 63, 61, 63, 68, 65, 00]"]);
+
+    let actual = match decoded {
+        NowMessage::File(NowFileMessage::DirEntry(msg)) => msg,
+        _ => panic!("Expected NowFileDirEntryMsg"),
+    };
+
+    assert_eq!(actual.request_id(), 8);
+    assert_eq!(actual.kind(), NowFileEntryKind::DIRECTORY);
+    assert_eq!(actual.file_size(), 0);
+    assert_eq!(actual.mtime(), 0x6000_0000);
+    assert_eq!(actual.attributes(), NowFileAttributes::HIDDEN);
+    assert_eq!(actual.file_name(), ".cache");
+}
+
+#[test]
+fn roundtrip_file_list_dir_rsp() {
+    let msg = NowFileListDirRspMsg::new_success(8).with_more();
+
+    let decoded = now_msg_roundtrip(
+        msg,
+        expect!["[0E, 00, 00, 00, 15, 0A, 01, 00, 08, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00]"],
+    );
+
+    let actual = match decoded {
+        NowMessage::File(NowFileMessage::ListDirRsp(msg)) => msg,
+        _ => panic!("Expected NowFileListDirRspMsg"),
+    };
+
+    assert_eq!(actual.request_id(), 8);
+    assert!(actual.has_more());
+    assert!(actual.to_result().is_ok());
+}
+
+#[test]
+fn roundtrip_file_mkdir() {
+    let msg = NowFileMkdirMsg::new(9, "/tmp/a/b").unwrap().with_recursive();
+
+    let decoded = now_msg_roundtrip(
+        msg,
+        expect!["[0E, 00, 00, 00, 15, 0B, 01, 00, 09, 00, 00, 00, 08, 2F, 74, 6D, 70, 2F, 61, 2F, 62, 00]"],
+    );
+
+    let actual = match decoded {
+        NowMessage::File(NowFileMessage::Mkdir(msg)) => msg,
+        _ => panic!("Expected NowFileMkdirMsg"),
+    };
+
+    assert_eq!(actual.request_id(), 9);
+    assert_eq!(actual.path(), "/tmp/a/b");
+    assert!(actual.is_recursive());
+}
+
+#[test]
+fn roundtrip_file_delete() {
+    let msg = NowFileDeleteMsg::new(10, "/tmp/a").unwrap();
+
+    let decoded = now_msg_roundtrip(
+        msg,
+        expect!["[0C, 00, 00, 00, 15, 0C, 00, 00, 0A, 00, 00, 00, 06, 2F, 74, 6D, 70, 2F, 61, 00]"],
+    );
+
+    let actual = match decoded {
+        NowMessage::File(NowFileMessage::Delete(msg)) => msg,
+        _ => panic!("Expected NowFileDeleteMsg"),
+    };
+
+    assert_eq!(actual.request_id(), 10);
+    assert_eq!(actual.path(), "/tmp/a");
+    assert!(!actual.is_recursive());
+}
+
+#[test]
+fn roundtrip_file_rename() {
+    let msg = NowFileRenameMsg::new(11, "/tmp/a", "/tmp/b").unwrap().with_replace();
+
+    let decoded = now_msg_roundtrip(msg, expect!["[14, 00, 00, 00, 15, 0D, 01, 00, 0B, 00, 00, 00, 06, 2F, 74, 6D, 70, 2F, 61, 00, 06, 2F, 74, 6D, 70, 2F, 62, 00]"]);
+
+    let actual = match decoded {
+        NowMessage::File(NowFileMessage::Rename(msg)) => msg,
+        _ => panic!("Expected NowFileRenameMsg"),
+    };
+
+    assert_eq!(actual.request_id(), 11);
+    assert_eq!(actual.path(), "/tmp/a");
+    assert_eq!(actual.new_path(), "/tmp/b");
+    assert!(actual.is_replace());
+}
+
+#[test]
+fn roundtrip_file_op_rsp() {
+    let msg = NowFileOpRspMsg::new_error(11, NowProtoError::AccessDenied).unwrap();
+
+    let decoded = now_msg_roundtrip(
+        msg,
+        expect!["[0E, 00, 00, 00, 15, 0E, 00, 00, 0B, 00, 00, 00, 01, 00, 01, 00, 05, 00, 00, 00, 00, 00]"],
+    );
+
+    let actual = match decoded {
+        NowMessage::File(NowFileMessage::OpRsp(msg)) => msg,
+        _ => panic!("Expected NowFileOpRspMsg"),
+    };
+
+    assert_eq!(actual.request_id(), 11);
+    assert_eq!(
+        actual.to_result().unwrap_err().kind(),
+        NowStatusErrorKind::Now(NowProtoError::AccessDenied)
+    );
+}
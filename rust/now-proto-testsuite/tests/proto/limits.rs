@@ -0,0 +1,88 @@
+use now_proto_pdu::ironrdp_core::{decode, encode_vec, DecodeErrorKind, DecodeResult, ReadCursor};
+use now_proto_pdu::*;
+use rstest::rstest;
+
+fn decode_limited(frame: &[u8], limits: NowDecodeLimits) -> DecodeResult<NowMessage<'_>> {
+    NowMessage::decode_with_options(&mut ReadCursor::new(frame), NowDecodeOptions::new().with_limits(limits))
+}
+
+fn encode(msg: impl Into<NowMessage<'static>>) -> Vec<u8> {
+    encode_vec(&msg.into()).unwrap()
+}
+
+fn process_msg() -> Vec<u8> {
+    encode(
+        NowExecProcessMsg::new(1, "app.exe")
+            .unwrap()
+            .with_parameters("--flag")
+            .unwrap()
+            .with_directory("C:\\dir")
+            .unwrap(),
+    )
+}
+
+fn data_msg() -> Vec<u8> {
+    encode(NowExecDataMsg::new(1, NowExecDataStreamKind::Stdout, false, vec![0u8; 16]).unwrap())
+}
+
+#[rstest]
+#[case::unlimited(process_msg(), NowDecodeLimits::new())]
+#[case::exact_message_size(process_msg(), NowDecodeLimits::new().with_max_message_size(29))]
+#[case::exact_string_len(process_msg(), NowDecodeLimits::new().with_max_string_len(7))]
+#[case::exact_total_alloc(process_msg(), NowDecodeLimits::new().with_max_total_alloc(19))]
+#[case::string_limit_for_buffer(data_msg(), NowDecodeLimits::new().with_max_string_len(0))]
+#[case::exact_buffer_len(data_msg(), NowDecodeLimits::new().with_max_buffer_len(16))]
+#[case::no_variable_fields(encode(NowSessionLockMsg::default()), NowDecodeLimits::new().with_max_total_alloc(0))]
+fn limits_accept(#[case] frame: Vec<u8>, #[case] limits: NowDecodeLimits) {
+    assert_eq!(
+        decode_limited(&frame, limits).unwrap(),
+        decode::<NowMessage<'_>>(&frame).unwrap()
+    );
+}
+
+#[rstest]
+#[case::message_size(process_msg(), NowDecodeLimits::new().with_max_message_size(28), NowDecodeLimitError::MessageSize { size: 29, limit: 28 })]
+#[case::string_len(process_msg(), NowDecodeLimits::new().with_max_string_len(6), NowDecodeLimitError::StringLength { len: 7, limit: 6 })]
+#[case::total_alloc(process_msg(), NowDecodeLimits::new().with_max_total_alloc(18), NowDecodeLimitError::TotalAlloc { size: 19, limit: 18 })]
+#[case::buffer_len(data_msg(), NowDecodeLimits::new().with_max_buffer_len(15), NowDecodeLimitError::BufferLength { len: 16, limit: 15 })]
+#[case::status_message(
+    encode(NowChannelCloseMsg::from_error(NowStatusError::new_generic(1).with_message("error").unwrap()).unwrap()),
+    NowDecodeLimits::new().with_max_string_len(4),
+    NowDecodeLimitError::StringLength { len: 5, limit: 4 }
+)]
+#[case::window_event(
+    encode(NowSessionWindowRecEventMsg::active_window(1, 2, "title", "C:\\app.exe").unwrap()),
+    NowDecodeLimits::new().with_max_total_alloc(14),
+    NowDecodeLimitError::TotalAlloc { size: 15, limit: 14 }
+)]
+fn limits_reject(#[case] frame: Vec<u8>, #[case] limits: NowDecodeLimits, #[case] expected: NowDecodeLimitError) {
+    let error = decode_limited(&frame, limits).unwrap_err();
+
+    assert!(NowDecodeLimits::is_limit_exceeded(&error));
+    assert!(matches!(error.kind(), DecodeErrorKind::Other { .. }));
+    assert_eq!(NowDecodeLimits::exceeded_limit(&error), Some(&expected));
+}
+
+#[test]
+fn limits_message_size_checked_before_body() {
+    // Header announces a 4GiB body, which is not received.
+    let frame = [0xFF, 0xFF, 0xFF, 0xFF, 0x13, 0x10, 0x00, 0x00];
+
+    let error = decode_limited(&frame, NowDecodeLimits::new().with_max_message_size(1024)).unwrap_err();
+    assert!(NowDecodeLimits::is_limit_exceeded(&error));
+
+    // Without limits, decoding fails due to the missing body.
+    let error = decode::<NowMessage<'_>>(&frame).unwrap_err();
+    assert!(!NowDecodeLimits::is_limit_exceeded(&error));
+}
+
+#[test]
+fn limits_apply_to_unknown_messages() {
+    let frame = [0x02, 0x00, 0x00, 0x00, 0x7F, 0x01, 0x00, 0x00, 0xAA, 0xBB];
+    let options = NowDecodeOptions::new()
+        .with_unknown_messages()
+        .with_limits(NowDecodeLimits::new().with_max_total_alloc(1));
+
+    let error = NowMessage::decode_with_options(&mut ReadCursor::new(&frame), options).unwrap_err();
+    assert!(NowDecodeLimits::is_limit_exceeded(&error));
+}
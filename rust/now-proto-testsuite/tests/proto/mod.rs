@@ -1,6 +1,12 @@
 mod channel;
 mod exec;
+mod extension;
+mod file;
+mod limits;
+mod primitives;
 mod rdm;
 mod regression;
 mod session;
+mod strict;
 mod system;
+mod unknown;
@@ -0,0 +1,218 @@
+use std::borrow::Cow;
+
+use now_proto_pdu::ironrdp_core::{
+    decode, encode_vec, Decode, DecodeResult, Encode, EncodeResult, ReadCursor, WriteCursor,
+};
+use now_proto_pdu::*;
+use rstest::rstest;
+
+#[rstest]
+#[case(0x00, &[0x00])]
+#[case(0x3F, &[0x3F])]
+#[case(0x40, &[0x40, 0x40])]
+#[case(0x3FFF, &[0x7F, 0xFF])]
+#[case(0x4000, &[0x80, 0x40, 0x00])]
+#[case(0x3F_FFFF, &[0xBF, 0xFF, 0xFF])]
+#[case(0x40_0000, &[0xC0, 0x40, 0x00, 0x00])]
+#[case(VarU32::MAX, &[0xFF, 0xFF, 0xFF, 0xFF])]
+fn var_u32_roundtrip(#[case] value: u32, #[case] expected: &[u8]) {
+    let encoded = encode_vec(&VarU32::new(value).unwrap()).unwrap();
+    assert_eq!(encoded, expected);
+    assert_eq!(decode::<VarU32>(&encoded).unwrap().value(), value);
+}
+
+#[test]
+fn var_u32_out_of_range() {
+    assert!(VarU32::new(VarU32::MAX + 1).is_err());
+    assert!(VarU32::try_from(u32::MAX).is_err());
+}
+
+fn checked_message_size(sizes: &[usize]) -> EncodeResult<()> {
+    match sizes {
+        [size] => {
+            ensure_now_message_size!(*size);
+        }
+        [fixed, variable] => {
+            ensure_now_message_size!(*fixed, *variable);
+        }
+        [fixed, first, rest] => {
+            ensure_now_message_size!(*fixed, *first, *rest);
+        }
+        _ => unreachable!(),
+    }
+
+    Ok(())
+}
+
+#[rstest]
+#[case::single(&[0xFFFF_FFFF], true)]
+#[case::single_overflow(&[0x1_0000_0000], false)]
+#[case::pair(&[4, 0xFFFF_FFFB], true)]
+#[case::pair_overflow(&[4, 0xFFFF_FFFC], false)]
+#[case::pair_usize_overflow(&[usize::MAX, 1], false)]
+#[case::many(&[4, 0x7FFF_FFFF, 0x7FFF_FFFC], true)]
+#[case::many_overflow(&[4, 0x7FFF_FFFF, 0x7FFF_FFFD], false)]
+fn ensure_now_message_size(#[case] sizes: &[usize], #[case] is_ok: bool) {
+    assert_eq!(checked_message_size(sizes).is_ok(), is_ok);
+}
+
+#[test]
+fn var_str_roundtrip() {
+    let value = NowVarStr::try_from("hello").unwrap();
+    let encoded = encode_vec(&value).unwrap();
+
+    assert_eq!(encoded, [0x05, b'h', b'e', b'l', b'l', b'o', 0x00]);
+
+    let decoded = decode::<NowVarStr<'_>>(&encoded).unwrap();
+    assert_eq!(decoded.as_str(), "hello");
+    assert!(matches!(decoded.into_inner(), Cow::Borrowed("hello")));
+}
+
+#[test]
+fn var_str_invalid_utf8() {
+    assert!(decode::<NowVarStr<'_>>(&[0x02, 0xC3, 0x28, 0x00]).is_err());
+}
+
+#[test]
+fn var_buf_roundtrip() {
+    let value = NowVarBuf::try_from(vec![0xAA; 0x40]).unwrap();
+    let encoded = encode_vec(&value).unwrap();
+
+    assert_eq!(&encoded[..2], [0x40, 0x40]);
+    assert_eq!(encoded.len(), 0x42);
+    assert_eq!(decode::<NowVarBuf<'_>>(&encoded).unwrap(), value);
+}
+
+#[test]
+fn guid_roundtrip() {
+    let uuid = Uuid::parse_str("67F8A4E2-6D79-4D9A-9AE4-10A4C1BC3C30").unwrap();
+    let encoded = encode_vec(&NowGuid::from(uuid)).unwrap();
+
+    let mut expected = vec![36];
+    expected.extend_from_slice(b"67f8a4e2-6d79-4d9a-9ae4-10a4c1bc3c30\0");
+    assert_eq!(encoded, expected);
+
+    assert_eq!(Uuid::from(decode::<NowGuid>(&encoded).unwrap()), uuid);
+}
+
+#[test]
+fn header_builder() {
+    let header = NowHeader::new(NowMessageClass(0x40), 0x01)
+        .with_flags(0x0002)
+        .with_body_size(16)
+        .unwrap();
+
+    assert_eq!(
+        encode_vec(&header).unwrap(),
+        [0x10, 0x00, 0x00, 0x00, 0x40, 0x01, 0x02, 0x00]
+    );
+
+    assert!(header.ensure_message_type(NowMessageClass(0x40), 0x01).is_ok());
+    assert!(header.ensure_message_type(NowMessageClass(0x40), 0x02).is_err());
+    assert!(header.ensure_message_type(NowMessageClass::EXEC, 0x01).is_err());
+}
+
+#[test]
+fn header_read_body() {
+    let header = NowHeader::new(NowMessageClass(0x40), 0x01).with_body_size(2).unwrap();
+
+    let mut src = ReadCursor::new(&[0xAA, 0xBB, 0xCC]);
+    assert_eq!(header.read_body(&mut src).unwrap(), [0xAA, 0xBB]);
+    assert_eq!(src.len(), 1);
+
+    let mut src = ReadCursor::new(&[0xAA]);
+    assert!(header.read_body(&mut src).is_err());
+}
+
+/// Vendor message defined outside of the `now-proto-pdu` crate.
+#[derive(Debug, PartialEq, Eq)]
+struct DiagnosticsReportMsg<'a> {
+    level: u32,
+    source: NowGuid,
+    report: NowVarStr<'a>,
+    data: NowVarBuf<'a>,
+}
+
+impl<'a> DiagnosticsReportMsg<'a> {
+    const CLASS: NowMessageClass = NowMessageClass(0x40);
+    const KIND: u8 = 0x01;
+    const FIXED_PART_SIZE: usize = 4;
+
+    fn new(level: u32, source: Uuid, report: &'a str, data: &'a [u8]) -> EncodeResult<Self> {
+        let msg = Self {
+            level,
+            source: NowGuid::new(source),
+            report: NowVarStr::new(report)?,
+            data: NowVarBuf::new(data)?,
+        };
+
+        ensure_now_message_size!(
+            Self::FIXED_PART_SIZE,
+            msg.source.size(),
+            msg.report.size(),
+            msg.data.size()
+        );
+
+        Ok(msg)
+    }
+
+    fn body_size(&self) -> usize {
+        Self::FIXED_PART_SIZE + self.source.size() + self.report.size() + self.data.size()
+    }
+}
+
+impl Encode for DiagnosticsReportMsg<'_> {
+    fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
+        NowHeader::new(Self::CLASS, Self::KIND)
+            .with_body_size(self.body_size())?
+            .encode(dst)?;
+
+        dst.write_u32(self.level);
+        self.source.encode(dst)?;
+        self.report.encode(dst)?;
+        self.data.encode(dst)
+    }
+
+    fn name(&self) -> &'static str {
+        "DIAGNOSTICS_REPORT_MSG"
+    }
+
+    fn size(&self) -> usize {
+        NowHeader::FIXED_PART_SIZE + self.body_size()
+    }
+}
+
+impl<'de> Decode<'de> for DiagnosticsReportMsg<'de> {
+    fn decode(src: &mut ReadCursor<'de>) -> DecodeResult<Self> {
+        let header = NowHeader::decode(src)?;
+        header.ensure_message_type(Self::CLASS, Self::KIND)?;
+
+        let mut body = ReadCursor::new(header.read_body(src)?);
+        now_proto_pdu::ironrdp_core::ensure_size!(in: body, size: Self::FIXED_PART_SIZE);
+
+        Ok(Self {
+            level: body.read_u32(),
+            source: NowGuid::decode(&mut body)?,
+            report: NowVarStr::decode(&mut body)?,
+            data: NowVarBuf::decode(&mut body)?,
+        })
+    }
+}
+
+#[test]
+fn vendor_message_roundtrip() {
+    let msg = DiagnosticsReportMsg::new(2, Uuid::nil(), "disk is full", &[0x01, 0x02]).unwrap();
+    let encoded = encode_vec(&msg).unwrap();
+
+    assert_eq!(encoded.len(), msg.size());
+    assert_eq!(&encoded[..8], [0x3B, 0x00, 0x00, 0x00, 0x40, 0x01, 0x00, 0x00]);
+    assert_eq!(decode::<DiagnosticsReportMsg<'_>>(&encoded).unwrap(), msg);
+
+    // Vendor messages could be relayed by NOW-PROTO decoders as unknown messages.
+    let relayed = NowMessage::decode_with_options(
+        &mut ReadCursor::new(&encoded),
+        NowDecodeOptions::new().with_unknown_messages(),
+    )
+    .unwrap();
+    assert!(matches!(relayed, NowMessage::Unknown { .. }));
+}
@@ -0,0 +1,157 @@
+use now_proto_pdu::ironrdp_core::{decode, encode_vec, DecodeResult, ReadCursor};
+use now_proto_pdu::*;
+use rstest::rstest;
+
+fn decode_strict(frame: &[u8]) -> DecodeResult<NowMessage<'_>> {
+    NowMessage::decode_with_options(&mut ReadCursor::new(frame), NowDecodeOptions::new().with_strict())
+}
+
+fn encode(msg: impl Into<NowMessage<'static>>) -> Vec<u8> {
+    encode_vec(&msg.into()).unwrap()
+}
+
+/// NOW_SYSTEM_SHUTDOWN_MSG with "hello" message.
+const SHUTDOWN: &[u8] = &[
+    0x0B, 0x00, 0x00, 0x00, 0x11, 0x03, 0x01, 0x00, 0x7B, 0x00, 0x00, 0x00, 0x05, 0x68, 0x65, 0x6C, 0x6C, 0x6F, 0x00,
+];
+
+#[rstest]
+#[case::shutdown(SHUTDOWN.to_vec())]
+#[case::capset(encode(
+    NowChannelCapsetMsg::default()
+        .with_exec_capset(NowExecCapsetFlags::STYLE_RUN)
+        .with_heartbeat_interval(core::time::Duration::from_secs(60))
+        .unwrap()
+))]
+#[case::capset_file_capset(encode(
+    NowChannelCapsetMsg::default().with_file_capset(NowFileCapsetFlags::READ)
+))]
+#[case::file_open(encode(NowFileOpenMsg::new(1, NowFileOpenMode::Write, "a").unwrap().with_resume()))]
+#[case::file_close(encode(NowFileCloseMsg::new(1).with_sha256([0; 32])))]
+#[case::file_stat_rsp(encode(
+    NowFileStatRspMsg::new_success(1, NowFileEntryKind::FILE).with_attributes(NowFileAttributes::all())
+))]
+#[case::file_list_dir_rsp(encode(NowFileListDirRspMsg::new_success(1).with_more()))]
+#[case::file_rename(encode(NowFileRenameMsg::new(1, "a", "b").unwrap().with_replace()))]
+#[case::exec_data(encode(NowExecDataMsg::new(1, NowExecDataStreamKind::Stdout, true, b"data".as_slice()).unwrap()))]
+#[case::exec_winps(encode(NowExecWinPsMsg::new(1, "ls").unwrap().with_apartment_state(ComApartmentStateKind::Sta)))]
+#[case::exec_result(encode(NowExecResultMsg::new_error(1, NowProtoError::NotFound).unwrap()))]
+#[case::long_string(encode(NowExecRunMsg::new(1, "a".repeat(0x4000)).unwrap()))]
+#[case::window_event(encode(NowSessionWindowRecEventMsg::title_changed(1, "title").unwrap()))]
+fn strict_accepts_canonical(#[case] frame: Vec<u8>) {
+    assert_eq!(
+        decode_strict(&frame).unwrap(),
+        decode::<NowMessage<'_>>(&frame).unwrap()
+    );
+}
+
+fn patched(frame: &[u8], patch: impl FnOnce(&mut Vec<u8>)) -> Vec<u8> {
+    let mut frame = frame.to_vec();
+    patch(&mut frame);
+    frame
+}
+
+#[rstest]
+#[case::non_null_terminator(patched(SHUTDOWN, |f| f[18] = b'!'))]
+#[case::overlong_var_u32(patched(SHUTDOWN, |f| {
+    f[12] = 0x40;
+    f.insert(13, 0x05);
+    f[0] += 1;
+}))]
+#[case::trailing_data(patched(SHUTDOWN, |f| {
+    f.extend_from_slice(&[0x01, 0x02]);
+    f[0] += 2;
+}))]
+#[case::unknown_header_flags(patched(SHUTDOWN, |f| f[7] = 0x80))]
+#[case::flags_on_message_without_flags(patched(&encode(NowSessionLockMsg::default()), |f| f[6] = 0x01))]
+#[case::unknown_capset_flags(patched(&encode(NowChannelCapsetMsg::default()), |f| f[13] = 0x80))]
+#[case::unknown_file_capset_flags(patched(
+    &encode(NowChannelCapsetMsg::default().with_file_capset(NowFileCapsetFlags::READ)),
+    |f| f[23] = 0x80,
+))]
+#[case::file_open_read_and_write(patched(
+    &encode(NowFileOpenMsg::new(1, NowFileOpenMode::Read, "a").unwrap()),
+    |f| f[6] |= 0x02,
+))]
+#[case::file_open_read_resume(patched(
+    &encode(NowFileOpenMsg::new(1, NowFileOpenMode::Read, "a").unwrap()),
+    |f| f[6] |= 0x04,
+))]
+#[case::file_chunk_unknown_flags(patched(
+    &encode(NowFileChunkMsg::new(1, 0, false, b"".as_slice()).unwrap()),
+    |f| f[6] |= 0x02,
+))]
+#[case::file_close_invalid_digest(patched(&encode(NowFileCloseMsg::new(1)), |f| {
+    f[12] = 0x01;
+    f.push(0xAB);
+    f[0] += 1;
+}))]
+#[case::file_stat_rsp_unknown_attributes(patched(
+    &encode(NowFileStatRspMsg::new_success(1, NowFileEntryKind::FILE)),
+    |f| f[19] = 0x80,
+))]
+#[case::file_stat_rsp_unknown_status_flags(patched(
+    &encode(NowFileStatRspMsg::new_success(1, NowFileEntryKind::FILE)),
+    |f| f[37] = 0x80,
+))]
+#[case::file_dir_entry_unknown_attributes(patched(
+    &encode(NowFileDirEntryMsg::new(1, NowFileEntryKind::FILE, "a").unwrap()),
+    |f| f[16] = 0x20,
+))]
+#[case::file_list_dir_rsp_unknown_flags(patched(&encode(NowFileListDirRspMsg::new_success(1)), |f| f[6] |= 0x02))]
+#[case::file_mkdir_unknown_flags(patched(&encode(NowFileMkdirMsg::new(1, "a").unwrap()), |f| f[6] |= 0x02))]
+#[case::file_delete_unknown_flags(patched(&encode(NowFileDeleteMsg::new(1, "a").unwrap()), |f| f[6] |= 0x02))]
+#[case::unknown_status_flags(patched(&encode(NowChannelCloseMsg::default()), |f| f[9] = 0x80))]
+#[case::multiple_stream_kinds(patched(
+    &encode(NowExecDataMsg::new(1, NowExecDataStreamKind::Stdout, false, b"".as_slice()).unwrap()),
+    |f| f[6] |= 0x08,
+))]
+#[case::no_stream_kind(patched(
+    &encode(NowExecDataMsg::new(1, NowExecDataStreamKind::Stdout, false, b"".as_slice()).unwrap()),
+    |f| f[6] = 0x00,
+))]
+#[case::sta_and_mta(patched(
+    &encode(NowExecWinPsMsg::new(1, "ls").unwrap().with_apartment_state(ComApartmentStateKind::Sta)),
+    |f| f[6] |= 0x08,
+))]
+#[case::multiple_window_event_kinds(patched(
+    &encode(NowSessionWindowRecEventMsg::no_active_window(1)),
+    |f| f[6] |= 0x01,
+))]
+#[case::uppercase_guid(patched(
+    &encode(NowMessage::Rdm(NowRdmMessage::SessionAction(NowRdmSessionActionMsg::new_focus(
+        Uuid::from_u128(0xAABBCCDD_0000_0000_0000_000000000000)
+    )))),
+    |f| f[13..21].make_ascii_uppercase(),
+))]
+fn strict_rejects_non_canonical(#[case] frame: Vec<u8>) {
+    decode::<NowMessage<'_>>(&frame).expect("lenient decoding should accept the message");
+    assert!(decode_strict(&frame).is_err());
+}
+
+#[test]
+fn strict_rejects_next_and_prev_kbd_layout() {
+    let frame = patched(&encode(NowSessionSetKbdLayoutMsg::new_next()), |f| f[6] |= 0x02);
+
+    assert!(decode::<NowMessage<'_>>(&frame).is_err());
+    assert!(decode_strict(&frame).is_err());
+}
+
+#[test]
+fn strict_rejects_missing_file_capset() {
+    // NOW_CHANNEL_SET_FILE_CAPSET flag set, but the trailing field is missing.
+    let frame = patched(&encode(NowChannelCapsetMsg::default()), |f| f[6] |= 0x02);
+
+    assert!(decode::<NowMessage<'_>>(&frame).is_err());
+    assert!(decode_strict(&frame).is_err());
+}
+
+#[test]
+fn strict_does_not_validate_unknown_messages() {
+    const ENCODED: &[u8] = &[0x01, 0x00, 0x00, 0x00, 0x7F, 0x01, 0xFF, 0xFF, 0xAA];
+
+    let options = NowDecodeOptions::new().with_strict().with_unknown_messages();
+    let msg = NowMessage::decode_with_options(&mut ReadCursor::new(ENCODED), options).unwrap();
+
+    assert!(matches!(msg, NowMessage::Unknown { .. }));
+}
@@ -20,3 +20,248 @@ fn roundtrip_system_shutdown() {
 
     assert_eq!(actual.timeout(), core::time::Duration::from_secs(123));
 }
+
+#[test]
+fn roundtrip_system_info_req() {
+    let decoded = now_msg_roundtrip(
+        NowSystemInfoReqMsg::new(7),
+        expect!["[04, 00, 00, 00, 11, 01, 00, 00, 07, 00, 00, 00]"],
+    );
+
+    let actual = match decoded {
+        NowMessage::System(NowSystemMessage::InfoReq(msg)) => msg,
+        _ => panic!("Expected NowSystemInfoReqMsg"),
+    };
+
+    assert_eq!(actual.request_id(), 7);
+}
+
+#[test]
+fn roundtrip_system_info_rsp() {
+    let msg = NowSystemInfoRspMsg::new_success(7)
+        .with_os_name("Linux")
+        .unwrap()
+        .with_hostname("host")
+        .unwrap()
+        .with_uptime(core::time::Duration::from_secs(3600))
+        .with_agent_version("1.0")
+        .unwrap();
+
+    let decoded = now_msg_roundtrip(msg, expect!["[2E, 00, 00, 00, 11, 02, 55, 00, 07, 00, 00, 00, 10, 0E, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 05, 4C, 69, 6E, 75, 78, 00, 00, 00, 04, 68, 6F, 73, 74, 00, 00, 00, 00, 00, 03, 31, 2E, 30, 00]"]);
+
+    let actual = match decoded {
+        NowMessage::System(NowSystemMessage::InfoRsp(msg)) => msg,
+        _ => panic!("Expected NowSystemInfoRspMsg"),
+    };
+
+    actual.to_result().unwrap();
+    assert_eq!(actual.request_id(), 7);
+    assert_eq!(actual.os_name(), Some("Linux"));
+    assert_eq!(actual.os_version(), None);
+    assert_eq!(actual.hostname(), Some("host"));
+    assert_eq!(actual.architecture(), None);
+    assert_eq!(actual.uptime(), Some(core::time::Duration::from_secs(3600)));
+    assert_eq!(actual.logged_on_user(), None);
+    assert_eq!(actual.agent_version(), Some("1.0"));
+}
+
+#[test]
+fn roundtrip_system_info_rsp_error() {
+    let msg = NowSystemInfoRspMsg::new_error(7, NowProtoError::NotImplemented).unwrap();
+
+    let decoded = now_msg_roundtrip(msg, expect!["[22, 00, 00, 00, 11, 02, 00, 00, 07, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 01, 00, 01, 00, 07, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00]"]);
+
+    let actual = match decoded {
+        NowMessage::System(NowSystemMessage::InfoRsp(msg)) => msg,
+        _ => panic!("Expected NowSystemInfoRspMsg"),
+    };
+
+    assert!(actual.to_result().is_err());
+    assert_eq!(actual.request_id(), 7);
+    assert_eq!(actual.os_name(), None);
+    assert_eq!(actual.uptime(), None);
+}
+
+#[test]
+fn roundtrip_system_shutdown_abort() {
+    let decoded = now_msg_roundtrip(
+        NowSystemShutdownAbortMsg::new(7),
+        expect!["[04, 00, 00, 00, 11, 04, 00, 00, 07, 00, 00, 00]"],
+    );
+
+    let actual = match decoded {
+        NowMessage::System(NowSystemMessage::ShutdownAbort(msg)) => msg,
+        _ => panic!("Expected NowSystemShutdownAbortMsg"),
+    };
+
+    assert_eq!(actual.request_id(), 7);
+}
+
+#[test]
+fn roundtrip_system_power_action() {
+    let msg = NowSystemPowerActionMsg::new(0x1234, NowSystemPowerAction::REBOOT)
+        .with_timeout(core::time::Duration::from_secs(30))
+        .unwrap()
+        .with_message("bye")
+        .unwrap()
+        .with_force();
+
+    let decoded = now_msg_roundtrip(
+        msg,
+        expect!["[11, 00, 00, 00, 11, 05, 01, 00, 34, 12, 00, 00, 02, 00, 00, 00, 1E, 00, 00, 00, 03, 62, 79, 65, 00]"],
+    );
+
+    let actual = match decoded {
+        NowMessage::System(NowSystemMessage::PowerAction(msg)) => msg,
+        _ => panic!("Expected NowSystemPowerActionMsg"),
+    };
+
+    assert_eq!(actual.request_id(), 0x1234);
+    assert_eq!(actual.action(), NowSystemPowerAction::REBOOT);
+    assert_eq!(actual.timeout(), core::time::Duration::from_secs(30));
+    assert_eq!(actual.message(), "bye");
+    assert!(actual.is_force());
+}
+
+#[test]
+fn system_power_action_timeout_too_big() {
+    let result = NowSystemPowerActionMsg::new(0, NowSystemPowerAction::SHUTDOWN)
+        .with_timeout(core::time::Duration::from_secs(u64::from(u32::MAX)));
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn roundtrip_system_power_rsp() {
+    let decoded = now_msg_roundtrip(
+        NowSystemPowerRspMsg::new_success(0x1234),
+        expect!["[0E, 00, 00, 00, 11, 06, 00, 00, 34, 12, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00, 00]"],
+    );
+
+    let actual = match decoded {
+        NowMessage::System(NowSystemMessage::PowerRsp(msg)) => msg,
+        _ => panic!("Expected NowSystemPowerRspMsg"),
+    };
+
+    assert_eq!(actual.request_id(), 0x1234);
+    actual.to_result().unwrap();
+}
+
+#[test]
+fn roundtrip_system_power_rsp_error() {
+    let msg = NowSystemPowerRspMsg::new_error(0x1234, NowProtoError::AccessDenied).unwrap();
+
+    let decoded = now_msg_roundtrip(
+        msg,
+        expect!["[0E, 00, 00, 00, 11, 06, 00, 00, 34, 12, 00, 00, 01, 00, 01, 00, 05, 00, 00, 00, 00, 00]"],
+    );
+
+    let actual = match decoded {
+        NowMessage::System(NowSystemMessage::PowerRsp(msg)) => msg,
+        _ => panic!("Expected NowSystemPowerRspMsg"),
+    };
+
+    assert!(actual.to_result().is_err());
+}
+
+#[test]
+fn roundtrip_system_process_list_req() {
+    let decoded = now_msg_roundtrip(
+        NowSystemProcessListReqMsg::new(3),
+        expect!["[04, 00, 00, 00, 11, 07, 00, 00, 03, 00, 00, 00]"],
+    );
+
+    let actual = match decoded {
+        NowMessage::System(NowSystemMessage::ProcessListReq(msg)) => msg,
+        _ => panic!("Expected NowSystemProcessListReqMsg"),
+    };
+
+    assert_eq!(actual.request_id(), 3);
+}
+
+#[test]
+fn roundtrip_system_process_info() {
+    let msg = NowSystemProcessInfoMsg::new(3, 1234, "sshd")
+        .unwrap()
+        .with_parent_pid(1)
+        .with_session_id(2)
+        .with_start_time(0x6000_0000)
+        .with_exe_path("/usr/sbin/sshd")
+        .unwrap()
+        .with_user("root")
+        .unwrap();
+
+    let decoded = now_msg_roundtrip(msg, expect!["[34, 00, 00, 00, 11, 08, 00, 00, 03, 00, 00, 00, D2, 04, 00, 00, 01, 00, 00, 00, 02, 00, 00, 00, 00, 00, 00, 60, 00, 00, 00, 00, 04, 73, 73, 68, 64, 00, 0E, 2F, 75, 73, 72, 2F, 73, 62, 69, 6E, 2F, 73, 73, 68, 64, 00, 04, 72, 6F, 6F, 74, 00]"]);
+
+    let actual = match decoded {
+        NowMessage::System(NowSystemMessage::ProcessInfo(msg)) => msg,
+        _ => panic!("Expected NowSystemProcessInfoMsg"),
+    };
+
+    assert_eq!(actual.request_id(), 3);
+    assert_eq!(actual.pid(), 1234);
+    assert_eq!(actual.parent_pid(), 1);
+    assert_eq!(actual.session_id(), 2);
+    assert_eq!(actual.start_time(), 0x6000_0000);
+    assert_eq!(actual.process_name(), "sshd");
+    assert_eq!(actual.exe_path(), "/usr/sbin/sshd");
+    assert_eq!(actual.user(), "root");
+}
+
+#[test]
+fn roundtrip_system_process_list_rsp_error() {
+    let msg = NowSystemProcessListRspMsg::new_error(3, NowProtoError::AccessDenied).unwrap();
+
+    let decoded = now_msg_roundtrip(
+        msg,
+        expect!["[0E, 00, 00, 00, 11, 09, 00, 00, 03, 00, 00, 00, 01, 00, 01, 00, 05, 00, 00, 00, 00, 00]"],
+    );
+
+    let actual = match decoded {
+        NowMessage::System(NowSystemMessage::ProcessListRsp(msg)) => msg,
+        _ => panic!("Expected NowSystemProcessListRspMsg"),
+    };
+
+    assert_eq!(actual.request_id(), 3);
+    assert!(actual.to_result().is_err());
+}
+
+#[test]
+fn roundtrip_system_process_terminate() {
+    let msg = NowSystemProcessTerminateMsg::new(4, 1234).with_force();
+
+    let decoded = now_msg_roundtrip(
+        msg,
+        expect!["[08, 00, 00, 00, 11, 0A, 01, 00, 04, 00, 00, 00, D2, 04, 00, 00]"],
+    );
+
+    let actual = match decoded {
+        NowMessage::System(NowSystemMessage::ProcessTerminate(msg)) => msg,
+        _ => panic!("Expected NowSystemProcessTerminateMsg"),
+    };
+
+    assert_eq!(actual.request_id(), 4);
+    assert_eq!(actual.pid(), 1234);
+    assert!(actual.is_force());
+}
+
+#[test]
+fn roundtrip_system_process_terminate_rsp() {
+    let msg = NowSystemProcessTerminateRspMsg::new_error(4, NowProtoError::NotFound).unwrap();
+
+    let decoded = now_msg_roundtrip(
+        msg,
+        expect!["[0E, 00, 00, 00, 11, 0B, 00, 00, 04, 00, 00, 00, 01, 00, 01, 00, 04, 00, 00, 00, 00, 00]"],
+    );
+
+    let actual = match decoded {
+        NowMessage::System(NowSystemMessage::ProcessTerminateRsp(msg)) => msg,
+        _ => panic!("Expected NowSystemProcessTerminateRspMsg"),
+    };
+
+    assert_eq!(actual.request_id(), 4);
+    assert_eq!(
+        actual.to_result().unwrap_err().kind(),
+        NowStatusErrorKind::Now(NowProtoError::NotFound)
+    );
+}
@@ -0,0 +1,59 @@
+use now_proto_pdu::ironrdp_core::{decode, encode_vec, IntoOwned as _, ReadCursor};
+use now_proto_pdu::*;
+
+fn decode_unknown(frame: &[u8]) -> NowMessage<'_> {
+    let options = NowDecodeOptions::new().with_unknown_messages();
+    NowMessage::decode_with_options(&mut ReadCursor::new(frame), options).unwrap()
+}
+
+#[test]
+fn unknown_class_roundtrip() {
+    const ENCODED: &[u8] = &[0x03, 0x00, 0x00, 0x00, 0x7F, 0x01, 0x34, 0x12, 0xAA, 0xBB, 0xCC];
+
+    assert!(decode::<NowMessage<'_>>(ENCODED).is_err());
+
+    let msg = decode_unknown(ENCODED);
+
+    let NowMessage::Unknown { header, body } = &msg else {
+        panic!("Expected unknown message");
+    };
+    assert_eq!(header.class, NowMessageClass(0x7F));
+    assert_eq!(header.kind, 0x01);
+    assert_eq!(header.flags, 0x1234);
+    assert_eq!(body.as_ref(), &[0xAA, 0xBB, 0xCC]);
+
+    assert_eq!(encode_vec(&msg).unwrap(), ENCODED);
+    assert_eq!(encode_vec(&msg.into_owned()).unwrap(), ENCODED);
+}
+
+#[test]
+fn unknown_kind_in_known_class_roundtrip() {
+    // System message kind which is not defined by the protocol.
+    const ENCODED: &[u8] = &[0x00, 0x00, 0x00, 0x00, 0x11, 0x7F, 0x00, 0x00];
+
+    assert!(decode::<NowMessage<'_>>(ENCODED).is_err());
+
+    let msg = decode_unknown(ENCODED);
+
+    assert!(
+        matches!(&msg, NowMessage::Unknown { header, body } if header.class == NowMessageClass::SYSTEM && body.is_empty())
+    );
+    assert_eq!(encode_vec(&msg).unwrap(), ENCODED);
+}
+
+#[test]
+fn unknown_messages_option_keeps_known_messages() {
+    let msg = NowMessage::from(NowExecStartedMsg::new(42));
+    let encoded = encode_vec(&msg).unwrap();
+
+    assert_eq!(decode_unknown(&encoded), msg);
+}
+
+#[test]
+fn unknown_messages_option_keeps_decoding_errors() {
+    // Known message with truncated body is still an error.
+    const ENCODED: &[u8] = &[0x02, 0x00, 0x00, 0x00, 0x13, 0x06, 0x00, 0x00, 0x01, 0x02];
+
+    let options = NowDecodeOptions::new().with_unknown_messages();
+    assert!(NowMessage::decode_with_options(&mut ReadCursor::new(ENCODED), options).is_err());
+}
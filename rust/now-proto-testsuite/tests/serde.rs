@@ -0,0 +1,217 @@
+use core::time;
+
+use expect_test::{expect, Expect};
+use now_proto_pdu::ironrdp_core::encode_vec;
+use now_proto_pdu::*;
+use rstest::rstest;
+
+const SESSION_ID: Uuid = Uuid::from_u128(0x00112233_4455_6677_8899_aabbccddeeff);
+
+fn json_roundtrip(msg: impl Into<NowMessage<'static>>) -> String {
+    let msg = msg.into();
+
+    let json = serde_json::to_string(&msg).unwrap();
+    let deserialized: OwnedNowMessage = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(deserialized, msg);
+    assert_eq!(encode_vec(&deserialized).unwrap(), encode_vec(&msg).unwrap());
+
+    json
+}
+
+fn rdm(msg: NowRdmMessage<'static>) -> NowMessage<'static> {
+    NowMessage::Rdm(msg)
+}
+
+fn assert_json(msg: impl Into<NowMessage<'static>>, expected: Expect) {
+    expected.assert_eq(&json_roundtrip(msg));
+}
+
+#[rstest]
+#[case::channel_heartbeat(NowChannelHeartbeatMsg::default().into())]
+#[case::channel_close(NowChannelCloseMsg::default().into())]
+#[case::system_shutdown(
+    NowSystemShutdownMsg::new(time::Duration::from_secs(30), "bye").unwrap().with_reboot().into()
+)]
+#[case::session_lock(NowSessionLockMsg::default().into())]
+#[case::session_logoff(NowSessionLogoffMsg::default().into())]
+#[case::session_msg_box_req(
+    NowSessionMsgBoxReqMsg::new(1, "hello")
+        .unwrap()
+        .with_title("title")
+        .unwrap()
+        .with_style(NowMessageBoxStyle::YES_NO)
+        .with_response()
+        .into()
+)]
+#[case::session_msg_box_rsp(NowSessionMsgBoxRspMsg::new_success(1, NowMsgBoxResponse::YES).into())]
+#[case::session_set_kbd_layout(NowSessionSetKbdLayoutMsg::new_specific("00000409").unwrap().into())]
+#[case::session_window_rec_start(NowSessionWindowRecStartMsg::new(1000, WindowRecStartFlags::TRACK_TITLE_CHANGE).into())]
+#[case::session_window_rec_stop(NowSessionWindowRecStopMsg::default().into())]
+#[case::session_window_rec_event(
+    NowSessionWindowRecEventMsg::active_window(1_700_000_000, 42, "Notepad", "C:\\notepad.exe").unwrap().into()
+)]
+#[case::session_window_rec_event_no_window(NowSessionWindowRecEventMsg::no_active_window(1_700_000_000).into())]
+#[case::exec_abort(NowExecAbortMsg::new(1, 2).into())]
+#[case::exec_cancel_req(NowExecCancelReqMsg::new(1).into())]
+#[case::exec_cancel_rsp(NowExecCancelRspMsg::new_error(1, NowProtoError::NotFound).unwrap().into())]
+#[case::exec_result(
+    NowExecResultMsg::new_error(1, NowStatusError::new_unix(2).with_message("oops").unwrap()).unwrap().into()
+)]
+#[case::exec_started(NowExecStartedMsg::new(1).into())]
+#[case::exec_run(NowExecRunMsg::new(1, "notepad.exe").unwrap().with_directory("C:\\").unwrap().into())]
+#[case::exec_process(
+    NowExecProcessMsg::new(1, "cmd.exe")
+        .unwrap()
+        .with_parameters("/c dir")
+        .unwrap()
+        .with_io_redirection()
+        .into()
+)]
+#[case::exec_shell(NowExecShellMsg::new(1, "ls").unwrap().with_shell("/bin/bash").unwrap().into())]
+#[case::exec_batch(NowExecBatchMsg::new(1, "dir").unwrap().with_unicode_console().into())]
+#[case::exec_winps(
+    NowExecWinPsMsg::new(1, "Get-Date")
+        .unwrap()
+        .with_apartment_state(ComApartmentStateKind::Mta)
+        .into()
+)]
+#[case::exec_pwsh(NowExecPwshMsg::new_server_mode(1).unwrap().with_detached().into())]
+#[case::system_power_action(
+    NowSystemPowerActionMsg::new(1, NowSystemPowerAction::REBOOT)
+        .with_timeout(time::Duration::from_secs(60))
+        .unwrap()
+        .into()
+)]
+#[case::file_open(NowFileOpenMsg::new(1, NowFileOpenMode::Write, "a.txt").unwrap().into())]
+#[case::file_close(NowFileCloseMsg::new(1).with_sha256([0xAB; 32]).into())]
+#[case::rdm_capabilities(rdm(NowRdmMessage::Capabilities(NowRdmCapabilitiesMsg::new(1_700_000_000, "2025.1.0.0").unwrap().with_app_available())))]
+#[case::rdm_app_start(rdm(NowRdmMessage::AppStart(NowRdmAppStartMsg::default().with_maximized())))]
+#[case::rdm_app_action(rdm(NowRdmMessage::AppAction(NowRdmAppActionMsg::new_minimize())))]
+#[case::rdm_app_notify(rdm(NowRdmMessage::AppNotify(NowRdmAppNotifyMsg::new(
+    NowRdmAppState::READY,
+    NowRdmReason::NOT_SPECIFIED
+))))]
+#[case::rdm_session_start(rdm(NowRdmMessage::SessionStart(NowRdmSessionStartMsg::new(SESSION_ID, Uuid::nil(), "<xml/>").unwrap())))]
+#[case::rdm_session_action(rdm(NowRdmMessage::SessionAction(NowRdmSessionActionMsg::new_focus(SESSION_ID))))]
+#[case::rdm_session_notify(
+    rdm(NowRdmMessage::SessionNotify(NowRdmSessionNotifyMsg::new_close(SESSION_ID).with_log_data("log").unwrap()))
+)]
+fn serde_json_roundtrip(#[case] msg: NowMessage<'static>) {
+    json_roundtrip(msg);
+}
+
+#[test]
+fn serde_json_capset() {
+    let msg = NowChannelCapsetMsg::default()
+        .with_exec_capset(NowExecCapsetFlags::STYLE_RUN | NowExecCapsetFlags::UNICODE_CONSOLE)
+        .with_session_capset(NowSessionCapsetFlags::LOCK)
+        .with_heartbeat_interval(time::Duration::from_secs(60))
+        .unwrap();
+
+    assert_json(
+        msg,
+        expect![[
+            r#"{"channel":{"capset":{"version":{"major":1,"minor":7},"system_capset":"","session_capset":"LOCK","exec_capset":"STYLE_RUN | UNICODE_CONSOLE","heartbeat_interval":60}}}"#
+        ]],
+    );
+}
+
+#[test]
+fn serde_json_exec_data() {
+    let msg = NowExecDataMsg::new(7, NowExecDataStreamKind::Stdout, true, b"hi\n".as_slice()).unwrap();
+
+    assert_json(
+        msg,
+        expect![[r#"{"exec":{"data":{"flags":"LAST | STDOUT","session_id":7,"data":"68690a"}}}"#]],
+    );
+}
+
+#[test]
+fn serde_json_exec_result_status() {
+    let msg = NowExecResultMsg::new_error(3, NowProtoError::AccessDenied).unwrap();
+
+    assert_json(
+        msg,
+        expect![[
+            r#"{"exec":{"result":{"session_id":3,"exit_code":0,"status":{"error":{"kind":{"now":"access_denied"},"message":""}}}}}"#
+        ]],
+    );
+}
+
+#[test]
+fn serde_json_exec_result_success() {
+    assert_json(
+        NowExecResultMsg::new_success(3, 0),
+        expect![[r#"{"exec":{"result":{"session_id":3,"exit_code":0,"status":"success"}}}"#]],
+    );
+}
+
+#[test]
+fn serde_json_rdm_session_guid() {
+    assert_json(
+        rdm(NowRdmMessage::SessionAction(NowRdmSessionActionMsg::new_close(
+            SESSION_ID,
+        ))),
+        expect![[
+            r#"{"rdm":{"session_action":{"session_action":1,"session_id":"00112233-4455-6677-8899-aabbccddeeff"}}}"#
+        ]],
+    );
+}
+
+#[test]
+fn serde_json_status_error() {
+    let error = NowStatusError::new_proto(NowProtoError::NotFound)
+        .with_message("missing")
+        .unwrap();
+
+    let json = serde_json::to_string(&error).unwrap();
+    expect![[r#"{"kind":{"now":"not_found"},"message":"missing"}"#]].assert_eq(&json);
+
+    let deserialized: NowStatusError = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized, error);
+}
+
+#[test]
+fn serde_json_unknown_flags_are_preserved() {
+    let json = r#"{"exec":{"data":{"flags":"LAST | STDERR | 0x100","session_id":1,"data":""}}}"#;
+
+    let msg: OwnedNowMessage = serde_json::from_str(json).unwrap();
+
+    assert_eq!(serde_json::to_string(&msg).unwrap(), json);
+}
+
+#[rstest]
+#[case::odd_hex_digits(r#"{"exec":{"data":{"flags":"LAST | STDOUT","session_id":1,"data":"abc"}}}"#)]
+#[case::invalid_hex_digit(r#"{"exec":{"data":{"flags":"LAST | STDOUT","session_id":1,"data":"zz"}}}"#)]
+#[case::unknown_flag_name(r#"{"exec":{"data":{"flags":"LAST | STDWHAT","session_id":1,"data":""}}}"#)]
+#[case::invalid_guid(r#"{"rdm":{"session_action":{"session_action":1,"session_id":"not-a-guid"}}}"#)]
+#[case::exec_data_multiple_streams(r#"{"exec":{"data":{"flags":"STDOUT | STDERR","session_id":1,"data":""}}}"#)]
+#[case::exec_data_no_stream(r#"{"exec":{"data":{"flags":"LAST","session_id":1,"data":""}}}"#)]
+#[case::exec_winps_multiple_apartment_states(
+    r#"{"exec":{"win_ps":{"flags":"STA | MTA","session_id":1,"command":"","directory":"","execution_policy":"","configuration_name":""}}}"#
+)]
+#[case::exec_pwsh_multiple_apartment_states(
+    r#"{"exec":{"pwsh":{"flags":"STA | MTA","session_id":1,"command":"","directory":"","execution_policy":"","configuration_name":""}}}"#
+)]
+#[case::exec_result_raw_status(
+    r#"{"exec":{"result":{"session_id":3,"exit_code":0,"status":{"flags":"ERROR","kind":1,"code":5,"message":""}}}}"#
+)]
+#[case::file_open_no_mode(r#"{"file":{"open":{"flags":"","transfer_id":1,"offset":0,"path":"a.txt"}}}"#)]
+#[case::file_close_invalid_digest(r#"{"file":{"close":{"transfer_id":1,"sha256":"0011"}}}"#)]
+#[case::system_power_action_timeout(
+    r#"{"system":{"power_action":{"flags":"","request_id":1,"action":1,"timeout":4294967295,"message":""}}}"#
+)]
+#[case::system_shutdown_timeout(r#"{"system":{"shutdown":{"flags":"","timeout":4294967295,"message":""}}}"#)]
+#[case::session_msg_box_timeout(
+    r#"{"session":{"msg_box_req":{"flags":"TIMEOUT","request_id":1,"style":0,"timeout":4294967295,"title":"","message":""}}}"#
+)]
+#[case::session_set_kbd_layout_next_and_prev(
+    r#"{"session":{"set_kbd_layout":{"flags":"NEXT_LAYOUT | PREV_LAYOUT","layout":""}}}"#
+)]
+#[case::channel_capset_heartbeat(
+    r#"{"channel":{"capset":{"version":{"major":1,"minor":7},"system_capset":"","session_capset":"","exec_capset":"","heartbeat_interval":1}}}"#
+)]
+fn serde_json_invalid(#[case] json: &str) {
+    assert!(serde_json::from_str::<OwnedNowMessage>(json).is_err());
+}
@@ -0,0 +1,774 @@
+use std::sync::{Arc, Mutex};
+
+use futures_util::{SinkExt as _, StreamExt as _};
+use now_proto_channel::NowChannelViolation;
+use now_proto_client::{NowClient, NowClientError, NowExecEvent, NowFileDirPage};
+use now_proto_pdu::*;
+use now_proto_server::*;
+use now_proto_testsuite::proto::extension::{DiagnosticsPingMsg, DIAGNOSTICS_REGISTRY};
+use tokio::sync::Notify;
+use tokio_util::codec::Framed;
+
+/// Content of the "data" file served by [`TestHandler`].
+const FILE_CONTENT: &[u8] = b"hello world";
+
+#[derive(Default)]
+struct TestHandler {
+    locked: Arc<Notify>,
+    /// Content of the "upload" file written by the client.
+    uploaded: Arc<Mutex<Vec<u8>>>,
+    /// Directories created by the client.
+    created: Arc<Mutex<Vec<String>>>,
+}
+
+impl NowServerHandler for TestHandler {
+    async fn session_lock(&self) -> NowHandlerResult<()> {
+        self.locked.notify_one();
+        Ok(())
+    }
+
+    async fn system_power_action(&self, request: OwnedNowSystemPowerActionMsg) -> NowHandlerResult<()> {
+        match request.action() {
+            NowSystemPowerAction::SLEEP => Ok(()),
+            _ => Err(NowStatusError::new_proto(NowProtoError::AccessDenied)),
+        }
+    }
+
+    async fn system_process_list(
+        &self,
+        request: NowSystemProcessListReqMsg,
+    ) -> NowHandlerResult<Vec<OwnedNowSystemProcessInfoMsg>> {
+        let processes = [(1, "init"), (42, "sshd")]
+            .into_iter()
+            .map(|(pid, name)| NowSystemProcessInfoMsg::new(request.request_id(), pid, name).unwrap())
+            .collect();
+
+        Ok(processes)
+    }
+
+    async fn system_process_terminate(&self, request: NowSystemProcessTerminateMsg) -> NowHandlerResult<()> {
+        match request.pid() {
+            1 => Err(NowStatusError::new_proto(NowProtoError::AccessDenied)),
+            42 => Ok(()),
+            _ => Err(NowStatusError::new_proto(NowProtoError::NotFound)),
+        }
+    }
+
+    async fn session_msg_box(&self, request: OwnedNowSessionMsgBoxReqMsg) -> NowHandlerResult<NowMsgBoxResponse> {
+        match request.message() {
+            "denied" => Err(NowStatusError::new_proto(NowProtoError::AccessDenied)),
+            _ => Ok(NowMsgBoxResponse::OK),
+        }
+    }
+
+    async fn extension_message(&self, message: NowExtensionMsg, sender: NowMessageSender) -> NowHandlerResult<()> {
+        let ping = message
+            .downcast_ref::<DiagnosticsPingMsg>()
+            .ok_or_else(|| NowStatusError::new_proto(NowProtoError::NotImplemented))?;
+
+        sender.send(NowExtensionMsg::new(ping.clone()).into()).await.unwrap();
+        Ok(())
+    }
+
+    async fn exec_shell(&self, request: OwnedNowExecShellMsg, mut session: NowExecContext) -> NowHandlerResult<u32> {
+        if request.command() != "cat" {
+            return Ok(42);
+        }
+
+        // Echo stdin to stdout until stdin is closed.
+        while let Some(event) = session.next_event().await {
+            match event {
+                NowExecSessionEvent::Stdin { data, last } => {
+                    session.send_stdout(&data, last).await.unwrap();
+                    if last {
+                        return Ok(0);
+                    }
+                }
+                NowExecSessionEvent::Cancel | NowExecSessionEvent::Abort { .. } => break,
+            }
+        }
+
+        Err(NowStatusError::new_proto(NowProtoError::Aborted))
+    }
+
+    async fn file_read(
+        &self,
+        request: OwnedNowFileOpenMsg,
+        mut transfer: NowFileTransferContext,
+    ) -> NowHandlerResult<()> {
+        if request.path() != "data" {
+            return Err(NowStatusError::new_proto(NowProtoError::NotFound));
+        }
+
+        transfer.accept(FILE_CONTENT.len() as u64).await.unwrap();
+
+        let offset = usize::try_from(request.offset()).unwrap();
+        let chunks: Vec<&[u8]> = FILE_CONTENT[offset..].chunks(4).collect();
+
+        for (index, chunk) in chunks.iter().enumerate() {
+            transfer.send_chunk(chunk, index + 1 == chunks.len()).await.unwrap();
+        }
+
+        Ok(())
+    }
+
+    async fn file_write(
+        &self,
+        request: OwnedNowFileOpenMsg,
+        mut transfer: NowFileTransferContext,
+    ) -> NowHandlerResult<()> {
+        if request.path() == "readonly" {
+            return Err(NowStatusError::new_proto(NowProtoError::AccessDenied));
+        }
+
+        let offset = {
+            let mut uploaded = self.uploaded.lock().unwrap();
+
+            if !request.is_resume() {
+                uploaded.truncate(usize::try_from(request.offset()).unwrap());
+            }
+
+            uploaded.len()
+        };
+
+        transfer.accept(offset as u64).await.unwrap();
+
+        while let Some(data) = transfer.next_chunk().await {
+            // Simulates write failure (e.g. disk full) after the transfer has been accepted.
+            if request.path() == "broken" {
+                return Err(NowStatusError::new_proto(NowProtoError::Internal));
+            }
+
+            self.uploaded.lock().unwrap().extend(data);
+        }
+
+        Ok(())
+    }
+
+    async fn file_stat(&self, request: OwnedNowFileStatReqMsg) -> NowHandlerResult<OwnedNowFileStatRspMsg> {
+        match request.path() {
+            "data" => Ok(
+                NowFileStatRspMsg::new_success(request.request_id(), NowFileEntryKind::FILE)
+                    .with_file_size(FILE_CONTENT.len() as u64)
+                    .with_attributes(NowFileAttributes::READONLY),
+            ),
+            _ => Err(NowStatusError::new_proto(NowProtoError::NotFound)),
+        }
+    }
+
+    async fn file_list_dir(&self, request: OwnedNowFileListDirReqMsg) -> NowHandlerResult<NowFileDirListing> {
+        const NAMES: [&str; 5] = ["a", "b", "c", "d", "e"];
+
+        let entries = |names: &[&'static str]| -> Vec<OwnedNowFileDirEntryMsg> {
+            names
+                .iter()
+                .map(|name| NowFileDirEntryMsg::new(request.request_id(), NowFileEntryKind::FILE, *name).unwrap())
+                .collect()
+        };
+
+        match request.path() {
+            "/" => {}
+            // Misbehaving handler ignoring the requested page.
+            "/unpaged" => return Ok(NowFileDirListing::new(entries(&NAMES))),
+            _ => return Err(NowStatusError::new_proto(NowProtoError::NotFound)),
+        }
+
+        let start_index = usize::try_from(request.start_index()).unwrap();
+        let max_entries = request
+            .max_entries()
+            .map_or(NAMES.len(), |max| usize::try_from(max).unwrap());
+
+        let page = NAMES.get(start_index..).unwrap_or_default();
+        let listing = NowFileDirListing::new(entries(&page[..max_entries.min(page.len())]));
+
+        Ok(if start_index.saturating_add(max_entries) < NAMES.len() {
+            listing.with_more()
+        } else {
+            listing
+        })
+    }
+
+    async fn file_mkdir(&self, request: OwnedNowFileMkdirMsg) -> NowHandlerResult<()> {
+        self.created.lock().unwrap().push(request.path().to_owned());
+        Ok(())
+    }
+
+    async fn file_delete(&self, request: OwnedNowFileDeleteMsg) -> NowHandlerResult<()> {
+        match request.path() {
+            "dir" if !request.is_recursive() => Err(NowStatusError::new_proto(NowProtoError::InvalidRequest)),
+            _ => Ok(()),
+        }
+    }
+
+    async fn file_rename(&self, request: OwnedNowFileRenameMsg) -> NowHandlerResult<()> {
+        match request.new_path() {
+            "data" if !request.is_replace() => Err(NowStatusError::new_proto(NowProtoError::InUse)),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn server_capabilities() -> NowChannelCapsetMsg {
+    NowChannelCapsetMsg::default()
+        .with_system_capset(
+            NowSystemCapsetFlags::INFO
+                | NowSystemCapsetFlags::SHUTDOWN_ABORT
+                | NowSystemCapsetFlags::POWER_ACTION
+                | NowSystemCapsetFlags::PROCESS,
+        )
+        .with_session_capset(NowSessionCapsetFlags::LOCK | NowSessionCapsetFlags::MSGBOX)
+        .with_exec_capset(
+            NowExecCapsetFlags::STYLE_SHELL | NowExecCapsetFlags::STYLE_BATCH | NowExecCapsetFlags::IO_REDIRECTION,
+        )
+        .with_file_capset(
+            NowFileCapsetFlags::READ
+                | NowFileCapsetFlags::WRITE
+                | NowFileCapsetFlags::BROWSE
+                | NowFileCapsetFlags::MANAGE,
+        )
+}
+
+async fn connect(handler: TestHandler) -> NowClient {
+    let (client_io, server_io) = tokio::io::duplex(1024);
+
+    let server = NowServer::new(server_capabilities(), handler);
+    tokio::spawn(async move { server.serve(server_io).await });
+
+    NowClient::connect(client_io).await.unwrap()
+}
+
+#[tokio::test]
+async fn server_negotiates_capabilities() {
+    let client = connect(TestHandler::default()).await;
+
+    let capabilities = client.capabilities();
+    assert_eq!(
+        capabilities.session_capset(),
+        NowSessionCapsetFlags::LOCK | NowSessionCapsetFlags::MSGBOX
+    );
+    assert_eq!(
+        capabilities.system_capset(),
+        NowSystemCapsetFlags::INFO
+            | NowSystemCapsetFlags::SHUTDOWN_ABORT
+            | NowSystemCapsetFlags::POWER_ACTION
+            | NowSystemCapsetFlags::PROCESS
+    );
+    assert_eq!(
+        capabilities.heartbeat_interval(),
+        Some(core::time::Duration::from_secs(60))
+    );
+}
+
+#[tokio::test]
+async fn server_system_info_not_implemented() {
+    let client = connect(TestHandler::default()).await;
+
+    let error = client.system_info().await.unwrap_err();
+    assert!(matches!(
+        error,
+        NowClientError::Status(status) if status.kind() == NowStatusErrorKind::Now(NowProtoError::NotImplemented)
+    ));
+}
+
+#[tokio::test]
+async fn server_power_action_response() {
+    let client = connect(TestHandler::default()).await;
+
+    client
+        .system_power_action(|id| Ok(NowSystemPowerActionMsg::new(id, NowSystemPowerAction::SLEEP)))
+        .await
+        .unwrap();
+
+    let error = client
+        .system_power_action(|id| Ok(NowSystemPowerActionMsg::new(id, NowSystemPowerAction::REBOOT)))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        NowClientError::Status(status) if status.kind() == NowStatusErrorKind::Now(NowProtoError::AccessDenied)
+    ));
+
+    let error = client.system_shutdown_abort().await.unwrap_err();
+    assert!(matches!(
+        error,
+        NowClientError::Status(status) if status.kind() == NowStatusErrorKind::Now(NowProtoError::NotImplemented)
+    ));
+}
+
+#[tokio::test]
+async fn server_process_list_and_terminate() {
+    let client = connect(TestHandler::default()).await;
+
+    let processes = client.system_process_list().await.unwrap();
+    let processes: Vec<_> = processes
+        .iter()
+        .map(|process| (process.pid(), process.process_name()))
+        .collect();
+    assert_eq!(processes, [(1, "init"), (42, "sshd")]);
+
+    client.system_process_terminate(42, true).await.unwrap();
+
+    let status = |error| match error {
+        NowClientError::Status(status) => status.kind(),
+        error => panic!("unexpected error: {error}"),
+    };
+
+    let error = client.system_process_terminate(1, false).await.unwrap_err();
+    assert_eq!(status(error), NowStatusErrorKind::Now(NowProtoError::AccessDenied));
+
+    let error = client.system_process_terminate(7, false).await.unwrap_err();
+    assert_eq!(status(error), NowStatusErrorKind::Now(NowProtoError::NotFound));
+}
+
+#[tokio::test]
+async fn server_dispatches_session_lock() {
+    let handler = TestHandler::default();
+    let locked = Arc::clone(&handler.locked);
+
+    let client = connect(handler).await;
+    client.session_lock().await.unwrap();
+
+    locked.notified().await;
+}
+
+#[tokio::test]
+async fn server_msg_box_response() {
+    let client = connect(TestHandler::default()).await;
+
+    let response = client
+        .msg_box(|id| NowSessionMsgBoxReqMsg::new(id, "hello"))
+        .await
+        .unwrap();
+    assert_eq!(response, NowMsgBoxResponse::OK);
+
+    let error = client
+        .msg_box(|id| NowSessionMsgBoxReqMsg::new(id, "denied"))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        NowClientError::Status(status) if status.kind() == NowStatusErrorKind::Now(NowProtoError::AccessDenied)
+    ));
+}
+
+#[tokio::test]
+async fn server_exec_result() {
+    let client = connect(TestHandler::default()).await;
+
+    let mut session = client
+        .exec_shell(|id| NowExecShellMsg::new(id, "exit 42"))
+        .await
+        .unwrap();
+
+    // Started message is sent automatically for successful sessions.
+    assert_eq!(session.next_event().await, Some(NowExecEvent::Started));
+    assert_eq!(session.wait().await.unwrap(), 42);
+}
+
+#[tokio::test]
+async fn server_exec_io_redirection() {
+    let client = connect(TestHandler::default()).await;
+
+    let mut session = client
+        .exec_shell(|id| Ok(NowExecShellMsg::new(id, "cat")?.with_io_redirection()))
+        .await
+        .unwrap();
+
+    session.send_stdin(b"hello", true).await.unwrap();
+
+    assert_eq!(session.next_event().await, Some(NowExecEvent::Started));
+    assert_eq!(
+        session.next_event().await,
+        Some(NowExecEvent::Stdout {
+            data: b"hello".to_vec(),
+            last: true
+        })
+    );
+    assert_eq!(session.wait().await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn server_exec_cancel() {
+    let client = connect(TestHandler::default()).await;
+
+    let session = client
+        .exec_shell(|id| Ok(NowExecShellMsg::new(id, "cat")?.with_io_redirection()))
+        .await
+        .unwrap();
+
+    session.cancel().await.unwrap();
+
+    let error = session.wait().await.unwrap_err();
+    assert!(matches!(
+        error,
+        NowClientError::Status(status) if status.kind() == NowStatusErrorKind::Now(NowProtoError::Aborted)
+    ));
+}
+
+#[tokio::test]
+async fn server_exec_not_implemented() {
+    let client = connect(TestHandler::default()).await;
+
+    let session = client.exec_batch(|id| NowExecBatchMsg::new(id, "dir")).await.unwrap();
+
+    let error = session.wait().await.unwrap_err();
+    assert!(matches!(
+        error,
+        NowClientError::Status(status) if status.kind() == NowStatusErrorKind::Now(NowProtoError::NotImplemented)
+    ));
+}
+
+#[tokio::test]
+async fn server_rejects_message_before_capset() {
+    let (client_io, server_io) = tokio::io::duplex(1024);
+
+    let server = NowServer::new(server_capabilities(), TestHandler::default());
+    let server_task = tokio::spawn(async move { server.serve(server_io).await });
+
+    let mut client = Framed::new(client_io, NowMessageCodec::new());
+    client.send(NowSessionLockMsg::default().into()).await.unwrap();
+
+    let close = match client.next().await.unwrap().unwrap() {
+        NowMessage::Channel(NowChannelMessage::Close(close)) => close,
+        other => panic!("unexpected message: {other:?}"),
+    };
+
+    let error = close.to_result().unwrap_err();
+    assert_eq!(error.kind(), NowStatusErrorKind::Now(NowProtoError::InvalidRequest));
+    assert!(matches!(
+        server_task.await.unwrap(),
+        Err(NowServerError::Protocol(NowChannelViolation::CapsetExpected { .. }))
+    ));
+}
+
+#[tokio::test]
+async fn server_rejects_request_not_negotiated() {
+    let (client_io, server_io) = tokio::io::duplex(1024);
+
+    let server = NowServer::new(server_capabilities(), TestHandler::default());
+    let server_task = tokio::spawn(async move { server.serve(server_io).await });
+
+    let mut client = Framed::new(client_io, NowMessageCodec::new());
+    client
+        .send(
+            NowChannelCapsetMsg::default()
+                .with_session_capset(NowSessionCapsetFlags::LOCK | NowSessionCapsetFlags::LOGOFF)
+                .into(),
+        )
+        .await
+        .unwrap();
+
+    let capabilities = match client.next().await.unwrap().unwrap() {
+        NowMessage::Channel(NowChannelMessage::Capset(capabilities)) => capabilities,
+        other => panic!("unexpected message: {other:?}"),
+    };
+    assert_eq!(capabilities.session_capset(), NowSessionCapsetFlags::LOCK);
+
+    client.send(NowSessionLogoffMsg::default().into()).await.unwrap();
+
+    let close = match client.next().await.unwrap().unwrap() {
+        NowMessage::Channel(NowChannelMessage::Close(close)) => close,
+        other => panic!("unexpected message: {other:?}"),
+    };
+
+    let error = close.to_result().unwrap_err();
+    assert_eq!(error.kind(), NowStatusErrorKind::Now(NowProtoError::InvalidRequest));
+    assert!(matches!(
+        server_task.await.unwrap(),
+        Err(NowServerError::Protocol(
+            NowChannelViolation::CapabilityNotNegotiated { .. }
+        ))
+    ));
+}
+
+#[tokio::test]
+async fn server_closes_channel_on_exceeded_decode_limits() {
+    let (client_io, server_io) = tokio::io::duplex(1024);
+
+    let server = NowServer::new(server_capabilities(), TestHandler::default())
+        .with_decode_limits(NowDecodeLimits::new().with_max_string_len(16));
+    let server_task = tokio::spawn(async move { server.serve(server_io).await });
+
+    let mut client = Framed::new(client_io, NowMessageCodec::new());
+    client.send(server_capabilities().into()).await.unwrap();
+    client.next().await.unwrap().unwrap();
+
+    let request = NowSessionMsgBoxReqMsg::new(1, "a".repeat(17)).unwrap();
+    client.send(request.into()).await.unwrap();
+
+    let close = match client.next().await.unwrap().unwrap() {
+        NowMessage::Channel(NowChannelMessage::Close(close)) => close,
+        other => panic!("unexpected message: {other:?}"),
+    };
+
+    let error = close.to_result().unwrap_err();
+    assert_eq!(error.kind(), NowStatusErrorKind::Now(NowProtoError::InvalidRequest));
+    assert!(matches!(
+        server_task.await.unwrap(),
+        Err(NowServerError::Decode(error)) if NowDecodeLimits::is_limit_exceeded(&error)
+    ));
+}
+
+#[tokio::test]
+async fn server_dispatches_extension_messages() {
+    let (client_io, server_io) = tokio::io::duplex(1024);
+
+    let server = NowServer::new(server_capabilities(), TestHandler::default()).with_extensions(&DIAGNOSTICS_REGISTRY);
+    tokio::spawn(async move { server.serve(server_io).await });
+
+    let codec =
+        NowMessageCodec::new().with_decode_options(NowDecodeOptions::new().with_extensions(&DIAGNOSTICS_REGISTRY));
+    let mut client = Framed::new(client_io, codec);
+    client.send(server_capabilities().into()).await.unwrap();
+    client.next().await.unwrap().unwrap();
+
+    let ping = NowExtensionMsg::new(DiagnosticsPingMsg::new(1, "ping").unwrap());
+    client.send(ping.clone().into()).await.unwrap();
+
+    let response = loop {
+        match client.next().await.unwrap().unwrap() {
+            NowMessage::Extension(response) => break response,
+            _ => continue,
+        }
+    };
+
+    assert_eq!(response, ping);
+}
+
+#[tokio::test]
+async fn server_exec_session_id_in_use() {
+    let (client_io, server_io) = tokio::io::duplex(1024);
+
+    let server = NowServer::new(server_capabilities(), TestHandler::default());
+    tokio::spawn(async move { server.serve(server_io).await });
+
+    let mut client = Framed::new(client_io, NowMessageCodec::new());
+    client.send(server_capabilities().into()).await.unwrap();
+    client.next().await.unwrap().unwrap();
+
+    // Handler waits for stdin, therefore the first session stays active.
+    let request = NowExecShellMsg::new(1, "cat").unwrap().with_io_redirection();
+    client.send(request.clone().into()).await.unwrap();
+    client.send(request.into()).await.unwrap();
+
+    let result = loop {
+        match client.next().await.unwrap().unwrap() {
+            NowMessage::Exec(NowExecMessage::Result(result)) => break result,
+            _ => continue,
+        }
+    };
+
+    assert_eq!(result.session_id(), 1);
+    assert_eq!(
+        result.to_result().unwrap_err().kind(),
+        NowStatusErrorKind::Now(NowProtoError::InUse)
+    );
+}
+
+#[tokio::test]
+async fn server_file_read() {
+    let client = connect(TestHandler::default()).await;
+
+    let mut transfer = client
+        .file_open(|id| Ok(NowFileOpenMsg::new(id, NowFileOpenMode::Read, "data")?.with_offset(2)))
+        .await
+        .unwrap();
+
+    assert_eq!(transfer.file_size(), FILE_CONTENT.len() as u64);
+    assert_eq!(transfer.offset(), 2);
+
+    let mut data = Vec::new();
+    while let Some(chunk) = transfer.read_chunk().await.unwrap() {
+        data.extend(chunk);
+    }
+
+    assert_eq!(data, b"llo world");
+    transfer.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn server_file_open_error() {
+    let client = connect(TestHandler::default()).await;
+
+    let error = client
+        .file_open(|id| NowFileOpenMsg::new(id, NowFileOpenMode::Read, "missing"))
+        .await
+        .unwrap_err();
+
+    assert!(matches!(
+        error,
+        NowClientError::Status(status) if status.kind() == NowStatusErrorKind::Now(NowProtoError::NotFound)
+    ));
+}
+
+#[tokio::test]
+async fn server_file_write_resume() {
+    let handler = TestHandler::default();
+    let uploaded = Arc::clone(&handler.uploaded);
+    let client = connect(handler).await;
+
+    let mut transfer = client
+        .file_open(|id| NowFileOpenMsg::new(id, NowFileOpenMode::Write, "upload"))
+        .await
+        .unwrap();
+    transfer.write_chunk(b"hello ").await.unwrap();
+    transfer.close().await.unwrap();
+
+    let mut transfer = client
+        .file_open(|id| Ok(NowFileOpenMsg::new(id, NowFileOpenMode::Write, "upload")?.with_resume()))
+        .await
+        .unwrap();
+    assert_eq!(transfer.offset(), 6);
+
+    transfer.write_chunk(b"wor").await.unwrap();
+    transfer.write_chunk(b"ld").await.unwrap();
+    transfer.close().await.unwrap();
+
+    assert_eq!(*uploaded.lock().unwrap(), b"hello world");
+}
+
+#[tokio::test]
+async fn server_file_write_error_after_accept() {
+    let client = connect(TestHandler::default()).await;
+
+    let mut transfer = client
+        .file_open(|id| NowFileOpenMsg::new(id, NowFileOpenMode::Write, "broken"))
+        .await
+        .unwrap();
+    transfer.write_chunk(b"data").await.unwrap();
+
+    // Server completes the transfer with the handler error without waiting for the client.
+    let error = transfer.close().await.unwrap_err();
+    assert!(matches!(
+        error,
+        NowClientError::Status(status) if status.kind() == NowStatusErrorKind::Now(NowProtoError::Internal)
+    ));
+}
+
+#[tokio::test]
+async fn server_file_digest_mismatch() {
+    let (client_io, server_io) = tokio::io::duplex(1024);
+
+    let server = NowServer::new(server_capabilities(), TestHandler::default());
+    tokio::spawn(async move { server.serve(server_io).await });
+
+    let mut client = Framed::new(client_io, NowMessageCodec::new());
+    client.send(server_capabilities().into()).await.unwrap();
+    client.next().await.unwrap().unwrap();
+
+    let open = NowFileOpenMsg::new(1, NowFileOpenMode::Write, "upload").unwrap();
+    client.send(open.into()).await.unwrap();
+
+    match client.next().await.unwrap().unwrap() {
+        NowMessage::File(NowFileMessage::OpenRsp(response)) => response.to_result().unwrap(),
+        other => panic!("unexpected message: {other:?}"),
+    }
+
+    let chunk = NowFileChunkMsg::new(1, 0, false, b"data".as_slice()).unwrap();
+    client.send(chunk.into()).await.unwrap();
+    client
+        .send(NowFileCloseMsg::new(1).with_sha256([0; NOW_FILE_SHA256_SIZE]).into())
+        .await
+        .unwrap();
+
+    let response = match client.next().await.unwrap().unwrap() {
+        NowMessage::File(NowFileMessage::CloseRsp(response)) => response,
+        other => panic!("unexpected message: {other:?}"),
+    };
+
+    assert_eq!(response.transfer_id(), 1);
+    assert_eq!(
+        response.to_result().unwrap_err().kind(),
+        NowStatusErrorKind::Now(NowProtoError::InvalidRequest)
+    );
+}
+
+#[tokio::test]
+async fn server_file_stat() {
+    let client = connect(TestHandler::default()).await;
+
+    let stat = client.file_stat("data").await.unwrap();
+    assert_eq!(stat.kind(), NowFileEntryKind::FILE);
+    assert_eq!(stat.file_size(), FILE_CONTENT.len() as u64);
+    assert_eq!(stat.attributes(), NowFileAttributes::READONLY);
+
+    let error = client.file_stat("missing").await.unwrap_err();
+    assert!(matches!(
+        error,
+        NowClientError::Status(status) if status.kind() == NowStatusErrorKind::Now(NowProtoError::NotFound)
+    ));
+}
+
+#[tokio::test]
+async fn server_file_list_dir_pages() {
+    let client = connect(TestHandler::default()).await;
+
+    let names = |page: &NowFileDirPage| {
+        page.entries()
+            .iter()
+            .map(|entry| entry.file_name().to_owned())
+            .collect::<Vec<_>>()
+    };
+
+    let page = client.file_list_dir("/", 0, 2).await.unwrap();
+    assert_eq!(names(&page), ["a", "b"]);
+    assert!(page.has_more());
+
+    let page = client.file_list_dir("/", 3, 2).await.unwrap();
+    assert_eq!(names(&page), ["d", "e"]);
+    assert!(!page.has_more());
+
+    let page = client.file_list_dir("/", 1, 0).await.unwrap();
+    assert_eq!(names(&page), ["b", "c", "d", "e"]);
+    assert!(!page.has_more());
+
+    let page = client.file_list_dir("/", 10, 2).await.unwrap();
+    assert!(page.entries().is_empty());
+    assert!(!page.has_more());
+
+    // Entries beyond the requested page are not sent.
+    let page = client.file_list_dir("/unpaged", 0, 2).await.unwrap();
+    assert_eq!(names(&page), ["a", "b"]);
+    assert!(page.has_more());
+
+    let error = client.file_list_dir("missing", 0, 0).await.unwrap_err();
+    assert!(matches!(
+        error,
+        NowClientError::Status(status) if status.kind() == NowStatusErrorKind::Now(NowProtoError::NotFound)
+    ));
+}
+
+#[tokio::test]
+async fn server_file_operations() {
+    let handler = TestHandler::default();
+    let created = Arc::clone(&handler.created);
+    let client = connect(handler).await;
+
+    client.file_mkdir("a/b", true).await.unwrap();
+    assert_eq!(*created.lock().unwrap(), ["a/b"]);
+
+    client.file_delete("dir", true).await.unwrap();
+    let error = client.file_delete("dir", false).await.unwrap_err();
+    assert!(matches!(
+        error,
+        NowClientError::Status(status) if status.kind() == NowStatusErrorKind::Now(NowProtoError::InvalidRequest)
+    ));
+
+    client.file_rename("upload", "data", true).await.unwrap();
+    let error = client.file_rename("upload", "data", false).await.unwrap_err();
+    assert!(matches!(
+        error,
+        NowClientError::Status(status) if status.kind() == NowStatusErrorKind::Now(NowProtoError::InUse)
+    ));
+}
+
+#[test]
+fn server_status_from_io_error() {
+    let status = now_status_from_io_error(&std::io::Error::from_raw_os_error(2));
+    assert_eq!(status.kind(), NowStatusErrorKind::Unix(2));
+    assert!(!status.message().is_empty());
+
+    let status = now_status_from_io_error(&std::io::Error::new(std::io::ErrorKind::NotFound, "gone"));
+    assert_eq!(status.kind(), NowStatusErrorKind::Now(NowProtoError::NotFound));
+    assert_eq!(status.message(), "gone");
+}